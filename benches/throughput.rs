@@ -0,0 +1,21 @@
+extern crate bdb;
+
+use bdb::bench::{throughput_suite, BenchmarkOptions};
+use std::env;
+use std::io;
+
+/// Number of records in each generated corpus.
+const COUNT: usize = 2000;
+
+fn main() {
+    // Use the first non-flag argument as a filter, like libtest.
+    let filter = env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let options = BenchmarkOptions {
+        filter: filter,
+        ..BenchmarkOptions::default()
+    };
+
+    let suite = throughput_suite(options, COUNT).unwrap();
+    let stdout = io::stdout();
+    suite.report(&mut stdout.lock()).unwrap();
+}
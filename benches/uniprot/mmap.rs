@@ -0,0 +1,54 @@
+#[macro_use]
+extern crate bencher;
+extern crate bdb;
+
+use bencher::{black_box, Bencher};
+use bdb::MmapFile;
+use bdb::db::uniprot::low_level::*;
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+
+// HELPERS
+
+/// Write a FASTA document with many records to a temporary file.
+fn fasta_path() -> PathBuf {
+    let text = b">sp|P46406|G3P_RABIT Glyceraldehyde-3-phosphate dehydrogenase OS=Oryctolagus cuniculus OX=9986 GN=GAPDH PE=1 SV=3\nMVKVGVNGFGRIGRLVTRAAFNSGKVDVVAINDPFIDLHYMVYMFQYDSTHGKFHGTVKA\nENGKLVINGKAITIFQERDPANIKWGDAGAEYVVESTGVFTTMEKAGAHLKGGAKRVIIS\nAPSADAPMFVMGVNHEKYDNSLKIVSNASCTTNCLAPLAKVIHDHFGIVEGLMTTVHAIT\nATQKTVDGPSGKLWRDGRGAAQNIIPASTGAAKAVGKVIPELNGKLTGMAFRVPTPNVSV\nVDLTCRLEKAAKYDDIKKVVKQASEGPLKGILGYTEDQVVSCDFNSATHSSTFDAGAGIA\nLNDHFVKLISWYDNEFGYSNRVVDLMVHMASKE\n";
+    let path = env::temp_dir().join("bdb_mmap_bench.fasta");
+    let mut document = Vec::with_capacity(text.len() * 1000);
+    for _ in 0..1000 {
+        document.extend_from_slice(text);
+    }
+    fs::write(&path, document).unwrap();
+    path
+}
+
+// BENCHES
+
+fn buffered_fasta(bench: &mut Bencher) {
+    let path = fasta_path();
+    bench.iter(|| {
+        let reader = BufReader::new(File::open(&path).unwrap());
+        for record in iterator_from_fasta(reader) {
+            black_box(record.unwrap());
+        }
+    })
+}
+
+fn mmap_fasta(bench: &mut Bencher) {
+    let path = fasta_path();
+    let file = unsafe { MmapFile::open(&path).unwrap() };
+    bench.iter(|| {
+        for record in iterator_from_fasta_mmap(file.as_bytes()) {
+            black_box(record.unwrap());
+        }
+    })
+}
+
+benchmark_group!(
+    benches,
+    buffered_fasta,
+    mmap_fasta
+);
+benchmark_main!(benches);
@@ -0,0 +1,164 @@
+//! Deterministic, generated corpora for the throughput benchmarks.
+//!
+//! The corpora are generated from a fixed seed, so every run of the
+//! benchmarks parses and writes identical documents, independent of
+//! the test data shipped with the repository.
+
+#[cfg(feature = "uniprot")]
+use bio::proteins::ProteinMassTable;
+#[cfg(feature = "uniprot")]
+use db::uniprot;
+#[cfg(feature = "mass_spectrometry")]
+use db::mass_spectra::{self, Peak};
+use util::Rng;
+
+/// Standard aminoacids, to generate protein sequences.
+#[cfg(feature = "uniprot")]
+const AMINOACIDS: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Seed for the corpus generators.
+const SEED: u64 = 0x5DEE_CE66_D;
+
+// UNIPROT
+
+/// Generate `count` valid UniProt records.
+///
+/// Count must be less than 100000, to generate unique accession numbers.
+#[cfg(feature = "uniprot")]
+pub fn protein_records(count: usize) -> Vec<uniprot::Record> {
+    let mut rng = Rng::new(SEED);
+    let table = ProteinMassTable::average();
+    (1..count+1).map(|i| {
+        let length = rng.range(50, 800);
+        let sequence = rng.sequence(AMINOACIDS, length);
+        let mut record = uniprot::Record::new();
+        record.sequence_version = rng.range(1, 4) as u8;
+        record.protein_evidence = uniprot::ProteinEvidence::ProteinLevel;
+        record.mass = table.total_sequence_mass(&sequence).round() as u64;
+        record.length = length as u32;
+        record.gene = format!("GENE{}", i);
+        record.id = format!("P{:05}", i);
+        record.mnemonic = format!("{:05X}_HUMAN", i);
+        record.name = format!("Generated protein {}", i);
+        record.organism = String::from("Homo sapiens");
+        record.proteome = String::from("UP000005640");
+        record.sequence = sequence;
+        record.taxonomy = String::from("9606");
+        record.reviewed = true;
+        record
+    }).collect()
+}
+
+/// Generate a FASTA document with `count` UniProt records.
+#[cfg(all(feature = "uniprot", feature = "fasta"))]
+pub fn fasta_corpus(count: usize) -> Vec<u8> {
+    let mut writer = vec![];
+    uniprot::low_level::reference_iterator_to_fasta(&mut writer, protein_records(count).iter())
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+/// Generate a tab-delimited CSV document with `count` UniProt records.
+#[cfg(all(feature = "uniprot", feature = "csv"))]
+pub fn csv_corpus(count: usize) -> Vec<u8> {
+    let mut writer = vec![];
+    uniprot::low_level::reference_iterator_to_csv(&mut writer, protein_records(count).iter(), b'\t')
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+/// Generate an XML document with `count` UniProt records.
+#[cfg(all(feature = "uniprot", feature = "xml"))]
+pub fn xml_corpus(count: usize) -> Vec<u8> {
+    let mut writer = vec![];
+    uniprot::low_level::reference_iterator_to_xml(&mut writer, protein_records(count).iter())
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+// MASS SPECTRA
+
+/// Generate `count` valid MS2 spectra.
+#[cfg(feature = "mass_spectrometry")]
+pub fn spectra_records(count: usize) -> Vec<mass_spectra::Record> {
+    let mut rng = Rng::new(SEED);
+    (1..count+1).map(|i| {
+        let peak_count = rng.range(20, 200);
+        let mut mz = 100.0;
+        let peaks = (0..peak_count).map(|_| {
+            mz += 0.01 + rng.float() * 10.0;
+            Peak {
+                mz: (mz * 1e4).round() / 1e4,
+                intensity: (rng.float() * 1e6).round(),
+                z: 0,
+                mobility: None,
+            }
+        }).collect();
+
+        let mut record = mass_spectra::Record::new();
+        record.num = i as u32;
+        record.ms_level = 2;
+        record.rt = i as f64 * 0.5;
+        record.parent_mz = (400.0 + rng.float() * 1200.0).round();
+        record.parent_intensity = (rng.float() * 1e7).round() + 1.0;
+        record.parent_z = rng.range(1, 5) as i8;
+        record.file = String::from("generated");
+        record.peaks = peaks;
+        record
+    }).collect()
+}
+
+/// Generate an MSConvert MGF document with `count` spectra.
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+pub fn mgf_corpus(count: usize) -> Vec<u8> {
+    use traits::MgfKind;
+
+    let mut writer = vec![];
+    let records = spectra_records(count);
+    mass_spectra::low_level::reference_iterator_to_mgf(&mut writer, records.iter(), MgfKind::MsConvert)
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traits::Valid;
+
+    #[cfg(feature = "uniprot")]
+    #[test]
+    fn protein_records_test() {
+        let v = protein_records(20);
+        assert_eq!(v.len(), 20);
+        assert!(v.iter().all(|r| r.is_valid()));
+        assert_eq!(v, protein_records(20));
+        assert_eq!(v[19].id, "P00020");
+    }
+
+    #[cfg(all(feature = "uniprot", feature = "fasta"))]
+    #[test]
+    fn fasta_corpus_test() {
+        use db::uniprot::low_level::iterator_from_fasta;
+
+        let corpus = fasta_corpus(20);
+        let v: Vec<_> = iterator_from_fasta(&corpus[..]).map(|r| r.unwrap()).collect();
+        let expected = protein_records(20);
+        assert_eq!(v.len(), 20);
+        for (x, y) in v.iter().zip(expected.iter()) {
+            assert_eq!(x.id, y.id);
+            assert_eq!(x.mnemonic, y.mnemonic);
+            assert_eq!(x.sequence, y.sequence);
+        }
+    }
+
+    #[cfg(feature = "mass_spectrometry")]
+    #[test]
+    fn spectra_records_test() {
+        let v = spectra_records(20);
+        assert_eq!(v.len(), 20);
+        assert!(v.iter().all(|r| r.is_valid()));
+    }
+}
@@ -0,0 +1,231 @@
+//! Minimal statistics-driven benchmark harness.
+//!
+//! Each benchmark is run for a number of warmup iterations, and then
+//! timed over a number of samples, reporting the mean and standard
+//! deviation of the sample times, and the throughput from the size
+//! of the processed document.
+
+use std::io::Write;
+use std::mem;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use util::Result;
+
+/// Prevent the optimizer from removing an unused value.
+#[inline(never)]
+pub fn black_box<T>(dummy: T) -> T {
+    unsafe {
+        let value = ptr::read_volatile(&dummy);
+        mem::forget(dummy);
+        value
+    }
+}
+
+/// Convert a duration to fractional seconds.
+#[inline]
+fn as_seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9
+}
+
+// OPTIONS
+
+/// Options to control the number of benchmark iterations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkOptions {
+    /// Number of untimed iterations before sampling.
+    pub warmup: usize,
+    /// Number of timed iterations.
+    pub samples: usize,
+    /// Only run benchmarks with names containing the filter, if set.
+    pub filter: Option<String>,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        BenchmarkOptions {
+            warmup: 3,
+            samples: 20,
+            filter: None,
+        }
+    }
+}
+
+// MEASUREMENT
+
+/// Timed samples for a single benchmark.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    /// Benchmark name, for example, `"fasta/parse"`.
+    pub name: String,
+    /// Number of bytes processed by each iteration.
+    pub bytes: usize,
+    /// Time taken by each timed iteration.
+    pub samples: Vec<Duration>,
+}
+
+impl Measurement {
+    /// Get the mean sample time, in seconds.
+    ///
+    /// Returns `None` if there are no samples.
+    pub fn mean(&self) -> Option<f64> {
+        match self.samples.len() {
+            0 => None,
+            n => Some(self.samples.iter().map(|&d| as_seconds(d)).sum::<f64>() / n as f64),
+        }
+    }
+
+    /// Get the sample standard deviation of the sample times, in seconds.
+    ///
+    /// Returns `None` if there are fewer than 2 samples.
+    pub fn std_dev(&self) -> Option<f64> {
+        let n = self.samples.len();
+        if n < 2 {
+            return None;
+        }
+        let mean = self.mean()?;
+        let sum: f64 = self.samples.iter()
+            .map(|&d| (as_seconds(d) - mean).powi(2))
+            .sum();
+        Some((sum / (n - 1) as f64).sqrt())
+    }
+
+    /// Get the mean throughput, in megabytes (10^6 bytes) per second.
+    ///
+    /// Returns `None` if there are no samples, or they took no time.
+    pub fn throughput(&self) -> Option<f64> {
+        match self.mean() {
+            Some(mean) if mean > 0.0 => Some(self.bytes as f64 / mean / 1e6),
+            _                        => None,
+        }
+    }
+}
+
+// SUITE
+
+/// Collection of benchmarks sharing the same options.
+#[derive(Clone, Debug, Default)]
+pub struct Suite {
+    options: BenchmarkOptions,
+    measurements: Vec<Measurement>,
+}
+
+impl Suite {
+    /// Create new suite from options.
+    #[inline]
+    pub fn new(options: BenchmarkOptions) -> Self {
+        Suite {
+            options: options,
+            measurements: vec![],
+        }
+    }
+
+    /// Get the suite options.
+    #[inline]
+    pub fn options(&self) -> &BenchmarkOptions {
+        &self.options
+    }
+
+    /// Get the measurements of the completed benchmarks.
+    #[inline]
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    /// Run a benchmark processing `bytes` bytes in each iteration.
+    ///
+    /// Benchmarks excluded by the filter are skipped. Any error from
+    /// the benchmarked function stops the benchmark and is returned.
+    pub fn bench<F>(&mut self, name: &str, bytes: usize, mut f: F) -> Result<()>
+        where F: FnMut() -> Result<()>
+    {
+        if let Some(ref filter) = self.options.filter {
+            if !name.contains(filter.as_str()) {
+                return Ok(());
+            }
+        }
+
+        for _ in 0..self.options.warmup {
+            f()?;
+        }
+
+        let mut samples = Vec::with_capacity(self.options.samples);
+        for _ in 0..self.options.samples {
+            let start = Instant::now();
+            f()?;
+            samples.push(start.elapsed());
+        }
+
+        self.measurements.push(Measurement {
+            name: String::from(name),
+            bytes: bytes,
+            samples: samples,
+        });
+        Ok(())
+    }
+
+    /// Write a report of the measurements, one benchmark per line.
+    pub fn report<T: Write>(&self, writer: &mut T) -> Result<()> {
+        for measurement in self.measurements.iter() {
+            let mean = measurement.mean().unwrap_or(0.0);
+            let std_dev = measurement.std_dev().unwrap_or(0.0);
+            let throughput = measurement.throughput().unwrap_or(0.0);
+            writeln!(
+                writer,
+                "{:<16} {:>12.3} ms/iter (+/- {:.3}) {:>10.2} MB/s",
+                measurement.name, mean * 1e3, std_dev * 1e3, throughput
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::ErrorKind;
+
+    #[test]
+    fn measurement_test() {
+        let m = Measurement {
+            name: String::from("test"),
+            bytes: 2_000_000,
+            samples: vec![Duration::from_millis(1000), Duration::from_millis(3000)],
+        };
+        assert_approx_eq!(m.mean().unwrap(), 2.0);
+        assert_approx_eq!(m.std_dev().unwrap(), 2f64.sqrt());
+        assert_approx_eq!(m.throughput().unwrap(), 1.0);
+
+        let empty = Measurement { name: String::new(), bytes: 1, samples: vec![] };
+        assert_eq!(empty.mean(), None);
+        assert_eq!(empty.std_dev(), None);
+        assert_eq!(empty.throughput(), None);
+    }
+
+    #[test]
+    fn suite_test() {
+        let options = BenchmarkOptions {
+            warmup: 1,
+            samples: 3,
+            filter: Some(String::from("parse")),
+        };
+        let mut suite = Suite::new(options);
+        let mut count = 0;
+        suite.bench("fasta/parse", 10, || { count += 1; Ok(()) }).unwrap();
+        suite.bench("fasta/write", 10, || panic!("filtered")).unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(suite.measurements().len(), 1);
+        assert_eq!(suite.measurements()[0].samples.len(), 3);
+
+        let result = suite.bench("csv/parse", 10, || Err(From::from(ErrorKind::InvalidInput)));
+        assert!(result.is_err());
+        assert_eq!(suite.measurements().len(), 1);
+
+        let mut report = vec![];
+        suite.report(&mut report).unwrap();
+        assert!(String::from_utf8(report).unwrap().starts_with("fasta/parse"));
+    }
+}
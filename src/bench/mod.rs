@@ -0,0 +1,14 @@
+//! Benchmark harness for parse and write throughput.
+//!
+//! The benchmarks use generated corpora, so performance regressions
+//! in the readers and writers are measurable without external data.
+//! Run the bundled suite with `cargo bench --features bench --bench throughput`,
+//! or build custom suites from the harness and corpora.
+
+pub(crate) mod corpus;
+pub(crate) mod harness;
+pub(crate) mod throughput;
+
+pub use self::corpus::*;
+pub use self::harness::{black_box, BenchmarkOptions, Measurement, Suite};
+pub use self::throughput::throughput_suite;
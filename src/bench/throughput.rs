@@ -0,0 +1,149 @@
+//! Parse and write throughput benchmarks for the hot paths.
+
+use util::Result;
+use super::harness::{black_box, BenchmarkOptions, Suite};
+
+#[allow(unused_imports)]
+use super::corpus::*;
+
+#[cfg(all(feature = "uniprot", any(feature = "fasta", feature = "csv", feature = "xml")))]
+use db::uniprot::low_level as uniprot;
+
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+use db::mass_spectra::low_level as mass_spectra;
+
+/// Benchmark UniProt FASTA parsing and writing.
+#[cfg(all(feature = "uniprot", feature = "fasta"))]
+fn fasta(suite: &mut Suite, count: usize) -> Result<()> {
+    let corpus = fasta_corpus(count);
+    suite.bench("fasta/parse", corpus.len(), || {
+        for record in uniprot::iterator_from_fasta(&corpus[..]) {
+            black_box(record?);
+        }
+        Ok(())
+    })?;
+
+    let records = protein_records(count);
+    suite.bench("fasta/write", corpus.len(), || {
+        let mut writer = Vec::with_capacity(corpus.len());
+        uniprot::reference_iterator_to_fasta(&mut writer, records.iter())?;
+        black_box(writer);
+        Ok(())
+    })
+}
+
+/// Benchmark UniProt CSV parsing and writing.
+#[cfg(all(feature = "uniprot", feature = "csv"))]
+fn csv(suite: &mut Suite, count: usize) -> Result<()> {
+    let corpus = csv_corpus(count);
+    suite.bench("csv/parse", corpus.len(), || {
+        for record in uniprot::iterator_from_csv(&corpus[..], b'\t') {
+            black_box(record?);
+        }
+        Ok(())
+    })?;
+
+    let records = protein_records(count);
+    suite.bench("csv/write", corpus.len(), || {
+        let mut writer = Vec::with_capacity(corpus.len());
+        uniprot::reference_iterator_to_csv(&mut writer, records.iter(), b'\t')?;
+        black_box(writer);
+        Ok(())
+    })
+}
+
+/// Benchmark UniProt XML parsing and writing.
+///
+/// XML is parsed with the strict reader, so the timings include
+/// record validation.
+#[cfg(all(feature = "uniprot", feature = "xml"))]
+fn xml(suite: &mut Suite, count: usize) -> Result<()> {
+    let corpus = xml_corpus(count);
+    suite.bench("xml/parse", corpus.len(), || {
+        for record in uniprot::iterator_from_xml_strict(&corpus[..]) {
+            black_box(record?);
+        }
+        Ok(())
+    })?;
+
+    let records = protein_records(count);
+    suite.bench("xml/write", corpus.len(), || {
+        let mut writer = Vec::with_capacity(corpus.len());
+        uniprot::reference_iterator_to_xml(&mut writer, records.iter())?;
+        black_box(writer);
+        Ok(())
+    })
+}
+
+/// Benchmark MSConvert MGF parsing and writing.
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+fn mgf(suite: &mut Suite, count: usize) -> Result<()> {
+    use traits::MgfKind;
+
+    let corpus = mgf_corpus(count);
+    suite.bench("mgf/parse", corpus.len(), || {
+        for record in mass_spectra::iterator_from_mgf(&corpus[..], MgfKind::MsConvert) {
+            black_box(record?);
+        }
+        Ok(())
+    })?;
+
+    let records = spectra_records(count);
+    suite.bench("mgf/write", corpus.len(), || {
+        let mut writer = Vec::with_capacity(corpus.len());
+        mass_spectra::reference_iterator_to_mgf(&mut writer, records.iter(), MgfKind::MsConvert)?;
+        black_box(writer);
+        Ok(())
+    })
+}
+
+/// Run the throughput benchmarks for all enabled formats.
+///
+/// Each corpus contains `count` generated records, and benchmarks
+/// for formats disabled at compile-time are omitted.
+#[allow(unused_variables, unused_mut)]
+pub fn throughput_suite(options: BenchmarkOptions, count: usize) -> Result<Suite> {
+    let mut suite = Suite::new(options);
+
+    #[cfg(all(feature = "uniprot", feature = "fasta"))]
+    fasta(&mut suite, count)?;
+
+    #[cfg(all(feature = "uniprot", feature = "csv"))]
+    csv(&mut suite, count)?;
+
+    #[cfg(all(feature = "uniprot", feature = "xml"))]
+    xml(&mut suite, count)?;
+
+    #[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+    mgf(&mut suite, count)?;
+
+    Ok(suite)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throughput_suite_test() {
+        let options = BenchmarkOptions {
+            warmup: 0,
+            samples: 1,
+            filter: None,
+        };
+        let suite = throughput_suite(options, 5).unwrap();
+        assert!(suite.measurements().iter().all(|m| m.bytes > 0 && m.samples.len() == 1));
+
+        #[cfg(all(feature = "uniprot", feature = "fasta", feature = "csv", feature = "xml", feature = "mass_spectrometry", feature = "mgf"))]
+        {
+            let names: Vec<&str> = suite.measurements().iter().map(|m| m.name.as_str()).collect();
+            assert_eq!(names, vec![
+                "fasta/parse", "fasta/write", "csv/parse", "csv/write",
+                "xml/parse", "xml/write", "mgf/parse", "mgf/write",
+            ]);
+        }
+    }
+}
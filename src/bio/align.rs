@@ -0,0 +1,157 @@
+//! Pairwise sequence alignment.
+//!
+//! Global (Needleman-Wunsch) alignment with linear gap penalties, in
+//! O(n*m) time and memory. Residues are compared ignoring case.
+
+/// Scores for pairwise alignment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AlignmentScoring {
+    /// Score for identical residues.
+    pub matched: i32,
+    /// Score for different residues.
+    pub mismatched: i32,
+    /// Score for each gap position, typically negative.
+    pub gap: i32,
+}
+
+impl Default for AlignmentScoring {
+    #[inline]
+    fn default() -> Self {
+        AlignmentScoring {
+            matched: 1,
+            mismatched: -1,
+            gap: -2,
+        }
+    }
+}
+
+/// Pairwise alignment of two sequences.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Alignment {
+    /// Alignment score.
+    pub score: i32,
+    /// First sequence, with `-` for gaps.
+    pub x: Vec<u8>,
+    /// Second sequence, with `-` for gaps.
+    pub y: Vec<u8>,
+}
+
+impl Alignment {
+    /// Get the number of aligned columns, including gaps.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Check if the alignment has no columns.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// Get the number of columns with identical residues.
+    #[inline]
+    pub fn matches(&self) -> usize {
+        self.x.iter()
+            .zip(self.y.iter())
+            .filter(|&(a, b)| *a != b'-' && a.eq_ignore_ascii_case(b))
+            .count()
+    }
+
+    /// Get the fraction of columns with identical residues, or 0 if empty.
+    #[inline]
+    pub fn identity(&self) -> f64 {
+        match self.len() {
+            0 => 0.0,
+            n => self.matches() as f64 / n as f64,
+        }
+    }
+}
+
+/// Globally align two sequences.
+pub fn global_align(x: &[u8], y: &[u8], scoring: &AlignmentScoring) -> Alignment {
+    let (n, m) = (x.len(), y.len());
+    let columns = m + 1;
+    let mut scores = vec![0i32; (n + 1) * columns];
+    for i in 1..n+1 {
+        scores[i * columns] = i as i32 * scoring.gap;
+    }
+    for (j, score) in scores[..columns].iter_mut().enumerate() {
+        *score = j as i32 * scoring.gap;
+    }
+    let substitution = |i: usize, j: usize| match x[i-1].eq_ignore_ascii_case(&y[j-1]) {
+        true  => scoring.matched,
+        false => scoring.mismatched,
+    };
+    for i in 1..n+1 {
+        for j in 1..m+1 {
+            let diagonal = scores[(i-1) * columns + j-1] + substitution(i, j);
+            let up = scores[(i-1) * columns + j] + scoring.gap;
+            let left = scores[i * columns + j-1] + scoring.gap;
+            scores[i * columns + j] = diagonal.max(up).max(left);
+        }
+    }
+
+    // Traceback, preferring substitutions over gaps.
+    let mut aligned_x = Vec::with_capacity(n.max(m));
+    let mut aligned_y = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        let score = scores[i * columns + j];
+        if i > 0 && j > 0 && score == scores[(i-1) * columns + j-1] + substitution(i, j) {
+            aligned_x.push(x[i-1]);
+            aligned_y.push(y[j-1]);
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && score == scores[(i-1) * columns + j] + scoring.gap {
+            aligned_x.push(x[i-1]);
+            aligned_y.push(b'-');
+            i -= 1;
+        } else {
+            aligned_x.push(b'-');
+            aligned_y.push(y[j-1]);
+            j -= 1;
+        }
+    }
+    aligned_x.reverse();
+    aligned_y.reverse();
+
+    Alignment {
+        score: scores[n * columns + m],
+        x: aligned_x,
+        y: aligned_y,
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_align_test() {
+        let scoring = AlignmentScoring::default();
+        let alignment = global_align(b"GATTACA", b"GATTACA", &scoring);
+        assert_eq!(alignment.score, 7);
+        assert_eq!(alignment.identity(), 1.0);
+
+        let alignment = global_align(b"GATTACA", b"GCATGCA", &scoring);
+        assert_eq!(alignment.x.len(), alignment.y.len());
+        assert_eq!(alignment.score, 1);
+        assert_eq!(alignment.matches(), 4);
+
+        let alignment = global_align(b"MKWVTFISLL", b"mkwvtlll", &scoring);
+        assert_eq!(alignment.x, b"MKWVTFISLL".to_vec());
+        assert_eq!(alignment.y, b"mkwvt--lll".to_vec());
+        assert_eq!(alignment.score, 2);
+        assert_approx_eq!(alignment.identity(), 0.7, 1e-12);
+
+        let alignment = global_align(b"", b"AC", &scoring);
+        assert_eq!(alignment.x, b"--".to_vec());
+        assert_eq!(alignment.score, -4);
+        assert!(global_align(b"", b"", &scoring).is_empty());
+        assert_eq!(global_align(b"", b"", &scoring).identity(), 0.0);
+    }
+}
@@ -0,0 +1,169 @@
+//! Sequence alphabet policies.
+//!
+//! Sequences may contain ambiguous or non-standard residues, or be
+//! soft-masked with lowercase letters. An `Alphabet` selects which
+//! residues are accepted, for both protein and nucleotide sequences.
+
+use util::{ErrorKind, Result};
+
+/// Standard 20 aminoacid 1-letter codes.
+const PROTEIN_STRICT: &'static [u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Non-standard, genetically-encoded aminoacids (selenocysteine, pyrrolysine).
+const PROTEIN_EXTENDED: &'static [u8] = b"UO";
+
+/// Ambiguous aminoacid 1-letter codes.
+const PROTEIN_AMBIGUOUS: &'static [u8] = b"BJZX";
+
+/// Standard DNA nucleotide 1-letter codes.
+const NUCLEOTIDE_STRICT: &'static [u8] = b"ACGT";
+
+/// Uracil and the unknown nucleotide.
+const NUCLEOTIDE_EXTENDED: &'static [u8] = b"UN";
+
+/// IUPAC ambiguous nucleotide 1-letter codes.
+const NUCLEOTIDE_AMBIGUOUS: &'static [u8] = b"RYSWKMBDHV";
+
+/// Policy for the residues accepted in a sequence.
+///
+/// Each policy accepts the residues of the previous policy, and only
+/// `CaseInsensitive` accepts lowercase residues.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Standard residues only (the 20 aminoacids, or "ACGT").
+    Strict,
+    /// Standard and non-standard residues ("U" and "O", or "U" and "N").
+    Extended,
+    /// Extended and IUPAC ambiguous residues.
+    Ambiguous,
+    /// Ambiguous residues, in either case, such as for soft-masked sequences.
+    CaseInsensitive,
+}
+
+impl Alphabet {
+    /// Determine if a residue is within any of the residue sets.
+    #[inline]
+    fn contains(&self, sets: [&'static [u8]; 3], residue: u8) -> bool {
+        let residue = match *self {
+            Alphabet::CaseInsensitive => residue.to_ascii_uppercase(),
+            _                         => residue,
+        };
+        let count = match *self {
+            Alphabet::Strict    => 1,
+            Alphabet::Extended  => 2,
+            _                   => 3,
+        };
+        sets[..count].iter().any(|set| set.contains(&residue))
+    }
+
+    /// Determine if the aminoacid is accepted by the alphabet.
+    #[inline]
+    pub fn is_aminoacid(&self, residue: u8) -> bool {
+        self.contains([PROTEIN_STRICT, PROTEIN_EXTENDED, PROTEIN_AMBIGUOUS], residue)
+    }
+
+    /// Determine if the nucleotide is accepted by the alphabet.
+    #[inline]
+    pub fn is_nucleotide(&self, residue: u8) -> bool {
+        self.contains([NUCLEOTIDE_STRICT, NUCLEOTIDE_EXTENDED, NUCLEOTIDE_AMBIGUOUS], residue)
+    }
+
+    /// Determine if all residues in the protein sequence are accepted.
+    #[inline]
+    pub fn is_protein_sequence(&self, sequence: &[u8]) -> bool {
+        sequence.iter().all(|&c| self.is_aminoacid(c))
+    }
+
+    /// Determine if all residues in the nucleotide sequence are accepted.
+    #[inline]
+    pub fn is_nucleotide_sequence(&self, sequence: &[u8]) -> bool {
+        sequence.iter().all(|&c| self.is_nucleotide(c))
+    }
+
+    /// Normalize the sequence, in-place.
+    ///
+    /// Case-insensitive sequences are converted to uppercase, removing
+    /// any soft-masking. Other alphabets leave the sequence unchanged.
+    #[inline]
+    pub fn normalize(&self, sequence: &mut [u8]) {
+        if *self == Alphabet::CaseInsensitive {
+            sequence.make_ascii_uppercase();
+        }
+    }
+}
+
+/// Normalize and check a protein sequence from a parser, in-place.
+///
+/// Without an alphabet, the sequence is left unchanged.
+pub(crate) fn check_protein_sequence(alphabet: Option<Alphabet>, sequence: &mut [u8])
+    -> Result<()>
+{
+    if let Some(alphabet) = alphabet {
+        alphabet.normalize(sequence);
+        bool_to_error!(alphabet.is_protein_sequence(sequence), InvalidRecord);
+    }
+    Ok(())
+}
+
+/// Normalize and check a nucleotide sequence from a parser, in-place.
+///
+/// Without an alphabet, the sequence is left unchanged.
+pub(crate) fn check_nucleotide_sequence(alphabet: Option<Alphabet>, sequence: &mut [u8])
+    -> Result<()>
+{
+    if let Some(alphabet) = alphabet {
+        alphabet.normalize(sequence);
+        bool_to_error!(alphabet.is_nucleotide_sequence(sequence), InvalidRecord);
+    }
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protein_alphabet_test() {
+        assert!(Alphabet::Strict.is_protein_sequence(b"MVKVGVNGFGRIGR"));
+        assert!(!Alphabet::Strict.is_protein_sequence(b"MVKUGV"));
+        assert!(Alphabet::Extended.is_protein_sequence(b"MVKUGVO"));
+        assert!(!Alphabet::Extended.is_protein_sequence(b"MVKXGV"));
+        assert!(Alphabet::Ambiguous.is_protein_sequence(b"MVKXGVBZJ"));
+        assert!(!Alphabet::Ambiguous.is_protein_sequence(b"mvkvgv"));
+        assert!(Alphabet::CaseInsensitive.is_protein_sequence(b"mvkXGV"));
+        assert!(!Alphabet::CaseInsensitive.is_protein_sequence(b"MVK*"));
+        assert!(Alphabet::Strict.is_protein_sequence(b""));
+    }
+
+    #[test]
+    fn nucleotide_alphabet_test() {
+        assert!(Alphabet::Strict.is_nucleotide_sequence(b"CATTAG"));
+        assert!(!Alphabet::Strict.is_nucleotide_sequence(b"CANTAG"));
+        assert!(Alphabet::Extended.is_nucleotide_sequence(b"CANUAG"));
+        assert!(!Alphabet::Extended.is_nucleotide_sequence(b"CARTAG"));
+        assert!(Alphabet::Ambiguous.is_nucleotide_sequence(b"CARTYGKMSWBDHV"));
+        assert!(!Alphabet::Ambiguous.is_nucleotide_sequence(b"cattag"));
+        assert!(Alphabet::CaseInsensitive.is_nucleotide_sequence(b"catTAGn"));
+        assert!(!Alphabet::CaseInsensitive.is_nucleotide_sequence(b"CATXAG"));
+    }
+
+    #[test]
+    fn normalize_test() {
+        let mut sequence = b"catTAG".to_vec();
+        Alphabet::Ambiguous.normalize(&mut sequence);
+        assert_eq!(sequence, b"catTAG".to_vec());
+        Alphabet::CaseInsensitive.normalize(&mut sequence);
+        assert_eq!(sequence, b"CATTAG".to_vec());
+
+        let mut sequence = b"mvkX".to_vec();
+        assert!(check_protein_sequence(None, &mut sequence).is_ok());
+        assert_eq!(sequence, b"mvkX".to_vec());
+        assert!(check_protein_sequence(Some(Alphabet::Ambiguous), &mut sequence).is_err());
+        assert!(check_protein_sequence(Some(Alphabet::CaseInsensitive), &mut sequence).is_ok());
+        assert_eq!(sequence, b"MVKX".to_vec());
+        assert!(check_nucleotide_sequence(Some(Alphabet::Strict), &mut b"CANTAG".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Greedy incremental clustering of sequences by identity (CD-HIT-like).
+//!
+//! Sequences are processed from longest to shortest, and each sequence
+//! joins the first representative with sufficient identity, or becomes
+//! a new representative. Candidate representatives are prefiltered by
+//! the number of shared k-mers, and verified by global alignment.
+
+use std::collections::HashSet;
+
+use concurrency::CancellationToken;
+use util::Result;
+use super::align::{global_align, AlignmentScoring};
+use super::distance::kmers;
+
+/// Options to cluster sequences.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusterOptions {
+    /// Minimum identity to the representative, from 0 to 1.
+    ///
+    /// Identity is the number of identical residues in the alignment,
+    /// divided by the length of the shorter sequence.
+    pub identity: f64,
+    /// Length of the k-mers for the prefilter, 0 to disable the prefilter.
+    pub kmer: usize,
+    /// Scores to align the sequences.
+    pub scoring: AlignmentScoring,
+}
+
+impl Default for ClusterOptions {
+    #[inline]
+    fn default() -> Self {
+        ClusterOptions {
+            identity: 0.9,
+            kmer: 5,
+            scoring: AlignmentScoring::default(),
+        }
+    }
+}
+
+/// Cluster of sequences, identified by index.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cluster {
+    /// Index of the representative (longest) sequence.
+    pub representative: usize,
+    /// Indexes of all sequences in the cluster, including the representative.
+    pub members: Vec<usize>,
+}
+
+/// Minimum number of k-mers in a sequence of `length` shared with
+/// a sequence of at least `identity`.
+///
+/// Each residue differing from the other sequence breaks at most `k` k-mers.
+/// The number of differences is rounded up, so the bound never rejects
+/// a sequence with sufficient identity.
+fn min_shared_kmers(length: usize, k: usize, identity: f64) -> usize {
+    let windows = (length + 1).saturating_sub(k);
+    let differences = (length as f64 * (1.0 - identity)).ceil() as usize;
+    windows.saturating_sub(differences * k)
+}
+
+/// Calculate the identity between two sequences, relative to the shorter.
+fn identity(x: &[u8], y: &[u8], scoring: &AlignmentScoring) -> f64 {
+    match x.len().min(y.len()) {
+        0 => 0.0,
+        n => global_align(x, y, scoring).matches() as f64 / n as f64,
+    }
+}
+
+/// Cluster sequences by identity.
+///
+/// Clusters are ordered by their representative, longest first, with
+/// ties broken by the original order. Empty sequences form singleton
+/// clusters.
+pub fn cluster_sequences(sequences: &[&[u8]], options: &ClusterOptions) -> Vec<Cluster> {
+    // A new token is never cancelled.
+    cluster_sequences_with_cancel(sequences, options, &CancellationToken::new())
+        .expect("clustering without cancellation cannot fail")
+}
+
+/// Cluster sequences by identity, stopping early if cancelled.
+///
+/// The token is checked before clustering each sequence.
+pub fn cluster_sequences_with_cancel(sequences: &[&[u8]], options: &ClusterOptions, cancel: &CancellationToken)
+    -> Result<Vec<Cluster>>
+{
+    let mut order: Vec<usize> = (0..sequences.len()).collect();
+    order.sort_by(|&i, &j| sequences[j].len().cmp(&sequences[i].len()));
+
+    let mut clusters: Vec<Cluster> = vec![];
+    let mut words: Vec<HashSet<Vec<u8>>> = vec![];
+    for index in order {
+        cancel.check()?;
+        let sequence = sequences[index];
+        let min_shared = min_shared_kmers(sequence.len(), options.kmer, options.identity);
+        let mut found = None;
+        for (i, cluster) in clusters.iter().enumerate() {
+            if options.kmer > 0 && min_shared > 0 {
+                let shared = sequence.windows(options.kmer)
+                    .filter(|w| words[i].contains(&w.to_ascii_uppercase()))
+                    .count();
+                if shared < min_shared {
+                    continue;
+                }
+            }
+            let representative = sequences[cluster.representative];
+            if identity(representative, sequence, &options.scoring) >= options.identity {
+                found = Some(i);
+                break;
+            }
+        }
+
+        match found {
+            Some(i) => clusters[i].members.push(index),
+            None    => {
+                clusters.push(Cluster { representative: index, members: vec![index] });
+                words.push(kmers(sequence, options.kmer));
+            },
+        }
+    }
+
+    Ok(clusters)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_shared_kmers_test() {
+        assert_eq!(min_shared_kmers(100, 5, 0.9), 46);
+        assert_eq!(min_shared_kmers(100, 5, 1.0), 96);
+        assert_eq!(min_shared_kmers(20, 5, 0.5), 0);
+        assert_eq!(min_shared_kmers(3, 5, 0.9), 0);
+    }
+
+    #[test]
+    fn cluster_sequences_test() {
+        let x = &b"MVKVGVNGFGRIGRLVTRAAFNSGKVDIVAINDPFIDLNYMVYMFQYDSTHGKFHGTVKAENGKLVINGK"[..];
+        let mut y = x.to_vec();
+        y[10] = b'W';
+        y[40] = b'W';
+        let z = &b"MKWVTFISLLLLFSSAYSRGVFRRDTHKSEIAHRFKDLGEEHFKGLVLIAFSQYLQQCPFDEHVKLVNEL"[..];
+        let short = &x[..30];
+        let sequences = vec![short, z, x, &y[..], &b""[..]];
+
+        let clusters = cluster_sequences(&sequences, &ClusterOptions::default());
+        assert_eq!(clusters.len(), 3);
+        assert_eq!(clusters[0], Cluster { representative: 1, members: vec![1] });
+        assert_eq!(clusters[1], Cluster { representative: 2, members: vec![2, 3, 0] });
+        assert_eq!(clusters[2], Cluster { representative: 4, members: vec![4] });
+
+        let options = ClusterOptions { identity: 1.0, ..ClusterOptions::default() };
+        let clusters = cluster_sequences(&sequences, &options);
+        assert_eq!(clusters.len(), 4);
+        assert_eq!(clusters[1].members, vec![2, 0]);
+        assert_eq!(clusters[2].members, vec![3]);
+
+        // The prefilter does not change the result.
+        let options = ClusterOptions { kmer: 0, ..ClusterOptions::default() };
+        assert_eq!(cluster_sequences(&sequences, &options).len(), 3);
+
+        let cancel = CancellationToken::new();
+        assert_eq!(cluster_sequences_with_cancel(&sequences, &options, &cancel).unwrap().len(), 3);
+        cancel.cancel();
+        assert!(cluster_sequences_with_cancel(&sequences, &options, &cancel).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Crosslinker definitions, and masses of crosslinked peptide pairs.
+//!
+//! Crosslinking mass spectrometry covalently links reactive residues
+//! in proximity, so a pair of peptides from the digestion is observed
+//! as a single precursor, with the mass of both peptides and the linker.
+
+use std::cmp::Ordering;
+
+use super::digest::Peptide;
+
+/// Definition of a homobifunctional crosslinking reagent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crosslinker {
+    /// Crosslinker name, for example, `"DSS"`.
+    pub name: String,
+    /// Monoisotopic mass added by the crosslink, in Daltons.
+    pub mass: f64,
+    /// Residues reacting with the crosslinker, in uppercase.
+    pub residues: Vec<u8>,
+    /// Crosslinker reacts with the protein N-terminus.
+    pub protein_nterm: bool,
+}
+
+impl Crosslinker {
+    /// Create new crosslinker reacting with residues.
+    #[inline]
+    pub fn new(name: &str, mass: f64, residues: &[u8]) -> Self {
+        Crosslinker {
+            name: String::from(name),
+            mass: mass,
+            residues: residues.to_vec(),
+            protein_nterm: false,
+        }
+    }
+
+    /// Disuccinimidyl suberate (DSS), reacting with lysine and the protein N-terminus.
+    #[inline]
+    pub fn dss() -> Self {
+        Crosslinker {
+            protein_nterm: true,
+            ..Crosslinker::new("DSS", 138.068080, b"K")
+        }
+    }
+
+    /// Bis(sulfosuccinimidyl) suberate (BS3), with the same linker as DSS.
+    #[inline]
+    pub fn bs3() -> Self {
+        Crosslinker {
+            name: String::from("BS3"),
+            ..Crosslinker::dss()
+        }
+    }
+
+    /// Disuccinimidyl sulfoxide (DSSO), an MS-cleavable crosslinker.
+    #[inline]
+    pub fn dsso() -> Self {
+        Crosslinker {
+            protein_nterm: true,
+            ..Crosslinker::new("DSSO", 158.003765, b"K")
+        }
+    }
+
+    /// Check if the crosslinker reacts with a residue, ignoring case.
+    #[inline]
+    pub fn reacts(&self, residue: u8) -> bool {
+        self.residues.contains(&residue.to_ascii_uppercase())
+    }
+
+    /// Get the 0-based positions of the reactive sites within a peptide.
+    ///
+    /// The C-terminal residue is excluded, since the protease does not
+    /// cleave after a crosslinked residue, unless it is the last
+    /// residue of the protein.
+    pub fn sites(&self, peptide: &Peptide, protein_length: usize) -> Vec<usize> {
+        let is_protein_cterm = peptide.end() == protein_length;
+        let last = peptide.sequence.len().saturating_sub(1);
+        (0..peptide.sequence.len())
+            .filter(|&i| {
+                let is_nterm = i == 0 && peptide.start == 0 && self.protein_nterm;
+                let is_reactive = self.reacts(peptide.sequence[i]) && (i != last || is_protein_cterm);
+                is_nterm || is_reactive
+            })
+            .collect()
+    }
+}
+
+/// Calculate the neutral mass of a pair of crosslinked peptides.
+#[inline]
+pub fn crosslinked_mass(alpha: &Peptide, beta: &Peptide, crosslinker: &Crosslinker) -> f64 {
+    alpha.mass + beta.mass + crosslinker.mass
+}
+
+/// Candidate pair of crosslinked peptides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrosslinkedPair {
+    /// Index of the first (alpha) peptide.
+    pub alpha: usize,
+    /// Index of the second (beta) peptide.
+    pub beta: usize,
+    /// Neutral mass of the crosslinked pair, in Daltons.
+    pub mass: f64,
+}
+
+/// Enumerate the crosslinkable pairs from digested peptides.
+///
+/// Pairs each peptide with every peptide at or after it, including
+/// itself (for homodimers), if both have a reactive site. The peptides
+/// must be from a protein of `protein_length` residues, for example,
+/// from `digest()` with missed cleavages. Pairs are sorted by mass.
+pub fn crosslinked_pairs(peptides: &[Peptide], protein_length: usize, crosslinker: &Crosslinker)
+    -> Vec<CrosslinkedPair>
+{
+    let reactive: Vec<usize> = (0..peptides.len())
+        .filter(|&i| !crosslinker.sites(&peptides[i], protein_length).is_empty())
+        .collect();
+
+    let mut pairs = vec![];
+    for (k, &alpha) in reactive.iter().enumerate() {
+        for &beta in reactive[k..].iter() {
+            pairs.push(CrosslinkedPair {
+                alpha: alpha,
+                beta: beta,
+                mass: crosslinked_mass(&peptides[alpha], &peptides[beta], crosslinker),
+            });
+        }
+    }
+    pairs.sort_by(|x, y| x.mass.partial_cmp(&y.mass).unwrap_or(Ordering::Equal));
+    pairs
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::digest::{digest, DigestOptions};
+    use bio::proteins::ProteinMassTable;
+    use super::*;
+
+    #[test]
+    fn sites_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let mut options = DigestOptions::default();
+        options.missed_cleavages = 1;
+        options.min_length = 1;
+        options.min_mass = 0.0;
+        let sequence = b"SAKMPLEKAGR";
+        let peptides = digest(sequence, &options, &table);
+        let summary: Vec<(&[u8], Vec<usize>)> = peptides.iter()
+            .map(|p| (&p.sequence[..], Crosslinker::dss().sites(p, sequence.len())))
+            .collect();
+        assert_eq!(summary, vec![
+            (&b"SAK"[..], vec![0]),
+            (&b"SAKMPLEK"[..], vec![0, 2]),
+            (&b"MPLEK"[..], vec![]),
+            (&b"MPLEKAGR"[..], vec![4]),
+            (&b"AGR"[..], vec![]),
+        ]);
+
+        let crosslinker = Crosslinker::new("Custom", 100.0, b"R");
+        assert_eq!(crosslinker.sites(&peptides[4], sequence.len()), vec![2]);
+        assert!(!Crosslinker::dsso().reacts(b'R'));
+    }
+
+    #[test]
+    fn crosslinked_pairs_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let mut options = DigestOptions::default();
+        options.missed_cleavages = 1;
+        options.min_length = 1;
+        options.min_mass = 0.0;
+        let sequence = b"SAKMPLEKAGR";
+        let peptides = digest(sequence, &options, &table);
+        let crosslinker = Crosslinker::bs3();
+
+        let pairs = crosslinked_pairs(&peptides, sequence.len(), &crosslinker);
+        assert_eq!(pairs.len(), 6);
+        assert!(pairs.windows(2).all(|w| w[0].mass <= w[1].mass));
+        assert_eq!((pairs[0].alpha, pairs[0].beta), (0, 0));
+        assert_approx_eq!(pairs[0].mass, 2.0 * peptides[0].mass + 138.068080);
+        assert_approx_eq!(crosslinked_mass(&peptides[0], &peptides[3], &crosslinker), peptides[0].mass + peptides[3].mass + 138.068080);
+    }
+}
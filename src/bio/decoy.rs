@@ -0,0 +1,83 @@
+//! Decoy peptides for target-decoy false discovery rate estimation.
+//!
+//! Decoys keep the C-terminal residue of the target peptide, so decoys
+//! from a specific digest end at a cleavage site, and have the same
+//! mass as the target. Shuffled decoys are reproducible from the seed.
+
+use util::{stable_hash, Rng};
+
+/// Number of shuffles to try before returning the target sequence.
+const MAX_SHUFFLES: usize = 10;
+
+/// Reverse a peptide, keeping the C-terminal residue (pseudo-reversed decoy).
+pub fn reverse_decoy(sequence: &[u8]) -> Vec<u8> {
+    match sequence.split_last() {
+        Some((last, rest)) => rest.iter().rev().chain(Some(last)).cloned().collect(),
+        None               => vec![],
+    }
+}
+
+/// Shuffle a peptide, keeping the C-terminal residue.
+///
+/// Shuffles again if the decoy matches the target, and returns the
+/// target sequence if no shuffle differs, such as for homopolymers.
+pub fn shuffle_decoy(sequence: &[u8], rng: &mut Rng) -> Vec<u8> {
+    let mut decoy = sequence.to_vec();
+    if let Some((_, rest)) = decoy.split_last_mut() {
+        for _ in 0..MAX_SHUFFLES {
+            rng.shuffle(rest);
+            if rest != &sequence[..sequence.len() - 1] {
+                break;
+            }
+        }
+    }
+    decoy
+}
+
+/// Shuffle a peptide, seeding the generator from the sequence and seed.
+///
+/// Every occurrence of a target peptide produces the same decoy,
+/// independent of the order the peptides are processed.
+#[inline]
+pub fn shuffle_decoy_seeded(sequence: &[u8], seed: u64) -> Vec<u8> {
+    shuffle_decoy(sequence, &mut Rng::new(stable_hash(sequence, seed)))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_decoy_test() {
+        assert_eq!(reverse_decoy(b"PEPTIDEK"), b"EDITPEPK".to_vec());
+        assert_eq!(reverse_decoy(b"K"), b"K".to_vec());
+        assert!(reverse_decoy(b"").is_empty());
+    }
+
+    #[test]
+    fn shuffle_decoy_test() {
+        let decoy = shuffle_decoy(b"SAMPLERPEPTIDEK", &mut Rng::new(1));
+        assert_ne!(decoy, b"SAMPLERPEPTIDEK".to_vec());
+        assert_eq!(decoy.last(), Some(&b'K'));
+        let mut sorted = decoy.clone();
+        sorted.sort();
+        let mut target = b"SAMPLERPEPTIDEK".to_vec();
+        target.sort();
+        assert_eq!(sorted, target);
+        assert_eq!(decoy, shuffle_decoy(b"SAMPLERPEPTIDEK", &mut Rng::new(1)));
+
+        assert_eq!(shuffle_decoy(b"AAAK", &mut Rng::new(1)), b"AAAK".to_vec());
+        assert!(shuffle_decoy(b"", &mut Rng::new(1)).is_empty());
+    }
+
+    #[test]
+    fn shuffle_decoy_seeded_test() {
+        let decoy = shuffle_decoy_seeded(b"SAMPLERPEPTIDEK", 7);
+        assert_eq!(decoy, shuffle_decoy_seeded(b"SAMPLERPEPTIDEK", 7));
+        assert_eq!(decoy.last(), Some(&b'K'));
+        assert_ne!(decoy, shuffle_decoy_seeded(b"SAMPLERPEPTIDEK", 8));
+    }
+}
@@ -0,0 +1,214 @@
+//! Proteolytic digestion of protein sequences.
+//!
+//! Proteins are cleaved at the enzyme cleavage sites, and peptides
+//! spanning up to a maximum number of missed cleavages are enumerated,
+//! filtered by length and mass, to generate the searchable peptides
+//! for a protein database.
+
+use super::proteins::ProteinMassTable;
+
+/// Proteolytic enzyme, with its cleavage rules.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Enzyme {
+    /// Cleaves after K or R, except before P.
+    Trypsin,
+    /// Cleaves after K or R, including before P.
+    TrypsinP,
+    /// Cleaves after K, except before P.
+    LysC,
+    /// Cleaves after R, except before P.
+    ArgC,
+    /// Cleaves after E, except before P.
+    GluC,
+    /// Cleaves before D.
+    AspN,
+    /// Cleaves after F, W, Y or L, except before P.
+    Chymotrypsin,
+}
+
+impl Enzyme {
+    /// Check if the enzyme cleaves between `previous` and `next`.
+    #[inline]
+    pub fn cleaves(&self, previous: u8, next: u8) -> bool {
+        let not_proline = next != b'P';
+        match *self {
+            Enzyme::Trypsin      => (previous == b'K' || previous == b'R') && not_proline,
+            Enzyme::TrypsinP     => previous == b'K' || previous == b'R',
+            Enzyme::LysC         => previous == b'K' && not_proline,
+            Enzyme::ArgC         => previous == b'R' && not_proline,
+            Enzyme::GluC         => previous == b'E' && not_proline,
+            Enzyme::AspN         => next == b'D',
+            Enzyme::Chymotrypsin => b"FWYL".contains(&previous) && not_proline,
+        }
+    }
+
+    /// Create enzyme from its name, ignoring case, for example, `"Lys-C"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trypsin"        => Some(Enzyme::Trypsin),
+            "trypsin/p"      => Some(Enzyme::TrypsinP),
+            "lys-c" | "lysc" => Some(Enzyme::LysC),
+            "arg-c" | "argc" => Some(Enzyme::ArgC),
+            "glu-c" | "gluc" => Some(Enzyme::GluC),
+            "asp-n" | "aspn" => Some(Enzyme::AspN),
+            "chymotrypsin"   => Some(Enzyme::Chymotrypsin),
+            _                => None,
+        }
+    }
+
+    /// Get the cleavage sites, including the start and end of the sequence.
+    ///
+    /// Each site is the index of the first residue after the cleavage.
+    pub fn cleavage_sites(&self, sequence: &[u8]) -> Vec<usize> {
+        let mut sites = vec![0];
+        sites.extend((1..sequence.len()).filter(|&i| self.cleaves(sequence[i-1], sequence[i])));
+        if !sequence.is_empty() {
+            sites.push(sequence.len());
+        }
+        sites
+    }
+}
+
+/// Options for proteolytic digestion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DigestOptions {
+    /// Proteolytic enzyme.
+    pub enzyme: Enzyme,
+    /// Maximum number of missed cleavages in a peptide.
+    pub missed_cleavages: usize,
+    /// Minimum peptide length, in residues.
+    pub min_length: usize,
+    /// Maximum peptide length, in residues.
+    pub max_length: usize,
+    /// Minimum neutral peptide mass, in Daltons.
+    pub min_mass: f64,
+    /// Maximum neutral peptide mass, in Daltons.
+    pub max_mass: f64,
+}
+
+impl Default for DigestOptions {
+    /// Tryptic peptides with up to 2 missed cleavages, in a typical search range.
+    #[inline]
+    fn default() -> Self {
+        DigestOptions {
+            enzyme: Enzyme::Trypsin,
+            missed_cleavages: 2,
+            min_length: 7,
+            max_length: 50,
+            min_mass: 500.0,
+            max_mass: 5000.0,
+        }
+    }
+}
+
+/// Peptide produced by the digestion of a protein.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Peptide {
+    /// Peptide sequence.
+    pub sequence: Vec<u8>,
+    /// 0-based position of the first residue in the protein.
+    pub start: usize,
+    /// Number of missed cleavages within the peptide.
+    pub missed_cleavages: usize,
+    /// Neutral peptide mass, in Daltons.
+    pub mass: f64,
+}
+
+impl Peptide {
+    /// Get the 0-based position past the last residue in the protein.
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.start + self.sequence.len()
+    }
+
+    /// Check if the peptide contains a 0-based protein position.
+    #[inline]
+    pub fn contains(&self, position: usize) -> bool {
+        position >= self.start && position < self.end()
+    }
+}
+
+/// Digest a protein sequence into peptides.
+///
+/// Peptides are ordered by start position, then by missed cleavages.
+/// The mass is calculated with the mass table, for example, from
+/// `ProteinMassTable::monoisotopic()`.
+pub fn digest(sequence: &[u8], options: &DigestOptions, table: &ProteinMassTable) -> Vec<Peptide> {
+    let sites = options.enzyme.cleavage_sites(sequence);
+    let mut peptides = vec![];
+    for i in 0..sites.len().saturating_sub(1) {
+        let last = (i + options.missed_cleavages + 1).min(sites.len() - 1);
+        for j in i+1..last+1 {
+            let (start, end) = (sites[i], sites[j]);
+            let length = end - start;
+            if length > options.max_length {
+                break;
+            }
+            if length < options.min_length {
+                continue;
+            }
+            let peptide = &sequence[start..end];
+            let mass = table.total_sequence_mass(peptide);
+            if mass >= options.min_mass && mass <= options.max_mass {
+                peptides.push(Peptide {
+                    sequence: peptide.to_vec(),
+                    start: start,
+                    missed_cleavages: j - i - 1,
+                    mass: mass,
+                });
+            }
+        }
+    }
+    peptides
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleavage_sites_test() {
+        assert_eq!(Enzyme::Trypsin.cleavage_sites(b"MKRPAKDE"), vec![0, 2, 6, 8]);
+        assert_eq!(Enzyme::TrypsinP.cleavage_sites(b"MKRPAKDE"), vec![0, 2, 3, 6, 8]);
+        assert_eq!(Enzyme::AspN.cleavage_sites(b"MKRPAKDE"), vec![0, 6, 8]);
+        assert_eq!(Enzyme::LysC.cleavage_sites(b""), vec![0]);
+    }
+
+    #[test]
+    fn from_name_test() {
+        assert_eq!(Enzyme::from_name("Trypsin"), Some(Enzyme::Trypsin));
+        assert_eq!(Enzyme::from_name("trypsin/P"), Some(Enzyme::TrypsinP));
+        assert_eq!(Enzyme::from_name("Lys-C"), Some(Enzyme::LysC));
+        assert_eq!(Enzyme::from_name("aspn"), Some(Enzyme::AspN));
+        assert_eq!(Enzyme::from_name("pepsin"), None);
+    }
+
+    #[test]
+    fn digest_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let options = DigestOptions {
+            missed_cleavages: 1,
+            min_length: 1,
+            min_mass: 0.0,
+            ..DigestOptions::default()
+        };
+        let peptides = digest(b"SAMPLERKPEPTIDEK", &options, &table);
+        let sequences: Vec<&[u8]> = peptides.iter().map(|p| p.sequence.as_slice()).collect();
+        assert_eq!(sequences, vec![&b"SAMPLER"[..], &b"SAMPLERKPEPTIDEK"[..], &b"KPEPTIDEK"[..]]);
+        assert_eq!(peptides[1].missed_cleavages, 1);
+        assert_eq!(peptides[2].start, 7);
+        assert_eq!(peptides[2].end(), 16);
+        assert!(peptides[2].contains(15) && !peptides[2].contains(6));
+        assert_eq!(peptides[0].mass, table.total_sequence_mass(b"SAMPLER"));
+
+        // Length and mass filters.
+        let options = DigestOptions { missed_cleavages: 1, min_length: 8, ..DigestOptions::default() };
+        let peptides = digest(b"SAMPLERKPEPTIDEK", &options, &table);
+        assert_eq!(peptides.len(), 2);
+        let options = DigestOptions { max_mass: 900.0, min_length: 1, ..DigestOptions::default() };
+        assert_eq!(digest(b"SAMPLERKPEPTIDEK", &options, &table).len(), 1);
+    }
+}
@@ -0,0 +1,141 @@
+//! Pairwise distance matrices between sequences.
+
+use std::collections::HashSet;
+
+use super::align::{global_align, AlignmentScoring};
+
+/// Method to calculate the distance between two sequences.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceMethod {
+    /// `1 - identity` of the global alignment of the sequences.
+    Identity(AlignmentScoring),
+    /// Jaccard distance between the sets of k-mers of the sequences.
+    ///
+    /// Much faster than alignment, for large sets of sequences.
+    /// Sequences shorter than `k` have a distance of 1.
+    Kmer(usize),
+}
+
+impl DistanceMethod {
+    /// Calculate the distance between two sequences, from 0 to 1.
+    pub fn distance(&self, x: &[u8], y: &[u8]) -> f64 {
+        match *self {
+            DistanceMethod::Identity(ref scoring) => 1.0 - global_align(x, y, scoring).identity(),
+            DistanceMethod::Kmer(k) => {
+                let x = kmers(x, k);
+                let y = kmers(y, k);
+                let union = x.union(&y).count();
+                match union {
+                    0 => 1.0,
+                    n => 1.0 - x.intersection(&y).count() as f64 / n as f64,
+                }
+            },
+        }
+    }
+}
+
+/// Get the set of k-mers in a sequence, ignoring case.
+pub(crate) fn kmers(sequence: &[u8], k: usize) -> HashSet<Vec<u8>> {
+    match k {
+        0 => HashSet::new(),
+        _ => sequence.windows(k).map(|w| w.to_ascii_uppercase()).collect(),
+    }
+}
+
+/// Symmetric matrix of pairwise distances between labeled items.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceMatrix {
+    labels: Vec<String>,
+    values: Vec<f64>,
+}
+
+impl DistanceMatrix {
+    /// Create new matrix of zero distances between the labeled items.
+    #[inline]
+    pub fn new(labels: Vec<String>) -> Self {
+        let n = labels.len();
+        DistanceMatrix {
+            labels: labels,
+            values: vec![0.0; n * n],
+        }
+    }
+
+    /// Calculate the distance matrix between labeled sequences.
+    pub fn from_sequences<'a, Iter>(sequences: Iter, method: DistanceMethod) -> Self
+        where Iter: IntoIterator<Item = (&'a str, &'a [u8])>
+    {
+        let (labels, sequences): (Vec<&str>, Vec<&[u8]>) = sequences.into_iter().unzip();
+        let mut matrix = DistanceMatrix::new(labels.into_iter().map(String::from).collect());
+        for i in 0..sequences.len() {
+            for j in i+1..sequences.len() {
+                matrix.set(i, j, method.distance(sequences[i], sequences[j]));
+            }
+        }
+        matrix
+    }
+
+    /// Get the number of items.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Check if the matrix has no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Get the labels of the items.
+    #[inline]
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Get the distance between two items.
+    #[inline]
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        self.values[i * self.len() + j]
+    }
+
+    /// Set the distance between two items, symmetrically.
+    #[inline]
+    pub fn set(&mut self, i: usize, j: usize, distance: f64) {
+        let n = self.len();
+        self.values[i * n + j] = distance;
+        self.values[j * n + i] = distance;
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_method_test() {
+        let identity = DistanceMethod::Identity(AlignmentScoring::default());
+        assert_eq!(identity.distance(b"GATTACA", b"gattaca"), 0.0);
+        assert_approx_eq!(identity.distance(b"MKWVTFISLL", b"MKWVTLLL"), 0.3, 1e-12);
+
+        let kmer = DistanceMethod::Kmer(3);
+        assert_eq!(kmer.distance(b"GATTACA", b"GATTACA"), 0.0);
+        // {GAT, ATT, TTA, TAC, ACA} and {GAT, ATT, TTA, TAG}.
+        assert_approx_eq!(kmer.distance(b"GATTACA", b"GATTAG"), 1.0 - 3.0 / 6.0, 1e-12);
+        assert_eq!(kmer.distance(b"GA", b"GA"), 1.0);
+    }
+
+    #[test]
+    fn distance_matrix_test() {
+        let sequences = vec![("x", &b"GATTACA"[..]), ("y", &b"GATTAG"[..]), ("z", &b"GATTACA"[..])];
+        let matrix = DistanceMatrix::from_sequences(sequences, DistanceMethod::Kmer(3));
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix.labels(), &[String::from("x"), String::from("y"), String::from("z")]);
+        assert_eq!(matrix.get(0, 0), 0.0);
+        assert_eq!(matrix.get(0, 1), matrix.get(1, 0));
+        assert_approx_eq!(matrix.get(1, 2), 0.5, 1e-12);
+        assert_eq!(matrix.get(0, 2), 0.0);
+    }
+}
@@ -1,4 +0,0 @@
-//! General purpose DNA routines.
-
-/// Valid nucleotide 1-letter codes.
-pub const MONOMERS: &'static str = "ACGT";
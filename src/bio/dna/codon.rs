@@ -0,0 +1,330 @@
+//! Codon usage tables and codon optimization.
+//!
+//! Codon usage is stored as relative frequencies for each of the 64
+//! codons, in "TCAG" order, for example, per thousand codons. Bundled
+//! tables are from the Codon Usage Database (Kazusa).
+
+use util::{ErrorKind, Result};
+use super::{codon_index, GeneticCode};
+use super::restriction::RestrictionEnzyme;
+
+/// Nucleotides in the "TCAG" order of the codon tables.
+const TCAG: &'static [u8] = b"TCAG";
+
+/// Escherichia coli K-12 codon usage, per thousand codons.
+const ECOLI_USAGE: [f64; 64] = [
+    22.1, 16.0, 14.3, 13.0, 10.4, 9.1, 8.9, 8.5, 17.5, 12.2, 2.0, 0.3, 5.2, 6.1, 1.0, 13.9,
+    11.9, 10.2, 4.2, 48.4, 7.5, 5.4, 8.6, 20.9, 12.5, 9.3, 14.6, 28.4, 20.0, 19.7, 3.8, 5.9,
+    29.8, 23.7, 6.8, 26.4, 10.3, 22.0, 9.3, 13.7, 20.6, 21.4, 35.3, 12.4, 9.9, 15.2, 3.6, 2.1,
+    21.6, 13.1, 11.5, 23.7, 18.9, 23.7, 21.1, 30.5, 37.9, 20.5, 43.7, 18.4, 21.3, 27.1, 9.5, 11.3,
+];
+
+/// Saccharomyces cerevisiae codon usage, per thousand codons.
+const YEAST_USAGE: [f64; 64] = [
+    26.1, 18.4, 26.2, 27.2, 23.5, 14.2, 18.7, 8.6, 18.8, 14.8, 1.1, 0.5, 8.1, 4.8, 0.7, 10.4,
+    12.3, 5.4, 13.4, 10.5, 13.5, 6.8, 18.3, 5.3, 13.6, 7.8, 27.3, 12.1, 6.4, 2.6, 3.0, 1.7,
+    30.1, 17.2, 17.8, 20.9, 20.3, 12.7, 17.8, 8.0, 35.7, 24.8, 41.9, 30.8, 14.2, 9.8, 21.3, 9.2,
+    22.1, 11.8, 11.8, 10.8, 21.2, 12.6, 16.2, 6.2, 37.6, 20.2, 45.6, 19.2, 23.9, 9.8, 10.9, 6.0,
+];
+
+/// Homo sapiens codon usage, per thousand codons.
+const HUMAN_USAGE: [f64; 64] = [
+    17.6, 20.3, 7.7, 12.9, 15.2, 17.7, 12.2, 4.4, 12.2, 15.3, 1.0, 0.8, 10.6, 12.6, 1.6, 13.2,
+    13.2, 19.6, 7.2, 39.6, 17.5, 19.8, 16.9, 6.9, 10.9, 15.1, 12.3, 34.2, 4.5, 10.4, 6.2, 11.4,
+    16.0, 20.8, 7.5, 22.0, 13.1, 18.9, 15.1, 6.1, 17.0, 19.1, 24.4, 31.9, 12.1, 19.5, 12.2, 12.0,
+    11.0, 14.5, 7.1, 28.1, 18.4, 27.7, 15.8, 7.4, 21.8, 25.1, 29.0, 39.6, 10.8, 22.2, 16.5, 16.5,
+];
+
+/// Get the codon for an index in "TCAG" order.
+#[inline]
+fn codon(index: usize) -> [u8; 3] {
+    [TCAG[index / 16], TCAG[index / 4 % 4], TCAG[index % 4]]
+}
+
+/// Get the index of a codon in "TCAG" order, or `None` for ambiguous codons.
+#[inline]
+fn index(codon: &[u8]) -> Option<usize> {
+    if codon.len() != 3 {
+        return None;
+    }
+    match (codon_index(codon[0]), codon_index(codon[1]), codon_index(codon[2])) {
+        (Some(i), Some(j), Some(k)) => Some(16*i + 4*j + k),
+        _                           => None,
+    }
+}
+
+/// Relative codon frequencies of an expression host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodonUsage {
+    frequencies: [f64; 64],
+}
+
+impl CodonUsage {
+    /// Create codon usage from the frequencies in "TCAG" order.
+    #[inline]
+    pub fn new(frequencies: [f64; 64]) -> Self {
+        CodonUsage { frequencies: frequencies }
+    }
+
+    /// Codon usage for Escherichia coli K-12.
+    #[inline]
+    pub fn ecoli() -> Self {
+        CodonUsage::new(ECOLI_USAGE)
+    }
+
+    /// Codon usage for Saccharomyces cerevisiae.
+    #[inline]
+    pub fn yeast() -> Self {
+        CodonUsage::new(YEAST_USAGE)
+    }
+
+    /// Codon usage for Homo sapiens.
+    #[inline]
+    pub fn human() -> Self {
+        CodonUsage::new(HUMAN_USAGE)
+    }
+
+    /// Parse codon usage from a table of codons and frequencies.
+    ///
+    /// Each codon is followed by its frequency, separated by whitespace,
+    /// as in the Codon Usage Database, for example, "UUU 17.6(714298)".
+    /// Counts in parentheses are ignored, and every codon is required.
+    /// Frequencies must be finite and non-negative.
+    pub fn from_table(text: &str) -> Result<Self> {
+        let mut frequencies = [0.0; 64];
+        let mut found = [false; 64];
+        let mut tokens = text.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let i = none_to_error!(index(token.as_bytes()), InvalidInput);
+            let value = none_to_error!(tokens.next(), InvalidInput);
+            let value = match value.find('(') {
+                Some(end) => &value[..end],
+                None      => value,
+            };
+            let value = value.parse::<f64>()?;
+            bool_to_error!(value.is_finite() && value >= 0.0, InvalidInput);
+            frequencies[i] = value;
+            found[i] = true;
+        }
+        bool_to_error!(found.iter().all(|&x| x), InvalidInput);
+
+        Ok(CodonUsage::new(frequencies))
+    }
+
+    /// Count the codon usage of coding sequences from the host.
+    ///
+    /// Sequences are read in frame from the first nucleotide, and
+    /// ambiguous codons are ignored.
+    pub fn from_coding_sequences<'a, Iter>(sequences: Iter) -> Self
+        where Iter: IntoIterator<Item = &'a [u8]>
+    {
+        let mut frequencies = [0.0; 64];
+        for sequence in sequences {
+            for i in sequence.chunks(3).filter_map(index) {
+                frequencies[i] += 1.0;
+            }
+        }
+        CodonUsage::new(frequencies)
+    }
+
+    /// Get the frequency of a codon, or 0 for ambiguous codons.
+    #[inline]
+    pub fn frequency(&self, codon: &[u8]) -> f64 {
+        match index(codon) {
+            Some(i) => self.frequencies[i],
+            None    => 0.0,
+        }
+    }
+
+    /// Get the codons encoding an aminoacid, by decreasing frequency.
+    pub fn synonymous_codons(&self, aminoacid: u8, code: GeneticCode) -> Vec<[u8; 3]> {
+        let aminoacid = aminoacid.to_ascii_uppercase();
+        let mut indexes: Vec<usize> = (0..64)
+            .filter(|&i| code.translate_codon(&codon(i)) == aminoacid)
+            .collect();
+        indexes.sort_by(|&i, &j| self.frequencies[j].total_cmp(&self.frequencies[i]));
+        indexes.into_iter().map(codon).collect()
+    }
+
+    /// Get the frequency of a codon relative to the most frequent
+    /// synonymous codon, from 0 to 1.
+    pub fn relative_adaptiveness(&self, codon: &[u8], code: GeneticCode) -> f64 {
+        let best = match self.synonymous_codons(code.translate_codon(codon), code).first() {
+            Some(best) => self.frequency(best),
+            None       => return 0.0,
+        };
+        match best > 0.0 {
+            true  => self.frequency(codon) / best,
+            false => 0.0,
+        }
+    }
+
+    /// Calculate the codon adaptation index (CAI) of a coding sequence.
+    ///
+    /// The CAI is the geometric mean of the relative adaptiveness of
+    /// the codons, excluding stop codons, ambiguous codons, and
+    /// aminoacids with a single codon. Returns 0 without such codons.
+    pub fn codon_adaptation_index(&self, sequence: &[u8], code: GeneticCode) -> f64 {
+        let (mut sum, mut count) = (0.0, 0);
+        for codon in sequence.chunks(3) {
+            let aminoacid = code.translate_codon(codon);
+            if aminoacid == b'X' || aminoacid == b'*' || self.synonymous_codons(aminoacid, code).len() < 2 {
+                continue;
+            }
+            sum += self.relative_adaptiveness(codon, code).ln();
+            count += 1;
+        }
+        match count {
+            0 => 0.0,
+            n => (sum / n as f64).exp(),
+        }
+    }
+}
+
+/// Options for codon optimization.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodonOptions {
+    /// Genetic code of the expression host.
+    pub code: GeneticCode,
+    /// Restriction enzymes with recognition sites to avoid, for example,
+    /// the enzymes used for cloning.
+    pub avoid: Vec<&'static RestrictionEnzyme>,
+}
+
+impl Default for CodonOptions {
+    #[inline]
+    fn default() -> Self {
+        CodonOptions {
+            code: GeneticCode::Standard,
+            avoid: vec![],
+        }
+    }
+}
+
+/// Determine if the end of the sequence contains a site for any enzyme.
+fn has_site(sequence: &[u8], window: usize, enzymes: &[&'static RestrictionEnzyme]) -> bool {
+    enzymes.iter().any(|e| {
+        let start = sequence.len().saturating_sub(window + e.site.len() - 1);
+        !e.sites(&sequence[start..], false).is_empty()
+    })
+}
+
+/// Back-translate a protein into an optimized coding sequence.
+///
+/// Each aminoacid uses the most frequent synonymous codon in the host
+/// which does not create a recognition site for the avoided enzymes.
+/// If every codon creates a site, the most frequent codon is used.
+/// Stop codons ("*") are back-translated, but not added. Returns an
+/// error for aminoacids not encoded by the genetic code.
+pub fn back_translate(protein: &[u8], usage: &CodonUsage, options: &CodonOptions) -> Result<Vec<u8>> {
+    let mut sequence = Vec::with_capacity(3 * protein.len());
+    for &aminoacid in protein {
+        let codons = usage.synonymous_codons(aminoacid, options.code);
+        bool_to_error!(!codons.is_empty(), InvalidInput);
+        let length = sequence.len();
+        let mut found = false;
+        for codon in codons.iter() {
+            sequence.extend_from_slice(codon);
+            if !has_site(&sequence, 3, &options.avoid) {
+                found = true;
+                break;
+            }
+            sequence.truncate(length);
+        }
+        if !found {
+            sequence.extend_from_slice(&codons[0]);
+        }
+    }
+    Ok(sequence)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::restriction::find_enzyme;
+
+    #[test]
+    fn codon_index_test() {
+        for i in 0..64 {
+            assert_eq!(index(&codon(i)), Some(i));
+        }
+        assert_eq!(&codon(35), b"ATG");
+        assert_eq!(index(b"AUG"), Some(35));
+        assert_eq!(index(b"ANG"), None);
+        assert_eq!(index(b"AT"), None);
+    }
+
+    #[test]
+    fn codon_usage_test() {
+        let usage = CodonUsage::human();
+        assert_eq!(usage.frequency(b"CTG"), 39.6);
+        assert_eq!(usage.frequency(b"ctg"), 39.6);
+        assert_eq!(usage.synonymous_codons(b'K', GeneticCode::Standard), vec![*b"AAG", *b"AAA"]);
+        assert_eq!(usage.synonymous_codons(b'M', GeneticCode::Standard), vec![*b"ATG"]);
+        assert_eq!(usage.synonymous_codons(b'*', GeneticCode::Standard)[0], *b"TGA");
+        assert!(usage.synonymous_codons(b'X', GeneticCode::Standard).is_empty());
+        assert_approx_eq!(usage.relative_adaptiveness(b"AAA", GeneticCode::Standard), 24.4 / 31.9, 1e-12);
+
+        assert_eq!(CodonUsage::ecoli().synonymous_codons(b'R', GeneticCode::Standard)[0], *b"CGT");
+        assert_eq!(CodonUsage::yeast().synonymous_codons(b'R', GeneticCode::Standard)[0], *b"AGA");
+        assert_eq!(CodonUsage::human().synonymous_codons(b'R', GeneticCode::Standard)[0], *b"AGA");
+    }
+
+    #[test]
+    fn from_table_test() {
+        let text: String = (0..64)
+            .map(|i| format!("{} {}({})\n", String::from_utf8(codon(i).to_vec()).unwrap().replace("T", "U"), i, 10 * i))
+            .collect();
+        let usage = CodonUsage::from_table(&text).unwrap();
+        assert_eq!(usage.frequency(b"GGG"), 63.0);
+        assert_eq!(usage.frequency(b"TTT"), 0.0);
+
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines.pop();
+        assert!(CodonUsage::from_table(&lines.join("\n")).is_err());
+        assert!(CodonUsage::from_table("UUU").is_err());
+        assert!(CodonUsage::from_table("UUU x").is_err());
+        assert!(CodonUsage::from_table(&text.replacen("UUU 0(0)", "UUU NaN(0)", 1)).is_err());
+        assert!(CodonUsage::from_table(&text.replacen("UUU 0(0)", "UUU -1(0)", 1)).is_err());
+        assert!(CodonUsage::from_table(&text.replacen("UUU 0(0)", "UUU inf(0)", 1)).is_err());
+
+        // NaN frequencies from other sources do not panic
+        let mut frequencies = [1.0; 64];
+        frequencies[0] = ::std::f64::NAN;
+        assert_eq!(CodonUsage::new(frequencies).synonymous_codons(b'F', GeneticCode::Standard).len(), 2);
+    }
+
+    #[test]
+    fn from_coding_sequences_test() {
+        let sequences = vec![&b"ATGAAAAAGTAA"[..], &b"ATGAAANNNAA"[..]];
+        let usage = CodonUsage::from_coding_sequences(sequences);
+        assert_eq!(usage.frequency(b"ATG"), 2.0);
+        assert_eq!(usage.frequency(b"AAA"), 2.0);
+        assert_eq!(usage.frequency(b"AAG"), 1.0);
+        assert_eq!(usage.frequency(b"TAA"), 1.0);
+        assert_approx_eq!(usage.codon_adaptation_index(b"ATGAAAAAG", GeneticCode::Standard), 0.5f64.sqrt(), 1e-12);
+        assert_eq!(usage.codon_adaptation_index(b"ATG", GeneticCode::Standard), 0.0);
+    }
+
+    #[test]
+    fn back_translate_test() {
+        let usage = CodonUsage::yeast();
+        let options = CodonOptions::default();
+        let sequence = back_translate(b"MNS*", &usage, &options).unwrap();
+        assert_eq!(sequence, b"ATGAATTCTTAA".to_vec());
+        assert_eq!(GeneticCode::Standard.translate(&sequence), b"MNS*".to_vec());
+        assert_eq!(usage.codon_adaptation_index(&sequence, GeneticCode::Standard), 1.0);
+
+        // Avoid the EcoRI site.
+        let options = CodonOptions { avoid: vec![find_enzyme("EcoRI").unwrap()], ..CodonOptions::default() };
+        let sequence = back_translate(b"MNS*", &usage, &options).unwrap();
+        assert_eq!(sequence, b"ATGAATAGTTAA".to_vec());
+        assert_eq!(GeneticCode::Standard.translate(&sequence), b"MNS*".to_vec());
+
+        assert!(back_translate(b"MXK", &usage, &options).is_err());
+        assert!(back_translate(b"", &usage, &options).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+//! General purpose DNA routines.
+
+use util::{ErrorKind, Result};
+
+pub mod codon;
+pub mod primer;
+pub mod restriction;
+
+/// Valid nucleotide 1-letter codes.
+pub const MONOMERS: &'static str = "ACGT";
+
+/// Aminoacids encoded by the standard genetic code.
+///
+/// Codons are ordered by the "TCAG" index of each nucleotide, with
+/// "*" for stop codons.
+const STANDARD_CODE: &'static [u8] = b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// Aminoacids encoded by the vertebrate mitochondrial code.
+const VERTEBRATE_MITOCHONDRIAL_CODE: &'static [u8] = b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG";
+
+/// Aminoacids encoded by the yeast mitochondrial code.
+const YEAST_MITOCHONDRIAL_CODE: &'static [u8] = b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// Aminoacids encoded by the mold, protozoan and coelenterate mitochondrial code.
+const MOLD_MITOCHONDRIAL_CODE: &'static [u8] = b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// Aminoacids encoded by the invertebrate mitochondrial code.
+const INVERTEBRATE_MITOCHONDRIAL_CODE: &'static [u8] = b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG";
+
+/// Aminoacids encoded by the ciliate, dasycladacean and hexamita nuclear code.
+const CILIATE_NUCLEAR_CODE: &'static [u8] = b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// Genetic code used to translate codons to aminoacids.
+///
+/// The discriminants are the NCBI translation table identifiers.
+/// The bacterial, archaeal and plant plastid code only differs from
+/// the standard code by the alternative start codons.
+///
+/// More documentation can be found [`here`].
+///
+/// [`here`]: https://www.ncbi.nlm.nih.gov/Taxonomy/Utils/wprintgc.cgi
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum GeneticCode {
+    /// Standard nuclear code.
+    Standard = 1,
+    /// Vertebrate mitochondrial code.
+    VertebrateMitochondrial = 2,
+    /// Yeast mitochondrial code.
+    YeastMitochondrial = 3,
+    /// Mold, protozoan and coelenterate mitochondrial code.
+    MoldMitochondrial = 4,
+    /// Invertebrate mitochondrial code.
+    InvertebrateMitochondrial = 5,
+    /// Ciliate, dasycladacean and hexamita nuclear code.
+    CiliateNuclear = 6,
+    /// Bacterial, archaeal and plant plastid code.
+    Bacterial = 11,
+}
+
+impl GeneticCode {
+    /// Create enumerated value from the NCBI translation table identifier.
+    pub fn from_table_id(id: u8) -> Result<Self> {
+        match id {
+            1  => Ok(GeneticCode::Standard),
+            2  => Ok(GeneticCode::VertebrateMitochondrial),
+            3  => Ok(GeneticCode::YeastMitochondrial),
+            4  => Ok(GeneticCode::MoldMitochondrial),
+            5  => Ok(GeneticCode::InvertebrateMitochondrial),
+            6  => Ok(GeneticCode::CiliateNuclear),
+            11 => Ok(GeneticCode::Bacterial),
+            _  => Err(From::from(ErrorKind::InvalidEnumeration)),
+        }
+    }
+
+    /// Get the NCBI translation table identifier.
+    #[inline]
+    pub fn table_id(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Get the aminoacids encoded by each codon, in "TCAG" order.
+    #[inline]
+    fn table(&self) -> &'static [u8] {
+        match *self {
+            GeneticCode::Standard                   => STANDARD_CODE,
+            GeneticCode::VertebrateMitochondrial    => VERTEBRATE_MITOCHONDRIAL_CODE,
+            GeneticCode::YeastMitochondrial         => YEAST_MITOCHONDRIAL_CODE,
+            GeneticCode::MoldMitochondrial          => MOLD_MITOCHONDRIAL_CODE,
+            GeneticCode::InvertebrateMitochondrial  => INVERTEBRATE_MITOCHONDRIAL_CODE,
+            GeneticCode::CiliateNuclear             => CILIATE_NUCLEAR_CODE,
+            GeneticCode::Bacterial                  => STANDARD_CODE,
+        }
+    }
+
+    /// Translate a codon to an aminoacid.
+    ///
+    /// Stop codons are translated to "*", and codons with ambiguous
+    /// nucleotides are translated to "X".
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        if codon.len() != 3 {
+            return b'X';
+        }
+
+        match (codon_index(codon[0]), codon_index(codon[1]), codon_index(codon[2])) {
+            (Some(i), Some(j), Some(k)) => self.table()[16*i + 4*j + k],
+            _                           => b'X',
+        }
+    }
+
+    /// Translate a nucleotide sequence.
+    ///
+    /// Translation starts at the first nucleotide, and trailing nucleotides
+    /// which do not form a complete codon are ignored.
+    #[inline]
+    pub fn translate(&self, sequence: &[u8]) -> Vec<u8> {
+        sequence.chunks(3)
+            .filter(|codon| codon.len() == 3)
+            .map(|codon| self.translate_codon(codon))
+            .collect()
+    }
+}
+
+impl Default for GeneticCode {
+    #[inline]
+    fn default() -> Self {
+        GeneticCode::Standard
+    }
+}
+
+/// Get the complement of a nucleotide, including IUPAC ambiguity codes.
+///
+/// The case of the nucleotide is preserved, and unknown nucleotides
+/// are returned unchanged. Uracil is complemented to adenine.
+pub fn complement(nucleotide: u8) -> u8 {
+    let complement = match nucleotide.to_ascii_uppercase() {
+        b'A'        => b'T',
+        b'C'        => b'G',
+        b'G'        => b'C',
+        b'T' | b'U' => b'A',
+        b'R'        => b'Y',
+        b'Y'        => b'R',
+        b'K'        => b'M',
+        b'M'        => b'K',
+        b'B'        => b'V',
+        b'V'        => b'B',
+        b'D'        => b'H',
+        b'H'        => b'D',
+        _           => return nucleotide,
+    };
+
+    if nucleotide.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
+}
+
+/// Get the reverse complement of a nucleotide sequence.
+#[inline]
+pub fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|&c| complement(c)).collect()
+}
+
+/// Get the "TCAG" index of a nucleotide, treating uracil as thymine.
+#[inline]
+fn codon_index(nucleotide: u8) -> Option<usize> {
+    match nucleotide.to_ascii_uppercase() {
+        b'T' | b'U' => Some(0),
+        b'C'        => Some(1),
+        b'A'        => Some(2),
+        b'G'        => Some(3),
+        _           => None,
+    }
+}
+
+/// Translate a codon to an aminoacid using the standard genetic code.
+///
+/// Stop codons are translated to "*", and codons with ambiguous
+/// nucleotides are translated to "X".
+#[inline]
+pub fn translate_codon(codon: &[u8]) -> u8 {
+    GeneticCode::Standard.translate_codon(codon)
+}
+
+/// Translate a nucleotide sequence using the standard genetic code.
+///
+/// Translation starts at the first nucleotide, and trailing nucleotides
+/// which do not form a complete codon are ignored.
+#[inline]
+pub fn translate(sequence: &[u8]) -> Vec<u8> {
+    GeneticCode::Standard.translate(sequence)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_complement_test() {
+        assert_eq!(complement(b'A'), b'T');
+        assert_eq!(complement(b'u'), b'a');
+        assert_eq!(complement(b'R'), b'Y');
+        assert_eq!(complement(b'N'), b'N');
+        assert_eq!(complement(b'-'), b'-');
+        assert_eq!(reverse_complement(b"CATTAGnRY"), b"RYnCTAATG".to_vec());
+        assert_eq!(reverse_complement(b""), b"".to_vec());
+    }
+
+    #[test]
+    fn translate_test() {
+        assert_eq!(translate_codon(b"ATG"), b'M');
+        assert_eq!(translate_codon(b"uaa"), b'*');
+        assert_eq!(translate_codon(b"GGN"), b'X');
+        assert_eq!(translate_codon(b"GG"), b'X');
+        assert_eq!(translate(b"ATGGTGAAGGTCGGTTAAGC"), b"MVKVG*".to_vec());
+        assert_eq!(translate(b"AT"), b"".to_vec());
+    }
+
+    #[test]
+    fn genetic_code_test() {
+        let code = GeneticCode::from_table_id(2).unwrap();
+        assert_eq!(code, GeneticCode::VertebrateMitochondrial);
+        assert_eq!(code.table_id(), 2);
+        assert_eq!(code.translate(b"ATGTGAATAAGA"), b"MWM*".to_vec());
+        assert_eq!(GeneticCode::Standard.translate(b"ATGTGAATAAGA"), b"M*IR".to_vec());
+        assert_eq!(GeneticCode::YeastMitochondrial.translate_codon(b"CTG"), b'T');
+        assert_eq!(GeneticCode::CiliateNuclear.translate_codon(b"TAA"), b'Q');
+        assert_eq!(GeneticCode::Bacterial.translate(b"ATGTGA"), b"M*".to_vec());
+        assert!(GeneticCode::from_table_id(7).is_err());
+        assert_eq!(GeneticCode::default(), GeneticCode::Standard);
+    }
+}
@@ -0,0 +1,326 @@
+//! PCR primer design and melting temperature calculation.
+//!
+//! Melting temperatures use the unified nearest-neighbor parameters
+//! of SantaLucia (1998), with the entropy salt correction for
+//! monovalent cations, assuming primers in excess of their target.
+
+use util::{ErrorKind, Result};
+use super::reverse_complement;
+
+/// Gas constant, in cal/(K mol).
+const GAS_CONSTANT: f64 = 1.9872;
+
+/// Get the nearest-neighbor enthalpy (kcal/mol) and entropy (cal/(K mol))
+/// of a dinucleotide, or `None` for ambiguous nucleotides.
+fn nearest_neighbor(x: u8, y: u8) -> Option<(f64, f64)> {
+    let pair = [x.to_ascii_uppercase(), y.to_ascii_uppercase()];
+    let parameters = match &pair {
+        b"AA" | b"TT" => (-7.9, -22.2),
+        b"AT"         => (-7.2, -20.4),
+        b"TA"         => (-7.2, -21.3),
+        b"CA" | b"TG" => (-8.5, -22.7),
+        b"GT" | b"AC" => (-8.4, -22.4),
+        b"CT" | b"AG" => (-7.8, -21.0),
+        b"GA" | b"TC" => (-8.2, -22.2),
+        b"CG"         => (-10.6, -27.2),
+        b"GC"         => (-9.8, -24.4),
+        b"GG" | b"CC" => (-8.0, -19.9),
+        _             => return None,
+    };
+    Some(parameters)
+}
+
+/// Get the initiation enthalpy and entropy for a terminal nucleotide.
+#[inline]
+fn initiation(nucleotide: u8) -> (f64, f64) {
+    match is_gc(nucleotide) {
+        true  => (0.1, -2.8),
+        false => (2.3, 4.1),
+    }
+}
+
+/// Determine if a nucleotide is guanine or cytosine.
+#[inline]
+fn is_gc(nucleotide: u8) -> bool {
+    let nucleotide = nucleotide.to_ascii_uppercase();
+    nucleotide == b'G' || nucleotide == b'C'
+}
+
+/// Reaction conditions for melting temperature calculations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PcrConditions {
+    /// Primer concentration, in nM.
+    pub primer_concentration: f64,
+    /// Monovalent cation (Na+, K+) concentration, in mM.
+    pub salt_concentration: f64,
+}
+
+impl Default for PcrConditions {
+    #[inline]
+    fn default() -> Self {
+        PcrConditions {
+            primer_concentration: 50.0,
+            salt_concentration: 50.0,
+        }
+    }
+}
+
+/// Calculate the melting temperature of a primer, in degrees Celsius.
+///
+/// Returns an error for primers shorter than 2 nucleotides, or with
+/// nucleotides other than "ACGT".
+pub fn melting_temperature(primer: &[u8], conditions: &PcrConditions) -> Result<f64> {
+    bool_to_error!(primer.len() >= 2, InvalidInput);
+    let (mut enthalpy, mut entropy) = (0.0, 0.0);
+    for window in primer.windows(2) {
+        let (h, s) = none_to_error!(nearest_neighbor(window[0], window[1]), InvalidInput);
+        enthalpy += h;
+        entropy += s;
+    }
+    for &nucleotide in [primer[0], primer[primer.len() - 1]].iter() {
+        let (h, s) = initiation(nucleotide);
+        enthalpy += h;
+        entropy += s;
+    }
+    if primer.eq_ignore_ascii_case(&reverse_complement(primer)) {
+        entropy -= 1.4;
+    }
+
+    let salt = conditions.salt_concentration / 1000.0;
+    entropy += 0.368 * (primer.len() - 1) as f64 * salt.ln();
+    let primer_concentration = conditions.primer_concentration * 1e-9;
+    Ok(enthalpy * 1000.0 / (entropy + GAS_CONSTANT * primer_concentration.ln()) - 273.15)
+}
+
+/// Calculate the fraction of guanine and cytosine in a sequence.
+#[inline]
+pub fn gc_content(sequence: &[u8]) -> f64 {
+    match sequence.len() {
+        0 => 0.0,
+        n => sequence.iter().filter(|&&c| is_gc(c)).count() as f64 / n as f64,
+    }
+}
+
+/// Determine if a primer has a GC clamp at the 3' end.
+///
+/// The last nucleotide must be G or C, with at most 3 G or C in the
+/// last 5 nucleotides, to stabilize binding without mispriming.
+pub fn has_gc_clamp(primer: &[u8]) -> bool {
+    let tail = &primer[primer.len().saturating_sub(5)..];
+    let gc = tail.iter().filter(|&&c| is_gc(c)).count();
+    match primer.last() {
+        Some(&c) => is_gc(c) && gc <= 3,
+        None     => false,
+    }
+}
+
+/// Options for primer pair search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrimerOptions {
+    /// Minimum primer length.
+    pub min_length: usize,
+    /// Maximum primer length.
+    pub max_length: usize,
+    /// Minimum primer melting temperature, in degrees Celsius.
+    pub min_tm: f64,
+    /// Maximum primer melting temperature, in degrees Celsius.
+    pub max_tm: f64,
+    /// Maximum melting temperature difference within a pair.
+    pub max_tm_difference: f64,
+    /// Minimum fraction of G and C in a primer.
+    pub min_gc: f64,
+    /// Maximum fraction of G and C in a primer.
+    pub max_gc: f64,
+    /// Require a GC clamp at the 3' end of the primers.
+    pub gc_clamp: bool,
+    /// Minimum length of the amplified product.
+    pub min_product: usize,
+    /// Maximum length of the amplified product.
+    pub max_product: usize,
+    /// Reaction conditions for the melting temperatures.
+    pub conditions: PcrConditions,
+}
+
+impl Default for PrimerOptions {
+    #[inline]
+    fn default() -> Self {
+        PrimerOptions {
+            min_length: 18,
+            max_length: 25,
+            min_tm: 55.0,
+            max_tm: 65.0,
+            max_tm_difference: 3.0,
+            min_gc: 0.4,
+            max_gc: 0.6,
+            gc_clamp: true,
+            min_product: 100,
+            max_product: 1000,
+            conditions: PcrConditions::default(),
+        }
+    }
+}
+
+/// Primer binding to a template sequence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Primer {
+    /// Primer sequence, 5' to 3'.
+    pub sequence: Vec<u8>,
+    /// Index of the first nucleotide of the binding region on the
+    /// forward strand of the template.
+    pub start: usize,
+    /// Melting temperature, in degrees Celsius.
+    pub tm: f64,
+    /// Fraction of G and C.
+    pub gc: f64,
+}
+
+/// Forward and reverse primers amplifying a template region.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrimerPair {
+    /// Primer binding to the reverse strand, extending along the forward strand.
+    pub forward: Primer,
+    /// Primer binding to the forward strand, extending along the reverse strand.
+    pub reverse: Primer,
+    /// Length of the amplified product, including the primers.
+    pub product_length: usize,
+}
+
+impl PrimerPair {
+    /// Get the melting temperature difference between the primers.
+    #[inline]
+    pub fn tm_difference(&self) -> f64 {
+        (self.forward.tm - self.reverse.tm).abs()
+    }
+}
+
+/// Create a primer if the sequence satisfies the options.
+fn primer(sequence: Vec<u8>, start: usize, options: &PrimerOptions) -> Option<Primer> {
+    let gc = gc_content(&sequence);
+    if gc < options.min_gc || gc > options.max_gc {
+        return None;
+    }
+    if options.gc_clamp && !has_gc_clamp(&sequence) {
+        return None;
+    }
+    let tm = match melting_temperature(&sequence, &options.conditions) {
+        Ok(tm) if tm >= options.min_tm && tm <= options.max_tm => tm,
+        _ => return None,
+    };
+    Some(Primer { sequence: sequence, start: start, tm: tm, gc: gc })
+}
+
+/// Find the candidate primers on one strand of the template.
+fn candidates(template: &[u8], options: &PrimerOptions, forward: bool) -> Vec<Primer> {
+    let mut primers = vec![];
+    for length in options.min_length..options.max_length+1 {
+        for start in 0..(template.len() + 1).saturating_sub(length) {
+            let region = &template[start..start+length];
+            let sequence = match forward {
+                true  => region.to_vec(),
+                false => reverse_complement(region),
+            };
+            primers.extend(primer(sequence, start, options));
+        }
+    }
+    primers
+}
+
+/// Find primer pairs to amplify a template.
+///
+/// Pairs are ordered by melting temperature difference, then by
+/// product length.
+pub fn find_primer_pairs(template: &[u8], options: &PrimerOptions) -> Vec<PrimerPair> {
+    let forward = candidates(template, options, true);
+    let reverse = candidates(template, options, false);
+    let mut pairs = vec![];
+    for f in forward.iter() {
+        for r in reverse.iter() {
+            let end = r.start + r.sequence.len();
+            if r.start < f.start + f.sequence.len() {
+                continue;
+            }
+            let product_length = end - f.start;
+            if product_length < options.min_product || product_length > options.max_product {
+                continue;
+            }
+            if !((f.tm - r.tm).abs() <= options.max_tm_difference) {
+                continue;
+            }
+            pairs.push(PrimerPair {
+                forward: f.clone(),
+                reverse: r.clone(),
+                product_length: product_length,
+            });
+        }
+    }
+    pairs.sort_by(|x, y| {
+        x.tm_difference().total_cmp(&y.tm_difference())
+            .then(x.product_length.cmp(&y.product_length))
+    });
+    pairs
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn melting_temperature_test() {
+        let conditions = PcrConditions::default();
+        assert_approx_eq!(melting_temperature(b"AGCGGATAACAATTTCACACAGGA", &conditions).unwrap(), 56.452, 0.001);
+        assert_approx_eq!(melting_temperature(b"gtaaaacgacggccagt", &conditions).unwrap(), 51.322, 0.001);
+        // Self-complementary.
+        assert_approx_eq!(melting_temperature(b"GAATTC", &conditions).unwrap(), -20.442, 0.001);
+        let salty = PcrConditions { salt_concentration: 200.0, ..conditions };
+        assert!(melting_temperature(b"GTAAAACGACGGCCAGT", &salty).unwrap() > melting_temperature(b"GTAAAACGACGGCCAGT", &conditions).unwrap());
+        assert!(melting_temperature(b"A", &conditions).is_err());
+        assert!(melting_temperature(b"GTAAANCG", &conditions).is_err());
+    }
+
+    #[test]
+    fn gc_test() {
+        assert_eq!(gc_content(b"GCAT"), 0.5);
+        assert_eq!(gc_content(b""), 0.0);
+        assert!(has_gc_clamp(b"AAAAATTAGC"));
+        assert!(!has_gc_clamp(b"AAAAAGCGCC"));
+        assert!(!has_gc_clamp(b"AAAAAGCGCA"));
+        assert!(has_gc_clamp(b"g"));
+        assert!(!has_gc_clamp(b""));
+    }
+
+    #[test]
+    fn find_primer_pairs_test() {
+        let template = b"ATGGGGAAGGTGAAGGTCGGAGTCAACGGATTTGGTCGTATTGGGCGCCTGGTCACCAGGGCTGCTTTTAACTCTGGTAAAGTGGATATTGTTGCCATCAATGACCCCTTCATTGACC";
+        let options = PrimerOptions { min_product: 60, ..PrimerOptions::default() };
+        let pairs = find_primer_pairs(template, &options);
+        assert_eq!(pairs.len(), 1319);
+        assert_eq!(pairs[0].forward.sequence, b"GAGTCAACGGATTTGGTCGTATTGG".to_vec());
+        assert_eq!(pairs[0].forward.start, 19);
+        assert_eq!(pairs[0].reverse.sequence, b"GGGGTCATTGATGGCAACAATATCC".to_vec());
+        assert_eq!(pairs[0].reverse.start, 83);
+        assert_eq!(pairs[0].product_length, 89);
+        assert!(pairs[0].tm_difference() < 0.001);
+        for pair in pairs.iter() {
+            let f = &pair.forward;
+            let r = &pair.reverse;
+            assert_eq!(&f.sequence[..], &template[f.start..f.start+f.sequence.len()]);
+            assert_eq!(reverse_complement(&r.sequence), template[r.start..r.start+r.sequence.len()].to_vec());
+            assert!(pair.tm_difference() <= 3.0);
+            assert!(has_gc_clamp(&f.sequence) && has_gc_clamp(&r.sequence));
+        }
+
+        let options = PrimerOptions { min_product: 200, ..PrimerOptions::default() };
+        assert!(find_primer_pairs(template, &options).is_empty());
+
+        // Invalid conditions produce NaN melting temperatures, which do not panic.
+        let mut options = PrimerOptions { min_product: 60, min_tm: ::std::f64::NEG_INFINITY, max_tm: ::std::f64::INFINITY, ..PrimerOptions::default() };
+        options.conditions.salt_concentration = -1.0;
+        assert!(find_primer_pairs(template, &options).is_empty());
+        options.conditions.salt_concentration = 0.0;
+        options.conditions.primer_concentration = 0.0;
+        find_primer_pairs(template, &options);
+    }
+}
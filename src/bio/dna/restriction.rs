@@ -0,0 +1,289 @@
+//! Restriction enzyme digestion of DNA sequences.
+//!
+//! Recognition sites may contain IUPAC ambiguity codes, and are
+//! matched on both strands. Cut positions are reported on the
+//! forward strand, as the index of the first nucleotide after the cut.
+
+use super::reverse_complement;
+
+/// Model for a restriction enzyme.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RestrictionEnzyme {
+    /// Enzyme name, for example, "EcoRI".
+    pub name: &'static str,
+    /// Recognition site on the forward strand, with IUPAC codes.
+    pub site: &'static [u8],
+    /// Cut offset on the forward strand, from the start of the site.
+    pub cut: usize,
+    /// Cut offset on the reverse strand, from the start of the site
+    /// on the forward strand.
+    pub complement_cut: usize,
+}
+
+/// Common commercial restriction enzymes.
+pub const RESTRICTION_ENZYMES: &'static [RestrictionEnzyme] = &[
+    RestrictionEnzyme { name: "AluI", site: b"AGCT", cut: 2, complement_cut: 2 },
+    RestrictionEnzyme { name: "AvaI", site: b"CYCGRG", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "BamHI", site: b"GGATCC", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "BglII", site: b"AGATCT", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "BsaI", site: b"GGTCTC", cut: 7, complement_cut: 11 },
+    RestrictionEnzyme { name: "DpnII", site: b"GATC", cut: 0, complement_cut: 4 },
+    RestrictionEnzyme { name: "EcoRI", site: b"GAATTC", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "EcoRV", site: b"GATATC", cut: 3, complement_cut: 3 },
+    RestrictionEnzyme { name: "HaeIII", site: b"GGCC", cut: 2, complement_cut: 2 },
+    RestrictionEnzyme { name: "HindIII", site: b"AAGCTT", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "HinfI", site: b"GANTC", cut: 1, complement_cut: 4 },
+    RestrictionEnzyme { name: "KpnI", site: b"GGTACC", cut: 5, complement_cut: 1 },
+    RestrictionEnzyme { name: "MspI", site: b"CCGG", cut: 1, complement_cut: 3 },
+    RestrictionEnzyme { name: "NcoI", site: b"CCATGG", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "NdeI", site: b"CATATG", cut: 2, complement_cut: 4 },
+    RestrictionEnzyme { name: "NotI", site: b"GCGGCCGC", cut: 2, complement_cut: 6 },
+    RestrictionEnzyme { name: "PstI", site: b"CTGCAG", cut: 5, complement_cut: 1 },
+    RestrictionEnzyme { name: "SacI", site: b"GAGCTC", cut: 5, complement_cut: 1 },
+    RestrictionEnzyme { name: "SalI", site: b"GTCGAC", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "SmaI", site: b"CCCGGG", cut: 3, complement_cut: 3 },
+    RestrictionEnzyme { name: "SpeI", site: b"ACTAGT", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "StyI", site: b"CCWWGG", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "TaqI", site: b"TCGA", cut: 1, complement_cut: 3 },
+    RestrictionEnzyme { name: "XbaI", site: b"TCTAGA", cut: 1, complement_cut: 5 },
+    RestrictionEnzyme { name: "XhoI", site: b"CTCGAG", cut: 1, complement_cut: 5 },
+];
+
+/// Find a common restriction enzyme by name, ignoring case.
+pub fn find_enzyme(name: &str) -> Option<&'static RestrictionEnzyme> {
+    RESTRICTION_ENZYMES.iter().find(|e| e.name.eq_ignore_ascii_case(name))
+}
+
+/// Determine if a nucleotide matches an IUPAC code.
+///
+/// Ambiguous nucleotides in the sequence only match "N".
+pub fn iupac_matches(code: u8, nucleotide: u8) -> bool {
+    let nucleotide = match nucleotide.to_ascii_uppercase() {
+        b'U' => b'T',
+        n    => n,
+    };
+    let matches: &[u8] = match code.to_ascii_uppercase() {
+        b'A'        => b"A",
+        b'C'        => b"C",
+        b'G'        => b"G",
+        b'T' | b'U' => b"T",
+        b'R'        => b"AG",
+        b'Y'        => b"CT",
+        b'S'        => b"CG",
+        b'W'        => b"AT",
+        b'K'        => b"GT",
+        b'M'        => b"AC",
+        b'B'        => b"CGT",
+        b'D'        => b"AGT",
+        b'H'        => b"ACT",
+        b'V'        => b"ACG",
+        b'N'        => return true,
+        _           => return false,
+    };
+    matches.contains(&nucleotide)
+}
+
+/// Determine if the pattern matches the start of the sequence.
+#[inline]
+fn matches_at(pattern: &[u8], sequence: &[u8]) -> bool {
+    pattern.len() <= sequence.len() &&
+        pattern.iter().zip(sequence.iter()).all(|(&c, &n)| iupac_matches(c, n))
+}
+
+impl RestrictionEnzyme {
+    /// Determine if the recognition site is its own reverse complement.
+    #[inline]
+    pub fn is_palindromic(&self) -> bool {
+        reverse_complement(self.site) == self.site
+    }
+
+    /// Find the start of each recognition site on either strand.
+    ///
+    /// Sites on the reverse strand are reported by the start of the
+    /// site on the forward strand. For circular sequences, sites may
+    /// span the origin.
+    pub fn sites(&self, sequence: &[u8], circular: bool) -> Vec<(usize, bool)> {
+        let length = sequence.len();
+        let mut search = sequence.to_vec();
+        if circular && length > 0 {
+            search.extend(sequence.iter().cycle().take(self.site.len() - 1));
+        }
+
+        let reverse = reverse_complement(self.site);
+        let palindromic = reverse == self.site;
+        let mut sites = vec![];
+        for start in 0..length {
+            if matches_at(self.site, &search[start..]) {
+                sites.push((start, true));
+            } else if !palindromic && matches_at(&reverse, &search[start..]) {
+                sites.push((start, false));
+            }
+        }
+        sites
+    }
+
+    /// Find the forward strand cut positions, sorted and deduplicated.
+    ///
+    /// Cuts at the ends of linear sequences, or outside them, are ignored.
+    pub fn cut_positions(&self, sequence: &[u8], circular: bool) -> Vec<usize> {
+        let length = sequence.len() as isize;
+        let site_length = self.site.len() as isize;
+        let mut cuts: Vec<usize> = self.sites(sequence, circular).into_iter()
+            .filter_map(|(start, forward)| {
+                let start = start as isize;
+                let cut = match forward {
+                    true  => start + self.cut as isize,
+                    false => start + site_length - self.complement_cut as isize,
+                };
+                match circular {
+                    true  => Some(cut.rem_euclid(length) as usize),
+                    false if cut > 0 && cut < length => Some(cut as usize),
+                    false => None,
+                }
+            })
+            .collect();
+        cuts.sort();
+        cuts.dedup();
+        cuts
+    }
+}
+
+/// Fragment of a digested DNA sequence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RestrictionFragment {
+    /// Index of the first nucleotide in the sequence.
+    pub start: usize,
+    /// Number of nucleotides, which may span the origin of circular sequences.
+    pub length: usize,
+}
+
+impl RestrictionFragment {
+    /// Extract the fragment from the digested sequence.
+    #[inline]
+    pub fn sequence(&self, sequence: &[u8]) -> Vec<u8> {
+        sequence.iter().cycle().skip(self.start).take(self.length).cloned().collect()
+    }
+}
+
+/// Digest a DNA sequence with one or more restriction enzymes.
+///
+/// Fragments are ordered by start position. Sequences without cuts
+/// produce a single fragment of the whole sequence.
+pub fn digest(sequence: &[u8], enzymes: &[&RestrictionEnzyme], circular: bool) -> Vec<RestrictionFragment> {
+    let length = sequence.len();
+    if length == 0 {
+        return vec![];
+    }
+    let mut cuts: Vec<usize> = enzymes.iter()
+        .flat_map(|e| e.cut_positions(sequence, circular))
+        .collect();
+    cuts.sort();
+    cuts.dedup();
+
+    let mut fragments = vec![];
+    if circular {
+        if cuts.is_empty() {
+            fragments.push(RestrictionFragment { start: 0, length: length });
+        }
+        for (i, &cut) in cuts.iter().enumerate() {
+            let end = match cuts.get(i + 1) {
+                Some(&next) => next,
+                None        => cuts[0] + length,
+            };
+            fragments.push(RestrictionFragment { start: cut, length: end - cut });
+        }
+        fragments.sort_by_key(|f| f.start);
+    } else {
+        let mut start = 0;
+        for &cut in cuts.iter().chain(Some(&length)) {
+            fragments.push(RestrictionFragment { start: start, length: cut - start });
+            start = cut;
+        }
+    }
+    fragments
+}
+
+/// Predict the fragment sizes of a digest, largest first, as on a gel.
+pub fn fragment_sizes(sequence: &[u8], enzymes: &[&RestrictionEnzyme], circular: bool) -> Vec<usize> {
+    let mut sizes: Vec<usize> = digest(sequence, enzymes, circular).iter()
+        .map(|f| f.length)
+        .collect();
+    sizes.sort_by(|a, b| b.cmp(a));
+    sizes
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iupac_matches_test() {
+        assert!(iupac_matches(b'A', b'a'));
+        assert!(iupac_matches(b'R', b'G'));
+        assert!(iupac_matches(b'T', b'U'));
+        assert!(!iupac_matches(b'Y', b'A'));
+        assert!(iupac_matches(b'N', b'N'));
+        assert!(!iupac_matches(b'A', b'N'));
+    }
+
+    #[test]
+    fn find_enzyme_test() {
+        let ecori = find_enzyme("ecori").unwrap();
+        assert_eq!(ecori.name, "EcoRI");
+        assert!(ecori.is_palindromic());
+        assert!(!find_enzyme("BsaI").unwrap().is_palindromic());
+        assert!(find_enzyme("Unknown").is_none());
+    }
+
+    #[test]
+    fn cut_positions_test() {
+        let ecori = find_enzyme("EcoRI").unwrap();
+        let sequence = b"AAGAATTCAAAAgaattcAA";
+        assert_eq!(ecori.sites(sequence, false), vec![(2, true), (12, true)]);
+        assert_eq!(ecori.cut_positions(sequence, false), vec![3, 13]);
+
+        // IUPAC recognition site.
+        let hinfi = find_enzyme("HinfI").unwrap();
+        assert_eq!(hinfi.cut_positions(b"AGACTCAGATTCA", false), vec![2, 8]);
+
+        // Non-palindromic site on the reverse strand, cutting outside the site.
+        let bsai = find_enzyme("BsaI").unwrap();
+        let sequence = b"AAAAAAAAAAAAGGTCTCAAAAAAAAAAAA";
+        assert_eq!(bsai.cut_positions(sequence, false), vec![19]);
+        let reverse = reverse_complement(sequence);
+        assert_eq!(bsai.sites(&reverse, false), vec![(12, false)]);
+        assert_eq!(bsai.cut_positions(&reverse, false), vec![7]);
+
+        // Site spanning the origin of a circular sequence.
+        assert_eq!(ecori.cut_positions(b"TTCAAAAGAA", false), Vec::<usize>::new());
+        assert_eq!(ecori.cut_positions(b"TTCAAAAGAA", true), vec![8]);
+    }
+
+    #[test]
+    fn digest_test() {
+        let ecori = find_enzyme("EcoRI").unwrap();
+        let bamhi = find_enzyme("BamHI").unwrap();
+        let sequence = b"AAGAATTCAAAAGGATCCAAAAAAAA";
+        let fragments = digest(sequence, &[ecori, bamhi], false);
+        assert_eq!(fragments, vec![
+            RestrictionFragment { start: 0, length: 3 },
+            RestrictionFragment { start: 3, length: 10 },
+            RestrictionFragment { start: 13, length: 13 },
+        ]);
+        assert_eq!(fragments[1].sequence(sequence), b"AATTCAAAAG".to_vec());
+        assert_eq!(fragment_sizes(sequence, &[ecori, bamhi], false), vec![13, 10, 3]);
+        assert_eq!(fragment_sizes(sequence, &[], false), vec![26]);
+
+        let fragments = digest(sequence, &[ecori, bamhi], true);
+        assert_eq!(fragments, vec![
+            RestrictionFragment { start: 3, length: 10 },
+            RestrictionFragment { start: 13, length: 16 },
+        ]);
+        assert_eq!(fragments[1].sequence(sequence), b"GATCCAAAAAAAAAAG".to_vec());
+        assert_eq!(fragment_sizes(sequence, &[], true), vec![26]);
+        assert!(digest(b"", &[ecori], true).is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! Theoretical fragment ions of peptides.
+//!
+//! Fragments are generated from collision-induced backbone cleavages,
+//! as N-terminal (a- and b-) or C-terminal (y-) ions, with residue
+//! mass shifts for modified residues.
+
+use super::mass::{neutral_mass_to_mz, Adduct, Element};
+use super::proteins::ProteinMassTable;
+
+/// Type of backbone fragment ion.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum IonType {
+    /// N-terminal ion, from the b-ion by loss of CO.
+    A = 1,
+    /// N-terminal ion, from cleavage of the amide bond.
+    B = 2,
+    /// C-terminal ion, from cleavage of the amide bond.
+    Y = 3,
+}
+
+impl IonType {
+    /// Get the lowercase ion series letter.
+    #[inline]
+    pub fn letter(&self) -> char {
+        match *self {
+            IonType::A => 'a',
+            IonType::B => 'b',
+            IonType::Y => 'y',
+        }
+    }
+
+    /// Create ion type from the ion series letter, ignoring case.
+    #[inline]
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter.to_ascii_lowercase() {
+            'a' => Some(IonType::A),
+            'b' => Some(IonType::B),
+            'y' => Some(IonType::Y),
+            _   => None,
+        }
+    }
+}
+
+/// Theoretical fragment ion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fragment {
+    /// Type of fragment ion.
+    pub ion: IonType,
+    /// Number of residues in the fragment.
+    pub ordinal: usize,
+    /// Charge state of the fragment.
+    pub charge: i8,
+    /// Mass to charge ratio of the fragment.
+    pub mz: f64,
+}
+
+impl Fragment {
+    /// Get the fragment annotation, for example, `y3` or `b5^2`.
+    pub fn annotation(&self) -> String {
+        match self.charge {
+            1 => format!("{}{}", self.ion.letter(), self.ordinal),
+            z => format!("{}{}^{}", self.ion.letter(), self.ordinal, z),
+        }
+    }
+}
+
+/// Generate the fragment ions of a peptide, sorted by m/z.
+///
+/// `shifts` are the mass shifts of each residue, for example, from
+/// modifications, and are ignored if empty. Fragments are generated
+/// for every backbone cleavage, at charges 1 through `max_charge`.
+pub fn fragment_ions(sequence: &[u8], shifts: &[f64], ions: &[IonType], max_charge: i8, table: &ProteinMassTable)
+    -> Vec<Fragment>
+{
+    let shift = |i: usize| shifts.get(i).cloned().unwrap_or(0.0);
+    let residues: Vec<f64> = sequence.iter()
+        .enumerate()
+        .map(|(i, &r)| table.residue_mass(r) + shift(i))
+        .collect();
+    let elements = table.elements();
+    let co = elements.mass(Element::C) + elements.mass(Element::O);
+    let water = table.termini_mass();
+
+    let mut fragments = vec![];
+    let mut push = |ion: IonType, ordinal: usize, mass: f64| {
+        for charge in 1..max_charge+1 {
+            if let Some(mz) = neutral_mass_to_mz(mass, charge, Adduct::Proton) {
+                fragments.push(Fragment { ion: ion, ordinal: ordinal, charge: charge, mz: mz });
+            }
+        }
+    };
+    for ordinal in 1..residues.len() {
+        let prefix: f64 = residues[..ordinal].iter().sum();
+        let suffix: f64 = residues[residues.len() - ordinal..].iter().sum();
+        for &ion in ions {
+            match ion {
+                IonType::A => push(ion, ordinal, prefix - co),
+                IonType::B => push(ion, ordinal, prefix),
+                IonType::Y => push(ion, ordinal, suffix + water),
+            }
+        }
+    }
+    fragments.sort_by(|x, y| x.mz.total_cmp(&y.mz));
+
+    fragments
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ion_type_test() {
+        assert_eq!(IonType::from_letter('Y'), Some(IonType::Y));
+        assert_eq!(IonType::from_letter('x'), None);
+        assert_eq!(IonType::B.letter(), 'b');
+    }
+
+    #[test]
+    fn fragment_ions_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let fragments = fragment_ions(b"PEPTIDE", &[], &[IonType::B, IonType::Y], 1, &table);
+        assert_eq!(fragments.len(), 12);
+        let b2 = fragments.iter().find(|f| f.ion == IonType::B && f.ordinal == 2).unwrap();
+        assert_approx_eq!(b2.mz, 227.10263, 1e-4);
+        let y1 = fragments.iter().find(|f| f.ion == IonType::Y && f.ordinal == 1).unwrap();
+        assert_approx_eq!(y1.mz, 148.06042, 1e-4);
+        assert!(fragments.windows(2).all(|w| w[0].mz <= w[1].mz));
+
+        // Charge states and mass shifts.
+        let fragments = fragment_ions(b"PEPTIDE", &[0.0, 0.0, 0.0, 79.966331], &[IonType::A], 2, &table);
+        assert_eq!(fragments.len(), 12);
+        let a4 = fragments.iter().find(|f| f.ordinal == 4 && f.charge == 1).unwrap();
+        let a4_2 = fragments.iter().find(|f| f.ordinal == 4 && f.charge == 2).unwrap();
+        assert_approx_eq!(a4.mz, 425.20308 - 27.99491 + 79.96633, 1e-4);
+        assert_approx_eq!(a4_2.mz, (a4.mz + 1.00728) / 2.0, 1e-4);
+        assert_eq!(a4_2.annotation(), "a4^2");
+        assert_eq!(a4.annotation(), "a4");
+
+        // NaN mass shifts sort last, rather than panicking.
+        let fragments = fragment_ions(b"PEPTIDE", &[0.0, ::std::f64::NAN], &[IonType::B], 1, &table);
+        assert_eq!(fragments.len(), 6);
+        assert!(!fragments[0].mz.is_nan());
+        assert!(fragments.last().unwrap().mz.is_nan());
+    }
+}
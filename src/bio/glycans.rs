@@ -0,0 +1,302 @@
+//! Glycan compositions and masses.
+//!
+//! Glycans are described by their monosaccharide composition, such as
+//! "HexNAc2Hex5", without the linkage or branching, which suffices to
+//! calculate the masses of glycopeptides.
+
+use std::fmt;
+
+use util::{ErrorKind, Result};
+use super::digest::Peptide;
+use super::mass::{Composition, Element, ElementTable};
+use super::proteins::ProteinMassTable;
+
+/// Number of monosaccharide types.
+const MONOSACCHARIDE_COUNT: usize = 6;
+
+/// Monosaccharide residue within a glycan.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Monosaccharide {
+    /// N-acetylhexosamine, for example, GlcNAc.
+    HexNAc,
+    /// Hexose, for example, mannose or galactose.
+    Hex,
+    /// Deoxyhexose, for example, fucose.
+    DHex,
+    /// N-acetylneuraminic acid (sialic acid).
+    NeuAc,
+    /// N-glycolylneuraminic acid.
+    NeuGc,
+    /// Pentose, for example, xylose.
+    Pent,
+}
+
+impl Monosaccharide {
+    /// All monosaccharides, in the order written in compositions.
+    pub const ALL: [Monosaccharide; MONOSACCHARIDE_COUNT] = [
+        Monosaccharide::HexNAc,
+        Monosaccharide::Hex,
+        Monosaccharide::DHex,
+        Monosaccharide::NeuAc,
+        Monosaccharide::NeuGc,
+        Monosaccharide::Pent,
+    ];
+
+    /// Get the abbreviated name of the monosaccharide.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Monosaccharide::HexNAc => "HexNAc",
+            Monosaccharide::Hex    => "Hex",
+            Monosaccharide::DHex   => "dHex",
+            Monosaccharide::NeuAc  => "NeuAc",
+            Monosaccharide::NeuGc  => "NeuGc",
+            Monosaccharide::Pent   => "Pent",
+        }
+    }
+
+    /// Parse the monosaccharide from an abbreviated name.
+    ///
+    /// Also accepts "Fuc" for deoxyhexose.
+    pub fn from_name(name: &str) -> Option<Monosaccharide> {
+        match name {
+            "Fuc" => Some(Monosaccharide::DHex),
+            _     => Monosaccharide::ALL.iter().find(|m| m.name() == name).cloned(),
+        }
+    }
+
+    /// Get the composition of the (dehydrated) monosaccharide residue.
+    pub fn composition(&self) -> Composition {
+        let (c, h, n, o) = match *self {
+            Monosaccharide::HexNAc => (8, 13, 1, 5),
+            Monosaccharide::Hex    => (6, 10, 0, 5),
+            Monosaccharide::DHex   => (6, 10, 0, 4),
+            Monosaccharide::NeuAc  => (11, 17, 1, 8),
+            Monosaccharide::NeuGc  => (11, 17, 1, 9),
+            Monosaccharide::Pent   => (5, 8, 0, 4),
+        };
+        Composition::new()
+            .with_count(Element::C, c)
+            .with_count(Element::H, h)
+            .with_count(Element::N, n)
+            .with_count(Element::O, o)
+    }
+}
+
+/// Monosaccharide composition of a glycan.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct GlycanComposition {
+    counts: [u32; MONOSACCHARIDE_COUNT],
+}
+
+impl GlycanComposition {
+    /// Create new, empty glycan composition.
+    #[inline]
+    pub fn new() -> Self {
+        GlycanComposition {
+            counts: [0; MONOSACCHARIDE_COUNT],
+        }
+    }
+
+    /// Parse a composition, such as "HexNAc2Hex5" or "HexNAc(4)Hex(5)Fuc".
+    ///
+    /// Counts may optionally be enclosed in parentheses, and a missing
+    /// count is 1. Repeated monosaccharides are summed.
+    pub fn from_string(text: &str) -> Result<Self> {
+        // Match the longest names first, so "HexNAc" is not read as "Hex".
+        const NAMES: [&'static str; 7] = ["HexNAc", "NeuAc", "NeuGc", "Pent", "dHex", "Hex", "Fuc"];
+
+        let mut composition = GlycanComposition::new();
+        let mut rest = text.trim();
+        while !rest.is_empty() {
+            let name = none_to_error!(NAMES.iter().find(|n| rest.starts_with(*n)), InvalidInput);
+            let monosaccharide = none_to_error!(Monosaccharide::from_name(name), InvalidInput);
+            rest = &rest[name.len()..];
+
+            let parenthesized = rest.starts_with('(');
+            if parenthesized {
+                rest = &rest[1..];
+            }
+            let digits = rest.bytes().take_while(|c| c.is_ascii_digit()).count();
+            let count: u32 = match digits {
+                0 if parenthesized => return Err(From::from(ErrorKind::InvalidInput)),
+                0                  => 1,
+                _                  => rest[..digits].parse()?,
+            };
+            rest = &rest[digits..];
+            if parenthesized {
+                bool_to_error!(rest.starts_with(')'), InvalidInput);
+                rest = &rest[1..];
+            }
+            composition.counts[monosaccharide as usize] += count;
+        }
+
+        Ok(composition)
+    }
+
+    /// Get the number of residues of a monosaccharide.
+    #[inline]
+    pub fn count(&self, monosaccharide: Monosaccharide) -> u32 {
+        self.counts[monosaccharide as usize]
+    }
+
+    /// Set the number of residues of a monosaccharide.
+    #[inline]
+    pub fn set_count(&mut self, monosaccharide: Monosaccharide, count: u32) {
+        self.counts[monosaccharide as usize] = count;
+    }
+
+    /// Set the number of residues of a monosaccharide, consuming and returning the composition.
+    #[inline]
+    pub fn with_count(mut self, monosaccharide: Monosaccharide, count: u32) -> Self {
+        self.set_count(monosaccharide, count);
+        self
+    }
+
+    /// Check if the glycan has no residues.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|n| *n == 0)
+    }
+
+    /// Get the elemental composition of the glycan residues.
+    ///
+    /// Excludes the water of a free glycan, so the composition is the
+    /// mass shift of the glycan attached to a peptide.
+    pub fn elements(&self) -> Composition {
+        Monosaccharide::ALL.iter().fold(Composition::new(), |sum, m| {
+            let mut residue = Composition::new();
+            for &element in Element::ALL.iter() {
+                residue.set_count(element, m.composition().count(element) * self.count(*m) as i32);
+            }
+            sum + &residue
+        })
+    }
+
+    /// Calculate the mass shift of the attached glycan from an element table.
+    #[inline]
+    pub fn mass(&self, table: &ElementTable) -> f64 {
+        self.elements().mass(table)
+    }
+}
+
+impl fmt::Display for GlycanComposition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for monosaccharide in Monosaccharide::ALL.iter() {
+            let count = self.count(*monosaccharide);
+            if count != 0 {
+                write!(f, "{}{}", monosaccharide.name(), count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// N-GLYCANS
+
+/// Common human N-glycans, by name and composition.
+const COMMON_N_GLYCANS: [(&'static str, &'static str); 16] = [
+    ("Man3", "HexNAc2Hex3"),
+    ("Man5", "HexNAc2Hex5"),
+    ("Man6", "HexNAc2Hex6"),
+    ("Man7", "HexNAc2Hex7"),
+    ("Man8", "HexNAc2Hex8"),
+    ("Man9", "HexNAc2Hex9"),
+    ("G0", "HexNAc4Hex3"),
+    ("G0F", "HexNAc4Hex3dHex1"),
+    ("G1", "HexNAc4Hex4"),
+    ("G1F", "HexNAc4Hex4dHex1"),
+    ("G2", "HexNAc4Hex5"),
+    ("G2F", "HexNAc4Hex5dHex1"),
+    ("G2S1", "HexNAc4Hex5NeuAc1"),
+    ("G2FS1", "HexNAc4Hex5dHex1NeuAc1"),
+    ("G2S2", "HexNAc4Hex5NeuAc2"),
+    ("G2FS2", "HexNAc4Hex5dHex1NeuAc2"),
+];
+
+/// Get the common human N-glycans, as `(name, composition)` pairs.
+///
+/// Includes the high-mannose glycans, and the fucosylated and
+/// sialylated biantennary complex glycans.
+pub fn common_n_glycans() -> Vec<(&'static str, GlycanComposition)> {
+    COMMON_N_GLYCANS.iter()
+        .map(|&(name, text)| (name, GlycanComposition::from_string(text).unwrap()))
+        .collect()
+}
+
+// GLYCOPEPTIDES
+
+/// Get the 0-based positions of the N-glycosylation sequons (N-X-S/T, X != P).
+pub fn n_glycosylation_sites(sequence: &[u8]) -> Vec<usize> {
+    sequence.windows(3)
+        .enumerate()
+        .filter(|&(_, w)| {
+            w[0].eq_ignore_ascii_case(&b'N') &&
+            !w[1].eq_ignore_ascii_case(&b'P') &&
+            (w[2].eq_ignore_ascii_case(&b'S') || w[2].eq_ignore_ascii_case(&b'T'))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Calculate the neutral mass of a peptide with an attached glycan.
+///
+/// The mass table should match the table used for the digestion.
+#[inline]
+pub fn glycopeptide_mass(peptide: &Peptide, glycan: &GlycanComposition, table: &ProteinMassTable) -> f64 {
+    peptide.mass + glycan.mass(table.elements())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::digest::{digest, DigestOptions};
+    use super::*;
+
+    #[test]
+    fn parse_composition_test() {
+        let glycan = GlycanComposition::from_string("HexNAc2Hex5").unwrap();
+        assert_eq!(glycan.count(Monosaccharide::HexNAc), 2);
+        assert_eq!(glycan.count(Monosaccharide::Hex), 5);
+        assert_eq!(glycan.to_string(), "HexNAc2Hex5");
+
+        assert!(GlycanComposition::from_string("Hex(5)HexNAc(4)Fuc NeuAc").is_err());
+        let glycan = GlycanComposition::from_string("Hex(5)HexNAc(4)FucNeuAcHex").unwrap();
+        assert_eq!(glycan.to_string(), "HexNAc4Hex6dHex1NeuAc1");
+        assert!(GlycanComposition::from_string("").unwrap().is_empty());
+
+        assert!(GlycanComposition::from_string("HexNAc()").is_err());
+        assert!(GlycanComposition::from_string("HexNAc(2").is_err());
+        assert!(GlycanComposition::from_string("Kdn2").is_err());
+    }
+
+    #[test]
+    fn mass_test() {
+        let table = ElementTable::monoisotopic();
+        assert_approx_eq!(Monosaccharide::Hex.composition().mass(&table), 162.052824, 1e-5);
+        assert_approx_eq!(Monosaccharide::HexNAc.composition().mass(&table), 203.079373, 1e-5);
+        assert_approx_eq!(Monosaccharide::NeuAc.composition().mass(&table), 291.095417, 1e-5);
+
+        let man5 = GlycanComposition::from_string("HexNAc2Hex5").unwrap();
+        assert_eq!(man5.elements().to_string(), "C46H76N2O35");
+        assert_approx_eq!(man5.mass(&table), 1216.422863, 1e-5);
+
+        let glycans = common_n_glycans();
+        assert_eq!(glycans[1], ("Man5", man5));
+        assert_eq!(glycans.len(), 16);
+    }
+
+    #[test]
+    fn glycopeptide_test() {
+        assert_eq!(n_glycosylation_sites(b"ANGTKNPSRNLT"), vec![1, 9]);
+        assert!(n_glycosylation_sites(b"NG").is_empty());
+
+        let table = ProteinMassTable::monoisotopic();
+        let options = DigestOptions::default();
+        let peptide = digest(b"ANGTKNPSRNLTR", &options, &table).remove(0);
+        let glycan = GlycanComposition::new().with_count(Monosaccharide::HexNAc, 1);
+        assert_approx_eq!(glycopeptide_mass(&peptide, &glycan, &table) - peptide.mass, 203.079373, 1e-5);
+    }
+}
@@ -0,0 +1,107 @@
+//! Conversions between neutral mass and mass-to-charge ratios.
+
+/// Mass of a proton, in Da.
+pub const PROTON_MASS: f64 = 1.007276466812;
+
+/// Mass of an electron, in Da.
+pub const ELECTRON_MASS: f64 = 0.00054857990946;
+
+/// Charge carrier added to (or removed from) a neutral molecule.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+pub enum Adduct {
+    /// Protonation, [M+H]+, or deprotonation, [M-H]-.
+    Proton = 1,
+    /// Sodiation, [M+Na]+.
+    Sodium = 2,
+    /// Ammoniation, [M+NH4]+.
+    Ammonium = 3,
+}
+
+impl Adduct {
+    /// Get the monoisotopic mass of the charged adduct ion.
+    #[inline]
+    pub fn mass(&self) -> f64 {
+        match self {
+            Adduct::Proton   => PROTON_MASS,
+            Adduct::Sodium   => 22.9897692809 - ELECTRON_MASS,
+            Adduct::Ammonium => 18.03382554,
+        }
+    }
+}
+
+/// Convert neutral mass to m/z for a charge state and adduct type.
+///
+/// Negative charges remove the adduct, for example, `[M-2H]2-`.
+/// Returns `None` for an uncharged (or unknown charge) species.
+#[inline]
+pub fn neutral_mass_to_mz(mass: f64, z: i8, adduct: Adduct) -> Option<f64> {
+    match z {
+        0 => None,
+        _ => {
+            let z = z as f64;
+            Some((mass + z * adduct.mass()) / z.abs())
+        },
+    }
+}
+
+/// Convert m/z to neutral mass for a charge state and adduct type.
+///
+/// Negative charges remove the adduct, for example, `[M-2H]2-`.
+/// Returns `None` for an uncharged (or unknown charge) species.
+#[inline]
+pub fn mz_to_neutral_mass(mz: f64, z: i8, adduct: Adduct) -> Option<f64> {
+    match z {
+        0 => None,
+        _ => {
+            let z = z as f64;
+            Some(mz * z.abs() - z * adduct.mass())
+        },
+    }
+}
+
+/// Convert m/z from one charge state to another for the same adduct type.
+#[inline]
+pub fn convert_mz(mz: f64, from_z: i8, to_z: i8, adduct: Adduct) -> Option<f64> {
+    mz_to_neutral_mass(mz, from_z, adduct)
+        .and_then(|mass| neutral_mass_to_mz(mass, to_z, adduct))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_mass_to_mz_test() {
+        // SAMPLER, monoisotopic neutral mass.
+        let mass = 802.4007;
+        assert_approx_eq!(neutral_mass_to_mz(mass, 1, Adduct::Proton).unwrap(), 803.407976, 1e-6);
+        assert_approx_eq!(neutral_mass_to_mz(mass, 2, Adduct::Proton).unwrap(), 402.207626, 1e-6);
+        assert_approx_eq!(neutral_mass_to_mz(mass, -1, Adduct::Proton).unwrap(), 801.393424, 1e-6);
+        assert_approx_eq!(neutral_mass_to_mz(mass, 1, Adduct::Sodium).unwrap(), 825.389921, 1e-6);
+        assert_approx_eq!(neutral_mass_to_mz(mass, 1, Adduct::Ammonium).unwrap(), 820.434526, 1e-6);
+        assert_eq!(neutral_mass_to_mz(mass, 0, Adduct::Proton), None);
+    }
+
+    #[test]
+    fn mz_to_neutral_mass_test() {
+        let mass = 802.4007;
+        for z in [-3i8, -1, 1, 2, 4].iter() {
+            for adduct in [Adduct::Proton, Adduct::Sodium, Adduct::Ammonium].iter() {
+                let mz = neutral_mass_to_mz(mass, *z, *adduct).unwrap();
+                assert_approx_eq!(mz_to_neutral_mass(mz, *z, *adduct).unwrap(), mass, 1e-9);
+            }
+        }
+        assert_eq!(mz_to_neutral_mass(802.4, 0, Adduct::Proton), None);
+    }
+
+    #[test]
+    fn convert_mz_test() {
+        let mz = convert_mz(803.407976, 1, 2, Adduct::Proton).unwrap();
+        assert_approx_eq!(mz, 402.207626, 1e-6);
+        assert_eq!(convert_mz(803.407976, 1, 0, Adduct::Proton), None);
+    }
+}
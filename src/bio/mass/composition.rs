@@ -0,0 +1,286 @@
+//! Elemental composition (molecular formula) of biological molecules.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+use bio::proteins::residue_formula;
+use util::{ErrorKind, Result};
+use super::element::{Element, ElementTable, ELEMENT_COUNT};
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Elemental composition of a molecule.
+///
+/// Element counts are signed, so a composition may also describe the
+/// net change of a modification, such as a loss of water.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Composition {
+    counts: [i32; ELEMENT_COUNT],
+}
+
+impl Composition {
+    /// Create new, empty composition.
+    #[inline]
+    pub fn new() -> Self {
+        Composition {
+            counts: [0; ELEMENT_COUNT],
+        }
+    }
+
+    /// Composition of water (H2O), added to a sequence at the termini.
+    #[inline]
+    pub fn water() -> Self {
+        Composition::new()
+            .with_count(Element::H, 2)
+            .with_count(Element::O, 1)
+    }
+
+    /// Composition of a carbamidomethyl modification (C2H3NO).
+    #[inline]
+    pub fn carbamidomethyl() -> Self {
+        Composition::new()
+            .with_count(Element::C, 2)
+            .with_count(Element::H, 3)
+            .with_count(Element::N, 1)
+            .with_count(Element::O, 1)
+    }
+
+    /// Composition of an oxidation modification (O).
+    #[inline]
+    pub fn oxidation() -> Self {
+        Composition::new().with_count(Element::O, 1)
+    }
+
+    /// Composition of a phosphorylation modification (HPO3).
+    #[inline]
+    pub fn phosphorylation() -> Self {
+        Composition::new()
+            .with_count(Element::H, 1)
+            .with_count(Element::P, 1)
+            .with_count(Element::O, 3)
+    }
+
+    /// Composition of an acetylation modification (C2H2O).
+    #[inline]
+    pub fn acetylation() -> Self {
+        Composition::new()
+            .with_count(Element::C, 2)
+            .with_count(Element::H, 2)
+            .with_count(Element::O, 1)
+    }
+
+    /// Composition of an internal (dehydrated) aminoacid residue.
+    ///
+    /// Returns `None` for ambiguous or unknown residues.
+    pub fn residue(residue: u8) -> Option<Self> {
+        residue_formula(residue).map(|formula| {
+            formula.iter().fold(Composition::new(), |c, &(e, n)| c.with_count(e, n as i32))
+        })
+    }
+
+    /// Composition of a peptide or protein sequence, including water.
+    ///
+    /// Fails with `InvalidInput` for sequences with ambiguous residues.
+    pub fn protein(sequence: &[u8]) -> Result<Self> {
+        let mut composition = Composition::water();
+        for residue in sequence {
+            let formula = none_to_error!(residue_formula(*residue), InvalidInput);
+            for &(e, n) in formula {
+                composition.counts[e as usize] += n as i32;
+            }
+        }
+
+        Ok(composition)
+    }
+
+    /// Parse composition from a formula, such as "C2H3NO" or "H-2O-1".
+    pub fn from_formula(formula: &str) -> Result<Self> {
+        let mut composition = Composition::new();
+        let bytes = formula.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            // Parse the symbol, an uppercase letter with an optional lowercase letter.
+            let start = i;
+            bool_to_error!(bytes[i].is_ascii_uppercase(), InvalidInput);
+            i += 1;
+            if i < bytes.len() && bytes[i].is_ascii_lowercase() {
+                i += 1;
+            }
+            let element = none_to_error!(Element::from_symbol(&formula[start..i]), InvalidInput);
+
+            // Parse the optional, signed count.
+            let start = i;
+            if i < bytes.len() && bytes[i] == b'-' {
+                i += 1;
+            }
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            let count: i32 = match &formula[start..i] {
+                ""  => 1,
+                "-" => return Err(From::from(ErrorKind::InvalidInput)),
+                s   => s.parse()?,
+            };
+            composition.counts[element as usize] += count;
+        }
+
+        Ok(composition)
+    }
+
+    /// Get the number of atoms of an element.
+    #[inline]
+    pub fn count(&self, element: Element) -> i32 {
+        self.counts[element as usize]
+    }
+
+    /// Set the number of atoms of an element.
+    #[inline]
+    pub fn set_count(&mut self, element: Element, count: i32) {
+        self.counts[element as usize] = count;
+    }
+
+    /// Set the number of atoms of an element, consuming and returning the composition.
+    #[inline]
+    pub fn with_count(mut self, element: Element, count: i32) -> Self {
+        self.set_count(element, count);
+        self
+    }
+
+    /// Check if the composition has no atoms.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|n| *n == 0)
+    }
+
+    /// Get the non-zero element counts, in Hill order.
+    ///
+    /// Hill order lists carbon first, hydrogen second, and all
+    /// other elements alphabetically by symbol.
+    pub fn elements(&self) -> Vec<(Element, i32)> {
+        let mut elements: Vec<(Element, i32)> = Element::ALL.iter()
+            .map(|e| (*e, self.count(*e)))
+            .filter(|&(_, n)| n != 0)
+            .collect();
+        elements.sort_by_key(|&(e, _)| match e {
+            Element::C  => (0, ""),
+            Element::H  => (1, ""),
+            _           => (2, e.symbol()),
+        });
+        elements
+    }
+
+    /// Calculate the mass of the composition from an element table.
+    #[inline]
+    pub fn mass(&self, table: &ElementTable) -> f64 {
+        Element::ALL.iter().fold(0.0, |sum, e| sum + table.mass(*e) * self.count(*e) as f64)
+    }
+}
+
+impl fmt::Display for Composition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (element, count) in self.elements() {
+            write!(f, "{}", element.symbol())?;
+            if count != 1 {
+                write!(f, "{}", count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> AddAssign<&'a Composition> for Composition {
+    #[inline]
+    fn add_assign(&mut self, other: &'a Composition) {
+        for i in 0..ELEMENT_COUNT {
+            self.counts[i] += other.counts[i];
+        }
+    }
+}
+
+impl<'a> SubAssign<&'a Composition> for Composition {
+    #[inline]
+    fn sub_assign(&mut self, other: &'a Composition) {
+        for i in 0..ELEMENT_COUNT {
+            self.counts[i] -= other.counts[i];
+        }
+    }
+}
+
+impl<'a> Add<&'a Composition> for Composition {
+    type Output = Composition;
+
+    #[inline]
+    fn add(mut self, other: &'a Composition) -> Composition {
+        self += other;
+        self
+    }
+}
+
+impl<'a> Sub<&'a Composition> for Composition {
+    type Output = Composition;
+
+    #[inline]
+    fn sub(mut self, other: &'a Composition) -> Composition {
+        self -= other;
+        self
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::mass::SequenceMass;
+    use bio::proteins::MonoisotopicMass;
+    use super::*;
+
+    #[test]
+    fn protein_test() {
+        let c = Composition::protein(b"SAMPLER").unwrap();
+        assert_eq!(c.to_string(), "C33H58N10O11S");
+        assert_eq!(c.count(Element::N), 10);
+        assert_approx_eq!(c.mass(&ElementTable::monoisotopic()), MonoisotopicMass::total_sequence_mass(b"SAMPLER"), 1e-6);
+
+        // Lowercase residues are equivalent, ambiguous residues are not allowed.
+        assert_eq!(Composition::protein(b"sampler").unwrap(), c);
+        assert!(Composition::protein(b"SAMPLEX").is_err());
+
+        // An empty sequence is water.
+        assert_eq!(Composition::protein(b"").unwrap(), Composition::water());
+    }
+
+    #[test]
+    fn residue_test() {
+        assert_eq!(Composition::residue(b'U').unwrap().to_string(), "C3H5NOSe");
+        assert_eq!(Composition::residue(b'B'), None);
+    }
+
+    #[test]
+    fn modification_test() {
+        let mut c = Composition::protein(b"PEPTCIDE").unwrap();
+        c += &Composition::carbamidomethyl();
+        c += &Composition::phosphorylation();
+        assert_eq!(c.count(Element::P), 1);
+
+        let base = Composition::protein(b"PEPTCIDE").unwrap();
+        let delta = c.mass(&ElementTable::monoisotopic()) - base.mass(&ElementTable::monoisotopic());
+        assert_approx_eq!(delta, 57.021464 + 79.966331, 1e-5);
+
+        let c = c - &Composition::carbamidomethyl() - &Composition::phosphorylation();
+        assert_eq!(c, base);
+    }
+
+    #[test]
+    fn from_formula_test() {
+        assert_eq!(Composition::from_formula("C2H3NO").unwrap(), Composition::carbamidomethyl());
+        assert_eq!(Composition::from_formula("HO3P").unwrap(), Composition::phosphorylation());
+        assert_eq!(Composition::from_formula("H2O").unwrap(), Composition::water());
+        assert_eq!(Composition::from_formula("H-2O-1").unwrap().to_string(), "H-2O-1");
+        assert!(Composition::from_formula("NaCl").is_err());
+        assert!(Composition::from_formula("").unwrap().is_empty());
+        assert!(Composition::from_formula("c2").is_err());
+        assert!(Composition::from_formula("C-").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Chemical element definitions and configurable atomic mass tables.
+
+/// Chemical elements found in biological molecules and common adducts.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+pub enum Element {
+    H = 0,
+    C = 1,
+    N = 2,
+    O = 3,
+    P = 4,
+    S = 5,
+    Se = 6,
+    Na = 7,
+    K = 8,
+}
+
+/// Number of elements in an `ElementTable`.
+pub(crate) const ELEMENT_COUNT: usize = 9;
+
+impl Element {
+    /// All elements, in table order.
+    pub const ALL: [Element; ELEMENT_COUNT] = [
+        Element::H,
+        Element::C,
+        Element::N,
+        Element::O,
+        Element::P,
+        Element::S,
+        Element::Se,
+        Element::Na,
+        Element::K,
+    ];
+
+    /// Get the atomic symbol for the element.
+    #[inline]
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Element::H  => "H",
+            Element::C  => "C",
+            Element::N  => "N",
+            Element::O  => "O",
+            Element::P  => "P",
+            Element::S  => "S",
+            Element::Se => "Se",
+            Element::Na => "Na",
+            Element::K  => "K",
+        }
+    }
+
+    /// Create element from atomic symbol.
+    #[inline]
+    pub fn from_symbol(symbol: &str) -> Option<Element> {
+        Element::ALL.iter().find(|e| e.symbol() == symbol).cloned()
+    }
+}
+
+/// Table of atomic masses used for mass calculations.
+///
+/// The default tables use either the monoisotopic mass of the lightest
+/// stable isotope, or the abundance-weighted average mass of each element.
+/// Individual masses may be overridden, for example, to model uniform
+/// metabolic labeling (such as 15N) by replacing the mass of an element
+/// with the mass of the heavy isotope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementTable {
+    masses: [f64; ELEMENT_COUNT],
+}
+
+impl ElementTable {
+    /// Create table from the monoisotopic masses of each element.
+    #[inline]
+    pub fn monoisotopic() -> Self {
+        ElementTable {
+            masses: [
+                1.00782503207,      // H
+                12.0,               // C
+                14.0030740048,      // N
+                15.99491461956,     // O
+                30.97376163,        // P
+                31.97207069,        // S
+                79.9165213,         // Se
+                22.9897692809,      // Na
+                38.96370668,        // K
+            ]
+        }
+    }
+
+    /// Create table from the average masses of each element.
+    #[inline]
+    pub fn average() -> Self {
+        ElementTable {
+            masses: [
+                1.00794,            // H
+                12.0107,            // C
+                14.0067,            // N
+                15.9994,            // O
+                30.973762,          // P
+                32.065,             // S
+                78.96,              // Se
+                22.98976928,        // Na
+                39.0983,            // K
+            ]
+        }
+    }
+
+    /// Get the mass of an element.
+    #[inline]
+    pub fn mass(&self, element: Element) -> f64 {
+        self.masses[element as usize]
+    }
+
+    /// Override the mass of an element.
+    #[inline]
+    pub fn set_mass(&mut self, element: Element, mass: f64) {
+        self.masses[element as usize] = mass;
+    }
+
+    /// Override the mass of an element, consuming and returning the table.
+    #[inline]
+    pub fn with_mass(mut self, element: Element, mass: f64) -> Self {
+        self.set_mass(element, mass);
+        self
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_test() {
+        for element in Element::ALL.iter() {
+            assert_eq!(Element::from_symbol(element.symbol()), Some(*element));
+        }
+        assert_eq!(Element::from_symbol("Se"), Some(Element::Se));
+        assert_eq!(Element::from_symbol("X"), None);
+    }
+
+    #[test]
+    fn mass_test() {
+        let mono = ElementTable::monoisotopic();
+        let avg = ElementTable::average();
+        for element in Element::ALL.iter() {
+            assert_approx_eq!(mono.mass(*element), avg.mass(*element), 1.0);
+        }
+        assert_eq!(mono.mass(Element::C), 12.0);
+        assert_eq!(avg.mass(Element::C), 12.0107);
+    }
+
+    #[test]
+    fn override_mass_test() {
+        let mut table = ElementTable::monoisotopic();
+        table.set_mass(Element::N, 15.0001088982);
+        assert_eq!(table.mass(Element::N), 15.0001088982);
+        assert_ne!(table, ElementTable::monoisotopic());
+
+        let table = ElementTable::monoisotopic().with_mass(Element::C, 13.0033548378);
+        assert_eq!(table.mass(Element::C), 13.0033548378);
+    }
+}
@@ -1,5 +1,18 @@
 //! General purpose mass routines.
 
+pub(crate) mod charge;
+pub(crate) mod composition;
+pub(crate) mod element;
+pub(crate) mod tolerance;
+
+// Re-export the element models into the parent module.
+pub use self::charge::*;
+pub use self::composition::Composition;
+pub use self::element::{Element, ElementTable};
+pub use self::tolerance::Tolerance;
+
+use super::proteins::ResidueLabel;
+
 /// Calculate the mass of a biological sequence.
 ///
 /// Different biological application depend on different assumptions for
@@ -35,4 +48,27 @@ pub trait SequenceMass {
     fn total_sequence_mass(sequence: &[u8]) -> f64 {
         Self::internal_sequence_mass(sequence) + Self::termini_mass()
     }
+
+    /// Calculate the mass of an internal residue shifted by stable isotope labels.
+    ///
+    /// Labels match the residue ignoring case, and shifts from multiple
+    /// labels on the same residue are summed.
+    #[inline]
+    fn labeled_residue_mass(residue: u8, labels: &[ResidueLabel]) -> f64 {
+        labels.iter()
+            .filter(|label| label.residue.eq_ignore_ascii_case(&residue))
+            .fold(Self::residue_mass(residue), |sum, label| sum + label.delta)
+    }
+
+    /// Calculate the mass of a protein sequence shifted by stable isotope labels.
+    #[inline]
+    fn labeled_internal_sequence_mass(sequence: &[u8], labels: &[ResidueLabel]) -> f64 {
+        sequence.iter().fold(0.0, |sum, x| sum + Self::labeled_residue_mass(*x, labels))
+    }
+
+    /// Calculate the mass of a protein sequence with N- or C-termini, shifted by stable isotope labels.
+    #[inline(always)]
+    fn labeled_total_sequence_mass(sequence: &[u8], labels: &[ResidueLabel]) -> f64 {
+        Self::labeled_internal_sequence_mass(sequence, labels) + Self::termini_mass()
+    }
 }
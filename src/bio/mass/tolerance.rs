@@ -0,0 +1,60 @@
+//! Mass tolerances, to match observed and expected m/z values.
+
+/// Maximum difference between matching mass or m/z values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance {
+    /// Tolerance relative to the expected value, in parts-per-million.
+    Ppm(f64),
+    /// Absolute tolerance, in Daltons (or m/z units).
+    Da(f64),
+}
+
+impl Tolerance {
+    /// Get the absolute tolerance around an expected value.
+    #[inline]
+    pub fn width(&self, expected: f64) -> f64 {
+        match *self {
+            Tolerance::Ppm(ppm) => expected.abs() * ppm * 1e-6,
+            Tolerance::Da(da)   => da,
+        }
+    }
+
+    /// Get the inclusive range `[lo, hi]` of values matching an expected value.
+    #[inline]
+    pub fn range(&self, expected: f64) -> (f64, f64) {
+        let width = self.width(expected);
+        (expected - width, expected + width)
+    }
+
+    /// Check if an observed value matches the expected value.
+    #[inline]
+    pub fn matches(&self, expected: f64, observed: f64) -> bool {
+        (observed - expected).abs() <= self.width(expected)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerance_test() {
+        let ppm = Tolerance::Ppm(10.0);
+        assert_approx_eq!(ppm.width(500.0), 0.005, 1e-12);
+        let (lo, hi) = ppm.range(500.0);
+        assert_approx_eq!(lo, 499.995, 1e-9);
+        assert_approx_eq!(hi, 500.005, 1e-9);
+        assert!(ppm.matches(500.0, 500.004));
+        assert!(!ppm.matches(500.0, 500.006));
+        assert!(!ppm.matches(2000.0, 1999.9));
+
+        let da = Tolerance::Da(0.02);
+        assert_eq!(da.width(500.0), 0.02);
+        assert_eq!(da.range(100.0), (99.98, 100.02));
+        assert!(da.matches(2000.0, 1999.99));
+        assert!(!da.matches(100.0, 100.03));
+    }
+}
@@ -1,11 +1,31 @@
 //! Biological molecule definitions.
 
-mod mass;
-
 // Expose biological molecules in public submodules.
-pub mod dna;
+pub mod mass;
 pub mod proteins;
-pub mod rna;
 
-// Publicly re-export the SequenceMass.
-pub use self::mass::SequenceMass;
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        pub mod align;
+        pub mod alphabet;
+        pub mod cluster;
+        pub mod crosslinks;
+        pub mod decoy;
+        pub mod digest;
+        pub mod distance;
+        pub mod dna;
+        pub mod fragment;
+        pub mod glycans;
+        pub mod motif;
+        pub mod phylo;
+        pub mod rna;
+        pub mod rt;
+    }
+}
+
+// Publicly re-export the Alphabet, GeneticCode, SequenceMass and Tolerance.
+pub use self::mass::{SequenceMass, Tolerance};
+#[cfg(feature = "std")]
+pub use self::alphabet::Alphabet;
+#[cfg(feature = "std")]
+pub use self::dna::GeneticCode;
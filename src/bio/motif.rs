@@ -0,0 +1,163 @@
+//! Sequence motif utilities.
+
+use util::{ErrorKind, Result};
+
+// MOTIF
+
+/// Single position in a sequence motif.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum MotifElement {
+    /// Any residue.
+    Any,
+    /// Any of the residues.
+    OneOf(Vec<u8>),
+    /// Any residue except the residues.
+    NoneOf(Vec<u8>),
+}
+
+impl MotifElement {
+    /// Check if the element matches a residue, ignoring case.
+    #[inline]
+    fn matches(&self, residue: u8) -> bool {
+        let residue = residue.to_ascii_uppercase();
+        match *self {
+            MotifElement::Any            => true,
+            MotifElement::OneOf(ref v)   => v.contains(&residue),
+            MotifElement::NoneOf(ref v)  => !v.contains(&residue),
+        }
+    }
+}
+
+/// Fixed-length sequence motif, in a PROSITE-like syntax.
+///
+/// Each position is a residue (`N`), any residue (`X`), any of a set
+/// of residues (`[ST]`), or any residue except a set of residues
+/// (`{P}`). Positions may be separated by `-`, as in PROSITE patterns,
+/// so the N-glycosylation sequon is `N{P}[ST]` or `N-{P}-[ST]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Motif {
+    elements: Vec<MotifElement>,
+}
+
+impl Motif {
+    /// Parse a motif from a pattern.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut elements = vec![];
+        let mut bytes = pattern.bytes().map(|c| c.to_ascii_uppercase());
+        while let Some(c) = bytes.next() {
+            let element = match c {
+                b'-'            => continue,
+                b'X'            => MotifElement::Any,
+                b'[' | b'{'     => {
+                    let close = if c == b'[' { b']' } else { b'}' };
+                    let mut residues = vec![];
+                    loop {
+                        let c = none_to_error!(bytes.next(), InvalidInput);
+                        match c == close {
+                            true  => break,
+                            false => residues.push(c),
+                        }
+                    }
+                    bool_to_error!(!residues.is_empty(), InvalidInput);
+                    bool_to_error!(residues.iter().all(u8::is_ascii_alphabetic), InvalidInput);
+                    match c {
+                        b'[' => MotifElement::OneOf(residues),
+                        _    => MotifElement::NoneOf(residues),
+                    }
+                },
+                c if c.is_ascii_alphabetic() => MotifElement::OneOf(vec![c]),
+                _               => return Err(From::from(ErrorKind::InvalidInput)),
+            };
+            elements.push(element);
+        }
+        bool_to_error!(!elements.is_empty(), InvalidInput);
+
+        Ok(Motif { elements: elements })
+    }
+
+    /// Get the number of residues matched by the motif.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Check if the motif matches no residues.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Check if the motif matches the start of a sequence.
+    #[inline]
+    pub fn matches(&self, sequence: &[u8]) -> bool {
+        sequence.len() >= self.len() && self.elements.iter().zip(sequence).all(|(e, &c)| e.matches(c))
+    }
+
+    /// Find the 0-based start positions of every match, including overlapping matches.
+    pub fn find(&self, sequence: &[u8]) -> Vec<usize> {
+        match sequence.len() >= self.len() {
+            true  => (0..sequence.len() - self.len() + 1).filter(|&i| self.matches(&sequence[i..])).collect(),
+            false => vec![],
+        }
+    }
+}
+
+// WINDOW
+
+/// Extract the residues within `flank` residues of a position.
+///
+/// The window has `2 * flank + 1` residues, centered on the position,
+/// and is padded with `padding` past the ends of the sequence, so
+/// windows from every position align, as expected by motif analysis
+/// tools (for example, motif-x). Returns `None` if the position is
+/// outside the sequence.
+pub fn sequence_window(sequence: &[u8], position: usize, flank: usize, padding: u8) -> Option<Vec<u8>> {
+    if position >= sequence.len() {
+        return None;
+    }
+
+    let mut window = Vec::with_capacity(2 * flank + 1);
+    let start = position.saturating_sub(flank);
+    let end = (position + flank + 1).min(sequence.len());
+    window.resize(flank - (position - start), padding);
+    window.extend_from_slice(&sequence[start..end]);
+    window.resize(2 * flank + 1, padding);
+    Some(window)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn motif_test() {
+        let motif = Motif::new("N{P}[ST]").unwrap();
+        assert_eq!(motif.len(), 3);
+        assert_eq!(motif, Motif::new("n-{p}-[st]").unwrap());
+        assert!(motif.matches(b"NAS"));
+        assert!(!motif.matches(b"NPS"));
+        assert!(!motif.matches(b"NA"));
+        assert_eq!(motif.find(b"MNNSTNPTNGT"), vec![1, 2, 8]);
+        assert_eq!(Motif::new("XX").unwrap().find(b"ABC"), vec![0, 1]);
+        assert!(Motif::new("XXXX").unwrap().find(b"ABC").is_empty());
+
+        assert!(Motif::new("").is_err());
+        assert!(Motif::new("N[]S").is_err());
+        assert!(Motif::new("N[S").is_err());
+        assert!(Motif::new("N*S").is_err());
+    }
+
+    #[test]
+    fn sequence_window_test() {
+        let sequence = b"MKWVTFISLLLLFSSAYS";
+        assert_eq!(sequence_window(sequence, 4, 2, b'_').unwrap(), b"WVTFI".to_vec());
+        assert_eq!(sequence_window(sequence, 1, 3, b'_').unwrap(), b"__MKWVT".to_vec());
+        assert_eq!(sequence_window(sequence, 17, 3, b'_').unwrap(), b"SAYS___".to_vec());
+        assert_eq!(sequence_window(b"S", 0, 2, b'X').unwrap(), b"XXSXX".to_vec());
+        assert_eq!(sequence_window(sequence, 0, 0, b'_').unwrap(), b"M".to_vec());
+        assert!(sequence_window(sequence, 18, 2, b'_').is_none());
+    }
+}
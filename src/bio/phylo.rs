@@ -0,0 +1,472 @@
+//! Phylogenetic trees, and the Newick tree format.
+//!
+//! Trees are stored as a node arena, with nodes referenced by their
+//! index, and the root at index 0. Each node has an optional label,
+//! for both leaves and internal nodes, and an optional branch length
+//! to its parent.
+
+use util::{ErrorKind, Result};
+use super::distance::DistanceMatrix;
+
+/// Index of a node in a tree.
+pub type NodeId = usize;
+
+/// Node in a phylogenetic tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// Node label, or empty if unlabeled.
+    pub label: String,
+    /// Length of the branch to the parent, if known.
+    pub length: Option<f64>,
+    /// Parent of the node, or `None` for the root.
+    pub parent: Option<NodeId>,
+    /// Children of the node, in order.
+    pub children: Vec<NodeId>,
+}
+
+impl Node {
+    /// Check if the node has no children.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// Rooted phylogenetic tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    /// Create new tree with an unlabeled root.
+    #[inline]
+    pub fn new() -> Self {
+        Tree {
+            nodes: vec![Node { label: String::new(), length: None, parent: None, children: vec![] }],
+        }
+    }
+
+    /// Get the root of the tree.
+    #[inline]
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    /// Get the number of nodes in the tree, including the root.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the tree only contains the root.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Get a node by index.
+    #[inline]
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    /// Get a mutable node by index.
+    #[inline]
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id]
+    }
+
+    /// Add a child to a node, and get the index of the child.
+    pub fn add_child(&mut self, parent: NodeId, label: &str, length: Option<f64>) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            label: String::from(label),
+            length: length,
+            parent: Some(parent),
+            children: vec![],
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// Find the first node with a label, in preorder.
+    pub fn find(&self, label: &str) -> Option<NodeId> {
+        self.preorder().into_iter().find(|&id| self.nodes[id].label == label)
+    }
+
+    /// Get the nodes in preorder, parents before their children.
+    pub fn preorder(&self) -> Vec<NodeId> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![self.root()];
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.nodes[id].children.iter().rev());
+        }
+        order
+    }
+
+    /// Get the nodes in postorder, children before their parents.
+    pub fn postorder(&self) -> Vec<NodeId> {
+        // Reversing the preorder with reversed children gives the postorder.
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![self.root()];
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.nodes[id].children.iter());
+        }
+        order.reverse();
+        order
+    }
+
+    /// Get the leaves, in preorder.
+    pub fn leaves(&self) -> Vec<NodeId> {
+        self.preorder().into_iter().filter(|&id| self.nodes[id].is_leaf()).collect()
+    }
+
+    /// Get the ancestors of a node, from its parent to the root.
+    pub fn ancestors(&self, id: NodeId) -> Vec<NodeId> {
+        let mut ancestors = vec![];
+        let mut current = self.nodes[id].parent;
+        while let Some(parent) = current {
+            ancestors.push(parent);
+            current = self.nodes[parent].parent;
+        }
+        ancestors
+    }
+
+    /// Get the sum of the branch lengths from the root to a node.
+    ///
+    /// Unknown branch lengths are treated as 0.
+    pub fn distance_from_root(&self, id: NodeId) -> f64 {
+        let mut distance = self.nodes[id].length.unwrap_or(0.0);
+        for ancestor in self.ancestors(id) {
+            distance += self.nodes[ancestor].length.unwrap_or(0.0);
+        }
+        distance
+    }
+
+    // NEWICK
+
+    /// Parse a tree from the Newick format.
+    ///
+    /// Labels may be quoted with single quotes, and comments in
+    /// square brackets are ignored. The trailing `;` is required.
+    pub fn from_newick(text: &str) -> Result<Self> {
+        let mut parser = NewickParser { bytes: text.as_bytes(), index: 0 };
+        let mut tree = Tree::new();
+        let root = tree.root();
+        parser.parse_subtree(&mut tree, root)?;
+        parser.skip_whitespace();
+        bool_to_error!(parser.next() == Some(b';'), InvalidInput);
+        parser.skip_whitespace();
+        bool_to_error!(parser.peek().is_none(), InvalidInput);
+
+        Ok(tree)
+    }
+
+    /// Export the tree to the Newick format.
+    pub fn to_newick(&self) -> String {
+        let mut text = String::new();
+        self.write_subtree(&mut text, self.root());
+        text.push(';');
+        text
+    }
+
+    fn write_subtree(&self, text: &mut String, id: NodeId) {
+        let node = &self.nodes[id];
+        if !node.is_leaf() {
+            text.push('(');
+            for (i, &child) in node.children.iter().enumerate() {
+                if i > 0 {
+                    text.push(',');
+                }
+                self.write_subtree(text, child);
+            }
+            text.push(')');
+        }
+        write_label(text, &node.label);
+        if let Some(length) = node.length {
+            text.push(':');
+            text.push_str(&length.to_string());
+        }
+    }
+}
+
+impl Default for Tree {
+    #[inline]
+    fn default() -> Self {
+        Tree::new()
+    }
+}
+
+// NEIGHBOR JOINING
+
+/// Build a tree from a distance matrix by neighbor-joining.
+///
+/// The leaves are labeled by the matrix labels. Neighbor-joining
+/// produces an unrooted tree, which is rooted at the final join,
+/// with three children. Negative branch lengths are set to 0.
+pub fn neighbor_joining(matrix: &DistanceMatrix) -> Tree {
+    // Clusters as (label, children with branch lengths), leaves first.
+    let n = matrix.len();
+    let mut clusters: Vec<(String, Vec<(usize, f64)>)> = matrix.labels().iter()
+        .map(|l| (l.clone(), vec![]))
+        .collect();
+    let mut active: Vec<usize> = (0..n).collect();
+    let mut distances: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| matrix.get(i, j)).collect()).collect();
+
+    while active.len() > 3 {
+        let count = active.len() as f64;
+        let sums: Vec<f64> = active.iter().map(|&i| active.iter().map(|&k| distances[i][k]).sum()).collect();
+
+        // Find the pair minimizing the Q-criterion, the first in order on ties.
+        let mut best = (0, 1, f64::INFINITY);
+        for a in 0..active.len() {
+            for b in a+1..active.len() {
+                let q = (count - 2.0) * distances[active[a]][active[b]] - sums[a] - sums[b];
+                if q < best.2 {
+                    best = (a, b, q);
+                }
+            }
+        }
+        let (a, b, _) = best;
+        let (i, j) = (active[a], active[b]);
+        let dij = distances[i][j];
+        let li = dij / 2.0 + (sums[a] - sums[b]) / (2.0 * (count - 2.0));
+        let lj = dij - li;
+
+        // Join the pair into a new cluster, replacing the first of the pair.
+        let u = clusters.len();
+        clusters.push((String::new(), vec![(i, li.max(0.0)), (j, lj.max(0.0))]));
+        let mut row: Vec<f64> = distances.iter().map(|r| (r[i] + r[j] - dij) / 2.0).collect();
+        row.push(0.0);
+        for (k, r) in distances.iter_mut().enumerate() {
+            r.push(row[k]);
+        }
+        distances.push(row);
+        active[a] = u;
+        active.remove(b);
+    }
+
+    // Join the remaining clusters at the root.
+    let mut tree = Tree::new();
+    let root = tree.root();
+    let lengths: Vec<f64> = match active.len() {
+        3 => {
+            let d = |x: usize, y: usize| distances[active[x]][active[y]];
+            vec![(d(0, 1) + d(0, 2) - d(1, 2)) / 2.0, (d(0, 1) + d(1, 2) - d(0, 2)) / 2.0, (d(0, 2) + d(1, 2) - d(0, 1)) / 2.0]
+        },
+        2 => vec![distances[active[0]][active[1]] / 2.0; 2],
+        _ => vec![0.0; active.len()],
+    };
+    for (&cluster, length) in active.iter().zip(lengths) {
+        add_cluster(&mut tree, root, &clusters, cluster, length.max(0.0));
+    }
+    tree
+}
+
+/// Recursively add a cluster, and its children, to the tree.
+fn add_cluster(tree: &mut Tree, parent: NodeId, clusters: &[(String, Vec<(usize, f64)>)], cluster: usize, length: f64) {
+    let id = tree.add_child(parent, &clusters[cluster].0, Some(length));
+    for &(child, length) in clusters[cluster].1.iter() {
+        add_cluster(tree, id, clusters, child, length);
+    }
+}
+
+/// Check if a character must be quoted in a Newick label.
+#[inline]
+fn is_special(c: u8) -> bool {
+    b"()[]',:; \t\r\n".contains(&c)
+}
+
+/// Write a label, quoting it if required.
+fn write_label(text: &mut String, label: &str) {
+    match label.bytes().any(is_special) {
+        true  => {
+            text.push('\'');
+            text.push_str(&label.replace('\'', "''"));
+            text.push('\'');
+        },
+        false => text.push_str(label),
+    }
+}
+
+/// Recursive-descent parser for the Newick format.
+struct NewickParser<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> NewickParser<'a> {
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.index).cloned()
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek();
+        self.index += 1;
+        c
+    }
+
+    /// Skip whitespace and bracketed comments.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b'[' => {
+                    while let Some(c) = self.next() {
+                        if c == b']' {
+                            break;
+                        }
+                    }
+                },
+                c if c.is_ascii_whitespace() => self.index += 1,
+                _    => break,
+            }
+        }
+    }
+
+    /// Parse a node, with its children, label and branch length.
+    fn parse_subtree(&mut self, tree: &mut Tree, id: NodeId) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(b'(') {
+            self.index += 1;
+            loop {
+                let child = tree.add_child(id, "", None);
+                self.parse_subtree(tree, child)?;
+                self.skip_whitespace();
+                match self.next() {
+                    Some(b',') => continue,
+                    Some(b')') => break,
+                    _          => return Err(From::from(ErrorKind::InvalidInput)),
+                }
+            }
+        }
+
+        self.skip_whitespace();
+        tree.nodes[id].label = self.parse_label()?;
+        self.skip_whitespace();
+        if self.peek() == Some(b':') {
+            self.index += 1;
+            self.skip_whitespace();
+            let start = self.index;
+            while let Some(c) = self.peek() {
+                if is_special(c) {
+                    break;
+                }
+                self.index += 1;
+            }
+            let length = ::std::str::from_utf8(&self.bytes[start..self.index])?;
+            tree.nodes[id].length = Some(length.parse::<f64>()?);
+        }
+        Ok(())
+    }
+
+    /// Parse an optional, possibly quoted, label.
+    fn parse_label(&mut self) -> Result<String> {
+        let mut label = vec![];
+        if self.peek() == Some(b'\'') {
+            self.index += 1;
+            loop {
+                match self.next() {
+                    Some(b'\'') if self.peek() == Some(b'\'') => {
+                        self.index += 1;
+                        label.push(b'\'');
+                    },
+                    Some(b'\'') => break,
+                    Some(c)     => label.push(c),
+                    None        => return Err(From::from(ErrorKind::UnexpectedEof)),
+                }
+            }
+        } else {
+            while let Some(c) = self.peek() {
+                if is_special(c) {
+                    break;
+                }
+                label.push(c);
+                self.index += 1;
+            }
+        }
+        Ok(String::from_utf8(label)?)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_test() {
+        let mut tree = Tree::new();
+        assert!(tree.is_empty());
+        let root = tree.root();
+        let a = tree.add_child(root, "A", Some(0.1));
+        let inner = tree.add_child(root, "", Some(0.5));
+        let b = tree.add_child(inner, "B", Some(0.2));
+        let c = tree.add_child(inner, "C", None);
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.preorder(), vec![root, a, inner, b, c]);
+        assert_eq!(tree.postorder(), vec![a, b, c, inner, root]);
+        assert_eq!(tree.leaves(), vec![a, b, c]);
+        assert_eq!(tree.ancestors(b), vec![inner, root]);
+        assert_approx_eq!(tree.distance_from_root(b), 0.7, 1e-12);
+        assert_eq!(tree.find("C"), Some(c));
+        assert_eq!(tree.find("D"), None);
+        assert_eq!(tree.node(b).parent, Some(inner));
+    }
+
+    #[test]
+    fn newick_test() {
+        let text = "((Homo_sapiens:0.1,'Pan troglodytes':0.2)Hominini:0.3,[outgroup] Mus_musculus:1.5)root;";
+        let tree = Tree::from_newick(text).unwrap();
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.node(tree.root()).label, "root");
+        let labels: Vec<&str> = tree.leaves().into_iter().map(|id| tree.node(id).label.as_str()).collect();
+        assert_eq!(labels, vec!["Homo_sapiens", "Pan troglodytes", "Mus_musculus"]);
+        let hominini = tree.find("Hominini").unwrap();
+        assert_eq!(tree.node(hominini).length, Some(0.3));
+        assert_eq!(tree.node(hominini).children.len(), 2);
+        assert!(Tree::from_newick("(A,B);").unwrap().node(0).label.is_empty());
+
+        // Writer round-trip.
+        let text = "((A:0.1,'B C':0.2)AB:0.3,'D''s':1.5);";
+        let tree = Tree::from_newick(text).unwrap();
+        assert_eq!(tree.to_newick(), text);
+        assert_eq!(Tree::from_newick(&tree.to_newick()).unwrap(), tree);
+        assert_eq!(Tree::new().to_newick(), ";");
+    }
+
+    #[test]
+    fn neighbor_joining_test() {
+        let labels = vec!["a", "b", "c", "d", "e"];
+        let mut matrix = DistanceMatrix::new(labels.into_iter().map(String::from).collect());
+        let values = [[0.0, 5.0, 9.0, 9.0, 8.0], [5.0, 0.0, 10.0, 10.0, 9.0], [9.0, 10.0, 0.0, 8.0, 7.0], [9.0, 10.0, 8.0, 0.0, 3.0], [8.0, 9.0, 7.0, 3.0, 0.0]];
+        for (i, row) in values.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                matrix.set(i, j, value);
+            }
+        }
+        let tree = neighbor_joining(&matrix);
+        assert_eq!(tree.to_newick(), "(((a:2,b:3):3,c:4):2,d:2,e:1);");
+
+        let mut matrix = DistanceMatrix::new(vec![String::from("x"), String::from("y")]);
+        matrix.set(0, 1, 0.5);
+        assert_eq!(neighbor_joining(&matrix).to_newick(), "(x:0.25,y:0.25);");
+        assert_eq!(neighbor_joining(&DistanceMatrix::new(vec![])).to_newick(), ";");
+    }
+
+    #[test]
+    fn invalid_newick_test() {
+        assert!(Tree::from_newick("(A,B)").is_err());
+        assert!(Tree::from_newick("(A,B;").is_err());
+        assert!(Tree::from_newick("(A:x,B);").is_err());
+        assert!(Tree::from_newick("('A,B);").is_err());
+        assert!(Tree::from_newick("(A,B);C").is_err());
+        assert!(Tree::from_newick("(Homo sapiens,B);").is_err());
+    }
+}
@@ -2,11 +2,178 @@
 //!
 //! Masses are valid for low-pH LC-MS.
 
-use super::mass::SequenceMass;
+use super::mass::{Composition, Element, ElementTable, SequenceMass};
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 
 /// Valid aminoacid 1-letter codes.
 pub const MONOMERS: &'static str = "ABCDEFGHIJKLMNPQRSTVWXYZ";
 
+/// Get the elemental formula of an internal (dehydrated) residue.
+///
+/// Returns `None` for ambiguous or unknown residues.
+pub(crate) fn residue_formula(residue: u8) -> Option<&'static [(Element, u32)]> {
+    use super::mass::Element::*;
+
+    match residue.to_ascii_uppercase() {
+        b'A' => Some(&[(C, 3), (H, 5), (N, 1), (O, 1)]),
+        b'C' => Some(&[(C, 3), (H, 5), (N, 1), (O, 1), (S, 1)]),
+        b'D' => Some(&[(C, 4), (H, 5), (N, 1), (O, 3)]),
+        b'E' => Some(&[(C, 5), (H, 7), (N, 1), (O, 3)]),
+        b'F' => Some(&[(C, 9), (H, 9), (N, 1), (O, 1)]),
+        b'G' => Some(&[(C, 2), (H, 3), (N, 1), (O, 1)]),
+        b'H' => Some(&[(C, 6), (H, 7), (N, 3), (O, 1)]),
+        b'I' => Some(&[(C, 6), (H, 11), (N, 1), (O, 1)]),
+        b'K' => Some(&[(C, 6), (H, 12), (N, 2), (O, 1)]),
+        b'L' => Some(&[(C, 6), (H, 11), (N, 1), (O, 1)]),
+        b'M' => Some(&[(C, 5), (H, 9), (N, 1), (O, 1), (S, 1)]),
+        b'N' => Some(&[(C, 4), (H, 6), (N, 2), (O, 2)]),
+        b'P' => Some(&[(C, 5), (H, 7), (N, 1), (O, 1)]),
+        b'Q' => Some(&[(C, 5), (H, 8), (N, 2), (O, 2)]),
+        b'R' => Some(&[(C, 6), (H, 12), (N, 4), (O, 1)]),
+        b'S' => Some(&[(C, 3), (H, 5), (N, 1), (O, 2)]),
+        b'T' => Some(&[(C, 4), (H, 7), (N, 1), (O, 2)]),
+        b'U' => Some(&[(C, 3), (H, 5), (N, 1), (O, 1), (Se, 1)]),
+        b'V' => Some(&[(C, 5), (H, 9), (N, 1), (O, 1)]),
+        b'W' => Some(&[(C, 11), (H, 10), (N, 2), (O, 1)]),
+        b'Y' => Some(&[(C, 9), (H, 9), (N, 1), (O, 2)]),
+        _    => None,
+    }
+}
+
+/// Stable isotope label, shifting the mass of a single residue.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResidueLabel {
+    /// Aminoacid 1-letter code of the labeled residue.
+    pub residue: u8,
+    /// Mass shift of the labeled residue relative to the unlabeled residue.
+    pub delta: f64,
+}
+
+impl ResidueLabel {
+    /// SILAC medium lysine, Lys4 (4,4,5,5-D4).
+    pub const LYS4: ResidueLabel = ResidueLabel { residue: b'K', delta: 4.0251069836 };
+    /// SILAC heavy lysine, Lys6 (13C6).
+    pub const LYS6: ResidueLabel = ResidueLabel { residue: b'K', delta: 6.0201290268 };
+    /// SILAC heavy lysine, Lys8 (13C6 15N2).
+    pub const LYS8: ResidueLabel = ResidueLabel { residue: b'K', delta: 8.0141988132 };
+    /// SILAC medium arginine, Arg6 (13C6).
+    pub const ARG6: ResidueLabel = ResidueLabel { residue: b'R', delta: 6.0201290268 };
+    /// SILAC heavy arginine, Arg10 (13C6 15N4).
+    pub const ARG10: ResidueLabel = ResidueLabel { residue: b'R', delta: 10.0082686 };
+
+    /// SILAC medium labels (Lys4, Arg6).
+    pub const SILAC_MEDIUM: [ResidueLabel; 2] = [ResidueLabel::LYS4, ResidueLabel::ARG6];
+    /// SILAC heavy labels (Lys8, Arg10).
+    pub const SILAC_HEAVY: [ResidueLabel; 2] = [ResidueLabel::LYS8, ResidueLabel::ARG10];
+}
+
+/// Configurable protein mass calculator.
+///
+/// Unlike `MonoisotopicMass` and `AverageMass`, which use fixed
+/// constants, residue masses are derived from an `ElementTable`.
+/// Both may be shifted by stable isotope labels, either stored in
+/// the table or passed to the `SequenceMass` labeled methods.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProteinMassTable {
+    elements: ElementTable,
+    termini: f64,
+    residues: Vec<f64>,
+}
+
+impl ProteinMassTable {
+    /// Create mass table from an element table.
+    pub fn new(elements: ElementTable) -> Self {
+        let termini = 2.0 * elements.mass(Element::H) + elements.mass(Element::O);
+        let mut residues = vec![0.0; 128];
+        for residue in 0..128u8 {
+            if let Some(composition) = Composition::residue(residue) {
+                residues[residue as usize] = composition.mass(&elements);
+            }
+        }
+
+        ProteinMassTable {
+            elements: elements,
+            termini: termini,
+            residues: residues,
+        }
+    }
+
+    /// Create mass table from monoisotopic element masses.
+    #[inline]
+    pub fn monoisotopic() -> Self {
+        ProteinMassTable::new(ElementTable::monoisotopic())
+    }
+
+    /// Create mass table from average element masses.
+    #[inline]
+    pub fn average() -> Self {
+        ProteinMassTable::new(ElementTable::average())
+    }
+
+    /// Get the element table used to derive the residue masses.
+    #[inline]
+    pub fn elements(&self) -> &ElementTable {
+        &self.elements
+    }
+
+    /// Override the mass of a residue, ignoring case.
+    pub fn set_residue_mass(&mut self, residue: u8, mass: f64) {
+        if residue.is_ascii() {
+            self.residues[residue.to_ascii_uppercase() as usize] = mass;
+            self.residues[residue.to_ascii_lowercase() as usize] = mass;
+        }
+    }
+
+    /// Apply a stable isotope label, consuming and returning the table.
+    #[inline]
+    pub fn with_label(mut self, label: ResidueLabel) -> Self {
+        let mass = self.residue_mass(label.residue) + label.delta;
+        self.set_residue_mass(label.residue, mass);
+        self
+    }
+
+    /// Apply stable isotope labels, consuming and returning the table.
+    #[inline]
+    pub fn with_labels(self, labels: &[ResidueLabel]) -> Self {
+        labels.iter().fold(self, |table, label| table.with_label(*label))
+    }
+
+    /// Calculate the mass at the termini.
+    #[inline(always)]
+    pub fn termini_mass(&self) -> f64 {
+        self.termini
+    }
+
+    /// Calculate the mass of an internal residue.
+    #[inline]
+    pub fn residue_mass(&self, residue: u8) -> f64 {
+        match self.residues.get(residue as usize) {
+            Some(mass) => *mass,
+            None       => 0.0,
+        }
+    }
+
+    /// Calculate the mass of an monomer with N- and C-termini.
+    #[inline(always)]
+    pub fn monomer_mass(&self, residue: u8) -> f64 {
+        self.residue_mass(residue) + self.termini_mass()
+    }
+
+    /// Calculate the mass of a protein sequence.
+    #[inline]
+    pub fn internal_sequence_mass(&self, sequence: &[u8]) -> f64 {
+        sequence.iter().fold(0.0, |sum, x| sum + self.residue_mass(*x))
+    }
+
+    /// Calculate the mass of a protein sequence with N- or C-termini.
+    #[inline(always)]
+    pub fn total_sequence_mass(&self, sequence: &[u8]) -> f64 {
+        self.internal_sequence_mass(sequence) + self.termini_mass()
+    }
+}
+
 /// Calculate protein mass using only high-resolution masses from monoisotopic elements.
 pub struct MonoisotopicMass;
 
@@ -132,6 +299,38 @@ impl SequenceMass for AverageMass {
     }
 }
 
+// CHECKSUM
+
+/// Generator polynomial for CRC-64-ISO, in reversed bit order.
+#[cfg(feature = "std")]
+const CRC64_POLYNOMIAL: u64 = 0xD800000000000000;
+
+#[cfg(feature = "std")]
+lazy_static! {
+    static ref CRC64_TABLE: [u64; 256] = {
+        let mut table = [0u64; 256];
+        for (i, value) in table.iter_mut().enumerate() {
+            let mut part = i as u64;
+            for _ in 0..8 {
+                part = match part & 1 {
+                    1 => (part >> 1) ^ CRC64_POLYNOMIAL,
+                    _ => part >> 1,
+                };
+            }
+            *value = part;
+        }
+        table
+    };
+}
+
+/// Calculate the CRC-64-ISO checksum of a sequence, as used by UniProt.
+#[cfg(feature = "std")]
+pub fn sequence_checksum(sequence: &[u8]) -> u64 {
+    sequence.iter().fold(0, |crc, &c| {
+        CRC64_TABLE[((crc ^ c as u64) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
 // TESTS
 // -----
 
@@ -167,6 +366,15 @@ mod tests {
         one_letter_mass::<AverageMass>();
     }
 
+    // CHECKSUM
+
+    #[test]
+    fn sequence_checksum_test() {
+        let gapdh = b"MVKVGVNGFGRIGRLVTRAAFNSGKVDVVAINDPFIDLHYMVYMFQYDSTHGKFHGTVKAENGKLVINGKAITIFQERDPANIKWGDAGAEYVVESTGVFTTMEKAGAHLKGGAKRVIISAPSADAPMFVMGVNHEKYDNSLKIVSNASCTTNCLAPLAKVIHDHFGIVEGLMTTVHAITATQKTVDGPSGKLWRDGRGAAQNIIPASTGAAKAVGKVIPELNGKLTGMAFRVPTPNVSVVDLTCRLEKAAKYDDIKKVVKQASEGPLKGILGYTEDQVVSCDFNSATHSSTFDAGAGIALNDHFVKLISWYDNEFGYSNRVVDLMVHMASKE";
+        assert_eq!(sequence_checksum(gapdh), 0x346DB9B59DF3C1DF);
+        assert_eq!(sequence_checksum(b""), 0);
+    }
+
     // SEQUENCE
 
     #[test]
@@ -206,4 +414,75 @@ mod tests {
         assert_approx_eq!(T::internal_sequence_mass(peptide), 2527.067977,  0.001);
         assert_approx_eq!(T::total_sequence_mass(peptide),  2545.0785414, 0.001);
     }
+
+    // TABLE
+
+    #[test]
+    fn mass_table_test() {
+        // derived residue masses should match the hard-coded constants
+        let mono = ProteinMassTable::monoisotopic();
+        let avg = ProteinMassTable::average();
+        for a in MONOMERS.bytes() {
+            assert_approx_eq!(mono.residue_mass(a), MonoisotopicMass::residue_mass(a), 1e-6);
+            assert_approx_eq!(avg.residue_mass(a), AverageMass::residue_mass(a), 1e-3);
+            assert_eq!(mono.residue_mass(a), mono.residue_mass(a.to_ascii_lowercase()));
+        }
+        assert_approx_eq!(mono.termini_mass(), MonoisotopicMass::termini_mass(), 1e-6);
+        assert_approx_eq!(avg.termini_mass(), AverageMass::termini_mass(), 1e-3);
+        assert_eq!(mono.residue_mass(0xFF), 0.0);
+    }
+
+    #[test]
+    fn label_mass_table_test() {
+        let light = ProteinMassTable::monoisotopic();
+        let heavy = ProteinMassTable::monoisotopic().with_labels(&ResidueLabel::SILAC_HEAVY);
+        let medium = ProteinMassTable::monoisotopic().with_labels(&ResidueLabel::SILAC_MEDIUM);
+
+        let peptide = b"SAMPLER";
+        let delta = heavy.total_sequence_mass(peptide) - light.total_sequence_mass(peptide);
+        assert_approx_eq!(delta, 10.008269, 1e-5);
+
+        let peptide = b"SAMPLEKk";
+        let delta = heavy.total_sequence_mass(peptide) - light.total_sequence_mass(peptide);
+        assert_approx_eq!(delta, 16.028398, 1e-5);
+        let delta = medium.total_sequence_mass(peptide) - light.total_sequence_mass(peptide);
+        assert_approx_eq!(delta, 8.050214, 1e-5);
+    }
+
+    #[test]
+    fn label_sequence_mass_test() {
+        pub type A = AverageMass;
+        pub type M = MonoisotopicMass;
+
+        let labels = ResidueLabel::SILAC_HEAVY;
+        assert_eq!(M::labeled_residue_mass(b'S', &labels), M::residue_mass(b'S'));
+        assert_approx_eq!(M::labeled_residue_mass(b'k', &labels), M::residue_mass(b'K') + 8.0141988132, 1e-9);
+        assert_eq!(M::labeled_total_sequence_mass(b"SAMPLER", &[]), M::total_sequence_mass(b"SAMPLER"));
+
+        // labeled constants should match the labeled mass tables
+        let mono = ProteinMassTable::monoisotopic().with_labels(&labels);
+        let avg = ProteinMassTable::average().with_labels(&labels);
+        let peptide = b"SAMPLEKk";
+        assert_approx_eq!(M::labeled_total_sequence_mass(peptide, &labels), mono.total_sequence_mass(peptide), 1e-5);
+        assert_approx_eq!(A::labeled_total_sequence_mass(peptide, &labels), avg.total_sequence_mass(peptide), 1e-2);
+
+        // stacked labels on the same residue are summed
+        let stacked = [ResidueLabel::LYS4, ResidueLabel::LYS4];
+        let delta = M::labeled_internal_sequence_mass(b"K", &stacked) - M::internal_sequence_mass(b"K");
+        assert_approx_eq!(delta, 8.050214, 1e-5);
+    }
+
+    #[test]
+    fn element_mass_table_test() {
+        // uniform 15N labeling
+        let elements = ElementTable::monoisotopic().with_mass(Element::N, 15.0001088982);
+        let labeled = ProteinMassTable::new(elements);
+        let light = ProteinMassTable::monoisotopic();
+
+        // SAMPLER contains 10 nitrogen atoms
+        let peptide = b"SAMPLER";
+        let delta = labeled.total_sequence_mass(peptide) - light.total_sequence_mass(peptide);
+        assert_approx_eq!(delta, 9.970349, 1e-5);
+        assert_eq!(labeled.elements().mass(Element::N), 15.0001088982);
+    }
 }
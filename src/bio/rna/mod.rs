@@ -1,4 +1,6 @@
 //! General purpose RNA routines.
 
+pub mod structure;
+
 /// Valid nucleotide 1-letter codes.
 pub const MONOMERS: &'static str = "ACGU";
@@ -0,0 +1,324 @@
+//! RNA secondary structures and base-pair maximization folding.
+//!
+//! Structures are read from dot-bracket notation, including the WUSS
+//! notation of Rfam consensus structures, where bracket types other
+//! than "()" may denote pseudoknots.
+
+use util::{ErrorKind, Result};
+
+/// Opening and closing brackets, by nesting level on export.
+const BRACKETS: [(u8, u8); 4] = [(b'(', b')'), (b'[', b']'), (b'{', b'}'), (b'<', b'>')];
+
+/// Determine if a character denotes an unpaired position.
+#[inline]
+fn is_unpaired(c: u8) -> bool {
+    b".,_-:~".contains(&c)
+}
+
+/// Secondary structure of an RNA sequence, as a table of base pairs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SecondaryStructure {
+    partners: Vec<Option<usize>>,
+}
+
+impl SecondaryStructure {
+    /// Create new structure without base pairs.
+    #[inline]
+    pub fn new(length: usize) -> Self {
+        SecondaryStructure { partners: vec![None; length] }
+    }
+
+    /// Create new structure from the base pairs.
+    ///
+    /// Returns an error if a position is out of bounds, paired with
+    /// itself, or paired more than once.
+    pub fn from_pairs(length: usize, pairs: &[(usize, usize)]) -> Result<Self> {
+        let mut structure = SecondaryStructure::new(length);
+        for &(i, j) in pairs {
+            structure.add_pair(i, j)?;
+        }
+        Ok(structure)
+    }
+
+    /// Parse the structure from dot-bracket notation.
+    ///
+    /// Brackets are "()", "[]", "{}" or "<>", and unpaired positions
+    /// are any of ".,_-:~". Returns an error for unbalanced brackets
+    /// or unknown characters.
+    pub fn from_dot_bracket(text: &[u8]) -> Result<Self> {
+        let mut structure = SecondaryStructure::new(text.len());
+        let mut stacks: Vec<Vec<usize>> = vec![vec![]; BRACKETS.len()];
+        for (i, &c) in text.iter().enumerate() {
+            if is_unpaired(c) {
+                continue;
+            } else if let Some(level) = BRACKETS.iter().position(|b| b.0 == c) {
+                stacks[level].push(i);
+            } else {
+                let level = none_to_error!(BRACKETS.iter().position(|b| b.1 == c), InvalidInput);
+                let j = none_to_error!(stacks[level].pop(), InvalidInput);
+                structure.add_pair(j, i)?;
+            }
+        }
+        bool_to_error!(stacks.iter().all(|s| s.is_empty()), InvalidInput);
+
+        Ok(structure)
+    }
+
+    /// Export the structure to dot-bracket notation.
+    ///
+    /// Nested pairs use "()", and pairs crossing other pairs use the
+    /// next bracket type which does not cross, for pseudoknots.
+    /// Returns an error if more than 4 bracket types are required.
+    pub fn to_dot_bracket(&self) -> Result<Vec<u8>> {
+        let mut text = vec![b'.'; self.len()];
+        let mut levels: Vec<Vec<(usize, usize)>> = vec![vec![]; BRACKETS.len()];
+        for (i, j) in self.pairs() {
+            let crosses = |&(k, l): &(usize, usize)| (k < i && i < l && l < j) || (i < k && k < j && j < l);
+            let level = none_to_error!(levels.iter().position(|pairs| !pairs.iter().any(&crosses)), InvalidInput);
+            levels[level].push((i, j));
+            text[i] = BRACKETS[level].0;
+            text[j] = BRACKETS[level].1;
+        }
+        Ok(text)
+    }
+
+    /// Get the number of positions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.partners.len()
+    }
+
+    /// Check if the structure has no positions.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.partners.is_empty()
+    }
+
+    /// Get the position paired with a position, if any.
+    #[inline]
+    pub fn partner(&self, i: usize) -> Option<usize> {
+        self.partners[i]
+    }
+
+    /// Get the base pairs, as `(i, j)` with `i < j`, sorted by `i`.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        self.partners.iter()
+            .enumerate()
+            .filter_map(|(i, p)| match *p {
+                Some(j) if i < j => Some((i, j)),
+                _                => None,
+            })
+            .collect()
+    }
+
+    /// Add a base pair between two positions.
+    pub fn add_pair(&mut self, i: usize, j: usize) -> Result<()> {
+        bool_to_error!(i != j && i < self.len() && j < self.len(), InvalidInput);
+        bool_to_error!(self.partners[i].is_none() && self.partners[j].is_none(), InvalidInput);
+        self.partners[i] = Some(j);
+        self.partners[j] = Some(i);
+        Ok(())
+    }
+
+    /// Determine if any base pairs cross, forming a pseudoknot.
+    pub fn is_pseudoknotted(&self) -> bool {
+        let mut stack = vec![];
+        for (i, p) in self.partners.iter().enumerate() {
+            match *p {
+                Some(j) if i < j                => stack.push(j),
+                Some(_) if stack.pop() != Some(i) => return true,
+                _                               => (),
+            }
+        }
+        false
+    }
+}
+
+// FOLDING
+
+/// Scores for base pairs when folding a sequence.
+pub trait PairScore {
+    /// Get the score of a base pair, or `None` if the bases cannot pair.
+    fn score(&self, x: u8, y: u8) -> Option<f64>;
+}
+
+/// Watson-Crick and GU wobble base pairs.
+const CANONICAL_PAIRS: [&'static [u8; 2]; 6] = [b"AU", b"UA", b"GC", b"CG", b"GU", b"UG"];
+
+/// Determine if two nucleotides form a canonical (Watson-Crick or GU wobble) pair.
+///
+/// Thymine is treated as uracil, ignoring case.
+pub fn is_canonical_pair(x: u8, y: u8) -> bool {
+    let normalize = |c: u8| match c.to_ascii_uppercase() {
+        b'T' => b'U',
+        c    => c,
+    };
+    CANONICAL_PAIRS.contains(&&[normalize(x), normalize(y)])
+}
+
+/// Score each canonical base pair as 1, to maximize the number of pairs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BasePairCount;
+
+impl PairScore for BasePairCount {
+    #[inline]
+    fn score(&self, x: u8, y: u8) -> Option<f64> {
+        match is_canonical_pair(x, y) {
+            true  => Some(1.0),
+            false => None,
+        }
+    }
+}
+
+/// Score each canonical base pair by its hydrogen bonds (GC 3, AU 2, GU 1).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HydrogenBonds;
+
+impl PairScore for HydrogenBonds {
+    fn score(&self, x: u8, y: u8) -> Option<f64> {
+        if !is_canonical_pair(x, y) {
+            return None;
+        }
+        let x = x.to_ascii_uppercase();
+        let y = y.to_ascii_uppercase();
+        match (x, y) {
+            (b'G', b'C') | (b'C', b'G') => Some(3.0),
+            (b'G', _) | (_, b'G')       => Some(1.0),
+            _                           => Some(2.0),
+        }
+    }
+}
+
+/// Fold a sequence by maximizing the base-pair score (Nussinov algorithm).
+///
+/// Hairpin loops contain at least `min_loop` unpaired bases. Returns
+/// the structure and its score, without pseudoknots. Runs in O(n^3)
+/// time and O(n^2) memory.
+pub fn nussinov<S: PairScore>(sequence: &[u8], min_loop: usize, scoring: &S) -> (SecondaryStructure, f64) {
+    let n = sequence.len();
+    let mut structure = SecondaryStructure::new(n);
+    if n == 0 {
+        return (structure, 0.0);
+    }
+
+    // `scores[i * n + j]` is the best score of the subsequence `i..=j`.
+    let mut scores = vec![0.0f64; n * n];
+    let pair = |i: usize, j: usize| scoring.score(sequence[i], sequence[j]);
+    for span in min_loop+1..n {
+        for i in 0..n-span {
+            let j = i + span;
+            let mut best = scores[(i + 1) * n + j].max(scores[i * n + j - 1]);
+            if let Some(s) = pair(i, j) {
+                best = best.max(scores[(i + 1) * n + j - 1] + s);
+            }
+            for k in i+1..j {
+                best = best.max(scores[i * n + k] + scores[(k + 1) * n + j]);
+            }
+            scores[i * n + j] = best;
+        }
+    }
+
+    // Traceback, preferring unpaired bases, then pairs, then bifurcations.
+    let mut stack = vec![(0, n - 1)];
+    while let Some((i, j)) = stack.pop() {
+        if i >= j {
+            continue;
+        }
+        let score = scores[i * n + j];
+        if score == scores[(i + 1) * n + j] {
+            stack.push((i + 1, j));
+        } else if score == scores[i * n + j - 1] {
+            stack.push((i, j - 1));
+        } else if j - i > min_loop && pair(i, j).map(|s| scores[(i + 1) * n + j - 1] + s) == Some(score) {
+            structure.partners[i] = Some(j);
+            structure.partners[j] = Some(i);
+            stack.push((i + 1, j - 1));
+        } else {
+            let k = (i+1..j)
+                .find(|&k| scores[i * n + k] + scores[(k + 1) * n + j] == score)
+                .expect("Nussinov traceback must find a bifurcation.");
+            stack.push((i, k));
+            stack.push((k + 1, j));
+        }
+    }
+
+    (structure, scores[n - 1])
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_bracket_test() {
+        let structure = SecondaryStructure::from_dot_bracket(b"((..((...))..))").unwrap();
+        assert_eq!(structure.len(), 15);
+        assert_eq!(structure.pairs(), vec![(0, 14), (1, 13), (4, 10), (5, 9)]);
+        assert_eq!(structure.partner(14), Some(0));
+        assert_eq!(structure.partner(2), None);
+        assert!(!structure.is_pseudoknotted());
+        assert_eq!(structure.to_dot_bracket().unwrap(), b"((..((...))..))".to_vec());
+
+        // WUSS notation.
+        let structure = SecondaryStructure::from_dot_bracket(b"<<<__>>>,,((--))::").unwrap();
+        assert_eq!(structure.to_dot_bracket().unwrap(), b"(((..)))..((..))..".to_vec());
+
+        // Pseudoknot.
+        let structure = SecondaryStructure::from_dot_bracket(b"((..[[..))..]]").unwrap();
+        assert!(structure.is_pseudoknotted());
+        assert_eq!(structure.pairs(), vec![(0, 9), (1, 8), (4, 13), (5, 12)]);
+        assert_eq!(structure.to_dot_bracket().unwrap(), b"((..[[..))..]]".to_vec());
+
+        assert!(SecondaryStructure::from_dot_bracket(b"((..)").is_err());
+        assert!(SecondaryStructure::from_dot_bracket(b"(..))").is_err());
+        assert!(SecondaryStructure::from_dot_bracket(b"(..]").is_err());
+        assert!(SecondaryStructure::from_dot_bracket(b"(.x.)").is_err());
+        assert!(SecondaryStructure::from_dot_bracket(b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn from_pairs_test() {
+        let structure = SecondaryStructure::from_pairs(6, &[(0, 5), (1, 4)]).unwrap();
+        assert_eq!(structure.to_dot_bracket().unwrap(), b"((..))".to_vec());
+        assert!(SecondaryStructure::from_pairs(6, &[(0, 6)]).is_err());
+        assert!(SecondaryStructure::from_pairs(6, &[(0, 5), (5, 2)]).is_err());
+        assert!(SecondaryStructure::from_pairs(6, &[(3, 3)]).is_err());
+    }
+
+    #[test]
+    fn pair_score_test() {
+        assert!(is_canonical_pair(b'G', b'u'));
+        assert!(is_canonical_pair(b'A', b'T'));
+        assert!(!is_canonical_pair(b'A', b'G'));
+        assert_eq!(BasePairCount.score(b'A', b'U'), Some(1.0));
+        assert_eq!(BasePairCount.score(b'A', b'A'), None);
+        assert_eq!(HydrogenBonds.score(b'c', b'G'), Some(3.0));
+        assert_eq!(HydrogenBonds.score(b'U', b'A'), Some(2.0));
+        assert_eq!(HydrogenBonds.score(b'U', b'G'), Some(1.0));
+        assert_eq!(HydrogenBonds.score(b'U', b'C'), None);
+    }
+
+    #[test]
+    fn nussinov_test() {
+        let (structure, score) = nussinov(b"GGGAAAUCC", 3, &BasePairCount);
+        assert_eq!(score, 3.0);
+        assert_eq!(structure.to_dot_bracket().unwrap(), b"(((...)))".to_vec());
+
+        let (structure, score) = nussinov(b"GGGGAAAACCCCAUUUUGAAAAUAAAA", 3, &HydrogenBonds);
+        assert!(!structure.is_pseudoknotted());
+        let sequence = b"GGGGAAAACCCCAUUUUGAAAAUAAAA";
+        let total: f64 = structure.pairs().iter()
+            .map(|&(i, j)| HydrogenBonds.score(sequence[i], sequence[j]).unwrap())
+            .sum();
+        assert_eq!(total, score);
+        assert!(structure.pairs().iter().all(|&(i, j)| j - i > 3));
+
+        let (structure, score) = nussinov(b"AAAA", 3, &BasePairCount);
+        assert_eq!(score, 0.0);
+        assert!(structure.pairs().is_empty());
+        assert_eq!(nussinov(b"", 3, &BasePairCount).1, 0.0);
+    }
+}
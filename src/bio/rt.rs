@@ -0,0 +1,250 @@
+//! Peptide retention time prediction.
+//!
+//! Predictors produce a retention score for a peptide, which may be
+//! linearly calibrated against observed retention times, to flag
+//! peptide-spectrum matches eluting far from the expected time.
+
+/// Model to predict the retention of a peptide.
+pub trait RetentionPredictor {
+    /// Predict the retention of a peptide.
+    fn predict(&self, peptide: &[u8]) -> f64;
+
+    /// Get the difference between the observed and predicted retention.
+    #[inline]
+    fn residual(&self, peptide: &[u8], observed: f64) -> f64 {
+        observed - self.predict(peptide)
+    }
+
+    /// Check if the observed retention is within `tolerance` of the prediction.
+    #[inline]
+    fn is_consistent(&self, peptide: &[u8], observed: f64, tolerance: f64) -> bool {
+        self.residual(peptide, observed).abs() <= tolerance
+    }
+}
+
+// HYDROPHOBICITY
+
+/// Retention coefficients for residues, and at the N-terminus, by 1-letter code.
+///
+/// Approximate the reversed-phase (C18, TFA) coefficients of SSRCalc.
+/// Unknown residues do not contribute to the retention.
+const RETENTION_COEFFICIENTS: [(u8, f64, f64); 20] = [
+    (b'A',  0.8, -1.5),
+    (b'C',  0.7,  4.0),
+    (b'D', -0.5,  9.0),
+    (b'E',  0.0,  4.5),
+    (b'F', 10.5, -7.0),
+    (b'G', -0.9,  5.0),
+    (b'H', -1.3,  4.0),
+    (b'I',  8.4, -8.0),
+    (b'K', -1.9,  4.6),
+    (b'L',  9.6, -9.0),
+    (b'M',  5.8, -5.5),
+    (b'N', -1.2,  5.0),
+    (b'P',  0.2,  4.0),
+    (b'Q', -0.9,  5.0),
+    (b'R', -1.3,  8.0),
+    (b'S', -0.8,  5.0),
+    (b'T',  0.2,  5.0),
+    (b'V',  5.0, -5.5),
+    (b'W', 11.0, -4.0),
+    (b'Y',  4.0, -3.0),
+];
+
+/// Weights of the N-terminal coefficients for the first 3 residues.
+const NTERM_WEIGHTS: [f64; 3] = [0.42, 0.22, 0.05];
+
+/// Simple SSRCalc-like hydrophobicity model.
+///
+/// Predicts the hydrophobicity index of a peptide from the sum of the
+/// residue retention coefficients, with corrections for the N-terminal
+/// residues, short and long peptides, and highly hydrophobic peptides.
+/// The index is roughly proportional to the acetonitrile percentage at
+/// elution, and should be calibrated to predict retention times.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HydrophobicityModel {
+    residue: [f64; 26],
+    nterm: [f64; 26],
+}
+
+impl HydrophobicityModel {
+    /// Create model with the default retention coefficients.
+    pub fn new() -> Self {
+        let mut model = HydrophobicityModel {
+            residue: [0.0; 26],
+            nterm: [0.0; 26],
+        };
+        for &(residue, rc, nterm) in RETENTION_COEFFICIENTS.iter() {
+            model.set_coefficients(residue, rc, nterm);
+        }
+        model
+    }
+
+    /// Set the retention coefficients for a residue.
+    ///
+    /// Non-alphabetic residues are ignored.
+    pub fn set_coefficients(&mut self, residue: u8, rc: f64, nterm: f64) {
+        if let Some(i) = residue_index(residue) {
+            self.residue[i] = rc;
+            self.nterm[i] = nterm;
+        }
+    }
+}
+
+impl Default for HydrophobicityModel {
+    #[inline]
+    fn default() -> Self {
+        HydrophobicityModel::new()
+    }
+}
+
+impl RetentionPredictor for HydrophobicityModel {
+    fn predict(&self, peptide: &[u8]) -> f64 {
+        let indexes: Vec<usize> = peptide.iter().filter_map(|&c| residue_index(c)).collect();
+        let sum: f64 = indexes.iter().map(|&i| self.residue[i]).sum();
+        let nterm: f64 = indexes.iter()
+            .zip(NTERM_WEIGHTS.iter())
+            .map(|(&i, &w)| w * self.nterm[i])
+            .sum();
+
+        let n = indexes.len() as f64;
+        let length = if n < 10.0 {
+            1.0 - 0.027 * (10.0 - n)
+        } else if n > 20.0 {
+            1.0 - 0.014 * (n - 20.0)
+        } else {
+            1.0
+        };
+
+        let index = length * (sum + nterm);
+        match index < 38.0 {
+            true  => index,
+            false => index - 0.3 * (index - 38.0),
+        }
+    }
+}
+
+/// Get the coefficient index for a residue.
+#[inline]
+fn residue_index(residue: u8) -> Option<usize> {
+    match residue.is_ascii_alphabetic() {
+        true  => Some((residue.to_ascii_uppercase() - b'A') as usize),
+        false => None,
+    }
+}
+
+// CALIBRATION
+
+/// Linear calibration of a predictor to observed retention times.
+///
+/// Predicts the retention time as `slope * score + intercept`, where
+/// `score` is the retention predicted by the underlying model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalibratedPredictor<P: RetentionPredictor> {
+    /// Uncalibrated retention predictor.
+    pub predictor: P,
+    /// Change in retention time per unit of predicted retention.
+    pub slope: f64,
+    /// Retention time at a predicted retention of 0.
+    pub intercept: f64,
+}
+
+impl<P: RetentionPredictor> CalibratedPredictor<P> {
+    /// Create calibrated predictor from known coefficients.
+    #[inline]
+    pub fn new(predictor: P, slope: f64, intercept: f64) -> Self {
+        CalibratedPredictor {
+            predictor: predictor,
+            slope: slope,
+            intercept: intercept,
+        }
+    }
+
+    /// Fit the calibration by least squares from peptides and observed retention times.
+    ///
+    /// Returns `None` with fewer than 2 peptides, or if all peptides
+    /// have the same predicted retention.
+    pub fn fit<'a, Iter>(predictor: P, observations: Iter) -> Option<Self>
+        where Iter: IntoIterator<Item = (&'a [u8], f64)>
+    {
+        let points: Vec<(f64, f64)> = observations.into_iter()
+            .map(|(peptide, rt)| (predictor.predict(peptide), rt))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        if sxx == 0.0 {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        Some(CalibratedPredictor::new(predictor, slope, mean_y - slope * mean_x))
+    }
+}
+
+impl<P: RetentionPredictor> RetentionPredictor for CalibratedPredictor<P> {
+    #[inline]
+    fn predict(&self, peptide: &[u8]) -> f64 {
+        self.slope * self.predictor.predict(peptide) + self.intercept
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hydrophobicity_model_test() {
+        let model = HydrophobicityModel::new();
+        assert_eq!(model.predict(b""), 0.0);
+
+        // hydrophobic residues are retained longer
+        let hydrophilic = model.predict(b"SAMPLEKR");
+        let hydrophobic = model.predict(b"LLWFLLWK");
+        assert!(hydrophobic > hydrophilic);
+
+        // case and unknown residues are ignored
+        assert_approx_eq!(model.predict(b"sampler"), model.predict(b"SAMPLER"));
+        assert_approx_eq!(model.predict(b"SAM-PLER"), model.predict(b"SAMPLER"));
+
+        // N-terminal residues are weighted
+        assert!(model.predict(b"DLLLLLLLLK") > model.predict(b"LLLLLLLLDK"));
+
+        // custom coefficients
+        let mut model = HydrophobicityModel::new();
+        model.set_coefficients(b'X', 2.0, 0.0);
+        assert_approx_eq!(model.predict(b"AAAAAAAAAXAA") - model.predict(b"AAAAAAAAAAA"), 2.0);
+    }
+
+    #[test]
+    fn calibrated_predictor_test() {
+        let model = HydrophobicityModel::new();
+        let peptides: Vec<&[u8]> = vec![b"SAMPLEKR", b"LLWFLLWK", b"DLLLLLLLLK", b"AAGGSSK"];
+        let observations: Vec<(&[u8], f64)> = peptides.iter()
+            .map(|&p| (p, 2.0 * model.predict(p) + 5.0))
+            .collect();
+
+        let calibrated = CalibratedPredictor::fit(model, observations).unwrap();
+        assert_approx_eq!(calibrated.slope, 2.0);
+        assert_approx_eq!(calibrated.intercept, 5.0);
+
+        let rt = calibrated.predict(b"SAMPLEKR");
+        assert_approx_eq!(calibrated.residual(b"SAMPLEKR", rt + 1.0), 1.0);
+        assert!(calibrated.is_consistent(b"SAMPLEKR", rt + 1.0, 2.0));
+        assert!(!calibrated.is_consistent(b"SAMPLEKR", rt - 3.0, 2.0));
+
+        // degenerate calibrations
+        let model = HydrophobicityModel::new();
+        assert!(CalibratedPredictor::fit(model.clone(), vec![(&b"SAMPLEKR"[..], 1.0)]).is_none());
+        assert!(CalibratedPredictor::fit(model, vec![(&b"AK"[..], 1.0), (&b"AK"[..], 2.0)]).is_none());
+    }
+}
@@ -0,0 +1,272 @@
+//! Concurrency configuration shared by the parallel subsystems.
+//!
+//! Parallel algorithms consult a single configuration for the number
+//! of worker threads and the number of items processed per task, rather
+//! than each sizing its own pool. The process-wide configuration is set
+//! once with `Concurrency::set_global`, and an explicit configuration
+//! may be passed to `Concurrency::map` for a single operation:
+//!
+//! ```
+//! use bdb::concurrency::Concurrency;
+//!
+//! Concurrency { threads: 4, chunk_size: 256 }.set_global();
+//! let squares = Concurrency::global().map(&[1, 2, 3], |x| x * x);
+//! assert_eq!(squares, vec![1, 4, 9]);
+//! ```
+//!
+//! Results never depend on the configuration: work is split into
+//! chunks of consecutive items, and results are returned in input order.
+//!
+//! Long-running operations accept a `CancellationToken`, checked
+//! periodically, so a caller may abort them from another thread.
+//! Cancelled operations fail with `ErrorKind::Cancelled`.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use util::{ErrorKind, Result};
+
+/// Default number of items processed per task.
+pub const DEFAULT_CHUNK_SIZE: usize = 64;
+
+// Process-wide configuration, where 0 uses the default value.
+static GLOBAL_THREADS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_CHUNK_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the number of hardware threads, or 1 if unknown.
+#[inline]
+fn available_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// CANCELLATION
+
+/// Token to cancel long-running operations.
+///
+/// Clones share the same state, so the token may be cancelled from
+/// another thread, for example, a GUI event handler.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create new, uncancelled token.
+    #[inline]
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Request cancellation of the operations using the token.
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Determine if cancellation was requested.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return an error if cancellation was requested.
+    #[inline]
+    pub fn check(&self) -> Result<()> {
+        match self.is_cancelled() {
+            true  => Err(From::from(ErrorKind::Cancelled)),
+            false => Ok(()),
+        }
+    }
+}
+
+// CONCURRENCY
+
+/// Configuration of the worker threads for parallel algorithms.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Concurrency {
+    /// Maximum number of worker threads, including the calling thread.
+    pub threads: usize,
+    /// Number of consecutive items processed per task.
+    pub chunk_size: usize,
+}
+
+impl Concurrency {
+    /// Create new configuration using all hardware threads.
+    #[inline]
+    pub fn new() -> Self {
+        Concurrency {
+            threads: available_threads(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Create new configuration running on the calling thread only.
+    #[inline]
+    pub fn serial() -> Self {
+        Concurrency {
+            threads: 1,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Get the process-wide configuration.
+    pub fn global() -> Self {
+        let threads = GLOBAL_THREADS.load(Ordering::Relaxed);
+        let chunk_size = GLOBAL_CHUNK_SIZE.load(Ordering::Relaxed);
+        Concurrency {
+            threads: if threads == 0 { available_threads() } else { threads },
+            chunk_size: if chunk_size == 0 { DEFAULT_CHUNK_SIZE } else { chunk_size },
+        }
+    }
+
+    /// Set the process-wide configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread count or chunk size is 0.
+    pub fn set_global(&self) {
+        assert!(self.threads > 0, "thread count must be positive");
+        assert!(self.chunk_size > 0, "chunk size must be positive");
+        GLOBAL_THREADS.store(self.threads, Ordering::Relaxed);
+        GLOBAL_CHUNK_SIZE.store(self.chunk_size, Ordering::Relaxed);
+    }
+
+    /// Reset the process-wide configuration to the defaults.
+    pub fn reset_global() {
+        GLOBAL_THREADS.store(0, Ordering::Relaxed);
+        GLOBAL_CHUNK_SIZE.store(0, Ordering::Relaxed);
+    }
+
+    /// Apply a function to every item in parallel, returning the results in order.
+    ///
+    /// Runs on the calling thread if a single thread is configured,
+    /// or if the items fit within a single chunk.
+    #[inline]
+    pub fn map<T, U, F>(&self, items: &[T], f: F) -> Vec<U>
+        where T: Sync,
+              U: Send,
+              F: Fn(&T) -> U + Sync
+    {
+        self.map_impl(items, None, f).expect("map without a token cannot be cancelled")
+    }
+
+    /// Apply a function to every item in parallel, stopping early if cancelled.
+    ///
+    /// The token is checked before processing each chunk.
+    #[inline]
+    pub fn map_with_cancel<T, U, F>(&self, items: &[T], cancel: &CancellationToken, f: F) -> Result<Vec<U>>
+        where T: Sync,
+              U: Send,
+              F: Fn(&T) -> U + Sync
+    {
+        self.map_impl(items, Some(cancel), f).ok_or_else(|| From::from(ErrorKind::Cancelled))
+    }
+
+    /// Apply a function to every item, returning `None` if cancelled.
+    fn map_impl<T, U, F>(&self, items: &[T], cancel: Option<&CancellationToken>, f: F) -> Option<Vec<U>>
+        where T: Sync,
+              U: Send,
+              F: Fn(&T) -> U + Sync
+    {
+        let is_cancelled = || cancel.is_some_and(|c| c.is_cancelled());
+        let chunk_size = self.chunk_size.max(1);
+        let chunks = items.len().div_ceil(chunk_size);
+        let threads = self.threads.min(chunks);
+        if threads <= 1 {
+            let mut results = Vec::with_capacity(items.len());
+            for chunk in items.chunks(chunk_size) {
+                if is_cancelled() {
+                    return None;
+                }
+                results.extend(chunk.iter().map(&f));
+            }
+            return Some(results);
+        }
+
+        // Workers claim the next unprocessed chunk, so uneven chunks
+        // are balanced between threads.
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<(usize, Vec<U>)>> = Mutex::new(Vec::with_capacity(chunks));
+        let work = || {
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= chunks || is_cancelled() {
+                    break;
+                }
+                let start = index * chunk_size;
+                let end = (start + chunk_size).min(items.len());
+                let chunk: Vec<U> = items[start..end].iter().map(&f).collect();
+                results.lock().unwrap().push((index, chunk));
+            }
+        };
+        thread::scope(|scope| {
+            for _ in 1..threads {
+                scope.spawn(work);
+            }
+            work();
+        });
+
+        let mut results = results.into_inner().unwrap();
+        if results.len() < chunks {
+            return None;
+        }
+        results.sort_by_key(|r| r.0);
+        Some(results.into_iter().flat_map(|r| r.1).collect())
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_test() {
+        let items: Vec<u32> = (0..1000).collect();
+        let expected: Vec<u32> = items.iter().map(|x| x * 2).collect();
+        for &(threads, chunk_size) in &[(1, 64), (4, 1), (4, 7), (3, 1000), (8, 2000)] {
+            let concurrency = Concurrency { threads: threads, chunk_size: chunk_size };
+            assert_eq!(concurrency.map(&items, |x| x * 2), expected);
+        }
+        assert!(Concurrency::new().map(&[] as &[u32], |x| x * 2).is_empty());
+    }
+
+    #[test]
+    fn global_test() {
+        let concurrency = Concurrency { threads: 3, chunk_size: 17 };
+        concurrency.set_global();
+        assert_eq!(Concurrency::global(), concurrency);
+        Concurrency::reset_global();
+        assert_eq!(Concurrency::global(), Concurrency::new());
+        assert_eq!(Concurrency::serial().threads, 1);
+    }
+
+    #[test]
+    fn cancel_test() {
+        let items: Vec<u32> = (0..1000).collect();
+        let cancel = CancellationToken::new();
+        let concurrency = Concurrency { threads: 4, chunk_size: 10 };
+        assert_eq!(concurrency.map_with_cancel(&items, &cancel, |x| x * 2).unwrap().len(), 1000);
+        assert!(cancel.check().is_ok());
+
+        // Cancel from within the operation, as from another thread.
+        for &threads in &[1, 4] {
+            let cancel = CancellationToken::new();
+            let concurrency = Concurrency { threads: threads, chunk_size: 10 };
+            let result = concurrency.map_with_cancel(&items, &cancel, |&x| {
+                if x == 500 {
+                    cancel.clone().cancel();
+                }
+                x
+            });
+            match result.unwrap_err().kind() {
+                &ErrorKind::Cancelled => (),
+                kind                  => panic!("unexpected error: {:?}", kind),
+            }
+            assert!(cancel.is_cancelled());
+        }
+    }
+}
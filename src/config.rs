@@ -0,0 +1,492 @@
+//! Declarative pipeline configuration from TOML parameter files.
+//!
+//! A single parameter file describes the digestion, modifications,
+//! search and conversion parameters of a pipeline, and is loaded into
+//! the option structs consumed by the corresponding APIs, so runs are
+//! reproducible from the file alone:
+//!
+//! ```toml
+//! max_variable_modifications = 2
+//!
+//! [digest]
+//! enzyme = "trypsin"
+//! missed_cleavages = 2
+//! min_length = 7
+//! max_length = 50
+//! min_mass = 500.0
+//! max_mass = 5000.0
+//!
+//! [[modifications]]
+//! name = "Carbamidomethyl"
+//! residues = "C"
+//! mass = 57.021464
+//! fixed = true
+//!
+//! [search]
+//! precursor_tolerance = "10 ppm"
+//! fragment_tolerance = "0.02 Da"
+//! ions = ["b", "y"]
+//! max_fragment_charge = 1
+//! default_charges = [2, 3]
+//! min_matched = 4
+//!
+//! [convert]
+//! input = "fasta"
+//! output = "xml"
+//!
+//! [concurrency]
+//! threads = 8
+//! chunk_size = 64
+//! ```
+//!
+//! Every section and key is optional, and missing values use the
+//! defaults of the option structs. Unknown keys and invalid values are
+//! rejected with `InvalidConfig` and the dotted path of the key, so
+//! misspelled parameters are never silently ignored. The `[search]`
+//! section requires the `search` feature, and the `[concurrency]`
+//! section takes effect once applied with `Concurrency::set_global`.
+
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+use bio::digest::{DigestOptions, Enzyme};
+use concurrency::Concurrency;
+use db::peptide_search_matches::ModificationRule;
+use util::{Error, ErrorKind, Result};
+
+#[cfg(feature = "search")]
+use bio::fragment::IonType;
+
+#[cfg(feature = "search")]
+use bio::mass::Tolerance;
+
+#[cfg(feature = "search")]
+use search::SearchOptions;
+
+/// Default maximum number of variable modifications per peptide.
+const MAX_VARIABLE_MODIFICATIONS: usize = 2;
+
+// FORMAT
+
+/// File format for record conversion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileFormat {
+    /// CSV (or tab-delimited) text.
+    Csv,
+    /// FASTA text.
+    Fasta,
+    /// FASTQ text.
+    Fastq,
+    /// Mascot generic format.
+    Mgf,
+    /// XML text.
+    Xml,
+}
+
+impl FileFormat {
+    /// Create file format from its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" | "tsv" => Some(FileFormat::Csv),
+            "fasta"       => Some(FileFormat::Fasta),
+            "fastq"       => Some(FileFormat::Fastq),
+            "mgf"         => Some(FileFormat::Mgf),
+            "xml"         => Some(FileFormat::Xml),
+            _             => None,
+        }
+    }
+}
+
+/// Options for record conversion between file formats.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConvertOptions {
+    /// Format of the input records.
+    pub input: FileFormat,
+    /// Format of the output records.
+    pub output: FileFormat,
+}
+
+// CONFIG
+
+/// Typed pipeline parameters, loaded from a TOML parameter file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineConfig {
+    /// Options for proteolytic digestion.
+    pub digest: DigestOptions,
+    /// Fixed and variable modification rules.
+    pub modifications: Vec<ModificationRule>,
+    /// Maximum number of variable modifications per peptide.
+    pub max_variable_modifications: usize,
+    /// Options for the database search.
+    #[cfg(feature = "search")]
+    pub search: SearchOptions,
+    /// Options for record conversion, if configured.
+    pub convert: Option<ConvertOptions>,
+    /// Worker threads for parallel algorithms.
+    pub concurrency: Concurrency,
+}
+
+impl PipelineConfig {
+    /// Create new configuration with the default parameters.
+    pub fn new() -> Self {
+        PipelineConfig {
+            digest: DigestOptions::default(),
+            modifications: vec![],
+            max_variable_modifications: MAX_VARIABLE_MODIFICATIONS,
+            #[cfg(feature = "search")]
+            search: SearchOptions::default(),
+            convert: None,
+            concurrency: Concurrency::new(),
+        }
+    }
+
+    /// Load configuration from TOML text.
+    pub fn from_string(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let mut config = PipelineConfig::new();
+        for (key, value) in table.iter() {
+            match key.as_str() {
+                "max_variable_modifications" => {
+                    config.max_variable_modifications = to_usize(value, key)?;
+                },
+                "digest"                     => parse_digest(value, &mut config.digest)?,
+                "modifications"              => config.modifications = parse_modifications(value)?,
+                #[cfg(feature = "search")]
+                "search"                     => parse_search(value, &mut config.search)?,
+                "convert"                    => config.convert = Some(parse_convert(value)?),
+                "concurrency"                => parse_concurrency(value, &mut config.concurrency)?,
+                _                            => return Err(invalid(key)),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load configuration from a TOML file, adding the path to errors.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .map_err(Error::from)
+            .and_then(|text| PipelineConfig::from_string(&text))
+            .map_err(|e| e.with_path(path))
+    }
+}
+
+// SECTIONS
+
+/// Parse the `[digest]` section.
+fn parse_digest(value: &Value, digest: &mut DigestOptions) -> Result<()> {
+    for (key, value) in to_table(value, "digest")?.iter() {
+        let path = format!("digest.{}", key);
+        match key.as_str() {
+            "enzyme"           => {
+                let name = to_str(value, &path)?;
+                digest.enzyme = none_to_config_error(Enzyme::from_name(name), &path)?;
+            },
+            "missed_cleavages" => digest.missed_cleavages = to_usize(value, &path)?,
+            "min_length"       => digest.min_length = to_usize(value, &path)?,
+            "max_length"       => digest.max_length = to_usize(value, &path)?,
+            "min_mass"         => digest.min_mass = to_f64(value, &path)?,
+            "max_mass"         => digest.max_mass = to_f64(value, &path)?,
+            _                  => return Err(invalid(&path)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the `[[modifications]]` array of tables.
+fn parse_modifications(value: &Value) -> Result<Vec<ModificationRule>> {
+    let array = none_to_config_error(value.as_array(), "modifications")?;
+    let mut rules = Vec::with_capacity(array.len());
+    for (index, value) in array.iter().enumerate() {
+        let prefix = format!("modifications.{}", index);
+        let mut rule = ModificationRule::variable("", b"", 0.0);
+        let mut has_mass = false;
+        for (key, value) in to_table(value, &prefix)?.iter() {
+            let path = format!("{}.{}", prefix, key);
+            match key.as_str() {
+                "name"     => rule.name = to_str(value, &path)?.to_string(),
+                "residues" => rule.residues = to_str(value, &path)?.to_ascii_uppercase().into_bytes(),
+                "mass"     => { rule.mass = to_f64(value, &path)?; has_mass = true; },
+                "fixed"    => rule.fixed = none_to_config_error(value.as_bool(), &path)?,
+                _          => return Err(invalid(&path)),
+            }
+        }
+        if rule.name.is_empty() {
+            return Err(invalid(&format!("{}.name", prefix)));
+        } else if rule.residues.is_empty() {
+            return Err(invalid(&format!("{}.residues", prefix)));
+        } else if !has_mass {
+            return Err(invalid(&format!("{}.mass", prefix)));
+        }
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+/// Parse the `[search]` section.
+#[cfg(feature = "search")]
+fn parse_search(value: &Value, search: &mut SearchOptions) -> Result<()> {
+    for (key, value) in to_table(value, "search")?.iter() {
+        let path = format!("search.{}", key);
+        match key.as_str() {
+            "precursor_tolerance" => search.precursor_tolerance = to_tolerance(value, &path)?,
+            "fragment_tolerance"  => search.fragment_tolerance = to_tolerance(value, &path)?,
+            "ions"                => {
+                search.ions = to_array(value, &path, |v| {
+                    let mut chars = v.as_str()?.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => IonType::from_letter(c),
+                        _               => None,
+                    }
+                })?;
+            },
+            "max_fragment_charge" => search.max_fragment_charge = to_charge(value, &path)?,
+            "default_charges"     => {
+                search.default_charges = to_array(value, &path, |v| {
+                    v.as_integer().filter(|&z| z > 0 && z <= i8::MAX as i64).map(|z| z as i8)
+                })?;
+            },
+            "min_matched"         => search.min_matched = to_usize(value, &path)?,
+            _                     => return Err(invalid(&path)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the `[convert]` section.
+fn parse_convert(value: &Value) -> Result<ConvertOptions> {
+    let mut input = None;
+    let mut output = None;
+    for (key, value) in to_table(value, "convert")?.iter() {
+        let path = format!("convert.{}", key);
+        let format = none_to_config_error(FileFormat::from_name(to_str(value, &path)?), &path)?;
+        match key.as_str() {
+            "input"  => input = Some(format),
+            "output" => output = Some(format),
+            _        => return Err(invalid(&path)),
+        }
+    }
+
+    Ok(ConvertOptions {
+        input: none_to_config_error(input, "convert.input")?,
+        output: none_to_config_error(output, "convert.output")?,
+    })
+}
+
+/// Parse the `[concurrency]` section.
+fn parse_concurrency(value: &Value, concurrency: &mut Concurrency) -> Result<()> {
+    for (key, value) in to_table(value, "concurrency")?.iter() {
+        let path = format!("concurrency.{}", key);
+        let count = to_usize(value, &path)?;
+        bool_to_config_error(count > 0, &path)?;
+        match key.as_str() {
+            "threads"    => concurrency.threads = count,
+            "chunk_size" => concurrency.chunk_size = count,
+            _            => return Err(invalid(&path)),
+        }
+    }
+
+    Ok(())
+}
+
+// VALUES
+
+/// Create an error for an invalid key or value.
+#[inline]
+fn invalid(path: &str) -> Error {
+    From::from(ErrorKind::InvalidConfig(path.to_string()))
+}
+
+/// Convert a `None` value to an error for the key.
+#[inline]
+fn none_to_config_error<T>(value: Option<T>, path: &str) -> Result<T> {
+    value.ok_or_else(|| invalid(path))
+}
+
+/// Convert `false` to an error for the key.
+#[inline]
+fn bool_to_config_error(value: bool, path: &str) -> Result<()> {
+    match value {
+        true  => Ok(()),
+        false => Err(invalid(path)),
+    }
+}
+
+/// Get a table value.
+#[inline]
+fn to_table<'a>(value: &'a Value, path: &str) -> Result<&'a Table> {
+    none_to_config_error(value.as_table(), path)
+}
+
+/// Get a string value.
+#[inline]
+fn to_str<'a>(value: &'a Value, path: &str) -> Result<&'a str> {
+    none_to_config_error(value.as_str(), path)
+}
+
+/// Get a non-negative integer value.
+#[inline]
+fn to_usize(value: &Value, path: &str) -> Result<usize> {
+    let integer = value.as_integer().filter(|&i| i >= 0);
+    none_to_config_error(integer, path).map(|i| i as usize)
+}
+
+/// Get a float value, accepting integers.
+#[inline]
+fn to_f64(value: &Value, path: &str) -> Result<f64> {
+    let float = value.as_float().or_else(|| value.as_integer().map(|i| i as f64));
+    none_to_config_error(float, path)
+}
+
+/// Get a positive charge value.
+#[cfg(feature = "search")]
+#[inline]
+fn to_charge(value: &Value, path: &str) -> Result<i8> {
+    let charge = value.as_integer().filter(|&z| z > 0 && z <= i8::MAX as i64);
+    none_to_config_error(charge, path).map(|z| z as i8)
+}
+
+/// Get an array of values, converting each item.
+#[cfg(feature = "search")]
+fn to_array<T, F>(value: &Value, path: &str, convert: F) -> Result<Vec<T>>
+    where F: Fn(&Value) -> Option<T>
+{
+    let array = none_to_config_error(value.as_array(), path)?;
+    array.iter()
+        .enumerate()
+        .map(|(i, v)| none_to_config_error(convert(v), &format!("{}.{}", path, i)))
+        .collect()
+}
+
+/// Get a tolerance from text, for example, `"10 ppm"` or `"0.02 Da"`.
+#[cfg(feature = "search")]
+fn to_tolerance(value: &Value, path: &str) -> Result<Tolerance> {
+    let text = to_str(value, path)?;
+    let mut tokens = text.split_whitespace();
+    let tolerance = match (tokens.next(), tokens.next(), tokens.next()) {
+        (Some(number), Some(unit), None) => {
+            let number: Option<f64> = number.parse().ok().filter(|x: &f64| x.is_finite() && *x >= 0.0);
+            match (number, unit.to_ascii_lowercase().as_str()) {
+                (Some(x), "ppm") => Some(Tolerance::Ppm(x)),
+                (Some(x), "da")  => Some(Tolerance::Da(x)),
+                _                => None,
+            }
+        },
+        _ => None,
+    };
+    none_to_config_error(tolerance, path)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use super::*;
+
+    const PARAMETERS: &'static str = r#"
+max_variable_modifications = 3
+
+[digest]
+enzyme = "Lys-C"
+missed_cleavages = 1
+min_mass = 600
+
+[[modifications]]
+name = "Carbamidomethyl"
+residues = "C"
+mass = 57.021464
+fixed = true
+
+[[modifications]]
+name = "Oxidation"
+residues = "m"
+mass = 15.994915
+
+[convert]
+input = "fasta"
+output = "tsv"
+
+[concurrency]
+threads = 2
+"#;
+
+    fn error_key(text: &str) -> String {
+        match PipelineConfig::from_string(text).unwrap_err().kind() {
+            ErrorKind::InvalidConfig(ref key) => key.clone(),
+            kind                              => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn from_string_test() {
+        let config = PipelineConfig::from_string(PARAMETERS).unwrap();
+        assert_eq!(config.max_variable_modifications, 3);
+        assert_eq!(config.digest, DigestOptions {
+            enzyme: Enzyme::LysC,
+            missed_cleavages: 1,
+            min_mass: 600.0,
+            ..DigestOptions::default()
+        });
+        assert_eq!(config.modifications, vec![ModificationRule::carbamidomethyl(), ModificationRule::oxidation()]);
+        assert_eq!(config.convert, Some(ConvertOptions { input: FileFormat::Fasta, output: FileFormat::Csv }));
+        assert_eq!(config.concurrency, Concurrency { threads: 2, ..Concurrency::new() });
+
+        // defaults
+        assert_eq!(PipelineConfig::from_string("").unwrap(), PipelineConfig::new());
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert_eq!(error_key("threads = 4"), "threads");
+        assert_eq!(error_key("[digest]\nenzyme = \"pepsin\""), "digest.enzyme");
+        assert_eq!(error_key("[digest]\nmissed_cleavages = -1"), "digest.missed_cleavages");
+        assert_eq!(error_key("[digest]\nmin_mas = 500.0"), "digest.min_mas");
+        assert_eq!(error_key("[[modifications]]\nname = \"Phospho\"\nresidues = \"STY\""), "modifications.0.mass");
+        assert_eq!(error_key("[convert]\ninput = \"fasta\""), "convert.output");
+        assert_eq!(error_key("[convert]\ninput = \"pdb\"\noutput = \"xml\""), "convert.input");
+        assert_eq!(error_key("[concurrency]\nthreads = 0"), "concurrency.threads");
+
+        let err = PipelineConfig::from_string("[digest\n").unwrap_err();
+        match err.kind() {
+            ErrorKind::Toml(_) => (),
+            kind               => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[cfg(feature = "search")]
+    #[test]
+    fn search_test() {
+        let text = "[search]\nprecursor_tolerance = \"20 ppm\"\nfragment_tolerance = \"0.5 Da\"\nions = [\"a\", \"B\", \"y\"]\ndefault_charges = [2, 3, 4]\n";
+        let config = PipelineConfig::from_string(text).unwrap();
+        assert_eq!(config.search, SearchOptions {
+            precursor_tolerance: Tolerance::Ppm(20.0),
+            fragment_tolerance: Tolerance::Da(0.5),
+            ions: vec![IonType::A, IonType::B, IonType::Y],
+            default_charges: vec![2, 3, 4],
+            ..SearchOptions::default()
+        });
+
+        assert_eq!(error_key("[search]\nprecursor_tolerance = \"10 ppb\""), "search.precursor_tolerance");
+        assert_eq!(error_key("[search]\nions = [\"b\", \"z\"]"), "search.ions.1");
+        assert_eq!(error_key("[search]\ndefault_charges = [0]"), "search.default_charges.0");
+    }
+
+    #[test]
+    fn from_file_test() {
+        let path = env::temp_dir().join(format!("bdb-config-{}.toml", ::std::process::id()));
+        fs::write(&path, PARAMETERS).unwrap();
+        let config = PipelineConfig::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(config.digest.enzyme, Enzyme::LysC);
+
+        let err = PipelineConfig::from_file(&path).unwrap_err();
+        assert_eq!(err.path(), Some(path.as_path()));
+    }
+}
@@ -0,0 +1,216 @@
+//! Helper utilities for Clustal loading and saving.
+
+use std::io::prelude::*;
+
+use traits::*;
+use util::*;
+use super::record::{is_gap, AlignedSequence, Record};
+
+/// Number of columns per alignment block on export.
+const BLOCK_LENGTH: usize = 60;
+
+/// Minimum number of spaces between the identifier and the columns.
+const LABEL_PADDING: usize = 6;
+
+/// Groups of strongly similar residues, annotated by ":".
+const STRONG_GROUPS: &'static [&'static [u8]] = &[
+    b"STA", b"NEQK", b"NHQK", b"NDEQ", b"QHRK", b"MILV", b"MILF", b"HY", b"FYW",
+];
+
+/// Groups of weakly similar residues, annotated by ".".
+const WEAK_GROUPS: &'static [&'static [u8]] = &[
+    b"CSA", b"ATV", b"SAG", b"STNK", b"STPA", b"SGND", b"SNDEQK", b"NDEQHK",
+    b"NEQHRK", b"FVLIM", b"HFY",
+];
+
+// CONSERVATION
+
+/// Determine if all residues are members of any of the groups.
+#[inline]
+fn in_any_group(groups: &[&[u8]], residues: &[u8]) -> bool {
+    groups.iter().any(|group| residues.iter().all(|c| group.contains(c)))
+}
+
+/// Calculate the Clustal conservation symbol for each column.
+///
+/// Fully conserved columns are annotated by "*", and columns of strongly
+/// or weakly similar residues by ":" and ".", respectively. Columns
+/// with any gap are not annotated.
+fn conservation(record: &Record) -> Vec<u8> {
+    let mut residues = Vec::with_capacity(record.sequences.len());
+    (0..record.columns()).map(|column| {
+        residues.clear();
+        for sequence in record.sequences.iter() {
+            match sequence.sequence.get(column) {
+                Some(&c) if !is_gap(c) => residues.push(c.to_ascii_uppercase()),
+                _                      => return b' ',
+            }
+        }
+
+        if residues.iter().all(|&c| c == residues[0]) {
+            b'*'
+        } else if in_any_group(STRONG_GROUPS, &residues) {
+            b':'
+        } else if in_any_group(WEAK_GROUPS, &residues) {
+            b'.'
+        } else {
+            b' '
+        }
+    }).collect()
+}
+
+// SIZE
+
+/// Estimate the size of a Clustal record.
+///
+/// Used to prevent reallocations during record exportation to string,
+/// to minimize costly library calls.
+#[inline]
+fn estimate_record_size(record: &Record) -> usize {
+    const CLUSTAL_VOCABULARY_SIZE: usize = 40;
+    let width = label_width(record);
+    let blocks = (record.columns() + BLOCK_LENGTH - 1) / BLOCK_LENGTH;
+    CLUSTAL_VOCABULARY_SIZE +
+        (record.sequences.len() + 2) * (blocks * (width + 1) + record.columns())
+}
+
+// WRITER
+
+/// Get the width of the identifier column, including padding.
+#[inline]
+fn label_width(record: &Record) -> usize {
+    record.sequences.iter().map(|s| s.id.len()).max().unwrap_or(0) + LABEL_PADDING
+}
+
+/// Write a label, padded to the width, followed by the aligned columns.
+#[inline]
+fn write_columns<T: Write>(writer: &mut T, label: &str, width: usize, columns: &[u8])
+    -> Result<()>
+{
+    writer.write_all(label.as_bytes())?;
+    for _ in label.len()..width {
+        writer.write_all(b" ")?;
+    }
+    write_alls!(writer, columns, b"\n")?;
+    Ok(())
+}
+
+/// Export record to Clustal.
+///
+/// The alignment is split into blocks of 60 columns, each followed by
+/// the conservation of the columns. Alignment and sequence annotations
+/// are not exported.
+pub fn record_to_clustal<T: Write>(writer: &mut T, record: &Record)
+    -> Result<()>
+{
+    let width = label_width(record);
+    let conservation = conservation(record);
+    writer.write_all(b"CLUSTAL W multiple sequence alignment\n\n")?;
+
+    for (index, block) in conservation.chunks(BLOCK_LENGTH).enumerate() {
+        let start = index * BLOCK_LENGTH;
+        let end = start + block.len();
+        writer.write_all(b"\n")?;
+        for sequence in record.sequences.iter() {
+            write_columns(writer, &sequence.id, width, &sequence.sequence[start..end])?;
+        }
+        let length = block.iter().rposition(|&c| c != b' ').map_or(0, |i| i + 1);
+        write_columns(writer, "", width, &block[..length])?;
+    }
+
+    Ok(())
+}
+
+// READER
+
+/// Import record from Clustal.
+///
+/// Blocks are joined, and residue counts after the aligned columns
+/// are ignored. Conservation lines are ignored, since they are derived
+/// from the alignment.
+pub fn record_from_clustal<T: BufRead>(reader: &mut T)
+    -> Result<Record>
+{
+    let mut lines = reader.lines();
+    let header = none_to_error!(lines.next(), InvalidInput)?;
+    bool_to_error!(strip_bom(header.trim_end()).starts_with("CLUSTAL"), InvalidInput);
+
+    let mut record = Record::new();
+    for line in lines {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with(char::is_whitespace) {
+            // Blank or conservation line.
+            continue;
+        }
+
+        let mut split = line.split_whitespace();
+        let id = none_to_error!(split.next(), InvalidInput);
+        let columns = none_to_error!(split.next(), InvalidInput);
+        match record.sequences.iter().position(|s| s.id == id) {
+            Some(index) => record.sequences[index].sequence.extend_from_slice(columns.as_bytes()),
+            None        => record.sequences.push(AlignedSequence::new(id, columns.as_bytes())),
+        }
+    }
+
+    Ok(record)
+}
+
+// TRAITS
+
+impl Clustal for Record {
+    #[inline]
+    fn estimate_clustal_size(&self) -> usize {
+        estimate_record_size(self)
+    }
+
+    #[inline(always)]
+    fn to_clustal<T: Write>(&self, writer: &mut T) -> Result<()> {
+        record_to_clustal(writer, self)
+    }
+
+    #[inline(always)]
+    fn from_clustal<T: BufRead>(reader: &mut T) -> Result<Self> {
+        record_from_clustal(reader)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use test::windows_text;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn conservation_test() {
+        let r = cbs();
+        assert_eq!(conservation(&r), b" . : : :      . :     *  :.  :  ".to_vec());
+        assert_eq!(conservation(&Record::new()), b"".to_vec());
+    }
+
+    #[test]
+    fn record_clustal_test() {
+        let r = cbs();
+        assert_eq!(r.to_clustal_bytes().unwrap(), CBS_CLUSTAL.to_vec());
+
+        // Annotations are lost.
+        let x = Record::from_clustal_bytes(CBS_CLUSTAL).unwrap();
+        assert_eq!(x.sequences.len(), 2);
+        assert_eq!(x.sequences[0].id, r.sequences[0].id);
+        assert_eq!(x.sequences[0].sequence, r.sequences[0].sequence);
+        assert_eq!(x.sequences[1].sequence, r.sequences[1].sequence);
+        assert!(x.annotations.is_empty());
+        assert_eq!(Record::from_clustal_bytes(&windows_text(CBS_CLUSTAL)).unwrap(), x);
+
+        // Multiple blocks, with residue counts.
+        let y = Record::from_clustal_bytes(CBS_BLOCKS_CLUSTAL).unwrap();
+        assert_eq!(y, x);
+
+        assert!(Record::from_clustal_bytes(b"O31698/18-71 AVKK").is_err());
+        assert!(Record::from_clustal_bytes(b"CLUSTAL W\n\nO31698/18-71\n").is_err());
+        assert!(Record::from_clustal_bytes(b"").is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Multiple sequence alignment (MSA) integrations.
+//!
+//! Reads and writes aligned sequences with their annotations, for
+//! interoperability with Pfam and Rfam alignments.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
+
+pub(crate) mod complete;
+pub(crate) mod profile;
+pub(crate) mod record;
+pub(crate) mod record_list;
+pub(crate) mod valid;
+
+#[cfg(feature = "clustal")]
+pub(crate) mod clustal;
+
+#[cfg(feature = "stockholm")]
+pub(crate) mod stockholm;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+// Re-export the models into the parent module.
+pub use self::profile::{pssm_to_text, Profile, Pssm, AMINOACID_ALPHABET, DNA_ALPHABET, RNA_ALPHABET};
+pub use self::record::{AlignedSequence, Annotation, Record};
+pub use self::record_list::RecordList;
@@ -0,0 +1,328 @@
+//! Consensus sequences and profiles of multiple sequence alignments.
+//!
+//! Residues are counted per column, ignoring case. Gaps and residues
+//! outside the profile alphabet are not counted as residues.
+
+use std::io::Write;
+
+use util::Result;
+use super::record::{is_gap, Record};
+
+/// Standard amino acids, for protein profiles.
+pub const AMINOACID_ALPHABET: &'static [u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Standard nucleotides (DNA), for nucleotide profiles.
+pub const DNA_ALPHABET: &'static [u8] = b"ACGT";
+
+/// Standard nucleotides (RNA), for nucleotide profiles.
+pub const RNA_ALPHABET: &'static [u8] = b"ACGU";
+
+/// Residue counts for each column of an alignment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    alphabet: Vec<u8>,
+    sequences: usize,
+    counts: Vec<Vec<u32>>,
+    gaps: Vec<u32>,
+}
+
+impl Profile {
+    /// Count the residues in each column of the alignment.
+    ///
+    /// The alphabet must be uppercase. Short sequences are padded
+    /// with gaps.
+    pub fn new(record: &Record, alphabet: &[u8]) -> Self {
+        let columns = record.columns();
+        let mut counts = vec![vec![0; alphabet.len()]; columns];
+        let mut gaps = vec![0; columns];
+        for sequence in record.sequences.iter() {
+            for (column, &residue) in sequence.sequence.iter().enumerate() {
+                let residue = residue.to_ascii_uppercase();
+                if is_gap(residue) {
+                    gaps[column] += 1;
+                } else if let Some(i) = alphabet.iter().position(|&c| c == residue) {
+                    counts[column][i] += 1;
+                }
+            }
+            for gap in gaps[sequence.sequence.len()..].iter_mut() {
+                *gap += 1;
+            }
+        }
+
+        Profile {
+            alphabet: alphabet.to_vec(),
+            sequences: record.sequences.len(),
+            counts: counts,
+            gaps: gaps,
+        }
+    }
+
+    /// Get the profile alphabet.
+    #[inline]
+    pub fn alphabet(&self) -> &[u8] {
+        &self.alphabet
+    }
+
+    /// Get the number of columns.
+    #[inline]
+    pub fn columns(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Get the number of aligned sequences.
+    #[inline]
+    pub fn sequences(&self) -> usize {
+        self.sequences
+    }
+
+    /// Get the number of occurrences of a residue in a column.
+    #[inline]
+    pub fn count(&self, column: usize, residue: u8) -> u32 {
+        let residue = residue.to_ascii_uppercase();
+        match self.alphabet.iter().position(|&c| c == residue) {
+            Some(i) => self.counts[column][i],
+            None    => 0,
+        }
+    }
+
+    /// Get the number of gaps in a column.
+    #[inline]
+    pub fn gaps(&self, column: usize) -> u32 {
+        self.gaps[column]
+    }
+
+    /// Get the number of residues from the alphabet in a column.
+    #[inline]
+    pub fn residues(&self, column: usize) -> u32 {
+        self.counts[column].iter().sum()
+    }
+
+    /// Get the most frequent residue in a column, and its count.
+    ///
+    /// Ties are broken by the alphabet order.
+    fn most_frequent(&self, column: usize) -> Option<(u8, u32)> {
+        let mut best: Option<(u8, u32)> = None;
+        for (&residue, &count) in self.alphabet.iter().zip(self.counts[column].iter()) {
+            let better = match best {
+                Some((_, b)) => count > b,
+                None         => count > 0,
+            };
+            if better {
+                best = Some((residue, count));
+            }
+        }
+        best
+    }
+
+    /// Call the consensus sequence of the alignment.
+    ///
+    /// Each column uses the most frequent residue, in uppercase if
+    /// the fraction of sequences with the residue is at least the
+    /// threshold, otherwise in lowercase. Columns without residues
+    /// are gaps ("-").
+    pub fn consensus(&self, threshold: f64) -> Vec<u8> {
+        (0..self.columns()).map(|column| match self.most_frequent(column) {
+            Some((residue, count)) => {
+                match count as f64 / self.sequences as f64 >= threshold {
+                    true  => residue,
+                    false => residue.to_ascii_lowercase(),
+                }
+            },
+            None => b'-',
+        }).collect()
+    }
+
+    /// Calculate the conservation of each column, from 0 to 1.
+    ///
+    /// Conservation is `1 - H / log2(K)`, where `H` is the Shannon
+    /// entropy of the residues in the column and `K` is the alphabet
+    /// size, scaled by the fraction of sequences with residues in
+    /// the column.
+    pub fn conservation(&self) -> Vec<f64> {
+        let max_entropy = (self.alphabet.len() as f64).log2();
+        self.counts.iter().map(|counts| {
+            let residues: u32 = counts.iter().sum();
+            if residues == 0 || max_entropy == 0.0 {
+                return 0.0;
+            }
+            let total = residues as f64;
+            let entropy: f64 = counts.iter()
+                .filter(|&&c| c > 0)
+                .map(|&c| {
+                    let p = c as f64 / total;
+                    -p * p.log2()
+                })
+                .sum();
+            (1.0 - entropy / max_entropy) * total / self.sequences as f64
+        }).collect()
+    }
+
+    /// Create a position-specific scoring matrix from the profile.
+    ///
+    /// Scores are the log2-odds of the residue frequencies against a
+    /// uniform background. `pseudocount` residues are distributed
+    /// evenly over the alphabet in each column, to avoid infinite
+    /// scores for unobserved residues.
+    pub fn pssm(&self, pseudocount: f64) -> Pssm {
+        let size = self.alphabet.len() as f64;
+        let scores = self.counts.iter().map(|counts| {
+            let total = counts.iter().sum::<u32>() as f64 + pseudocount;
+            counts.iter().map(|&c| match total > 0.0 {
+                true  => ((c as f64 + pseudocount / size) / total * size).log2(),
+                false => 0.0,
+            }).collect()
+        }).collect();
+
+        Pssm {
+            alphabet: self.alphabet.clone(),
+            scores: scores,
+        }
+    }
+}
+
+/// Position-specific scoring matrix, with log2-odds scores.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pssm {
+    alphabet: Vec<u8>,
+    scores: Vec<Vec<f64>>,
+}
+
+impl Pssm {
+    /// Get the matrix alphabet.
+    #[inline]
+    pub fn alphabet(&self) -> &[u8] {
+        &self.alphabet
+    }
+
+    /// Get the number of columns.
+    #[inline]
+    pub fn columns(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Get the score of a residue in a column, or `None` for unknown residues.
+    #[inline]
+    pub fn score(&self, column: usize, residue: u8) -> Option<f64> {
+        let residue = residue.to_ascii_uppercase();
+        self.alphabet.iter()
+            .position(|&c| c == residue)
+            .map(|i| self.scores[column][i])
+    }
+
+    /// Score an ungapped sequence aligned to the first column.
+    ///
+    /// Unknown residues and residues past the last column score 0.
+    pub fn score_sequence(&self, sequence: &[u8]) -> f64 {
+        sequence.iter()
+            .enumerate()
+            .take(self.columns())
+            .filter_map(|(column, &residue)| self.score(column, residue))
+            .sum()
+    }
+}
+
+/// Export the scoring matrix as a tab-delimited table.
+///
+/// The header contains "Pos" and the alphabet, and each row contains
+/// the 1-based column and the scores, to 3 decimal places.
+pub fn pssm_to_text<T: Write>(writer: &mut T, pssm: &Pssm) -> Result<()> {
+    write!(writer, "Pos")?;
+    for &residue in pssm.alphabet.iter() {
+        write!(writer, "\t{}", residue as char)?;
+    }
+    writeln!(writer)?;
+
+    for (column, scores) in pssm.scores.iter().enumerate() {
+        write!(writer, "{}", column + 1)?;
+        for score in scores.iter() {
+            write!(writer, "\t{:.3}", score)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::record::AlignedSequence;
+    use super::super::test::*;
+
+    fn dna() -> Record {
+        let mut record = Record::new();
+        record.sequences = vec![
+            AlignedSequence::new("x", b"ACGT-"),
+            AlignedSequence::new("y", b"ACGA-"),
+            AlignedSequence::new("z", b"acTAN"),
+            AlignedSequence::new("w", b"AC-C"),
+        ];
+        record
+    }
+
+    #[test]
+    fn profile_test() {
+        let profile = Profile::new(&dna(), DNA_ALPHABET);
+        assert_eq!(profile.columns(), 5);
+        assert_eq!(profile.sequences(), 4);
+        assert_eq!(profile.count(0, b'A'), 4);
+        assert_eq!(profile.count(2, b'g'), 2);
+        assert_eq!(profile.count(2, b'N'), 0);
+        assert_eq!(profile.gaps(2), 1);
+        assert_eq!(profile.gaps(4), 3);
+        assert_eq!(profile.residues(4), 0);
+
+        let profile = Profile::new(&cbs(), AMINOACID_ALPHABET);
+        assert_eq!(profile.columns(), 32);
+        assert_eq!(profile.consensus(0.5), b"ATCKAEHIADPLARGLDELAACACANKKAHGS".to_vec());
+        assert_eq!(profile.consensus(0.6), b"atckaehiadplargldelaacAcankkahgs".to_vec());
+    }
+
+    #[test]
+    fn consensus_test() {
+        let profile = Profile::new(&dna(), DNA_ALPHABET);
+        assert_eq!(profile.consensus(0.5), b"ACGA-".to_vec());
+        assert_eq!(profile.consensus(0.75), b"ACga-".to_vec());
+    }
+
+    #[test]
+    fn conservation_test() {
+        let conservation = Profile::new(&dna(), DNA_ALPHABET).conservation();
+        assert_eq!(conservation[0], 1.0);
+        assert_eq!(conservation[1], 1.0);
+        // {G: 2, T: 1}, in 3 of 4 sequences.
+        let entropy = -(2.0f64 / 3.0) * (2.0f64 / 3.0).log2() - (1.0f64 / 3.0) * (1.0f64 / 3.0).log2();
+        assert_approx_eq!(conservation[2], (1.0 - entropy / 2.0) * 0.75, 1e-12);
+        assert_eq!(conservation[4], 0.0);
+    }
+
+    #[test]
+    fn pssm_test() {
+        let pssm = Profile::new(&dna(), DNA_ALPHABET).pssm(0.0);
+        assert_eq!(pssm.columns(), 5);
+        assert_eq!(pssm.score(0, b'A'), Some(2.0));
+        assert_eq!(pssm.score(0, b'C'), Some(f64::NEG_INFINITY));
+        assert_eq!(pssm.score(0, b'N'), None);
+        assert_eq!(pssm.score(4, b'A'), Some(0.0));
+
+        let pssm = Profile::new(&dna(), DNA_ALPHABET).pssm(4.0);
+        // (4 + 1) / 8 * 4
+        assert_approx_eq!(pssm.score(0, b'a').unwrap(), 2.5f64.log2(), 1e-12);
+        // (0 + 1) / 8 * 4
+        assert_approx_eq!(pssm.score(0, b'C').unwrap(), -1.0, 1e-12);
+        assert_approx_eq!(pssm.score_sequence(b"AC"), 2.0 * 2.5f64.log2(), 1e-12);
+        assert_approx_eq!(pssm.score_sequence(b"ACNAAAA"), pssm.score_sequence(b"AC") + pssm.score(3, b'A').unwrap() + pssm.score(4, b'A').unwrap(), 1e-12);
+    }
+
+    #[test]
+    fn pssm_to_text_test() {
+        let mut record = Record::new();
+        record.sequences = vec![AlignedSequence::new("x", b"A-"), AlignedSequence::new("y", b"AC")];
+        let pssm = Profile::new(&record, DNA_ALPHABET).pssm(0.0);
+        let mut w = vec![];
+        pssm_to_text(&mut w, &pssm).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "Pos\tA\tC\tG\tT\n1\t2.000\t-inf\t-inf\t-inf\n2\t-inf\t2.000\t-inf\t-inf\n");
+    }
+}
@@ -0,0 +1,158 @@
+//! Model for multiple sequence alignment definitions.
+
+/// Free-text or per-column annotation.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Annotation {
+    /// Annotation feature, for example, "ID" or "SS_cons".
+    pub tag: String,
+    /// Annotation text, with one character per column for per-column annotations.
+    pub text: String,
+}
+
+impl Annotation {
+    /// Create new annotation from the tag and text.
+    #[inline]
+    pub fn new(tag: &str, text: &str) -> Self {
+        Annotation {
+            tag: String::from(tag),
+            text: String::from(text),
+        }
+    }
+}
+
+/// Model for a single aligned sequence.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct AlignedSequence {
+    /// Sequence identifier, for example, "O31698/18-71".
+    pub id: String,
+    /// Aligned residues, with "-" or "." for gaps.
+    pub sequence: Vec<u8>,
+    /// Free-text annotations for the sequence (Stockholm "#=GS").
+    pub annotations: Vec<Annotation>,
+    /// Per-residue annotations for the sequence (Stockholm "#=GR").
+    pub column_annotations: Vec<Annotation>,
+}
+
+impl AlignedSequence {
+    /// Create new aligned sequence, without annotations.
+    #[inline]
+    pub fn new(id: &str, sequence: &[u8]) -> Self {
+        AlignedSequence {
+            id: String::from(id),
+            sequence: sequence.to_vec(),
+            annotations: vec![],
+            column_annotations: vec![],
+        }
+    }
+
+    /// Get the residues, without gaps.
+    #[inline]
+    pub fn ungapped(&self) -> Vec<u8> {
+        self.sequence.iter().cloned().filter(|&c| !is_gap(c)).collect()
+    }
+}
+
+/// Model for a multiple sequence alignment.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Record {
+    /// Free-text annotations for the alignment (Stockholm "#=GF").
+    pub annotations: Vec<Annotation>,
+    /// Aligned sequences, in alignment order.
+    pub sequences: Vec<AlignedSequence>,
+    /// Per-column annotations for the alignment (Stockholm "#=GC").
+    pub column_annotations: Vec<Annotation>,
+}
+
+impl Record {
+    /// Create new, empty alignment.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            annotations: vec![],
+            sequences: vec![],
+            column_annotations: vec![],
+        }
+    }
+
+    /// Get the number of columns in the alignment.
+    #[inline]
+    pub fn columns(&self) -> usize {
+        self.sequences.iter().map(|s| s.sequence.len()).max().unwrap_or(0)
+    }
+
+    /// Get the text of the first free-text annotation with the tag.
+    #[inline]
+    pub fn annotation(&self, tag: &str) -> Option<&str> {
+        find_annotation(&self.annotations, tag)
+    }
+
+    /// Get the text of the per-column annotation with the tag.
+    #[inline]
+    pub fn column_annotation(&self, tag: &str) -> Option<&str> {
+        find_annotation(&self.column_annotations, tag)
+    }
+
+    /// Get the alignment identifier, from the "ID" annotation.
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.annotation("ID")
+    }
+
+    /// Get the aligned sequence with the identifier.
+    #[inline]
+    pub fn sequence(&self, id: &str) -> Option<&AlignedSequence> {
+        self.sequences.iter().find(|s| s.id == id)
+    }
+}
+
+/// Determine if the residue is an alignment gap.
+#[inline]
+pub(crate) fn is_gap(residue: u8) -> bool {
+    residue == b'-' || residue == b'.'
+}
+
+/// Find the text of the first annotation with the tag.
+#[inline]
+fn find_annotation<'a>(annotations: &'a [Annotation], tag: &str) -> Option<&'a str> {
+    annotations.iter().find(|a| a.tag == tag).map(|a| a.text.as_str())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use traits::*;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn properties_record_test() {
+        let r = cbs();
+        assert_eq!(r.columns(), 32);
+        assert_eq!(r.id(), Some("CBS"));
+        assert_eq!(r.annotation("AC"), Some("PF00571"));
+        assert_eq!(r.column_annotation("SS_cons"), Some("CCCCCHHHHHHHHHHHHHHH.EEEEEEEEEEE"));
+        assert_eq!(r.sequence("O83071/192-246").unwrap().ungapped(), b"MTCRAQLIAVPRASSLDEAIACAQKMAHL".to_vec());
+        assert!(r.is_valid());
+        assert!(r.is_complete());
+
+        // Remove the alignment identifier.
+        let mut x = r.clone();
+        x.annotations.remove(0);
+        assert!(x.is_valid());
+        assert!(!x.is_complete());
+
+        // Unequal sequence lengths.
+        let mut x = r.clone();
+        x.sequences[0].sequence.pop();
+        assert!(!x.is_valid());
+
+        // Column annotation with the wrong length.
+        let mut x = r.clone();
+        x.column_annotations[0].text.pop();
+        assert!(!x.is_valid());
+
+        assert!(!Record::new().is_valid());
+    }
+}
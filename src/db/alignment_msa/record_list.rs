@@ -0,0 +1,6 @@
+//! Model for alignment collections.
+
+use super::record::Record;
+
+/// Alignment record collection type.
+pub type RecordList = Vec<Record>;
@@ -0,0 +1,553 @@
+//! Helper utilities for Stockholm loading and saving.
+
+use std::io::prelude::*;
+
+use traits::*;
+use util::*;
+use super::record::{AlignedSequence, Annotation, Record};
+use super::record_list::RecordList;
+
+// STOCKHOLM ITERATOR
+
+/// Iterator to parse individual Stockholm alignments from a document.
+///
+/// Convert a stream to a lazy reader that fetches individual Stockholm
+/// alignments from the document.
+pub struct StockholmIter<T: BufRead> {
+    reader: T,
+    buf: Bytes,
+    line: Bytes,
+}
+
+impl<T: BufRead> StockholmIter<T> {
+    /// Create new StockholmIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        StockholmIter::with_capacity(reader, DEFAULT_BUFFER_CAPACITY, DEFAULT_LINE_CAPACITY)
+    }
+
+    /// Create new StockholmIter from a buffered reader and buffer capacities.
+    #[inline]
+    pub fn with_capacity(reader: T, buffer_capacity: usize, line_capacity: usize) -> Self {
+        StockholmIter {
+            reader: reader,
+            buf: Vec::with_capacity(buffer_capacity),
+            line: Vec::with_capacity(line_capacity)
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for StockholmIter<T> {
+    type Item = Result<Bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        bytes_next_skip_whitespace(b"# STOCKHOLM", None, &mut self.reader, &mut self.buf, &mut self.line)
+    }
+}
+
+// SIZE
+
+/// Estimate the size of a Stockholm record.
+///
+/// Used to prevent reallocations during record exportation to string,
+/// to minimize costly library calls.
+#[inline]
+fn estimate_record_size(record: &Record) -> usize {
+    const STOCKHOLM_VOCABULARY_SIZE: usize = 20;
+    const LINE_VOCABULARY_SIZE: usize = 8;
+    let width = label_width(record) + 1;
+    let columns = record.columns();
+    let annotation_size = |a: &Annotation| LINE_VOCABULARY_SIZE + a.tag.len() + a.text.len();
+    let column_count = record.column_annotations.len() + record.sequences.iter().fold(0, |sum, s| {
+        sum + 1 + s.column_annotations.len()
+    });
+
+    STOCKHOLM_VOCABULARY_SIZE +
+        record.annotations.iter().fold(0, |sum, a| sum + annotation_size(a)) +
+        record.sequences.iter().fold(0, |sum, s| {
+            sum + s.annotations.iter().fold(0, |sum, a| sum + s.id.len() + annotation_size(a))
+        }) +
+        column_count * (width + columns + 1)
+}
+
+/// Estimate the size of a Stockholm record list.
+#[inline]
+fn estimate_list_size(list: &RecordList) -> usize {
+    list.iter().fold(0, |sum, x| sum + estimate_record_size(x))
+}
+
+// WRITER
+
+/// Get the width of the longest label before the aligned columns.
+fn label_width(record: &Record) -> usize {
+    let sequences = record.sequences.iter().map(|s| {
+        s.column_annotations.iter().fold(s.id.len(), |width, a| {
+            width.max(s.id.len() + a.tag.len() + 6)
+        })
+    });
+    let columns = record.column_annotations.iter().map(|a| a.tag.len() + 5);
+    sequences.chain(columns).max().unwrap_or(0)
+}
+
+/// Write a label, padded to the width, followed by the aligned columns.
+#[inline]
+fn write_columns<T: Write>(writer: &mut T, label: &str, width: usize, columns: &[u8])
+    -> Result<()>
+{
+    write_alls!(writer, b"\n", label.as_bytes())?;
+    for _ in label.len()..width + 1 {
+        writer.write_all(b" ")?;
+    }
+    writer.write_all(columns)?;
+    Ok(())
+}
+
+#[inline(always)]
+fn to_stockholm<'a, T: Write>(writer: &mut T, record: &'a Record) -> Result<()> {
+    record_to_stockholm(writer, record)
+}
+
+/// Export record to Stockholm.
+///
+/// Each alignment is written as a single block, with the sequence
+/// identifiers and column annotation tags padded to a common width.
+pub fn record_to_stockholm<T: Write>(writer: &mut T, record: &Record)
+    -> Result<()>
+{
+    let width = label_width(record);
+    writer.write_all(b"# STOCKHOLM 1.0")?;
+
+    for annotation in record.annotations.iter() {
+        write_alls!(writer, b"\n#=GF ", annotation.tag.as_bytes(), b" ", annotation.text.as_bytes())?;
+    }
+
+    for sequence in record.sequences.iter() {
+        for annotation in sequence.annotations.iter() {
+            write_alls!(
+                writer,
+                b"\n#=GS ", sequence.id.as_bytes(),
+                b" ", annotation.tag.as_bytes(),
+                b" ", annotation.text.as_bytes()
+            )?;
+        }
+    }
+
+    for sequence in record.sequences.iter() {
+        write_columns(writer, &sequence.id, width, &sequence.sequence)?;
+        for annotation in sequence.column_annotations.iter() {
+            let label = format!("#=GR {} {}", sequence.id, annotation.tag);
+            write_columns(writer, &label, width, annotation.text.as_bytes())?;
+        }
+    }
+
+    for annotation in record.column_annotations.iter() {
+        let label = format!("#=GC {}", annotation.tag);
+        write_columns(writer, &label, width, annotation.text.as_bytes())?;
+    }
+
+    writer.write_all(b"\n//")?;
+
+    Ok(())
+}
+
+// WRITER -- DEFAULT
+
+#[inline(always)]
+fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
+    -> Result<TextWriterState<'_, T>>
+{
+    Ok(TextWriterState::new(writer, delimiter))
+}
+
+#[inline(always)]
+fn export_cb<'a, T: Write>(writer: &mut TextWriterState<T>, record: &'a Record)
+    -> Result<()>
+{
+    writer.export(record, &to_stockholm)
+}
+
+#[inline(always)]
+fn dest_cb<T: Write>(_: &mut TextWriterState<T>)
+    -> Result<()>
+{
+    Ok(())
+}
+
+/// Default exporter from a non-owning iterator to Stockholm.
+#[inline(always)]
+pub fn reference_iterator_to_stockholm<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to Stockholm.
+#[inline(always)]
+pub fn value_iterator_to_stockholm<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- STRICT
+
+/// Strict exporter from a non-owning iterator to Stockholm.
+#[inline(always)]
+pub fn reference_iterator_to_stockholm_strict<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to Stockholm.
+#[inline(always)]
+pub fn value_iterator_to_stockholm_strict<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- LENIENT
+
+/// Lenient exporter from a non-owning iterator to Stockholm.
+#[inline(always)]
+pub fn reference_iterator_to_stockholm_lenient<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to Stockholm.
+#[inline(always)]
+pub fn value_iterator_to_stockholm_lenient<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// READER
+
+/// Split the first word from a line, returning the word and the remainder.
+#[inline]
+fn split_word(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], line[i..].trim_start()),
+        None    => (line, ""),
+    }
+}
+
+/// Get the aligned sequence with the identifier, adding it if not present.
+fn sequence_mut<'a>(record: &'a mut Record, id: &str) -> &'a mut AlignedSequence {
+    let index = match record.sequences.iter().position(|s| s.id == id) {
+        Some(index) => index,
+        None        => {
+            record.sequences.push(AlignedSequence::new(id, b""));
+            record.sequences.len() - 1
+        },
+    };
+    &mut record.sequences[index]
+}
+
+/// Extend the per-column annotation with the tag, adding it if not present.
+fn extend_column_annotation(annotations: &mut Vec<Annotation>, tag: &str, text: &str) {
+    match annotations.iter().position(|a| a.tag == tag) {
+        Some(index) => annotations[index].text.push_str(text),
+        None        => annotations.push(Annotation::new(tag, text)),
+    }
+}
+
+/// Import record from Stockholm.
+///
+/// Interleaved alignments, split over multiple blocks, are joined.
+/// Unrecognized markup and comment lines are ignored.
+pub fn record_from_stockholm<T: BufRead>(reader: &mut T)
+    -> Result<Record>
+{
+    let mut lines = reader.lines();
+    let header = none_to_error!(lines.next(), InvalidInput)?;
+    bool_to_error!(strip_bom(header.trim_end()).starts_with("# STOCKHOLM"), InvalidInput);
+
+    let mut record = Record::new();
+    for line in lines {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        } else if line == "//" {
+            break;
+        }
+
+        let (word, rest) = split_word(line);
+        match word {
+            "#=GF" => {
+                let (tag, text) = split_word(rest);
+                record.annotations.push(Annotation::new(tag, text));
+            },
+            "#=GS" => {
+                let (id, rest) = split_word(rest);
+                let (tag, text) = split_word(rest);
+                sequence_mut(&mut record, id).annotations.push(Annotation::new(tag, text));
+            },
+            "#=GR" => {
+                let (id, rest) = split_word(rest);
+                let (tag, text) = split_word(rest);
+                let sequence = sequence_mut(&mut record, id);
+                extend_column_annotation(&mut sequence.column_annotations, tag, text);
+            },
+            "#=GC" => {
+                let (tag, text) = split_word(rest);
+                extend_column_annotation(&mut record.column_annotations, tag, text);
+            },
+            _ if word.starts_with('#') => (),
+            _ => {
+                bool_to_error!(!rest.is_empty(), InvalidInput);
+                sequence_mut(&mut record, word).sequence.extend_from_slice(rest.as_bytes());
+            },
+        }
+    }
+
+    Ok(record)
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `StockholmIter` and converts the text to records.
+pub struct StockholmRecordIter<T: BufRead> {
+    iter: StockholmIter<T>,
+    encoding: Encoding,
+}
+
+impl<T: BufRead> StockholmRecordIter<T> {
+    /// Create new StockholmRecordIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        StockholmRecordIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new StockholmRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
+        StockholmRecordIter {
+            iter: StockholmIter::with_capacity(reader, options.buffer_capacity, options.line_capacity),
+            encoding: options.encoding,
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for StockholmRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = match self.iter.next()? {
+            Err(e)   => return Some(Err(e)),
+            Ok(bytes) => bytes,
+        };
+
+        let bytes = self.encoding.decode_bytes(bytes);
+        let result = Record::from_stockholm_bytes(bytes.as_slice());
+        log_parsed!("Stockholm", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_stockholm<T: BufRead>(reader: T) -> StockholmRecordIter<T> {
+    StockholmRecordIter::new(reader)
+}
+
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_stockholm_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> StockholmRecordIter<T> {
+    StockholmRecordIter::with_options(reader, options)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `StockholmIter` and converts the text to records strictly.
+pub type StockholmRecordStrictIter<T> = StrictIter<Record, StockholmRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_stockholm_strict<T: BufRead>(reader: T) -> StockholmRecordStrictIter<T> {
+    StockholmRecordStrictIter::new(iterator_from_stockholm(reader))
+}
+
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_stockholm_strict_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> StockholmRecordStrictIter<T> {
+    StockholmRecordStrictIter::new(iterator_from_stockholm_with_options(reader, options))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `StockholmIter` and converts the text to records leniently.
+pub type StockholmRecordLenientIter<T> = LenientIter<Record, StockholmRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_stockholm_lenient<T: BufRead>(reader: T) -> StockholmRecordLenientIter<T> {
+    StockholmRecordLenientIter::new(iterator_from_stockholm(reader))
+}
+
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_stockholm_lenient_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> StockholmRecordLenientIter<T> {
+    StockholmRecordLenientIter::new(iterator_from_stockholm_with_options(reader, options))
+}
+
+// TRAITS
+
+impl Stockholm for Record {
+    #[inline]
+    fn estimate_stockholm_size(&self) -> usize {
+        estimate_record_size(self)
+    }
+
+    #[inline(always)]
+    fn to_stockholm<T: Write>(&self, writer: &mut T) -> Result<()> {
+        record_to_stockholm(writer, self)
+    }
+
+    #[inline(always)]
+    fn from_stockholm<T: BufRead>(reader: &mut T) -> Result<Self> {
+        record_from_stockholm(reader)
+    }
+
+    #[inline(always)]
+    fn from_stockholm_iter<'a, T: 'a + BufRead>(reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_stockholm(reader))
+    }
+
+    #[inline(always)]
+    fn to_stockholm_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+    {
+        reference_iterator_to_stockholm(writer, iter)
+    }
+}
+
+impl Stockholm for RecordList {
+    #[inline]
+    fn estimate_stockholm_size(&self) -> usize {
+        estimate_list_size(self)
+    }
+
+    #[inline(always)]
+    fn to_stockholm<T: Write>(&self, writer: &mut T) -> Result<()> {
+        reference_iterator_to_stockholm(writer, self.iter())
+    }
+
+    #[inline(always)]
+    fn from_stockholm<T: BufRead>(reader: &mut T) -> Result<RecordList> {
+        iterator_from_stockholm(reader).collect()
+    }
+}
+
+impl StockholmCollection for RecordList {
+    #[inline(always)]
+    fn to_stockholm_strict<T: Write>(&self, writer: &mut T) -> Result<()> {
+        reference_iterator_to_stockholm_strict(writer, self.iter())
+    }
+
+    #[inline(always)]
+    fn to_stockholm_lenient<T: Write>(&self, writer: &mut T) -> Result<()> {
+        reference_iterator_to_stockholm_lenient(writer, self.iter())
+    }
+
+    #[inline(always)]
+    fn from_stockholm_strict<T: BufRead>(reader: &mut T) -> Result<RecordList> {
+        iterator_from_stockholm_strict(reader).collect()
+    }
+
+    #[inline(always)]
+    fn from_stockholm_lenient<T: BufRead>(reader: &mut T) -> Result<RecordList> {
+        iterator_from_stockholm_lenient(reader).collect()
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test::windows_text;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn record_stockholm_test() {
+        let r = cbs();
+        assert_eq!(r.to_stockholm_bytes().unwrap(), CBS_STOCKHOLM.to_vec());
+        assert_eq!(Record::from_stockholm_bytes(CBS_STOCKHOLM).unwrap(), r);
+
+        // Interleaved blocks, with extra padding and comments.
+        let x = Record::from_stockholm_bytes(CBS_INTERLEAVED_STOCKHOLM).unwrap();
+        assert_eq!(x.sequences, r.sequences);
+        assert_eq!(x.column_annotations, r.column_annotations);
+
+        assert!(Record::from_stockholm_bytes(b"O31698/18-71 AVKK\n//").is_err());
+        assert!(Record::from_stockholm_bytes(b"# STOCKHOLM 1.0\nO31698/18-71\n//").is_err());
+        assert!(Record::from_stockholm_bytes(b"").is_err());
+    }
+
+    #[test]
+    fn iterator_to_stockholm_test() {
+        let v = vec![cbs(), cbs()];
+        let u = vec![cbs(), Record::new(), cbs()];
+        let expected = [CBS_STOCKHOLM, b"\n", CBS_STOCKHOLM].concat();
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_stockholm(&mut w, v.iter()).unwrap();
+        assert_eq!(w.into_inner(), expected);
+
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_stockholm_strict(&mut w, u.iter()).is_err());
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_stockholm_lenient(&mut w, u.iter()).unwrap();
+        assert_eq!(w.into_inner(), expected);
+    }
+
+    #[test]
+    fn iterator_from_stockholm_test() {
+        let text = [CBS_STOCKHOLM, b"\n", CBS_STOCKHOLM].concat();
+        let iter = iterator_from_stockholm(Cursor::new(&text));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![cbs(), cbs()]);
+
+        // Windows line endings and trailing whitespace.
+        let text = windows_text(&text);
+        let iter = iterator_from_stockholm_strict(Cursor::new(text));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![cbs(), cbs()]);
+
+        let text = [CBS_STOCKHOLM, b"\n# STOCKHOLM 1.0\n//\n"].concat();
+        let iter = iterator_from_stockholm_strict(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_stockholm_lenient(Cursor::new(&text));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap(), vec![cbs()]);
+    }
+}
@@ -0,0 +1,47 @@
+//! Shared helper utilities for alignment unit testing.
+
+use super::record::{AlignedSequence, Annotation, Record};
+
+// RECORDS
+
+/// Create an alignment for part of the CBS domain family.
+pub fn cbs() -> Record {
+    let mut o31698 = AlignedSequence::new("O31698/18-71", b"AVKKHEHVQDVLLRGSNVLA.CAMENSKLYGS");
+    o31698.annotations.push(Annotation::new("AC", "O31698"));
+    o31698.column_annotations.push(Annotation::new("SS", "CCCHHHHHHHHHHHHHHHHH.EEEEEEEEEEE"));
+
+    Record {
+        annotations: vec![
+            Annotation::new("ID", "CBS"),
+            Annotation::new("AC", "PF00571"),
+            Annotation::new("DE", "CBS domain"),
+        ],
+        sequences: vec![
+            o31698,
+            AlignedSequence::new("O83071/192-246", b"MTCRAQLIAVPRASSLDE..AIACAQKMAHL."),
+        ],
+        column_annotations: vec![
+            Annotation::new("SS_cons", "CCCCCHHHHHHHHHHHHHHH.EEEEEEEEEEE"),
+        ],
+    }
+}
+
+// STOCKHOLM
+
+/// Stockholm text for the CBS alignment.
+#[cfg(feature = "stockholm")]
+pub const CBS_STOCKHOLM: &'static [u8] = b"# STOCKHOLM 1.0\n#=GF ID CBS\n#=GF AC PF00571\n#=GF DE CBS domain\n#=GS O31698/18-71 AC O31698\nO31698/18-71         AVKKHEHVQDVLLRGSNVLA.CAMENSKLYGS\n#=GR O31698/18-71 SS CCCHHHHHHHHHHHHHHHHH.EEEEEEEEEEE\nO83071/192-246       MTCRAQLIAVPRASSLDE..AIACAQKMAHL.\n#=GC SS_cons         CCCCCHHHHHHHHHHHHHHH.EEEEEEEEEEE\n//";
+
+/// Interleaved Stockholm text for the CBS alignment, without alignment annotations.
+#[cfg(feature = "stockholm")]
+pub const CBS_INTERLEAVED_STOCKHOLM: &'static [u8] = b"# STOCKHOLM 1.0\n# Interleaved CBS alignment.\n#=GS O31698/18-71 AC O31698\n\nO31698/18-71      AVKKHEHVQDVLLRGS\n#=GR O31698/18-71 SS  CCCHHHHHHHHHHHHH\nO83071/192-246    MTCRAQLIAVPRASSL\n#=GC SS_cons      CCCCCHHHHHHHHHHH\n\nO31698/18-71      NVLA.CAMENSKLYGS\n#=GR O31698/18-71 SS  HHHH.EEEEEEEEEEE\nO83071/192-246    DE..AIACAQKMAHL.\n#=GC SS_cons      HHHH.EEEEEEEEEEE\n//\n";
+
+// CLUSTAL
+
+/// Clustal text for the CBS alignment.
+#[cfg(feature = "clustal")]
+pub const CBS_CLUSTAL: &'static [u8] = b"CLUSTAL W multiple sequence alignment\n\n\nO31698/18-71        AVKKHEHVQDVLLRGSNVLA.CAMENSKLYGS\nO83071/192-246      MTCRAQLIAVPRASSLDE..AIACAQKMAHL.\n                     . : : :      . :     *  :.  :\n";
+
+/// Clustal text for the CBS alignment, split into blocks with residue counts.
+#[cfg(feature = "clustal")]
+pub const CBS_BLOCKS_CLUSTAL: &'static [u8] = b"CLUSTAL 2.1 multiple sequence alignment\n\n\nO31698/18-71      AVKKHEHVQDVLLRGS 16\nO83071/192-246    MTCRAQLIAVPRASSL 16\n                  :        .  :   \n\nO31698/18-71      NVLA.CAMENSKLYGS 31\nO83071/192-246    DE..AIACAQKMAHL. 29\n                       .   :      \n";
@@ -0,0 +1,33 @@
+//! Valid trait implementation for alignment models.
+
+use traits::Valid;
+use super::record::Record;
+use super::record_list::RecordList;
+
+/// Determine if an identifier or annotation tag is a single word.
+#[inline]
+fn is_word(word: &str) -> bool {
+    !word.is_empty() && !word.contains(char::is_whitespace)
+}
+
+impl Valid for Record {
+    fn is_valid(&self) -> bool {
+        let columns = self.columns();
+        !self.sequences.is_empty() &&
+            self.sequences.iter().all(|s| {
+                is_word(&s.id) &&
+                s.sequence.len() == columns &&
+                s.annotations.iter().all(|a| is_word(&a.tag)) &&
+                s.column_annotations.iter().all(|a| is_word(&a.tag) && a.text.len() == columns)
+            }) &&
+            self.annotations.iter().all(|a| is_word(&a.tag)) &&
+            self.column_annotations.iter().all(|a| is_word(&a.tag) && a.text.len() == columns)
+    }
+}
+
+impl Valid for RecordList {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.iter().all(|ref x| x.is_valid())
+    }
+}
@@ -0,0 +1,217 @@
+//! Helper utilities for HMMER per-domain hit table loading.
+//!
+//! Per-domain hit tables are written by `hmmscan` and `hmmsearch` with
+//! the `--domtblout` option, with one whitespace-delimited line per
+//! domain, and comment lines starting with "#".
+
+use std::io::prelude::*;
+use std::io::Lines;
+
+use util::*;
+use super::record::Record;
+
+/// Number of whitespace-delimited columns before the description.
+const COLUMN_COUNT: usize = 22;
+
+// DOMTBL ITERATOR
+
+/// Iterator to parse individual domain hit lines from a table.
+///
+/// Convert a stream to a lazy reader that fetches individual domain
+/// hit lines from the table, skipping comments and blank lines.
+pub struct DomtblIter<T: BufRead> {
+    lines: Lines<T>,
+}
+
+impl<T: BufRead> DomtblIter<T> {
+    /// Create new DomtblIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        DomtblIter {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for DomtblIter<T> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Err(e)   => return Some(Err(From::from(e))),
+                Ok(line) => line,
+            };
+
+            let trimmed = strip_bom(line.trim());
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                return Some(Ok(String::from(trimmed)));
+            }
+        }
+    }
+}
+
+// READER
+
+/// Convert a missing ("-") accession to an empty string.
+#[inline]
+fn accession_from_str(accession: &str) -> String {
+    match accession {
+        "-" => String::new(),
+        _   => String::from(accession),
+    }
+}
+
+/// Import record from a domain hit line.
+///
+/// The description, the final column, may contain whitespace.
+pub fn record_from_domtbl(line: &str)
+    -> Result<Record>
+{
+    let mut columns: Vec<&str> = Vec::with_capacity(COLUMN_COUNT);
+    let mut rest = line.trim();
+    while columns.len() < COLUMN_COUNT {
+        bool_to_error!(!rest.is_empty(), InvalidInput);
+        let index = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        columns.push(&rest[..index]);
+        rest = rest[index..].trim_start();
+    }
+
+    Ok(Record {
+        target_name: String::from(columns[0]),
+        target_accession: accession_from_str(columns[1]),
+        target_length: from_string(columns[2])?,
+        query_name: String::from(columns[3]),
+        query_accession: accession_from_str(columns[4]),
+        query_length: from_string(columns[5])?,
+        evalue: from_string(columns[6])?,
+        score: from_string(columns[7])?,
+        bias: from_string(columns[8])?,
+        domain_number: from_string(columns[9])?,
+        domain_count: from_string(columns[10])?,
+        conditional_evalue: from_string(columns[11])?,
+        independent_evalue: from_string(columns[12])?,
+        domain_score: from_string(columns[13])?,
+        domain_bias: from_string(columns[14])?,
+        hmm_from: from_string(columns[15])?,
+        hmm_to: from_string(columns[16])?,
+        ali_from: from_string(columns[17])?,
+        ali_to: from_string(columns[18])?,
+        env_from: from_string(columns[19])?,
+        env_to: from_string(columns[20])?,
+        accuracy: from_string(columns[21])?,
+        description: String::from(rest),
+    })
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a table.
+///
+/// Wraps `DomtblIter` and converts the lines to records.
+pub struct DomtblRecordIter<T: BufRead> {
+    iter: DomtblIter<T>,
+}
+
+impl<T: BufRead> DomtblRecordIter<T> {
+    /// Create new DomtblRecordIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        DomtblRecordIter {
+            iter: DomtblIter::new(reader),
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for DomtblRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.iter.next()? {
+            Err(e)   => Err(e),
+            Ok(line) => record_from_domtbl(&line),
+        };
+        log_parsed!("domtbl", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_domtbl<T: BufRead>(reader: T) -> DomtblRecordIter<T> {
+    DomtblRecordIter::new(reader)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a table.
+///
+/// Wraps `DomtblIter` and converts the lines to records strictly.
+pub type DomtblRecordStrictIter<T> = StrictIter<Record, DomtblRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_domtbl_strict<T: BufRead>(reader: T) -> DomtblRecordStrictIter<T> {
+    DomtblRecordStrictIter::new(iterator_from_domtbl(reader))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a table.
+///
+/// Wraps `DomtblIter` and converts the lines to records leniently.
+pub type DomtblRecordLenientIter<T> = LenientIter<Record, DomtblRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_domtbl_lenient<T: BufRead>(reader: T) -> DomtblRecordLenientIter<T> {
+    DomtblRecordLenientIter::new(iterator_from_domtbl(reader))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test::windows_text;
+    use super::*;
+    use super::super::record_list::RecordList;
+    use super::super::test::*;
+
+    #[test]
+    fn record_domtbl_test() {
+        let line = "Gp_dh_C              PF02800.20   157 sp|P46406|G3P_RABIT  -            333   1.1e-68  229.8   0.0   1   1   1.2e-72   1.9e-68  229.1   0.0     1   157   159   315   159   315 0.99 Glyceraldehyde 3-phosphate dehydrogenase, C-terminal domain";
+        assert_eq!(record_from_domtbl(line).unwrap(), gapdh_c());
+
+        // Missing description.
+        let x = record_from_domtbl(&line[..line.find(" Glyceraldehyde").unwrap()]).unwrap();
+        assert_eq!(x.description, "");
+        assert_eq!(x.accuracy, 0.99);
+
+        // Missing or invalid columns.
+        assert!(record_from_domtbl(&line[..60]).is_err());
+        assert!(record_from_domtbl(&line.replace("157", "x")).is_err());
+        assert!(record_from_domtbl("").is_err());
+    }
+
+    #[test]
+    fn iterator_from_domtbl_test() {
+        let iter = iterator_from_domtbl(Cursor::new(GAPDH_DOMTBL));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh_n(), gapdh_c()]);
+
+        // Windows line endings and trailing whitespace.
+        let text = windows_text(GAPDH_DOMTBL);
+        let iter = iterator_from_domtbl_strict(Cursor::new(text));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh_n(), gapdh_c()]);
+
+        // Invalid domain number.
+        let text = [GAPDH_DOMTBL, b"Gp_dh_C PF02800.20 157 P46406 - 333 1e-68 229.8 0.0 2 1 1e-72 1e-68 229.1 0.0 1 157 159 315 159 315 0.99 -\n"].concat();
+        let iter = iterator_from_domtbl_strict(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_domtbl_lenient(Cursor::new(&text));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap(), vec![gapdh_n(), gapdh_c()]);
+    }
+}
@@ -0,0 +1,19 @@
+//! HMMER integrations.
+//!
+//! Reads per-domain hit tables from `hmmscan` and `hmmsearch`, for
+//! example, Pfam domain annotations for UniProt protein sequences.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
+
+pub(crate) mod domtbl;
+pub(crate) mod record;
+pub(crate) mod record_list;
+pub(crate) mod valid;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+// Re-export the models into the parent module.
+pub use self::record::Record;
+pub use self::record_list::{RecordList, RecordListExt};
@@ -0,0 +1,129 @@
+//! Model for HMMER domain hit definitions.
+
+use db::uniprot::re::accession_from_identifier;
+
+/// Model for a single domain hit, from a per-domain hit table.
+///
+/// For `hmmscan`, the target is the profile HMM (for example, a Pfam
+/// family) and the query is the sequence. For `hmmsearch`, the target
+/// is the sequence and the query is the profile HMM. Missing accessions,
+/// written as "-", are empty.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Record {
+    /// Name of the target sequence or profile.
+    pub target_name: String,
+    /// Accession of the target sequence or profile.
+    pub target_accession: String,
+    /// Length of the target sequence or profile.
+    pub target_length: u32,
+    /// Name of the query sequence or profile.
+    pub query_name: String,
+    /// Accession of the query sequence or profile.
+    pub query_accession: String,
+    /// Length of the query sequence or profile.
+    pub query_length: u32,
+    /// E-value of the full sequence.
+    pub evalue: f64,
+    /// Bit score of the full sequence.
+    pub score: f64,
+    /// Biased composition score correction for the full sequence.
+    pub bias: f64,
+    /// Index of the domain within the sequence, starting from 1.
+    pub domain_number: u32,
+    /// Number of domains within the sequence.
+    pub domain_count: u32,
+    /// Conditional E-value of the domain.
+    pub conditional_evalue: f64,
+    /// Independent E-value of the domain.
+    pub independent_evalue: f64,
+    /// Bit score of the domain.
+    pub domain_score: f64,
+    /// Biased composition score correction for the domain.
+    pub domain_bias: f64,
+    /// Start of the alignment within the profile HMM.
+    pub hmm_from: u32,
+    /// End of the alignment within the profile HMM.
+    pub hmm_to: u32,
+    /// Start of the alignment within the sequence.
+    pub ali_from: u32,
+    /// End of the alignment within the sequence.
+    pub ali_to: u32,
+    /// Start of the domain envelope within the sequence.
+    pub env_from: u32,
+    /// End of the domain envelope within the sequence.
+    pub env_to: u32,
+    /// Mean posterior probability of the aligned residues.
+    pub accuracy: f64,
+    /// Description of the target.
+    pub description: String,
+}
+
+impl Record {
+    /// Create new, empty domain hit.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            target_name: String::new(),
+            target_accession: String::new(),
+            target_length: 0,
+            query_name: String::new(),
+            query_accession: String::new(),
+            query_length: 0,
+            evalue: 0.0,
+            score: 0.0,
+            bias: 0.0,
+            domain_number: 0,
+            domain_count: 0,
+            conditional_evalue: 0.0,
+            independent_evalue: 0.0,
+            domain_score: 0.0,
+            domain_bias: 0.0,
+            hmm_from: 0,
+            hmm_to: 0,
+            ali_from: 0,
+            ali_to: 0,
+            env_from: 0,
+            env_to: 0,
+            accuracy: 0.0,
+            description: String::new(),
+        }
+    }
+
+    /// Get the UniProt accession number of the sequence.
+    ///
+    /// The accession is extracted from UniProt FASTA identifiers
+    /// ("sp|P46406|G3P_RABIT"), or from bare accession numbers, for
+    /// either the query (`hmmscan`) or target (`hmmsearch`) name.
+    #[inline]
+    pub fn accession(&self) -> Option<&str> {
+        accession_from_identifier(&self.query_name)
+            .or_else(|| accession_from_identifier(&self.target_name))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn accession_test() {
+        let mut x = gapdh_n();
+        assert_eq!(x.accession(), Some("P46406"));
+
+        // hmmsearch, with a bare accession as the target.
+        x.query_name = String::from("Gp_dh_N");
+        x.target_name = String::from("P46406");
+        assert_eq!(x.accession(), Some("P46406"));
+
+        x.target_name = String::from("tr|A0A024R161|A0A024R161_HUMAN");
+        assert_eq!(x.accession(), Some("A0A024R161"));
+
+        x.target_name = String::from("sp|G3P_RABIT");
+        assert_eq!(x.accession(), None);
+        assert_eq!(Record::new().accession(), None);
+    }
+}
@@ -0,0 +1,56 @@
+//! Model for HMMER domain hit collections.
+
+use std::collections::HashMap;
+
+use super::record::Record;
+
+/// Domain hit collection type.
+pub type RecordList = Vec<Record>;
+
+/// Lookup maps for domain hit collections.
+///
+/// Group domain hits by sequence, to merge domain annotations onto
+/// the UniProt records for the sequences.
+pub trait RecordListExt {
+    /// Create a map from UniProt accession number to all domain hits.
+    ///
+    /// Hits without a UniProt accession number are not indexed.
+    fn index_by_accession(&self) -> HashMap<&str, Vec<&Record>>;
+}
+
+impl RecordListExt for [Record] {
+    fn index_by_accession(&self) -> HashMap<&str, Vec<&Record>> {
+        let mut map: HashMap<&str, Vec<&Record>> = HashMap::new();
+        for record in self.iter() {
+            if let Some(accession) = record.accession() {
+                map.entry(accession).or_insert_with(Vec::new).push(record);
+            }
+        }
+        map
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use db::uniprot;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn index_list_test() {
+        let v = vec![gapdh_n(), gapdh_c(), Record::new()];
+        let map = v.index_by_accession();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["P46406"], vec![&v[0], &v[1]]);
+
+        // Merge onto the UniProt record.
+        let record = uniprot::test::gapdh();
+        let domains: Vec<&str> = map[record.id.as_str()].iter()
+            .map(|d| d.target_accession.as_str())
+            .collect();
+        assert_eq!(domains, vec!["PF00044.24", "PF02800.20"]);
+    }
+}
@@ -0,0 +1,68 @@
+//! Shared helper utilities for HMMER unit testing.
+
+use super::record::Record;
+
+// RECORDS
+
+/// Create a domain hit for the GAPDH NAD binding domain.
+pub fn gapdh_n() -> Record {
+    Record {
+        target_name: String::from("Gp_dh_N"),
+        target_accession: String::from("PF00044.24"),
+        target_length: 102,
+        query_name: String::from("sp|P46406|G3P_RABIT"),
+        query_accession: String::new(),
+        query_length: 333,
+        evalue: 4.3e-38,
+        score: 130.1,
+        bias: 0.1,
+        domain_number: 1,
+        domain_count: 1,
+        conditional_evalue: 6.9e-42,
+        independent_evalue: 6.9e-38,
+        domain_score: 129.4,
+        domain_bias: 0.1,
+        hmm_from: 1,
+        hmm_to: 101,
+        ali_from: 3,
+        ali_to: 104,
+        env_from: 3,
+        env_to: 106,
+        accuracy: 0.97,
+        description: String::from("Glyceraldehyde 3-phosphate dehydrogenase, NAD binding domain"),
+    }
+}
+
+/// Create a domain hit for the GAPDH C-terminal domain.
+pub fn gapdh_c() -> Record {
+    Record {
+        target_name: String::from("Gp_dh_C"),
+        target_accession: String::from("PF02800.20"),
+        target_length: 157,
+        query_name: String::from("sp|P46406|G3P_RABIT"),
+        query_accession: String::new(),
+        query_length: 333,
+        evalue: 1.1e-68,
+        score: 229.8,
+        bias: 0.0,
+        domain_number: 1,
+        domain_count: 1,
+        conditional_evalue: 1.2e-72,
+        independent_evalue: 1.9e-68,
+        domain_score: 229.1,
+        domain_bias: 0.0,
+        hmm_from: 1,
+        hmm_to: 157,
+        ali_from: 159,
+        ali_to: 315,
+        env_from: 159,
+        env_to: 315,
+        accuracy: 0.99,
+        description: String::from("Glyceraldehyde 3-phosphate dehydrogenase, C-terminal domain"),
+    }
+}
+
+// DOMTBL
+
+/// Domain table from `hmmscan --domtblout` for GAPDH.
+pub const GAPDH_DOMTBL: &'static [u8] = b"#                                                                            --- full sequence --- -------------- this domain -------------   hmm coord   ali coord   env coord\n# target name        accession   tlen query name           accession   qlen   E-value  score  bias   #  of  c-Evalue  i-Evalue  score  bias  from    to  from    to  from    to  acc description of target\n#------------------- ---------- ----- -------------------- ---------- ----- --------- ------ ----- --- --- --------- --------- ------ ----- ----- ----- ----- ----- ----- ----- ---- ---------------------\nGp_dh_N              PF00044.24   102 sp|P46406|G3P_RABIT  -            333   4.3e-38  130.1   0.1   1   1   6.9e-42   6.9e-38  129.4   0.1     1   101     3   104     3   106 0.97 Glyceraldehyde 3-phosphate dehydrogenase, NAD binding domain\nGp_dh_C              PF02800.20   157 sp|P46406|G3P_RABIT  -            333   1.1e-68  229.8   0.0   1   1   1.2e-72   1.9e-68  229.1   0.0     1   157   159   315   159   315 0.99 Glyceraldehyde 3-phosphate dehydrogenase, C-terminal domain\n#\n# Program:         hmmscan\n# [ok]\n";
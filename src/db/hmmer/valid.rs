@@ -0,0 +1,43 @@
+//! Valid trait implementation for HMMER models.
+
+use traits::Valid;
+use super::record::Record;
+use super::record_list::RecordList;
+
+/// Determine if a name is a single, non-empty word.
+#[inline]
+fn is_word(name: &str) -> bool {
+    !name.is_empty() && !name.contains(char::is_whitespace)
+}
+
+/// Determine if a coordinate range is non-empty and 1-based.
+#[inline]
+fn is_range(from: u32, to: u32) -> bool {
+    from > 0 && from <= to
+}
+
+impl Valid for Record {
+    fn is_valid(&self) -> bool {
+        is_word(&self.target_name) &&
+            is_word(&self.query_name) &&
+            self.target_length > 0 &&
+            self.query_length > 0 &&
+            self.evalue >= 0.0 &&
+            self.conditional_evalue >= 0.0 &&
+            self.independent_evalue >= 0.0 &&
+            self.domain_number > 0 &&
+            self.domain_number <= self.domain_count &&
+            is_range(self.hmm_from, self.hmm_to) &&
+            is_range(self.ali_from, self.ali_to) &&
+            is_range(self.env_from, self.env_to) &&
+            self.accuracy >= 0.0 &&
+            self.accuracy <= 1.0
+    }
+}
+
+impl Valid for RecordList {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.iter().all(|ref x| x.is_valid())
+    }
+}
@@ -0,0 +1,20 @@
+//! Re-exports for low-level, efficient APIs.
+//!
+//! In order for high-performance processing of large documents,
+//! We must use parsers that lazily read and write items to and from
+//! documents. The writers accept both by-value and by-reference
+//! iterators, allowing you to easily chain lazy readers and writers
+//! to convert between export formats.
+//!
+//! The memory footprint of these lazy low-level functions is minimal,
+//! typically < 16 KB required for internal buffers, and < 1 KB for each
+//! individual item.
+
+// Re-export the strict and lenient iterator wrappers, to name the
+// strict and lenient reader types.
+pub use util::iterator::{LenientIter, StrictIter};
+
+pub use super::tsv::*;
+
+#[cfg(feature = "xml")]
+pub use super::xml::*;
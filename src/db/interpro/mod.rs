@@ -0,0 +1,23 @@
+//! InterProScan integrations.
+//!
+//! Reads protein signature matches from InterProScan TSV and XML
+//! output, for example, to annotate UniProt records with domains and
+//! GO terms.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
+
+pub(crate) mod record;
+pub(crate) mod record_list;
+pub(crate) mod tsv;
+pub(crate) mod valid;
+
+#[cfg(feature = "xml")]
+pub(crate) mod xml;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+// Re-export the models into the parent module.
+pub use self::record::Record;
+pub use self::record_list::{RecordList, RecordListExt};
@@ -0,0 +1,94 @@
+//! Model for InterProScan match definitions.
+
+use db::uniprot::re::accession_from_identifier;
+
+/// Model for a single signature match location on a protein.
+///
+/// Matches with multiple locations have one record per location.
+/// Missing values, written as "-" in the TSV output, are empty.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Record {
+    /// Protein identifier, from the input sequence.
+    pub accession: String,
+    /// MD5 digest of the protein sequence.
+    pub md5: String,
+    /// Length of the protein sequence.
+    pub length: u32,
+    /// Member database for the signature, for example, "Pfam".
+    pub member_database: String,
+    /// Signature accession, for example, "PF00044".
+    pub signature_accession: String,
+    /// Signature description.
+    pub signature_description: String,
+    /// Start of the match within the protein.
+    pub start: u32,
+    /// End of the match within the protein.
+    pub stop: u32,
+    /// E-value or score of the match, if reported by the member database.
+    pub evalue: Option<f64>,
+    /// Match status, "T" for true.
+    pub status: String,
+    /// Date of the analysis, in "DD-MM-YYYY" format.
+    pub date: String,
+    /// InterPro entry accession, for example, "IPR020828".
+    pub interpro_accession: String,
+    /// InterPro entry description.
+    pub interpro_description: String,
+    /// GO term identifiers, for example, "GO:0016620".
+    pub go_terms: Vec<String>,
+    /// Pathway annotations, for example, "KEGG: 00010+1.2.1.12".
+    pub pathways: Vec<String>,
+}
+
+impl Record {
+    /// Create new, empty match.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            accession: String::new(),
+            md5: String::new(),
+            length: 0,
+            member_database: String::new(),
+            signature_accession: String::new(),
+            signature_description: String::new(),
+            start: 0,
+            stop: 0,
+            evalue: None,
+            status: String::new(),
+            date: String::new(),
+            interpro_accession: String::new(),
+            interpro_description: String::new(),
+            go_terms: vec![],
+            pathways: vec![],
+        }
+    }
+
+    /// Get the UniProt accession number of the protein.
+    ///
+    /// The accession is extracted from UniProt FASTA identifiers
+    /// ("sp|P46406|G3P_RABIT"), or from bare accession numbers.
+    #[inline]
+    pub fn uniprot_accession(&self) -> Option<&str> {
+        accession_from_identifier(&self.accession)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::super::test::*;
+
+    #[test]
+    fn uniprot_accession_test() {
+        let mut x = gapdh_pfam();
+        assert_eq!(x.uniprot_accession(), Some("P46406"));
+
+        x.accession = String::from("sp|P46406|G3P_RABIT");
+        assert_eq!(x.uniprot_accession(), Some("P46406"));
+
+        x.accession = String::from("contig_1");
+        assert_eq!(x.uniprot_accession(), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Model for InterProScan match collections.
+
+use std::collections::HashMap;
+
+use super::record::Record;
+
+/// InterProScan match collection type.
+pub type RecordList = Vec<Record>;
+
+/// Lookup maps for InterProScan match collections.
+///
+/// Group matches by protein, to merge signature and GO annotations
+/// onto the UniProt records for the proteins.
+pub trait RecordListExt {
+    /// Create a map from UniProt accession number to all matches.
+    ///
+    /// Matches without a UniProt accession number are not indexed.
+    fn index_by_accession(&self) -> HashMap<&str, Vec<&Record>>;
+
+    /// Create a map from UniProt accession number to the unique GO terms.
+    ///
+    /// GO terms are sorted, and matches without a UniProt accession
+    /// number are not indexed.
+    fn go_terms_by_accession(&self) -> HashMap<&str, Vec<&str>>;
+}
+
+impl RecordListExt for [Record] {
+    fn index_by_accession(&self) -> HashMap<&str, Vec<&Record>> {
+        let mut map: HashMap<&str, Vec<&Record>> = HashMap::new();
+        for record in self.iter() {
+            if let Some(accession) = record.uniprot_accession() {
+                map.entry(accession).or_insert_with(Vec::new).push(record);
+            }
+        }
+        map
+    }
+
+    fn go_terms_by_accession(&self) -> HashMap<&str, Vec<&str>> {
+        let mut map: HashMap<&str, Vec<&str>> = HashMap::new();
+        for record in self.iter() {
+            if let Some(accession) = record.uniprot_accession() {
+                let terms = map.entry(accession).or_insert_with(Vec::new);
+                terms.extend(record.go_terms.iter().map(|t| t.as_str()));
+            }
+        }
+        for terms in map.values_mut() {
+            terms.sort();
+            terms.dedup();
+        }
+        map
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use db::uniprot;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn index_list_test() {
+        let v = vec![gapdh_pfam(), gapdh_prints(), Record::new()];
+        let map = v.index_by_accession();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["P46406"], vec![&v[0], &v[1]]);
+
+        // Merge onto the UniProt record.
+        let record = uniprot::test::gapdh();
+        let terms = v.go_terms_by_accession();
+        assert_eq!(terms[record.id.as_str()], vec!["GO:0016620", "GO:0050661", "GO:0055114"]);
+    }
+}
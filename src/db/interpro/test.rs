@@ -0,0 +1,79 @@
+//! Shared helper utilities for InterProScan unit testing.
+
+use super::record::Record;
+
+// RECORDS
+
+/// Create a Pfam match for the GAPDH NAD binding domain.
+pub fn gapdh_pfam() -> Record {
+    Record {
+        accession: String::from("P46406"),
+        md5: String::from("a7bf3b3fac0aef2f2fd7dd6aa2ec7d33"),
+        length: 333,
+        member_database: String::from("Pfam"),
+        signature_accession: String::from("PF00044"),
+        signature_description: String::from("Glyceraldehyde 3-phosphate dehydrogenase, NAD binding domain"),
+        start: 3,
+        stop: 104,
+        evalue: Some(6.9e-38),
+        status: String::from("T"),
+        date: String::from("08-10-2020"),
+        interpro_accession: String::from("IPR020828"),
+        interpro_description: String::from("Glyceraldehyde 3-phosphate dehydrogenase, NAD(P) binding domain"),
+        go_terms: vec![String::from("GO:0016620"), String::from("GO:0050661"), String::from("GO:0055114")],
+        pathways: vec![],
+    }
+}
+
+/// Create a PRINTS match for the GAPDH signature.
+pub fn gapdh_prints() -> Record {
+    Record {
+        accession: String::from("P46406"),
+        md5: String::from("a7bf3b3fac0aef2f2fd7dd6aa2ec7d33"),
+        length: 333,
+        member_database: String::from("PRINTS"),
+        signature_accession: String::from("PR00078"),
+        signature_description: String::from("Glyceraldehyde-3-phosphate dehydrogenase signature"),
+        start: 110,
+        stop: 123,
+        evalue: Some(1.5e-50),
+        status: String::from("T"),
+        date: String::from("08-10-2020"),
+        interpro_accession: String::from("IPR020831"),
+        interpro_description: String::from("Glyceraldehyde/Erythrose phosphate dehydrogenase family"),
+        go_terms: vec![String::from("GO:0016620"), String::from("GO:0055114")],
+        pathways: vec![String::from("KEGG: 00010+1.2.1.12"), String::from("Reactome: R-HSA-70171")],
+    }
+}
+
+/// Create a MobiDB-lite disorder prediction for GAPDH.
+pub fn gapdh_mobidb() -> Record {
+    Record {
+        accession: String::from("P46406"),
+        md5: String::from("a7bf3b3fac0aef2f2fd7dd6aa2ec7d33"),
+        length: 333,
+        member_database: String::from("MobiDBLite"),
+        signature_accession: String::from("mobidb-lite"),
+        signature_description: String::from("consensus disorder prediction"),
+        start: 1,
+        stop: 4,
+        evalue: None,
+        status: String::from("T"),
+        date: String::from("08-10-2020"),
+        interpro_accession: String::from(""),
+        interpro_description: String::from(""),
+        go_terms: vec![],
+        pathways: vec![],
+    }
+}
+
+// TSV
+
+/// InterProScan TSV output for GAPDH.
+pub const GAPDH_TSV: &'static [u8] = b"P46406\ta7bf3b3fac0aef2f2fd7dd6aa2ec7d33\t333\tPfam\tPF00044\tGlyceraldehyde 3-phosphate dehydrogenase, NAD binding domain\t3\t104\t6.9E-38\tT\t08-10-2020\tIPR020828\tGlyceraldehyde 3-phosphate dehydrogenase, NAD(P) binding domain\tGO:0016620|GO:0050661|GO:0055114\t-\nP46406\ta7bf3b3fac0aef2f2fd7dd6aa2ec7d33\t333\tPRINTS\tPR00078\tGlyceraldehyde-3-phosphate dehydrogenase signature\t110\t123\t1.5E-50\tT\t08-10-2020\tIPR020831\tGlyceraldehyde/Erythrose phosphate dehydrogenase family\tGO:0016620|GO:0055114\tKEGG: 00010+1.2.1.12|Reactome: R-HSA-70171\nP46406\ta7bf3b3fac0aef2f2fd7dd6aa2ec7d33\t333\tMobiDBLite\tmobidb-lite\tconsensus disorder prediction\t1\t4\t-\tT\t08-10-2020\t-\t-\t-\t-\n";
+
+// XML
+
+/// InterProScan XML output for GAPDH.
+#[cfg(feature = "xml")]
+pub const GAPDH_XML: &'static [u8] = b"<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<protein-matches xmlns=\"http://www.ebi.ac.uk/interpro/resources/schemas/interproscan5\" interproscan-version=\"5.48-83.0\">\n    <protein>\n        <sequence md5=\"a7bf3b3fac0aef2f2fd7dd6aa2ec7d33\">MVKVGVNGFGRIGRLVTRAAFNSGKVDVVAINDPFIDLHYMVYMFQYDSTHGKFHGTVKAENGKLVINGKAITIFQERDPANIKWGDAGAEYVVESTGVFTTMEKAGAHLKGGAKRVIISAPSADAPMFVMGVNHEKYDNSLKIVSNASCTTNCLAPLAKVIHDHFGIVEGLMTTVHAITATQKTVDGPSGKLWRDGRGAAQNIIPASTGAAKAVGKVIPELNGKLTGMAFRVPTPNVSVVDLTCRLEKAAKYDDIKKVVKQASEGPLKGILGYTEDQVVSCDFNSATHSSTFDAGAGIALNDHFVKLISWYDNEFGYSNRVVDLMVHMASKE</sequence>\n        <xref id=\"P46406\" name=\"G3P_RABIT\"/>\n        <matches>\n            <hmmer3-match evalue=\"6.9E-38\" score=\"129.4\">\n                <signature ac=\"PF00044\" desc=\"Glyceraldehyde 3-phosphate dehydrogenase, NAD binding domain\" name=\"Gp_dh_N\">\n                    <entry ac=\"IPR020828\" desc=\"Glyceraldehyde 3-phosphate dehydrogenase, NAD(P) binding domain\" name=\"GlycerAld_3-P_DH_NAD(P)-bd\" type=\"DOMAIN\">\n                        <go-xref category=\"MOLECULAR_FUNCTION\" db=\"GO\" id=\"GO:0016620\" name=\"oxidoreductase activity\"/>\n                        <go-xref category=\"MOLECULAR_FUNCTION\" db=\"GO\" id=\"GO:0050661\" name=\"NADP binding\"/>\n                        <go-xref category=\"BIOLOGICAL_PROCESS\" db=\"GO\" id=\"GO:0055114\" name=\"oxidation-reduction process\"/>\n                    </entry>\n                    <signature-library-release library=\"PFAM\" version=\"33.1\"/>\n                </signature>\n                <model-ac>PF00044</model-ac>\n                <locations>\n                    <hmmer3-location env-end=\"106\" env-start=\"3\" score=\"129.4\" evalue=\"6.9E-38\" hmm-start=\"1\" hmm-end=\"101\" start=\"3\" end=\"104\">\n                        <location-fragments>\n                            <hmmer3-location-fragment start=\"3\" end=\"104\" dc-status=\"CONTINUOUS\"/>\n                        </location-fragments>\n                    </hmmer3-location>\n                </locations>\n            </hmmer3-match>\n            <fingerprints-match evalue=\"1.5E-50\" graphscan=\"IIIIIIII\">\n                <signature ac=\"PR00078\" desc=\"Glyceraldehyde-3-phosphate dehydrogenase signature\" name=\"G3PDHDRGNASE\">\n                    <entry ac=\"IPR020831\" desc=\"Glyceraldehyde/Erythrose phosphate dehydrogenase family\" name=\"GlycerAld/Erythrose_P_DH\" type=\"FAMILY\">\n                        <go-xref category=\"MOLECULAR_FUNCTION\" db=\"GO\" id=\"GO:0016620\" name=\"oxidoreductase activity\"/>\n                        <go-xref category=\"BIOLOGICAL_PROCESS\" db=\"GO\" id=\"GO:0055114\" name=\"oxidation-reduction process\"/>\n                        <pathway-xref db=\"KEGG\" id=\"00010+1.2.1.12\" name=\"Glycolysis / Gluconeogenesis\"/>\n                        <pathway-xref db=\"Reactome\" id=\"R-HSA-70171\" name=\"Glycolysis\"/>\n                    </entry>\n                    <signature-library-release library=\"PRINTS\" version=\"42.0\"/>\n                </signature>\n                <model-ac>PR00078</model-ac>\n                <locations>\n                    <fingerprints-location motifNumber=\"1\" pvalue=\"1.1E-9\" score=\"61.2\" start=\"110\" end=\"123\"/>\n                </locations>\n            </fingerprints-match>\n            <mobidblite-match>\n                <signature ac=\"mobidb-lite\" desc=\"consensus disorder prediction\" name=\"disorder_prediction\">\n                    <signature-library-release library=\"MOBIDB_LITE\" version=\"2.0\"/>\n                </signature>\n                <model-ac>mobidb-lite</model-ac>\n                <locations>\n                    <mobidblite-location start=\"1\" end=\"4\"/>\n                </locations>\n            </mobidblite-match>\n        </matches>\n    </protein>\n</protein-matches>\n";
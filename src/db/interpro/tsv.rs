@@ -0,0 +1,222 @@
+//! Helper utilities for InterProScan TSV loading.
+//!
+//! The TSV output has one tab-delimited line per match location, with
+//! 11 mandatory columns, followed by the optional InterPro annotation,
+//! GO term and pathway columns. Missing values are written as "-".
+
+use std::io::prelude::*;
+use std::io::Lines;
+
+use util::*;
+use super::record::Record;
+
+/// Number of mandatory columns in each line.
+const REQUIRED_COLUMNS: usize = 11;
+
+// TSV ITERATOR
+
+/// Iterator to parse individual match lines from a TSV document.
+///
+/// Convert a stream to a lazy reader that fetches individual match
+/// lines from the document, skipping blank lines.
+pub struct TsvIter<T: BufRead> {
+    lines: Lines<T>,
+}
+
+impl<T: BufRead> TsvIter<T> {
+    /// Create new TsvIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        TsvIter {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for TsvIter<T> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Err(e)   => return Some(Err(From::from(e))),
+                Ok(line) => line,
+            };
+
+            let trimmed = strip_bom(line.trim_end());
+            if !trimmed.trim_start().is_empty() {
+                return Some(Ok(String::from(trimmed)));
+            }
+        }
+    }
+}
+
+// READER
+
+/// Convert a missing ("-") value to an empty string.
+#[inline]
+fn value_from_str(value: &str) -> &str {
+    match value {
+        "-" => "",
+        _   => value,
+    }
+}
+
+/// Parse a "|"-delimited list, such as GO terms or pathways.
+///
+/// Trailing sources for GO terms ("GO:0016620(InterPro)") are removed.
+fn list_from_str(value: &str) -> Vec<String> {
+    value_from_str(value)
+        .split('|')
+        .filter(|item| !item.is_empty())
+        .map(|item| String::from(item.split('(').next().unwrap_or(item)))
+        .collect()
+}
+
+/// Import record from a TSV match line.
+pub fn record_from_tsv(line: &str)
+    -> Result<Record>
+{
+    let columns: Vec<&str> = line.split('\t').collect();
+    bool_to_error!(columns.len() >= REQUIRED_COLUMNS, InvalidInput);
+    let optional = |index: usize| columns.get(index).map_or("", |&c| value_from_str(c));
+
+    let evalue = match value_from_str(columns[8]) {
+        ""    => None,
+        value => Some(from_string(value)?),
+    };
+
+    Ok(Record {
+        accession: String::from(columns[0]),
+        md5: String::from(columns[1]),
+        length: from_string(columns[2])?,
+        member_database: String::from(columns[3]),
+        signature_accession: String::from(columns[4]),
+        signature_description: String::from(value_from_str(columns[5])),
+        start: from_string(columns[6])?,
+        stop: from_string(columns[7])?,
+        evalue: evalue,
+        status: String::from(value_from_str(columns[9])),
+        date: String::from(value_from_str(columns[10])),
+        interpro_accession: String::from(optional(11)),
+        interpro_description: String::from(optional(12)),
+        go_terms: list_from_str(optional(13)),
+        pathways: list_from_str(optional(14)),
+    })
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `TsvIter` and converts the lines to records.
+pub struct TsvRecordIter<T: BufRead> {
+    iter: TsvIter<T>,
+}
+
+impl<T: BufRead> TsvRecordIter<T> {
+    /// Create new TsvRecordIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        TsvRecordIter {
+            iter: TsvIter::new(reader),
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for TsvRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.iter.next()? {
+            Err(e)   => Err(e),
+            Ok(line) => record_from_tsv(&line),
+        };
+        log_parsed!("InterProScan TSV", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_tsv<T: BufRead>(reader: T) -> TsvRecordIter<T> {
+    TsvRecordIter::new(reader)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `TsvIter` and converts the lines to records strictly.
+pub type TsvRecordStrictIter<T> = StrictIter<Record, TsvRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_tsv_strict<T: BufRead>(reader: T) -> TsvRecordStrictIter<T> {
+    TsvRecordStrictIter::new(iterator_from_tsv(reader))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `TsvIter` and converts the lines to records leniently.
+pub type TsvRecordLenientIter<T> = LenientIter<Record, TsvRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_tsv_lenient<T: BufRead>(reader: T) -> TsvRecordLenientIter<T> {
+    TsvRecordLenientIter::new(iterator_from_tsv(reader))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test::windows_text;
+    use super::*;
+    use super::super::record_list::RecordList;
+    use super::super::test::*;
+
+    #[test]
+    fn record_tsv_test() {
+        let lines: Vec<&str> = ::std::str::from_utf8(GAPDH_TSV).unwrap().lines().collect();
+        assert_eq!(record_from_tsv(lines[0]).unwrap(), gapdh_pfam());
+        assert_eq!(record_from_tsv(lines[1]).unwrap(), gapdh_prints());
+        assert_eq!(record_from_tsv(lines[2]).unwrap(), gapdh_mobidb());
+
+        // Without the optional columns.
+        let x = record_from_tsv(&lines[0].splitn(12, '\t').take(11).collect::<Vec<_>>().join("\t")).unwrap();
+        assert_eq!(x.interpro_accession, "");
+        assert!(x.go_terms.is_empty());
+
+        // GO terms with sources.
+        let x = record_from_tsv(&lines[1].replace("GO:0055114", "GO:0055114(InterPro)")).unwrap();
+        assert_eq!(x.go_terms, gapdh_prints().go_terms);
+
+        assert!(record_from_tsv(&lines[0].replace("\t333\t", "\tx\t")).is_err());
+        assert!(record_from_tsv("P46406\t-\t333").is_err());
+    }
+
+    #[test]
+    fn iterator_from_tsv_test() {
+        let iter = iterator_from_tsv(Cursor::new(GAPDH_TSV));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh_pfam(), gapdh_prints(), gapdh_mobidb()]);
+
+        // Windows line endings and trailing whitespace.
+        let text = windows_text(GAPDH_TSV);
+        let iter = iterator_from_tsv_strict(Cursor::new(text));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh_pfam(), gapdh_prints(), gapdh_mobidb()]);
+
+        // Stop after the sequence length.
+        let text = [GAPDH_TSV, b"P46406\t-\t333\tPfam\tPF00044\t-\t300\t400\t-\tT\t08-10-2020\n"].concat();
+        let iter = iterator_from_tsv_strict(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_tsv_lenient(Cursor::new(&text));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap().len(), 3);
+    }
+}
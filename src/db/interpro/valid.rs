@@ -0,0 +1,24 @@
+//! Valid trait implementation for InterProScan models.
+
+use traits::Valid;
+use super::record::Record;
+use super::record_list::RecordList;
+
+impl Valid for Record {
+    fn is_valid(&self) -> bool {
+        !self.accession.is_empty() &&
+            !self.member_database.is_empty() &&
+            !self.signature_accession.is_empty() &&
+            self.start > 0 &&
+            self.start <= self.stop &&
+            (self.length == 0 || self.stop <= self.length) &&
+            self.go_terms.iter().all(|t| t.starts_with("GO:"))
+    }
+}
+
+impl Valid for RecordList {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.iter().all(|ref x| x.is_valid())
+    }
+}
@@ -0,0 +1,313 @@
+//! Helper utilities for InterProScan XML loading.
+//!
+//! Each `<protein>` element contains the matches for a sequence, with
+//! match and location elements named by the member database analysis
+//! (for example, `<hmmer3-match>` and `<hmmer3-location>`). Each match
+//! location is converted to a record, as in the TSV output.
+
+use quick_xml::events::{BytesStart, Event};
+use std::collections::VecDeque;
+use std::io::prelude::*;
+
+use traits::Deserializable;
+use util::*;
+use super::record::Record;
+
+// HELPERS
+
+/// Get the unescaped values for the attribute keys, in order.
+fn attribute_values(event: &BytesStart, keys: &[&[u8]])
+    -> Result<Vec<Option<String>>>
+{
+    let mut values = vec![None; keys.len()];
+    for result in event.attributes() {
+        let attribute = match result {
+            Err(e) => return Err(From::from(ErrorKind::Xml(e))),
+            Ok(v)  => v,
+        };
+        if let Some(index) = keys.iter().position(|&k| k == attribute.key) {
+            let value = match attribute.unescaped_value() {
+                Err(e) => return Err(From::from(ErrorKind::Xml(e))),
+                Ok(v)  => v.to_vec(),
+            };
+            values[index] = Some(String::from_utf8(value)?);
+        }
+    }
+    Ok(values)
+}
+
+/// Parse an optional number from an attribute value.
+#[inline]
+fn parse_optional<Number: Deserializable>(value: Option<String>)
+    -> Result<Option<Number>>
+{
+    match value {
+        None        => Ok(None),
+        Some(value) => Ok(Some(from_string(&value)?)),
+    }
+}
+
+/// Parse a required number from an attribute value.
+#[inline]
+fn parse_required<Number: Deserializable>(value: Option<String>)
+    -> Result<Number>
+{
+    let value = none_to_error!(value, InvalidInput);
+    from_string(&value)
+}
+
+/// Update the record for a start element within a protein.
+///
+/// Returns the record for a match location, if the element is a location.
+fn parse_start(event: &BytesStart, protein: &mut Record, current: &mut Record)
+    -> Result<Option<Record>>
+{
+    let name = event.name();
+    if name == b"sequence" {
+        let mut values = attribute_values(event, &[b"md5"])?;
+        protein.md5 = values.remove(0).unwrap_or_default();
+    } else if name == b"xref" {
+        let mut values = attribute_values(event, &[b"id"])?;
+        if protein.accession.is_empty() {
+            protein.accession = values.remove(0).unwrap_or_default();
+        }
+    } else if name.ends_with(b"-match") {
+        let mut values = attribute_values(event, &[b"evalue"])?;
+        *current = Record::new();
+        current.accession = protein.accession.clone();
+        current.md5 = protein.md5.clone();
+        current.length = protein.length;
+        current.evalue = parse_optional(values.remove(0))?;
+    } else if name == b"signature" {
+        let mut values = attribute_values(event, &[b"ac", b"desc", b"name"])?;
+        let name = values.remove(2);
+        current.signature_accession = values.remove(0).unwrap_or_default();
+        current.signature_description = values.remove(0).or(name).unwrap_or_default();
+    } else if name == b"entry" {
+        let mut values = attribute_values(event, &[b"ac", b"desc"])?;
+        current.interpro_accession = values.remove(0).unwrap_or_default();
+        current.interpro_description = values.remove(0).unwrap_or_default();
+    } else if name == b"go-xref" {
+        let mut values = attribute_values(event, &[b"id"])?;
+        current.go_terms.push(none_to_error!(values.remove(0), InvalidInput));
+    } else if name == b"pathway-xref" {
+        let mut values = attribute_values(event, &[b"db", b"id"])?;
+        let db = none_to_error!(values.remove(0), InvalidInput);
+        let id = none_to_error!(values.remove(0), InvalidInput);
+        current.pathways.push(format!("{}: {}", db, id));
+    } else if name == b"signature-library-release" {
+        let mut values = attribute_values(event, &[b"library"])?;
+        current.member_database = values.remove(0).unwrap_or_default();
+    } else if name.ends_with(b"-location") {
+        let mut values = attribute_values(event, &[b"start", b"end", b"evalue"])?;
+        let mut record = current.clone();
+        record.start = parse_required(values.remove(0))?;
+        record.stop = parse_required(values.remove(0))?;
+        if let Some(evalue) = parse_optional(values.remove(0))? {
+            record.evalue = Some(evalue);
+        }
+        return Ok(Some(record));
+    }
+
+    Ok(None)
+}
+
+// READER
+
+/// Iterator to lazily load `Record`s from an XML document.
+///
+/// Parses each `<protein>` element in turn, yielding a record for each
+/// match location.
+pub struct XmlRecordIter<T: BufRead> {
+    reader: XmlReader<DecodeReader<T>>,
+    records: VecDeque<Record>,
+}
+
+impl<T: BufRead> XmlRecordIter<T> {
+    /// Create new XmlRecordIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        XmlRecordIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new XmlRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
+        XmlRecordIter {
+            reader: XmlReader::with_capacity(DecodeReader::new(reader, options.encoding), options.buffer_capacity),
+            records: VecDeque::new(),
+        }
+    }
+
+    /// Read the match locations for the next protein.
+    fn read_protein(&mut self) -> Option<Result<Vec<Record>>> {
+        let mut protein = Record::new();
+        let mut current = Record::new();
+        let mut records = vec![];
+        let mut in_protein = false;
+
+        loop {
+            let mut is_sequence = false;
+            let result = match self.reader.read_event() {
+                Err(e)              => Err(e),
+                Ok(Event::Eof)      => {
+                    if in_protein {
+                        return Some(Err(From::from(ErrorKind::UnexpectedEof)));
+                    }
+                    return None;
+                },
+                Ok(Event::Start(e)) => {
+                    if e.name() == b"protein" {
+                        in_protein = true;
+                        Ok(None)
+                    } else if in_protein {
+                        is_sequence = e.name() == b"sequence";
+                        parse_start(&e, &mut protein, &mut current)
+                    } else {
+                        Ok(None)
+                    }
+                },
+                Ok(Event::End(e))   => {
+                    if in_protein && e.name() == b"protein" {
+                        self.reader.reset_buffer();
+                        return Some(Ok(records));
+                    }
+                    Ok(None)
+                },
+                _                   => Ok(None),
+            };
+            self.reader.reset_buffer();
+
+            match result {
+                Err(e)           => return Some(Err(e)),
+                Ok(Some(record)) => records.push(record),
+                Ok(None)         => (),
+            }
+
+            if is_sequence {
+                match self.reader.read_text(b"sequence") {
+                    Err(e) => return Some(Err(e)),
+                    Ok(v)  => {
+                        let length = v.iter().filter(|c| !c.is_ascii_whitespace()).count();
+                        protein.length = length as u32;
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for XmlRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.records.pop_front() {
+                log_trace!("parsed InterProScan XML record");
+                return Some(Ok(record));
+            }
+
+            match self.read_protein()? {
+                Err(e)      => return Some(Err(e)),
+                Ok(records) => self.records.extend(records),
+            }
+        }
+    }
+}
+
+// READER -- DEFAULT
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_xml<T: BufRead>(reader: T) -> XmlRecordIter<T> {
+    XmlRecordIter::new(reader)
+}
+
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_xml_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> XmlRecordIter<T> {
+    XmlRecordIter::with_options(reader, options)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document.
+pub type XmlRecordStrictIter<T> = StrictIter<Record, XmlRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_xml_strict<T: BufRead>(reader: T) -> XmlRecordStrictIter<T> {
+    XmlRecordStrictIter::new(iterator_from_xml(reader))
+}
+
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_xml_strict_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> XmlRecordStrictIter<T> {
+    XmlRecordStrictIter::new(iterator_from_xml_with_options(reader, options))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document.
+pub type XmlRecordLenientIter<T> = LenientIter<Record, XmlRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_xml_lenient<T: BufRead>(reader: T) -> XmlRecordLenientIter<T> {
+    XmlRecordLenientIter::new(iterator_from_xml(reader))
+}
+
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_xml_lenient_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> XmlRecordLenientIter<T> {
+    XmlRecordLenientIter::new(iterator_from_xml_with_options(reader, options))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use super::super::record_list::RecordList;
+    use super::super::test::*;
+
+    /// Convert a TSV record to the values reported in the XML output.
+    fn from_tsv(mut record: Record, member_database: &str) -> Record {
+        record.member_database = String::from(member_database);
+        record.status = String::new();
+        record.date = String::new();
+        record
+    }
+
+    #[test]
+    fn iterator_from_xml_test() {
+        let iter = iterator_from_xml(Cursor::new(GAPDH_XML));
+        let v: RecordList = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v, vec![
+            from_tsv(gapdh_pfam(), "PFAM"),
+            from_tsv(gapdh_prints(), "PRINTS"),
+            from_tsv(gapdh_mobidb(), "MOBIDB_LITE"),
+        ]);
+
+        // Multiple proteins.
+        let text = String::from_utf8(GAPDH_XML.to_vec()).unwrap();
+        let protein = &text[text.find("<protein>").unwrap()..text.find("</protein-matches>").unwrap()];
+        let text = text.replace("</protein-matches>", &format!("{}</protein-matches>", protein));
+        let iter = iterator_from_xml_strict(Cursor::new(text.as_bytes()));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap().len(), 6);
+
+        // Location past the end of the sequence.
+        let text = String::from_utf8(GAPDH_XML.to_vec()).unwrap();
+        let text = text.replace("start=\"1\" end=\"4\"", "start=\"1\" end=\"400\"");
+        let iter = iterator_from_xml_strict(Cursor::new(text.as_bytes()));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_xml_lenient(Cursor::new(text.as_bytes()));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap().len(), 2);
+
+        // Truncated document.
+        let iter = iterator_from_xml(Cursor::new(&GAPDH_XML[..GAPDH_XML.len() / 2]));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! Spectrum vectorization by binning peak intensities.
+//!
+//! Converts peak lists to fixed-size intensity vectors, for example,
+//! to use spectra as features for machine learning models.
+
+use std::io::prelude::*;
+
+use util::*;
+use super::peak::Peak;
+use super::record::Record;
+
+/// Normalization applied to a binned intensity vector.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Normalization {
+    /// Keep the summed raw intensities.
+    None,
+    /// Scale so the most intense bin is 1.
+    Max,
+    /// Scale so the bins sum to 1.
+    Sum,
+    /// Scale to a unit vector (Euclidean norm of 1).
+    L2,
+}
+
+/// Options to convert a peak list to a binned intensity vector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinOptions {
+    /// Lower m/z bound (inclusive) of the first bin.
+    pub min_mz: f64,
+    /// Upper m/z bound (exclusive) of the last bin.
+    pub max_mz: f64,
+    /// Width of each bin, in m/z.
+    pub bin_width: f64,
+    /// Normalization applied after binning.
+    pub normalization: Normalization,
+}
+
+impl BinOptions {
+    /// Create options from an m/z range and bin width, normalizing to the max bin.
+    #[inline]
+    pub fn new(min_mz: f64, max_mz: f64, bin_width: f64) -> Self {
+        BinOptions {
+            min_mz: min_mz,
+            max_mz: max_mz,
+            bin_width: bin_width,
+            normalization: Normalization::Max,
+        }
+    }
+
+    /// Set the normalization, consuming and returning the options.
+    #[inline]
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Check the m/z range and bin width are valid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.bin_width > 0.0 && self.max_mz > self.min_mz
+    }
+
+    /// Get the number of bins, or 0 if the options are invalid.
+    #[inline]
+    pub fn bin_count(&self) -> usize {
+        if self.is_valid() {
+            ((self.max_mz - self.min_mz) / self.bin_width).ceil() as usize
+        } else {
+            0
+        }
+    }
+
+    /// Get the bin index for an m/z value, if it is within the range.
+    #[inline]
+    pub fn bin_index(&self, mz: f64) -> Option<usize> {
+        if !self.is_valid() || !(mz >= self.min_mz && mz < self.max_mz) {
+            return None;
+        }
+        let index = ((mz - self.min_mz) / self.bin_width) as usize;
+        Some(index.min(self.bin_count() - 1))
+    }
+}
+
+impl Default for BinOptions {
+    /// Nominal-mass bins from 100 to 2000 m/z, normalized to the max bin.
+    #[inline]
+    fn default() -> Self {
+        BinOptions::new(100.0, 2000.0, 1.0005079)
+    }
+}
+
+// BINNING
+
+/// Scale bins in-place using the normalization.
+fn normalize(bins: &mut [f64], normalization: Normalization) {
+    let scale = match normalization {
+        Normalization::None => return,
+        Normalization::Max  => bins.iter().fold(0.0f64, |m, x| m.max(*x)),
+        Normalization::Sum  => bins.iter().sum(),
+        Normalization::L2   => bins.iter().map(|x| x * x).sum::<f64>().sqrt(),
+    };
+    if scale > 0.0 {
+        for bin in bins.iter_mut() {
+            *bin /= scale;
+        }
+    }
+}
+
+/// Convert a peak list to a binned intensity vector.
+///
+/// Intensities of peaks within the same bin are summed, and peaks
+/// outside of the m/z range are ignored. The peak list does not need
+/// to be sorted.
+pub fn bin_peaks(peaks: &[Peak], options: &BinOptions) -> Vec<f64> {
+    let mut bins = vec![0.0; options.bin_count()];
+    for peak in peaks {
+        if let Some(index) = options.bin_index(peak.mz) {
+            bins[index] += peak.intensity;
+        }
+    }
+    normalize(&mut bins, options.normalization);
+
+    bins
+}
+
+// WRITER
+
+/// Export binned spectra to CSV, with one row per scan.
+///
+/// The first column is the scan number, and each subsequent column
+/// is a bin, with a header containing the lower m/z bound of each bin.
+#[cfg(feature = "csv")]
+pub fn reference_iterator_to_binned_csv<'a, Iter, T>(writer: &mut T, iter: Iter, options: &BinOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    bool_to_error!(options.is_valid(), InvalidInput);
+
+    // Write the header.
+    writer.write_all(b"num")?;
+    for index in 0..options.bin_count() {
+        let mz = to_bytes(&(options.min_mz + index as f64 * options.bin_width))?;
+        write_alls!(writer, b",", mz.as_slice())?;
+    }
+    writer.write_all(b"\n")?;
+
+    // Write the binned spectra.
+    for record in iter {
+        let num = to_bytes(&record.num)?;
+        writer.write_all(num.as_slice())?;
+        for bin in bin_peaks(&record.peaks, options) {
+            let bin = to_bytes(&bin)?;
+            write_alls!(writer, b",", bin.as_slice())?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// Write a NumPy (`.npy`, version 1.0) header for a 2D `f64` array.
+fn write_npy_header<T: Write>(writer: &mut T, rows: usize, columns: usize)
+    -> Result<()>
+{
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}", rows, columns);
+    // Pad the header so the data is 64-byte aligned, including the
+    // 10-byte preamble and the trailing newline.
+    let padding = 63 - (10 + header.len()) % 64;
+    header.extend((0..padding).map(|_| ' '));
+    header.push('\n');
+
+    let length = header.len() as u16;
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&[(length & 0xFF) as u8, (length >> 8) as u8])?;
+    writer.write_all(header.as_bytes())?;
+
+    Ok(())
+}
+
+/// Export binned spectra to a NumPy (`.npy`) 2D array of little-endian `f64`.
+///
+/// The array has one row per scan, and one column per bin, and may
+/// be loaded directly with `numpy.load` or the `ndarray-npy` crate.
+/// Since the header requires the number of rows, all binned spectra
+/// are kept in memory before writing.
+pub fn reference_iterator_to_binned_npy<'a, Iter, T>(writer: &mut T, iter: Iter, options: &BinOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    bool_to_error!(options.is_valid(), InvalidInput);
+
+    let rows: Vec<Vec<f64>> = iter.map(|r| bin_peaks(&r.peaks, options)).collect();
+    write_npy_header(writer, rows.len(), options.bin_count())?;
+    for row in rows {
+        for bin in row {
+            let bits = bin.to_bits();
+            let mut bytes = [0u8; 8];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = (bits >> (8 * i)) as u8;
+            }
+            writer.write_all(&bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    fn peaks() -> Vec<Peak> {
+        vec![
+            Peak { mz: 100.2, intensity: 1.0, z: 0, mobility: None },
+            Peak { mz: 100.7, intensity: 3.0, z: 0, mobility: None },
+            Peak { mz: 102.5, intensity: 4.0, z: 0, mobility: None },
+            Peak { mz: 99.9, intensity: 9.0, z: 0, mobility: None },
+            Peak { mz: 104.0, intensity: 9.0, z: 0, mobility: None },
+        ]
+    }
+
+    #[test]
+    fn bin_options_test() {
+        let options = BinOptions::new(100.0, 104.0, 1.0);
+        assert!(options.is_valid());
+        assert_eq!(options.bin_count(), 4);
+        assert_eq!(options.bin_index(100.0), Some(0));
+        assert_eq!(options.bin_index(103.99), Some(3));
+        assert_eq!(options.bin_index(104.0), None);
+        assert_eq!(options.bin_index(99.0), None);
+
+        let options = BinOptions::new(100.0, 100.0, 1.0);
+        assert!(!options.is_valid());
+        assert_eq!(options.bin_count(), 0);
+        assert_eq!(options.bin_index(100.0), None);
+    }
+
+    #[test]
+    fn bin_peaks_test() {
+        let options = BinOptions::new(100.0, 104.0, 1.0);
+        let p = peaks();
+        let n = |x| options.clone().with_normalization(x);
+        assert_eq!(bin_peaks(&p, &n(Normalization::None)), vec![4.0, 0.0, 4.0, 0.0]);
+        assert_eq!(bin_peaks(&p, &n(Normalization::Max)), vec![1.0, 0.0, 1.0, 0.0]);
+        assert_eq!(bin_peaks(&p, &n(Normalization::Sum)), vec![0.5, 0.0, 0.5, 0.0]);
+
+        let l2 = bin_peaks(&p, &n(Normalization::L2));
+        assert_approx_eq!(l2[0], 0.5f64.sqrt());
+        assert_approx_eq!(l2[2], 0.5f64.sqrt());
+
+        // empty spectra are not normalized
+        assert_eq!(bin_peaks(&[], &options), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn bin_record_test() {
+        let r = mgf_33450();
+        let bins = bin_peaks(&r.peaks, &BinOptions::default());
+        assert_eq!(bins.len(), 1900);
+        assert_eq!(bins.iter().cloned().fold(0.0, f64::max), 1.0);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn binned_csv_test() {
+        let mut r = mgf_empty();
+        r.peaks = peaks();
+        let options = BinOptions::new(100.0, 102.0, 1.0);
+        let mut w = vec![];
+        reference_iterator_to_binned_csv(&mut w, [r.clone(), r].iter(), &options).unwrap();
+        assert_eq!(w, b"num,100.0,101.0\n33450,1.0,0.0\n33450,1.0,0.0\n".to_vec());
+
+        let options = BinOptions::new(100.0, 100.0, 1.0);
+        let mut w = vec![];
+        assert!(reference_iterator_to_binned_csv(&mut w, [].iter(), &options).is_err());
+    }
+
+    #[test]
+    fn binned_npy_test() {
+        let mut r = mgf_empty();
+        r.peaks = peaks();
+        let options = BinOptions::new(100.0, 102.0, 1.0);
+        let mut w = vec![];
+        reference_iterator_to_binned_npy(&mut w, [r.clone(), r].iter(), &options).unwrap();
+
+        // header is padded to a multiple of 64 bytes, followed by 2x2 f64 values.
+        assert_eq!(w.len(), 128 + 32);
+        assert_eq!(&w[..8], b"\x93NUMPY\x01\x00");
+        assert_eq!(w[8] as usize + ((w[9] as usize) << 8), 118);
+        assert!(w[10..128].starts_with(b"{'descr': '<f8', 'fortran_order': False, 'shape': (2, 2), }"));
+        assert_eq!(w[127], b'\n');
+        assert_eq!(&w[128..136], &[0, 0, 0, 0, 0, 0, 0xF0, 0x3F]);
+        assert_eq!(&w[136..144], &[0; 8]);
+    }
+}
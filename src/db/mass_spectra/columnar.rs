@@ -0,0 +1,151 @@
+//! Helper utilities for mass spectra columnar (Arrow and Parquet) export.
+//!
+//! Exports one row per scan, with the peaks stored as list columns of
+//! the m/z, intensity and charge values. The unrecognized header
+//! lines are not exported.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Builder, Int8Builder, ListBuilder, StringBuilder, UInt32Builder, UInt8Builder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use traits::Columnar;
+use util::Result;
+use super::record::Record;
+
+/// Create a nullable list field, matching the fields of `ListBuilder`.
+#[inline]
+fn list_field(name: &str, item: DataType) -> Field {
+    Field::new(name, DataType::List(Arc::new(Field::new("item", item, true))), false)
+}
+
+impl Columnar for Record {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("num", DataType::UInt32, false),
+            Field::new("ms_level", DataType::UInt8, false),
+            Field::new("rt", DataType::Float64, false),
+            Field::new("parent_mz", DataType::Float64, false),
+            Field::new("parent_intensity", DataType::Float64, false),
+            Field::new("parent_z", DataType::Int8, false),
+            Field::new("file", DataType::Utf8, false),
+            Field::new("filter", DataType::Utf8, false),
+            Field::new("injection_time", DataType::Float64, true),
+            Field::new("tic", DataType::Float64, true),
+            Field::new("ion_mobility", DataType::Float64, true),
+            Field::new("ccs", DataType::Float64, true),
+            list_field("mz", DataType::Float64),
+            list_field("intensity", DataType::Float64),
+            list_field("z", DataType::Int8),
+            list_field("parent", DataType::UInt32),
+            list_field("children", DataType::UInt32),
+        ]))
+    }
+
+    fn to_record_batch(models: &[&Self]) -> Result<RecordBatch> {
+        let mut num = UInt32Builder::with_capacity(models.len());
+        let mut ms_level = UInt8Builder::with_capacity(models.len());
+        let mut rt = Float64Builder::with_capacity(models.len());
+        let mut parent_mz = Float64Builder::with_capacity(models.len());
+        let mut parent_intensity = Float64Builder::with_capacity(models.len());
+        let mut parent_z = Int8Builder::with_capacity(models.len());
+        let mut file = StringBuilder::new();
+        let mut filter = StringBuilder::new();
+        let mut injection_time = Float64Builder::with_capacity(models.len());
+        let mut tic = Float64Builder::with_capacity(models.len());
+        let mut ion_mobility = Float64Builder::with_capacity(models.len());
+        let mut ccs = Float64Builder::with_capacity(models.len());
+        let mut mz = ListBuilder::new(Float64Builder::new());
+        let mut intensity = ListBuilder::new(Float64Builder::new());
+        let mut z = ListBuilder::new(Int8Builder::new());
+        let mut parent = ListBuilder::new(UInt32Builder::new());
+        let mut children = ListBuilder::new(UInt32Builder::new());
+
+        for record in models.iter() {
+            num.append_value(record.num);
+            ms_level.append_value(record.ms_level);
+            rt.append_value(record.rt);
+            parent_mz.append_value(record.parent_mz);
+            parent_intensity.append_value(record.parent_intensity);
+            parent_z.append_value(record.parent_z);
+            file.append_value(&record.file);
+            filter.append_value(&record.filter);
+            injection_time.append_option(record.injection_time);
+            tic.append_option(record.tic);
+            ion_mobility.append_option(record.ion_mobility);
+            ccs.append_option(record.ccs);
+            for peak in record.peaks.iter() {
+                mz.values().append_value(peak.mz);
+                intensity.values().append_value(peak.intensity);
+                z.values().append_value(peak.z);
+            }
+            mz.append(true);
+            intensity.append(true);
+            z.append(true);
+            parent.values().append_slice(&record.parent);
+            parent.append(true);
+            children.values().append_slice(&record.children);
+            children.append(true);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(num.finish()),
+            Arc::new(ms_level.finish()),
+            Arc::new(rt.finish()),
+            Arc::new(parent_mz.finish()),
+            Arc::new(parent_intensity.finish()),
+            Arc::new(parent_z.finish()),
+            Arc::new(file.finish()),
+            Arc::new(filter.finish()),
+            Arc::new(injection_time.finish()),
+            Arc::new(tic.finish()),
+            Arc::new(ion_mobility.finish()),
+            Arc::new(ccs.finish()),
+            Arc::new(mz.finish()),
+            Arc::new(intensity.finish()),
+            Arc::new(z.finish()),
+            Arc::new(parent.finish()),
+            Arc::new(children.finish()),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use arrow::array::{Array, Float64Array, ListArray};
+    use arrow::ipc::reader::FileReader;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn record_batch_test() {
+        let mut x = mgf_33450();
+        x.tic = Some(1000.0);
+        let y = mgf_empty();
+        let batch = Record::to_record_batch(&[&x, &y]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+
+        let tic = batch.column(9).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(tic.value(0), 1000.0);
+        assert!(tic.is_null(1));
+
+        let mz = batch.column(12).as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(mz.value_length(0) as usize, x.peaks.len());
+        assert_eq!(mz.value_length(1), 0);
+        let first = mz.value(0);
+        let first = first.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(first.value(0), x.peaks[0].mz);
+
+        let mut w = Cursor::new(vec![]);
+        Record::to_arrow_iter(&mut w, vec![x, y].iter()).unwrap();
+        let reader = FileReader::try_new(Cursor::new(w.into_inner()), None).unwrap();
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 2);
+    }
+}
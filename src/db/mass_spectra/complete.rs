@@ -8,11 +8,9 @@ use super::record_list::RecordList;
 impl Complete for Record {
     #[inline]
     fn is_complete(&self) -> bool {
-        (
-            self.is_valid() &&
+        self.is_valid() &&
             self.ms_level != 0 &&
             !self.filter.is_empty()
-        )
     }
 }
 
@@ -0,0 +1,188 @@
+//! Model for controlled-vocabulary (CV) terms and term tables.
+
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Values;
+
+/// Model for a single controlled-vocabulary term.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CvTerm {
+    /// Term accession (for example, "MS:1000511").
+    pub id: String,
+    /// Human-readable term name.
+    pub name: String,
+    /// Term definition, with OBO escapes removed.
+    pub def: String,
+    /// Accessions of the direct parent terms.
+    pub is_a: Vec<String>,
+    /// Term is obsolete and should not be used in new documents.
+    pub is_obsolete: bool,
+}
+
+impl CvTerm {
+    /// Create new, empty CV term.
+    #[inline]
+    pub fn new() -> Self {
+        CvTerm {
+            id: String::new(),
+            name: String::new(),
+            def: String::new(),
+            is_a: vec![],
+            is_obsolete: false,
+        }
+    }
+}
+
+/// Table of controlled-vocabulary terms, indexed by accession.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlledVocabulary {
+    terms: HashMap<String, CvTerm>,
+}
+
+impl ControlledVocabulary {
+    /// Create new, empty vocabulary.
+    #[inline]
+    pub fn new() -> Self {
+        ControlledVocabulary {
+            terms: HashMap::new(),
+        }
+    }
+
+    /// Add term to vocabulary, returning the term it replaced, if any.
+    #[inline]
+    pub fn insert(&mut self, term: CvTerm) -> Option<CvTerm> {
+        self.terms.insert(term.id.clone(), term)
+    }
+
+    /// Get number of terms in the vocabulary.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Check if the vocabulary has no terms.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Iterate over all terms, in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Values<'_, String, CvTerm> {
+        self.terms.values()
+    }
+
+    /// Get term from accession.
+    #[inline]
+    pub fn get(&self, id: &str) -> Option<&CvTerm> {
+        self.terms.get(id)
+    }
+
+    /// Check if the vocabulary defines an accession.
+    #[inline]
+    pub fn contains(&self, id: &str) -> bool {
+        self.terms.contains_key(id)
+    }
+
+    /// Get term name from accession.
+    #[inline]
+    pub fn name(&self, id: &str) -> Option<&str> {
+        self.get(id).map(|t| t.name.as_str())
+    }
+
+    /// Check if a cvParam accession and name pair are defined and consistent.
+    ///
+    /// Obsolete terms are not considered valid.
+    pub fn is_valid_param(&self, id: &str, name: &str) -> bool {
+        match self.get(id) {
+            Some(t) => !t.is_obsolete && t.name == name,
+            None    => false,
+        }
+    }
+
+    /// Check if a term is, or directly or transitively derives from, `ancestor`.
+    pub fn is_a(&self, id: &str, ancestor: &str) -> bool {
+        // Track visited terms to guard against malformed, cyclic vocabularies.
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut stack: Vec<&str> = vec![id];
+        while let Some(current) = stack.pop() {
+            if current == ancestor {
+                return true;
+            } else if !visited.insert(current) {
+                continue;
+            }
+            if let Some(term) = self.get(current) {
+                stack.extend(term.is_a.iter().map(|x| x.as_str()));
+            }
+        }
+        false
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(id: &str, name: &str, is_a: &[&str]) -> CvTerm {
+        CvTerm {
+            id: String::from(id),
+            name: String::from(name),
+            def: String::new(),
+            is_a: is_a.iter().map(|x| String::from(*x)).collect(),
+            is_obsolete: false,
+        }
+    }
+
+    fn vocabulary() -> ControlledVocabulary {
+        let mut cv = ControlledVocabulary::new();
+        cv.insert(term("MS:1000499", "spectrum attribute", &[]));
+        cv.insert(term("MS:1000511", "ms level", &["MS:1000499"]));
+        cv.insert(term("MS:1000285", "total ion current", &["MS:1000499"]));
+        cv
+    }
+
+    #[test]
+    fn debug_term_test() {
+        let t = term("MS:1000511", "ms level", &["MS:1000499"]);
+        let text = format!("{:?}", t);
+        assert_eq!(text, "CvTerm { id: \"MS:1000511\", name: \"ms level\", def: \"\", is_a: [\"MS:1000499\"], is_obsolete: false }");
+    }
+
+    #[test]
+    fn lookup_test() {
+        let cv = vocabulary();
+        assert_eq!(cv.len(), 3);
+        assert!(cv.contains("MS:1000511"));
+        assert!(!cv.contains("MS:0000000"));
+        assert_eq!(cv.name("MS:1000285"), Some("total ion current"));
+        assert_eq!(cv.name("MS:0000000"), None);
+    }
+
+    #[test]
+    fn is_valid_param_test() {
+        let mut cv = vocabulary();
+        assert!(cv.is_valid_param("MS:1000511", "ms level"));
+        assert!(!cv.is_valid_param("MS:1000511", "total ion current"));
+        assert!(!cv.is_valid_param("MS:0000000", "ms level"));
+
+        let mut obsolete = term("MS:1000001", "sample number", &[]);
+        obsolete.is_obsolete = true;
+        cv.insert(obsolete);
+        assert!(!cv.is_valid_param("MS:1000001", "sample number"));
+    }
+
+    #[test]
+    fn is_a_test() {
+        let mut cv = vocabulary();
+        assert!(cv.is_a("MS:1000511", "MS:1000511"));
+        assert!(cv.is_a("MS:1000511", "MS:1000499"));
+        assert!(!cv.is_a("MS:1000499", "MS:1000511"));
+
+        // Cyclic vocabularies must terminate.
+        cv.insert(term("X:1", "x", &["X:2"]));
+        cv.insert(term("X:2", "y", &["X:1"]));
+        assert!(!cv.is_a("X:1", "MS:1000499"));
+    }
+}
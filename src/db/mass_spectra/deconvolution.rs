@@ -0,0 +1,276 @@
+//! Charge-state deconvolution of spectra to neutral mass lists.
+//!
+//! Groups the peaks of each isotope envelope, and the envelopes of each
+//! charge state, into a single neutral mass with the summed intensity,
+//! for example, to interpret intact protein spectra.
+
+#[cfg(feature = "csv")]
+use std::io::prelude::*;
+
+use bio::mass::{mz_to_neutral_mass, Adduct, Tolerance};
+use util::search::partition_point;
+#[cfg(feature = "csv")]
+use util::*;
+use super::peak::Peak;
+use super::peak_list::PeakListExt;
+use super::precursor::ISOTOPE_SPACING;
+
+/// Options to deconvolve a spectrum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeconvolutionOptions {
+    /// Tolerance of isotope peak m/z values.
+    pub tol: Tolerance,
+    /// Minimum charge to consider.
+    pub min_z: i8,
+    /// Maximum charge to consider.
+    pub max_z: i8,
+    /// Minimum number of isotope peaks in an envelope.
+    pub min_peaks: usize,
+    /// Tolerance to merge neutral masses from different charge states.
+    pub mass_tol: Tolerance,
+}
+
+impl Default for DeconvolutionOptions {
+    #[inline]
+    fn default() -> Self {
+        DeconvolutionOptions {
+            tol: Tolerance::Ppm(10.0),
+            min_z: 1,
+            max_z: 50,
+            min_peaks: 3,
+            mass_tol: Tolerance::Ppm(20.0),
+        }
+    }
+}
+
+/// Deconvolved neutral mass, summed over the charge states.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeutralMass {
+    /// Neutral mass of the lightest observed isotope, in Daltons.
+    pub mass: f64,
+    /// Summed intensity of the isotope peaks over all charge states.
+    pub intensity: f64,
+    /// Observed charge states, in increasing order.
+    pub charges: Vec<i8>,
+}
+
+/// Neutral mass collection type, sorted by increasing mass.
+pub type NeutralMassList = Vec<NeutralMass>;
+
+/// Find the nearest unassigned peak to the target m/z, within the tolerance.
+fn find_unused(peaks: &[Peak], used: &[bool], target: f64, tol: Tolerance) -> Option<usize> {
+    let (lo, hi) = tol.range(target);
+    let first = partition_point(peaks, |p| p.mz < lo);
+    let last = partition_point(peaks, |p| p.mz <= hi);
+    (first..last.max(first))
+        .filter(|&i| !used[i])
+        .min_by(|&x, &y| {
+            let dx = (peaks[x].mz - target).abs();
+            let dy = (peaks[y].mz - target).abs();
+            dx.total_cmp(&dy)
+        })
+}
+
+/// Find the indexes of the isotope envelope containing a peak, at a charge.
+///
+/// The indexes are sorted by increasing m/z.
+fn find_envelope(peaks: &[Peak], used: &[bool], seed: usize, z: i8, tol: Tolerance) -> Vec<usize> {
+    // Offset each isotope from the seed peak, so errors do not accumulate.
+    let spacing = ISOTOPE_SPACING / (z as f64).abs();
+    let mz = peaks[seed].mz;
+    let mut lighter = vec![];
+    while let Some(i) = find_unused(peaks, used, mz - (lighter.len() + 1) as f64 * spacing, tol) {
+        lighter.push(i);
+    }
+
+    let mut envelope: Vec<usize> = lighter.into_iter().rev().collect();
+    envelope.push(seed);
+    let mut heavier = 1;
+    while let Some(i) = find_unused(peaks, used, mz + heavier as f64 * spacing, tol) {
+        envelope.push(i);
+        heavier += 1;
+    }
+    envelope
+}
+
+/// Merge neutral masses from different charge states within the tolerance.
+fn merge_masses(mut masses: NeutralMassList, tol: Tolerance) -> NeutralMassList {
+    masses.sort_by(|x, y| x.mass.total_cmp(&y.mass));
+    let mut merged: NeutralMassList = vec![];
+    for mass in masses {
+        let is_match = match merged.last() {
+            Some(last) => tol.matches(last.mass, mass.mass),
+            None       => false,
+        };
+        if is_match {
+            let last = merged.last_mut().unwrap();
+            let intensity = last.intensity + mass.intensity;
+            if intensity > 0.0 {
+                last.mass = (last.mass * last.intensity + mass.mass * mass.intensity) / intensity;
+            }
+            last.intensity = intensity;
+            last.charges.extend(mass.charges);
+            last.charges.sort();
+            last.charges.dedup();
+        } else {
+            merged.push(mass);
+        }
+    }
+    merged
+}
+
+/// Deconvolve the peaks of a spectrum to neutral masses.
+///
+/// Peaks are assigned greedily, from the most intense peak, to the
+/// longest isotope envelope within the charge range, preferring the
+/// higher charge on ties. Peaks with a known charge are only assigned
+/// to that charge. The neutral masses of each charge state are then
+/// merged within the mass tolerance. Peaks that do not form an
+/// envelope of at least `min_peaks` peaks are discarded.
+pub fn deconvolve(peaks: &[Peak], options: &DeconvolutionOptions) -> NeutralMassList {
+    let mut peaks = peaks.to_vec();
+    if !peaks.is_sorted_by_mz() {
+        peaks.sort_by_mz();
+    }
+
+    let mut order: Vec<usize> = (0..peaks.len()).collect();
+    order.sort_by(|&x, &y| peaks[y].intensity.total_cmp(&peaks[x].intensity));
+
+    let mut used = vec![false; peaks.len()];
+    let mut masses = vec![];
+    for seed in order {
+        if used[seed] {
+            continue;
+        }
+        let (min_z, max_z) = match peaks[seed].z {
+            0 => (options.min_z, options.max_z),
+            z => (z, z),
+        };
+
+        let mut best: Option<(i8, Vec<usize>)> = None;
+        for z in min_z..max_z.saturating_add(1) {
+            if z == 0 {
+                continue;
+            }
+            let envelope = find_envelope(&peaks, &used, seed, z, options.tol);
+            let is_better = match best {
+                None                => true,
+                Some((_, ref other)) => envelope.len() >= other.len(),
+            };
+            if is_better {
+                best = Some((z, envelope));
+            }
+        }
+
+        used[seed] = true;
+        let (z, envelope) = match best {
+            Some((z, ref envelope)) if envelope.len() >= options.min_peaks => (z, envelope.clone()),
+            _                                                          => continue,
+        };
+        for &i in envelope.iter() {
+            used[i] = true;
+        }
+        if let Some(mass) = mz_to_neutral_mass(peaks[envelope[0]].mz, z, Adduct::Proton) {
+            masses.push(NeutralMass {
+                mass: mass,
+                intensity: envelope.iter().map(|&i| peaks[i].intensity).sum(),
+                charges: vec![z],
+            });
+        }
+    }
+
+    merge_masses(masses, options.mass_tol)
+}
+
+// WRITER
+
+/// Export a neutral mass list to CSV, with one row per neutral mass.
+///
+/// The observed charge states are separated by semicolons.
+#[cfg(feature = "csv")]
+pub fn neutral_masses_to_csv<T: Write>(writer: &mut T, list: &[NeutralMass])
+    -> Result<()>
+{
+    writer.write_all(b"mass,intensity,charges\n")?;
+    for neutral in list.iter() {
+        let mass = to_bytes(&neutral.mass)?;
+        let intensity = to_bytes(&neutral.intensity)?;
+        write_alls!(writer, mass.as_slice(), b",", intensity.as_slice(), b",")?;
+        for (i, z) in neutral.charges.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b";")?;
+            }
+            writer.write_all(to_bytes(z)?.as_slice())?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::mass::neutral_mass_to_mz;
+    use super::*;
+
+    /// Create the isotope envelope of a neutral mass at a charge.
+    fn envelope(mass: f64, z: i8, intensities: &[f64]) -> Vec<Peak> {
+        let mz = neutral_mass_to_mz(mass, z, Adduct::Proton).unwrap();
+        intensities.iter().enumerate().map(|(i, &intensity)| Peak {
+            mz: mz + i as f64 * ISOTOPE_SPACING / z as f64,
+            intensity: intensity,
+            z: 0,
+            mobility: None,
+        }).collect()
+    }
+
+    #[test]
+    fn deconvolve_test() {
+        let mut peaks = envelope(10000.0, 10, &[50.0, 100.0, 80.0, 40.0]);
+        peaks.extend(envelope(10000.0, 11, &[30.0, 60.0, 50.0, 20.0]));
+        peaks.extend(envelope(8500.0, 8, &[10.0, 20.0, 10.0]));
+        peaks.push(Peak { mz: 1500.0, intensity: 500.0, z: 0, mobility: None });
+
+        let masses = deconvolve(&peaks, &DeconvolutionOptions::default());
+        assert_eq!(masses.len(), 2);
+        assert_approx_eq!(masses[0].mass, 8500.0, 1e-6);
+        assert_eq!(masses[0].intensity, 40.0);
+        assert_eq!(masses[0].charges, vec![8]);
+        assert_approx_eq!(masses[1].mass, 10000.0, 1e-6);
+        assert_eq!(masses[1].intensity, 430.0);
+        assert_eq!(masses[1].charges, vec![10, 11]);
+
+        // peaks with a known charge are only assigned to that charge
+        let mut peaks = envelope(10000.0, 10, &[50.0, 100.0, 80.0]);
+        peaks[1].z = 5;
+        let masses = deconvolve(&peaks, &DeconvolutionOptions::default());
+        assert!(masses.is_empty());
+
+        // minimum envelope length
+        let mut options = DeconvolutionOptions::default();
+        options.min_peaks = 5;
+        assert!(deconvolve(&envelope(10000.0, 10, &[50.0, 100.0, 80.0]), &options).is_empty());
+
+        // NaN intensities do not change the order of the other peaks
+        let mut peaks = envelope(8500.0, 8, &[10.0, 20.0, 10.0]);
+        peaks.push(Peak { mz: 1500.0, intensity: ::std::f64::NAN, z: 0, mobility: None });
+        let masses = deconvolve(&peaks, &DeconvolutionOptions::default());
+        assert_eq!(masses.len(), 1);
+        assert_approx_eq!(masses[0].mass, 8500.0, 1e-6);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn neutral_masses_to_csv_test() {
+        let list = vec![
+            NeutralMass { mass: 8000.5, intensity: 40.0, charges: vec![8] },
+            NeutralMass { mass: 10000.0, intensity: 430.0, charges: vec![10, 11] },
+        ];
+        let mut w = vec![];
+        neutral_masses_to_csv(&mut w, &list).unwrap();
+        assert_eq!(w, b"mass,intensity,charges\n8000.5,40.0,8\n10000.0,430.0,10;11\n".to_vec());
+    }
+}
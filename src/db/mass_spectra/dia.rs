@@ -0,0 +1,281 @@
+//! Isolation window schemes for data-independent acquisition (DIA).
+//!
+//! Schemes may use fixed or variable window widths, and adjacent
+//! windows may overlap. MS2 scans are assigned to the window matching
+//! their isolation target, stored as the parent m/z.
+
+#[cfg(feature = "csv")]
+use csv;
+#[cfg(feature = "csv")]
+use std::io::prelude::*;
+
+#[cfg(feature = "csv")]
+use traits::Csv;
+#[cfg(feature = "csv")]
+use util::*;
+use super::record::Record;
+use super::record_list::RecordList;
+
+/// Precursor isolation window for DIA MS2 scans.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct IsolationWindow {
+    /// Lower m/z bound (inclusive) of the window.
+    pub start: f64,
+    /// Upper m/z bound (inclusive) of the window.
+    pub end: f64,
+}
+
+impl IsolationWindow {
+    /// Create window from the m/z bounds.
+    #[inline]
+    pub fn new(start: f64, end: f64) -> Self {
+        IsolationWindow {
+            start: start,
+            end: end,
+        }
+    }
+
+    /// Get the isolation target (center) of the window.
+    #[inline]
+    pub fn center(&self) -> f64 {
+        (self.start + self.end) / 2.0
+    }
+
+    /// Get the width of the window, in m/z.
+    #[inline]
+    pub fn width(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// Check if the window contains an m/z value.
+    #[inline]
+    pub fn contains(&self, mz: f64) -> bool {
+        mz >= self.start && mz <= self.end
+    }
+
+    /// Check the window bounds are valid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.start >= 0.0 && self.end > self.start
+    }
+}
+
+/// DIA isolation window scheme, with the windows sorted by m/z.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowScheme {
+    /// Isolation windows, sorted by the lower m/z bound.
+    pub windows: Vec<IsolationWindow>,
+}
+
+impl WindowScheme {
+    /// Create scheme from a list of (possibly variable-width) windows.
+    ///
+    /// Windows with NaN bounds are sorted last, and are not valid.
+    pub fn new(mut windows: Vec<IsolationWindow>) -> Self {
+        windows.sort_by(|x, y| x.start.total_cmp(&y.start).then_with(|| x.end.total_cmp(&y.end)));
+        WindowScheme {
+            windows: windows,
+        }
+    }
+
+    /// Create scheme with fixed-width windows covering an m/z range.
+    ///
+    /// Each window starts `width - overlap` after the previous window,
+    /// and the last window may extend past `end`. Returns `None` if
+    /// the range is empty, or the overlap is not smaller than the width.
+    pub fn fixed(start: f64, end: f64, width: f64, overlap: f64) -> Option<Self> {
+        if !(end > start && overlap >= 0.0 && width > overlap) {
+            return None;
+        }
+
+        let step = width - overlap;
+        let mut windows = vec![];
+        let mut lower = start;
+        while lower < end {
+            windows.push(IsolationWindow::new(lower, lower + width));
+            lower = start + step * windows.len() as f64;
+        }
+        Some(WindowScheme::new(windows))
+    }
+
+    /// Check all the windows are valid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        !self.windows.is_empty() && self.windows.iter().all(|w| w.is_valid())
+    }
+
+    /// Get the index of the window for an isolation target.
+    ///
+    /// Where windows overlap, the window with the closest center is
+    /// used. Returns `None` if no window contains the m/z.
+    pub fn find(&self, mz: f64) -> Option<usize> {
+        let distance = |i: &usize| (self.windows[*i].center() - mz).abs();
+        (0..self.windows.len())
+            .filter(|&i| self.windows[i].contains(mz))
+            .min_by(|x, y| distance(x).total_cmp(&distance(y)))
+    }
+
+    /// Get the index of the window for an MS2 record.
+    ///
+    /// Returns `None` if the record is not an MS2 scan, or the
+    /// isolation target is outside every window.
+    #[inline]
+    pub fn assign(&self, record: &Record) -> Option<usize> {
+        match record.ms_level {
+            2 => self.find(record.parent_mz),
+            _ => None,
+        }
+    }
+
+    /// Group MS2 records by window, in the order of the windows.
+    ///
+    /// Records not assigned to a window are discarded.
+    pub fn group<Iter>(&self, iter: Iter) -> Vec<RecordList>
+        where Iter: IntoIterator<Item = Record>
+    {
+        let mut groups: Vec<RecordList> = vec![vec![]; self.windows.len()];
+        for record in iter {
+            if let Some(index) = self.assign(&record) {
+                groups[index].push(record);
+            }
+        }
+        groups
+    }
+}
+
+// CSV
+
+/// Column headers for window scheme definitions.
+#[cfg(feature = "csv")]
+const CSV_HEADER: [&'static str; 2] = ["Start", "End"];
+
+#[cfg(feature = "csv")]
+impl Csv for WindowScheme {
+    #[inline]
+    fn estimate_csv_size(&self) -> usize {
+        // Header, and approximately 16 bytes per window.
+        10 + 16 * self.windows.len()
+    }
+
+    /// Export the scheme as a 2-column (start, end) CSV document.
+    fn to_csv<T: Write>(&self, writer: &mut T, delimiter: u8) -> Result<()> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(writer);
+        writer.write_record(&CSV_HEADER)?;
+        for window in self.windows.iter() {
+            let start = to_bytes(&window.start)?;
+            let end = to_bytes(&window.end)?;
+            writer.write_record(&[start, end])?;
+        }
+        Ok(())
+    }
+
+    /// Import the scheme from a CSV document with (start, end) rows.
+    ///
+    /// The header row is optional, and extra columns are ignored.
+    fn from_csv<T: Read>(reader: &mut T, delimiter: u8) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .comment(Some(CSV_COMMENT[0]))
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        let mut windows = vec![];
+        for (index, row) in reader.records().enumerate() {
+            let row = row?;
+            let start = none_to_error!(row.get(0), InvalidInput);
+            let end = none_to_error!(row.get(1), InvalidInput);
+            let is_header = index == 0 && start.eq_ignore_ascii_case(CSV_HEADER[0]);
+            if !is_header {
+                let window = IsolationWindow::new(from_string(strip_bom(start))?, from_string(end)?);
+                bool_to_error!(window.is_valid(), InvalidInput);
+                windows.push(window);
+            }
+        }
+
+        let scheme = WindowScheme::new(windows);
+        bool_to_error!(scheme.is_valid(), InvalidInput);
+        Ok(scheme)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms2(parent_mz: f64) -> Record {
+        let mut record = Record::new();
+        record.ms_level = 2;
+        record.parent_mz = parent_mz;
+        record
+    }
+
+    #[test]
+    fn fixed_scheme_test() {
+        let scheme = WindowScheme::fixed(400.0, 500.0, 25.0, 1.0).unwrap();
+        let starts: Vec<f64> = scheme.windows.iter().map(|w| w.start).collect();
+        assert_eq!(starts, vec![400.0, 424.0, 448.0, 472.0, 496.0]);
+        assert_eq!(scheme.windows[1].center(), 436.5);
+        assert_eq!(scheme.windows[1].width(), 25.0);
+        assert!(scheme.is_valid());
+
+        assert!(WindowScheme::fixed(400.0, 500.0, 25.0, 25.0).is_none());
+        assert!(WindowScheme::fixed(500.0, 400.0, 25.0, 0.0).is_none());
+        assert!(!WindowScheme::new(vec![]).is_valid());
+
+        // NaN bounds must not panic.
+        let scheme = WindowScheme::new(vec![
+            IsolationWindow::new(f64::NAN, 425.0),
+            IsolationWindow::new(400.0, 425.0),
+        ]);
+        assert_eq!(scheme.windows[0].start, 400.0);
+        assert!(!scheme.is_valid());
+        assert_eq!(scheme.find(410.0), Some(0));
+        assert_eq!(scheme.find(f64::NAN), None);
+    }
+
+    #[test]
+    fn assign_test() {
+        let scheme = WindowScheme::new(vec![
+            IsolationWindow::new(420.0, 460.0),
+            IsolationWindow::new(400.0, 421.0),
+        ]);
+        assert_eq!(scheme.windows[0].start, 400.0);
+        assert_eq!(scheme.find(410.0), Some(0));
+        assert_eq!(scheme.find(420.5), Some(0));
+        assert_eq!(scheme.find(440.0), Some(1));
+        assert_eq!(scheme.find(470.0), None);
+
+        let mut ms1 = ms2(410.0);
+        ms1.ms_level = 1;
+        assert_eq!(scheme.assign(&ms1), None);
+
+        let groups = scheme.group(vec![ms2(410.0), ms1, ms2(440.0), ms2(500.0), ms2(405.0)]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].len(), 2);
+        assert_eq!(groups[1].len(), 1);
+        assert_eq!(groups[1][0].parent_mz, 440.0);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_test() {
+        let text = b"Start,End\n400,425.5\n424.5,450,extra\n";
+        let scheme = WindowScheme::from_csv_bytes(text, b',').unwrap();
+        assert_eq!(scheme.windows, vec![IsolationWindow::new(400.0, 425.5), IsolationWindow::new(424.5, 450.0)]);
+        assert_eq!(scheme.to_csv_bytes(b',').unwrap(), b"Start,End\n400.0,425.5\n424.5,450.0\n".to_vec());
+
+        let scheme = WindowScheme::from_csv_bytes(b"#comment\n400\t425.5\n", b'\t').unwrap();
+        assert_eq!(scheme.windows.len(), 1);
+
+        assert!(WindowScheme::from_csv_bytes(b"400,300\n", b',').is_err());
+        assert!(WindowScheme::from_csv_bytes(b"400\n", b',').is_err());
+        assert!(WindowScheme::from_csv_bytes(b"Start,End\n", b',').is_err());
+    }
+}
@@ -5,8 +5,9 @@ use std::io::Lines;
 
 use traits::*;
 use util::*;
-use super::mgf::MgfRecordIter;
+use super::mgf::{estimate_extra_size, estimate_mobility_size, export_extra, export_mobility, export_peak_mobility, next_header_line, parse_extra_line, parse_mobility_line, parse_peak_mobility, MgfRecordIter};
 use super::peak::Peak;
+use super::peak_list::PeakListExt;
 use super::re::*;
 use super::record::Record;
 
@@ -20,7 +21,10 @@ pub(crate) fn estimate_fullms_mgf_record_size(record: &Record) -> usize {
     const MGF_VOCABULARY_SIZE: usize = 175;
     // Estimated average is ~20 characters per line, assume slightly above.
     const MGF_PEAK_SIZE: usize = 25;
-    MGF_VOCABULARY_SIZE + MGF_PEAK_SIZE * record.peaks.len()
+    MGF_VOCABULARY_SIZE +
+        estimate_extra_size(record) +
+        estimate_mobility_size(record) +
+        MGF_PEAK_SIZE * record.peaks.len()
 }
 
 // WRITER
@@ -52,6 +56,28 @@ fn export_rt<T: Write>(writer: &mut T, record: &Record)
     Ok(())
 }
 
+#[inline(always)]
+fn export_ion_injection_time<T: Write>(writer: &mut T, record: &Record)
+    -> Result<()>
+{
+    // Export 0 for an unknown injection time, since the field is required.
+    let injection_time = to_bytes(&record.injection_time.unwrap_or(0.0))?;
+    write_alls!(writer, b"IonInjectionTime(ms): ", injection_time.as_slice(), b"\n")?;
+
+    Ok(())
+}
+
+#[inline(always)]
+fn export_total_ion_current<T: Write>(writer: &mut T, record: &Record)
+    -> Result<()>
+{
+    // Export the summed peak intensities for an unknown TIC.
+    let tic = to_bytes(&record.total_ion_current())?;
+    write_alls!(writer, b"TotalIonCurrent: ", tic.as_slice(), b"\n")?;
+
+    Ok(())
+}
+
 #[inline(always)]
 fn export_basepeak<T: Write>(writer: &mut T, record: &Record)
     -> Result<()>
@@ -84,7 +110,9 @@ fn export_spectra<T: Write>(writer: &mut T, record: &Record)
     for peak in record.peaks.iter() {
         let mz = to_bytes(&peak.mz)?;
         let intensity = to_bytes(&peak.intensity)?;
-        write_alls!(writer, mz.as_slice(), b"\t", intensity.as_slice(), b"\n")?;
+        write_alls!(writer, mz.as_slice(), b"\t", intensity.as_slice())?;
+        export_peak_mobility(writer, peak, b"\t")?;
+        writer.write_all(b"\n")?;
     }
 
     Ok(())
@@ -96,9 +124,11 @@ pub(crate) fn record_to_fullms_mgf<T: Write>(writer: &mut T, record: &Record)
 {
     export_scan(writer, record)?;
     export_rt(writer, record)?;
-    // Export null values,since we don't store this information.
-    writer.write_all(b"IonInjectionTime(ms): 0.0\nTotalIonCurrent: 0\n")?;
+    export_ion_injection_time(writer, record)?;
+    export_total_ion_current(writer, record)?;
     export_basepeak(writer, record)?;
+    export_mobility(writer, record, b": ")?;
+    export_extra(writer, record, b": ")?;
     export_spectra(writer, record)?;
     writer.write_all(b"\n\n")?;
 
@@ -109,7 +139,7 @@ pub(crate) fn record_to_fullms_mgf<T: Write>(writer: &mut T, record: &Record)
 
 #[inline(always)]
 fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
-    -> Result<TextWriterState<T>>
+    -> Result<TextWriterState<'_, T>>
 {
     Ok(TextWriterState::new(writer, delimiter))
 }
@@ -219,7 +249,7 @@ fn parse_rt_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     type Rt = FullMsMgfRtRegex;
 
     // Verify and parse the RT line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["Ret.Time: "], ": ")?;
     let captures = none_to_error!(Rt::extract().captures(&line), InvalidInput);
 
     let rt = capture_as_str(&captures, Rt::RT_INDEX);
@@ -230,35 +260,39 @@ fn parse_rt_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
 
 /// Parse the ion injection time line.
 #[inline(always)]
-fn parse_ion_injection_time_line<T: BufRead>(lines: &mut Lines<T>, _: &mut Record)
+fn parse_ion_injection_time_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     -> Result<()>
 {
-    // Verify the ion injection time line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    // Verify and parse the ion injection time line.
+    let line = next_header_line(lines, record, &["IonInjectionTime(ms): "], ": ")?;
     bool_to_error!(line.starts_with("IonInjectionTime(ms): "), InvalidInput);
+    let injection_time = &line["IonInjectionTime(ms): ".len()..];
+    record.injection_time = Some(from_string(injection_time)?);
 
     Ok(())
 }
 
 /// Parse the total ion current line.
 #[inline(always)]
-fn parse_total_ion_current_line<T: BufRead>(lines: &mut Lines<T>, _: &mut Record)
+fn parse_total_ion_current_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     -> Result<()>
 {
-    // Verify the total ion current line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    // Verify and parse the total ion current line.
+    let line = next_header_line(lines, record, &["TotalIonCurrent: "], ": ")?;
     bool_to_error!(line.starts_with("TotalIonCurrent: "), InvalidInput);
+    let tic = &line["TotalIonCurrent: ".len()..];
+    record.tic = Some(from_string(tic)?);
 
     Ok(())
 }
 
 /// Parse the basepeak mass line.
 #[inline(always)]
-fn parse_basepeak_mass_line<T: BufRead>(lines: &mut Lines<T>, _: &mut Record)
+fn parse_basepeak_mass_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     -> Result<()>
 {
     // Verify the basepeak mass line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["BasePeakMass: "], ": ")?;
     bool_to_error!(line.starts_with("BasePeakMass: "), InvalidInput);
 
     Ok(())
@@ -266,11 +300,11 @@ fn parse_basepeak_mass_line<T: BufRead>(lines: &mut Lines<T>, _: &mut Record)
 
 /// Parse the basepeak intensity line.
 #[inline(always)]
-fn parse_basepeak_intensity_line<T: BufRead>(lines: &mut Lines<T>, _: &mut Record)
+fn parse_basepeak_intensity_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     -> Result<()>
 {
     // Verify the basepeak intensity line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["BasePeakIntensity: "], ": ")?;
     bool_to_error!(line.starts_with("BasePeakIntensity: "), InvalidInput);
 
     Ok(())
@@ -286,17 +320,25 @@ fn parse_spectra<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
         if line.is_empty() {
             break;
         }
+        if parse_mobility_line(&line, record, ": ")? {
+            continue;
+        }
+        if parse_extra_line(&line, record, ": ") {
+            continue;
+        }
 
         // Parse the line data
         let mut items = line.split('\t');
         let mz = none_to_error!(items.next(), InvalidInput);
         let intensity = none_to_error!(items.next(), InvalidInput);
+        let mobility = parse_peak_mobility(items.next())?;
         bool_to_error!(items.next().is_none(), InvalidInput);
 
         record.peaks.push(Peak {
             mz: from_string(mz)?,
             intensity: from_string(intensity)?,
             z: 0,
+            mobility: mobility,
         });
     }
 
@@ -318,6 +360,10 @@ pub(crate) fn record_from_fullms_mgf<T: BufRead>(reader: &mut T)
     parse_basepeak_intensity_line(&mut lines, &mut record)?;
     parse_spectra(&mut lines, &mut record)?;
 
+    // Guarantee the sorted invariant for the peak list.
+    if !record.peaks.is_sorted_by_mz() {
+        record.peaks.sort_by_mz();
+    }
     record.peaks.shrink_to_fit();
     Ok(record)
 }
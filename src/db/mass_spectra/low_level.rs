@@ -10,7 +10,27 @@
 //! typically < 16 KB required for internal buffers, and < 1 KB for each
 //! individual item.
 
+// Re-export the strict and lenient iterator wrappers, to name the
+// strict and lenient reader types.
+pub use util::iterator::{LenientIter, StrictIter};
+
+pub use super::binning::*;
+
 #[cfg(feature = "mgf")]
 pub use super::mgf::*;
 
+#[cfg(feature = "mgf")]
+pub use super::multi_mgf::*;
+
+#[cfg(feature = "csv")]
+pub use super::noise::reference_iterator_to_noise_csv;
+
+#[cfg(feature = "obo")]
+pub use super::obo::*;
+
+pub use super::deconvolution::*;
+pub use super::precursor::*;
 pub use super::re::*;
+pub use super::similarity::*;
+pub use super::transform::*;
+pub use super::xic::*;
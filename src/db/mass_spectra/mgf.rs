@@ -1,11 +1,14 @@
 //! Helper utilities for MGF loading and saving.
 
+use std::io;
 use std::io::prelude::*;
 
+use bio::mass::Adduct;
 use traits::*;
 use util::*;
 use super::fullms_mgf::*;
 use super::msconvert_mgf::*;
+use super::peak::Peak;
 use super::pava_mgf::*;
 use super::pwiz_mgf::*;
 use super::record::Record;
@@ -28,11 +31,17 @@ impl<T: BufRead> MgfIter<T> {
     /// Create new MgfIter from a buffered reader.
     #[inline]
     pub fn new(reader: T, start: &'static [u8]) -> Self {
+        MgfIter::with_capacity(reader, start, DEFAULT_BUFFER_CAPACITY, DEFAULT_LINE_CAPACITY)
+    }
+
+    /// Create new MgfIter from a buffered reader and buffer capacities.
+    #[inline]
+    pub fn with_capacity(reader: T, start: &'static [u8], buffer_capacity: usize, line_capacity: usize) -> Self {
         MgfIter {
             reader: reader,
             start: start,
-            buf: Vec::with_capacity(8000),
-            line: Bytes::with_capacity(8000)
+            buf: Vec::with_capacity(buffer_capacity),
+            line: Bytes::with_capacity(line_capacity)
         }
     }
 }
@@ -42,10 +51,15 @@ impl<T: BufRead> Iterator for MgfIter<T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         bytes_next!(&mut self.reader, &mut self.buf, &mut self.line, unsafe {
-            if self.line == b"\n" || self.line == b"\r\n" || self.line.starts_with(b"MASS=") {
+            normalize_line(&mut self.line);
+            if self.line.is_empty() || self.line.starts_with(b"MASS=") {
                 // Ignore whitespace and lines with "Mass".
                 self.line.set_len(0);
                 continue;
+            } else if self.buf.is_empty() && self.line.starts_with(MGF_COMMENT) {
+                // Ignore leading comments, such as export metadata.
+                self.line.set_len(0);
+                continue;
             } else if self.buf.len() > 0 && self.line.starts_with(self.start) {
                 // Create result from existing buffer,
                 // clear the existing buffer, and add
@@ -61,6 +75,172 @@ impl<T: BufRead> Iterator for MgfIter<T> {
     }
 }
 
+/// Get the line prefix starting each scan for the MGF kind.
+#[inline]
+fn scan_start(kind: MgfKind) -> &'static [u8] {
+    match kind {
+        MgfKind::FullMs => b"Scan#:",
+        _               => b"BEGIN IONS",
+    }
+}
+
+// COUNT
+
+/// Count the MGF scans in a document, without parsing the scans.
+///
+/// Much cheaper than loading the records, to pre-allocate collections
+/// or to report progress totals for large documents.
+pub fn count_scans_mgf<T: BufRead>(reader: T, kind: MgfKind) -> Result<usize> {
+    let mut count = 0;
+    for result in MgfIter::new(reader, scan_start(kind)) {
+        result?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+// EXTRA HEADERS
+
+/// Estimate the size of the unrecognized header lines of an MGF record.
+#[inline]
+pub(crate) fn estimate_extra_size(record: &Record) -> usize {
+    record.extra.iter().fold(0, |sum, &(ref k, ref v)| sum + k.len() + v.len() + 3)
+}
+
+/// Export the unrecognized header lines, joining keys and values with `separator`.
+#[inline]
+pub(crate) fn export_extra<T: Write>(writer: &mut T, record: &Record, separator: &[u8])
+    -> Result<()>
+{
+    for &(ref key, ref value) in record.extra.iter() {
+        write_alls!(writer, key.as_bytes(), separator, value.as_bytes(), b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// Check if a line is a header line.
+///
+/// Header lines start with an ASCII letter, unlike peak lines, and
+/// contain a `separator` between the key and value.
+#[inline]
+pub(crate) fn is_header_line(line: &str, separator: &str) -> bool {
+    let is_alphabetic = line.as_bytes().first().map_or(false, |c| c.is_ascii_alphabetic());
+    is_alphabetic && line.contains(separator)
+}
+
+/// Parse an unrecognized header line, returning if the line was a header.
+#[inline]
+pub(crate) fn parse_extra_line(line: &str, record: &mut Record, separator: &str)
+    -> bool
+{
+    if !is_header_line(line, separator) {
+        return false;
+    }
+    let index = line.find(separator).unwrap();
+    let key = String::from(&line[..index]);
+    let value = String::from(&line[index + separator.len()..]);
+    record.extra.push((key, value));
+    true
+}
+
+/// Read the next known header line, starting with one of the `keys`.
+///
+/// Stores the ion mobility and unrecognized header lines before the
+/// known header, so these may be anywhere in the record header.
+/// Returns the first line that is neither, to be verified by the caller.
+pub(crate) fn next_header_line<I>(lines: &mut I, record: &mut Record, keys: &[&str], separator: &str)
+    -> Result<String>
+    where I: Iterator<Item = io::Result<String>>
+{
+    loop {
+        let line = none_to_error!(lines.next(), InvalidInput)?;
+        if keys.iter().any(|key| line.starts_with(key)) {
+            return Ok(line);
+        } else if parse_mobility_line(&line, record, separator)? {
+            continue;
+        } else if !parse_extra_line(&line, record, separator) {
+            return Ok(line);
+        }
+    }
+}
+
+// ION MOBILITY
+
+/// Header key for the ion mobility of the parent ion.
+const ION_MOBILITY_KEY: &'static str = "ION_MOBILITY";
+
+/// Header key for the collisional cross section of the parent ion.
+const CCS_KEY: &'static str = "CCS";
+
+/// Estimate the size of the ion mobility header lines of an MGF record.
+#[inline]
+pub(crate) fn estimate_mobility_size(record: &Record) -> usize {
+    // Key, separator, value and line ending.
+    const MOBILITY_SIZE: usize = 40;
+    MOBILITY_SIZE * (record.ion_mobility.is_some() as usize + record.ccs.is_some() as usize)
+}
+
+/// Export the known ion mobility header lines, joining keys and values with `separator`.
+#[inline]
+pub(crate) fn export_mobility<T: Write>(writer: &mut T, record: &Record, separator: &[u8])
+    -> Result<()>
+{
+    if let Some(ion_mobility) = record.ion_mobility {
+        let ion_mobility = to_bytes(&ion_mobility)?;
+        write_alls!(writer, ION_MOBILITY_KEY.as_bytes(), separator, ion_mobility.as_slice(), b"\n")?;
+    }
+    if let Some(ccs) = record.ccs {
+        let ccs = to_bytes(&ccs)?;
+        write_alls!(writer, CCS_KEY.as_bytes(), separator, ccs.as_slice(), b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// Parse an ion mobility header line, returning if the line was a mobility header.
+#[inline]
+pub(crate) fn parse_mobility_line(line: &str, record: &mut Record, separator: &str)
+    -> Result<bool>
+{
+    let index = match line.find(separator) {
+        Some(index) => index,
+        None        => return Ok(false),
+    };
+    let value = &line[index + separator.len()..];
+    match &line[..index] {
+        ION_MOBILITY_KEY => record.ion_mobility = Some(from_string(value)?),
+        CCS_KEY          => record.ccs = Some(from_string(value)?),
+        _                => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Export the ion mobility of a peak, as a trailing column after `separator`.
+#[inline]
+pub(crate) fn export_peak_mobility<T: Write>(writer: &mut T, peak: &Peak, separator: &[u8])
+    -> Result<()>
+{
+    if let Some(mobility) = peak.mobility {
+        let mobility = to_bytes(&mobility)?;
+        write_alls!(writer, separator, mobility.as_slice())?;
+    }
+
+    Ok(())
+}
+
+/// Parse the optional ion mobility column of a peak.
+#[inline]
+pub(crate) fn parse_peak_mobility(item: Option<&str>)
+    -> Result<Option<f64>>
+{
+    match item {
+        Some(mobility) => Ok(Some(from_string(mobility)?)),
+        None           => Ok(None),
+    }
+}
+
 // SIZE
 
 /// Estimate the size of an MGF record.
@@ -212,16 +392,26 @@ pub fn record_from_mgf<T: BufRead>(reader: &mut T, kind: MgfKind)
 /// Wraps `MgfIter` and converts the text to records.
 pub struct MgfRecordIter<T: BufRead> {
     iter: MgfIter<T>,
-    kind: MgfKind
+    kind: MgfKind,
+    encoding: Encoding,
+    adduct: Option<Adduct>,
 }
 
 impl<T: BufRead> MgfRecordIter<T> {
     /// Create new MgfRecordIter from a buffered reader.
     #[inline]
     pub fn new(reader: T, start: &'static [u8], kind: MgfKind) -> Self {
+        MgfRecordIter::with_options(reader, start, kind, &ReaderOptions::default())
+    }
+
+    /// Create new MgfRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, start: &'static [u8], kind: MgfKind, options: &ReaderOptions) -> Self {
         MgfRecordIter {
-            iter: MgfIter::new(reader, start),
-            kind: kind
+            iter: MgfIter::with_capacity(reader, start, options.buffer_capacity, options.line_capacity),
+            kind: kind,
+            encoding: options.encoding,
+            adduct: options.adduct,
         }
     }
 }
@@ -236,7 +426,13 @@ impl<T: BufRead> Iterator for MgfRecordIter<T> {
 
         };
 
-        Some(Record::from_mgf_bytes(&bytes, self.kind))
+        let bytes = self.encoding.decode_bytes(bytes);
+        let result = Record::from_mgf_bytes(&bytes, self.kind).map(|mut record| {
+            record.adduct = self.adduct;
+            record
+        });
+        log_parsed!("MGF", &result);
+        Some(result)
     }
 }
 
@@ -253,6 +449,14 @@ pub fn iterator_from_mgf<T: BufRead>(reader: T, kind: MgfKind)
     }
 }
 
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_mgf_with_options<T: BufRead>(reader: T, kind: MgfKind, options: &ReaderOptions)
+    -> MgfRecordIter<T>
+{
+    MgfRecordIter::with_options(reader, scan_start(kind), kind, options)
+}
+
 // READER -- STRICT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -268,6 +472,14 @@ pub fn iterator_from_mgf_strict<T: BufRead>(reader: T, kind: MgfKind)
     MgfRecordStrictIter::new(iterator_from_mgf(reader, kind))
 }
 
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_mgf_strict_with_options<T: BufRead>(reader: T, kind: MgfKind, options: &ReaderOptions)
+    -> MgfRecordStrictIter<T>
+{
+    MgfRecordStrictIter::new(iterator_from_mgf_with_options(reader, kind, options))
+}
+
 // READER -- LENIENT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -283,6 +495,113 @@ pub fn iterator_from_mgf_lenient<T: BufRead>(reader: T, kind: MgfKind)
     MgfRecordLenientIter::new(iterator_from_mgf(reader, kind))
 }
 
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_mgf_lenient_with_options<T: BufRead>(reader: T, kind: MgfKind, options: &ReaderOptions)
+    -> MgfRecordLenientIter<T>
+{
+    MgfRecordLenientIter::new(iterator_from_mgf_with_options(reader, kind, options))
+}
+
+// READER -- MMAP
+
+/// Iterator to lazily load `Record`s from a memory-mapped document.
+///
+/// Parses the records from slices of the mapped memory, only copying
+/// records which require line normalization or text decoding. Lines
+/// before the first scan, such as `MASS=` headers, are skipped.
+#[cfg(feature = "mmap")]
+pub struct MgfMmapRecordIter<'a> {
+    iter: MmapBlockIter<'a>,
+    kind: MgfKind,
+    encoding: Encoding,
+    adduct: Option<Adduct>,
+}
+
+#[cfg(feature = "mmap")]
+impl<'a> MgfMmapRecordIter<'a> {
+    /// Create new MgfMmapRecordIter from mapped memory.
+    #[inline]
+    pub fn new(data: &'a [u8], kind: MgfKind) -> Self {
+        MgfMmapRecordIter::with_options(data, kind, &ReaderOptions::default())
+    }
+
+    /// Create new MgfMmapRecordIter from mapped memory and reader options.
+    #[inline]
+    pub fn with_options(data: &'a [u8], kind: MgfKind, options: &ReaderOptions) -> Self {
+        MgfMmapRecordIter {
+            iter: MmapBlockIter::new(skip_metadata(data, MGF_COMMENT), scan_start(kind)),
+            kind: kind,
+            encoding: options.encoding,
+            adduct: options.adduct,
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<'a> Iterator for MgfMmapRecordIter<'a> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut block = normalize_block(self.iter.next()?, Some(b"MASS="));
+        while !block.starts_with(scan_start(self.kind)) {
+            // Skip the lines before the first scan, as `MgfIter` does.
+            block = normalize_block(self.iter.next()?, Some(b"MASS="));
+        }
+        let bytes = self.encoding.decode_cow(block);
+        let result = Record::from_mgf_bytes(&bytes, self.kind).map(|mut record| {
+            record.adduct = self.adduct;
+            record
+        });
+        log_parsed!("MGF", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from mapped memory.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_mgf_mmap<'a>(data: &'a [u8], kind: MgfKind)
+    -> MgfMmapRecordIter<'a>
+{
+    MgfMmapRecordIter::new(data, kind)
+}
+
+/// Create default record iterator from mapped memory, with custom reader options.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_mgf_mmap_with_options<'a>(data: &'a [u8], kind: MgfKind, options: &ReaderOptions)
+    -> MgfMmapRecordIter<'a>
+{
+    MgfMmapRecordIter::with_options(data, kind, options)
+}
+
+/// Iterator to lazily load `Record`s from a memory-mapped document strictly.
+#[cfg(feature = "mmap")]
+pub type MgfMmapRecordStrictIter<'a> = StrictIter<Record, MgfMmapRecordIter<'a>>;
+
+/// Create strict record iterator from mapped memory.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_mgf_mmap_strict<'a>(data: &'a [u8], kind: MgfKind)
+    -> MgfMmapRecordStrictIter<'a>
+{
+    MgfMmapRecordStrictIter::new(iterator_from_mgf_mmap(data, kind))
+}
+
+/// Iterator to lazily load `Record`s from a memory-mapped document leniently.
+#[cfg(feature = "mmap")]
+pub type MgfMmapRecordLenientIter<'a> = LenientIter<Record, MgfMmapRecordIter<'a>>;
+
+/// Create lenient record iterator from mapped memory.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_mgf_mmap_lenient<'a>(data: &'a [u8], kind: MgfKind)
+    -> MgfMmapRecordLenientIter<'a>
+{
+    MgfMmapRecordLenientIter::new(iterator_from_mgf_mmap(data, kind))
+}
+
 // TRAITS
 
 impl Mgf for Record {
@@ -300,6 +619,20 @@ impl Mgf for Record {
     fn from_mgf<T: BufRead>(reader: &mut T, kind: MgfKind) -> Result<Self> {
         record_from_mgf(reader, kind)
     }
+
+    #[inline(always)]
+    fn from_mgf_iter<'a, T: 'a + BufRead>(reader: T, kind: MgfKind)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_mgf(reader, kind))
+    }
+
+    #[inline(always)]
+    fn to_mgf_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter, kind: MgfKind)
+        -> Result<()>
+    {
+        reference_iterator_to_mgf(writer, iter, kind)
+    }
 }
 
 impl Mgf for RecordList {
@@ -350,7 +683,7 @@ mod tests {
     use std::fs::File;
     use std::io::{BufReader, Cursor};
     use std::path::PathBuf;
-    use test::testdata_dir;
+    use test::{testdata_dir, windows_text};
     use super::*;
     use super::super::test::*;
 
@@ -381,6 +714,96 @@ mod tests {
         assert_eq!(r.unwrap(), &[b"BEGIN IONS\nT=A\nEND IONS\n".to_vec(), b"BEGIN IONS\nT=B\nEND IONS\n".to_vec()]);
     }
 
+    #[test]
+    fn unsorted_peaks_test() {
+        // Parsers must guarantee the peak list is sorted by m/z.
+        let text = b"BEGIN IONS\nTITLE=Scan 33450 (rt=8692.0) [QPvivo_2015_11_10_1targetmethod]\nPEPMASS=775.15625\t170643.953125\nCHARGE=4+\n257.514984\t1.0\n205.9304178\t2.0\nEND IONS\n";
+        let r = record_from_mgf(&mut Cursor::new(&text[..]), MgfKind::Pava).unwrap();
+        assert_eq!(r.peaks.len(), 2);
+        assert_eq!(r.peaks[0].mz, 205.9304178);
+        assert_eq!(r.peaks[0].intensity, 2.0);
+        assert_eq!(r.peaks[1].mz, 257.514984);
+    }
+
+    #[test]
+    fn count_scans_mgf_test() {
+        let count = |text: &[u8], kind| count_scans_mgf(Cursor::new(text), kind).unwrap();
+        assert_eq!(count(FULLMS_33450_MGF, MgfKind::FullMs), 1);
+        assert_eq!(count(MSCONVERT_33450_MGF, MgfKind::MsConvert), 1);
+        assert_eq!(count(PAVA_33450_MGF, MgfKind::Pava), 1);
+        assert_eq!(count(PWIZ_33450_MGF, MgfKind::Pwiz), 1);
+        assert_eq!(count(&[PAVA_33450_MGF, PAVA_EMPTY_MGF].concat(), MgfKind::Pava), 2);
+        assert_eq!(count(b"", MgfKind::Pava), 0);
+    }
+
+    #[test]
+    fn extra_headers_test() {
+        // Parsers must preserve unrecognized header lines.
+        let text = b"BEGIN IONS\nTITLE=Scan 33450 (rt=8692.0) [QPvivo_2015_11_10_1targetmethod]\nPEPMASS=775.15625\t170643.953125\nCHARGE=4+\nRAWFILE=QPvivo.raw\nINSTRUMENT=ESI-QUAD-TOF\n205.9304178\t2.0\nEND IONS\n";
+        let r = record_from_mgf(&mut Cursor::new(&text[..]), MgfKind::Pava).unwrap();
+        assert_eq!(r.extra, vec![
+            (String::from("RAWFILE"), String::from("QPvivo.raw")),
+            (String::from("INSTRUMENT"), String::from("ESI-QUAD-TOF")),
+        ]);
+        assert_eq!(r.peaks.len(), 1);
+
+        // Writers must re-emit them, for lossless conversions.
+        let mut w = vec![];
+        record_to_mgf(&mut w, &r, MgfKind::Pava).unwrap();
+        assert_eq!(w, [&text[..], b"\n"].concat());
+
+        let mut r = mgf_33450();
+        r.extra.push((String::from("SEQ"), String::from("SAMPLER")));
+        for kind in [MgfKind::FullMs, MgfKind::MsConvert, MgfKind::Pava, MgfKind::Pwiz].iter() {
+            let mut w = vec![];
+            record_to_mgf(&mut w, &r, *kind).unwrap();
+            let y = record_from_mgf(&mut Cursor::new(&w[..]), *kind).unwrap();
+            assert_eq!(y.extra, r.extra);
+            assert_eq!(y.peaks, r.peaks);
+        }
+    }
+
+    #[test]
+    fn leading_extra_headers_test() {
+        // Parsers must preserve unrecognized headers before the known headers.
+        let text = b"BEGIN IONS\nRAWFILE=QPvivo.raw\nTITLE=Scan 33450 (rt=8692.0) [QPvivo_2015_11_10_1targetmethod]\nINSTRUMENT=ESI-QUAD-TOF\nPEPMASS=775.15625\t170643.953125\nCHARGE=4+\n205.9304178\t2.0\nEND IONS\n";
+        let r = record_from_mgf(&mut Cursor::new(&text[..]), MgfKind::Pava).unwrap();
+        assert_eq!(r.extra, vec![
+            (String::from("RAWFILE"), String::from("QPvivo.raw")),
+            (String::from("INSTRUMENT"), String::from("ESI-QUAD-TOF")),
+        ]);
+        assert_eq!(r.num, 33450);
+        assert_eq!(r.parent_z, 4);
+        assert_eq!(r.peaks.len(), 1);
+
+        // Insert an unrecognized header before each known header.
+        let r = mgf_33450();
+        let kinds = [
+            (MgfKind::FullMs, "Instrument: Orbitrap"),
+            (MgfKind::MsConvert, "INSTRUMENT=Orbitrap"),
+            (MgfKind::Pava, "INSTRUMENT=Orbitrap"),
+            (MgfKind::Pwiz, "INSTRUMENT=Orbitrap"),
+        ];
+        for &(kind, extra) in kinds.iter() {
+            let mut w = vec![];
+            record_to_mgf(&mut w, &r, kind).unwrap();
+            let x = record_from_mgf(&mut Cursor::new(&w[..]), kind).unwrap();
+            let text = String::from_utf8(w).unwrap();
+            let lines: Vec<&str> = text.lines().collect();
+            let headers = lines.iter().skip(1).take_while(|l| l.as_bytes()[0].is_ascii_alphabetic()).count();
+            for i in 1..headers + 1 {
+                let mut copy = lines.clone();
+                copy.insert(i, extra);
+                let y = record_from_mgf(&mut Cursor::new(copy.join("\n").as_bytes()), kind).unwrap();
+                assert_eq!(y.extra.len(), 1);
+                assert_eq!(y.num, x.num);
+                assert_eq!(y.rt, x.rt);
+                assert_eq!(y.parent_z, x.parent_z);
+                assert_eq!(y.peaks, x.peaks);
+            }
+        }
+    }
+
     #[test]
     fn estimate_size_test() {
         let s = mgf_33450();
@@ -406,9 +829,9 @@ mod tests {
         assert_eq!(estimate_list_size(&v, kind), 2087);
     }
 
-    fn iterator_to_mgf_test(kind: MgfKind, expected: &[u8]) {
-        let v = vec![mgf_33450()];
-        let u = vec![mgf_33450(), mgf_empty()];
+    fn iterator_to_mgf_test(kind: MgfKind, record: Record, expected: &[u8]) {
+        let v = vec![record.clone()];
+        let u = vec![record, mgf_empty()];
 
         // reference -- default
         let mut w = Cursor::new(vec![]);
@@ -472,6 +895,25 @@ mod tests {
         let iter = iterator_from_mgf_lenient(Cursor::new(input.to_vec()), kind);
         let v: Result<RecordList> = iter.collect();
         assert_eq!(expected, v.unwrap());
+
+        // record iterator -- mmap
+        #[cfg(feature = "mmap")] {
+            let v: Result<RecordList> = iterator_from_mgf_mmap(input, kind).collect();
+            assert_eq!(expected, v.unwrap());
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_mass_header_test() {
+        // Both readers must skip the "MASS=" header before the first scan.
+        let text = [&b"MASS=Monoisotopic\n"[..], PAVA_33450_MGF].concat();
+        let expected: RecordList = iterator_from_mgf(Cursor::new(text.clone()), MgfKind::Pava)
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(expected.len(), 1);
+        let v: Result<RecordList> = iterator_from_mgf_mmap(&text, MgfKind::Pava).collect();
+        assert_eq!(expected, v.unwrap());
     }
 
     fn iterator_from_mgf_test_invalid(kind: MgfKind, input: &[u8], expected: RecordList) {
@@ -489,13 +931,171 @@ mod tests {
         let iter = iterator_from_mgf_lenient(Cursor::new(input.to_vec()), kind);
         let v: Result<RecordList> = iter.collect();
         assert_eq!(v.unwrap().len(), 0);
+
+        // record iterator -- mmap
+        #[cfg(feature = "mmap")] {
+            let v: Result<RecordList> = iterator_from_mgf_mmap(input, kind).collect();
+            assert_eq!(expected, v.unwrap());
+            let v: Result<RecordList> = iterator_from_mgf_mmap_strict(input, kind).collect();
+            assert!(v.is_err());
+            let v: Result<RecordList> = iterator_from_mgf_mmap_lenient(input, kind).collect();
+            assert_eq!(v.unwrap().len(), 0);
+        }
+    }
+
+    #[test]
+    fn windows_mgf_test() {
+        iterator_from_mgf_test_valid(MgfKind::FullMs, &windows_text(FULLMS_33450_MGF), vec![fullms_mgf_33450()]);
+        iterator_from_mgf_test_valid(MgfKind::MsConvert, &windows_text(MSCONVERT_33450_MGF), vec![mgf_33450()]);
+        iterator_from_mgf_test_valid(MgfKind::Pava, &windows_text(PAVA_33450_MGF), vec![mgf_33450()]);
+        iterator_from_mgf_test_valid(MgfKind::Pwiz, &windows_text(PWIZ_33450_MGF), vec![mgf_33450()]);
+        assert_eq!(count_scans_mgf(Cursor::new(windows_text(PAVA_33450_MGF)), MgfKind::Pava).unwrap(), 1);
+    }
+
+    #[test]
+    fn mgf_encoding_test() {
+        let title = b"TITLE=Scan 33450 (rt=8692.0) [QPvivo_2015_11_10_1targetmethod]";
+        let text = [&b"BEGIN IONS\nTITLE=Scan 33450 (rt=8692.0) [caf\xe9]"[..], &PAVA_33450_MGF[11 + title.len()..]].concat();
+
+        // strict UTF-8 fails the scan
+        let iter = iterator_from_mgf(Cursor::new(text.clone()), MgfKind::Pava);
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_mgf_lenient_with_options(Cursor::new(text.clone()), MgfKind::Pava, &ReaderOptions::default());
+        assert_eq!(iter.filter_map(Result::ok).count(), 0);
+
+        // lossy UTF-8
+        let iter = iterator_from_mgf_with_options(Cursor::new(text.clone()), MgfKind::Pava, &ReaderOptions::with_encoding(Encoding::Utf8Lossy));
+        let list = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].file, "caf\u{FFFD}");
+        assert_eq!(list[0].peaks, mgf_33450().peaks);
+
+        // latin1
+        let iter = iterator_from_mgf_strict_with_options(Cursor::new(text), MgfKind::Pava, &ReaderOptions::with_encoding(Encoding::Latin1));
+        let list = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].file, "caf\u{e9}");
+
+        // valid UTF-8 is unchanged
+        let iter = iterator_from_mgf_with_options(Cursor::new(PAVA_33450_MGF), MgfKind::Pava, &ReaderOptions::with_encoding(Encoding::Latin1));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap(), vec![mgf_33450()]);
+    }
+
+    #[test]
+    fn mgf_adduct_test() {
+        // unknown by default
+        let list = iterator_from_mgf(Cursor::new(PAVA_33450_MGF), MgfKind::Pava).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].adduct, None);
+        assert_eq!(list[0].parent_mass(), mgf_33450().parent_mass_with_adduct(Adduct::Proton));
+
+        let options = ReaderOptions::with_adduct(Adduct::Sodium);
+        let iter = iterator_from_mgf_with_options(Cursor::new(PAVA_33450_MGF), MgfKind::Pava, &options);
+        let list = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].adduct, Some(Adduct::Sodium));
+        assert_eq!(list[0].parent_mass(), mgf_33450().parent_mass_with_adduct(Adduct::Sodium));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_adduct_test() {
+        let options = ReaderOptions::with_adduct(Adduct::Sodium);
+        let iter = iterator_from_mgf_mmap_with_options(PAVA_33450_MGF, MgfKind::Pava, &options);
+        let list = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].adduct, Some(Adduct::Sodium));
+        assert_eq!(list[0].parent_mass(), mgf_33450().parent_mass_with_adduct(Adduct::Sodium));
+    }
+
+    #[test]
+    fn map_records_mgf_test() {
+        // Renumber scans while converting between formats.
+        let text = [PAVA_33450_MGF, PAVA_33450_MGF].concat();
+        let mut num = 0;
+        let iter = iterator_from_mgf(Cursor::new(&text[..]), MgfKind::Pava)
+            .map_records(|mut r| { num += 1; r.num = num; r });
+        let mut w = Cursor::new(vec![]);
+        value_iterator_to_mgf(&mut w, iter, MgfKind::MsConvert).unwrap();
+
+        let w = w.into_inner();
+        let r: RecordList = iterator_from_mgf(Cursor::new(&w[..]), MgfKind::MsConvert)
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(r.iter().map(|r| r.num).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(r[1].peaks, mgf_33450().peaks);
+    }
+
+    #[test]
+    fn mgf_trait_iter_test() {
+        let text = [PAVA_33450_MGF, PAVA_33450_MGF].concat();
+        let v: RecordList = Record::from_mgf_iter(Cursor::new(&text[..]), MgfKind::Pava)
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(v, vec![mgf_33450(), mgf_33450()]);
+
+        let mut w = Cursor::new(vec![]);
+        Record::to_mgf_iter(&mut w, v.iter(), MgfKind::Pava).unwrap();
+        let w = w.into_inner();
+        let r: RecordList = Record::from_mgf_iter(Cursor::new(&w[..]), MgfKind::Pava)
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(r, v);
+    }
+
+    #[test]
+    fn mgf_metadata_test() {
+        let v = vec![mgf_33450()];
+        let metadata = ExportMetadata::new("33450.raw").parameter("kind", "pava");
+        let mut w = Cursor::new(vec![]);
+        v.to_mgf_with_metadata(&mut w, MgfKind::Pava, &metadata).unwrap();
+        let text = w.into_inner();
+        assert!(text.starts_with(b"#bdb-metadata: 1\n#tool: bdb "));
+
+        // readers skip the metadata block
+        let list = RecordList::from_mgf_bytes(&text, MgfKind::Pava).unwrap();
+        assert_eq!(list, v);
+        assert_eq!(count_scans_mgf(Cursor::new(&text), MgfKind::Pava).unwrap(), 1);
+        #[cfg(feature = "mmap")] {
+            let list: RecordList = iterator_from_mgf_mmap_strict(&text, MgfKind::Pava).collect::<Result<RecordList>>().unwrap();
+            assert_eq!(list, v);
+        }
+    }
+
+    #[test]
+    fn ion_mobility_test() {
+        let mut record = mgf_33450();
+        record.ion_mobility = Some(1.0625);
+        record.ccs = Some(452.5);
+        record.peaks[0].mobility = Some(1.0625);
+        record.peaks[1].z = 2;
+        record.peaks[1].mobility = Some(0.875);
+
+        for &kind in [MgfKind::MsConvert, MgfKind::Pava, MgfKind::Pwiz].iter() {
+            let mut expected = record.clone();
+            if kind != MgfKind::Pava {
+                // Only the PAVA format stores the peak charge.
+                expected.peaks[1].z = 0;
+            }
+            let text = record.to_mgf_string(kind).unwrap();
+            assert!(text.contains("ION_MOBILITY=1.0625\nCCS=452.5\n"));
+            let actual = Record::from_mgf_string(&text, kind).unwrap();
+            assert_eq!(actual, expected);
+            assert!(actual.extra.is_empty());
+        }
+
+        let mut record = fullms_mgf_33450();
+        record.ion_mobility = Some(1.0625);
+        record.peaks[0].mobility = Some(1.0625);
+        let text = record.to_mgf_string(MgfKind::FullMs).unwrap();
+        assert!(text.contains("ION_MOBILITY: 1.0625\n"));
+        assert_eq!(Record::from_mgf_string(&text, MgfKind::FullMs).unwrap(), record);
+
+        // invalid mobility values are errors
+        let text = String::from_utf8(MSCONVERT_33450_MGF.to_vec()).unwrap().replace("END IONS", "ION_MOBILITY=a\nEND IONS");
+        assert!(Record::from_mgf_string(&text, MgfKind::MsConvert).is_err());
     }
 
     // FULLMS
 
     #[test]
     fn iterator_to_fullms_mgf_test() {
-        iterator_to_mgf_test(MgfKind::FullMs, FULLMS_33450_MGF)
+        iterator_to_mgf_test(MgfKind::FullMs, fullms_mgf_33450(), FULLMS_33450_MGF)
     }
 
     #[test]
@@ -508,7 +1108,7 @@ mod tests {
 
     #[test]
     fn iterator_to_msconvert_mgf_test() {
-        iterator_to_mgf_test(MgfKind::MsConvert, MSCONVERT_33450_MGF)
+        iterator_to_mgf_test(MgfKind::MsConvert, mgf_33450(), MSCONVERT_33450_MGF)
     }
 
     #[test]
@@ -521,7 +1121,7 @@ mod tests {
 
     #[test]
     fn iterator_to_pava_mgf_test() {
-        iterator_to_mgf_test(MgfKind::Pava, PAVA_33450_MGF)
+        iterator_to_mgf_test(MgfKind::Pava, mgf_33450(), PAVA_33450_MGF)
     }
 
     #[test]
@@ -530,11 +1130,21 @@ mod tests {
         iterator_from_mgf_test_invalid(MgfKind::Pava, PAVA_EMPTY_MGF, vec![mgf_empty()]);
     }
 
+    #[test]
+    fn pava_mgf_peak_charge_test() {
+        let mut r = mgf_33450();
+        r.peaks[0].z = 2;
+        let mut w = vec![];
+        reference_iterator_to_mgf(&mut w, Some(&r).into_iter(), MgfKind::Pava).unwrap();
+        let v: Vec<Record> = iterator_from_mgf(&w[..], MgfKind::Pava).map(|r| r.unwrap()).collect();
+        assert_eq!(v, vec![r]);
+    }
+
     // PWIZ
 
     #[test]
     fn iterator_to_pwiz_mgf_test() {
-        iterator_to_mgf_test(MgfKind::Pwiz, PWIZ_33450_MGF)
+        iterator_to_mgf_test(MgfKind::Pwiz, mgf_33450(), PWIZ_33450_MGF)
     }
 
     #[test]
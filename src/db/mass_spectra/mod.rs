@@ -1,31 +1,74 @@
 //! Mass spectrum integrations.
 
-// Expose the low-level API in a public submodule.
-pub mod low_level;
-
 pub(crate) mod complete;
 pub(crate) mod peak;
 pub(crate) mod peak_list;
-pub(crate) mod re;
 pub(crate) mod record;
 pub(crate) mod record_list;
 pub(crate) mod valid;
 
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        // Expose the low-level API in a public submodule.
+        pub mod low_level;
+
+        // Expose the run-level QC metrics in a public submodule.
+        pub mod qc;
+
+        pub(crate) mod binning;
+        pub(crate) mod cv;
+        pub(crate) mod deconvolution;
+        pub(crate) mod dia;
+        pub(crate) mod noise;
+        pub(crate) mod precursor;
+        pub(crate) mod re;
+        pub(crate) mod similarity;
+        pub(crate) mod transform;
+        pub(crate) mod xic;
+    }
+}
+
 cfg_if! {
     if #[cfg(feature = "mgf")] {
         pub(crate) mod mgf;
         pub(crate) mod fullms_mgf;
         pub(crate) mod msconvert_mgf;
+        pub(crate) mod multi_mgf;
         pub(crate) mod pava_mgf;
         pub(crate) mod pwiz_mgf;
     }
 }
 
+#[cfg(feature = "columnar")]
+pub(crate) mod columnar;
+
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
+
+#[cfg(feature = "obo")]
+pub(crate) mod obo;
+
 #[cfg(test)]
 pub(crate) mod test;
 
 // Re-export the models into the parent module.
 pub use self::peak::Peak;
-pub use self::peak_list::PeakList;
+pub use self::peak_list::{PeakList, PeakListExt};
 pub use self::record::Record;
 pub use self::record_list::RecordList;
+pub use self::valid::{ValidationIssue, ValidationReport};
+#[cfg(feature = "mgf")]
+pub use self::multi_mgf::ScanNumbering;
+
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        pub use self::binning::{BinOptions, Normalization};
+        pub use self::cv::{ControlledVocabulary, CvTerm};
+        pub use self::deconvolution::{DeconvolutionOptions, NeutralMass, NeutralMassList};
+        pub use self::dia::{IsolationWindow, WindowScheme};
+        pub use self::noise::NoiseEstimate;
+        pub use self::precursor::PrecursorOptions;
+        pub use self::similarity::ClusterOptions;
+        pub use self::transform::{SpectraIterExt, Transform};
+    }
+}
@@ -6,8 +6,9 @@ use std::iter::Peekable;
 
 use traits::*;
 use util::*;
-use super::mgf::MgfRecordIter;
+use super::mgf::{estimate_extra_size, estimate_mobility_size, export_extra, export_mobility, export_peak_mobility, is_header_line, next_header_line, parse_extra_line, parse_mobility_line, parse_peak_mobility, MgfRecordIter};
 use super::peak::Peak;
+use super::peak_list::PeakListExt;
 use super::re::*;
 use super::record::Record;
 
@@ -24,6 +25,8 @@ pub(crate) fn estimate_msconvert_mgf_record_size(record: &Record) -> usize {
     MGF_VOCABULARY_SIZE +
         record.file.len() +
         record.file.len() +
+        estimate_extra_size(record) +
+        estimate_mobility_size(record) +
         MGF_PEAK_SIZE * record.peaks.len()
 }
 
@@ -105,7 +108,9 @@ fn export_spectra<T: Write>(writer: &mut T, record: &Record)
     for peak in record.peaks.iter() {
         let mz = to_bytes(&peak.mz)?;
         let intensity = to_bytes(&peak.intensity)?;
-        write_alls!(writer, mz.as_slice(), b" ", intensity.as_slice(), b"\n")?;
+        write_alls!(writer, mz.as_slice(), b" ", intensity.as_slice())?;
+        export_peak_mobility(writer, peak, b" ")?;
+        writer.write_all(b"\n")?;
     }
 
     Ok(())
@@ -120,6 +125,8 @@ pub(crate) fn record_to_msconvert_mgf<T: Write>(writer: &mut T, record: &Record)
     export_rt(writer, record)?;
     export_pepmass(writer, record)?;
     export_charge(writer, record)?;
+    export_mobility(writer, record, b"=")?;
+    export_extra(writer, record, b"=")?;
     export_spectra(writer, record)?;
     writer.write_all(b"END IONS\n")?;
 
@@ -130,7 +137,7 @@ pub(crate) fn record_to_msconvert_mgf<T: Write>(writer: &mut T, record: &Record)
 
 #[inline(always)]
 fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
-    -> Result<TextWriterState<T>>
+    -> Result<TextWriterState<'_, T>>
 {
     Ok(TextWriterState::new(writer, delimiter))
 }
@@ -237,7 +244,7 @@ fn parse_title_line<T: BufRead>(lines: &mut PeakableLines<T>, record: &mut Recor
     type Title = MsConvertMgfTitleRegex;
 
     // Verify and parse the title line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["TITLE="], "=")?;
     let captures = none_to_error!(Title::extract().captures(&line), InvalidInput);
     record.file = capture_as_string(&captures, Title::FILE_INDEX);
 
@@ -255,7 +262,7 @@ fn parse_rt_line<T: BufRead>(lines: &mut PeakableLines<T>, record: &mut Record)
     type Rt = MsConvertMgfRtRegex;
 
     // Verify and parse the RT line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["RTINSECONDS="], "=")?;
     let captures = none_to_error!(Rt::extract().captures(&line), InvalidInput);
 
     let rt = capture_as_str(&captures, Rt::RT_INDEX);
@@ -272,7 +279,7 @@ fn parse_pepmass_line<T: BufRead>(lines: &mut PeakableLines<T>, record: &mut Rec
     type PepMass = MsConvertMgfPepMassRegex;
 
     // Verify and parse the pepmass line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["PEPMASS="], "=")?;
     let captures = none_to_error!(PepMass::extract().captures(&line), InvalidInput);
 
     let mz = capture_as_str(&captures, PepMass::PARENT_MZ_INDEX);
@@ -291,6 +298,21 @@ fn parse_charge_line<T: BufRead>(lines: &mut PeakableLines<T>, record: &mut Reco
 {
     type Charge = MsConvertMgfChargeRegex;
 
+    // Store the header lines before the optional charge line.
+    loop {
+        let is_header = match lines.peek() {
+            Some(&Ok(ref v)) => !v.starts_with("CHARGE=") && is_header_line(v, "="),
+            _                => false,
+        };
+        if !is_header {
+            break;
+        }
+        let line = lines.next().unwrap()?;
+        if !parse_mobility_line(&line, record, "=")? {
+            parse_extra_line(&line, record, "=");
+        }
+    }
+
     // Check if the following line is an error or a charge line.
     let is_err: bool;
     let is_charge: bool;
@@ -342,17 +364,25 @@ fn parse_spectra<T: BufRead>(lines: &mut PeakableLines<T>, record: &mut Record)
         if line == "END IONS" {
             break;
         }
+        if parse_mobility_line(&line, record, "=")? {
+            continue;
+        }
+        if parse_extra_line(&line, record, "=") {
+            continue;
+        }
 
         // Parse the line data
         let mut items = line.split(' ');
         let mz = none_to_error!(items.next(), InvalidInput);
         let intensity = none_to_error!(items.next(), InvalidInput);
+        let mobility = parse_peak_mobility(items.next())?;
         bool_to_error!(items.next().is_none(), InvalidInput);
 
         record.peaks.push(Peak {
             mz: from_string(mz)?,
             intensity: from_string(intensity)?,
             z: 0,
+            mobility: mobility,
         });
     }
 
@@ -373,6 +403,10 @@ pub(crate) fn record_from_msconvert_mgf<T: BufRead>(reader: &mut T)
     parse_charge_line(&mut lines, &mut record)?;
     parse_spectra(&mut lines, &mut record)?;
 
+    // Guarantee the sorted invariant for the peak list.
+    if !record.peaks.is_sorted_by_mz() {
+        record.peaks.sort_by_mz();
+    }
     record.peaks.shrink_to_fit();
     Ok(record)
 }
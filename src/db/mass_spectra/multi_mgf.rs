@@ -0,0 +1,266 @@
+//! Helper utilities to load MGF spectra from multiple documents.
+//!
+//! Chains MGF readers over many documents, such as the fractions of a
+//! fractionated experiment, so they may be processed as a single
+//! stream. Each record is tagged with the document it was read from,
+//! and scan numbers may be renumbered to be unique across documents.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::prelude::*;
+use std::iter::Map;
+use std::path::Path;
+use std::vec::IntoIter;
+
+use traits::*;
+use util::*;
+use super::mgf::{iterator_from_mgf, MgfRecordIter};
+use super::record::Record;
+
+/// Scan renumbering when merging spectra from multiple documents.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScanNumbering {
+    /// Keep the scan numbers from each document.
+    Original,
+    /// Offset scan numbers by the highest scan number of the previous documents.
+    ///
+    /// Parent and children scan numbers are offset identically, so
+    /// links between scans in a document are preserved.
+    Offset,
+}
+
+// SOURCES
+
+/// Named document to read MGF spectra from.
+pub type MgfSource<T> = (String, T);
+
+/// Lazily opened MGF documents from file paths.
+pub type MgfFileSources<P> = Map<IntoIter<P>, fn(P) -> Result<MgfSource<BufReader<File>>>>;
+
+/// Open a file for reading, named by the file stem.
+fn open_source<P: AsRef<Path>>(path: P)
+    -> Result<MgfSource<BufReader<File>>>
+{
+    let path = path.as_ref();
+    let name = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned());
+    let file = File::open(path)?;
+    Ok((name, BufReader::new(file)))
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from multiple documents.
+///
+/// Reads each document in turn, setting the file of each record to
+/// the name of the document.
+pub struct MultiMgfRecordIter<T: BufRead, I: Iterator<Item = Result<MgfSource<T>>>> {
+    sources: I,
+    current: Option<(String, MgfRecordIter<T>)>,
+    kind: MgfKind,
+    numbering: ScanNumbering,
+    offset: u32,
+    max_num: u32,
+}
+
+impl<T, I> MultiMgfRecordIter<T, I>
+    where T: BufRead,
+          I: Iterator<Item = Result<MgfSource<T>>>
+{
+    /// Create new MultiMgfRecordIter from named documents.
+    #[inline]
+    pub fn new(sources: I, kind: MgfKind, numbering: ScanNumbering) -> Self {
+        MultiMgfRecordIter {
+            sources: sources,
+            current: None,
+            kind: kind,
+            numbering: numbering,
+            offset: 0,
+            max_num: 0,
+        }
+    }
+
+    /// Tag and renumber a record from the current document.
+    fn process(&mut self, mut record: Record, name: &str) -> Record {
+        record.file = String::from(name);
+        if self.numbering == ScanNumbering::Offset {
+            let offset = self.offset;
+            record.num = record.num.saturating_add(offset);
+            for num in record.parent.iter_mut().chain(record.children.iter_mut()) {
+                *num = num.saturating_add(offset);
+            }
+        }
+        self.max_num = self.max_num.max(record.num);
+        record
+    }
+}
+
+impl<T, I> Iterator for MultiMgfRecordIter<T, I>
+    where T: BufRead,
+          I: Iterator<Item = Result<MgfSource<T>>>
+{
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let next = match self.current {
+                Some((ref name, ref mut iter)) => iter.next().map(|r| (name.clone(), r)),
+                None                           => None,
+            };
+
+            match next {
+                Some((_, Err(e)))         => return Some(Err(e)),
+                Some((name, Ok(record)))  => return Some(Ok(self.process(record, &name))),
+                None                      => (),
+            }
+
+            // Current document is exhausted, start the next document.
+            self.offset = self.max_num;
+            match self.sources.next()? {
+                Err(e)             => {
+                    self.current = None;
+                    return Some(Err(e));
+                },
+                Ok((name, reader)) => {
+                    self.current = Some((name, iterator_from_mgf(reader, self.kind)));
+                },
+            }
+        }
+    }
+}
+
+/// Create default record iterator from named readers.
+#[inline(always)]
+pub fn iterator_from_mgf_sources<T, I>(sources: I, kind: MgfKind, numbering: ScanNumbering)
+    -> MultiMgfRecordIter<T, I>
+    where T: BufRead,
+          I: Iterator<Item = Result<MgfSource<T>>>
+{
+    MultiMgfRecordIter::new(sources, kind, numbering)
+}
+
+/// Create default record iterator from file paths.
+///
+/// Files are opened lazily, and each record is tagged with the file stem.
+#[inline]
+pub fn iterator_from_mgf_files<P: AsRef<Path>>(paths: Vec<P>, kind: MgfKind, numbering: ScanNumbering)
+    -> MultiMgfRecordIter<BufReader<File>, MgfFileSources<P>>
+{
+    let open: fn(P) -> Result<MgfSource<BufReader<File>>> = open_source::<P>;
+    iterator_from_mgf_sources(paths.into_iter().map(open), kind, numbering)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from multiple documents.
+///
+/// Wraps `MultiMgfRecordIter` and converts the text to records strictly.
+pub type MultiMgfRecordStrictIter<T, I> = StrictIter<Record, MultiMgfRecordIter<T, I>>;
+
+/// Create strict record iterator from named readers.
+#[inline(always)]
+pub fn iterator_from_mgf_sources_strict<T, I>(sources: I, kind: MgfKind, numbering: ScanNumbering)
+    -> MultiMgfRecordStrictIter<T, I>
+    where T: BufRead,
+          I: Iterator<Item = Result<MgfSource<T>>>
+{
+    MultiMgfRecordStrictIter::new(iterator_from_mgf_sources(sources, kind, numbering))
+}
+
+/// Create strict record iterator from file paths.
+#[inline(always)]
+pub fn iterator_from_mgf_files_strict<P: AsRef<Path>>(paths: Vec<P>, kind: MgfKind, numbering: ScanNumbering)
+    -> MultiMgfRecordStrictIter<BufReader<File>, MgfFileSources<P>>
+{
+    MultiMgfRecordStrictIter::new(iterator_from_mgf_files(paths, kind, numbering))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from multiple documents.
+///
+/// Wraps `MultiMgfRecordIter` and converts the text to records leniently.
+pub type MultiMgfRecordLenientIter<T, I> = LenientIter<Record, MultiMgfRecordIter<T, I>>;
+
+/// Create lenient record iterator from named readers.
+#[inline(always)]
+pub fn iterator_from_mgf_sources_lenient<T, I>(sources: I, kind: MgfKind, numbering: ScanNumbering)
+    -> MultiMgfRecordLenientIter<T, I>
+    where T: BufRead,
+          I: Iterator<Item = Result<MgfSource<T>>>
+{
+    MultiMgfRecordLenientIter::new(iterator_from_mgf_sources(sources, kind, numbering))
+}
+
+/// Create lenient record iterator from file paths.
+#[inline(always)]
+pub fn iterator_from_mgf_files_lenient<P: AsRef<Path>>(paths: Vec<P>, kind: MgfKind, numbering: ScanNumbering)
+    -> MultiMgfRecordLenientIter<BufReader<File>, MgfFileSources<P>>
+{
+    MultiMgfRecordLenientIter::new(iterator_from_mgf_files(paths, kind, numbering))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use super::super::record_list::RecordList;
+    use super::super::test::*;
+
+    fn sources(texts: Vec<&[u8]>) -> Vec<Result<MgfSource<Cursor<&[u8]>>>> {
+        texts.into_iter()
+            .enumerate()
+            .map(|(i, text)| Ok((format!("fraction{}", i + 1), Cursor::new(text))))
+            .collect()
+    }
+
+    #[test]
+    fn iterator_from_mgf_sources_test() {
+        let text = [PAVA_EMPTY_MGF, PAVA_EMPTY_MGF].concat();
+        let texts = vec![&text[..], PAVA_EMPTY_MGF];
+
+        // Original scan numbers.
+        let iter = iterator_from_mgf_sources(sources(texts.clone()).into_iter(), MgfKind::Pava, ScanNumbering::Original);
+        let v: RecordList = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v.iter().map(|r| r.num).collect::<Vec<_>>(), vec![33450, 33450, 33450]);
+        assert_eq!(v.iter().map(|r| r.file.as_str()).collect::<Vec<_>>(), vec!["fraction1", "fraction1", "fraction2"]);
+
+        // Offset scan numbers.
+        let iter = iterator_from_mgf_sources(sources(texts.clone()).into_iter(), MgfKind::Pava, ScanNumbering::Offset);
+        let v: RecordList = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v.iter().map(|r| r.num).collect::<Vec<_>>(), vec![33450, 33450, 66900]);
+        let mut x = mgf_empty();
+        x.file = String::from("fraction2");
+        x.num = 66900;
+        assert_eq!(v[2], x);
+
+        // No documents.
+        let iter = iterator_from_mgf_sources(sources(vec![]).into_iter(), MgfKind::Pava, ScanNumbering::Offset);
+        assert_eq!(iter.count(), 0);
+    }
+
+    #[test]
+    fn iterator_from_mgf_sources_error_test() {
+        // Unreadable document.
+        let mut s = sources(vec![PAVA_EMPTY_MGF, PAVA_EMPTY_MGF]);
+        s.insert(1, Err(From::from(ErrorKind::InvalidInput)));
+        let iter = iterator_from_mgf_sources(s.into_iter(), MgfKind::Pava, ScanNumbering::Original);
+        let v: Vec<Result<Record>> = iter.collect();
+        assert_eq!(v.len(), 3);
+        assert!(v[0].is_ok() && v[1].is_err() && v[2].is_ok());
+
+        // Invalid records.
+        let s = sources(vec![PAVA_33450_MGF, PAVA_EMPTY_MGF]);
+        let iter = iterator_from_mgf_sources_strict(s.into_iter(), MgfKind::Pava, ScanNumbering::Offset);
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let s = sources(vec![PAVA_33450_MGF, PAVA_EMPTY_MGF]);
+        let iter = iterator_from_mgf_sources_lenient(s.into_iter(), MgfKind::Pava, ScanNumbering::Offset);
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap().len(), 1);
+
+        // Missing files.
+        let iter = iterator_from_mgf_files(vec!["/nonexistent/fraction1.mgf"], MgfKind::Pava, ScanNumbering::Offset);
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+    }
+}
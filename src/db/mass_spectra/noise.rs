@@ -0,0 +1,216 @@
+//! Noise estimation and signal-to-noise ratios for spectra.
+//!
+//! Noise is estimated from the median absolute deviation (MAD) of the
+//! peak intensities, which is robust to the few intense signal peaks
+//! within a spectrum dominated by noise peaks.
+
+#[cfg(feature = "csv")]
+use std::io::prelude::*;
+
+#[cfg(feature = "csv")]
+use util::*;
+use super::peak::Peak;
+use super::record::Record;
+
+/// Scale factor from the MAD to the standard deviation of a normal distribution.
+const MAD_SCALE: f64 = 1.4826;
+
+/// Robust noise estimate for the intensities of a spectrum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseEstimate {
+    /// Median peak intensity, used as the baseline.
+    pub median: f64,
+    /// Median absolute deviation of the peak intensities from the median.
+    pub mad: f64,
+}
+
+impl NoiseEstimate {
+    /// Estimate the noise from a peak list, or `None` if the list is empty.
+    pub fn new(peaks: &[Peak]) -> Option<Self> {
+        if peaks.is_empty() {
+            return None;
+        }
+
+        let mut intensities: Vec<f64> = peaks.iter().map(|p| p.intensity).collect();
+        let baseline = median(&mut intensities);
+        for intensity in intensities.iter_mut() {
+            *intensity = (*intensity - baseline).abs();
+        }
+        let mad = median(&mut intensities);
+
+        Some(NoiseEstimate { median: baseline, mad: mad })
+    }
+
+    /// Get the noise level, the standard deviation estimated from the MAD.
+    #[inline]
+    pub fn noise(&self) -> f64 {
+        MAD_SCALE * self.mad
+    }
+
+    /// Calculate the signal-to-noise ratio of an intensity.
+    ///
+    /// The signal is the intensity above the median baseline. If the
+    /// noise level is 0, any signal above the baseline is infinite.
+    #[inline]
+    pub fn signal_to_noise(&self, intensity: f64) -> f64 {
+        let signal = intensity - self.median;
+        let noise = self.noise();
+        if noise > 0.0 {
+            signal / noise
+        } else if signal > 0.0 {
+            ::std::f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Calculate the median of values in-place, reordering the values.
+///
+/// NaN values sort last, using the IEEE total order.
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|x, y| x.total_cmp(y));
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+impl Record {
+    /// Estimate the noise of the spectrum, or `None` if it has no peaks.
+    #[inline]
+    pub fn noise(&self) -> Option<NoiseEstimate> {
+        NoiseEstimate::new(&self.peaks)
+    }
+
+    /// Calculate the signal-to-noise ratio of each peak, in peak order.
+    pub fn signal_to_noise(&self) -> Vec<f64> {
+        match self.noise() {
+            None        => vec![],
+            Some(noise) => self.peaks.iter().map(|p| noise.signal_to_noise(p.intensity)).collect(),
+        }
+    }
+
+    /// Remove peaks with a signal-to-noise ratio below `min_sn`.
+    ///
+    /// The noise is estimated once, from all peaks, before any peaks
+    /// are removed.
+    pub fn retain_signal_to_noise(&mut self, min_sn: f64) {
+        if let Some(noise) = self.noise() {
+            self.peaks.retain(|p| noise.signal_to_noise(p.intensity) >= min_sn);
+        }
+    }
+}
+
+// WRITER
+
+/// Export per-scan noise estimates to CSV, with one row per scan.
+///
+/// Columns are the scan number, peak count, median intensity, MAD,
+/// noise level and the maximum signal-to-noise ratio. Scans without
+/// peaks have empty noise columns.
+#[cfg(feature = "csv")]
+pub fn reference_iterator_to_noise_csv<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    writer.write_all(b"num,peaks,median,mad,noise,max_sn\n")?;
+    for record in iter {
+        let num = to_bytes(&record.num)?;
+        let peaks = to_bytes(&record.peaks.len())?;
+        write_alls!(writer, num.as_slice(), b",", peaks.as_slice())?;
+        if let Some(noise) = record.noise() {
+            let max_sn = record.peaks.iter()
+                .map(|p| noise.signal_to_noise(p.intensity))
+                .fold(::std::f64::NEG_INFINITY, f64::max);
+            for value in [noise.median, noise.mad, noise.noise(), max_sn].iter() {
+                let value = to_bytes(value)?;
+                write_alls!(writer, b",", value.as_slice())?;
+            }
+        } else {
+            writer.write_all(b",,,,")?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    fn peaks(intensities: &[f64]) -> Vec<Peak> {
+        intensities.iter()
+            .enumerate()
+            .map(|(i, &intensity)| Peak { mz: 100.0 + i as f64, intensity: intensity, z: 0, mobility: None })
+            .collect()
+    }
+
+    #[test]
+    fn noise_estimate_test() {
+        let noise = NoiseEstimate::new(&peaks(&[1.0, 2.0, 3.0, 4.0, 100.0])).unwrap();
+        assert_eq!(noise.median, 3.0);
+        assert_eq!(noise.mad, 1.0);
+        assert_approx_eq!(noise.noise(), 1.4826);
+        assert_approx_eq!(noise.signal_to_noise(100.0), 97.0 / 1.4826);
+        assert_approx_eq!(noise.signal_to_noise(3.0), 0.0);
+
+        let noise = NoiseEstimate::new(&peaks(&[1.0, 2.0, 4.0, 8.0])).unwrap();
+        assert_eq!(noise.median, 3.0);
+        assert_eq!(noise.mad, 1.5);
+
+        // NaN intensities sort last
+        let noise = NoiseEstimate::new(&peaks(&[::std::f64::NAN, 1.0, 2.0, 3.0, 100.0])).unwrap();
+        assert_eq!(noise.median, 3.0);
+        assert_eq!(noise.mad, 2.0);
+
+        assert_eq!(NoiseEstimate::new(&[]), None);
+    }
+
+    #[test]
+    fn zero_noise_test() {
+        let noise = NoiseEstimate::new(&peaks(&[5.0, 5.0, 5.0, 20.0])).unwrap();
+        assert_eq!(noise.mad, 0.0);
+        assert_eq!(noise.signal_to_noise(20.0), ::std::f64::INFINITY);
+        assert_eq!(noise.signal_to_noise(5.0), 0.0);
+    }
+
+    #[test]
+    fn record_signal_to_noise_test() {
+        let mut r = mgf_33450();
+        let sn = r.signal_to_noise();
+        assert_eq!(sn.len(), r.peaks.len());
+        let count = sn.iter().filter(|&&x| x >= 3.0).count();
+        assert!(count > 0 && count < r.peaks.len());
+
+        r.retain_signal_to_noise(3.0);
+        assert_eq!(r.peaks.len(), count);
+
+        let mut r = mgf_empty();
+        assert!(r.signal_to_noise().is_empty());
+        r.retain_signal_to_noise(3.0);
+        assert!(r.peaks.is_empty());
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn noise_csv_test() {
+        let mut r = mgf_empty();
+        r.peaks = peaks(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let mut w = vec![];
+        reference_iterator_to_noise_csv(&mut w, [r, mgf_empty()].iter()).unwrap();
+        let text = String::from_utf8(w).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "num,peaks,median,mad,noise,max_sn");
+        assert!(lines[1].starts_with("33450,5,3.0,1.0,1.4826,1.34"));
+        assert_eq!(lines[2], "33450,0,,,,");
+    }
+}
@@ -0,0 +1,236 @@
+//! Utilities to load controlled vocabularies from OBO files.
+//!
+//! Only `[Term]` stanzas are parsed, and only the tags required to
+//! name and validate cvParam accessions (`id`, `name`, `def`, `is_a`,
+//! `is_obsolete`) are kept. All other tags and stanzas are ignored.
+
+use std::io::prelude::*;
+use std::io::{BufReader, Lines};
+
+use util::*;
+use super::cv::{ControlledVocabulary, CvTerm};
+
+// OBO ITERATOR
+
+/// Iterator to parse individual `[Term]` stanzas from an OBO document.
+pub struct OboTermIter<T: BufRead> {
+    lines: Lines<T>,
+    term: Option<CvTerm>,
+}
+
+impl<T: BufRead> OboTermIter<T> {
+    /// Create new OboTermIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        OboTermIter {
+            lines: reader.lines(),
+            term: None,
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for OboTermIter<T> {
+    type Item = Result<CvTerm>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next() {
+                None           => return self.term.take().map(finish_term),
+                Some(Err(e))   => return Some(Err(From::from(e))),
+                Some(Ok(line)) => line,
+            };
+
+            let line = line.trim();
+            if line.starts_with('[') {
+                // New stanza, yield the previous term if present.
+                let previous = self.term.take();
+                if line == "[Term]" {
+                    self.term = Some(CvTerm::new());
+                }
+                if let Some(term) = previous {
+                    return Some(finish_term(term));
+                }
+            } else if let Some(ref mut term) = self.term {
+                if let Err(e) = parse_tag_line(line, term) {
+                    self.term = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+// READER
+
+/// Strip a trailing OBO comment ("! ...") from a tag value.
+#[inline]
+fn strip_comment(value: &str) -> &str {
+    match value.find(" !") {
+        Some(index) => value[..index].trim_end(),
+        None        => value,
+    }
+}
+
+/// Strip trailing OBO modifiers ("{...}") from a tag value.
+#[inline]
+fn strip_modifiers(value: &str) -> &str {
+    match value.find(" {") {
+        Some(index) => value[..index].trim_end(),
+        None        => value,
+    }
+}
+
+/// Parse the quoted string from a `def` tag, removing escapes.
+fn parse_quoted(value: &str) -> Result<String> {
+    let mut chars = value.chars();
+    bool_to_error!(chars.next() == Some('"'), InvalidInput);
+
+    let mut text = String::with_capacity(value.len());
+    loop {
+        match none_to_error!(chars.next(), InvalidInput) {
+            '"'  => return Ok(text),
+            '\\' => match none_to_error!(chars.next(), InvalidInput) {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                c   => text.push(c),
+            },
+            c    => text.push(c),
+        }
+    }
+}
+
+/// Parse a single tag-value line within a `[Term]` stanza.
+fn parse_tag_line(line: &str, term: &mut CvTerm) -> Result<()> {
+    if line.is_empty() || line.starts_with('!') {
+        return Ok(());
+    }
+
+    let index = none_to_error!(line.find(':'), InvalidInput);
+    let tag = &line[..index];
+    let value = line[index+1..].trim();
+    match tag {
+        "id"            => term.id = String::from(strip_modifiers(strip_comment(value))),
+        "name"          => term.name = String::from(strip_comment(value)),
+        "def"           => term.def = parse_quoted(value)?,
+        "is_a"          => term.is_a.push(String::from(strip_modifiers(strip_comment(value)))),
+        "is_obsolete"   => term.is_obsolete = strip_comment(value) == "true",
+        _               => (),
+    }
+
+    Ok(())
+}
+
+/// Validate a parsed term, requiring an accession.
+#[inline]
+fn finish_term(term: CvTerm) -> Result<CvTerm> {
+    bool_to_error!(!term.id.is_empty(), InvalidInput);
+    Ok(term)
+}
+
+/// Create term iterator from reader.
+#[inline(always)]
+pub fn iterator_from_obo<T: BufRead>(reader: T) -> OboTermIter<T> {
+    OboTermIter::new(reader)
+}
+
+/// Import controlled vocabulary from OBO.
+pub fn vocabulary_from_obo<T: BufRead>(reader: T) -> Result<ControlledVocabulary> {
+    let mut vocabulary = ControlledVocabulary::new();
+    for term in iterator_from_obo(reader) {
+        vocabulary.insert(term?);
+    }
+
+    Ok(vocabulary)
+}
+
+// PSI-MS
+
+/// Subset of the PSI-MS vocabulary, bundled with the crate.
+const PSI_MS_OBO: &'static str = include_str!("psi-ms.obo");
+
+lazy_static! {
+    static ref PSI_MS: ControlledVocabulary = {
+        let reader = BufReader::new(PSI_MS_OBO.as_bytes());
+        vocabulary_from_obo(reader).expect("Bundled PSI-MS vocabulary is invalid.")
+    };
+}
+
+/// Get the bundled PSI-MS controlled vocabulary.
+///
+/// The bundled table only contains the terms used by spectral formats
+/// in this crate. Load the full `psi-ms.obo` through
+/// `vocabulary_from_obo` for complete coverage.
+#[inline]
+pub fn psi_ms() -> &'static ControlledVocabulary {
+    &PSI_MS
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+
+    const OBO: &'static str = "format-version: 1.2\n\
+        ontology: ms\n\
+        \n\
+        [Term]\n\
+        id: MS:1000511\n\
+        name: ms level\n\
+        def: \"Stage \\\"number\\\" achieved.\" [PSI:MS]\n\
+        is_a: MS:1000499 ! spectrum attribute\n\
+        \n\
+        [Typedef]\n\
+        id: part_of\n\
+        name: part_of\n\
+        \n\
+        [Term]\n\
+        id: MS:1000001\n\
+        name: sample number\n\
+        is_obsolete: true\n";
+
+    #[test]
+    fn iterator_from_obo_test() {
+        let terms: Vec<CvTerm> = iterator_from_obo(Cursor::new(OBO))
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(terms.len(), 2);
+
+        assert_eq!(terms[0].id, "MS:1000511");
+        assert_eq!(terms[0].name, "ms level");
+        assert_eq!(terms[0].def, "Stage \"number\" achieved.");
+        assert_eq!(terms[0].is_a, vec![String::from("MS:1000499")]);
+        assert!(!terms[0].is_obsolete);
+
+        assert_eq!(terms[1].id, "MS:1000001");
+        assert!(terms[1].def.is_empty());
+        assert!(terms[1].is_obsolete);
+    }
+
+    #[test]
+    fn invalid_obo_test() {
+        // Missing accession.
+        let text = "[Term]\nname: ms level\n";
+        assert!(vocabulary_from_obo(Cursor::new(text)).is_err());
+
+        // Unterminated definition.
+        let text = "[Term]\nid: MS:1000511\ndef: \"Stage number\n";
+        assert!(vocabulary_from_obo(Cursor::new(text)).is_err());
+
+        // Missing tag separator.
+        let text = "[Term]\nid MS:1000511\n";
+        assert!(vocabulary_from_obo(Cursor::new(text)).is_err());
+    }
+
+    #[test]
+    fn psi_ms_test() {
+        let cv = psi_ms();
+        assert!(!cv.is_empty());
+        assert!(cv.is_valid_param("MS:1000511", "ms level"));
+        assert!(cv.is_valid_param("MS:1000285", "total ion current"));
+        assert!(cv.is_a("MS:1000523", "MS:1000518"));
+        assert!(!cv.contains("part_of"));
+    }
+}
@@ -5,8 +5,9 @@ use std::io::Lines;
 
 use traits::*;
 use util::*;
-use super::mgf::MgfRecordIter;
+use super::mgf::{estimate_extra_size, estimate_mobility_size, export_extra, export_mobility, next_header_line, parse_extra_line, parse_mobility_line, MgfRecordIter};
 use super::peak::Peak;
+use super::peak_list::PeakListExt;
 use super::re::*;
 use super::record::Record;
 
@@ -22,6 +23,8 @@ pub(crate) fn estimate_pava_mgf_record_size(record: &Record) -> usize {
     const MGF_PEAK_SIZE: usize = 25;
     MGF_VOCABULARY_SIZE +
         record.file.len() +
+        estimate_extra_size(record) +
+        estimate_mobility_size(record) +
         MGF_PEAK_SIZE * record.peaks.len()
 }
 
@@ -89,7 +92,18 @@ fn export_spectra<T: Write>(writer: &mut T, record: &Record)
     for peak in record.peaks.iter() {
         let mz = to_bytes(&peak.mz)?;
         let intensity = to_bytes(&peak.intensity)?;
-        write_alls!(writer, mz.as_slice(), b"\t", intensity.as_slice(), b"\n")?;
+        if let Some(mobility) = peak.mobility {
+            // Export the peak charge and mobility, to disambiguate the columns.
+            let z = to_bytes(&peak.z)?;
+            let mobility = to_bytes(&mobility)?;
+            write_alls!(writer, mz.as_slice(), b"\t", z.as_slice(), b"\t", intensity.as_slice(), b"\t", mobility.as_slice(), b"\n")?;
+        } else if peak.z == 0 {
+            write_alls!(writer, mz.as_slice(), b"\t", intensity.as_slice(), b"\n")?;
+        } else {
+            // Export the peak charge, between the m/z and intensity.
+            let z = to_bytes(&peak.z)?;
+            write_alls!(writer, mz.as_slice(), b"\t", z.as_slice(), b"\t", intensity.as_slice(), b"\n")?;
+        }
     }
 
     Ok(())
@@ -103,6 +117,8 @@ pub(crate) fn record_to_pava_mgf<T: Write>(writer: &mut T, record: &Record)
     export_title(writer, record)?;
     export_pepmass(writer, record)?;
     export_charge(writer, record)?;
+    export_mobility(writer, record, b"=")?;
+    export_extra(writer, record, b"=")?;
     export_spectra(writer, record)?;
     writer.write_all(b"END IONS\n\n")?;
 
@@ -113,7 +129,7 @@ pub(crate) fn record_to_pava_mgf<T: Write>(writer: &mut T, record: &Record)
 
 #[inline(always)]
 fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
-    -> Result<TextWriterState<T>>
+    -> Result<TextWriterState<'_, T>>
 {
     Ok(TextWriterState::new(writer, delimiter))
 }
@@ -218,7 +234,7 @@ fn parse_title_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     type Title = PavaMgfTitleRegex;
 
     // Verify and parse the title line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["TITLE="], "=")?;
     let captures = none_to_error!(Title::extract().captures(&line), InvalidInput);
     record.file = capture_as_string(&captures, Title::FILE_INDEX);
 
@@ -239,7 +255,7 @@ fn parse_pepmass_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     type PepMass = PavaMgfPepMassRegex;
 
     // Verify and parse the pepmass line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["PEPMASS="], "=")?;
     let captures = none_to_error!(PepMass::extract().captures(&line), InvalidInput);
 
     let mz = capture_as_str(&captures, PepMass::PARENT_MZ_INDEX);
@@ -259,7 +275,7 @@ fn parse_charge_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     type Charge = PavaMgfChargeRegex;
 
     // Verify and parse the charge line
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["CHARGE="], "=")?;
     let captures = none_to_error!(Charge::extract().captures(&line), InvalidInput);
     let z: i8 = from_string(capture_as_str(&captures, Charge::PARENT_Z_INDEX))?;
     let sign = capture_as_str(&captures, Charge::PARENT_Z_SIGN_INDEX);
@@ -283,6 +299,12 @@ fn parse_spectra<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
         if line == "END IONS" {
             break;
         }
+        if parse_mobility_line(&line, record, "=")? {
+            continue;
+        }
+        if parse_extra_line(&line, record, "=") {
+            continue;
+        }
 
         // Parse the line data
         let mut items: Vec<&str> = Vec::with_capacity(5);
@@ -293,14 +315,24 @@ fn parse_spectra<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
                 record.peaks.push(Peak {
                     mz: from_string(items.get_unchecked(0))?,
                     intensity: from_string(items.get_unchecked(1))?,
-                    z: 0
+                    z: 0,
+                    mobility: None,
                 });
             } else if items.len() == 3 {
                 // mz, z, intensity
                 record.peaks.push(Peak {
                     mz: from_string(items.get_unchecked(0))?,
                     intensity: from_string(items.get_unchecked(2))?,
-                    z: from_string(items.get_unchecked(1))?
+                    z: from_string(items.get_unchecked(1))?,
+                    mobility: None,
+                });
+            } else if items.len() == 4 {
+                // mz, z, intensity, mobility
+                record.peaks.push(Peak {
+                    mz: from_string(items.get_unchecked(0))?,
+                    intensity: from_string(items.get_unchecked(2))?,
+                    z: from_string(items.get_unchecked(1))?,
+                    mobility: Some(from_string(items.get_unchecked(3))?),
                 });
             } else {
                 return Err(From::from(ErrorKind::InvalidInput));
@@ -324,6 +356,10 @@ pub(crate) fn record_from_pava_mgf<T: BufRead>(reader: &mut T)
     parse_charge_line(&mut lines, &mut record)?;
     parse_spectra(&mut lines, &mut record)?;
 
+    // Guarantee the sorted invariant for the peak list.
+    if !record.peaks.is_sorted_by_mz() {
+        record.peaks.sort_by_mz();
+    }
     record.peaks.shrink_to_fit();
     Ok(record)
 }
@@ -9,6 +9,8 @@ pub struct Peak {
     pub intensity: f64,
     /// Charge state of the ion.
     pub z: i8,
+    /// Ion mobility (1/K0, in V·s/cm²) of the ion, if known.
+    pub mobility: Option<f64>,
 }
 
 impl Peak {
@@ -19,6 +21,7 @@ impl Peak {
             mz: 0.0,
             intensity: 0.0,
             z: 0,
+            mobility: None,
         }
     }
 }
@@ -32,16 +35,16 @@ mod tests {
 
     #[test]
     fn debug_peak_test() {
-        let peak = Peak { mz: 257.1, intensity: 457.5, z: 1 };
+        let peak = Peak { mz: 257.1, intensity: 457.5, z: 1, mobility: None };
         let text = format!("{:?}", peak);
-        assert_eq!(text, "Peak { mz: 257.1, intensity: 457.5, z: 1 }");
+        assert_eq!(text, "Peak { mz: 257.1, intensity: 457.5, z: 1, mobility: None }");
     }
 
     #[test]
     fn equality_peak_test() {
-        let x = Peak { mz: 257.1, intensity: 457.5, z: 1 };
-        let y = Peak { mz: 257.1, intensity: 457.5, z: 1 };
-        let z = Peak { mz: 257.1, intensity: 457.5, z: 2 };
+        let x = Peak { mz: 257.1, intensity: 457.5, z: 1, mobility: None };
+        let y = Peak { mz: 257.1, intensity: 457.5, z: 1, mobility: None };
+        let z = Peak { mz: 257.1, intensity: 457.5, z: 2, mobility: None };
         assert_eq!(x, y);
         assert_ne!(x, z);
         assert_ne!(y, z);
@@ -1,31 +1,99 @@
 //! Model for spectral collections.
 
+use std::cmp::Ordering;
+
+use bio::mass::Tolerance;
+use util::search::partition_point;
 use super::peak::Peak;
 
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
 /// Spectral peak collection type.
+///
+/// Peak lists created by the spectral parsers are sorted by increasing
+/// m/z, which is required by the search methods in `PeakListExt`.
+/// Peak lists created or modified manually must be re-sorted with
+/// `PeakListExt::sort_by_mz` before searching.
 pub type PeakList = Vec<Peak>;
 
+/// Sorting and binary-search operations on peak lists sorted by m/z.
+pub trait PeakListExt {
+    /// Sort peaks by increasing m/z, preserving the order of equal peaks.
+    ///
+    /// Uses the IEEE total order, so peaks with a NaN m/z are sorted
+    /// after all other peaks, and are never found by the searches.
+    fn sort_by_mz(&mut self);
+
+    /// Check if the peaks are sorted by increasing m/z, as by `sort_by_mz`.
+    fn is_sorted_by_mz(&self) -> bool;
+
+    /// Find the peak nearest to the target m/z, within the tolerance.
+    ///
+    /// Requires peaks sorted by increasing m/z.
+    fn find_nearest_mz(&self, target: f64, tol: Tolerance) -> Option<&Peak>;
+
+    /// Get the peaks with m/z within the inclusive range `[lo, hi]`.
+    ///
+    /// Requires peaks sorted by increasing m/z.
+    fn slice_mz_range(&self, lo: f64, hi: f64) -> &[Peak];
+}
+
+impl PeakListExt for [Peak] {
+    #[inline]
+    fn sort_by_mz(&mut self) {
+        self.sort_by(|x, y| x.mz.total_cmp(&y.mz));
+    }
+
+    #[inline]
+    fn is_sorted_by_mz(&self) -> bool {
+        self.windows(2).all(|w| w[0].mz.total_cmp(&w[1].mz) != Ordering::Greater)
+    }
+
+    fn find_nearest_mz(&self, target: f64, tol: Tolerance) -> Option<&Peak> {
+        let (lo, hi) = tol.range(target);
+        self.slice_mz_range(lo, hi)
+            .iter()
+            .min_by(|x, y| {
+                let dx = (x.mz - target).abs();
+                let dy = (y.mz - target).abs();
+                dx.total_cmp(&dy)
+            })
+    }
+
+    fn slice_mz_range(&self, lo: f64, hi: f64) -> &[Peak] {
+        let first = partition_point(self, |p| p.mz < lo);
+        let last = partition_point(self, |p| p.mz <= hi);
+        if first < last {
+            &self[first..last]
+        } else {
+            &self[0..0]
+        }
+    }
+}
+
 // TESTS
 // -----
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::test::*;
 
     #[test]
     fn debug_list_test() {
         let v = vec![
-            Peak { mz: 257.5, intensity: 457.5, z: 1 },
-            Peak { mz: 257.6, intensity: 742.2, z: 1 },
+            Peak { mz: 257.5, intensity: 457.5, z: 1, mobility: None },
+            Peak { mz: 257.6, intensity: 742.2, z: 1, mobility: None },
         ];
         let text = format!("{:?}", v);
-        assert_eq!(text, "[Peak { mz: 257.5, intensity: 457.5, z: 1 }, Peak { mz: 257.6, intensity: 742.2, z: 1 }]");
+        assert_eq!(text, "[Peak { mz: 257.5, intensity: 457.5, z: 1, mobility: None }, Peak { mz: 257.6, intensity: 742.2, z: 1, mobility: None }]");
     }
 
     #[test]
     fn equality_list_test() {
-        let p1 = Peak { mz: 257.5, intensity: 457.5, z: 1 };
-        let p2 = Peak { mz: 257.6, intensity: 742.2, z: 1 };
+        let p1 = Peak { mz: 257.5, intensity: 457.5, z: 1, mobility: None };
+        let p2 = Peak { mz: 257.6, intensity: 742.2, z: 1, mobility: None };
         let x = vec![p1.clone(), p2.clone()];
         let y = vec![p1.clone(), p2.clone()];
         let z = vec![p2.clone(), p1.clone()];
@@ -33,4 +101,53 @@ mod tests {
         assert_ne!(x, z);
         assert_ne!(y, z);
     }
+
+    #[test]
+    fn sort_by_mz_list_test() {
+        let x = mgf_33450().peaks;
+        assert!(x.is_sorted_by_mz());
+
+        let mut y = x.clone();
+        y.reverse();
+        assert!(!y.is_sorted_by_mz());
+        y.sort_by_mz();
+        assert_eq!(x, y);
+
+        // NaN m/z values sort last, and the searches still work.
+        let mut z = x.clone();
+        z[3].mz = f64::NAN;
+        z.sort_by_mz();
+        assert!(z.is_sorted_by_mz());
+        assert!(z.last().unwrap().mz.is_nan());
+        assert_eq!(z.find_nearest_mz(288.2038, Tolerance::Ppm(10.0)).unwrap().mz, 288.2038337);
+    }
+
+    #[test]
+    fn find_nearest_mz_list_test() {
+        let x = mgf_33450().peaks;
+        let peak = x.find_nearest_mz(288.2038, Tolerance::Ppm(10.0)).unwrap();
+        assert_eq!(peak.mz, 288.2038337);
+
+        // nearest peak is outside of the tolerance
+        assert_eq!(x.find_nearest_mz(288.21, Tolerance::Ppm(1.0)), None);
+        assert_eq!(x.find_nearest_mz(100.0, Tolerance::Ppm(10.0)), None);
+        assert_eq!(PeakList::new().find_nearest_mz(288.2038, Tolerance::Da(0.01)), None);
+    }
+
+    #[test]
+    fn slice_mz_range_list_test() {
+        let x = mgf_33450().peaks;
+        let slc = x.slice_mz_range(257.5, 257.533);
+        assert_eq!(slc.len(), 9);
+        assert_eq!(slc[0].mz, 257.514984);
+        assert_eq!(slc[8].mz, 257.5327357);
+
+        // inclusive bounds
+        let slc = x.slice_mz_range(205.9304178, 205.9320046);
+        assert_eq!(slc.len(), 2);
+
+        assert!(x.slice_mz_range(0.0, 100.0).is_empty());
+        assert!(x.slice_mz_range(300.0, 200.0).is_empty());
+        assert_eq!(x.slice_mz_range(0.0, 1000.0).len(), x.len());
+    }
 }
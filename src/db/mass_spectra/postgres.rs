@@ -0,0 +1,91 @@
+//! Helper utilities for mass spectra PostgreSQL COPY export.
+//!
+//! Exports one row per scan, with the peaks stored as array columns of
+//! the m/z, intensity and charge values. The unrecognized header
+//! lines are not exported.
+
+use std::borrow::Cow;
+
+use traits::{PgColumn, PgCopy, PgType, PgValue};
+use super::record::Record;
+
+impl PgCopy for Record {
+    fn pg_columns() -> Vec<PgColumn> {
+        vec![
+            PgColumn::new("num", PgType::Bigint),
+            PgColumn::new("ms_level", PgType::Smallint),
+            PgColumn::new("rt", PgType::Double),
+            PgColumn::new("parent_mz", PgType::Double),
+            PgColumn::new("parent_intensity", PgType::Double),
+            PgColumn::new("parent_z", PgType::Smallint),
+            PgColumn::new("file", PgType::Text),
+            PgColumn::new("filter", PgType::Text),
+            PgColumn::nullable("injection_time", PgType::Double),
+            PgColumn::nullable("tic", PgType::Double),
+            PgColumn::nullable("ion_mobility", PgType::Double),
+            PgColumn::nullable("ccs", PgType::Double),
+            PgColumn::new("mz", PgType::DoubleArray),
+            PgColumn::new("intensity", PgType::DoubleArray),
+            PgColumn::new("z", PgType::IntegerArray),
+            PgColumn::new("parent", PgType::BigintArray),
+            PgColumn::new("children", PgType::BigintArray),
+        ]
+    }
+
+    fn pg_values<'a>(&'a self) -> Vec<PgValue<'a>> {
+        vec![
+            PgValue::Bigint(self.num as i64),
+            PgValue::Smallint(self.ms_level as i16),
+            PgValue::Double(self.rt),
+            PgValue::Double(self.parent_mz),
+            PgValue::Double(self.parent_intensity),
+            PgValue::Smallint(self.parent_z as i16),
+            PgValue::Text(Cow::from(self.file.as_str())),
+            PgValue::Text(Cow::from(self.filter.as_str())),
+            PgValue::from_double(self.injection_time),
+            PgValue::from_double(self.tic),
+            PgValue::from_double(self.ion_mobility),
+            PgValue::from_double(self.ccs),
+            PgValue::DoubleArray(self.peaks.iter().map(|p| p.mz).collect()),
+            PgValue::DoubleArray(self.peaks.iter().map(|p| p.intensity).collect()),
+            PgValue::IntegerArray(self.peaks.iter().map(|p| p.z as i32).collect()),
+            PgValue::BigintArray(self.parent.iter().map(|&n| n as i64).collect()),
+            PgValue::BigintArray(self.children.iter().map(|&n| n as i64).collect()),
+        ]
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn pg_copy_test() {
+        let ddl = Record::pg_create_table("spectra");
+        assert!(ddl.contains("    tic double precision,\n"));
+        assert!(ddl.contains("    mz double precision[] NOT NULL,\n"));
+
+        let x = mgf_33450();
+        let y = mgf_empty();
+        let mut w = vec![];
+        Record::to_pg_text_iter(&mut w, vec![x.clone(), y].iter()).unwrap();
+        let text = String::from_utf8(w).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let fields: Vec<&str> = lines[0].split('\t').collect();
+        assert_eq!(fields.len(), 17);
+        assert_eq!(fields[9], "\\N");
+        assert!(fields[12].starts_with(&format!("{{{:?},", x.peaks[0].mz)));
+        let fields: Vec<&str> = lines[1].split('\t').collect();
+        assert_eq!(&fields[12..], &["{}", "{}", "{}", "{}", "{}"]);
+
+        let mut w = vec![];
+        Record::to_pg_binary_iter(&mut w, vec![x].iter()).unwrap();
+        assert_eq!(&w[19..21], &[0, 17]);
+        assert!(w.ends_with(b"\xff\xff"));
+    }
+}
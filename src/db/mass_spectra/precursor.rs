@@ -0,0 +1,276 @@
+//! Precursor m/z and charge correction from MS1 isotope envelopes.
+//!
+//! Instruments frequently select the most intense isotope of a precursor,
+//! rather than the monoisotopic peak, reporting a parent m/z shifted by
+//! one or more isotopes (the classic +1 Da error). Inspecting the isotope
+//! envelope around the reported precursor in the preceding MS1 scan
+//! recovers the monoisotopic m/z and the charge.
+
+use bio::mass::Tolerance;
+use util::Result;
+use super::peak::Peak;
+use super::peak_list::PeakListExt;
+use super::record::Record;
+
+/// Mass difference between the carbon-13 and carbon-12 isotopes.
+pub const ISOTOPE_SPACING: f64 = 1.0033548378;
+
+/// Options to correct precursors from isotope envelopes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrecursorOptions {
+    /// Tolerance of isotope peak m/z values.
+    pub tol: Tolerance,
+    /// Minimum charge to consider.
+    pub min_z: i8,
+    /// Maximum charge to consider.
+    pub max_z: i8,
+    /// Maximum number of isotopes the reported m/z may be shifted by.
+    pub max_shift: usize,
+    /// Minimum intensity of a lighter isotope, relative to the next isotope.
+    ///
+    /// Avoids assigning unrelated, low-intensity peaks as the
+    /// monoisotopic peak.
+    pub min_ratio: f64,
+}
+
+impl Default for PrecursorOptions {
+    #[inline]
+    fn default() -> Self {
+        PrecursorOptions {
+            tol: Tolerance::Ppm(10.0),
+            min_z: 1,
+            max_z: 6,
+            max_shift: 2,
+            min_ratio: 0.25,
+        }
+    }
+}
+
+/// Isotope envelope assignment for a precursor.
+#[derive(Clone, Debug, PartialEq)]
+struct Envelope {
+    /// Observed m/z of the monoisotopic peak.
+    mz: f64,
+    /// Number of isotopes the reported m/z is shifted by.
+    shift: usize,
+    /// Number of consecutive isotope peaks from the monoisotopic peak.
+    length: usize,
+}
+
+/// Find the isotope envelope containing the reported m/z at a charge.
+fn find_envelope(peaks: &[Peak], mz: f64, z: i8, options: &PrecursorOptions) -> Option<Envelope> {
+    let spacing = ISOTOPE_SPACING / (z as f64).abs();
+    let reported = peaks.find_nearest_mz(mz, options.tol)?;
+
+    // Walk down to the lightest plausible isotope.
+    let mut mono = reported;
+    let mut shift = 0;
+    while shift < options.max_shift {
+        let target = reported.mz - (shift + 1) as f64 * spacing;
+        match peaks.find_nearest_mz(target, options.tol) {
+            Some(peak) if peak.intensity >= options.min_ratio * mono.intensity => {
+                mono = peak;
+                shift += 1;
+            },
+            _ => break,
+        }
+    }
+
+    // Count the consecutive isotopes from the monoisotopic peak.
+    let mut length = 1;
+    while peaks.find_nearest_mz(mono.mz + length as f64 * spacing, options.tol).is_some() {
+        length += 1;
+    }
+
+    Some(Envelope { mz: mono.mz, shift: shift, length: length })
+}
+
+/// Correct the parent m/z and charge of a record from the MS1 peaks.
+///
+/// The charge with the longest isotope envelope, of at least 2 peaks,
+/// is assigned, preferring the reported charge on ties. The parent m/z
+/// is only changed if it was shifted from the monoisotopic peak.
+/// Returns if the record was modified. Requires peaks sorted by m/z.
+pub fn correct_precursor(ms1: &[Peak], record: &mut Record, options: &PrecursorOptions) -> bool {
+    if record.parent_mz == 0.0 {
+        return false;
+    }
+
+    let mut best: Option<(i8, Envelope)> = None;
+    for z in options.min_z..options.max_z.saturating_add(1) {
+        if z == 0 {
+            continue;
+        }
+        let envelope = match find_envelope(ms1, record.parent_mz, z, options) {
+            Some(envelope) => envelope,
+            None           => continue,
+        };
+        let is_better = match best {
+            None                      => true,
+            Some((best_z, ref other)) => {
+                envelope.length > other.length ||
+                    (envelope.length == other.length && z == record.parent_z && best_z != z)
+            },
+        };
+        if envelope.length >= 2 && is_better {
+            best = Some((z, envelope));
+        }
+    }
+
+    match best {
+        None              => false,
+        Some((z, envelope)) => {
+            let modified = envelope.shift > 0 || z != record.parent_z;
+            if envelope.shift > 0 {
+                record.parent_mz = envelope.mz;
+            }
+            record.parent_z = z;
+            modified
+        },
+    }
+}
+
+// ITERATOR
+
+/// Iterator which corrects the precursors of MS2 or higher scans.
+///
+/// Each MS2 or higher scan is corrected against the most recent MS1
+/// scan. Scans before the first MS1 scan, or with an unknown MS level,
+/// are unchanged.
+pub struct PrecursorCorrectionIter<U: Iterator<Item = Result<Record>>> {
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Options to correct precursors.
+    options: PrecursorOptions,
+    /// Peaks from the most recent MS1 scan.
+    ms1: Option<Vec<Peak>>,
+}
+
+impl<U: Iterator<Item = Result<Record>>> PrecursorCorrectionIter<U> {
+    /// Create new PrecursorCorrectionIter from an iterator and options.
+    #[inline]
+    pub fn new(iter: U, options: PrecursorOptions) -> Self {
+        PrecursorCorrectionIter {
+            iter: iter,
+            options: options,
+            ms1: None,
+        }
+    }
+}
+
+impl<U: Iterator<Item = Result<Record>>> Iterator for PrecursorCorrectionIter<U> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = match self.iter.next()? {
+            Err(e)     => return Some(Err(e)),
+            Ok(record) => record,
+        };
+
+        if record.ms_level == 1 {
+            let mut peaks = record.peaks.clone();
+            if !peaks.is_sorted_by_mz() {
+                peaks.sort_by_mz();
+            }
+            self.ms1 = Some(peaks);
+        } else if record.ms_level > 1 {
+            if let Some(ref ms1) = self.ms1 {
+                correct_precursor(ms1, &mut record, &self.options);
+            }
+        }
+
+        Some(Ok(record))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::mass::{mz_to_neutral_mass, Adduct};
+    use super::*;
+    use super::super::transform::SpectraIterExt;
+
+    fn peak(mz: f64, intensity: f64) -> Peak {
+        Peak { mz: mz, intensity: intensity, z: 0, mobility: None }
+    }
+
+    /// MS1 peaks with a 2+ isotope envelope from 500.0, and a noise peak.
+    fn ms1() -> Record {
+        let mut record = Record::new();
+        record.num = 1;
+        record.ms_level = 1;
+        record.peaks = vec![
+            peak(499.2, 10.0),
+            peak(500.0, 80.0),
+            peak(500.0 + ISOTOPE_SPACING / 2.0, 100.0),
+            peak(500.0 + ISOTOPE_SPACING, 60.0),
+            peak(500.0 + 1.5 * ISOTOPE_SPACING, 25.0),
+        ];
+        record
+    }
+
+    fn ms2(num: u32, mz: f64, z: i8) -> Record {
+        let mut record = Record::new();
+        record.num = num;
+        record.ms_level = 2;
+        record.parent_mz = mz;
+        record.parent_intensity = 100.0;
+        record.parent_z = z;
+        record
+    }
+
+    #[test]
+    fn correct_precursor_test() {
+        let peaks = ms1().peaks;
+        let options = PrecursorOptions::default();
+
+        // Shifted by a single isotope.
+        let mut x = ms2(2, 500.0 + ISOTOPE_SPACING / 2.0, 2);
+        assert!(correct_precursor(&peaks, &mut x, &options));
+        assert_eq!((x.parent_mz, x.parent_z), (500.0, 2));
+
+        // Incorrect charge.
+        let mut x = ms2(2, 500.0, 3);
+        assert!(correct_precursor(&peaks, &mut x, &options));
+        assert_eq!((x.parent_mz, x.parent_z), (500.0, 2));
+
+        // The neutral mass follows the corrected m/z and charge.
+        let mut x = ms2(2, 500.0 + ISOTOPE_SPACING / 2.0, 3);
+        x.adduct = Some(Adduct::Sodium);
+        assert!(correct_precursor(&peaks, &mut x, &options));
+        assert_eq!(x.parent_mass(), mz_to_neutral_mass(500.0, 2, Adduct::Sodium));
+
+        // Already correct.
+        let mut x = ms2(2, 500.0, 2);
+        assert!(!correct_precursor(&peaks, &mut x, &options));
+
+        // Low-intensity lighter peak.
+        let mut peaks = peaks.clone();
+        peaks[1].intensity = 5.0;
+        let mut x = ms2(2, 500.0 + ISOTOPE_SPACING / 2.0, 2);
+        assert!(!correct_precursor(&peaks, &mut x, &options));
+
+        // Precursor not in the MS1 scan.
+        let mut x = ms2(2, 600.0, 2);
+        assert!(!correct_precursor(&peaks, &mut x, &options));
+        assert_eq!((x.parent_mz, x.parent_z), (600.0, 2));
+    }
+
+    #[test]
+    fn precursor_iter_test() {
+        let v = vec![
+            Ok(ms2(1, 500.0 + ISOTOPE_SPACING, 2)),
+            Ok(ms1()),
+            Ok(ms2(3, 500.0 + ISOTOPE_SPACING, 2)),
+        ];
+        let v: Vec<Record> = v.into_iter()
+            .precursor_corrected(PrecursorOptions::default())
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(v[0].parent_mz, 500.0 + ISOTOPE_SPACING);
+        assert_eq!(v[1], ms1());
+        assert_eq!(v[2].parent_mz, 500.0);
+    }
+}
@@ -5,8 +5,9 @@ use std::io::Lines;
 
 use traits::*;
 use util::*;
-use super::mgf::MgfRecordIter;
+use super::mgf::{estimate_extra_size, estimate_mobility_size, export_extra, export_mobility, export_peak_mobility, next_header_line, parse_extra_line, parse_mobility_line, parse_peak_mobility, MgfRecordIter};
 use super::peak::Peak;
+use super::peak_list::PeakListExt;
 use super::re::*;
 use super::record::Record;
 
@@ -22,6 +23,8 @@ pub(crate) fn estimate_pwiz_mgf_record_size(record: &Record) -> usize {
     const MGF_PEAK_SIZE: usize = 25;
     MGF_VOCABULARY_SIZE +
         record.file.len() +
+        estimate_extra_size(record) +
+        estimate_mobility_size(record) +
         MGF_PEAK_SIZE * record.peaks.len()
 }
 
@@ -110,7 +113,9 @@ fn export_spectra<T: Write>(writer: &mut T, record: &Record)
     for peak in record.peaks.iter() {
         let mz = to_bytes(&peak.mz)?;
         let intensity = to_bytes(&peak.intensity)?;
-        write_alls!(writer, mz.as_slice(), b" ", intensity.as_slice(), b"\n")?;
+        write_alls!(writer, mz.as_slice(), b" ", intensity.as_slice())?;
+        export_peak_mobility(writer, peak, b" ")?;
+        writer.write_all(b"\n")?;
     }
 
     Ok(())
@@ -126,6 +131,8 @@ pub(crate) fn record_to_pwiz_mgf<T: Write>(writer: &mut T, record: &Record)
     export_charge(writer, record)?;
     export_rt(writer, record)?;
     export_scans(writer, record)?;
+    export_mobility(writer, record, b"=")?;
+    export_extra(writer, record, b"=")?;
     export_spectra(writer, record)?;
     writer.write_all(b"END IONS\n\n")?;
 
@@ -136,7 +143,7 @@ pub(crate) fn record_to_pwiz_mgf<T: Write>(writer: &mut T, record: &Record)
 
 #[inline(always)]
 fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
-    -> Result<TextWriterState<T>>
+    -> Result<TextWriterState<'_, T>>
 {
     Ok(TextWriterState::new(writer, delimiter))
 }
@@ -241,7 +248,7 @@ fn parse_title_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     type Title = PwizMgfTitleRegex;
 
     // Verify and parse the title line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["TITLE="], "=")?;
     let captures = none_to_error!(Title::extract().captures(&line), InvalidInput);
     record.file = capture_as_string(&captures, Title::FILE_INDEX);
 
@@ -259,7 +266,7 @@ fn parse_pepmass_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     type PepMass = PwizMgfPepMassRegex;
 
     // Verify and parse the pepmass line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["PEPMASS="], "=")?;
     let captures = none_to_error!(PepMass::extract().captures(&line), InvalidInput);
 
     let mz = capture_as_str(&captures, PepMass::PARENT_MZ_INDEX);
@@ -314,10 +321,10 @@ fn parse_rt_line(line: &str, record: &mut Record)
 fn parse_charge_and_rt_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     -> Result<()>
 {
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["CHARGE=", "RTINSECONDS="], "=")?;
     if line.starts_with("CHARGE") {
         parse_charge_line(&line, record)?;
-        let line = none_to_error!(lines.next(), InvalidInput)?;
+        let line = next_header_line(lines, record, &["RTINSECONDS="], "=")?;
         parse_rt_line(&line, record)
     } else {
         record.parent_z = 1;
@@ -327,11 +334,11 @@ fn parse_charge_and_rt_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Recor
 
 /// Parse the charge and RT header line.
 #[inline(always)]
-fn parse_scans_line<T: BufRead>(lines: &mut Lines<T>, _: &mut Record)
+fn parse_scans_line<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
     -> Result<()>
 {
     // Verify the start header line.
-    let line = none_to_error!(lines.next(), InvalidInput)?;
+    let line = next_header_line(lines, record, &["SCANS="], "=")?;
     bool_to_error!(line.starts_with("SCANS="), InvalidInput);
 
     Ok(())
@@ -347,17 +354,25 @@ fn parse_spectra<T: BufRead>(lines: &mut Lines<T>, record: &mut Record)
         if line == "END IONS" {
             break;
         }
+        if parse_mobility_line(&line, record, "=")? {
+            continue;
+        }
+        if parse_extra_line(&line, record, "=") {
+            continue;
+        }
 
         // Parse the line data
         let mut items = line.split(' ');
         let mz = none_to_error!(items.next(), InvalidInput);
         let intensity = none_to_error!(items.next(), InvalidInput);
+        let mobility = parse_peak_mobility(items.next())?;
         bool_to_error!(items.next().is_none(), InvalidInput);
 
         record.peaks.push(Peak {
             mz: from_string(mz)?,
             intensity: from_string(intensity)?,
             z: 0,
+            mobility: mobility,
         });
     }
 
@@ -378,6 +393,10 @@ pub(crate) fn record_from_pwiz_mgf<T: BufRead>(reader: &mut T)
     parse_scans_line(&mut lines, &mut record)?;
     parse_spectra(&mut lines, &mut record)?;
 
+    // Guarantee the sorted invariant for the peak list.
+    if !record.peaks.is_sorted_by_mz() {
+        record.peaks.sort_by_mz();
+    }
     record.peaks.shrink_to_fit();
     Ok(record)
 }
@@ -0,0 +1,301 @@
+//! Run-level quality control (QC) metrics for spectral collections.
+//!
+//! Metrics are accumulated scan-by-scan, so they may be computed
+//! lazily from the spectral iterators of a large run.
+
+use std::collections::BTreeMap;
+use std::io::prelude::*;
+
+use util::*;
+use super::record::Record;
+
+/// Summary of a single scan within a QC report.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QcScan {
+    /// Scan number for the spectrum.
+    pub num: u32,
+    /// MS acquisition level of the spectrum.
+    pub ms_level: u8,
+    /// Time of spectrum acquisition.
+    pub rt: f64,
+    /// Total ion current, reported or summed from the peak intensities.
+    pub tic: f64,
+    /// Number of peaks in the spectrum.
+    pub peaks: usize,
+}
+
+/// Run-level QC metrics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QcMetrics {
+    /// Number of MS1 scans.
+    pub ms1_count: usize,
+    /// Number of MS2 scans.
+    pub ms2_count: usize,
+    /// Number of MS3 or higher scans.
+    pub msn_count: usize,
+    /// Number of scans with an unknown MS level.
+    pub unknown_count: usize,
+    /// Number of precursors by charge, for all non-MS1 scans.
+    ///
+    /// Precursors with an unknown charge are counted with a charge of 0.
+    pub charge_counts: BTreeMap<i8, usize>,
+    /// Per-scan summaries, in the order the scans were added.
+    pub scans: Vec<QcScan>,
+}
+
+impl QcMetrics {
+    /// Create new, empty QC metrics.
+    #[inline]
+    pub fn new() -> Self {
+        QcMetrics {
+            ms1_count: 0,
+            ms2_count: 0,
+            msn_count: 0,
+            unknown_count: 0,
+            charge_counts: BTreeMap::new(),
+            scans: vec![],
+        }
+    }
+
+    /// Add a scan to the metrics.
+    pub fn add(&mut self, record: &Record) {
+        match record.ms_level {
+            0 => self.unknown_count += 1,
+            1 => self.ms1_count += 1,
+            2 => self.ms2_count += 1,
+            _ => self.msn_count += 1,
+        }
+        if record.ms_level != 1 {
+            *self.charge_counts.entry(record.parent_z).or_insert(0) += 1;
+        }
+        self.scans.push(QcScan {
+            num: record.num,
+            ms_level: record.ms_level,
+            rt: record.rt,
+            tic: record.total_ion_current(),
+            peaks: record.peaks.len(),
+        });
+    }
+
+    /// Calculate QC metrics from an iterator over spectra.
+    pub fn from_records<'a, Iter>(iter: Iter) -> Self
+        where Iter: Iterator<Item = &'a Record>
+    {
+        let mut metrics = QcMetrics::new();
+        for record in iter {
+            metrics.add(record);
+        }
+        metrics
+    }
+
+    /// Get the total number of scans.
+    #[inline]
+    pub fn scan_count(&self) -> usize {
+        self.scans.len()
+    }
+
+    /// Get the retention time range covered by the scans.
+    ///
+    /// Returns `None` if there are no scans with a valid retention time.
+    pub fn rt_range(&self) -> Option<(f64, f64)> {
+        self.scans.iter()
+            .map(|s| s.rt)
+            .filter(|rt| !rt.is_nan())
+            .fold(None, |range, rt| match range {
+                None           => Some((rt, rt)),
+                Some((lo, hi)) => Some((lo.min(rt), hi.max(rt))),
+            })
+    }
+
+    /// Get the total ion current over time, as `(rt, tic)` pairs.
+    #[inline]
+    pub fn tic(&self) -> Vec<(f64, f64)> {
+        self.scans.iter().map(|s| (s.rt, s.tic)).collect()
+    }
+
+    /// Get the mean number of peaks per scan, or `None` if there are no scans.
+    #[inline]
+    pub fn mean_peak_count(&self) -> Option<f64> {
+        match self.scans.len() {
+            0 => None,
+            n => Some(self.scans.iter().map(|s| s.peaks).sum::<usize>() as f64 / n as f64),
+        }
+    }
+}
+
+// WRITER
+
+/// Export the per-scan QC metrics to CSV, with one row per scan.
+#[cfg(feature = "csv")]
+pub fn qc_to_csv<T: Write>(writer: &mut T, metrics: &QcMetrics)
+    -> Result<()>
+{
+    writer.write_all(b"num,ms_level,rt,tic,peaks\n")?;
+    for scan in metrics.scans.iter() {
+        let num = to_bytes(&scan.num)?;
+        let ms_level = to_bytes(&scan.ms_level)?;
+        let rt = to_bytes(&scan.rt)?;
+        let tic = to_bytes(&scan.tic)?;
+        let peaks = to_bytes(&scan.peaks)?;
+        write_alls!(
+            writer,
+            num.as_slice(), b",", ms_level.as_slice(), b",", rt.as_slice(), b",",
+            tic.as_slice(), b",", peaks.as_slice(), b"\n"
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Export a float as a JSON number, or `null` if it is not finite.
+fn export_json_float<T: Write>(writer: &mut T, value: f64)
+    -> Result<()>
+{
+    if value.is_finite() {
+        let value = to_bytes(&value)?;
+        writer.write_all(value.as_slice())?;
+    } else {
+        writer.write_all(b"null")?;
+    }
+
+    Ok(())
+}
+
+/// Export the QC metrics to a JSON document.
+///
+/// The document contains the summary metrics and the per-scan metrics.
+pub fn qc_to_json<T: Write>(writer: &mut T, metrics: &QcMetrics)
+    -> Result<()>
+{
+    let ms1 = to_bytes(&metrics.ms1_count)?;
+    let ms2 = to_bytes(&metrics.ms2_count)?;
+    let msn = to_bytes(&metrics.msn_count)?;
+    let unknown = to_bytes(&metrics.unknown_count)?;
+    write_alls!(
+        writer,
+        b"{\"ms1_count\":", ms1.as_slice(),
+        b",\"ms2_count\":", ms2.as_slice(),
+        b",\"msn_count\":", msn.as_slice(),
+        b",\"unknown_count\":", unknown.as_slice()
+    )?;
+
+    writer.write_all(b",\"rt_range\":")?;
+    match metrics.rt_range() {
+        None           => writer.write_all(b"null")?,
+        Some((lo, hi)) => {
+            writer.write_all(b"[")?;
+            export_json_float(writer, lo)?;
+            writer.write_all(b",")?;
+            export_json_float(writer, hi)?;
+            writer.write_all(b"]")?;
+        },
+    }
+
+    writer.write_all(b",\"mean_peak_count\":")?;
+    match metrics.mean_peak_count() {
+        None    => writer.write_all(b"null")?,
+        Some(v) => export_json_float(writer, v)?,
+    }
+
+    // JSON keys must be strings, so use the charge as a string key.
+    writer.write_all(b",\"charge_counts\":{")?;
+    for (index, (z, count)) in metrics.charge_counts.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        let z = to_bytes(z)?;
+        let count = to_bytes(count)?;
+        write_alls!(writer, b"\"", z.as_slice(), b"\":", count.as_slice())?;
+    }
+
+    writer.write_all(b"},\"scans\":[")?;
+    for (index, scan) in metrics.scans.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        let num = to_bytes(&scan.num)?;
+        let ms_level = to_bytes(&scan.ms_level)?;
+        let peaks = to_bytes(&scan.peaks)?;
+        write_alls!(writer, b"{\"num\":", num.as_slice(), b",\"ms_level\":", ms_level.as_slice(), b",\"rt\":")?;
+        export_json_float(writer, scan.rt)?;
+        writer.write_all(b",\"tic\":")?;
+        export_json_float(writer, scan.tic)?;
+        write_alls!(writer, b",\"peaks\":", peaks.as_slice(), b"}")?;
+    }
+    writer.write_all(b"]}")?;
+
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    fn records() -> Vec<Record> {
+        let mut ms1 = fullms_mgf_33450();
+        ms1.ms_level = 1;
+        ms1.rt = 8000.0;
+        let mut ms2 = mgf_33450();
+        ms2.ms_level = 2;
+        let mut ms3 = mgf_empty();
+        ms3.ms_level = 3;
+        ms3.parent_z = 2;
+        vec![ms1, ms2, ms3, mgf_33450()]
+    }
+
+    #[test]
+    fn qc_metrics_test() {
+        let records = records();
+        let metrics = QcMetrics::from_records(records.iter());
+        assert_eq!(metrics.scan_count(), 4);
+        assert_eq!(metrics.ms1_count, 1);
+        assert_eq!(metrics.ms2_count, 1);
+        assert_eq!(metrics.msn_count, 1);
+        assert_eq!(metrics.unknown_count, 1);
+        assert_eq!(metrics.charge_counts.get(&4), Some(&2));
+        assert_eq!(metrics.charge_counts.get(&2), Some(&1));
+        assert_eq!(metrics.rt_range(), Some((8000.0, 8692.0)));
+        assert_eq!(metrics.scans[2].tic, 0.0);
+        assert_eq!(metrics.tic().len(), 4);
+
+        let peaks: usize = records.iter().map(|r| r.peaks.len()).sum();
+        assert_approx_eq!(metrics.mean_peak_count().unwrap(), peaks as f64 / 4.0);
+    }
+
+    #[test]
+    fn empty_qc_metrics_test() {
+        let metrics = QcMetrics::from_records([].iter());
+        assert_eq!(metrics, QcMetrics::new());
+        assert_eq!(metrics.rt_range(), None);
+        assert_eq!(metrics.mean_peak_count(), None);
+
+        let mut w = vec![];
+        qc_to_json(&mut w, &metrics).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "{\"ms1_count\":0,\"ms2_count\":0,\"msn_count\":0,\"unknown_count\":0,\"rt_range\":null,\"mean_peak_count\":null,\"charge_counts\":{},\"scans\":[]}");
+    }
+
+    #[test]
+    fn qc_json_test() {
+        let mut r = mgf_empty();
+        r.ms_level = 2;
+        let metrics = QcMetrics::from_records([r].iter());
+        let mut w = vec![];
+        qc_to_json(&mut w, &metrics).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "{\"ms1_count\":0,\"ms2_count\":1,\"msn_count\":0,\"unknown_count\":0,\"rt_range\":[8692.0,8692.0],\"mean_peak_count\":0.0,\"charge_counts\":{\"4\":1},\"scans\":[{\"num\":33450,\"ms_level\":2,\"rt\":8692.0,\"tic\":0.0,\"peaks\":0}]}");
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn qc_csv_test() {
+        let mut r = mgf_empty();
+        r.ms_level = 2;
+        let metrics = QcMetrics::from_records([r.clone(), r].iter());
+        let mut w = vec![];
+        qc_to_csv(&mut w, &metrics).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "num,ms_level,rt,tic,peaks\n33450,2,8692.0,0.0,0\n33450,2,8692.0,0.0,0\n");
+    }
+}
@@ -2,9 +2,14 @@
 
 use std::cmp::Ordering;
 
+use bio::mass::{Adduct, mz_to_neutral_mass};
+use traits::{Keyed, Renamable};
 use super::peak::Peak;
 use super::peak_list::PeakList;
 
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
 /// Model for a single record from a spectral scan.
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct Record {
@@ -24,12 +29,24 @@ pub struct Record {
     pub file: String,
     /// Scan filter for MS acquisition.
     pub filter: String,
+    /// Ion injection time, in milliseconds, if known.
+    pub injection_time: Option<f64>,
+    /// Total ion current reported by the instrument, if known.
+    pub tic: Option<f64>,
+    /// Ion mobility (1/K0, in V·s/cm²) of the parent ion, if known.
+    pub ion_mobility: Option<f64>,
+    /// Collisional cross section (in Å²) of the parent ion, if known.
+    pub ccs: Option<f64>,
+    /// Adduct of the parent ion, if known.
+    pub adduct: Option<Adduct>,
     /// MS spectral data (m/z, intensity, z)
     pub peaks: PeakList,
     /// Number of parent scans
     pub parent: Vec<u32>,
     /// Number of children scans.
     pub children: Vec<u32>,
+    /// Unrecognized header lines, as key-value pairs in input order.
+    pub extra: Vec<(String, String)>,
 }
 
 impl Record {
@@ -45,9 +62,15 @@ impl Record {
             parent_z: 0,
             file: String::new(),
             filter: String::new(),
+            injection_time: None,
+            tic: None,
+            ion_mobility: None,
+            ccs: None,
+            adduct: None,
             peaks: vec![],
             parent: vec![],
             children: vec![],
+            extra: vec![],
         }
     }
 
@@ -63,9 +86,48 @@ impl Record {
             parent_z: 0,
             file: String::new(),
             filter: String::new(),
+            injection_time: None,
+            tic: None,
+            ion_mobility: None,
+            ccs: None,
+            adduct: None,
             peaks: PeakList::with_capacity(capacity),
             parent: vec![],
             children: vec![],
+            extra: vec![],
+        }
+    }
+
+    /// Calculate the neutral mass of the parent ion from the record adduct.
+    ///
+    /// Assumes protonation if the adduct is unknown. Returns `None`
+    /// if the parent m/z or charge is unknown.
+    #[inline]
+    pub fn parent_mass(&self) -> Option<f64> {
+        self.parent_mass_with_adduct(self.adduct.unwrap_or(Adduct::Proton))
+    }
+
+    /// Calculate the neutral mass of the parent ion from the adduct type.
+    ///
+    /// Returns `None` if the parent m/z or charge is unknown.
+    #[inline]
+    pub fn parent_mass_with_adduct(&self, adduct: Adduct) -> Option<f64> {
+        if self.parent_mz == 0.0 {
+            None
+        } else {
+            mz_to_neutral_mass(self.parent_mz, self.parent_z, adduct)
+        }
+    }
+
+    /// Get the total ion current for the spectrum.
+    ///
+    /// Uses the instrument-reported TIC if known, otherwise, the sum
+    /// of all peak intensities.
+    #[inline]
+    pub fn total_ion_current(&self) -> f64 {
+        match self.tic {
+            Some(tic) => tic,
+            None      => self.peaks.iter().fold(0.0, |sum, p| sum + p.intensity),
         }
     }
 
@@ -82,6 +144,29 @@ impl Record {
     }
 }
 
+impl Keyed for Record {
+    type Key = u32;
+
+    /// Get the scan number.
+    #[inline]
+    fn key(&self) -> &u32 {
+        &self.num
+    }
+}
+
+impl Renamable for Record {
+    /// Get the source file name, from the spectrum title.
+    #[inline]
+    fn identifier(&self) -> &str {
+        &self.file
+    }
+
+    #[inline]
+    fn set_identifier(&mut self, identifier: String) {
+        self.file = identifier;
+    }
+}
+
 // TESTS
 // -----
 
@@ -94,7 +179,7 @@ mod tests {
     #[test]
     fn debug_record_test() {
         let text = format!("{:?}", mgf_empty());
-        assert_eq!(text, "Record { num: 33450, ms_level: 0, rt: 8692.0, parent_mz: 775.15625, parent_intensity: 170643.953125, parent_z: 4, file: \"QPvivo_2015_11_10_1targetmethod\", filter: \"\", peaks: [], parent: [], children: [] }");
+        assert_eq!(text, "Record { num: 33450, ms_level: 0, rt: 8692.0, parent_mz: 775.15625, parent_intensity: 170643.953125, parent_z: 4, file: \"QPvivo_2015_11_10_1targetmethod\", filter: \"\", injection_time: None, tic: None, ion_mobility: None, ccs: None, adduct: None, peaks: [], parent: [], children: [], extra: [] }");
     }
 
     #[test]
@@ -128,7 +213,7 @@ mod tests {
         assert!(!r2.is_complete());
         r2.num = r1.num;
 
-        r2.rt = 0.0;
+        r2.rt = -1.0;
         assert!(!r2.is_valid());
         assert!(!r2.is_complete());
         r2.rt = r1.rt;
@@ -142,6 +227,32 @@ mod tests {
         assert_approx_eq!(peak.intensity, 1740.2529296875);
     }
 
+    #[test]
+    fn total_ion_current_record_test() {
+        let mut r = mgf_33450();
+        let sum: f64 = r.peaks.iter().map(|p| p.intensity).sum();
+        assert_approx_eq!(r.total_ion_current(), sum);
+        r.tic = Some(1.5e6);
+        assert_eq!(r.total_ion_current(), 1.5e6);
+        assert_eq!(mgf_empty().total_ion_current(), 0.0);
+    }
+
+    #[test]
+    fn parent_mass_record_test() {
+        let mut r = mgf_33450();
+        assert_approx_eq!(r.parent_mass().unwrap(), 3096.595894, 1e-6);
+        assert_approx_eq!(r.parent_mass_with_adduct(Adduct::Sodium).unwrap(), 3008.668117, 1e-6);
+        r.adduct = Some(Adduct::Sodium);
+        assert_eq!(r.parent_mass(), r.parent_mass_with_adduct(Adduct::Sodium));
+        r.adduct = None;
+
+        r.parent_z = 0;
+        assert_eq!(r.parent_mass(), None);
+        r.parent_z = 4;
+        r.parent_mz = 0.0;
+        assert_eq!(r.parent_mass(), None);
+    }
+
     #[cfg(feature = "mgf")]
     fn mgf_record_test(r: Record, text: &[u8], kind: MgfKind) {
         let x = r.to_mgf_bytes(kind).unwrap();
@@ -165,4 +276,16 @@ mod tests {
         mgf_record_test(mgf_empty(), PAVA_EMPTY_MGF, MgfKind::Pava);
         mgf_record_test(mgf_empty(), PWIZ_EMPTY_MGF, MgfKind::Pwiz);
     }
+
+    #[cfg(feature = "mgf")]
+    #[test]
+    fn fullms_mgf_header_record_test() {
+        // unknown injection time and TIC are exported as 0 and the peak sum
+        let r = mgf_33450();
+        let x = r.to_mgf_bytes(MgfKind::FullMs).unwrap();
+        let y = Record::from_mgf_bytes(&x, MgfKind::FullMs).unwrap();
+        assert_eq!(y.injection_time, Some(0.0));
+        assert_approx_eq!(y.tic.unwrap(), r.total_ion_current());
+        assert_eq!(y.base_peak(), r.base_peak());
+    }
 }
@@ -2,6 +2,9 @@
 
 use super::record::Record;
 
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
 /// Spectral record collection type.
 pub type RecordList = Vec<Record>;
 
@@ -17,7 +20,7 @@ mod tests {
     #[test]
     fn debug_list_test() {
         let text = format!("{:?}", vec![mgf_empty(), mgf_empty()]);
-        assert_eq!(text, "[Record { num: 33450, ms_level: 0, rt: 8692.0, parent_mz: 775.15625, parent_intensity: 170643.953125, parent_z: 4, file: \"QPvivo_2015_11_10_1targetmethod\", filter: \"\", peaks: [], parent: [], children: [] }, Record { num: 33450, ms_level: 0, rt: 8692.0, parent_mz: 775.15625, parent_intensity: 170643.953125, parent_z: 4, file: \"QPvivo_2015_11_10_1targetmethod\", filter: \"\", peaks: [], parent: [], children: [] }]");
+        assert_eq!(text, "[Record { num: 33450, ms_level: 0, rt: 8692.0, parent_mz: 775.15625, parent_intensity: 170643.953125, parent_z: 4, file: \"QPvivo_2015_11_10_1targetmethod\", filter: \"\", injection_time: None, tic: None, ion_mobility: None, ccs: None, adduct: None, peaks: [], parent: [], children: [], extra: [] }, Record { num: 33450, ms_level: 0, rt: 8692.0, parent_mz: 775.15625, parent_intensity: 170643.953125, parent_z: 4, file: \"QPvivo_2015_11_10_1targetmethod\", filter: \"\", injection_time: None, tic: None, ion_mobility: None, ccs: None, adduct: None, peaks: [], parent: [], children: [], extra: [] }]");
     }
 
     #[test]
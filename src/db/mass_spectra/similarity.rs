@@ -0,0 +1,240 @@
+//! Spectral similarity scores and spectral clustering.
+//!
+//! All functions require peak lists sorted by increasing m/z.
+
+use bio::mass::Tolerance;
+use concurrency::CancellationToken;
+use util::Result;
+use util::search::partition_point;
+use super::peak::Peak;
+use super::record::Record;
+
+// SIMILARITY
+
+/// Find candidate peak pairs within the tolerance, optionally shifted by `shift`.
+fn candidate_pairs(x: &[Peak], y: &[Peak], tol: Tolerance, shift: f64, pairs: &mut Vec<(usize, usize, f64)>) {
+    for (i, p) in x.iter().enumerate() {
+        let (lo, hi) = tol.range(p.mz + shift);
+        let first = partition_point(y, |q| q.mz < lo);
+        let last = partition_point(y, |q| q.mz <= hi);
+        for j in first..last.max(first) {
+            pairs.push((i, j, p.intensity * y[j].intensity));
+        }
+    }
+}
+
+/// Calculate the cosine score from candidate pairs, assigning each peak once.
+///
+/// Pairs are greedily assigned from the highest intensity product.
+fn score_pairs(x: &[Peak], y: &[Peak], mut pairs: Vec<(usize, usize, f64)>) -> f64 {
+    let norm_x = x.iter().map(|p| p.intensity * p.intensity).sum::<f64>().sqrt();
+    let norm_y = y.iter().map(|p| p.intensity * p.intensity).sum::<f64>().sqrt();
+    if !(norm_x > 0.0 && norm_y > 0.0) {
+        return 0.0;
+    }
+
+    pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+    let mut used_x = vec![false; x.len()];
+    let mut used_y = vec![false; y.len()];
+    let mut dot = 0.0;
+    for (i, j, product) in pairs {
+        if !used_x[i] && !used_y[j] {
+            used_x[i] = true;
+            used_y[j] = true;
+            dot += product;
+        }
+    }
+
+    dot / (norm_x * norm_y)
+}
+
+/// Calculate the normalized dot product (cosine score) of two peak lists.
+///
+/// Peaks match if their m/z are within the tolerance, and each peak
+/// matches at most one peak in the other list. The score ranges from
+/// 0 (no shared peaks) to 1 (identical spectra).
+pub fn cosine_similarity(x: &[Peak], y: &[Peak], tol: Tolerance) -> f64 {
+    let mut pairs = vec![];
+    candidate_pairs(x, y, tol, 0.0, &mut pairs);
+    score_pairs(x, y, pairs)
+}
+
+/// Calculate the modified cosine score of two spectra.
+///
+/// Like `cosine_similarity`, but peaks may also match if their m/z
+/// differ by the difference in precursor m/z, so fragments containing
+/// a modification on either precursor still contribute to the score.
+pub fn modified_cosine_similarity(x: &Record, y: &Record, tol: Tolerance) -> f64 {
+    let mut pairs = vec![];
+    candidate_pairs(&x.peaks, &y.peaks, tol, 0.0, &mut pairs);
+    let shift = y.parent_mz - x.parent_mz;
+    if shift.abs() > tol.width(x.parent_mz) {
+        candidate_pairs(&x.peaks, &y.peaks, tol, shift, &mut pairs);
+    }
+    score_pairs(&x.peaks, &y.peaks, pairs)
+}
+
+// CLUSTERING
+
+/// Options for greedy spectral clustering.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusterOptions {
+    /// Tolerance of matching fragment peaks.
+    pub tol: Tolerance,
+    /// Tolerance of precursor m/z values within a cluster.
+    ///
+    /// Ignored by the modified cosine score, which matches spectra
+    /// with different precursor m/z values.
+    pub precursor_tol: Tolerance,
+    /// Minimum similarity to the cluster representative.
+    pub threshold: f64,
+    /// Use the modified cosine score rather than the cosine score.
+    ///
+    /// Clusters modified forms of the same analyte, so the precursor
+    /// m/z values are not checked against `precursor_tol`.
+    pub modified: bool,
+}
+
+impl Default for ClusterOptions {
+    #[inline]
+    fn default() -> Self {
+        ClusterOptions {
+            tol: Tolerance::Da(0.02),
+            precursor_tol: Tolerance::Da(0.02),
+            threshold: 0.9,
+            modified: false,
+        }
+    }
+}
+
+/// Greedily cluster spectra by similarity, returning the indexes of each cluster.
+///
+/// Spectra are visited in order, and are added to the first cluster
+/// whose representative (first member) has the same precursor charge,
+/// a precursor m/z within `precursor_tol` (unless `modified` is set),
+/// and a similarity of at least `threshold`. Otherwise, the spectrum
+/// creates a new cluster.
+/// Keeping only the first index of each cluster removes redundant scans.
+pub fn cluster_spectra(records: &[Record], options: &ClusterOptions) -> Vec<Vec<usize>> {
+    // A new token is never cancelled.
+    cluster_spectra_with_cancel(records, options, &CancellationToken::new())
+        .expect("clustering without cancellation cannot fail")
+}
+
+/// Greedily cluster spectra by similarity, stopping early if cancelled.
+///
+/// The token is checked before clustering each spectrum.
+pub fn cluster_spectra_with_cancel(records: &[Record], options: &ClusterOptions, cancel: &CancellationToken)
+    -> Result<Vec<Vec<usize>>>
+{
+    let mut clusters: Vec<Vec<usize>> = vec![];
+    for (index, record) in records.iter().enumerate() {
+        cancel.check()?;
+        let position = clusters.iter().position(|cluster| {
+            let representative = &records[cluster[0]];
+            if representative.parent_z != record.parent_z {
+                return false;
+            }
+            if !options.modified && !options.precursor_tol.matches(representative.parent_mz, record.parent_mz) {
+                return false;
+            }
+            let score = if options.modified {
+                modified_cosine_similarity(representative, record, options.tol)
+            } else {
+                cosine_similarity(&representative.peaks, &record.peaks, options.tol)
+            };
+            score >= options.threshold
+        });
+        match position {
+            Some(i) => clusters[i].push(index),
+            None    => clusters.push(vec![index]),
+        }
+    }
+
+    Ok(clusters)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    fn peaks(values: &[(f64, f64)]) -> Vec<Peak> {
+        values.iter().map(|&(mz, intensity)| Peak { mz: mz, intensity: intensity, z: 0, mobility: None }).collect()
+    }
+
+    #[test]
+    fn cosine_similarity_test() {
+        let x = mgf_33450();
+        assert_approx_eq!(cosine_similarity(&x.peaks, &x.peaks, Tolerance::Da(0.01)), 1.0);
+        assert_eq!(cosine_similarity(&x.peaks, &[], Tolerance::Da(0.01)), 0.0);
+
+        let a = peaks(&[(100.0, 1.0), (200.0, 1.0)]);
+        let b = peaks(&[(100.005, 1.0), (300.0, 1.0)]);
+        assert_approx_eq!(cosine_similarity(&a, &b, Tolerance::Da(0.01)), 0.5);
+        assert_eq!(cosine_similarity(&a, &b, Tolerance::Da(0.001)), 0.0);
+
+        // each peak is only matched once
+        let c = peaks(&[(100.0, 1.0), (100.004, 1.0)]);
+        let d = peaks(&[(100.002, 1.0), (500.0, 1.0)]);
+        assert_approx_eq!(cosine_similarity(&c, &d, Tolerance::Da(0.01)), 0.5);
+
+        // NaN intensities do not panic
+        let e = peaks(&[(100.0, ::std::f64::NAN), (200.0, 1.0)]);
+        assert_eq!(cosine_similarity(&e, &a, Tolerance::Da(0.01)), 0.0);
+    }
+
+    #[test]
+    fn modified_cosine_similarity_test() {
+        let mut x = mgf_empty();
+        x.parent_mz = 500.0;
+        x.peaks = peaks(&[(100.0, 1.0), (200.0, 1.0)]);
+        let mut y = x.clone();
+        y.parent_mz = 580.0;
+        y.peaks = peaks(&[(100.0, 1.0), (280.0, 1.0)]);
+
+        assert_approx_eq!(cosine_similarity(&x.peaks, &y.peaks, Tolerance::Da(0.01)), 0.5);
+        assert_approx_eq!(modified_cosine_similarity(&x, &y, Tolerance::Da(0.01)), 1.0);
+        assert_approx_eq!(modified_cosine_similarity(&x, &x, Tolerance::Da(0.01)), 1.0);
+    }
+
+    #[test]
+    fn cluster_spectra_test() {
+        let x = mgf_33450();
+        let mut y = x.clone();
+        y.peaks.truncate(10);
+        let mut z = x.clone();
+        z.parent_z = 3;
+
+        let options = ClusterOptions::default();
+        let records = vec![x.clone(), y, x.clone(), z];
+        let clusters = cluster_spectra(&records, &options);
+        assert_eq!(clusters, vec![vec![0, 2], vec![1], vec![3]]);
+        assert!(cluster_spectra(&[], &options).is_empty());
+
+        let cancel = CancellationToken::new();
+        assert_eq!(cluster_spectra_with_cancel(&records, &options, &cancel).unwrap(), clusters);
+        cancel.cancel();
+        assert!(cluster_spectra_with_cancel(&records, &options, &cancel).is_err());
+    }
+
+    #[test]
+    fn cluster_modified_spectra_test() {
+        // spectra only share peaks shifted by the precursor m/z difference
+        let mut x = mgf_empty();
+        x.parent_mz = 500.0;
+        x.peaks = peaks(&[(100.0, 1.0), (200.0, 1.0)]);
+        let mut y = x.clone();
+        y.parent_mz = 514.0;
+        y.peaks = peaks(&[(114.0, 1.0), (214.0, 1.0)]);
+        let records = vec![x, y];
+
+        let mut options = ClusterOptions::default();
+        assert_eq!(cluster_spectra(&records, &options), vec![vec![0], vec![1]]);
+        options.modified = true;
+        assert_eq!(cluster_spectra(&records, &options), vec![vec![0, 1]]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Transforms over spectral records, to apply before writing.
+//!
+//! Renumbers scans, and recalibrates retention times and precursor
+//! m/z values, for example, to align runs in conversion pipelines.
+
+use std::collections::HashMap;
+
+use util::Result;
+use super::precursor::{PrecursorCorrectionIter, PrecursorOptions};
+use super::record::Record;
+
+/// Transform applied to each spectral record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transform {
+    /// Renumber scans sequentially, in iteration order.
+    ///
+    /// Parent and children scan numbers are updated for scans already
+    /// renumbered, and removed otherwise.
+    Renumber {
+        /// Number for the next scan.
+        next: u32,
+        /// Map of original scan numbers to renumbered scan numbers.
+        numbers: HashMap<u32, u32>,
+    },
+    /// Recalibrate the retention time, as `rt * scale + offset`.
+    RetentionTime {
+        /// Offset added after scaling, in seconds.
+        offset: f64,
+        /// Factor to scale the retention time by.
+        scale: f64,
+    },
+    /// Recalibrate the parent m/z by a shift, in parts-per-million.
+    ParentMz {
+        /// Mass shift, in parts-per-million.
+        ppm: f64,
+    },
+}
+
+impl Transform {
+    /// Create transform to renumber scans sequentially from a start number.
+    #[inline]
+    pub fn renumber(start: u32) -> Self {
+        Transform::Renumber { next: start, numbers: HashMap::new() }
+    }
+
+    /// Create transform to recalibrate the retention time.
+    #[inline]
+    pub fn retention_time(offset: f64, scale: f64) -> Self {
+        Transform::RetentionTime { offset: offset, scale: scale }
+    }
+
+    /// Create transform to recalibrate the parent m/z by a ppm shift.
+    #[inline]
+    pub fn parent_mz(ppm: f64) -> Self {
+        Transform::ParentMz { ppm: ppm }
+    }
+
+    /// Apply the transform to a record.
+    pub fn apply(&mut self, mut record: Record) -> Record {
+        match *self {
+            Transform::Renumber { ref mut next, ref mut numbers } => {
+                numbers.insert(record.num, *next);
+                record.num = *next;
+                *next = next.saturating_add(1);
+                record.parent = record.parent.iter().filter_map(|n| numbers.get(n).cloned()).collect();
+                record.children = record.children.iter().filter_map(|n| numbers.get(n).cloned()).collect();
+            },
+            Transform::RetentionTime { offset, scale } => {
+                record.rt = record.rt * scale + offset;
+            },
+            Transform::ParentMz { ppm } => {
+                record.parent_mz *= 1.0 + ppm * 1e-6;
+            },
+        }
+        record
+    }
+}
+
+// ITERATOR
+
+/// Iterator which transforms each spectral record.
+pub struct TransformIter<U: Iterator<Item = Result<Record>>> {
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Transform applied to each record.
+    transform: Transform,
+}
+
+impl<U: Iterator<Item = Result<Record>>> TransformIter<U> {
+    /// Create new TransformIter from an iterator and transform.
+    #[inline]
+    pub fn new(iter: U, transform: Transform) -> Self {
+        TransformIter {
+            iter: iter,
+            transform: transform,
+        }
+    }
+}
+
+impl<U: Iterator<Item = Result<Record>>> Iterator for TransformIter<U> {
+    type Item = Result<Record>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let transform = &mut self.transform;
+        Some(self.iter.next()?.map(|r| transform.apply(r)))
+    }
+}
+
+/// Adaptors for iterators over spectral records, to transform records
+/// between readers and writers. Errors are left unchanged.
+pub trait SpectraIterExt: Iterator<Item = Result<Record>> + Sized {
+    /// Renumber scans sequentially from a start number.
+    #[inline]
+    fn renumbered(self, start: u32) -> TransformIter<Self> {
+        TransformIter::new(self, Transform::renumber(start))
+    }
+
+    /// Recalibrate retention times, as `rt * scale + offset`.
+    #[inline]
+    fn rt_recalibrated(self, offset: f64, scale: f64) -> TransformIter<Self> {
+        TransformIter::new(self, Transform::retention_time(offset, scale))
+    }
+
+    /// Recalibrate parent m/z values by a ppm shift.
+    #[inline]
+    fn parent_mz_recalibrated(self, ppm: f64) -> TransformIter<Self> {
+        TransformIter::new(self, Transform::parent_mz(ppm))
+    }
+
+    /// Correct precursors of MS2 or higher scans from the preceding MS1 scan.
+    #[inline]
+    fn precursor_corrected(self, options: PrecursorOptions) -> PrecursorCorrectionIter<Self> {
+        PrecursorCorrectionIter::new(self, options)
+    }
+}
+
+impl<U: Iterator<Item = Result<Record>>> SpectraIterExt for U {
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::mass::{mz_to_neutral_mass, Adduct};
+    use util::ErrorKind;
+    use super::*;
+    use super::super::test::*;
+
+    fn scan(num: u32, parent: Vec<u32>) -> Result<Record> {
+        let mut record = mgf_empty();
+        record.num = num;
+        record.parent = parent;
+        Ok(record)
+    }
+
+    #[test]
+    fn renumber_test() {
+        let v = vec![scan(50, vec![]), scan(52, vec![50]), Err(From::from(ErrorKind::InvalidInput)), scan(60, vec![40])];
+        let v: Vec<Result<Record>> = v.into_iter().renumbered(1).collect();
+        assert_eq!(v[0].as_ref().unwrap().num, 1);
+        assert_eq!(v[1].as_ref().unwrap().num, 2);
+        assert_eq!(v[1].as_ref().unwrap().parent, vec![1]);
+        assert!(v[2].is_err());
+        assert_eq!(v[3].as_ref().unwrap().num, 3);
+        assert!(v[3].as_ref().unwrap().parent.is_empty());
+    }
+
+    #[test]
+    fn recalibrate_test() {
+        let v = vec![scan(50, vec![])];
+        let x = v.into_iter()
+            .rt_recalibrated(8.0, 0.5)
+            .parent_mz_recalibrated(10.0)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(x.rt, 4354.0);
+        assert_approx_eq!(x.parent_mz, 775.1640015625, 1e-8);
+
+        // The neutral mass follows the recalibrated m/z.
+        let mut record = mgf_empty();
+        record.adduct = Some(Adduct::Sodium);
+        let x = Transform::parent_mz(10.0).apply(record);
+        assert_eq!(x.parent_mass(), mz_to_neutral_mass(x.parent_mz, 4, Adduct::Sodium));
+
+        // Apply the transform directly.
+        let mut transform = Transform::retention_time(-10.0, 1.0);
+        assert_eq!(transform.apply(mgf_empty()).rt, 8682.0);
+    }
+}
@@ -1,34 +1,143 @@
 //! Valid trait implementation for mass spectral models.
 
+use std::fmt;
+
 use traits::Valid;
 use super::record::Record;
 use super::record_list::RecordList;
 
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Individual validation rule violated by a spectral record.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValidationIssue {
+    /// Scan number is not set.
+    MissingScanNumber,
+    /// Retention time is negative or not a number.
+    InvalidRetentionTime,
+    /// Spectrum has no peaks.
+    EmptyPeaks,
+    /// Peak m/z is less than the previous peak m/z, at a peak index.
+    UnsortedPeak(usize),
+    /// Peak intensity is negative or not a number, at a peak index.
+    NegativeIntensity(usize),
+    /// Parent ion fields are inconsistent with the MS level.
+    ///
+    /// MS2 or higher scans require the parent m/z, intensity and charge,
+    /// while MS1 scans must not set them.
+    InconsistentParent,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValidationIssue::MissingScanNumber    => write!(f, "scan number is not set"),
+            ValidationIssue::InvalidRetentionTime => write!(f, "retention time is negative or not a number"),
+            ValidationIssue::EmptyPeaks           => write!(f, "spectrum has no peaks"),
+            ValidationIssue::UnsortedPeak(i)      => write!(f, "peak {} is not sorted by m/z", i),
+            ValidationIssue::NegativeIntensity(i) => write!(f, "peak {} has a negative intensity", i),
+            ValidationIssue::InconsistentParent   => write!(f, "parent ion is inconsistent with the MS level"),
+        }
+    }
+}
+
+/// Detailed report of all validation rules violated by a spectral record.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// Violated rules, in order of detection.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Check if the record passed all validation rules.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Check the parent fields are consistent with the MS level.
+#[inline]
+fn is_parent_consistent(record: &Record) -> bool {
+    match record.ms_level {
+        // Unknown MS level, any parent fields are valid.
+        0 => true,
+        1 => {
+            record.parent_mz == 0.0 &&
+                record.parent_intensity == 0.0 &&
+                record.parent_z == 0
+        },
+        _ => {
+            record.parent_mz != 0.0 &&
+                record.parent_intensity > 0.0 &&
+                record.parent_z != 0
+        },
+    }
+}
+
+/// Find the validation rules violated by a record.
+///
+/// If `exhaustive` is false, stop at the first violated rule.
+fn find_issues(record: &Record, exhaustive: bool, issues: &mut Vec<ValidationIssue>) {
+    macro_rules! push {
+        ($issue:expr) => ({
+            issues.push($issue);
+            if !exhaustive {
+                return;
+            }
+        });
+    }
+
+    if record.num == 0 {
+        push!(ValidationIssue::MissingScanNumber);
+    }
+    if !(record.rt >= 0.0) {
+        push!(ValidationIssue::InvalidRetentionTime);
+    }
+    if record.peaks.is_empty() {
+        push!(ValidationIssue::EmptyPeaks);
+    }
+    if !is_parent_consistent(record) {
+        push!(ValidationIssue::InconsistentParent);
+    }
+
+    let mut previous: Option<f64> = None;
+    for (index, peak) in record.peaks.iter().enumerate() {
+        if let Some(mz) = previous {
+            if !(peak.mz >= mz) {
+                push!(ValidationIssue::UnsortedPeak(index));
+            }
+        }
+        if !(peak.intensity >= 0.0) {
+            push!(ValidationIssue::NegativeIntensity(index));
+        }
+        previous = Some(peak.mz);
+    }
+}
+
+impl Record {
+    /// Create a detailed report of the validation rules the record violates.
+    ///
+    /// The record is valid, and accepted by strict writers, only if
+    /// the report contains no issues.
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues = vec![];
+        find_issues(self, true, &mut issues);
+        ValidationReport { issues: issues }
+    }
+}
+
 impl Valid for Record {
+    #[inline]
     fn is_valid(&self) -> bool {
-        (
-            self.num != 0 &&
-            self.rt != 0.0 &&
-            !self.peaks.is_empty() &&
-            // If the MS level is 2 or higher, check the parents are set.
-            (
-                (
-                    self.ms_level >= 2 &&
-                    self.parent_mz != 0.0 &&
-                    self.parent_intensity > 0.0 &&
-                    self.parent_z != 0
-                )
-                ||
-                (
-                    self.ms_level == 1 &&
-                    self.parent_mz == 0.0 &&
-                    self.parent_intensity == 0.0 &&
-                    self.parent_z == 0
-                )
-                ||
-                self.ms_level == 0
-            )
-        )
+        let mut issues = vec![];
+        find_issues(self, false, &mut issues);
+        issues.is_empty()
+    }
+
+    fn violations(&self) -> Vec<String> {
+        self.validate().issues.iter().map(|x| x.to_string()).collect()
     }
 }
 
@@ -38,3 +147,109 @@ impl Valid for RecordList {
         self.iter().all(|ref x| x.is_valid())
     }
 }
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::peak::Peak;
+    use super::super::peak_list::PeakListExt;
+    use super::super::test::*;
+
+    #[test]
+    fn validate_test() {
+        let r = mgf_33450();
+        assert!(r.validate().is_valid());
+        assert!(r.is_valid());
+
+        let r = mgf_empty();
+        assert_eq!(r.validate().issues, vec![ValidationIssue::EmptyPeaks]);
+        assert!(!r.is_valid());
+    }
+
+    #[test]
+    fn validate_rt_test() {
+        let mut r = mgf_33450();
+        r.rt = -1.0;
+        assert_eq!(r.validate().issues, vec![ValidationIssue::InvalidRetentionTime]);
+        r.rt = ::std::f64::NAN;
+        assert_eq!(r.validate().issues, vec![ValidationIssue::InvalidRetentionTime]);
+        r.rt = 0.0;
+        assert!(r.is_valid());
+    }
+
+    #[test]
+    fn validate_peaks_test() {
+        let mut r = mgf_33450();
+        r.peaks.swap(3, 4);
+        r.peaks[10].intensity = -1.0;
+        r.peaks.push(Peak { mz: 100.0, intensity: 1.0, z: 0, mobility: None });
+        assert_eq!(r.validate().issues, vec![
+            ValidationIssue::UnsortedPeak(4),
+            ValidationIssue::NegativeIntensity(10),
+            ValidationIssue::UnsortedPeak(69),
+        ]);
+        assert!(!r.is_valid());
+
+        // equal m/z values are sorted, consistent with `is_sorted_by_mz`
+        let mut r = mgf_33450();
+        let peak = r.peaks[3].clone();
+        r.peaks.insert(3, peak);
+        assert!(r.peaks.is_sorted_by_mz());
+        assert!(r.is_valid());
+    }
+
+    #[test]
+    fn validate_parent_test() {
+        let mut r = mgf_33450();
+        r.ms_level = 2;
+        assert!(r.is_valid());
+        r.parent_z = 0;
+        assert_eq!(r.validate().issues, vec![ValidationIssue::InconsistentParent]);
+
+        r.ms_level = 1;
+        assert_eq!(r.validate().issues, vec![ValidationIssue::InconsistentParent]);
+        r.parent_mz = 0.0;
+        r.parent_intensity = 0.0;
+        assert!(r.is_valid());
+    }
+
+    #[test]
+    fn validate_exhaustive_test() {
+        let mut r = mgf_empty();
+        r.num = 0;
+        r.rt = -1.0;
+        r.ms_level = 2;
+        r.parent_z = 0;
+        assert_eq!(r.validate().issues, vec![
+            ValidationIssue::MissingScanNumber,
+            ValidationIssue::InvalidRetentionTime,
+            ValidationIssue::EmptyPeaks,
+            ValidationIssue::InconsistentParent,
+        ]);
+    }
+
+    #[cfg(feature = "mgf")]
+    #[test]
+    fn validate_strict_writer_test() {
+        use traits::MgfKind;
+        use super::super::mgf::*;
+
+        let mut r = mgf_33450();
+        let mut w = vec![];
+        assert!(reference_iterator_to_mgf_strict(&mut w, [r.clone()].iter(), MgfKind::Pava).is_ok());
+
+        r.peaks.swap(3, 4);
+        let mut w = vec![];
+        let err = reference_iterator_to_mgf_strict(&mut w, [mgf_33450(), r.clone()].iter(), MgfKind::Pava).err().unwrap();
+        assert_eq!(err.record_index(), Some(1));
+        assert_eq!(err.violations(), Some(&[String::from("peak 4 is not sorted by m/z")][..]));
+        let mut x = vec![];
+        reference_iterator_to_mgf_strict(&mut x, [mgf_33450()].iter(), MgfKind::Pava).unwrap();
+        assert_eq!(w, x);
+        let mut w = vec![];
+        assert!(reference_iterator_to_mgf_lenient(&mut w, [r].iter(), MgfKind::Pava).is_ok());
+    }
+}
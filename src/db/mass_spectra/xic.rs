@@ -0,0 +1,57 @@
+//! Extracted ion chromatograms from MS1 scans.
+
+use bio::mass::Tolerance;
+use super::peak_list::PeakListExt;
+use super::record::Record;
+
+/// Check if the record is a survey (MS1) scan.
+///
+/// Scans with an unknown MS level are survey scans if they have no
+/// parent ion, such as scans from FullMs MGF files.
+#[inline]
+fn is_survey_scan(record: &Record) -> bool {
+    match record.ms_level {
+        0 => record.parent_mz == 0.0,
+        1 => true,
+        _ => false,
+    }
+}
+
+/// Extract the ion chromatogram for an m/z, as `(rt, intensity)` pairs.
+///
+/// The intensity of each survey scan is the summed intensity of the
+/// peaks within the tolerance of the m/z, or 0 if there are none.
+/// MS2 or higher scans are skipped. Requires peaks sorted by m/z.
+pub fn extract_ion_chromatogram<'a, Iter>(iter: Iter, mz: f64, tol: Tolerance) -> Vec<(f64, f64)>
+    where Iter: Iterator<Item = &'a Record>
+{
+    let (lo, hi) = tol.range(mz);
+    iter.filter(|r| is_survey_scan(r))
+        .map(|r| (r.rt, r.peaks.slice_mz_range(lo, hi).iter().map(|p| p.intensity).sum()))
+        .collect()
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn extract_ion_chromatogram_test() {
+        let x = fullms_mgf_33450();
+        let mut y = x.clone();
+        y.rt = 8700.0;
+        y.peaks.retain(|p| p.mz < 288.0);
+        let z = mgf_33450();
+
+        let records = vec![x, z, y];
+        let xic = extract_ion_chromatogram(records.iter(), 288.2038, Tolerance::Ppm(5.0));
+        assert_eq!(xic, vec![(8692.0, 1740.2529296875), (8700.0, 0.0)]);
+
+        let xic = extract_ion_chromatogram(records.iter(), 288.2, Tolerance::Da(0.002));
+        assert_approx_eq!(xic[0].1, 1173.0286865234 + 1705.58203125, 1e-6);
+    }
+}
@@ -1,16 +1,37 @@
 //! Database integrations and utilities.
 
+#[cfg(feature = "alignment_msa")]
+pub mod alignment_msa;
+
+#[cfg(feature = "hmmer")]
+pub mod hmmer;
+
+#[cfg(feature = "interpro")]
+pub mod interpro;
+
 #[cfg(feature = "mass_spectrometry")]
 pub mod mass_spectra;
 
 #[cfg(feature = "mass_spectrometry")]
 pub mod peptide_search_matches;
 
+#[cfg(feature = "nucleotide")]
+pub mod nucleotide;
+
 #[cfg(feature = "pdb")]
 pub mod pdb;
 
+#[cfg(feature = "proteoforms")]
+pub mod proteoforms;
+
+#[cfg(feature = "sdrf")]
+pub mod sdrf;
+
 #[cfg(feature = "sra")]
 pub mod sra;
 
+#[cfg(feature = "transitions")]
+pub mod transitions;
+
 #[cfg(feature = "uniprot")]
 pub mod uniprot;
@@ -0,0 +1,325 @@
+//! Genome assembly statistics and contig chunking.
+//!
+//! Statistics are accumulated contig-by-contig, and only store the
+//! contig lengths, so they may be computed lazily from the FASTA
+//! iterators of a large assembly.
+
+#[cfg(feature = "fasta")]
+use std::io::BufRead;
+
+use util::Result;
+use super::record::Record;
+
+/// Summary statistics for a genome assembly.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AssemblyStats {
+    /// Length of each contig, in the order the contigs were added.
+    pub lengths: Vec<usize>,
+    /// Number of unambiguous (`ACGTU`) nucleotides.
+    pub unambiguous_count: usize,
+    /// Number of `G` or `C` nucleotides.
+    pub gc_count: usize,
+}
+
+impl AssemblyStats {
+    /// Create new, empty assembly statistics.
+    #[inline]
+    pub fn new() -> Self {
+        AssemblyStats {
+            lengths: vec![],
+            unambiguous_count: 0,
+            gc_count: 0,
+        }
+    }
+
+    /// Add a contig to the statistics.
+    pub fn add(&mut self, record: &Record) {
+        self.lengths.push(record.sequence.len());
+        for &c in record.sequence.iter() {
+            match c.to_ascii_uppercase() {
+                b'G' | b'C'        => {
+                    self.gc_count += 1;
+                    self.unambiguous_count += 1;
+                },
+                b'A' | b'T' | b'U' => self.unambiguous_count += 1,
+                _                  => (),
+            }
+        }
+    }
+
+    /// Calculate assembly statistics from an iterator over contigs.
+    pub fn from_records<'a, Iter>(iter: Iter) -> Self
+        where Iter: Iterator<Item = &'a Record>
+    {
+        let mut stats = AssemblyStats::new();
+        for record in iter {
+            stats.add(record);
+        }
+        stats
+    }
+
+    /// Calculate assembly statistics from a nucleotide FASTA stream.
+    ///
+    /// Each contig is dropped after it is added, so the memory
+    /// footprint is bounded by the largest contig.
+    #[cfg(feature = "fasta")]
+    pub fn from_fasta<T: BufRead>(reader: T) -> Result<Self> {
+        let mut stats = AssemblyStats::new();
+        for record in super::fasta::iterator_from_fasta(reader) {
+            stats.add(&record?);
+        }
+        Ok(stats)
+    }
+
+    /// Get the number of contigs.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Get the total length of all contigs.
+    #[inline]
+    pub fn total_length(&self) -> usize {
+        self.lengths.iter().sum()
+    }
+
+    /// Get the length of the shortest contig.
+    #[inline]
+    pub fn min_length(&self) -> Option<usize> {
+        self.lengths.iter().cloned().min()
+    }
+
+    /// Get the length of the longest contig.
+    #[inline]
+    pub fn max_length(&self) -> Option<usize> {
+        self.lengths.iter().cloned().max()
+    }
+
+    /// Get the GC content, as a fraction of the unambiguous nucleotides.
+    ///
+    /// Returns `None` if there are no unambiguous nucleotides.
+    #[inline]
+    pub fn gc_content(&self) -> Option<f64> {
+        match self.unambiguous_count {
+            0 => None,
+            n => Some(self.gc_count as f64 / n as f64),
+        }
+    }
+
+    /// Get the Nx and Lx statistics for a percentage `x` in `(0, 100]`.
+    ///
+    /// Nx is the length of the shortest contig such that contigs of at
+    /// least that length cover `x` percent of the assembly, and Lx is
+    /// the number of those contigs.
+    fn nx_lx(&self, x: f64) -> Option<(usize, usize)> {
+        let total = self.total_length();
+        if total == 0 || !(x > 0.0 && x <= 100.0) {
+            return None;
+        }
+
+        let mut lengths = self.lengths.clone();
+        lengths.sort_unstable_by(|a, b| b.cmp(a));
+        let threshold = total as f64 * x / 100.0;
+        let mut sum = 0;
+        for (i, &length) in lengths.iter().enumerate() {
+            sum += length;
+            if sum as f64 >= threshold {
+                return Some((length, i + 1));
+            }
+        }
+        None
+    }
+
+    /// Get the Nx statistic for a percentage `x` in `(0, 100]`.
+    ///
+    /// Returns `None` for an empty assembly or an invalid percentage.
+    #[inline]
+    pub fn nx(&self, x: f64) -> Option<usize> {
+        self.nx_lx(x).map(|v| v.0)
+    }
+
+    /// Get the Lx statistic for a percentage `x` in `(0, 100]`.
+    ///
+    /// Returns `None` for an empty assembly or an invalid percentage.
+    #[inline]
+    pub fn lx(&self, x: f64) -> Option<usize> {
+        self.nx_lx(x).map(|v| v.1)
+    }
+
+    /// Get the N50 contig length.
+    #[inline]
+    pub fn n50(&self) -> Option<usize> {
+        self.nx(50.0)
+    }
+
+    /// Get the L50 contig count.
+    #[inline]
+    pub fn l50(&self) -> Option<usize> {
+        self.lx(50.0)
+    }
+}
+
+// SPLIT
+
+/// Split a contig into chunks of at most `max_length` nucleotides.
+///
+/// Each chunk is identified by the region it covers, with 1-based,
+/// inclusive positions, for example, `"chr1:1-1000"`. Contigs no
+/// longer than `max_length` are returned unchanged.
+pub fn split_contig(record: &Record, max_length: usize) -> Vec<Record> {
+    if max_length == 0 || record.sequence.len() <= max_length {
+        return vec![record.clone()];
+    }
+
+    record.sequence.chunks(max_length)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let start = i * max_length + 1;
+            Record {
+                id: format!("{}:{}-{}", record.id, start, start + chunk.len() - 1),
+                description: record.description.clone(),
+                sequence: chunk.to_vec(),
+            }
+        })
+        .collect()
+}
+
+/// Split each contig into chunks of at most `max_length` nucleotides.
+pub fn split_contigs<'a, Iter>(iter: Iter, max_length: usize) -> Vec<Record>
+    where Iter: Iterator<Item = &'a Record>
+{
+    iter.flat_map(|r| split_contig(r, max_length)).collect()
+}
+
+// MERGE
+
+/// Create a merged contig from the members.
+fn merge_group(prefix: &str, index: usize, group: &[&Record], gap: usize) -> Record {
+    let mut sequence = vec![];
+    for (i, record) in group.iter().enumerate() {
+        if i > 0 {
+            sequence.extend(::std::iter::repeat(b'N').take(gap));
+        }
+        sequence.extend_from_slice(&record.sequence);
+    }
+
+    let ids: Vec<&str> = group.iter().map(|r| r.id.as_str()).collect();
+    Record {
+        id: format!("{}{}", prefix, index),
+        description: ids.join(" "),
+        sequence: sequence,
+    }
+}
+
+/// Merge consecutive contigs into sequences of at most `max_length`.
+///
+/// Contigs are joined by `gap` unknown (`N`) nucleotides. Each merged
+/// sequence is identified by the prefix and a 1-based index, with the
+/// member identifiers as the description. Contigs longer than
+/// `max_length` form their own sequence.
+pub fn merge_contigs<'a, Iter>(iter: Iter, prefix: &str, max_length: usize, gap: usize)
+    -> Vec<Record>
+    where Iter: Iterator<Item = &'a Record>
+{
+    let mut merged = vec![];
+    let mut group: Vec<&Record> = vec![];
+    let mut length = 0;
+    for record in iter {
+        if !group.is_empty() && length + gap + record.sequence.len() > max_length {
+            merged.push(merge_group(prefix, merged.len() + 1, &group, gap));
+            group.clear();
+            length = 0;
+        }
+        length += record.sequence.len() + if group.is_empty() { 0 } else { gap };
+        group.push(record);
+    }
+    if !group.is_empty() {
+        merged.push(merge_group(prefix, merged.len() + 1, &group, gap));
+    }
+
+    merged
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    fn contig(id: &str, sequence: &[u8]) -> Record {
+        Record {
+            id: String::from(id),
+            description: String::new(),
+            sequence: sequence.to_vec(),
+        }
+    }
+
+    #[test]
+    fn assembly_stats_test() {
+        let v = vec![
+            contig("c1", b"GGGGGCCCCC"),
+            contig("c2", b"AAAAATTTTTAAAAATTTTTAAAAATTTTTAAAAATTTTT"),
+            contig("c3", b"ACGTNNNNNN"),
+            contig("c4", b"ACGTACGTACGTACGTACGT"),
+        ];
+        let stats = AssemblyStats::from_records(v.iter());
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.total_length(), 80);
+        assert_eq!(stats.min_length(), Some(10));
+        assert_eq!(stats.max_length(), Some(40));
+        assert_eq!(stats.n50(), Some(40));
+        assert_eq!(stats.l50(), Some(1));
+        assert_eq!(stats.nx(90.0), Some(10));
+        assert_eq!(stats.lx(90.0), Some(4));
+        assert_eq!(stats.nx(0.0), None);
+        assert_eq!(stats.gc_content(), Some(22.0 / 74.0));
+
+        let empty = AssemblyStats::new();
+        assert_eq!(empty.n50(), None);
+        assert_eq!(empty.gc_content(), None);
+    }
+
+    #[cfg(feature = "fasta")]
+    #[test]
+    fn assembly_stats_fasta_test() {
+        let stats = AssemblyStats::from_fasta(GAPDH_CDS_MASKED_FASTA).unwrap();
+        let expected = AssemblyStats::from_records(vec![gapdh_cds(), masked()].iter());
+        assert_eq!(stats, expected);
+        assert_eq!(stats.n50(), Some(118));
+    }
+
+    #[test]
+    fn split_contig_test() {
+        let x = contig("chr1", b"ACGTACGTAC");
+        let v = split_contig(&x, 4);
+        let ids: Vec<&str> = v.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["chr1:1-4", "chr1:5-8", "chr1:9-10"]);
+        assert_eq!(v[2].sequence, b"AC".to_vec());
+        assert_eq!(split_contig(&x, 10), vec![x.clone()]);
+
+        let v = split_contigs(vec![x.clone(), masked()].iter(), 8);
+        assert_eq!(v.len(), 4);
+        assert_eq!(v[3].id, "chrUn_1:9-13");
+    }
+
+    #[test]
+    fn merge_contigs_test() {
+        let v = vec![
+            contig("c1", b"AAAA"),
+            contig("c2", b"CCCC"),
+            contig("c3", b"GGGGGGGGGGGG"),
+            contig("c4", b"TT"),
+        ];
+        let merged = merge_contigs(v.iter(), "bin", 10, 2);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0], Record {
+            id: String::from("bin1"),
+            description: String::from("c1 c2"),
+            sequence: b"AAAANNCCCC".to_vec(),
+        });
+        assert_eq!(merged[1].description, "c3");
+        assert_eq!(merged[2].sequence, b"TT".to_vec());
+    }
+}
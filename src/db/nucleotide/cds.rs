@@ -0,0 +1,402 @@
+//! Translation of coding sequence features into protein records.
+//!
+//! Coding sequence (CDS) features split over multiple exons share
+//! an identifier, and are spliced in transcription order before
+//! translation. The translated proteins are emitted as UniProt
+//! records, connecting genome annotations to protein workflows.
+//! Protein positions, such as identified peptides, may be mapped
+//! back to genomic coordinates, and vice versa.
+
+use std::collections::HashMap;
+
+use bio::dna::{self, GeneticCode};
+use bio::dna::codon::{back_translate, CodonOptions, CodonUsage};
+use bio::proteins::ProteinMassTable;
+use db::uniprot::{self, ProteinEvidence};
+use util::{ErrorKind, Result};
+use super::feature::{Feature, Strand};
+use super::record::Record;
+
+/// Coding sequence assembled from one or more CDS features.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodingSequence {
+    /// Identifier shared by the CDS features.
+    pub id: String,
+    /// Identifier of the annotated sequence.
+    pub seqid: String,
+    /// Strand of the coding sequence.
+    pub strand: Strand,
+    /// 1-based, inclusive `(start, end)` positions of each segment, sorted by start.
+    pub segments: Vec<(u32, u32)>,
+    /// Phase of the first segment in transcription order.
+    pub phase: u8,
+    /// Gene name, from the `gene` attribute.
+    pub gene: String,
+    /// Protein name, from the `product` attribute.
+    pub product: String,
+    /// Protein identifier, from the `protein_id` attribute.
+    pub protein_id: String,
+}
+
+impl CodingSequence {
+    /// Extract the spliced coding sequence from the annotated sequence.
+    ///
+    /// Reverse-strand coding sequences are reverse complemented, and
+    /// the nucleotides before the first complete codon are skipped.
+    pub fn splice(&self, record: &Record) -> Result<Vec<u8>> {
+        bool_to_error!(record.id == self.seqid, InvalidInput);
+
+        let mut sequence = vec![];
+        for &(start, end) in self.segments.iter() {
+            let (start, end) = (start as usize, end as usize);
+            bool_to_error!(start > 0 && start <= end && end <= record.sequence.len(), InvalidInput);
+            sequence.extend_from_slice(&record.sequence[start-1..end]);
+        }
+        if self.strand == Strand::Reverse {
+            sequence = dna::reverse_complement(&sequence);
+        }
+
+        let phase = (self.phase as usize).min(sequence.len());
+        Ok(sequence.split_off(phase))
+    }
+
+    /// Translate the spliced coding sequence using the genetic code.
+    ///
+    /// A trailing stop codon is removed, while internal stop codons
+    /// are kept as "*".
+    pub fn translate(&self, record: &Record, code: GeneticCode) -> Result<Vec<u8>> {
+        let mut protein = code.translate(&self.splice(record)?);
+        if protein.last() == Some(&b'*') {
+            protein.pop();
+        }
+        Ok(protein)
+    }
+
+    /// Translate the coding sequence to a UniProt protein record.
+    ///
+    /// The record is identified by the protein identifier, or the CDS
+    /// identifier if there is none, and has predicted protein evidence.
+    pub fn to_protein(&self, record: &Record, code: GeneticCode) -> Result<uniprot::Record> {
+        let sequence = self.translate(record, code)?;
+        let mut protein = uniprot::Record::new();
+        protein.protein_evidence = ProteinEvidence::Predicted;
+        protein.mass = ProteinMassTable::average().total_sequence_mass(&sequence).round() as u64;
+        protein.length = sequence.len() as u32;
+        protein.gene = self.gene.clone();
+        protein.id = match self.protein_id.is_empty() {
+            true  => self.id.clone(),
+            false => self.protein_id.clone(),
+        };
+        protein.name = self.product.clone();
+        protein.sequence = sequence;
+        Ok(protein)
+    }
+
+    // COORDINATES
+
+    /// Get the segments in transcription order.
+    fn transcribed_segments(&self) -> Vec<(u32, u32)> {
+        let mut segments = self.segments.clone();
+        if self.strand == Strand::Reverse {
+            segments.reverse();
+        }
+        segments
+    }
+
+    /// Get the length of the spliced coding sequence, including the phase.
+    #[inline]
+    pub fn spliced_length(&self) -> u32 {
+        self.segments.iter().map(|&(start, end)| end - start + 1).sum()
+    }
+
+    /// Get the number of complete codons in the coding sequence.
+    #[inline]
+    pub fn codon_count(&self) -> u32 {
+        self.spliced_length().saturating_sub(self.phase as u32) / 3
+    }
+
+    /// Map a 1-based position in the spliced coding sequence to the genome.
+    ///
+    /// Spliced positions are in transcription order, and include the
+    /// nucleotides skipped by the phase.
+    pub fn spliced_to_genome(&self, position: u32) -> Option<u32> {
+        let mut offset = position.checked_sub(1)?;
+        for (start, end) in self.transcribed_segments() {
+            let length = end - start + 1;
+            if offset < length {
+                return match self.strand {
+                    Strand::Reverse => Some(end - offset),
+                    _               => Some(start + offset),
+                };
+            }
+            offset -= length;
+        }
+        None
+    }
+
+    /// Map a genomic position to a 1-based position in the spliced coding sequence.
+    ///
+    /// Returns `None` if the position is not within a segment.
+    pub fn genome_to_spliced(&self, position: u32) -> Option<u32> {
+        let mut offset = 0;
+        for (start, end) in self.transcribed_segments() {
+            if position >= start && position <= end {
+                return match self.strand {
+                    Strand::Reverse => Some(offset + end - position + 1),
+                    _               => Some(offset + position - start + 1),
+                };
+            }
+            offset += end - start + 1;
+        }
+        None
+    }
+
+    /// Map a genomic position to the protein position and codon position.
+    ///
+    /// Returns the 1-based aminoacid position, and the 1-based position
+    /// of the nucleotide within the codon, or `None` if the position is
+    /// not within a complete codon.
+    pub fn genome_to_protein(&self, position: u32) -> Option<(u32, u8)> {
+        let spliced = self.genome_to_spliced(position)?;
+        let offset = spliced.checked_sub(self.phase as u32 + 1)?;
+        let residue = offset / 3 + 1;
+        match residue <= self.codon_count() {
+            true  => Some((residue, (offset % 3) as u8 + 1)),
+            false => None,
+        }
+    }
+
+    /// Map a 1-based, inclusive range of aminoacids to genomic ranges.
+    ///
+    /// Codons spanning an intron are split over multiple ranges. The
+    /// ranges are 1-based, inclusive, and sorted by genomic position.
+    /// Returns `None` if the range is not within the translated protein.
+    pub fn protein_to_genome(&self, first: u32, last: u32) -> Option<Vec<(u32, u32)>> {
+        if first == 0 || last < first || last > self.codon_count() {
+            return None;
+        }
+
+        // Convert to the 1-based, inclusive range of spliced positions.
+        let lo = self.phase as u32 + 3 * (first - 1) + 1;
+        let hi = self.phase as u32 + 3 * last;
+        let mut ranges = vec![];
+        let mut offset = 0;
+        for (start, end) in self.transcribed_segments() {
+            let length = end - start + 1;
+            let (a, b) = (lo.max(offset + 1), hi.min(offset + length));
+            if a <= b {
+                let range = match self.strand {
+                    Strand::Reverse => (end - (b - offset - 1), end - (a - offset - 1)),
+                    _               => (start + (a - offset - 1), start + (b - offset - 1)),
+                };
+                ranges.push(range);
+            }
+            offset += length;
+        }
+        ranges.sort();
+
+        Some(ranges)
+    }
+}
+
+/// Create new coding sequence from the first CDS feature.
+fn new_coding_sequence(id: &str, feature: &Feature) -> CodingSequence {
+    let attribute = |tag| String::from(feature.attribute(tag).unwrap_or(""));
+    CodingSequence {
+        id: String::from(id),
+        seqid: feature.seqid.clone(),
+        strand: feature.strand,
+        segments: vec![],
+        phase: 0,
+        gene: attribute("gene"),
+        product: attribute("product"),
+        protein_id: attribute("protein_id"),
+    }
+}
+
+/// Group the CDS features into coding sequences.
+///
+/// CDS features are grouped by the `ID` attribute, or the `Parent`
+/// attribute if they have no identifier. Other features are ignored.
+/// Coding sequences are returned in the order they first appear.
+pub fn coding_sequences<'a, Iter>(iter: Iter) -> Vec<CodingSequence>
+    where Iter: Iterator<Item = &'a Feature>
+{
+    let mut cds: Vec<CodingSequence> = vec![];
+    let mut phases: Vec<Vec<(u32, u8)>> = vec![];
+    let mut indexes: HashMap<String, usize> = HashMap::new();
+    for feature in iter.filter(|f| f.kind == "CDS") {
+        let id = feature.attribute("ID")
+            .or(feature.attribute("Parent"))
+            .map(String::from)
+            .unwrap_or_else(|| format!("{}:{}-{}", feature.seqid, feature.start, feature.end));
+        let index = *indexes.entry(id.clone()).or_insert(cds.len());
+        if index == cds.len() {
+            cds.push(new_coding_sequence(&id, feature));
+            phases.push(vec![]);
+        }
+        cds[index].segments.push((feature.start, feature.end));
+        phases[index].push((feature.start, feature.phase.unwrap_or(0)));
+    }
+
+    // Sort the segments and find the phase of the 5' segment.
+    for (c, p) in cds.iter_mut().zip(phases.iter()) {
+        c.segments.sort();
+        let first = match c.strand {
+            Strand::Reverse => p.iter().max_by_key(|v| v.0),
+            _               => p.iter().min_by_key(|v| v.0),
+        };
+        c.phase = first.map(|v| v.1).unwrap_or(0);
+    }
+
+    cds
+}
+
+/// Translate the CDS features of the annotated sequences to proteins.
+///
+/// Fails with `InvalidInput` if a coding sequence is annotated on a
+/// missing sequence, or extends past the end of the sequence.
+pub fn translate_coding_sequences<'a, Iter>(records: &[Record], features: Iter, code: GeneticCode)
+    -> Result<Vec<uniprot::Record>>
+    where Iter: Iterator<Item = &'a Feature>
+{
+    let map: HashMap<&str, &Record> = records.iter()
+        .map(|r| (r.id.as_str(), r))
+        .collect();
+
+    coding_sequences(features).iter()
+        .map(|c| {
+            let record = none_to_error!(map.get(c.seqid.as_str()), InvalidInput);
+            c.to_protein(record, code)
+        })
+        .collect()
+}
+
+/// Back-translate a protein record into a codon-optimized coding sequence.
+///
+/// The coding sequence is identified by the protein accession, and
+/// ends with the preferred stop codon of the host.
+pub fn codon_optimize(protein: &uniprot::Record, usage: &CodonUsage, options: &CodonOptions)
+    -> Result<Record>
+{
+    let mut sequence = protein.sequence.clone();
+    if sequence.last() != Some(&b'*') {
+        sequence.push(b'*');
+    }
+
+    Ok(Record {
+        id: protein.id.clone(),
+        description: protein.name.clone(),
+        sequence: back_translate(&sequence, usage, options)?,
+    })
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use super::super::gff::iterator_from_gff_strict;
+    use super::super::test::*;
+
+    fn features() -> Vec<Feature> {
+        iterator_from_gff_strict(Cursor::new(SPLICED_GFF))
+            .collect::<Result<Vec<Feature>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn coding_sequences_test() {
+        let cds = coding_sequences(features().iter());
+        assert_eq!(cds.len(), 2);
+        assert_eq!(cds[0].id, "cds1");
+        assert_eq!(cds[0].segments, vec![(3, 9), (17, 24)]);
+        assert_eq!(cds[0].phase, 0);
+        assert_eq!(cds[0].gene, "ABC1");
+        assert_eq!(cds[1].strand, Strand::Reverse);
+
+        assert_eq!(cds[0].splice(&spliced_contig()).unwrap(), b"ATGGCTAAATGGTAA".to_vec());
+        assert_eq!(cds[1].splice(&reverse_contig()).unwrap(), b"ATGTTTTGA".to_vec());
+        assert!(cds[0].splice(&reverse_contig()).is_err());
+
+        let mut x = cds[0].clone();
+        x.phase = 1;
+        assert_eq!(x.translate(&spliced_contig(), GeneticCode::Standard).unwrap(), b"WLNG".to_vec());
+        assert_eq!(cds[0].translate(&spliced_contig(), GeneticCode::VertebrateMitochondrial).unwrap(), b"MAKW".to_vec());
+    }
+
+    #[test]
+    fn translate_coding_sequences_test() {
+        let records = vec![spliced_contig(), reverse_contig()];
+        let proteins = translate_coding_sequences(&records, features().iter(), GeneticCode::Standard).unwrap();
+        assert_eq!(proteins.len(), 2);
+        assert_eq!(proteins[0].id, "XP_000001.1");
+        assert_eq!(proteins[0].name, "Test protein");
+        assert_eq!(proteins[0].gene, "ABC1");
+        assert_eq!(proteins[0].sequence, b"MAKW".to_vec());
+        assert_eq!(proteins[0].length, 4);
+        assert_eq!(proteins[0].protein_evidence, ProteinEvidence::Predicted);
+        assert_eq!(proteins[1].id, "cds2");
+        assert_eq!(proteins[1].sequence, b"MF".to_vec());
+
+        // Missing sequence.
+        assert!(translate_coding_sequences(&records[..1], features().iter(), GeneticCode::Standard).is_err());
+    }
+
+    #[test]
+    fn codon_optimize_test() {
+        let mut protein = uniprot::Record::new();
+        protein.id = String::from("XP_000001.1");
+        protein.name = String::from("Test protein");
+        protein.sequence = b"MAKW".to_vec();
+
+        let record = codon_optimize(&protein, &CodonUsage::ecoli(), &CodonOptions::default()).unwrap();
+        assert_eq!(record.id, "XP_000001.1");
+        assert_eq!(record.description, "Test protein");
+        assert_eq!(record.sequence, b"ATGGCGAAATGGTAA".to_vec());
+        assert_eq!(record.translate(), b"MAKW*".to_vec());
+
+        protein.sequence = b"MAKB".to_vec();
+        assert!(codon_optimize(&protein, &CodonUsage::ecoli(), &CodonOptions::default()).is_err());
+    }
+
+    #[test]
+    fn coordinates_test() {
+        let cds = coding_sequences(features().iter());
+
+        // Forward strand, with a codon spanning the intron at 9/17.
+        let x = &cds[0];
+        assert_eq!(x.spliced_length(), 15);
+        assert_eq!(x.codon_count(), 5);
+        assert_eq!(x.spliced_to_genome(1), Some(3));
+        assert_eq!(x.spliced_to_genome(8), Some(17));
+        assert_eq!(x.spliced_to_genome(16), None);
+        assert_eq!(x.genome_to_spliced(17), Some(8));
+        assert_eq!(x.genome_to_spliced(12), None);
+        assert_eq!(x.genome_to_protein(3), Some((1, 1)));
+        assert_eq!(x.genome_to_protein(9), Some((3, 1)));
+        assert_eq!(x.genome_to_protein(18), Some((3, 3)));
+        assert_eq!(x.protein_to_genome(2, 2), Some(vec![(6, 8)]));
+        assert_eq!(x.protein_to_genome(3, 4), Some(vec![(9, 9), (17, 21)]));
+        assert_eq!(x.protein_to_genome(0, 1), None);
+        assert_eq!(x.protein_to_genome(5, 6), None);
+
+        // Reverse strand.
+        let y = &cds[1];
+        assert_eq!(y.spliced_to_genome(1), Some(11));
+        assert_eq!(y.genome_to_spliced(3), Some(9));
+        assert_eq!(y.genome_to_protein(11), Some((1, 1)));
+        assert_eq!(y.genome_to_protein(6), Some((2, 3)));
+        assert_eq!(y.protein_to_genome(1, 2), Some(vec![(6, 11)]));
+
+        // Phase.
+        let mut z = x.clone();
+        z.phase = 1;
+        assert_eq!(z.codon_count(), 4);
+        assert_eq!(z.genome_to_protein(3), None);
+        assert_eq!(z.genome_to_protein(4), Some((1, 1)));
+        assert_eq!(z.protein_to_genome(1, 1), Some(vec![(4, 6)]));
+    }
+}
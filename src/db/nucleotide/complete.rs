@@ -0,0 +1,20 @@
+//! Complete trait implementation for nucleotide models.
+
+use traits::{Complete, Valid};
+use super::record::Record;
+use super::record_list::RecordList;
+
+impl Complete for Record {
+    #[inline]
+    fn is_complete(&self) -> bool {
+        self.is_valid() &&
+            !self.description.is_empty()
+    }
+}
+
+impl Complete for RecordList {
+    #[inline]
+    fn is_complete(&self) -> bool {
+        self.iter().all(|ref x| x.is_complete())
+    }
+}
@@ -0,0 +1,456 @@
+//! Helper utilities for nucleotide FASTA loading and saving.
+
+use std::io::prelude::*;
+
+use bio::Alphabet;
+use bio::alphabet::check_nucleotide_sequence;
+use traits::*;
+use util::*;
+use super::record::Record;
+use super::record_list::RecordList;
+
+/// Number of nucleotides per sequence line on export.
+const LINE_LENGTH: usize = 60;
+
+// FASTA ITERATOR
+
+/// Iterator to parse individual FASTA entries from a document.
+///
+/// Convert a stream to a lazy reader that fetches individual FASTA entries
+/// from the document.
+pub struct FastaIter<T: BufRead> {
+    reader: T,
+    buf: Bytes,
+    line: Bytes,
+}
+
+impl<T: BufRead> FastaIter<T> {
+    /// Create new FastaIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        FastaIter::with_capacity(reader, DEFAULT_BUFFER_CAPACITY, DEFAULT_LINE_CAPACITY)
+    }
+
+    /// Create new FastaIter from a buffered reader and buffer capacities.
+    #[inline]
+    pub fn with_capacity(reader: T, buffer_capacity: usize, line_capacity: usize) -> Self {
+        FastaIter {
+            reader: reader,
+            buf: Vec::with_capacity(buffer_capacity),
+            line: Vec::with_capacity(line_capacity)
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for FastaIter<T> {
+    type Item = Result<Bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        bytes_next_skip_whitespace(b">", Some(FASTA_COMMENT), &mut self.reader, &mut self.buf, &mut self.line)
+    }
+}
+
+// SIZE
+
+/// Estimate the size of a FASTA record.
+///
+/// Used to prevent reallocations during record exportation to string,
+/// to minimize costly library calls.
+#[inline]
+fn estimate_record_size(record: &Record) -> usize {
+    const FASTA_VOCABULARY_SIZE: usize = 3;
+    FASTA_VOCABULARY_SIZE +
+        record.id.len() +
+        record.description.len() +
+        record.sequence.len() +
+        record.sequence.len() / LINE_LENGTH
+}
+
+/// Estimate the size of a FASTA record list.
+#[inline]
+fn estimate_list_size(list: &RecordList) -> usize {
+    list.iter().fold(0, |sum, x| sum + estimate_record_size(x))
+}
+
+// WRITER
+
+#[inline(always)]
+fn to_fasta<'a, T: Write>(writer: &mut T, record: &'a Record) -> Result<()> {
+    record_to_fasta(writer, record)
+}
+
+/// Export record to FASTA.
+///
+/// The sequence is wrapped at 60 nucleotides per line.
+pub fn record_to_fasta<T: Write>(writer: &mut T, record: &Record)
+    -> Result<()>
+{
+    write_alls!(writer, b">", record.id.as_bytes())?;
+    if !record.description.is_empty() {
+        write_alls!(writer, b" ", record.description.as_bytes())?;
+    }
+
+    for line in record.sequence.chunks(LINE_LENGTH) {
+        write_alls!(writer, b"\n", line)?;
+    }
+
+    Ok(())
+}
+
+// WRITER -- DEFAULT
+
+#[inline(always)]
+fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
+    -> Result<TextWriterState<'_, T>>
+{
+    Ok(TextWriterState::new(writer, delimiter))
+}
+
+#[inline(always)]
+fn export_cb<'a, T: Write>(writer: &mut TextWriterState<T>, record: &'a Record)
+    -> Result<()>
+{
+    writer.export(record, &to_fasta)
+}
+
+#[inline(always)]
+fn dest_cb<T: Write>(_: &mut TextWriterState<T>)
+    -> Result<()>
+{
+    Ok(())
+}
+
+/// Default exporter from a non-owning iterator to FASTA.
+#[inline(always)]
+pub fn reference_iterator_to_fasta<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to FASTA.
+#[inline(always)]
+pub fn value_iterator_to_fasta<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- STRICT
+
+/// Strict exporter from a non-owning iterator to FASTA.
+#[inline(always)]
+pub fn reference_iterator_to_fasta_strict<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to FASTA.
+#[inline(always)]
+pub fn value_iterator_to_fasta_strict<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- LENIENT
+
+/// Lenient exporter from a non-owning iterator to FASTA.
+#[inline(always)]
+pub fn reference_iterator_to_fasta_lenient<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to FASTA.
+#[inline(always)]
+pub fn value_iterator_to_fasta_lenient<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// READER
+
+/// Import record from FASTA.
+///
+/// The header is split into the identifier, up to the first whitespace,
+/// and the description. Wrapped sequence lines are joined.
+pub fn record_from_fasta<T: BufRead>(reader: &mut T)
+    -> Result<Record>
+{
+    // Split along lines.
+    // First line is the header, rest are the sequences.
+    let mut lines = reader.lines();
+    let header = none_to_error!(lines.next(), InvalidInput)?;
+    let header = strip_bom(header.trim_end());
+    bool_to_error!(header.starts_with('>'), InvalidFastaFormat);
+
+    let mut split = header[1..].splitn(2, char::is_whitespace);
+    let mut record = Record::new();
+    record.id = String::from(split.next().unwrap_or(""));
+    record.description = String::from(split.next().unwrap_or("").trim_start());
+
+    for line in lines {
+        record.sequence.extend_from_slice(line?.trim().as_bytes());
+    }
+
+    Ok(record)
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `FastaIter` and converts the text to records.
+pub struct FastaRecordIter<T: BufRead> {
+    iter: FastaIter<T>,
+    encoding: Encoding,
+    alphabet: Option<Alphabet>,
+}
+
+impl<T: BufRead> FastaRecordIter<T> {
+    /// Create new FastaRecordIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        FastaRecordIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new FastaRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
+        FastaRecordIter {
+            iter: FastaIter::with_capacity(reader, options.buffer_capacity, options.line_capacity),
+            encoding: options.encoding,
+            alphabet: options.alphabet,
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for FastaRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = match self.iter.next()? {
+            Err(e)   => return Some(Err(e)),
+            Ok(bytes) => bytes,
+        };
+
+        let bytes = self.encoding.decode_bytes(bytes);
+        let result = Record::from_fasta_bytes(bytes.as_slice()).and_then(|mut r| {
+            check_nucleotide_sequence(self.alphabet, &mut r.sequence)?;
+            Ok(r)
+        });
+        log_parsed!("nucleotide FASTA", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_fasta<T: BufRead>(reader: T) -> FastaRecordIter<T> {
+    FastaRecordIter::new(reader)
+}
+
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fasta_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaRecordIter<T> {
+    FastaRecordIter::with_options(reader, options)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `FastaIter` and converts the text to records strictly.
+pub type FastaRecordStrictIter<T> = StrictIter<Record, FastaRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_fasta_strict<T: BufRead>(reader: T) -> FastaRecordStrictIter<T> {
+    FastaRecordStrictIter::new(iterator_from_fasta(reader))
+}
+
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fasta_strict_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaRecordStrictIter<T> {
+    FastaRecordStrictIter::new(iterator_from_fasta_with_options(reader, options))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `FastaIter` and converts the text to records leniently.
+pub type FastaRecordLenientIter<T> = LenientIter<Record, FastaRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_fasta_lenient<T: BufRead>(reader: T) -> FastaRecordLenientIter<T> {
+    FastaRecordLenientIter::new(iterator_from_fasta(reader))
+}
+
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fasta_lenient_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaRecordLenientIter<T> {
+    FastaRecordLenientIter::new(iterator_from_fasta_with_options(reader, options))
+}
+
+// TRAITS
+
+impl Fasta for Record {
+    #[inline]
+    fn estimate_fasta_size(&self) -> usize {
+        estimate_record_size(self)
+    }
+
+    #[inline(always)]
+    fn to_fasta<T: Write>(&self, writer: &mut T) -> Result<()> {
+        record_to_fasta(writer, self)
+    }
+
+    #[inline(always)]
+    fn from_fasta<T: BufRead>(reader: &mut T) -> Result<Self> {
+        record_from_fasta(reader)
+    }
+
+    #[inline(always)]
+    fn from_fasta_iter<'a, T: 'a + BufRead>(reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_fasta(reader))
+    }
+
+    #[inline(always)]
+    fn to_fasta_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+    {
+        reference_iterator_to_fasta(writer, iter)
+    }
+}
+
+impl Fasta for RecordList {
+    #[inline]
+    fn estimate_fasta_size(&self) -> usize {
+        estimate_list_size(self)
+    }
+
+    #[inline(always)]
+    fn to_fasta<T: Write>(&self, writer: &mut T) -> Result<()> {
+        reference_iterator_to_fasta(writer, self.iter())
+    }
+
+    #[inline(always)]
+    fn from_fasta<T: BufRead>(reader: &mut T) -> Result<RecordList> {
+        iterator_from_fasta(reader).collect()
+    }
+}
+
+impl FastaCollection for RecordList {
+    #[inline(always)]
+    fn to_fasta_strict<T: Write>(&self, writer: &mut T) -> Result<()> {
+        reference_iterator_to_fasta_strict(writer, self.iter())
+    }
+
+    #[inline(always)]
+    fn to_fasta_lenient<T: Write>(&self, writer: &mut T) -> Result<()> {
+        reference_iterator_to_fasta_lenient(writer, self.iter())
+    }
+
+    #[inline(always)]
+    fn from_fasta_strict<T: BufRead>(reader: &mut T) -> Result<RecordList> {
+        iterator_from_fasta_strict(reader).collect()
+    }
+
+    #[inline(always)]
+    fn from_fasta_lenient<T: BufRead>(reader: &mut T) -> Result<RecordList> {
+        iterator_from_fasta_lenient(reader).collect()
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test::windows_text;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn record_fasta_test() {
+        let g = gapdh_cds();
+        assert_eq!(g.to_fasta_bytes().unwrap(), GAPDH_CDS_FASTA.to_vec());
+        assert_eq!(Record::from_fasta_bytes(GAPDH_CDS_FASTA).unwrap(), g);
+
+        let m = masked();
+        assert_eq!(m.to_fasta_bytes().unwrap(), MASKED_FASTA.to_vec());
+        assert_eq!(Record::from_fasta_bytes(MASKED_FASTA).unwrap(), m);
+
+        assert!(Record::from_fasta_bytes(b"ACGT").is_err());
+        assert!(Record::from_fasta_bytes(b"").is_err());
+    }
+
+    #[test]
+    fn iterator_to_fasta_test() {
+        let v = vec![gapdh_cds(), masked()];
+        let u = vec![gapdh_cds(), masked(), Record::new()];
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_fasta(&mut w, v.iter()).unwrap();
+        assert_eq!(w.into_inner(), GAPDH_CDS_MASKED_FASTA.to_vec());
+
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_fasta_strict(&mut w, u.iter()).is_err());
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_fasta_lenient(&mut w, u.iter()).unwrap();
+        assert_eq!(w.into_inner(), GAPDH_CDS_MASKED_FASTA.to_vec());
+    }
+
+    #[test]
+    fn iterator_from_fasta_test() {
+        let iter = iterator_from_fasta(Cursor::new(GAPDH_CDS_MASKED_FASTA));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh_cds(), masked()]);
+
+        // Windows line endings and trailing whitespace.
+        let text = windows_text(GAPDH_CDS_MASKED_FASTA);
+        let iter = iterator_from_fasta_strict(Cursor::new(text));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh_cds(), masked()]);
+
+        let text = [GAPDH_CDS_FASTA, b"\n>empty\n"].concat();
+        let iter = iterator_from_fasta_strict(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_fasta_lenient(Cursor::new(&text));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap(), vec![gapdh_cds()]);
+    }
+
+    #[test]
+    fn fasta_alphabet_test() {
+        let options = ReaderOptions::with_alphabet(Alphabet::Ambiguous);
+        let iter = iterator_from_fasta_with_options(Cursor::new(GAPDH_CDS_MASKED_FASTA), &options);
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+
+        let options = ReaderOptions::with_alphabet(Alphabet::CaseInsensitive);
+        let iter = iterator_from_fasta_with_options(Cursor::new(MASKED_FASTA), &options);
+        let v = iter.collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v[0].sequence, b"ACGTNNRYACGTN".to_vec());
+    }
+}
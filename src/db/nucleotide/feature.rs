@@ -0,0 +1,101 @@
+//! Model for annotated features of nucleotide sequences.
+
+/// Strand of a feature, relative to the nucleotide sequence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Strand {
+    /// Forward ("+") strand.
+    Forward,
+    /// Reverse ("-") strand.
+    Reverse,
+    /// Unknown or unstranded feature.
+    Unknown,
+}
+
+impl Default for Strand {
+    #[inline]
+    fn default() -> Self {
+        Strand::Unknown
+    }
+}
+
+/// Annotated region of a nucleotide sequence, such as a gene or exon.
+///
+/// Positions are 1-based and inclusive, as in GFF3 and GenBank.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Feature {
+    /// Identifier of the annotated sequence.
+    pub seqid: String,
+    /// Program or database which produced the feature.
+    pub source: String,
+    /// Feature type, for example, `"gene"` or `"CDS"`.
+    pub kind: String,
+    /// First position of the feature.
+    pub start: u32,
+    /// Last position of the feature.
+    pub end: u32,
+    /// Feature score, or empty if there is no score.
+    pub score: String,
+    /// Strand of the feature.
+    pub strand: Strand,
+    /// Number of nucleotides to skip to reach the first complete codon.
+    ///
+    /// Only defined for coding sequence features.
+    pub phase: Option<u8>,
+    /// Feature `(tag, value)` attributes, in document order.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Feature {
+    /// Create new, empty feature.
+    #[inline]
+    pub fn new() -> Self {
+        Feature::default()
+    }
+
+    /// Get the value of the first attribute with the tag.
+    #[inline]
+    pub fn attribute(&self, tag: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|a| a.0 == tag)
+            .map(|a| a.1.as_str())
+    }
+
+    /// Get the feature length, or `None` if the positions are invalid.
+    #[inline]
+    pub fn length(&self) -> Option<u32> {
+        match self.start == 0 || self.end < self.start {
+            true    => None,
+            false   => Some(self.end - self.start + 1),
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use traits::Valid;
+    use super::*;
+
+    #[test]
+    fn feature_test() {
+        let mut x = Feature::new();
+        x.start = 10;
+        x.end = 21;
+        x.attributes.push((String::from("ID"), String::from("cds1")));
+        assert_eq!(x.length(), Some(12));
+        assert_eq!(x.attribute("ID"), Some("cds1"));
+        assert_eq!(x.attribute("Parent"), None);
+        assert_eq!(x.strand, Strand::Unknown);
+        assert!(!x.is_valid());
+
+        x.seqid = String::from("chr1");
+        x.kind = String::from("CDS");
+        assert!(x.is_valid());
+
+        x.start = 0;
+        assert_eq!(x.length(), None);
+        assert!(!x.is_valid());
+    }
+}
@@ -0,0 +1,267 @@
+//! Helper utilities for GFF3 feature loading.
+//!
+//! GFF3 files contain one tab-delimited line per feature, with
+//! directives and comment lines starting with "#". An embedded
+//! FASTA section, starting with the "##FASTA" directive, ends the
+//! feature lines.
+
+use std::io::prelude::*;
+use std::io::Lines;
+
+use util::*;
+use super::feature::{Feature, Strand};
+
+/// Number of tab-delimited columns in a feature line.
+const COLUMN_COUNT: usize = 9;
+
+// GFF ITERATOR
+
+/// Iterator to parse individual feature lines from a GFF3 file.
+///
+/// Convert a stream to a lazy reader that fetches individual feature
+/// lines, skipping directives, comments and blank lines.
+pub struct GffIter<T: BufRead> {
+    lines: Lines<T>,
+    done: bool,
+}
+
+impl<T: BufRead> GffIter<T> {
+    /// Create new GffIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        GffIter {
+            lines: reader.lines(),
+            done: false,
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for GffIter<T> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let line = match self.lines.next()? {
+                Err(e)   => return Some(Err(From::from(e))),
+                Ok(line) => line,
+            };
+
+            let trimmed = strip_bom(line.trim_end());
+            if trimmed.starts_with("##FASTA") {
+                self.done = true;
+            } else if !trimmed.trim().is_empty() && !trimmed.starts_with('#') {
+                return Some(Ok(String::from(trimmed)));
+            }
+        }
+        None
+    }
+}
+
+// READER
+
+/// Decode the percent-encoded characters in a column.
+fn unescape_gff(text: &str) -> Result<String> {
+    if !text.contains('%') {
+        return Ok(String::from(text));
+    }
+
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            bool_to_error!(i + 2 < bytes.len(), InvalidInput);
+            let hex = ::std::str::from_utf8(&bytes[i+1..i+3])?;
+            decoded.push(u8::from_str_radix(hex, 16)?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    Ok(String::from_utf8(decoded)?)
+}
+
+/// Convert a missing (".") column to an empty string.
+#[inline]
+fn optional_from_str(column: &str) -> &str {
+    match column {
+        "." => "",
+        _   => column,
+    }
+}
+
+/// Parse the strand column.
+#[inline]
+fn strand_from_str(strand: &str) -> Result<Strand> {
+    match strand {
+        "+"       => Ok(Strand::Forward),
+        "-"       => Ok(Strand::Reverse),
+        "." | "?" => Ok(Strand::Unknown),
+        _         => Err(From::from(ErrorKind::InvalidInput)),
+    }
+}
+
+/// Parse the phase column.
+#[inline]
+fn phase_from_str(phase: &str) -> Result<Option<u8>> {
+    match phase {
+        "."              => Ok(None),
+        "0" | "1" | "2"  => Ok(Some(from_string(phase)?)),
+        _                => Err(From::from(ErrorKind::InvalidInput)),
+    }
+}
+
+/// Parse the `tag=value` attributes column.
+fn attributes_from_str(attributes: &str) -> Result<Vec<(String, String)>> {
+    let mut vec = vec![];
+    for attribute in optional_from_str(attributes).split(';') {
+        let attribute = attribute.trim();
+        if attribute.is_empty() {
+            continue;
+        }
+        let index = none_to_error!(attribute.find('='), InvalidInput);
+        vec.push((unescape_gff(&attribute[..index])?, unescape_gff(&attribute[index+1..])?));
+    }
+
+    Ok(vec)
+}
+
+/// Import feature from a GFF3 feature line.
+pub fn feature_from_gff(line: &str)
+    -> Result<Feature>
+{
+    let columns: Vec<&str> = line.split('\t').collect();
+    bool_to_error!(columns.len() == COLUMN_COUNT, InvalidInput);
+
+    Ok(Feature {
+        seqid: unescape_gff(columns[0])?,
+        source: unescape_gff(optional_from_str(columns[1]))?,
+        kind: unescape_gff(columns[2])?,
+        start: from_string(columns[3])?,
+        end: from_string(columns[4])?,
+        score: String::from(optional_from_str(columns[5])),
+        strand: strand_from_str(columns[6])?,
+        phase: phase_from_str(columns[7])?,
+        attributes: attributes_from_str(columns[8])?,
+    })
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Feature`s from a GFF3 file.
+///
+/// Wraps `GffIter` and converts the lines to features.
+pub struct GffFeatureIter<T: BufRead> {
+    iter: GffIter<T>,
+}
+
+impl<T: BufRead> GffFeatureIter<T> {
+    /// Create new GffFeatureIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        GffFeatureIter {
+            iter: GffIter::new(reader),
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for GffFeatureIter<T> {
+    type Item = Result<Feature>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.iter.next()? {
+            Err(e)   => Err(e),
+            Ok(line) => feature_from_gff(&line),
+        };
+        log_parsed!("gff", &result);
+        Some(result)
+    }
+}
+
+/// Create default feature iterator from reader.
+#[inline(always)]
+pub fn iterator_from_gff<T: BufRead>(reader: T) -> GffFeatureIter<T> {
+    GffFeatureIter::new(reader)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Feature`s from a GFF3 file.
+///
+/// Wraps `GffIter` and converts the lines to features strictly.
+pub type GffFeatureStrictIter<T> = StrictIter<Feature, GffFeatureIter<T>>;
+
+/// Create strict feature iterator from reader.
+#[inline(always)]
+pub fn iterator_from_gff_strict<T: BufRead>(reader: T) -> GffFeatureStrictIter<T> {
+    GffFeatureStrictIter::new(iterator_from_gff(reader))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Feature`s from a GFF3 file.
+///
+/// Wraps `GffIter` and converts the lines to features leniently.
+pub type GffFeatureLenientIter<T> = LenientIter<Feature, GffFeatureIter<T>>;
+
+/// Create lenient feature iterator from reader.
+#[inline(always)]
+pub fn iterator_from_gff_lenient<T: BufRead>(reader: T) -> GffFeatureLenientIter<T> {
+    GffFeatureLenientIter::new(iterator_from_gff(reader))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn feature_gff_test() {
+        let x = feature_from_gff("chr1\tRefSeq\tCDS\t10\t21\t.\t-\t0\tID=cds1;Parent=rna1;product=50%25 of a protein").unwrap();
+        assert_eq!(x.seqid, "chr1");
+        assert_eq!(x.source, "RefSeq");
+        assert_eq!(x.kind, "CDS");
+        assert_eq!((x.start, x.end), (10, 21));
+        assert_eq!(x.score, "");
+        assert_eq!(x.strand, Strand::Reverse);
+        assert_eq!(x.phase, Some(0));
+        assert_eq!(x.attribute("Parent"), Some("rna1"));
+        assert_eq!(x.attribute("product"), Some("50% of a protein"));
+
+        let x = feature_from_gff("chr1\t.\tgene\t1\t100\t0.5\t+\t.\t.").unwrap();
+        assert_eq!(x.source, "");
+        assert_eq!(x.score, "0.5");
+        assert_eq!(x.phase, None);
+        assert!(x.attributes.is_empty());
+
+        // Invalid columns.
+        assert!(feature_from_gff("chr1\t.\tgene\t1\t100\t.\t+\t.").is_err());
+        assert!(feature_from_gff("chr1\t.\tgene\tx\t100\t.\t+\t.\t.").is_err());
+        assert!(feature_from_gff("chr1\t.\tgene\t1\t100\t.\tx\t.\t.").is_err());
+        assert!(feature_from_gff("chr1\t.\tCDS\t1\t100\t.\t+\t3\t.").is_err());
+        assert!(feature_from_gff("chr1\t.\tgene\t1\t100\t.\t+\t.\tID").is_err());
+        assert!(feature_from_gff("chr1\t.\tgene\t1\t100\t.\t+\t.\tID=%2").is_err());
+    }
+
+    #[test]
+    fn iterator_from_gff_test() {
+        let features: Result<Vec<Feature>> = iterator_from_gff(Cursor::new(SPLICED_GFF)).collect();
+        let features = features.unwrap();
+        assert_eq!(features.len(), 4);
+        assert_eq!(features[0].kind, "gene");
+        assert_eq!(features[2].start, 17);
+        assert_eq!(features[3].strand, Strand::Reverse);
+
+        let text = b"##gff-version 3\nchr1\t.\tgene\t1\t10\t.\t+\t.\t.\nchr1\t.\tgene\t10\t1\t.\t+\t.\t.\n";
+        let strict: Result<Vec<Feature>> = iterator_from_gff_strict(Cursor::new(&text[..])).collect();
+        assert!(strict.is_err());
+        let lenient: Result<Vec<Feature>> = iterator_from_gff_lenient(Cursor::new(&text[..])).collect();
+        assert_eq!(lenient.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,39 @@
+//! Nucleotide sequence integrations.
+//!
+//! Models generic nucleotide sequences, such as genomic or transcript
+//! sequences, rather than sequence reads with quality scores.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
+
+pub(crate) mod assembly;
+pub(crate) mod complete;
+pub(crate) mod feature;
+pub(crate) mod gff;
+pub(crate) mod record;
+pub(crate) mod record_list;
+pub(crate) mod structure;
+pub(crate) mod valid;
+
+#[cfg(feature = "fasta")]
+pub(crate) mod fasta;
+
+#[cfg(feature = "uniprot")]
+pub(crate) mod cds;
+
+#[cfg(feature = "uniprot")]
+pub(crate) mod six_frame;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+// Re-export the models into the parent module.
+pub use self::assembly::AssemblyStats;
+#[cfg(feature = "uniprot")]
+pub use self::cds::{codon_optimize, CodingSequence};
+#[cfg(feature = "uniprot")]
+pub use self::six_frame::{OpenReadingFrame, SixFrameOptions};
+pub use self::feature::{Feature, Strand};
+pub use self::record::Record;
+pub use self::record_list::RecordList;
+pub use self::structure::{folded_from_vienna, folded_to_vienna, FoldedRecord};
@@ -0,0 +1,161 @@
+//! Model for nucleotide sequence definitions.
+
+use bio::dna::{self, GeneticCode};
+use bio::dna::restriction::{digest, RestrictionEnzyme};
+use traits::{Keyed, Renamable};
+
+/// Model for a single nucleotide sequence.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Record {
+    /// Sequence identifier, the first word of the header.
+    pub id: String,
+    /// Description for the sequence, the remainder of the header.
+    pub description: String,
+    /// Nucleotide sequence, which may contain IUPAC ambiguity codes.
+    pub sequence: Vec<u8>,
+}
+
+impl Record {
+    /// Create new, empty nucleotide record.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            id: String::new(),
+            description: String::new(),
+            sequence: vec![],
+        }
+    }
+
+    /// Get the record for the reverse complement of the sequence.
+    #[inline]
+    pub fn reverse_complement(&self) -> Record {
+        Record {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            sequence: dna::reverse_complement(&self.sequence),
+        }
+    }
+
+    /// Translate the sequence using the standard genetic code.
+    ///
+    /// Translates the sequence from the first nucleotide, in the
+    /// forward strand.
+    #[inline]
+    pub fn translate(&self) -> Vec<u8> {
+        dna::translate(&self.sequence)
+    }
+
+    /// Translate the sequence using a genetic code.
+    ///
+    /// Translates the sequence from the first nucleotide, in the
+    /// forward strand.
+    #[inline]
+    pub fn translate_with_code(&self, code: GeneticCode) -> Vec<u8> {
+        code.translate(&self.sequence)
+    }
+
+    /// Digest the sequence with one or more restriction enzymes.
+    ///
+    /// Each fragment is identified by the 1-based position of its
+    /// first and last nucleotides, for example, "seq/4-13".
+    pub fn restriction_fragments(&self, enzymes: &[&RestrictionEnzyme], circular: bool) -> Vec<Record> {
+        let length = self.sequence.len();
+        digest(&self.sequence, enzymes, circular).into_iter().map(|f| {
+            let end = (f.start + f.length - 1) % length + 1;
+            Record {
+                id: format!("{}/{}-{}", self.id, f.start + 1, end),
+                description: self.description.clone(),
+                sequence: f.sequence(&self.sequence),
+            }
+        }).collect()
+    }
+}
+
+impl Keyed for Record {
+    type Key = String;
+
+    /// Get the sequence identifier.
+    #[inline]
+    fn key(&self) -> &String {
+        &self.id
+    }
+}
+
+impl Renamable for Record {
+    /// Get the sequence identifier.
+    #[inline]
+    fn identifier(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    fn set_identifier(&mut self, identifier: String) {
+        self.id = identifier;
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::Alphabet;
+    use bio::dna::restriction::find_enzyme;
+    use traits::*;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn properties_record_test() {
+        let g = gapdh_cds();
+        assert!(g.is_valid());
+        assert!(g.is_complete());
+        assert!(g.is_valid_with_alphabet(Alphabet::Strict));
+
+        let m = masked();
+        assert!(m.is_valid());
+        assert!(!m.is_complete());
+        assert!(!m.is_valid_with_alphabet(Alphabet::Extended));
+        assert!(m.is_valid_with_alphabet(Alphabet::CaseInsensitive));
+
+        let mut r = g.clone();
+        r.sequence = b"ACGTX".to_vec();
+        assert!(!r.is_valid());
+        r.sequence = vec![];
+        assert!(!r.is_valid());
+        assert!(!Record::new().is_valid());
+    }
+
+    #[test]
+    fn reverse_complement_record_test() {
+        let m = masked();
+        let r = m.reverse_complement();
+        assert_eq!(r.id, m.id);
+        assert_eq!(r.sequence, b"nacgtRYNNACGT".to_vec());
+        assert_eq!(r.reverse_complement(), m);
+    }
+
+    #[test]
+    fn translate_record_test() {
+        assert_eq!(gapdh_cds().translate(), b"MGKVKVGVNGFGRIGRLVTRAAFNSGKVDIVAINDPFID".to_vec());
+        assert_eq!(gapdh_cds().translate_with_code(GeneticCode::VertebrateMitochondrial), b"MGKVKVGVNGFGRIGRLVT*AAFNSGKVDIVAINDPFID".to_vec());
+    }
+
+    #[test]
+    fn restriction_fragments_record_test() {
+        let ecori = find_enzyme("EcoRI").unwrap();
+        let mut r = Record::new();
+        r.id = String::from("pUC");
+        r.sequence = b"AAGAATTCAAAAGAATTCAA".to_vec();
+
+        let fragments = r.restriction_fragments(&[ecori], false);
+        let ids: Vec<&str> = fragments.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["pUC/1-3", "pUC/4-13", "pUC/14-20"]);
+        assert_eq!(fragments[1].sequence, b"AATTCAAAAG".to_vec());
+
+        let fragments = r.restriction_fragments(&[ecori], true);
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[1].id, "pUC/14-3");
+        assert_eq!(fragments[1].sequence, b"AATTCAAAAG".to_vec());
+    }
+}
@@ -0,0 +1,6 @@
+//! Model for nucleotide sequence collections.
+
+use super::record::Record;
+
+/// Nucleotide record collection type.
+pub type RecordList = Vec<Record>;
@@ -0,0 +1,265 @@
+//! Six-frame translation of nucleotide sequences.
+//!
+//! Six-frame translation databases contain the translation of each
+//! reading frame of both strands, and are used to search spectra
+//! against unannotated or novel coding regions (proteogenomics).
+
+use bio::dna::{self, GeneticCode};
+use bio::proteins::ProteinMassTable;
+use db::uniprot::{self, ProteinEvidence};
+use util::Result;
+use super::feature::Strand;
+use super::record::Record;
+
+/// Options for six-frame translation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SixFrameOptions {
+    /// Genetic code used to translate the codons.
+    pub code: GeneticCode,
+    /// Minimum number of aminoacids in a translated sequence.
+    pub min_length: usize,
+    /// Split the translated frames at stop codons.
+    ///
+    /// If false, each frame is translated as a single sequence, with
+    /// internal stop codons as "*".
+    pub split_stops: bool,
+}
+
+impl Default for SixFrameOptions {
+    #[inline]
+    fn default() -> Self {
+        SixFrameOptions {
+            code: GeneticCode::Standard,
+            min_length: 20,
+            split_stops: true,
+        }
+    }
+}
+
+/// Translated open reading frame (ORF) from a nucleotide sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenReadingFrame {
+    /// Identifier of the translated nucleotide sequence.
+    pub seqid: String,
+    /// Strand of the reading frame.
+    pub strand: Strand,
+    /// Reading frame, from 1 to 3, relative to the start of the strand.
+    pub frame: u8,
+    /// First position covered by the codons, 1-based, in the forward strand.
+    pub start: u32,
+    /// Last position covered by the codons, 1-based, in the forward strand.
+    pub end: u32,
+    /// Translated aminoacid sequence.
+    pub sequence: Vec<u8>,
+}
+
+impl OpenReadingFrame {
+    /// Get the signed reading frame, for example, `"+1"` or `"-3"`.
+    #[inline]
+    pub fn signed_frame(&self) -> String {
+        match self.strand {
+            Strand::Reverse => format!("-{}", self.frame),
+            _               => format!("+{}", self.frame),
+        }
+    }
+
+    /// Convert the reading frame to a UniProt protein record.
+    ///
+    /// The identifier contains the sequence identifier, the signed
+    /// frame, and the coordinates, for example, `"chr1_+1_1-60"`, and
+    /// the name describes the frame and coordinates.
+    pub fn to_protein(&self) -> uniprot::Record {
+        let frame = self.signed_frame();
+        let mut protein = uniprot::Record::new();
+        protein.protein_evidence = ProteinEvidence::Predicted;
+        protein.mass = ProteinMassTable::average().total_sequence_mass(&self.sequence).round() as u64;
+        protein.length = self.sequence.len() as u32;
+        protein.id = format!("{}_{}_{}-{}", self.seqid, frame, self.start, self.end);
+        protein.name = format!("{} frame={} start={} end={}", self.seqid, frame, self.start, self.end);
+        protein.sequence = self.sequence.clone();
+        protein
+    }
+}
+
+/// Add the translated segments of a single reading frame.
+///
+/// `offset` is the 0-based frame offset, and `length` is the length
+/// of the nucleotide sequence, to map reverse-strand coordinates.
+fn add_frame(orfs: &mut Vec<OpenReadingFrame>, seqid: &str, strand: Strand, offset: usize, length: usize, sequence: &[u8], options: &SixFrameOptions) {
+    let protein = options.code.translate(&sequence[offset.min(sequence.len())..]);
+    let mut segments = vec![];
+    if options.split_stops {
+        let mut first = 0;
+        for (i, &c) in protein.iter().enumerate() {
+            if c == b'*' {
+                segments.push((first, i));
+                first = i + 1;
+            }
+        }
+        segments.push((first, protein.len()));
+    } else {
+        segments.push((0, protein.len()));
+    }
+
+    for (first, last) in segments {
+        if last == first || last - first < options.min_length {
+            continue;
+        }
+        // 0-based, exclusive coordinates in the translated strand.
+        let lo = offset + 3 * first;
+        let hi = offset + 3 * last;
+        let (start, end) = match strand {
+            Strand::Reverse => (length - hi + 1, length - lo),
+            _               => (lo + 1, hi),
+        };
+        orfs.push(OpenReadingFrame {
+            seqid: String::from(seqid),
+            strand: strand,
+            frame: offset as u8 + 1,
+            start: start as u32,
+            end: end as u32,
+            sequence: protein[first..last].to_vec(),
+        });
+    }
+}
+
+/// Translate each reading frame of both strands of the sequence.
+///
+/// The reading frames are returned in frame order, from "+1" to
+/// "+3" then "-1" to "-3", and in strand order within each frame.
+pub fn six_frame_orfs(record: &Record, options: &SixFrameOptions) -> Vec<OpenReadingFrame> {
+    let length = record.sequence.len();
+    let reverse = dna::reverse_complement(&record.sequence);
+    let mut orfs = vec![];
+    for offset in 0..3 {
+        add_frame(&mut orfs, &record.id, Strand::Forward, offset, length, &record.sequence, options);
+    }
+    for offset in 0..3 {
+        add_frame(&mut orfs, &record.id, Strand::Reverse, offset, length, &reverse, options);
+    }
+
+    orfs
+}
+
+/// Translate the sequence to six-frame UniProt protein records.
+#[inline]
+pub fn six_frame_translate(record: &Record, options: &SixFrameOptions) -> Vec<uniprot::Record> {
+    six_frame_orfs(record, options).iter().map(|o| o.to_protein()).collect()
+}
+
+// ITERATOR
+
+/// Iterator which lazily translates nucleotide records in six frames.
+///
+/// Each nucleotide record is translated as it is read, so large
+/// genomes can be converted to search databases in a stream.
+pub struct SixFrameIter<U: Iterator<Item = Result<Record>>> {
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Options for six-frame translation.
+    options: SixFrameOptions,
+    /// Translated records from the current nucleotide record, in reverse order.
+    buffer: Vec<uniprot::Record>,
+}
+
+impl<U: Iterator<Item = Result<Record>>> SixFrameIter<U> {
+    /// Create new SixFrameIter from an iterator and options.
+    #[inline]
+    pub fn new(iter: U, options: SixFrameOptions) -> Self {
+        SixFrameIter {
+            iter: iter,
+            options: options,
+            buffer: vec![],
+        }
+    }
+}
+
+impl<U: Iterator<Item = Result<Record>>> Iterator for SixFrameIter<U> {
+    type Item = Result<uniprot::Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.is_empty() {
+            match self.iter.next()? {
+                Err(e)     => return Some(Err(e)),
+                Ok(record) => {
+                    self.buffer = six_frame_translate(&record, &self.options);
+                    self.buffer.reverse();
+                },
+            }
+        }
+
+        self.buffer.pop().map(Ok)
+    }
+}
+
+/// Create six-frame translation iterator from a nucleotide record iterator.
+#[inline(always)]
+pub fn iterator_to_six_frame<U>(iter: U, options: SixFrameOptions) -> SixFrameIter<U>
+    where U: Iterator<Item = Result<Record>>
+{
+    SixFrameIter::new(iter, options)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> Record {
+        Record {
+            id: String::from("chr1"),
+            description: String::new(),
+            sequence: b"ATGGCCTAAGGGCATTTCTAG".to_vec(),
+        }
+    }
+
+    fn options(min_length: usize, split_stops: bool) -> SixFrameOptions {
+        SixFrameOptions {
+            code: GeneticCode::Standard,
+            min_length: min_length,
+            split_stops: split_stops,
+        }
+    }
+
+    #[test]
+    fn six_frame_orfs_test() {
+        let orfs = six_frame_orfs(&record(), &options(1, true));
+        let frames: Vec<(String, u32, u32, &[u8])> = orfs.iter()
+            .map(|o| (o.signed_frame(), o.start, o.end, o.sequence.as_slice()))
+            .collect();
+        assert_eq!(frames, vec![
+            (String::from("+1"), 1, 6, &b"MA"[..]),
+            (String::from("+1"), 10, 18, &b"GHF"[..]),
+            (String::from("+2"), 2, 19, &b"WPKGIS"[..]),
+            (String::from("+3"), 3, 20, &b"GLRAFL"[..]),
+            (String::from("-1"), 1, 21, &b"LEMPLGH"[..]),
+            (String::from("-2"), 9, 17, &b"KCP"[..]),
+            (String::from("-2"), 3, 5, &b"A"[..]),
+            (String::from("-3"), 2, 19, &b"RNALRP"[..]),
+        ]);
+
+        // Minimum length and unsplit frames.
+        assert_eq!(six_frame_orfs(&record(), &options(4, true)).len(), 4);
+        let orfs = six_frame_orfs(&record(), &options(1, false));
+        assert_eq!(orfs.len(), 6);
+        assert_eq!(orfs[0].sequence, b"MA*GHF*".to_vec());
+        assert_eq!((orfs[0].start, orfs[0].end), (1, 21));
+    }
+
+    #[test]
+    fn six_frame_translate_test() {
+        let proteins = six_frame_translate(&record(), &options(6, true));
+        assert_eq!(proteins.len(), 4);
+        assert_eq!(proteins[0].id, "chr1_+2_2-19");
+        assert_eq!(proteins[0].name, "chr1 frame=+2 start=2 end=19");
+        assert_eq!(proteins[0].sequence, b"WPKGIS".to_vec());
+        assert_eq!(proteins[0].length, 6);
+        assert_eq!(proteins[3].id, "chr1_-3_2-19");
+
+        let v = vec![Ok(record()), Ok(Record::new()), Ok(record())];
+        let proteins: Result<Vec<uniprot::Record>> = iterator_to_six_frame(v.into_iter(), options(6, true)).collect();
+        assert_eq!(proteins.unwrap().len(), 8);
+    }
+}
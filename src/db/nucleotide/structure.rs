@@ -0,0 +1,166 @@
+//! Nucleotide records annotated with RNA secondary structures.
+//!
+//! Structures are loaded and saved in the Vienna (dot-bracket) format,
+//! with a FASTA header, the sequence, and the structure on a single
+//! line each, optionally followed by the score in parentheses.
+
+use std::io::prelude::*;
+
+use bio::rna::structure::{nussinov, PairScore, SecondaryStructure};
+use util::{ErrorKind, Result};
+use super::record::Record;
+
+/// Nucleotide record with its secondary structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoldedRecord {
+    /// Nucleotide record.
+    pub record: Record,
+    /// Secondary structure, with one position per nucleotide.
+    pub structure: SecondaryStructure,
+    /// Structure score or free energy, if known.
+    pub score: Option<f64>,
+}
+
+impl FoldedRecord {
+    /// Create new folded record, checking the structure matches the sequence.
+    pub fn new(record: Record, structure: SecondaryStructure, score: Option<f64>) -> Result<Self> {
+        bool_to_error!(record.sequence.len() == structure.len(), InvalidInput);
+        Ok(FoldedRecord {
+            record: record,
+            structure: structure,
+            score: score,
+        })
+    }
+
+    /// Fold the record by maximizing the base-pair score.
+    ///
+    /// See `bio::rna::structure::nussinov` for details.
+    pub fn fold<S: PairScore>(record: Record, min_loop: usize, scoring: &S) -> Self {
+        let (structure, score) = nussinov(&record.sequence, min_loop, scoring);
+        FoldedRecord {
+            record: record,
+            structure: structure,
+            score: Some(score),
+        }
+    }
+}
+
+/// Export the folded records to Vienna format.
+pub fn folded_to_vienna<'a, Iter, T>(writer: &mut T, records: Iter) -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a FoldedRecord>
+{
+    for folded in records {
+        let record = &folded.record;
+        write!(writer, ">{}", record.id)?;
+        if !record.description.is_empty() {
+            write!(writer, " {}", record.description)?;
+        }
+        writer.write_all(b"\n")?;
+        writer.write_all(&record.sequence)?;
+        writer.write_all(b"\n")?;
+        writer.write_all(&folded.structure.to_dot_bracket()?)?;
+        if let Some(score) = folded.score {
+            write!(writer, " ({:.2})", score)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Parse the structure line, with an optional score in parentheses.
+fn parse_structure(line: &str) -> Result<(SecondaryStructure, Option<f64>)> {
+    let line = line.trim_end();
+    let (structure, score) = match line.find(char::is_whitespace) {
+        Some(index) => {
+            let score = line[index..].trim();
+            bool_to_error!(score.starts_with('(') && score.ends_with(')'), InvalidRecord);
+            let score = score[1..score.len()-1].trim().parse::<f64>()?;
+            (&line[..index], Some(score))
+        },
+        None => (line, None),
+    };
+    Ok((SecondaryStructure::from_dot_bracket(structure.as_bytes())?, score))
+}
+
+/// Import folded records from Vienna format.
+///
+/// Blank lines between records are ignored.
+pub fn folded_from_vienna<T: BufRead>(reader: T) -> Result<Vec<FoldedRecord>> {
+    let mut records = vec![];
+    let mut lines = reader.lines();
+    while let Some(header) = lines.next() {
+        let header = header?;
+        if header.trim().is_empty() {
+            continue;
+        }
+        bool_to_error!(header.starts_with('>'), InvalidRecord);
+        let sequence = none_to_error!(lines.next(), UnexpectedEof);
+        let structure = none_to_error!(lines.next(), UnexpectedEof);
+        let (sequence, structure) = (sequence?, structure?);
+
+        let mut record = Record::new();
+        let mut header = header[1..].trim_end().splitn(2, ' ');
+        record.id = String::from(header.next().unwrap_or(""));
+        record.description = String::from(header.next().unwrap_or(""));
+        record.sequence = sequence.trim_end().as_bytes().to_vec();
+        let (structure, score) = parse_structure(&structure)?;
+        records.push(FoldedRecord::new(record, structure, score)?);
+    }
+    Ok(records)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use bio::rna::structure::BasePairCount;
+    use super::*;
+
+    const VIENNA: &'static str = ">hairpin Test hairpin\nGGGAAAUCC\n(((...))) (3.00)\n>unfolded\nAAAA\n....\n";
+
+    fn hairpin() -> Record {
+        Record {
+            id: String::from("hairpin"),
+            description: String::from("Test hairpin"),
+            sequence: b"GGGAAAUCC".to_vec(),
+        }
+    }
+
+    #[test]
+    fn folded_record_test() {
+        let folded = FoldedRecord::fold(hairpin(), 3, &BasePairCount);
+        assert_eq!(folded.structure.to_dot_bracket().unwrap(), b"(((...)))".to_vec());
+        assert_eq!(folded.score, Some(3.0));
+
+        assert!(FoldedRecord::new(hairpin(), SecondaryStructure::new(4), None).is_err());
+    }
+
+    #[test]
+    fn vienna_test() {
+        let mut unfolded = Record::new();
+        unfolded.id = String::from("unfolded");
+        unfolded.sequence = b"AAAA".to_vec();
+        let records = vec![
+            FoldedRecord::fold(hairpin(), 3, &BasePairCount),
+            FoldedRecord::new(unfolded, SecondaryStructure::new(4), None).unwrap(),
+        ];
+
+        let mut w = vec![];
+        folded_to_vienna(&mut w, &records).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), VIENNA);
+        assert_eq!(folded_from_vienna(Cursor::new(VIENNA)).unwrap(), records);
+
+        let text = "\n>x\nGGGAAAUCC\n(((...)))   ( -1.5 )\n\n";
+        let records = folded_from_vienna(Cursor::new(text)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].score, Some(-1.5));
+
+        assert!(folded_from_vienna(Cursor::new(">x\nGGGAAAUCC\n")).is_err());
+        assert!(folded_from_vienna(Cursor::new(">x\nGGGAAAUCC\n(((...))\n")).is_err());
+        assert!(folded_from_vienna(Cursor::new(">x\nGGGAAAUCC\n(((...))) -1.5\n")).is_err());
+        assert!(folded_from_vienna(Cursor::new("x\nGGG\n...\n")).is_err());
+    }
+}
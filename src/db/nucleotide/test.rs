@@ -0,0 +1,64 @@
+//! Shared helper utilities for nucleotide unit testing.
+
+use super::record::Record;
+
+// RECORDS
+
+/// Create a record for the start of the human GAPDH coding sequence.
+pub fn gapdh_cds() -> Record {
+    Record {
+        id: String::from("NM_002046.7"),
+        description: String::from("Homo sapiens glyceraldehyde-3-phosphate dehydrogenase (GAPDH), partial"),
+        sequence: b"ATGGGGAAGGTGAAGGTCGGAGTCAACGGATTTGGTCGTATTGGGCGCCTGGTCACCAGGGCTGCTTTTAACTCTGGTAAAGTGGATATTGTTGCCATCAATGACCCCTTCATTGACC".to_vec(),
+    }
+}
+
+/// Create a record with ambiguous and soft-masked nucleotides.
+pub fn masked() -> Record {
+    Record {
+        id: String::from("chrUn_1"),
+        description: String::new(),
+        sequence: b"ACGTNNRYacgtn".to_vec(),
+    }
+}
+
+/// Create a contig with a spliced, forward-strand coding sequence.
+pub fn spliced_contig() -> Record {
+    Record {
+        id: String::from("chrT"),
+        description: String::new(),
+        sequence: b"CCATGGCTAGTACCAGAATGGTAACCCCCC".to_vec(),
+    }
+}
+
+/// Create a contig with a reverse-strand coding sequence.
+pub fn reverse_contig() -> Record {
+    Record {
+        id: String::from("chrR"),
+        description: String::new(),
+        sequence: b"GGTCAAAACATGG".to_vec(),
+    }
+}
+
+// GFF
+
+/// GFF3 features for the spliced and reverse-strand coding sequences.
+pub const SPLICED_GFF: &'static [u8] = b"##gff-version 3\n\
+chrT\tRefSeq\tgene\t3\t24\t.\t+\t.\tID=gene1;Name=ABC1\n\
+chrT\tRefSeq\tCDS\t3\t9\t.\t+\t0\tID=cds1;Parent=gene1;gene=ABC1;product=Test protein;protein_id=XP_000001.1\n\
+chrT\tRefSeq\tCDS\t17\t24\t.\t+\t2\tID=cds1;Parent=gene1;gene=ABC1;product=Test protein;protein_id=XP_000001.1\n\
+chrR\tRefSeq\tCDS\t3\t11\t.\t-\t0\tID=cds2;gene=DEF2\n\
+##FASTA\n\
+>chrT\n\
+CCATGGCTAGTACCAGAATGGTAACCCCCC\n";
+
+// FASTA
+
+/// FASTA for the GAPDH coding sequence.
+pub const GAPDH_CDS_FASTA: &'static [u8] = b">NM_002046.7 Homo sapiens glyceraldehyde-3-phosphate dehydrogenase (GAPDH), partial\nATGGGGAAGGTGAAGGTCGGAGTCAACGGATTTGGTCGTATTGGGCGCCTGGTCACCAGG\nGCTGCTTTTAACTCTGGTAAAGTGGATATTGTTGCCATCAATGACCCCTTCATTGACC";
+
+/// FASTA for the masked sequence.
+pub const MASKED_FASTA: &'static [u8] = b">chrUn_1\nACGTNNRYacgtn";
+
+/// FASTA for the GAPDH coding sequence and the masked sequence.
+pub const GAPDH_CDS_MASKED_FASTA: &'static [u8] = b">NM_002046.7 Homo sapiens glyceraldehyde-3-phosphate dehydrogenase (GAPDH), partial\nATGGGGAAGGTGAAGGTCGGAGTCAACGGATTTGGTCGTATTGGGCGCCTGGTCACCAGG\nGCTGCTTTTAACTCTGGTAAAGTGGATATTGTTGCCATCAATGACCCCTTCATTGACC\n>chrUn_1\nACGTNNRYacgtn";
@@ -0,0 +1,43 @@
+//! Valid trait implementation for nucleotide models.
+
+use bio::Alphabet;
+use traits::Valid;
+use super::feature::Feature;
+use super::record::Record;
+use super::record_list::RecordList;
+
+impl Valid for Record {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        // Accept ambiguous and soft-masked (lowercase) nucleotides,
+        // which are common in genomic sequences.
+        self.is_valid_with_alphabet(Alphabet::CaseInsensitive)
+    }
+}
+
+impl Record {
+    /// Determine if the record is valid, with nucleotides from the alphabet.
+    #[inline]
+    pub fn is_valid_with_alphabet(&self, alphabet: Alphabet) -> bool {
+        !self.id.is_empty() &&
+            !self.id.contains(char::is_whitespace) &&
+            !self.sequence.is_empty() &&
+            alphabet.is_nucleotide_sequence(&self.sequence)
+    }
+}
+
+impl Valid for RecordList {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.iter().all(|ref x| x.is_valid())
+    }
+}
+
+impl Valid for Feature {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        !self.seqid.is_empty() &&
+            !self.kind.is_empty() &&
+            self.length().is_some()
+    }
+}
@@ -0,0 +1,17 @@
+//! Re-exports for low-level, efficient APIs.
+//!
+//! In order for high-performance processing of large documents,
+//! We must use parsers that lazily read and write items to and from
+//! documents. The writers accept both by-value and by-reference
+//! iterators, allowing you to easily chain lazy readers and writers
+//! to convert between export formats.
+//!
+//! The memory footprint of these lazy low-level functions is minimal,
+//! typically < 16 KB required for internal buffers, and < 1 KB for each
+//! individual item.
+
+// TODO(ahuszagh)
+//  Re-export the lazy readers and writers once implemented.
+//  The readers should accept `ReaderOptions`, and decode each record
+//  with `ReaderOptions.encoding`, since legacy PDB files may contain
+//  Latin-1 text.
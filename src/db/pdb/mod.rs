@@ -0,0 +1,4 @@
+//! Protein Data Bank (PDB) integrations.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
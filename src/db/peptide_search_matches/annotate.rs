@@ -0,0 +1,188 @@
+//! Annotation of peptide matches with UniProt protein metadata.
+//!
+//! Each match is joined to every protein containing the peptide,
+//! producing one row per match-protein pair, with the protein
+//! metadata and the position of the peptide in the protein.
+
+#[cfg(feature = "csv")]
+use csv;
+#[cfg(feature = "csv")]
+use std::io::Write;
+
+use db::uniprot;
+use io::store::RecordStore;
+#[cfg(feature = "csv")]
+use util::Result;
+use super::record::Record;
+use super::window::find_peptide;
+
+/// Peptide match joined with the metadata of a protein.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotatedMatch {
+    /// File name of the matched spectrum.
+    pub file: String,
+    /// Scan number of the matched spectrum.
+    pub num: u32,
+    /// Peptide sequence, without modifications.
+    pub peptide: Vec<u8>,
+    /// Charge state of the precursor.
+    pub charge: i8,
+    /// Search engine score.
+    pub score: f64,
+    /// Accession number of the protein.
+    pub protein: String,
+    /// Gene name of the protein.
+    pub gene: String,
+    /// Organism of the protein.
+    pub organism: String,
+    /// Protein name.
+    pub name: String,
+    /// Protein length, in residues.
+    pub length: u32,
+    /// 1-based positions of the first and last peptide residues in the protein.
+    pub position: Option<(u32, u32)>,
+}
+
+/// Join peptide matches with the metadata of their proteins.
+///
+/// Rows are ordered by match, then by protein. Proteins missing from
+/// the store produce rows with empty metadata, and peptides not found
+/// in the protein have no position. Peptides found multiple times in
+/// a protein use the first occurrence.
+pub fn annotate_matches<'a, Iter>(matches: Iter, proteins: &RecordStore<uniprot::Record>)
+    -> Vec<AnnotatedMatch>
+    where Iter: IntoIterator<Item = &'a Record>
+{
+    let mut rows = vec![];
+    for record in matches {
+        for accession in record.proteins.iter() {
+            let mut row = AnnotatedMatch {
+                file: record.file.clone(),
+                num: record.num,
+                peptide: record.peptide.clone(),
+                charge: record.charge,
+                score: record.score,
+                protein: accession.clone(),
+                gene: String::new(),
+                organism: String::new(),
+                name: String::new(),
+                length: 0,
+                position: None,
+            };
+            if let Some(protein) = proteins.get(accession.as_str()) {
+                row.gene = protein.gene.clone();
+                row.organism = protein.organism.clone();
+                row.name = protein.name.clone();
+                row.length = protein.length;
+                row.position = find_peptide(&protein.sequence, &record.peptide).map(|start| {
+                    (start as u32 + 1, (start + record.peptide.len()) as u32)
+                });
+            }
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+/// Header columns for the annotated match table.
+#[cfg(feature = "csv")]
+const ANNOTATION_HEADER: [&'static str; 12] = [
+    "File", "Scan", "Peptide", "Charge", "Score", "Entry", "Gene names  (primary )",
+    "Organism", "Protein names", "Length", "Start", "End",
+];
+
+/// Export the annotated matches to a CSV table, with one row per match-protein pair.
+#[cfg(feature = "csv")]
+pub fn annotations_to_csv<'a, Iter, T>(writer: &mut T, rows: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a AnnotatedMatch>
+{
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(csv::QuoteStyle::Necessary)
+        .flexible(false)
+        .from_writer(writer);
+    writer.write_record(&ANNOTATION_HEADER)?;
+    for row in rows {
+        let (start, end) = match row.position {
+            Some((start, end)) => (start.to_string(), end.to_string()),
+            None               => (String::new(), String::new()),
+        };
+        let length = match row.length {
+            0 => String::new(),
+            n => n.to_string(),
+        };
+        writer.write_record(&[
+            row.file.as_bytes(),
+            row.num.to_string().as_bytes(),
+            &row.peptide,
+            row.charge.to_string().as_bytes(),
+            row.score.to_string().as_bytes(),
+            row.protein.as_bytes(),
+            row.gene.as_bytes(),
+            row.organism.as_bytes(),
+            row.name.as_bytes(),
+            length.as_bytes(),
+            start.as_bytes(),
+            end.as_bytes(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches() -> Vec<Record> {
+        let mut record = Record::new();
+        record.num = 33450;
+        record.file = String::from("QPvivo.raw");
+        record.peptide = b"LVTR".to_vec();
+        record.proteins = vec![String::from("P46406"), String::from("P02769")];
+        record.charge = 2;
+        record.score = 25.5;
+        vec![record]
+    }
+
+    fn store() -> RecordStore<uniprot::Record> {
+        let mut protein = uniprot::Record::new();
+        protein.id = String::from("P46406");
+        protein.gene = String::from("GAPDH");
+        protein.organism = String::from("Oryctolagus cuniculus");
+        protein.name = String::from("Glyceraldehyde-3-phosphate dehydrogenase");
+        protein.sequence = b"MVKVGVNGFGRIGRLVTRAAFNSGK".to_vec();
+        protein.length = 25;
+        RecordStore::new(vec![protein])
+    }
+
+    #[test]
+    fn annotate_matches_test() {
+        let rows = annotate_matches(&matches(), &store());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].gene, "GAPDH");
+        assert_eq!(rows[0].length, 25);
+        assert_eq!(rows[0].position, Some((15, 18)));
+        assert_eq!(rows[1].protein, "P02769");
+        assert_eq!(rows[1].gene, "");
+        assert_eq!(rows[1].position, None);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn annotations_to_csv_test() {
+        let rows = annotate_matches(&matches(), &store());
+        let mut w = vec![];
+        annotations_to_csv(&mut w, &rows, b'\t').unwrap();
+        let text = String::from_utf8(w).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "QPvivo.raw\t33450\tLVTR\t2\t25.5\tP46406\tGAPDH\tOryctolagus cuniculus\tGlyceraldehyde-3-phosphate dehydrogenase\t25\t15\t18");
+        assert_eq!(lines[2], "QPvivo.raw\t33450\tLVTR\t2\t25.5\tP02769\t\t\t\t\t\t");
+    }
+}
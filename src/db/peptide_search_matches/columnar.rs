@@ -0,0 +1,119 @@
+//! Helper utilities for peptide search match columnar (Arrow and Parquet) export.
+//!
+//! Exports one row per match, with the modifications stored as list
+//! columns of the positions, names and mass shifts.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Builder, Int8Builder, ListBuilder, StringBuilder, UInt32Builder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use traits::Columnar;
+use util::Result;
+use super::record::Record;
+
+/// Create a nullable list field, matching the fields of `ListBuilder`.
+#[inline]
+fn list_field(name: &str, item: DataType) -> Field {
+    Field::new(name, DataType::List(Arc::new(Field::new("item", item, true))), false)
+}
+
+impl Columnar for Record {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("num", DataType::UInt32, false),
+            Field::new("file", DataType::Utf8, false),
+            Field::new("peptide", DataType::Utf8, false),
+            list_field("modification_positions", DataType::UInt32),
+            list_field("modification_names", DataType::Utf8),
+            list_field("modification_masses", DataType::Float64),
+            list_field("proteins", DataType::Utf8),
+            Field::new("charge", DataType::Int8, false),
+            Field::new("score", DataType::Float64, false),
+        ]))
+    }
+
+    fn to_record_batch(models: &[&Self]) -> Result<RecordBatch> {
+        let mut num = UInt32Builder::with_capacity(models.len());
+        let mut file = StringBuilder::new();
+        let mut peptide = StringBuilder::new();
+        let mut positions = ListBuilder::new(UInt32Builder::new());
+        let mut names = ListBuilder::new(StringBuilder::new());
+        let mut masses = ListBuilder::new(Float64Builder::new());
+        let mut proteins = ListBuilder::new(StringBuilder::new());
+        let mut charge = Int8Builder::with_capacity(models.len());
+        let mut score = Float64Builder::with_capacity(models.len());
+
+        for record in models.iter() {
+            num.append_value(record.num);
+            file.append_value(&record.file);
+            peptide.append_value(String::from_utf8_lossy(&record.peptide));
+            for modification in record.modifications.iter() {
+                positions.values().append_value(modification.position);
+                names.values().append_value(&modification.name);
+                masses.values().append_value(modification.mass);
+            }
+            positions.append(true);
+            names.append(true);
+            masses.append(true);
+            for protein in record.proteins.iter() {
+                proteins.values().append_value(protein);
+            }
+            proteins.append(true);
+            charge.append_value(record.charge);
+            score.append_value(record.score);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(num.finish()),
+            Arc::new(file.finish()),
+            Arc::new(peptide.finish()),
+            Arc::new(positions.finish()),
+            Arc::new(names.finish()),
+            Arc::new(masses.finish()),
+            Arc::new(proteins.finish()),
+            Arc::new(charge.finish()),
+            Arc::new(score.finish()),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, ListArray, StringArray};
+    use super::*;
+    use super::super::modification::Modification;
+
+    #[test]
+    fn record_batch_test() {
+        let mut record = Record::new();
+        record.peptide = b"SAMPLER".to_vec();
+        record.modifications.push(Modification { position: 3, name: String::from("Oxidation"), mass: 15.994915 });
+        record.proteins = vec![String::from("P12345"), String::from("Q67890")];
+        record.charge = 2;
+        let empty = Record::new();
+
+        let batch = Record::to_record_batch(&[&record, &empty]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let peptide = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(peptide.value(0), "SAMPLER");
+
+        let names = batch.column(4).as_any().downcast_ref::<ListArray>().unwrap();
+        let first = names.value(0);
+        let first = first.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(first.value(0), "Oxidation");
+        assert_eq!(names.value_length(1), 0);
+
+        let proteins = batch.column(6).as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(proteins.value_length(0), 2);
+
+        let mut w = vec![];
+        Record::to_parquet_iter(&mut w, vec![record, empty].iter()).unwrap();
+        assert!(w.starts_with(b"PAR1"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Model for crosslinked peptide search match definitions.
+
+/// Model for a crosslinked peptide pair identified from a spectral scan.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct CrosslinkMatch {
+    /// Scan number of the matched spectrum.
+    pub num: u32,
+    /// File name of the matched spectrum.
+    pub file: String,
+    /// Crosslinker name, for example, `"DSS"`.
+    pub crosslinker: String,
+    /// First (alpha) peptide sequence, without modifications.
+    pub alpha: Vec<u8>,
+    /// 0-based position of the crosslinked residue in the alpha peptide.
+    pub alpha_site: u32,
+    /// Accession numbers of the proteins containing the alpha peptide.
+    pub alpha_proteins: Vec<String>,
+    /// Second (beta) peptide sequence, without modifications.
+    pub beta: Vec<u8>,
+    /// 0-based position of the crosslinked residue in the beta peptide.
+    pub beta_site: u32,
+    /// Accession numbers of the proteins containing the beta peptide.
+    pub beta_proteins: Vec<String>,
+    /// Charge state of the precursor.
+    pub charge: i8,
+    /// Search engine score, higher is better.
+    pub score: f64,
+}
+
+impl CrosslinkMatch {
+    /// Create new, empty crosslinked peptide search match.
+    #[inline]
+    pub fn new() -> Self {
+        CrosslinkMatch {
+            num: 0,
+            file: String::new(),
+            crosslinker: String::new(),
+            alpha: vec![],
+            alpha_site: 0,
+            alpha_proteins: vec![],
+            beta: vec![],
+            beta_site: 0,
+            beta_proteins: vec![],
+            charge: 0,
+            score: 0.0,
+        }
+    }
+
+    /// Check if both peptides may come from the same protein.
+    ///
+    /// Inter-protein crosslinks (between different proteins) identify
+    /// protein interactions, while intra-protein crosslinks constrain
+    /// the protein structure.
+    #[inline]
+    pub fn is_intra_protein(&self) -> bool {
+        self.alpha_proteins.iter().any(|p| self.beta_proteins.contains(p))
+    }
+
+    /// Check if both peptides have the same sequence.
+    ///
+    /// Crosslinks between identical peptides come from separate copies
+    /// of the protein, within a homo-oligomer.
+    #[inline]
+    pub fn is_homotypic(&self) -> bool {
+        self.alpha == self.beta
+    }
+}
+
+/// Crosslinked peptide search match collection type.
+pub type CrosslinkMatchList = Vec<CrosslinkMatch>;
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properties_crosslink_test() {
+        let mut record = CrosslinkMatch::new();
+        record.alpha = b"SAKMPLEK".to_vec();
+        record.alpha_proteins = vec![String::from("P12345")];
+        record.beta = b"SAKMPLEK".to_vec();
+        record.beta_proteins = vec![String::from("Q67890"), String::from("P12345")];
+        assert!(record.is_intra_protein());
+        assert!(record.is_homotypic());
+
+        record.beta = b"MPLEKAGR".to_vec();
+        record.beta_proteins.pop();
+        assert!(!record.is_intra_protein());
+        assert!(!record.is_homotypic());
+    }
+}
@@ -0,0 +1,384 @@
+//! Mass-indexed peptide database, for precursor mass queries.
+//!
+//! Unique peptides from a digest are sorted by neutral mass, and
+//! written to disk as a fixed-width table of masses and offsets,
+//! followed by the peptide sequences and proteins. Queries binary
+//! search the table from disk, so only the matching peptides are
+//! read, and wide windows (for open-modification searches) are
+//! supported. Fixed modifications and isotope labels are applied
+//! through the mass table used to build the index.
+
+use std::fs::File;
+use std::io::{BufReader, SeekFrom};
+use std::io::prelude::*;
+use std::path::Path;
+
+use bio::mass::Tolerance;
+use io::atomic::write_atomic;
+use util::{open_buffered, read_f64, read_u32, read_u64, write_len, ErrorKind, Result};
+
+#[cfg(feature = "uniprot")]
+use std::collections::HashMap;
+#[cfg(feature = "uniprot")]
+use bio::digest::{digest, DigestOptions, Peptide};
+#[cfg(feature = "uniprot")]
+use bio::proteins::ProteinMassTable;
+#[cfg(feature = "uniprot")]
+use concurrency::{CancellationToken, Concurrency};
+#[cfg(feature = "uniprot")]
+use db::uniprot;
+
+/// Magic bytes and version at the start of peptide indexes.
+const INDEX_MAGIC: &[u8; 8] = b"BDBIDX\x00\x01";
+
+/// Size of the header, with the magic bytes and peptide count.
+const HEADER_SIZE: u64 = 16;
+
+/// Size of each table entry, with the mass, offset and lengths.
+const ENTRY_SIZE: u64 = 24;
+
+/// Unique peptide in the index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedPeptide {
+    /// Peptide sequence.
+    pub sequence: Vec<u8>,
+    /// Neutral peptide mass, in Daltons.
+    pub mass: f64,
+    /// Accession numbers of the proteins containing the peptide.
+    pub proteins: Vec<String>,
+}
+
+// BUILD
+
+/// Digest protein records into the unique peptides for an index.
+///
+/// Peptides are sorted by mass, then by sequence, and list every
+/// protein containing the peptide, in the order first seen. Proteins
+/// are digested in parallel, using the global `Concurrency`.
+#[cfg(feature = "uniprot")]
+pub fn index_peptides<'a, Iter>(records: Iter, options: &DigestOptions, table: &ProteinMassTable)
+    -> Vec<IndexedPeptide>
+    where Iter: IntoIterator<Item = &'a uniprot::Record>
+{
+    let records: Vec<&uniprot::Record> = records.into_iter().collect();
+    let digests = Concurrency::global().map(&records, |record| digest(&record.sequence, options, table));
+    merge_digests(&records, digests)
+}
+
+/// Digest protein records into the unique peptides for an index, stopping early if cancelled.
+///
+/// Identical to `index_peptides`, checking the token between chunks
+/// of proteins.
+#[cfg(feature = "uniprot")]
+pub fn index_peptides_with_cancel<'a, Iter>(records: Iter, options: &DigestOptions, table: &ProteinMassTable, cancel: &CancellationToken)
+    -> Result<Vec<IndexedPeptide>>
+    where Iter: IntoIterator<Item = &'a uniprot::Record>
+{
+    let records: Vec<&uniprot::Record> = records.into_iter().collect();
+    let digests = Concurrency::global().map_with_cancel(&records, cancel, |record| digest(&record.sequence, options, table))?;
+    Ok(merge_digests(&records, digests))
+}
+
+/// Merge the digested peptides into unique peptides, sorted by mass.
+#[cfg(feature = "uniprot")]
+fn merge_digests(records: &[&uniprot::Record], digests: Vec<Vec<Peptide>>) -> Vec<IndexedPeptide> {
+    let mut index: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut peptides: Vec<IndexedPeptide> = vec![];
+    for (record, digested) in records.iter().zip(digests) {
+        for peptide in digested {
+            let i = *index.entry(peptide.sequence.clone()).or_insert_with(|| {
+                peptides.push(IndexedPeptide {
+                    sequence: peptide.sequence,
+                    mass: peptide.mass,
+                    proteins: vec![],
+                });
+                peptides.len() - 1
+            });
+            let proteins = &mut peptides[i].proteins;
+            if !proteins.contains(&record.id) {
+                proteins.push(record.id.clone());
+            }
+        }
+    }
+    peptides.sort_by(|x, y| x.mass.total_cmp(&y.mass).then_with(|| x.sequence.cmp(&y.sequence)));
+
+    peptides
+}
+
+/// Export peptides to an index, sorting the peptides by mass.
+///
+/// Protein accession numbers are stored `;`-delimited, and must not
+/// contain `;`.
+pub fn write_peptide_index<'a, Iter, T>(writer: &mut T, peptides: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a IndexedPeptide>
+{
+    let mut peptides: Vec<&IndexedPeptide> = peptides.into_iter().collect();
+    for peptide in peptides.iter() {
+        bool_to_error!(!peptide.mass.is_nan(), InvalidRecord);
+        bool_to_error!(peptide.proteins.iter().all(|p| !p.contains(';')), InvalidRecord);
+    }
+    peptides.sort_by(|x, y| x.mass.total_cmp(&y.mass));
+
+    writer.write_all(INDEX_MAGIC)?;
+    writer.write_all(&(peptides.len() as u64).to_le_bytes())?;
+    let proteins: Vec<String> = peptides.iter().map(|p| p.proteins.join(";")).collect();
+    let mut offset: u64 = 0;
+    for (peptide, proteins) in peptides.iter().zip(proteins.iter()) {
+        writer.write_all(&peptide.mass.to_le_bytes())?;
+        writer.write_all(&offset.to_le_bytes())?;
+        write_len(writer, peptide.sequence.len())?;
+        write_len(writer, proteins.len())?;
+        offset += (peptide.sequence.len() + proteins.len()) as u64;
+    }
+    for (peptide, proteins) in peptides.iter().zip(proteins.iter()) {
+        writer.write_all(&peptide.sequence)?;
+        writer.write_all(proteins.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Export peptides to an index file, replacing the file atomically.
+pub fn write_peptide_index_path<'a, Iter, P>(path: P, peptides: Iter)
+    -> Result<()>
+    where P: AsRef<Path>,
+          Iter: IntoIterator<Item = &'a IndexedPeptide>
+{
+    let path = path.as_ref();
+    write_atomic(path, |w| write_peptide_index(w, peptides)).map_err(|e| e.with_path(path))
+}
+
+// QUERY
+
+/// Reader for an on-disk peptide index.
+pub struct PeptideIndex<T: Read + Seek> {
+    reader: T,
+    count: usize,
+    size: u64,
+}
+
+impl PeptideIndex<BufReader<File>> {
+    /// Open a peptide index file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        open_buffered(path)
+            .and_then(PeptideIndex::new)
+            .map_err(|e| e.with_path(path))
+    }
+}
+
+impl<T: Read + Seek> PeptideIndex<T> {
+    /// Create new peptide index from a reader, validating the header.
+    pub fn new(mut reader: T) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut magic)?;
+        bool_to_error!(&magic == INDEX_MAGIC, InvalidInput);
+        let count = read_u64(&mut reader)?;
+        let size = reader.seek(SeekFrom::End(0))?;
+
+        // Check the table fits in the file, so corrupt counts cannot
+        // overflow the entry offsets.
+        let table_end = ENTRY_SIZE.checked_mul(count).and_then(|x| x.checked_add(HEADER_SIZE));
+        bool_to_error!(table_end.map_or(false, |end| end <= size), InvalidInput);
+
+        Ok(PeptideIndex { reader: reader, count: count as usize, size: size })
+    }
+
+    /// Get the number of peptides in the index.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Check if the index contains no peptides.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Seek to the table entry of a peptide.
+    #[inline]
+    fn seek_entry(&mut self, index: usize) -> Result<()> {
+        bool_to_error!(index < self.count, InvalidInput);
+        self.reader.seek(SeekFrom::Start(HEADER_SIZE + ENTRY_SIZE * index as u64))?;
+        Ok(())
+    }
+
+    /// Get the mass of the peptide at an index.
+    pub fn mass(&mut self, index: usize) -> Result<f64> {
+        self.seek_entry(index)?;
+        read_f64(&mut self.reader)
+    }
+
+    /// Get the peptide at an index.
+    pub fn get(&mut self, index: usize) -> Result<IndexedPeptide> {
+        self.seek_entry(index)?;
+        let mass = read_f64(&mut self.reader)?;
+        let offset = read_u64(&mut self.reader)?;
+        let sequence_length = read_u32(&mut self.reader)? as usize;
+        let proteins_length = read_u32(&mut self.reader)? as usize;
+
+        // Check the lengths against the file size before allocating,
+        // so corrupt indexes cannot force large allocations.
+        let start = (HEADER_SIZE + ENTRY_SIZE * self.count as u64).saturating_add(offset);
+        let end = start.saturating_add((sequence_length + proteins_length) as u64);
+        bool_to_error!(end <= self.size, InvalidInput);
+        self.reader.seek(SeekFrom::Start(start))?;
+        let mut sequence = vec![0u8; sequence_length];
+        self.reader.read_exact(&mut sequence)?;
+        let mut proteins = vec![0u8; proteins_length];
+        self.reader.read_exact(&mut proteins)?;
+        let proteins = String::from_utf8(proteins)?;
+
+        Ok(IndexedPeptide {
+            sequence: sequence,
+            mass: mass,
+            proteins: match proteins.is_empty() {
+                true  => vec![],
+                false => proteins.split(';').map(String::from).collect(),
+            },
+        })
+    }
+
+    /// Find the index of the first peptide with a mass not below `mass`.
+    pub fn lower_bound(&mut self, mass: f64) -> Result<usize> {
+        let (mut lo, mut hi) = (0, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.mass(mid)? < mass {
+                true  => lo = mid + 1,
+                false => hi = mid,
+            }
+        }
+        Ok(lo)
+    }
+
+    /// Get the peptides with masses in the inclusive range `[lo, hi]`, sorted by mass.
+    pub fn range(&mut self, lo: f64, hi: f64) -> Result<Vec<IndexedPeptide>> {
+        let mut peptides = vec![];
+        let mut index = self.lower_bound(lo)?;
+        while index < self.count {
+            let peptide = self.get(index)?;
+            if peptide.mass > hi {
+                break;
+            }
+            peptides.push(peptide);
+            index += 1;
+        }
+        Ok(peptides)
+    }
+
+    /// Get the peptides matching a precursor mass within a tolerance.
+    ///
+    /// For open-modification searches, use a wide absolute tolerance,
+    /// for example, `Tolerance::Da(500.0)`.
+    #[inline]
+    pub fn query(&mut self, mass: f64, tolerance: Tolerance) -> Result<Vec<IndexedPeptide>> {
+        let (lo, hi) = tolerance.range(mass);
+        self.range(lo, hi)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use super::*;
+
+    fn peptide(sequence: &[u8], mass: f64, proteins: &[&str]) -> IndexedPeptide {
+        IndexedPeptide {
+            sequence: sequence.to_vec(),
+            mass: mass,
+            proteins: proteins.iter().map(|p| String::from(*p)).collect(),
+        }
+    }
+
+    fn peptides() -> Vec<IndexedPeptide> {
+        vec![
+            peptide(b"KPEPTIDEK", 1054.5, &["P1", "P2"]),
+            peptide(b"SAMPLER", 802.4, &["P1"]),
+            peptide(b"LVTR", 487.3, &[]),
+        ]
+    }
+
+    #[test]
+    fn query_test() {
+        let mut w = vec![];
+        write_peptide_index(&mut w, &peptides()).unwrap();
+        let mut index = PeptideIndex::new(Cursor::new(w)).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get(0).unwrap(), peptide(b"LVTR", 487.3, &[]));
+        assert_eq!(index.get(2).unwrap(), peptide(b"KPEPTIDEK", 1054.5, &["P1", "P2"]));
+        assert!(index.get(3).is_err());
+
+        assert_eq!(index.lower_bound(0.0).unwrap(), 0);
+        assert_eq!(index.lower_bound(802.4).unwrap(), 1);
+        assert_eq!(index.lower_bound(2000.0).unwrap(), 3);
+        let matches = index.query(802.40001, Tolerance::Ppm(10.0)).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].sequence, b"SAMPLER".to_vec());
+        assert_eq!(index.query(900.0, Tolerance::Da(500.0)).unwrap().len(), 3);
+        assert!(index.query(600.0, Tolerance::Da(1.0)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_index_test() {
+        assert!(PeptideIndex::new(Cursor::new(b"BDBLIB\x00\x01".to_vec())).is_err());
+
+        // Lengths past the end of the file.
+        let mut w = vec![];
+        write_peptide_index(&mut w, &peptides()).unwrap();
+        let entry = (HEADER_SIZE + 16) as usize;
+        w[entry..entry + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut index = PeptideIndex::new(Cursor::new(w.clone())).unwrap();
+        assert!(index.get(0).is_err());
+        assert!(index.get(1).is_ok());
+
+        // Counts past the end of the file, or overflowing the offsets.
+        let count = peptides().len() as u64;
+        w[8..16].copy_from_slice(&(count + 1000).to_le_bytes());
+        assert!(PeptideIndex::new(Cursor::new(w.clone())).is_err());
+        w[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(PeptideIndex::new(Cursor::new(w.clone())).is_err());
+        w[8..16].copy_from_slice(&count.to_le_bytes());
+        assert!(PeptideIndex::new(Cursor::new(w)).is_ok());
+        let mut w = vec![];
+        assert!(write_peptide_index(&mut w, &[peptide(b"LVTR", 487.3, &["P1;P2"])]).is_err());
+    }
+
+    #[cfg(feature = "uniprot")]
+    #[test]
+    fn index_peptides_test() {
+        let mut p1 = uniprot::Record::new();
+        p1.id = String::from("P1");
+        p1.sequence = b"SAMPLERKPEPTIDEK".to_vec();
+        let mut p2 = uniprot::Record::new();
+        p2.id = String::from("P2");
+        p2.sequence = b"MRKPEPTIDEK".to_vec();
+
+        let table = ProteinMassTable::monoisotopic();
+        let options = DigestOptions { missed_cleavages: 0, ..DigestOptions::default() };
+        let records = vec![p1, p2];
+        let peptides = index_peptides(&records, &options, &table);
+        assert_eq!(peptides.len(), 2);
+        assert_eq!(peptides[0].sequence, b"SAMPLER".to_vec());
+        assert_eq!(peptides[1].proteins, vec![String::from("P1"), String::from("P2")]);
+
+        let cancel = CancellationToken::new();
+        assert_eq!(index_peptides_with_cancel(&records, &options, &table, &cancel).unwrap(), peptides);
+        cancel.cancel();
+        assert!(index_peptides_with_cancel(&records, &options, &table, &cancel).is_err());
+
+        let path = env::temp_dir().join("bdb_peptide_index_test.idx");
+        write_peptide_index_path(&path, &peptides).unwrap();
+        let mut index = PeptideIndex::open(&path).unwrap();
+        assert_eq!(index.query(peptides[1].mass, Tolerance::Ppm(5.0)).unwrap(), vec![peptides[1].clone()]);
+        fs::remove_file(&path).unwrap();
+    }
+}
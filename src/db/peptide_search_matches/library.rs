@@ -0,0 +1,344 @@
+//! Theoretical spectral library generation.
+//!
+//! Proteins are digested, each peptide is expanded into its modified
+//! forms, and theoretical fragment ions are generated for each
+//! precursor charge. The library is exported as NIST MSP text, or as
+//! a compact binary format, which may be read back.
+//!
+//! Theoretical spectra have no intensity model, so every fragment
+//! has unit intensity.
+
+use std::io::prelude::*;
+
+use bio::digest::DigestOptions;
+use bio::fragment::{Fragment, IonType};
+use util::{read_bytes, read_f64, read_len, read_string, read_u32, read_u8, write_bytes, write_len, ErrorKind, Result};
+use super::modification::Modification;
+use super::rule::ModificationRule;
+
+#[cfg(feature = "uniprot")]
+use std::collections::HashMap;
+#[cfg(feature = "uniprot")]
+use bio::digest::digest;
+#[cfg(feature = "uniprot")]
+use bio::fragment::fragment_ions;
+#[cfg(feature = "uniprot")]
+use bio::mass::{neutral_mass_to_mz, Adduct};
+#[cfg(feature = "uniprot")]
+use bio::proteins::ProteinMassTable;
+#[cfg(feature = "uniprot")]
+use db::uniprot;
+#[cfg(feature = "uniprot")]
+use super::rule::{modification_shifts, modified_forms};
+
+/// Options for theoretical spectral library generation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LibraryOptions {
+    /// Digestion options, to generate the peptides.
+    pub digest: DigestOptions,
+    /// Fixed and variable modification rules.
+    pub modifications: Vec<ModificationRule>,
+    /// Maximum number of variable modifications per peptide.
+    pub max_variable_modifications: usize,
+    /// Precursor charge states.
+    pub charges: Vec<i8>,
+    /// Fragment ion types.
+    pub ions: Vec<IonType>,
+    /// Maximum fragment charge, capped by the precursor charge.
+    pub max_fragment_charge: i8,
+}
+
+impl Default for LibraryOptions {
+    /// Tryptic peptides, with carbamidomethyl cysteine and oxidized
+    /// methionine, and singly-charged b- and y-ions of 2+ and 3+ precursors.
+    fn default() -> Self {
+        LibraryOptions {
+            digest: DigestOptions::default(),
+            modifications: vec![ModificationRule::carbamidomethyl(), ModificationRule::oxidation()],
+            max_variable_modifications: 2,
+            charges: vec![2, 3],
+            ions: vec![IonType::B, IonType::Y],
+            max_fragment_charge: 1,
+        }
+    }
+}
+
+/// Theoretical spectrum of a modified peptide precursor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LibraryEntry {
+    /// Peptide sequence, without modifications.
+    pub peptide: Vec<u8>,
+    /// Modified residues in the peptide.
+    pub modifications: Vec<Modification>,
+    /// Accession numbers of the proteins containing the peptide.
+    pub proteins: Vec<String>,
+    /// Charge state of the precursor.
+    pub charge: i8,
+    /// Neutral mass of the modified peptide.
+    pub mass: f64,
+    /// Mass to charge ratio of the precursor.
+    pub precursor_mz: f64,
+    /// Fragment ions, sorted by m/z.
+    pub fragments: Vec<Fragment>,
+}
+
+// GENERATION
+
+/// Generate a theoretical spectral library from protein records.
+///
+/// Peptides shared by multiple proteins produce a single set of
+/// entries, listing every protein, in the order first seen.
+#[cfg(feature = "uniprot")]
+pub fn generate_library<'a, Iter>(records: Iter, options: &LibraryOptions, table: &ProteinMassTable)
+    -> Vec<LibraryEntry>
+    where Iter: IntoIterator<Item = &'a uniprot::Record>
+{
+    // Collect the unique peptides, and their proteins.
+    let mut index: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut peptides: Vec<(Vec<u8>, Vec<String>)> = vec![];
+    for record in records {
+        for peptide in digest(&record.sequence, &options.digest, table) {
+            let i = *index.entry(peptide.sequence.clone()).or_insert_with(|| {
+                peptides.push((peptide.sequence, vec![]));
+                peptides.len() - 1
+            });
+            let proteins = &mut peptides[i].1;
+            if !proteins.contains(&record.id) {
+                proteins.push(record.id.clone());
+            }
+        }
+    }
+
+    let mut entries = vec![];
+    for (peptide, proteins) in peptides {
+        let unmodified = table.total_sequence_mass(&peptide);
+        for modifications in modified_forms(&peptide, &options.modifications, options.max_variable_modifications) {
+            let mass = unmodified + modifications.iter().map(|m| m.mass).sum::<f64>();
+            let shifts = modification_shifts(peptide.len(), &modifications);
+            for &charge in options.charges.iter() {
+                let precursor_mz = match neutral_mass_to_mz(mass, charge, Adduct::Proton) {
+                    Some(mz) => mz,
+                    None     => continue,
+                };
+                let max_fragment_charge = options.max_fragment_charge.min(charge.abs());
+                entries.push(LibraryEntry {
+                    peptide: peptide.clone(),
+                    modifications: modifications.clone(),
+                    proteins: proteins.clone(),
+                    charge: charge,
+                    mass: mass,
+                    precursor_mz: precursor_mz,
+                    fragments: fragment_ions(&peptide, &shifts, &options.ions, max_fragment_charge, table),
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+// MSP
+
+/// Export the library as NIST MSP text.
+///
+/// Modifications are listed in the comment as `Mods=N/pos,residue,name`,
+/// with 0-based positions, and fragments are annotated by ion type.
+pub fn library_to_msp<'a, Iter, T>(writer: &mut T, entries: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a LibraryEntry>
+{
+    for entry in entries {
+        let peptide = String::from_utf8_lossy(&entry.peptide);
+        let mut mods = entry.modifications.len().to_string();
+        for modification in entry.modifications.iter() {
+            let residue = entry.peptide.get(modification.position as usize).cloned().unwrap_or(b'X');
+            mods.push_str(&format!("/{},{},{}", modification.position, residue as char, modification.name));
+        }
+        writeln!(writer, "Name: {}/{}", peptide, entry.charge)?;
+        writeln!(writer, "MW: {:.5}", entry.mass)?;
+        writeln!(writer, "PrecursorMZ: {:.5}", entry.precursor_mz)?;
+        writeln!(writer, "Comment: Parent={:.5} Mods={} Protein={}", entry.precursor_mz, mods, entry.proteins.join(";"))?;
+        writeln!(writer, "Num peaks: {}", entry.fragments.len())?;
+        for fragment in entry.fragments.iter() {
+            writeln!(writer, "{:.5}\t1\t\"{}\"", fragment.mz, fragment.annotation())?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+// BINARY
+
+/// Magic bytes and version at the start of binary libraries.
+const BINARY_MAGIC: &[u8; 8] = b"BDBLIB\x00\x01";
+
+/// Export the library in the binary format.
+///
+/// Integers and floats are little-endian, and variable-length fields
+/// are prefixed by their 32-bit length.
+pub fn library_to_binary<'a, Iter, T>(writer: &mut T, entries: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a LibraryEntry>
+{
+    let entries: Vec<&LibraryEntry> = entries.into_iter().collect();
+    writer.write_all(BINARY_MAGIC)?;
+    write_len(writer, entries.len())?;
+    for entry in entries {
+        write_bytes(writer, &entry.peptide)?;
+        write_len(writer, entry.modifications.len())?;
+        for modification in entry.modifications.iter() {
+            writer.write_all(&modification.position.to_le_bytes())?;
+            write_bytes(writer, modification.name.as_bytes())?;
+            writer.write_all(&modification.mass.to_le_bytes())?;
+        }
+        write_len(writer, entry.proteins.len())?;
+        for protein in entry.proteins.iter() {
+            write_bytes(writer, protein.as_bytes())?;
+        }
+        writer.write_all(&[entry.charge as u8])?;
+        writer.write_all(&entry.mass.to_le_bytes())?;
+        writer.write_all(&entry.precursor_mz.to_le_bytes())?;
+        write_len(writer, entry.fragments.len())?;
+        for fragment in entry.fragments.iter() {
+            writer.write_all(&[fragment.ion as u8])?;
+            write_len(writer, fragment.ordinal)?;
+            writer.write_all(&[fragment.charge as u8])?;
+            writer.write_all(&fragment.mz.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Import the library from the binary format.
+pub fn library_from_binary<T: Read>(reader: &mut T)
+    -> Result<Vec<LibraryEntry>>
+{
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    bool_to_error!(&magic == BINARY_MAGIC, InvalidInput);
+
+    // Do not trust the lengths for the capacity of the collections.
+    let count = read_len(reader)?;
+    let mut entries = vec![];
+    for _ in 0..count {
+        let peptide = read_bytes(reader)?;
+        let mut modifications = vec![];
+        for _ in 0..read_len(reader)? {
+            modifications.push(Modification {
+                position: read_u32(reader)?,
+                name: read_string(reader)?,
+                mass: read_f64(reader)?,
+            });
+        }
+        let mut proteins = vec![];
+        for _ in 0..read_len(reader)? {
+            proteins.push(read_string(reader)?);
+        }
+        let charge = read_u8(reader)? as i8;
+        let mass = read_f64(reader)?;
+        let precursor_mz = read_f64(reader)?;
+        let mut fragments = vec![];
+        for _ in 0..read_len(reader)? {
+            let ion = match read_u8(reader)? {
+                1 => IonType::A,
+                2 => IonType::B,
+                3 => IonType::Y,
+                _ => return Err(From::from(ErrorKind::InvalidEnumeration)),
+            };
+            fragments.push(Fragment {
+                ion: ion,
+                ordinal: read_len(reader)?,
+                charge: read_u8(reader)? as i8,
+                mz: read_f64(reader)?,
+            });
+        }
+        entries.push(LibraryEntry {
+            peptide: peptide,
+            modifications: modifications,
+            proteins: proteins,
+            charge: charge,
+            mass: mass,
+            precursor_mz: precursor_mz,
+            fragments: fragments,
+        });
+    }
+
+    Ok(entries)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+
+    fn entry() -> LibraryEntry {
+        LibraryEntry {
+            peptide: b"SAMPLER".to_vec(),
+            modifications: vec![Modification { position: 2, name: String::from("Oxidation"), mass: 15.994915 }],
+            proteins: vec![String::from("P02769")],
+            charge: 2,
+            mass: 818.37,
+            precursor_mz: 410.19,
+            fragments: vec![
+                Fragment { ion: IonType::B, ordinal: 2, charge: 1, mz: 159.07642 },
+                Fragment { ion: IonType::Y, ordinal: 1, charge: 1, mz: 175.11895 },
+            ],
+        }
+    }
+
+    #[cfg(feature = "uniprot")]
+    #[test]
+    fn generate_library_test() {
+        let mut p1 = uniprot::Record::new();
+        p1.id = String::from("P1");
+        p1.sequence = b"SAMPLERKPEPTIDEK".to_vec();
+        let mut p2 = uniprot::Record::new();
+        p2.id = String::from("P2");
+        p2.sequence = b"MRKPEPTIDEK".to_vec();
+
+        let table = ProteinMassTable::monoisotopic();
+        let options = LibraryOptions {
+            digest: DigestOptions { missed_cleavages: 0, ..DigestOptions::default() },
+            charges: vec![2],
+            ..LibraryOptions::default()
+        };
+        let entries = generate_library(&vec![p1, p2], &options, &table);
+
+        // SAMPLER, oxidized SAMPLER and KPEPTIDEK.
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].peptide, b"SAMPLER".to_vec());
+        assert_eq!(entries[1].modifications[0].name, "Oxidation");
+        assert_approx_eq!(entries[1].mass - entries[0].mass, 15.994915, 1e-6);
+        assert_eq!(entries[2].proteins, vec![String::from("P1"), String::from("P2")]);
+        assert_approx_eq!(entries[0].precursor_mz, (entries[0].mass + 2.0 * 1.007276) / 2.0, 1e-5);
+        assert_eq!(entries[0].fragments.len(), 12);
+    }
+
+    #[test]
+    fn library_to_msp_test() {
+        let mut w = vec![];
+        library_to_msp(&mut w, &[entry()]).unwrap();
+        let text = String::from_utf8(w).unwrap();
+        assert_eq!(text, "Name: SAMPLER/2\nMW: 818.37000\nPrecursorMZ: 410.19000\nComment: Parent=410.19000 Mods=1/2,M,Oxidation Protein=P02769\nNum peaks: 2\n159.07642\t1\t\"b2\"\n175.11895\t1\t\"y1\"\n\n");
+    }
+
+    #[test]
+    fn library_binary_test() {
+        let mut w = vec![];
+        library_to_binary(&mut w, &[entry(), entry()]).unwrap();
+        let entries = library_from_binary(&mut Cursor::new(w.clone())).unwrap();
+        assert_eq!(entries, vec![entry(), entry()]);
+
+        // Invalid magic and truncated input.
+        let mut invalid = w.clone();
+        invalid[0] = b'X';
+        assert!(library_from_binary(&mut Cursor::new(invalid)).is_err());
+        w.truncate(w.len() - 4);
+        assert!(library_from_binary(&mut Cursor::new(w)).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! Peptide search match integrations.
+
+#[cfg(feature = "uniprot")]
+pub(crate) mod annotate;
+pub(crate) mod modification;
+pub(crate) mod record;
+pub(crate) mod record_list;
+
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        // Expose the low-level API in a public submodule.
+        pub mod low_level;
+
+        pub(crate) mod crosslink;
+        pub(crate) mod index;
+        pub(crate) mod library;
+        pub(crate) mod rule;
+        pub(crate) mod window;
+    }
+}
+
+#[cfg(feature = "columnar")]
+pub(crate) mod columnar;
+
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
+
+// Re-export the models into the parent module.
+pub use self::modification::Modification;
+pub use self::record::Record;
+pub use self::record_list::RecordList;
+
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        pub use self::crosslink::{CrosslinkMatch, CrosslinkMatchList};
+        pub use self::index::{write_peptide_index, write_peptide_index_path, IndexedPeptide, PeptideIndex};
+        pub use self::library::{library_from_binary, library_to_binary, library_to_msp, LibraryEntry, LibraryOptions};
+        pub use self::rule::{modification_shifts, modified_forms, ModificationRule};
+        pub use self::window::{windows_to_motif, SiteWindow, WindowOptions};
+    }
+}
+#[cfg(feature = "uniprot")]
+pub use self::annotate::{annotate_matches, AnnotatedMatch};
+#[cfg(all(feature = "uniprot", feature = "csv"))]
+pub use self::annotate::annotations_to_csv;
+#[cfg(feature = "uniprot")]
+pub use self::index::{index_peptides, index_peptides_with_cancel};
+#[cfg(feature = "uniprot")]
+pub use self::library::generate_library;
+#[cfg(feature = "uniprot")]
+pub use self::window::site_windows;
@@ -0,0 +1,42 @@
+//! Model for peptide modification definitions.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Model for a modified residue within a peptide.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Modification {
+    /// 0-based position of the modified residue in the peptide.
+    pub position: u32,
+    /// Modification name, for example, `"Phospho"`.
+    pub name: String,
+    /// Monoisotopic mass shift, in Daltons.
+    pub mass: f64,
+}
+
+impl Modification {
+    /// Create new, empty modification.
+    #[inline]
+    pub fn new() -> Self {
+        Modification {
+            position: 0,
+            name: String::new(),
+            mass: 0.0,
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_modification_test() {
+        let modification = Modification { position: 2, name: String::from("Phospho"), mass: 79.966331 };
+        let text = format!("{:?}", modification);
+        assert_eq!(text, "Modification { position: 2, name: \"Phospho\", mass: 79.966331 }");
+    }
+}
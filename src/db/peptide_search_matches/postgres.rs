@@ -0,0 +1,64 @@
+//! Helper utilities for peptide search match PostgreSQL COPY export.
+//!
+//! Exports one row per match, with the modifications stored as array
+//! columns of the positions, names and mass shifts.
+
+use std::borrow::Cow;
+
+use traits::{PgColumn, PgCopy, PgType, PgValue};
+use super::record::Record;
+
+impl PgCopy for Record {
+    fn pg_columns() -> Vec<PgColumn> {
+        vec![
+            PgColumn::new("num", PgType::Bigint),
+            PgColumn::new("file", PgType::Text),
+            PgColumn::new("peptide", PgType::Text),
+            PgColumn::new("modification_positions", PgType::BigintArray),
+            PgColumn::new("modification_names", PgType::TextArray),
+            PgColumn::new("modification_masses", PgType::DoubleArray),
+            PgColumn::new("proteins", PgType::TextArray),
+            PgColumn::new("charge", PgType::Smallint),
+            PgColumn::new("score", PgType::Double),
+        ]
+    }
+
+    fn pg_values<'a>(&'a self) -> Vec<PgValue<'a>> {
+        vec![
+            PgValue::Bigint(self.num as i64),
+            PgValue::Text(Cow::from(self.file.as_str())),
+            PgValue::Text(String::from_utf8_lossy(&self.peptide)),
+            PgValue::BigintArray(self.modifications.iter().map(|m| m.position as i64).collect()),
+            PgValue::TextArray(self.modifications.iter().map(|m| Cow::from(m.name.as_str())).collect()),
+            PgValue::DoubleArray(self.modifications.iter().map(|m| m.mass).collect()),
+            PgValue::TextArray(self.proteins.iter().map(|p| Cow::from(p.as_str())).collect()),
+            PgValue::Smallint(self.charge as i16),
+            PgValue::Double(self.score),
+        ]
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::modification::Modification;
+
+    #[test]
+    fn pg_copy_test() {
+        let mut record = Record::new();
+        record.num = 33450;
+        record.peptide = b"SAMPLER".to_vec();
+        record.modifications.push(Modification { position: 3, name: String::from("Oxidation"), mass: 15.994915 });
+        record.proteins = vec![String::from("P12345"), String::from("Q67890")];
+        record.charge = 2;
+        record.score = 25.5;
+
+        let mut w = vec![];
+        Record::to_pg_text_iter(&mut w, vec![record].iter()).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "33450\t\tSAMPLER\t{3}\t{\"Oxidation\"}\t{15.994915}\t{\"P12345\",\"Q67890\"}\t2\t25.5\n");
+        assert_eq!(Record::pg_copy_statement("psms", true), "COPY psms (num, file, peptide, modification_positions, modification_names, modification_masses, proteins, charge, score) FROM STDIN (FORMAT binary);\n");
+    }
+}
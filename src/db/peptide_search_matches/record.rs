@@ -0,0 +1,57 @@
+//! Model for peptide search match definitions.
+
+use super::modification::Modification;
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Model for a peptide identified from a spectral scan.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Record {
+    /// Scan number of the matched spectrum.
+    pub num: u32,
+    /// File name of the matched spectrum.
+    pub file: String,
+    /// Peptide sequence, without modifications.
+    pub peptide: Vec<u8>,
+    /// Modified residues in the peptide.
+    pub modifications: Vec<Modification>,
+    /// Accession numbers of the proteins containing the peptide.
+    pub proteins: Vec<String>,
+    /// Charge state of the precursor.
+    pub charge: i8,
+    /// Search engine score, higher is better.
+    pub score: f64,
+}
+
+impl Record {
+    /// Create new, empty peptide search match.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            num: 0,
+            file: String::new(),
+            peptide: vec![],
+            modifications: vec![],
+            proteins: vec![],
+            charge: 0,
+            score: 0.0,
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_record_test() {
+        let mut record = Record::new();
+        record.peptide = b"SAMPLER".to_vec();
+        let text = format!("{:?}", record);
+        assert_eq!(text, "Record { num: 0, file: \"\", peptide: [83, 65, 77, 80, 76, 69, 82], modifications: [], proteins: [], charge: 0, score: 0.0 }");
+    }
+}
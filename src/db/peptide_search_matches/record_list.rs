@@ -0,0 +1,9 @@
+//! Model for peptide search match collections.
+
+use super::record::Record;
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Peptide search match collection type.
+pub type RecordList = Vec<Record>;
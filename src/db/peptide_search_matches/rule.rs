@@ -0,0 +1,173 @@
+//! Modification rules, and enumeration of modified peptide forms.
+
+use super::modification::Modification;
+
+/// Rule for a modification of specific residues.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModificationRule {
+    /// Modification name, for example, `"Phospho"`.
+    pub name: String,
+    /// Residues modified by the rule.
+    pub residues: Vec<u8>,
+    /// Monoisotopic mass shift, in Daltons.
+    pub mass: f64,
+    /// Modify every matching residue, rather than optionally.
+    pub fixed: bool,
+}
+
+impl ModificationRule {
+    /// Create new fixed modification rule.
+    #[inline]
+    pub fn fixed(name: &str, residues: &[u8], mass: f64) -> Self {
+        ModificationRule {
+            name: String::from(name),
+            residues: residues.to_vec(),
+            mass: mass,
+            fixed: true,
+        }
+    }
+
+    /// Create new variable modification rule.
+    #[inline]
+    pub fn variable(name: &str, residues: &[u8], mass: f64) -> Self {
+        ModificationRule {
+            fixed: false,
+            ..ModificationRule::fixed(name, residues, mass)
+        }
+    }
+
+    /// Fixed carbamidomethylation of cysteine.
+    #[inline]
+    pub fn carbamidomethyl() -> Self {
+        ModificationRule::fixed("Carbamidomethyl", b"C", 57.021464)
+    }
+
+    /// Variable oxidation of methionine.
+    #[inline]
+    pub fn oxidation() -> Self {
+        ModificationRule::variable("Oxidation", b"M", 15.994915)
+    }
+
+    /// Variable phosphorylation of serine, threonine and tyrosine.
+    #[inline]
+    pub fn phospho() -> Self {
+        ModificationRule::variable("Phospho", b"STY", 79.966331)
+    }
+
+    /// Check if the rule modifies a residue, ignoring case.
+    #[inline]
+    pub fn matches(&self, residue: u8) -> bool {
+        self.residues.contains(&residue.to_ascii_uppercase())
+    }
+
+    /// Create the modification of the residue at a 0-based position.
+    #[inline]
+    fn at(&self, position: usize) -> Modification {
+        Modification {
+            position: position as u32,
+            name: self.name.clone(),
+            mass: self.mass,
+        }
+    }
+}
+
+/// Enumerate the modified forms of a peptide.
+///
+/// Every form has all fixed modifications, and up to `max_variable`
+/// variable modifications, with at most one modification per residue.
+/// Residues with a fixed modification are not variably modified. The
+/// first form has no variable modifications, and the modifications in
+/// each form are sorted by position.
+pub fn modified_forms(peptide: &[u8], rules: &[ModificationRule], max_variable: usize)
+    -> Vec<Vec<Modification>>
+{
+    let mut fixed = vec![];
+    let mut candidates = vec![];
+    for (position, &residue) in peptide.iter().enumerate() {
+        match rules.iter().find(|r| r.fixed && r.matches(residue)) {
+            Some(rule) => fixed.push(rule.at(position)),
+            None       => {
+                for rule in rules.iter().filter(|r| !r.fixed && r.matches(residue)) {
+                    candidates.push(rule.at(position));
+                }
+            },
+        }
+    }
+
+    let mut forms = vec![];
+    let mut chosen = vec![];
+    choose_variable(&candidates, 0, max_variable, &mut chosen, &mut |chosen| {
+        let mut form = fixed.clone();
+        form.extend(chosen.iter().map(|m: &&Modification| (*m).clone()));
+        form.sort_by_key(|m| m.position);
+        forms.push(form);
+    });
+
+    forms
+}
+
+/// Recursively choose variable modifications at distinct positions.
+pub(crate) fn choose_variable<'a, F>(candidates: &'a [Modification], start: usize, remaining: usize, chosen: &mut Vec<&'a Modification>, emit: &mut F)
+    where F: FnMut(&[&'a Modification])
+{
+    emit(chosen);
+    if remaining == 0 {
+        return;
+    }
+    for (i, candidate) in candidates.iter().enumerate().skip(start) {
+        if chosen.iter().any(|m| m.position == candidate.position) {
+            continue;
+        }
+        chosen.push(candidate);
+        choose_variable(candidates, i + 1, remaining - 1, chosen, emit);
+        chosen.pop();
+    }
+}
+
+/// Get the mass shift of each residue in a peptide from its modifications.
+pub fn modification_shifts(length: usize, modifications: &[Modification]) -> Vec<f64> {
+    let mut shifts = vec![0.0; length];
+    for modification in modifications {
+        if let Some(shift) = shifts.get_mut(modification.position as usize) {
+            *shift += modification.mass;
+        }
+    }
+    shifts
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(form: &[Modification]) -> Vec<(u32, &str)> {
+        form.iter().map(|m| (m.position, m.name.as_str())).collect()
+    }
+
+    #[test]
+    fn modified_forms_test() {
+        let rules = vec![ModificationRule::carbamidomethyl(), ModificationRule::oxidation(), ModificationRule::phospho()];
+        let forms = modified_forms(b"CMSK", &rules, 2);
+        assert_eq!(forms.len(), 4);
+        assert_eq!(summary(&forms[0]), vec![(0, "Carbamidomethyl")]);
+        assert_eq!(summary(&forms[1]), vec![(0, "Carbamidomethyl"), (1, "Oxidation")]);
+        assert_eq!(summary(&forms[2]), vec![(0, "Carbamidomethyl"), (1, "Oxidation"), (2, "Phospho")]);
+        assert_eq!(summary(&forms[3]), vec![(0, "Carbamidomethyl"), (2, "Phospho")]);
+
+        assert_eq!(modified_forms(b"CMSK", &rules, 1).len(), 3);
+        assert_eq!(modified_forms(b"CMSK", &rules, 0).len(), 1);
+        assert_eq!(modified_forms(b"PEPK", &[], 2), vec![vec![]]);
+
+        // Fixed modifications exclude variable modifications.
+        let rules = vec![ModificationRule::fixed("TMT", b"K", 229.162932), ModificationRule::variable("Acetyl", b"K", 42.010565)];
+        assert_eq!(modified_forms(b"AKAK", &rules, 2).len(), 1);
+    }
+
+    #[test]
+    fn modification_shifts_test() {
+        let modifications = vec![ModificationRule::phospho().at(1), ModificationRule::oxidation().at(5)];
+        assert_eq!(modification_shifts(3, &modifications), vec![0.0, 79.966331, 0.0]);
+    }
+}
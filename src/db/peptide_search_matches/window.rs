@@ -0,0 +1,163 @@
+//! Sequence windows around modification sites, for motif analysis.
+//!
+//! Modified residues in peptide matches are mapped to their protein
+//! positions, and the surrounding protein residues are extracted as
+//! fixed-width, padded windows, centered on the modified residue.
+
+use std::io::Write;
+
+use util::Result;
+
+#[cfg(feature = "uniprot")]
+use std::collections::HashSet;
+#[cfg(feature = "uniprot")]
+use bio::motif::sequence_window;
+#[cfg(feature = "uniprot")]
+use db::uniprot;
+#[cfg(feature = "uniprot")]
+use io::store::RecordStore;
+#[cfg(feature = "uniprot")]
+use super::record::Record;
+
+/// Options for sequence windows around modification sites.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WindowOptions {
+    /// Number of residues on each side of the site.
+    pub flank: usize,
+    /// Character to pad the windows past the ends of the protein.
+    pub padding: u8,
+}
+
+impl Default for WindowOptions {
+    #[inline]
+    fn default() -> Self {
+        WindowOptions {
+            flank: 7,
+            padding: b'_',
+        }
+    }
+}
+
+/// Sequence window around a modification site in a protein.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SiteWindow {
+    /// Accession number of the protein.
+    pub protein: String,
+    /// 1-based position of the site in the protein.
+    pub position: u32,
+    /// Modified residue.
+    pub residue: u8,
+    /// Modification name.
+    pub modification: String,
+    /// Residues centered on the site.
+    pub window: Vec<u8>,
+}
+
+/// Extract the windows around the modification sites of peptide matches.
+///
+/// Each modified residue is mapped to every protein containing the
+/// peptide, at the first occurrence of the peptide in the protein.
+/// Proteins missing from the store, or not containing the peptide,
+/// are skipped. Sites shared by multiple matches are only reported
+/// once, in the order first seen.
+#[cfg(feature = "uniprot")]
+pub fn site_windows<'a, Iter>(matches: Iter, proteins: &RecordStore<uniprot::Record>, options: &WindowOptions)
+    -> Vec<SiteWindow>
+    where Iter: IntoIterator<Item = &'a Record>
+{
+    let mut seen = HashSet::new();
+    let mut windows = vec![];
+    for record in matches {
+        for accession in record.proteins.iter() {
+            let protein = match proteins.get(accession.as_str()) {
+                Some(protein) => protein,
+                None          => continue,
+            };
+            let offset = match find_peptide(&protein.sequence, &record.peptide) {
+                Some(offset) => offset,
+                None         => {
+                    log_trace!("peptide not found in protein {}", accession);
+                    continue;
+                },
+            };
+            for modification in record.modifications.iter() {
+                let index = offset + modification.position as usize;
+                let key = (accession.clone(), index, modification.name.clone());
+                if !seen.insert(key) {
+                    continue;
+                }
+                if let Some(window) = sequence_window(&protein.sequence, index, options.flank, options.padding) {
+                    windows.push(SiteWindow {
+                        protein: accession.clone(),
+                        position: index as u32 + 1,
+                        residue: protein.sequence[index],
+                        modification: modification.name.clone(),
+                        window: window,
+                    });
+                }
+            }
+        }
+    }
+    windows
+}
+
+/// Find the first occurrence of a peptide in a protein sequence.
+#[cfg(feature = "uniprot")]
+pub(crate) fn find_peptide(sequence: &[u8], peptide: &[u8]) -> Option<usize> {
+    if peptide.is_empty() || peptide.len() > sequence.len() {
+        return None;
+    }
+    sequence.windows(peptide.len()).position(|w| w == peptide)
+}
+
+/// Export the windows as motif analysis input, one window per line.
+pub fn windows_to_motif<'a, Iter, T>(writer: &mut T, windows: Iter) -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a SiteWindow>
+{
+    for window in windows {
+        writer.write_all(&window.window)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "uniprot")]
+    #[test]
+    fn site_windows_test() {
+        use super::super::modification::Modification;
+
+        let mut protein = uniprot::Record::new();
+        protein.id = String::from("P46406");
+        protein.sequence = b"MVKVGVNGFGRIGRLVTRAAFNSGK".to_vec();
+        let store = RecordStore::new(vec![protein]);
+
+        let mut record = Record::new();
+        record.peptide = b"LVTR".to_vec();
+        record.proteins = vec![String::from("P46406"), String::from("P02769")];
+        record.modifications = vec![Modification { position: 2, name: String::from("Phospho"), mass: 79.966331 }];
+        let mut terminal = Record::new();
+        terminal.peptide = b"MVK".to_vec();
+        terminal.proteins = vec![String::from("P46406")];
+        terminal.modifications = vec![Modification { position: 0, name: String::from("Acetyl"), mass: 42.010565 }];
+
+        let options = WindowOptions { flank: 3, padding: b'_' };
+        let windows = site_windows(vec![&record, &record, &terminal], &store, &options);
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].position, 17);
+        assert_eq!(windows[0].residue, b'T');
+        assert_eq!(windows[0].window, b"RLVTRAA".to_vec());
+        assert_eq!(windows[1].window, b"___MVKV".to_vec());
+
+        let mut w = vec![];
+        windows_to_motif(&mut w, windows.iter()).unwrap();
+        assert_eq!(w, b"RLVTRAA\n___MVKV\n".to_vec());
+    }
+}
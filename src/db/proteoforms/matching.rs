@@ -0,0 +1,187 @@
+//! Match observed intact masses to proteoforms of UniProt records.
+
+use std::cmp::Ordering;
+
+use bio::mass::Tolerance;
+use bio::proteins::ProteinMassTable;
+use db::mass_spectra::NeutralMass;
+use db::peptide_search_matches::Modification;
+use db::peptide_search_matches::rule::choose_variable;
+use db::uniprot;
+use super::record::Record;
+use super::record_list::RecordList;
+
+/// Mass shift for a modified residue annotation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KnownModification {
+    /// Case-insensitive keyword within the feature description.
+    pub keyword: String,
+    /// Modification name, for example, `"Phospho"`.
+    pub name: String,
+    /// Monoisotopic mass shift, in Daltons.
+    pub mass: f64,
+}
+
+impl KnownModification {
+    /// Create new known modification from a description keyword.
+    #[inline]
+    pub fn new(keyword: &str, name: &str, mass: f64) -> Self {
+        KnownModification {
+            keyword: keyword.to_lowercase(),
+            name: String::from(name),
+            mass: mass,
+        }
+    }
+}
+
+/// Options to match intact masses to proteoforms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProteoformOptions {
+    /// Tolerance of the observed neutral masses.
+    pub tol: Tolerance,
+    /// Mass shifts of the modified residue annotations.
+    ///
+    /// The first known modification with a keyword found in the
+    /// description is used, so more specific keywords must come first.
+    pub known: Vec<KnownModification>,
+    /// Maximum number of modified residues in a proteoform.
+    pub max_modifications: usize,
+}
+
+impl Default for ProteoformOptions {
+    /// Common modified residues from the UniProt controlled vocabulary.
+    fn default() -> Self {
+        ProteoformOptions {
+            tol: Tolerance::Ppm(10.0),
+            known: vec![
+                KnownModification::new("phospho", "Phospho", 79.966331),
+                KnownModification::new("acetyl", "Acetyl", 42.010565),
+                KnownModification::new("trimethyl", "Trimethyl", 42.04695),
+                KnownModification::new("dimethyl", "Dimethyl", 28.0313),
+                KnownModification::new("methyl", "Methyl", 14.01565),
+                KnownModification::new("hydroxy", "Hydroxylation", 15.994915),
+            ],
+            max_modifications: 3,
+        }
+    }
+}
+
+/// Get the known modifications from the modified residue features.
+fn known_modifications(record: &uniprot::Record, options: &ProteoformOptions) -> Vec<Modification> {
+    let mut modifications = vec![];
+    for feature in record.features.iter().filter(|f| f.kind == "modified residue") {
+        // UniProt feature positions are 1-based.
+        if feature.begin == 0 || feature.begin as usize > record.sequence.len() {
+            continue;
+        }
+        let description = feature.description.to_lowercase();
+        if let Some(known) = options.known.iter().find(|k| description.contains(&k.keyword)) {
+            modifications.push(Modification {
+                position: feature.begin - 1,
+                name: known.name.clone(),
+                mass: known.mass,
+            });
+        }
+    }
+    modifications
+}
+
+/// Match observed neutral masses to proteoforms of the UniProt records.
+///
+/// Each record is matched unmodified, and with every combination of
+/// up to `max_modifications` of its annotated modified residues.
+/// Returns a proteoform for each match within the tolerance, sorted
+/// by observed mass, then by increasing absolute mass error. The masses
+/// are calculated with the mass table, for example, from
+/// `ProteinMassTable::average()` for unresolved isotope envelopes.
+pub fn match_proteoforms(masses: &[NeutralMass], proteins: &[uniprot::Record], options: &ProteoformOptions, table: &ProteinMassTable)
+    -> RecordList
+{
+    let mut list = RecordList::new();
+    for protein in proteins.iter().filter(|p| !p.sequence.is_empty()) {
+        let unmodified = table.total_sequence_mass(&protein.sequence);
+        let candidates = known_modifications(protein, options);
+        let mut chosen = vec![];
+        choose_variable(&candidates, 0, options.max_modifications, &mut chosen, &mut |chosen| {
+            let shift: f64 = chosen.iter().map(|m| m.mass).sum();
+            let theoretical = unmodified + shift;
+            for mass in masses.iter().filter(|m| options.tol.matches(theoretical, m.mass)) {
+                let mut modifications: Vec<Modification> = chosen.iter().map(|&m| m.clone()).collect();
+                modifications.sort_by_key(|m| m.position);
+                list.push(Record {
+                    accession: protein.id.clone(),
+                    modifications: modifications,
+                    theoretical_mass: theoretical,
+                    observed_mass: mass.mass,
+                    intensity: mass.intensity,
+                });
+            }
+        });
+    }
+
+    list.sort_by(|x, y| {
+        x.observed_mass.partial_cmp(&y.observed_mass)
+            .unwrap_or(Ordering::Equal)
+            .then(x.mass_error().abs().partial_cmp(&y.mass_error().abs()).unwrap_or(Ordering::Equal))
+    });
+    list
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use db::uniprot::Feature;
+    use super::*;
+
+    fn protein() -> uniprot::Record {
+        let mut record = uniprot::Record::new();
+        record.id = String::from("P12345");
+        record.sequence = b"MSAMPLEKTSAMPLER".to_vec();
+        record.features = vec![
+            Feature::new("modified residue", "Phosphoserine", 2, 2),
+            Feature::new("modified residue", "N6-acetyllysine", 8, 8),
+            Feature::new("modified residue", "Unknown", 9, 9),
+            Feature::new("modified residue", "Phosphothreonine", 100, 100),
+            Feature::new("chain", "Protein", 1, 16),
+        ];
+        record
+    }
+
+    fn neutral(mass: f64) -> NeutralMass {
+        NeutralMass { mass: mass, intensity: 100.0, charges: vec![10] }
+    }
+
+    #[test]
+    fn known_modifications_test() {
+        let modifications = known_modifications(&protein(), &ProteoformOptions::default());
+        let summary: Vec<(u32, &str)> = modifications.iter().map(|m| (m.position, m.name.as_str())).collect();
+        assert_eq!(summary, vec![(1, "Phospho"), (7, "Acetyl")]);
+    }
+
+    #[test]
+    fn match_proteoforms_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let unmodified = table.total_sequence_mass(b"MSAMPLEKTSAMPLER");
+        let masses = vec![
+            neutral(unmodified + 79.966331 + 42.010565),
+            neutral(unmodified),
+            neutral(unmodified + 500.0),
+        ];
+        let options = ProteoformOptions::default();
+        let list = match_proteoforms(&masses, &[protein()], &options, &table);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].accession, "P12345");
+        assert!(list[0].modifications.is_empty());
+        assert_approx_eq!(list[0].mass_error(), 0.0);
+        assert_eq!(list[1].modifications.len(), 2);
+        assert_eq!(list[1].modifications[0].name, "Phospho");
+        assert_approx_eq!(list[1].observed_mass, unmodified + 121.976896);
+
+        // limit the number of modified residues
+        let mut options = ProteoformOptions::default();
+        options.max_modifications = 1;
+        assert_eq!(match_proteoforms(&masses, &[protein()], &options, &table).len(), 1);
+    }
+}
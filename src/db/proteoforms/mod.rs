@@ -0,0 +1,14 @@
+//! Top-down proteoform integrations.
+//!
+//! Matches the neutral masses from deconvolved intact protein spectra
+//! to UniProt records, with combinations of the modified residues
+//! annotated in each record.
+
+pub(crate) mod matching;
+pub(crate) mod record;
+pub(crate) mod record_list;
+
+// Re-export the models into the parent module.
+pub use self::matching::{match_proteoforms, KnownModification, ProteoformOptions};
+pub use self::record::Record;
+pub use self::record_list::RecordList;
@@ -0,0 +1,70 @@
+//! Model for proteoform definitions.
+
+use db::peptide_search_matches::Modification;
+
+/// Model for a proteoform matched to an observed intact mass.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Record {
+    /// UniProt accession number of the protein.
+    pub accession: String,
+    /// Modified residues, with 0-based positions in the protein sequence.
+    pub modifications: Vec<Modification>,
+    /// Theoretical neutral mass of the modified protein, in Daltons.
+    pub theoretical_mass: f64,
+    /// Observed neutral mass, in Daltons.
+    pub observed_mass: f64,
+    /// Intensity of the observed neutral mass.
+    pub intensity: f64,
+}
+
+impl Record {
+    /// Create new, empty proteoform.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            accession: String::new(),
+            modifications: vec![],
+            theoretical_mass: 0.0,
+            observed_mass: 0.0,
+            intensity: 0.0,
+        }
+    }
+
+    /// Get the summed mass shift of the modifications.
+    #[inline]
+    pub fn modification_mass(&self) -> f64 {
+        self.modifications.iter().map(|m| m.mass).sum()
+    }
+
+    /// Get the difference between the observed and theoretical mass.
+    #[inline]
+    pub fn mass_error(&self) -> f64 {
+        self.observed_mass - self.theoretical_mass
+    }
+
+    /// Get the mass error relative to the theoretical mass, in parts-per-million.
+    #[inline]
+    pub fn ppm_error(&self) -> f64 {
+        self.mass_error() / self.theoretical_mass * 1e6
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properties_record_test() {
+        let mut r = Record::new();
+        r.theoretical_mass = 10000.0;
+        r.observed_mass = 10000.1;
+        r.modifications.push(Modification { position: 1, name: String::from("Phospho"), mass: 79.966331 });
+        r.modifications.push(Modification { position: 0, name: String::from("Acetyl"), mass: 42.010565 });
+        assert_approx_eq!(r.modification_mass(), 121.976896);
+        assert_approx_eq!(r.mass_error(), 0.1, 1e-9);
+        assert_approx_eq!(r.ppm_error(), 10.0, 1e-6);
+    }
+}
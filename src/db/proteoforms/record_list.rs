@@ -0,0 +1,6 @@
+//! Model for proteoform collections.
+
+use super::record::Record;
+
+/// Proteoform collection type.
+pub type RecordList = Vec<Record>;
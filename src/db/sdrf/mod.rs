@@ -0,0 +1,21 @@
+//! SDRF-Proteomics integrations.
+//!
+//! Reads and writes sample and data relationship format (SDRF) files,
+//! which describe the experimental design of a proteomics experiment:
+//! the samples, their characteristics, and the data files and labels
+//! each sample was measured in.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
+
+pub(crate) mod record;
+pub(crate) mod record_list;
+pub(crate) mod tsv;
+pub(crate) mod valid;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+// Re-export the models into the parent module.
+pub use self::record::{Attribute, Record};
+pub use self::record_list::{RecordList, RecordListExt};
@@ -0,0 +1,163 @@
+//! Model for SDRF-Proteomics sample definitions.
+
+use std::path::Path;
+
+/// Named sample attribute, such as a characteristic or comment.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Attribute {
+    /// Attribute name, within the brackets of the column header.
+    pub name: String,
+    /// Attribute value.
+    pub value: String,
+}
+
+impl Attribute {
+    /// Create new attribute from the name and value.
+    #[inline]
+    pub fn new(name: &str, value: &str) -> Self {
+        Attribute {
+            name: String::from(name),
+            value: String::from(value),
+        }
+    }
+}
+
+/// Model for a single sample measured in a single assay (one SDRF row).
+///
+/// Attributes are stored in column order, and may be repeated, such
+/// as for multiple "modification parameters" comments.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Record {
+    /// Sample name ("source name").
+    pub source_name: String,
+    /// Sample characteristics ("characteristics[...]").
+    pub characteristics: Vec<Attribute>,
+    /// Assay name, typically the MS run ("assay name").
+    pub assay_name: String,
+    /// Technology type ("technology type").
+    pub technology_type: String,
+    /// Data file and acquisition comments ("comment[...]").
+    pub comments: Vec<Attribute>,
+    /// Experimental factors ("factor value[...]").
+    pub factor_values: Vec<Attribute>,
+}
+
+impl Record {
+    /// Create new, empty sample.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            source_name: String::new(),
+            characteristics: vec![],
+            assay_name: String::new(),
+            technology_type: String::new(),
+            comments: vec![],
+            factor_values: vec![],
+        }
+    }
+
+    /// Get the value of the first characteristic with the name.
+    #[inline]
+    pub fn characteristic(&self, name: &str) -> Option<&str> {
+        find_attribute(&self.characteristics, name)
+    }
+
+    /// Get the value of the first comment with the name.
+    #[inline]
+    pub fn comment(&self, name: &str) -> Option<&str> {
+        find_attribute(&self.comments, name)
+    }
+
+    /// Get the value of the first factor value with the name.
+    #[inline]
+    pub fn factor_value(&self, name: &str) -> Option<&str> {
+        find_attribute(&self.factor_values, name)
+    }
+
+    /// Get the organism of the sample.
+    #[inline]
+    pub fn organism(&self) -> Option<&str> {
+        self.characteristic("organism")
+    }
+
+    /// Get the data (raw spectra) file name for the assay.
+    #[inline]
+    pub fn data_file(&self) -> Option<&str> {
+        self.comment("data file")
+    }
+
+    /// Get the data file name, without the directory or extension.
+    ///
+    /// Links the sample to spectra or quantitation files converted
+    /// from the raw data file, such as "run1.mgf" for "run1.raw".
+    #[inline]
+    pub fn data_file_stem(&self) -> Option<&str> {
+        self.data_file().and_then(file_stem)
+    }
+
+    /// Get the fraction identifier for the assay.
+    #[inline]
+    pub fn fraction(&self) -> Option<&str> {
+        self.comment("fraction identifier")
+    }
+
+    /// Get the label (for example, "TMT126" or "label free sample").
+    ///
+    /// Labels are typically written as ontology terms, with the name
+    /// of the term following "NT=".
+    #[inline]
+    pub fn label(&self) -> Option<&str> {
+        self.comment("label").map(term_name)
+    }
+}
+
+/// Find the value of the first attribute with the name.
+///
+/// Attribute names are case-insensitive.
+#[inline]
+fn find_attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .find(|a| a.name.eq_ignore_ascii_case(name))
+        .map(|a| a.value.as_str())
+}
+
+/// Get the file name, without the directory or extension.
+#[inline]
+pub(crate) fn file_stem(path: &str) -> Option<&str> {
+    Path::new(path).file_stem().and_then(|s| s.to_str())
+}
+
+/// Get the name ("NT=") from an ontology term, or the value itself.
+fn term_name(value: &str) -> &str {
+    value.split(';')
+        .map(|s| s.trim())
+        .find(|s| s.starts_with("NT="))
+        .map_or(value, |s| &s[3..])
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn properties_record_test() {
+        let r = liver_normal();
+        assert_eq!(r.organism(), Some("Homo sapiens"));
+        assert_eq!(r.characteristic("Organism Part"), Some("liver"));
+        assert_eq!(r.data_file(), Some("run1.raw"));
+        assert_eq!(r.data_file_stem(), Some("run1"));
+        assert_eq!(r.fraction(), Some("1"));
+        assert_eq!(r.label(), Some("label free sample"));
+        assert_eq!(r.factor_value("disease"), Some("normal"));
+        assert_eq!(r.comment("modification parameters"), Some("NT=Oxidation;MT=Variable;TA=M;AC=Unimod:35"));
+        assert_eq!(r.characteristic("cell type"), None);
+
+        assert_eq!(term_name("TMT126"), "TMT126");
+        assert_eq!(file_stem("/data/run1.mzML"), Some("run1"));
+        assert_eq!(Record::new().data_file_stem(), None);
+    }
+}
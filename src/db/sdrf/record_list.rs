@@ -0,0 +1,80 @@
+//! Model for SDRF-Proteomics sample collections.
+
+use std::collections::HashMap;
+
+use super::record::{file_stem, Record};
+
+/// SDRF-Proteomics sample collection type.
+pub type RecordList = Vec<Record>;
+
+/// Lookup maps for SDRF-Proteomics sample collections.
+///
+/// Link samples to the spectra and quantitation files for each assay.
+/// Data files are matched by file name, without the directory or
+/// extension, so converted files ("run1.mgf") match the raw data
+/// file ("run1.raw").
+pub trait RecordListExt {
+    /// Create a map from sample name to all assays for the sample.
+    fn index_by_source_name(&self) -> HashMap<&str, Vec<&Record>>;
+
+    /// Create a map from data file name to all samples in the file.
+    ///
+    /// Multiplexed (labeled) data files contain multiple samples.
+    fn index_by_data_file(&self) -> HashMap<&str, Vec<&Record>>;
+
+    /// Find the sample measured in the data file with the label.
+    ///
+    /// Identifies the sample for a spectra file, or for a quantitation
+    /// column, with a data file and label (channel).
+    fn find_sample(&self, path: &str, label: &str) -> Option<&Record>;
+}
+
+impl RecordListExt for [Record] {
+    fn index_by_source_name(&self) -> HashMap<&str, Vec<&Record>> {
+        let mut map: HashMap<&str, Vec<&Record>> = HashMap::new();
+        for record in self.iter().filter(|r| !r.source_name.is_empty()) {
+            map.entry(record.source_name.as_str()).or_insert_with(Vec::new).push(record);
+        }
+        map
+    }
+
+    fn index_by_data_file(&self) -> HashMap<&str, Vec<&Record>> {
+        let mut map: HashMap<&str, Vec<&Record>> = HashMap::new();
+        for record in self.iter() {
+            if let Some(stem) = record.data_file_stem() {
+                map.entry(stem).or_insert_with(Vec::new).push(record);
+            }
+        }
+        map
+    }
+
+    fn find_sample(&self, path: &str, label: &str) -> Option<&Record> {
+        let stem = file_stem(path)?;
+        self.iter().find(|r| r.data_file_stem() == Some(stem) && r.label() == Some(label))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn index_list_test() {
+        let v = vec![liver_normal(), liver_tumor(), Record::new()];
+        let map = v.index_by_source_name();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["sample 1"], vec![&v[0]]);
+
+        let map = v.index_by_data_file();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["run2"], vec![&v[1]]);
+
+        assert_eq!(v.find_sample("spectra/run2.mgf", "label free sample"), Some(&v[1]));
+        assert_eq!(v.find_sample("run2.mgf", "TMT126"), None);
+        assert_eq!(v.find_sample("run3.mgf", "label free sample"), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Shared helper utilities for SDRF-Proteomics unit testing.
+
+use super::record::{Attribute, Record};
+
+// RECORDS
+
+/// Create a sample for normal liver tissue.
+pub fn liver_normal() -> Record {
+    Record {
+        source_name: String::from("sample 1"),
+        characteristics: vec![
+            Attribute::new("organism", "Homo sapiens"),
+            Attribute::new("organism part", "liver"),
+            Attribute::new("disease", "normal"),
+        ],
+        assay_name: String::from("run 1"),
+        technology_type: String::from("proteomic profiling by mass spectrometry"),
+        comments: vec![
+            Attribute::new("data file", "run1.raw"),
+            Attribute::new("fraction identifier", "1"),
+            Attribute::new("label", "AC=MS:1002038;NT=label free sample"),
+            Attribute::new("instrument", "AC=MS:1001911;NT=Q Exactive"),
+            Attribute::new("modification parameters", "NT=Oxidation;MT=Variable;TA=M;AC=Unimod:35"),
+            Attribute::new("modification parameters", "NT=Carbamidomethyl;TA=C;MT=fixed;AC=UNIMOD:4"),
+        ],
+        factor_values: vec![
+            Attribute::new("disease", "normal"),
+        ],
+    }
+}
+
+/// Create a sample for liver tumor tissue.
+pub fn liver_tumor() -> Record {
+    Record {
+        source_name: String::from("sample 2"),
+        characteristics: vec![
+            Attribute::new("organism", "Homo sapiens"),
+            Attribute::new("organism part", "liver"),
+            Attribute::new("disease", "hepatocellular carcinoma"),
+        ],
+        assay_name: String::from("run 2"),
+        technology_type: String::from("proteomic profiling by mass spectrometry"),
+        comments: vec![
+            Attribute::new("data file", "run2.raw"),
+            Attribute::new("fraction identifier", "1"),
+            Attribute::new("label", "AC=MS:1002038;NT=label free sample"),
+            Attribute::new("instrument", "AC=MS:1001911;NT=Q Exactive"),
+            Attribute::new("modification parameters", "NT=Oxidation;MT=Variable;TA=M;AC=Unimod:35"),
+            Attribute::new("modification parameters", "NT=Carbamidomethyl;TA=C;MT=fixed;AC=UNIMOD:4"),
+        ],
+        factor_values: vec![
+            Attribute::new("disease", "hepatocellular carcinoma"),
+        ],
+    }
+}
+
+// TSV
+
+/// SDRF-Proteomics TSV for the liver samples.
+pub const LIVER_TSV: &'static [u8] = b"source name\tcharacteristics[organism]\tcharacteristics[organism part]\tcharacteristics[disease]\tassay name\ttechnology type\tcomment[data file]\tcomment[fraction identifier]\tcomment[label]\tcomment[instrument]\tcomment[modification parameters]\tcomment[modification parameters]\tfactor value[disease]\nsample 1\tHomo sapiens\tliver\tnormal\trun 1\tproteomic profiling by mass spectrometry\trun1.raw\t1\tAC=MS:1002038;NT=label free sample\tAC=MS:1001911;NT=Q Exactive\tNT=Oxidation;MT=Variable;TA=M;AC=Unimod:35\tNT=Carbamidomethyl;TA=C;MT=fixed;AC=UNIMOD:4\tnormal\nsample 2\tHomo sapiens\tliver\thepatocellular carcinoma\trun 2\tproteomic profiling by mass spectrometry\trun2.raw\t1\tAC=MS:1002038;NT=label free sample\tAC=MS:1001911;NT=Q Exactive\tNT=Oxidation;MT=Variable;TA=M;AC=Unimod:35\tNT=Carbamidomethyl;TA=C;MT=fixed;AC=UNIMOD:4\thepatocellular carcinoma";
@@ -0,0 +1,417 @@
+//! Helper utilities for SDRF-Proteomics TSV loading and saving.
+//!
+//! The first line contains the column headers, and each following line
+//! describes a single sample in a single assay. Bracketed column headers,
+//! such as "characteristics[organism]", name the sample attributes.
+
+use std::io::prelude::*;
+use std::io::Lines;
+
+use util::*;
+use super::record::{Attribute, Record};
+
+// COLUMNS
+
+/// Column within an SDRF-Proteomics document.
+#[derive(Clone, Debug, PartialEq)]
+enum Column {
+    SourceName,
+    Characteristic(String),
+    AssayName,
+    TechnologyType,
+    Comment(String),
+    FactorValue(String),
+    /// Unrecognized column, which is ignored.
+    Other,
+}
+
+/// Get the name within the brackets of a column header with the prefix.
+///
+/// The prefix is case-insensitive.
+fn bracketed<'a>(header: &'a str, prefix: &str) -> Option<&'a str> {
+    let length = prefix.len();
+    let is_match = header.len() > length + 1 &&
+        header.is_char_boundary(length) &&
+        header[..length].eq_ignore_ascii_case(prefix) &&
+        header[length..].trim_start().starts_with('[') &&
+        header.ends_with(']');
+    if is_match {
+        let inner = header[length..].trim_start();
+        Some(inner[1..inner.len()-1].trim())
+    } else {
+        None
+    }
+}
+
+/// Parse the column from the column header.
+fn column_from_header(header: &str) -> Column {
+    let header = header.trim();
+    if let Some(name) = bracketed(header, "characteristics") {
+        Column::Characteristic(String::from(name))
+    } else if let Some(name) = bracketed(header, "comment") {
+        Column::Comment(String::from(name))
+    } else if let Some(name) = bracketed(header, "factor value") {
+        Column::FactorValue(String::from(name))
+    } else if header.eq_ignore_ascii_case("source name") {
+        Column::SourceName
+    } else if header.eq_ignore_ascii_case("assay name") {
+        Column::AssayName
+    } else if header.eq_ignore_ascii_case("technology type") {
+        Column::TechnologyType
+    } else {
+        Column::Other
+    }
+}
+
+/// Parse the columns from the header line.
+fn columns_from_header(line: &str) -> Result<Vec<Column>> {
+    let columns: Vec<Column> = line.split('\t').map(column_from_header).collect();
+    bool_to_error!(columns.contains(&Column::SourceName), InvalidInput);
+    Ok(columns)
+}
+
+/// Get the column headers for a record, in export order.
+fn record_header(record: &Record) -> Vec<String> {
+    let mut header = vec![String::from("source name")];
+    header.extend(record.characteristics.iter().map(|a| format!("characteristics[{}]", a.name)));
+    header.push(String::from("assay name"));
+    header.push(String::from("technology type"));
+    header.extend(record.comments.iter().map(|a| format!("comment[{}]", a.name)));
+    header.extend(record.factor_values.iter().map(|a| format!("factor value[{}]", a.name)));
+    header
+}
+
+// WRITER
+
+/// Internal writer state, which writes the header before the first record.
+///
+/// All records must share the same attribute columns as the first.
+pub struct TsvWriterState<'r, T: 'r + Write> {
+    writer: &'r mut T,
+    header: Option<Vec<String>>,
+}
+
+impl<'r, T: 'r + Write> TsvWriterState<'r, T> {
+    /// Construct new state from writer.
+    #[inline]
+    fn new(writer: &'r mut T) -> Self {
+        TsvWriterState {
+            writer: writer,
+            header: None,
+        }
+    }
+
+    /// Export record to TSV, writing the header if required.
+    fn export(&mut self, record: &Record) -> Result<()> {
+        let header = record_header(record);
+        match self.header {
+            Some(ref previous) => {
+                bool_to_error!(previous == &header, InvalidRecord);
+                self.writer.write_all(b"\n")?;
+            },
+            None => {
+                self.writer.write_all(header.join("\t").as_bytes())?;
+                self.writer.write_all(b"\n")?;
+            },
+        }
+        self.header = Some(header);
+        record_to_tsv(self.writer, record)
+    }
+}
+
+/// Export record to a TSV row, without the header.
+pub fn record_to_tsv<T: Write>(writer: &mut T, record: &Record)
+    -> Result<()>
+{
+    write_alls!(writer, record.source_name.as_bytes())?;
+    for attribute in record.characteristics.iter() {
+        write_alls!(writer, b"\t", attribute.value.as_bytes())?;
+    }
+    write_alls!(
+        writer,
+        b"\t", record.assay_name.as_bytes(),
+        b"\t", record.technology_type.as_bytes()
+    )?;
+    for attribute in record.comments.iter().chain(record.factor_values.iter()) {
+        write_alls!(writer, b"\t", attribute.value.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+// WRITER -- DEFAULT
+
+#[inline(always)]
+fn init_cb<T: Write>(writer: &mut T, _: u8)
+    -> Result<TsvWriterState<'_, T>>
+{
+    Ok(TsvWriterState::new(writer))
+}
+
+#[inline(always)]
+fn export_cb<'a, T: Write>(writer: &mut TsvWriterState<T>, record: &'a Record)
+    -> Result<()>
+{
+    writer.export(record)
+}
+
+#[inline(always)]
+fn dest_cb<T: Write>(_: &mut TsvWriterState<T>)
+    -> Result<()>
+{
+    Ok(())
+}
+
+/// Default exporter from a non-owning iterator to TSV.
+#[inline(always)]
+pub fn reference_iterator_to_tsv<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to TSV.
+#[inline(always)]
+pub fn value_iterator_to_tsv<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- STRICT
+
+/// Strict exporter from a non-owning iterator to TSV.
+#[inline(always)]
+pub fn reference_iterator_to_tsv_strict<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to TSV.
+#[inline(always)]
+pub fn value_iterator_to_tsv_strict<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- LENIENT
+
+/// Lenient exporter from a non-owning iterator to TSV.
+#[inline(always)]
+pub fn reference_iterator_to_tsv_lenient<'a, Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to TSV.
+#[inline(always)]
+pub fn value_iterator_to_tsv_lenient<Iter, T>(writer: &mut T, iter: Iter)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// READER
+
+/// Import record from a TSV row, with the columns from the header.
+fn record_from_row(columns: &[Column], line: &str)
+    -> Result<Record>
+{
+    let cells: Vec<&str> = line.split('\t').map(|c| c.trim()).collect();
+    bool_to_error!(cells.len() == columns.len(), InvalidInput);
+
+    let mut record = Record::new();
+    for (column, &value) in columns.iter().zip(cells.iter()) {
+        match *column {
+            Column::SourceName              => record.source_name = String::from(value),
+            Column::AssayName               => record.assay_name = String::from(value),
+            Column::TechnologyType          => record.technology_type = String::from(value),
+            Column::Characteristic(ref name) => record.characteristics.push(Attribute::new(name, value)),
+            Column::Comment(ref name)       => record.comments.push(Attribute::new(name, value)),
+            Column::FactorValue(ref name)   => record.factor_values.push(Attribute::new(name, value)),
+            Column::Other                   => (),
+        }
+    }
+
+    Ok(record)
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Parses the header from the first line, and converts each following
+/// line to a record. Unrecognized columns are ignored.
+pub struct TsvRecordIter<T: BufRead> {
+    lines: Lines<T>,
+    columns: Option<Vec<Column>>,
+}
+
+impl<T: BufRead> TsvRecordIter<T> {
+    /// Create new TsvRecordIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        TsvRecordIter {
+            lines: reader.lines(),
+            columns: None,
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for TsvRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Err(e)   => return Some(Err(From::from(e))),
+                Ok(line) => line,
+            };
+
+            let line = strip_bom(line.trim_end_matches(|c| c == '\r' || c == '\n'));
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let result = match self.columns {
+                Some(ref columns) => record_from_row(columns, line),
+                None              => {
+                    match columns_from_header(line) {
+                        Err(e) => Err(e),
+                        Ok(v)  => {
+                            self.columns = Some(v);
+                            continue;
+                        },
+                    }
+                },
+            };
+            log_parsed!("SDRF", &result);
+            return Some(result);
+        }
+    }
+}
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_tsv<T: BufRead>(reader: T) -> TsvRecordIter<T> {
+    TsvRecordIter::new(reader)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `TsvRecordIter` and converts the lines to records strictly.
+pub type TsvRecordStrictIter<T> = StrictIter<Record, TsvRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_tsv_strict<T: BufRead>(reader: T) -> TsvRecordStrictIter<T> {
+    TsvRecordStrictIter::new(iterator_from_tsv(reader))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document.
+///
+/// Wraps `TsvRecordIter` and converts the lines to records leniently.
+pub type TsvRecordLenientIter<T> = LenientIter<Record, TsvRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_tsv_lenient<T: BufRead>(reader: T) -> TsvRecordLenientIter<T> {
+    TsvRecordLenientIter::new(iterator_from_tsv(reader))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test::windows_text;
+    use super::*;
+    use super::super::record_list::RecordList;
+    use super::super::test::*;
+
+    #[test]
+    fn column_test() {
+        assert_eq!(column_from_header("Characteristics[organism]"), Column::Characteristic(String::from("organism")));
+        assert_eq!(column_from_header("comment [data file]"), Column::Comment(String::from("data file")));
+        assert_eq!(column_from_header("factor value[disease]"), Column::FactorValue(String::from("disease")));
+        assert_eq!(column_from_header("Source Name"), Column::SourceName);
+        assert_eq!(column_from_header("material type"), Column::Other);
+        assert_eq!(column_from_header("comment"), Column::Other);
+        assert!(columns_from_header("assay name\tcomment[data file]").is_err());
+    }
+
+    #[test]
+    fn iterator_to_tsv_test() {
+        let v = vec![liver_normal(), liver_tumor()];
+        let u = vec![liver_normal(), Record::new(), liver_tumor()];
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_tsv(&mut w, v.iter()).unwrap();
+        assert_eq!(w.into_inner(), LIVER_TSV.to_vec());
+
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_tsv_strict(&mut w, u.iter()).is_err());
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_tsv_lenient(&mut w, u.iter()).unwrap();
+        assert_eq!(w.into_inner(), LIVER_TSV.to_vec());
+
+        // Mismatched attribute columns.
+        let mut x = liver_tumor();
+        x.comments.pop();
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_tsv(&mut w, vec![liver_normal(), x].iter()).is_err());
+    }
+
+    #[test]
+    fn iterator_from_tsv_test() {
+        let iter = iterator_from_tsv(Cursor::new(LIVER_TSV));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![liver_normal(), liver_tumor()]);
+
+        // Windows line endings and trailing whitespace.
+        let text = windows_text(LIVER_TSV);
+        let iter = iterator_from_tsv_strict(Cursor::new(text));
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![liver_normal(), liver_tumor()]);
+
+        // Unrecognized columns are ignored.
+        let text = String::from_utf8(LIVER_TSV.to_vec()).unwrap();
+        let text = text.replace("\tassay name", "\tmaterial type\tassay name")
+            .replace("\trun ", "\ttissue\trun ");
+        let iter = iterator_from_tsv(Cursor::new(text.as_bytes()));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap(), vec![liver_normal(), liver_tumor()]);
+
+        // Missing assay name.
+        let text = [LIVER_TSV, b"\nsample 3\tHomo sapiens\tliver\tnormal\t\tproteomic profiling by mass spectrometry\trun3.raw\t1\t-\t-\t-\t-\tnormal"].concat();
+        let iter = iterator_from_tsv_strict(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_tsv_lenient(Cursor::new(&text));
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap().len(), 2);
+
+        // Wrong number of cells.
+        let text = [LIVER_TSV, b"\nsample 3\tHomo sapiens"].concat();
+        let iter = iterator_from_tsv(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+    }
+}
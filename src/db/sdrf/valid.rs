@@ -0,0 +1,40 @@
+//! Valid trait implementation for SDRF-Proteomics models.
+
+use traits::Valid;
+use super::record::{Attribute, Record};
+use super::record_list::RecordList;
+
+/// Determine if the value may be written to a single TSV cell.
+#[inline]
+fn is_cell(value: &str) -> bool {
+    !value.contains(|c| c == '\t' || c == '\n' || c == '\r')
+}
+
+/// Determine if the attribute name and value may be written to TSV.
+#[inline]
+fn is_valid_attribute(attribute: &Attribute) -> bool {
+    !attribute.name.is_empty() &&
+        is_cell(&attribute.name) &&
+        !attribute.name.contains(|c| c == '[' || c == ']') &&
+        is_cell(&attribute.value)
+}
+
+impl Valid for Record {
+    fn is_valid(&self) -> bool {
+        !self.source_name.is_empty() &&
+            is_cell(&self.source_name) &&
+            !self.assay_name.is_empty() &&
+            is_cell(&self.assay_name) &&
+            is_cell(&self.technology_type) &&
+            self.characteristics.iter().all(is_valid_attribute) &&
+            self.comments.iter().all(is_valid_attribute) &&
+            self.factor_values.iter().all(is_valid_attribute)
+    }
+}
+
+impl Valid for RecordList {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.iter().all(|ref x| x.is_valid())
+    }
+}
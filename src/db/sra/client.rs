@@ -1,25 +1,210 @@
 //! Client to request resources from the SRA.
+//!
+//! Run accessions are resolved to the FASTQ files mirrored by the
+//! European Nucleotide Archive (ENA), which are streamed and
+//! decompressed directly into the FASTQ reader, without converting
+//! the `.sra` archives with the SRA toolkit.
+//!
+//! Single-end runs have a single FASTQ file, while paired-end runs
+//! have a file for each mate, and may have a file for unpaired reads.
 
-// TODO(ahuszagh)
-//      Implement...
+use flate2::read::MultiGzDecoder;
+use std::io::{BufReader, Read};
+use url;
 
-// Look at the above
-// https://www.ncbi.nlm.nih.gov/books/NBK158899/
-// Example:
-//  ERR1953444
+use concurrency::CancellationToken;
+use io::http::{self, ResponseBody};
+use util::{ErrorKind, ReaderOptions, Result};
+use super::fastq::FastqRecordIter;
 
-// This is the file format reference:
-//      https://www.ncbi.nlm.nih.gov/sra/docs/submitformats/#bam-files
-// Need to support .sra binary records.... Le fuck.
-//      ERR1953444.sra
+/// Host URL for the ENA file report service.
+const HOST: &str = "https://www.ebi.ac.uk/ena/portal/api/filereport";
 
+/// Column containing the FASTQ file locations in the file report.
+const FASTQ_COLUMN: &str = "fastq_ftp";
 
-// Hey!
-//  We need to support these file formats...
-//    fastq-dump: Converts data to fastq and fasta format.
-//    sam-dump: Converts data to sam (human-readable bam). Data submitted as aligned bam are output as aligned sam, while other formats are output as unaligned sam.
-//    sff-dump: Converts data to sff format. Note that only data submitted as sff can be converted back to this format.
-//    abi-dump: Converts data to csfasta/csqual format. Note that data submitted in base-space can be represented in color-space, but please be aware of the advantages / disadvantages of converting between different encodings.
-//    illumina-dump: Converts data to Illumina native and qseq formats.
-//    vdb-dump: Exports the vdb-formatted data of the .sra file.
+/// Iterator over the records of a streamed FASTQ file.
+pub type RecordIterator = FastqRecordIter<BufReader<ResponseBody>>;
 
+// URLS
+
+/// Resolve a run accession (for example, `SRR390728`) to its FASTQ file URLs.
+pub fn fastq_urls(accession: &str) -> Result<Vec<String>> {
+    let url = format!("{}?{}", HOST, parameters(accession));
+    log_info!("ENA request: GET {}", url);
+    let mut response = http::get(&url)?;
+    let mut text = String::new();
+    response.read_to_string(&mut text)?;
+
+    parse_file_report(&text)
+}
+
+/// Parse the FASTQ file URLs from a tab-delimited ENA file report.
+fn parse_file_report(text: &str) -> Result<Vec<String>> {
+    let mut lines = text.lines();
+    let header = none_to_error!(lines.next(), InvalidInput);
+    let column = none_to_error!(header.split('\t').position(|c| c == FASTQ_COLUMN), InvalidInput);
+
+    let mut urls = vec![];
+    for line in lines.filter(|l| !l.is_empty()) {
+        let field = none_to_error!(line.split('\t').nth(column), InvalidInput);
+        urls.extend(field.split(';').filter(|f| !f.is_empty()).map(https_url));
+    }
+    bool_to_error!(!urls.is_empty(), InvalidInput);
+
+    Ok(urls)
+}
+
+/// Convert an ENA file location to an HTTPS URL.
+///
+/// The file report lists the FTP locations without a scheme,
+/// which are also served over HTTPS.
+fn https_url(location: &str) -> String {
+    let path = location.trim_start_matches("ftp://").trim_start_matches("https://");
+    format!("https://{}", path)
+}
+
+// RECORDS
+
+/// Stream the records from a FASTQ file URL.
+///
+/// Files with the `.gz` extension are decompressed while streaming.
+pub fn by_url(url: &str) -> Result<RecordIterator> {
+    by_url_with_options(url, &ReaderOptions::default())
+}
+
+/// Stream the records from a FASTQ file URL with reader options.
+pub fn by_url_with_options(url: &str, options: &ReaderOptions) -> Result<RecordIterator> {
+    log_info!("ENA request: GET {}", url);
+    let response = http::get(url)?;
+    Ok(record_iter(url, response, options))
+}
+
+/// Stream the records from a FASTQ file URL, stopping early if cancelled.
+///
+/// The token is checked while streaming the file, so reading
+/// a record fails once cancelled.
+pub fn by_url_with_cancel(url: &str, options: &ReaderOptions, cancel: &CancellationToken) -> Result<RecordIterator> {
+    log_info!("ENA request: GET {}", url);
+    let response = http::get_with_cancel(url, cancel)?;
+    Ok(record_iter(url, response, options))
+}
+
+/// Stream the records from each FASTQ file of a run accession.
+pub fn by_run(accession: &str) -> Result<Vec<RecordIterator>> {
+    by_run_with_options(accession, &ReaderOptions::default())
+}
+
+/// Stream the records from each FASTQ file of a run accession with reader options.
+pub fn by_run_with_options(accession: &str, options: &ReaderOptions) -> Result<Vec<RecordIterator>> {
+    fastq_urls(accession)?
+        .iter()
+        .map(|url| by_url_with_options(url, options))
+        .collect()
+}
+
+// HELPERS
+
+/// Create the record iterator for a response, decompressing `.gz` files.
+fn record_iter(url: &str, response: ResponseBody, options: &ReaderOptions) -> RecordIterator {
+    let reader: ResponseBody = match url.ends_with(".gz") {
+        true  => Box::new(MultiGzDecoder::new(response)),
+        false => response,
+    };
+
+    FastqRecordIter::with_options(BufReader::new(reader), options)
+}
+
+/// Create the query parameters for the file report of a run accession.
+fn parameters(accession: &str) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("accession", accession)
+        .append_pair("result", "read_run")
+        .append_pair("fields", FASTQ_COLUMN)
+        .append_pair("format", "tsv")
+        .finish()
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use io::http::{fixture_path, set_client_mode, ClientMode, CLIENT_MODE_LOCK};
+    use super::*;
+
+    #[test]
+    fn parse_file_report_test() {
+        let text = "run_accession\tfastq_ftp\nSRR390728\tftp.sra.ebi.ac.uk/vol1/fastq/SRR390/SRR390728/SRR390728_1.fastq.gz;ftp.sra.ebi.ac.uk/vol1/fastq/SRR390/SRR390728/SRR390728_2.fastq.gz\n";
+        let urls = parse_file_report(text).unwrap();
+        assert_eq!(urls, vec![
+            "https://ftp.sra.ebi.ac.uk/vol1/fastq/SRR390/SRR390728/SRR390728_1.fastq.gz",
+            "https://ftp.sra.ebi.ac.uk/vol1/fastq/SRR390/SRR390728/SRR390728_2.fastq.gz",
+        ]);
+
+        // Runs without FASTQ files, or reports without the column.
+        assert!(parse_file_report("run_accession\tfastq_ftp\nSRR390728\t\n").is_err());
+        assert!(parse_file_report("run_accession\nSRR390728\n").is_err());
+        assert!(parse_file_report("").is_err());
+    }
+
+    #[test]
+    fn https_url_test() {
+        assert_eq!(https_url("ftp.sra.ebi.ac.uk/vol1/x.fastq.gz"), "https://ftp.sra.ebi.ac.uk/vol1/x.fastq.gz");
+        assert_eq!(https_url("ftp://ftp.sra.ebi.ac.uk/vol1/x.fastq.gz"), "https://ftp.sra.ebi.ac.uk/vol1/x.fastq.gz");
+    }
+
+    #[test]
+    fn replay_test() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+        use super::super::test::{srr390728_2, srr390728_3};
+
+        let fastq = b"@SRR390728.2 2\nAAGTAGGTCTCGTCTGTGTTTTCTACGAGCTTGTGTTCCAGCTGACCCACTCCCTGGGTGGGGGGACTGGGT\n+SRR390728.2 2\n;;;;;;;;;;;;;;;;;4;;;;3;393.1+4&&5&&;;;;;;;;;;;;;;;;;;;;;<9;<;;;;;464262\n@SRR390728.3 3\nCCAGCCTGGCCAACAGAGTGTTACCCCGTTTTTACTTATTTATTATTATTATTTTGAGACAGAGCATTGGTC\n+SRR390728.3 3\n-;;;8;;;;;;;,*;;';-4,44;,:&,1,4'./&19;;;;;;669;;99;;;;;-;3;2;0;+;7442&2/\n";
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(fastq).unwrap();
+        let gzip = encoder.finish().unwrap();
+
+        let dir = env::temp_dir().join("bdb_sra_replay_test");
+        let report = format!("{}?{}", HOST, parameters("SRR390728"));
+        let file = "https://ftp.sra.ebi.ac.uk/vol1/fastq/SRR390/SRR390728/SRR390728.fastq.gz";
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(fixture_path(&dir, &report), format!("run_accession\tfastq_ftp\nSRR390728\t{}\n", &file[8..])).unwrap();
+        fs::write(fixture_path(&dir, file), gzip).unwrap();
+        let plain = "https://ftp.sra.ebi.ac.uk/vol1/fastq/SRR390/SRR390728/SRR390728.fastq";
+        fs::write(fixture_path(&dir, plain), &fastq[..]).unwrap();
+
+        let cancel = CancellationToken::new();
+        let (runs, cancelled) = {
+            let _lock = CLIENT_MODE_LOCK.lock().unwrap();
+            set_client_mode(ClientMode::Replay(dir.clone()));
+            let runs = by_run("SRR390728");
+            let cancelled = by_url_with_cancel(plain, &ReaderOptions::default(), &cancel);
+            set_client_mode(ClientMode::Online);
+            (runs, cancelled)
+        };
+
+        // Streaming fails once cancelled.
+        cancel.cancel();
+        assert!(cancelled.unwrap().next().unwrap().is_err());
+
+        let mut runs = runs.unwrap();
+        assert_eq!(runs.len(), 1);
+        let records: Vec<_> = runs.remove(0).map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![srr390728_2(), srr390728_3()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn by_run_test() {
+        let urls = fastq_urls("SRR390728").unwrap();
+        assert_eq!(urls.len(), 2);
+        let mut runs = by_run("SRR390728").unwrap();
+        let record = runs[0].next().unwrap().unwrap();
+        assert!(record.seq_id.starts_with("SRR390728"));
+    }
+}
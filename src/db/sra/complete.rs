@@ -8,10 +8,8 @@ use super::record_list::RecordList;
 impl Complete for Record {
     #[inline]
     fn is_complete(&self) -> bool {
-        (
-            self.is_valid() &&
+        self.is_valid() &&
             !self.description.is_empty()
-        )
     }
 }
 
@@ -0,0 +1,380 @@
+//! Quality-aware read error correction from the k-mer spectrum.
+//!
+//! Sequencing errors produce k-mers which are rare across the run,
+//! while k-mers from the genome are observed once for each read
+//! covering them. The k-mer spectrum is counted in a first pass over
+//! the reads, and k-mers observed fewer than a threshold are "weak".
+//! Bases only covered by weak k-mers are flagged, and, if the base has
+//! a low quality score, substituted when a single nucleotide makes
+//! every k-mer covering the base solid.
+//!
+//! K-mers are counted by their canonical form, the lesser of the k-mer
+//! and its reverse complement, so both strands share the same count.
+//! K-mers containing ambiguous nucleotides are not counted.
+
+use std::collections::HashMap;
+
+use bio::dna::reverse_complement;
+use util::Result;
+use super::record::Record;
+
+/// Unambiguous nucleotides, in the order substitutions are tried.
+const NUCLEOTIDES: &[u8] = b"ACGT";
+
+// SPECTRUM
+
+/// Counts of canonical k-mers from a collection of reads.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KmerSpectrum {
+    k: usize,
+    counts: HashMap<Vec<u8>, u32>,
+}
+
+impl KmerSpectrum {
+    /// Create new, empty spectrum for k-mers of length `k`.
+    #[inline]
+    pub fn new(k: usize) -> Self {
+        assert!(k > 0, "k-mer length must be positive");
+        KmerSpectrum { k: k, counts: HashMap::new() }
+    }
+
+    /// Count the k-mers from a collection of reads.
+    pub fn from_records<'a, Iter>(k: usize, iter: Iter) -> Self
+        where Iter: IntoIterator<Item = &'a Record>
+    {
+        let mut spectrum = KmerSpectrum::new(k);
+        for record in iter {
+            spectrum.add(record);
+        }
+        spectrum
+    }
+
+    /// Count the k-mers from a stream of reads, stopping at the first error.
+    pub fn from_iter<Iter>(k: usize, iter: Iter) -> Result<Self>
+        where Iter: IntoIterator<Item = Result<Record>>
+    {
+        let mut spectrum = KmerSpectrum::new(k);
+        for record in iter {
+            spectrum.add(&record?);
+        }
+        Ok(spectrum)
+    }
+
+    /// Get the k-mer length.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Get the number of distinct canonical k-mers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Check if the spectrum contains no k-mers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Count the k-mers from a read.
+    pub fn add(&mut self, record: &Record) {
+        if record.sequence.len() < self.k {
+            return;
+        }
+        for kmer in record.sequence.windows(self.k) {
+            if let Some(canonical) = canonical_kmer(kmer) {
+                *self.counts.entry(canonical).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Get the number of occurrences of a k-mer, on either strand.
+    #[inline]
+    pub fn count(&self, kmer: &[u8]) -> u32 {
+        match canonical_kmer(kmer) {
+            Some(canonical) => self.counts.get(&canonical).cloned().unwrap_or(0),
+            None            => 0,
+        }
+    }
+
+    /// Get the histogram of k-mer counts, as (count, distinct k-mers) pairs.
+    ///
+    /// The first minimum of the histogram is typically a good threshold
+    /// to separate erroneous from genomic k-mers.
+    pub fn histogram(&self) -> Vec<(u32, usize)> {
+        let mut histogram: HashMap<u32, usize> = HashMap::new();
+        for &count in self.counts.values() {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        let mut histogram: Vec<(u32, usize)> = histogram.into_iter().collect();
+        histogram.sort();
+        histogram
+    }
+}
+
+/// Get the canonical form of a k-mer, or `None` if it is ambiguous.
+fn canonical_kmer(kmer: &[u8]) -> Option<Vec<u8>> {
+    let forward: Vec<u8> = kmer.iter().map(|c| c.to_ascii_uppercase()).collect();
+    if !forward.iter().all(|c| NUCLEOTIDES.contains(c)) {
+        return None;
+    }
+    let reverse = reverse_complement(&forward);
+    Some(if reverse < forward { reverse } else { forward })
+}
+
+// CORRECTION
+
+/// Action taken on bases only covered by weak k-mers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CorrectionMode {
+    /// Report the weak bases, without modifying the read.
+    Flag,
+    /// Substitute the weak, low-quality bases, when the correction is unique.
+    Correct,
+}
+
+/// Options for k-mer spectrum error correction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CorrectionOptions {
+    /// Minimum count for a k-mer to be solid.
+    pub threshold: u32,
+    /// Maximum Phred quality score of bases which may be corrected.
+    pub max_quality: u8,
+    /// Offset of the ASCII-encoded quality scores.
+    pub quality_offset: u8,
+    /// Action taken on the weak bases.
+    pub mode: CorrectionMode,
+}
+
+impl Default for CorrectionOptions {
+    #[inline]
+    fn default() -> Self {
+        CorrectionOptions {
+            threshold: 3,
+            max_quality: 20,
+            quality_offset: 33,
+            mode: CorrectionMode::Correct,
+        }
+    }
+}
+
+/// Summary of the error correction of a read.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Correction {
+    /// Positions of the bases only covered by weak k-mers.
+    pub weak: Vec<usize>,
+    /// Positions of the substituted bases.
+    pub corrected: Vec<usize>,
+}
+
+impl Correction {
+    /// Check if the read had no weak bases.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.weak.is_empty()
+    }
+
+    /// Get the number of weak bases which were not corrected.
+    #[inline]
+    pub fn uncorrected(&self) -> usize {
+        self.weak.len() - self.corrected.len()
+    }
+}
+
+/// Check if a k-mer is solid in the spectrum.
+#[inline]
+fn is_solid(spectrum: &KmerSpectrum, kmer: &[u8], threshold: u32) -> bool {
+    spectrum.count(kmer) >= threshold
+}
+
+/// Get the start positions of the k-mers covering a base.
+#[inline]
+fn covering_kmers(position: usize, length: usize, k: usize) -> ::std::ops::Range<usize> {
+    let start = (position + 1).saturating_sub(k);
+    let end = (length - k).min(position) + 1;
+    start..end
+}
+
+/// Check if every k-mer covering a base is solid.
+fn is_covered(sequence: &[u8], position: usize, spectrum: &KmerSpectrum, threshold: u32) -> bool {
+    let k = spectrum.k();
+    covering_kmers(position, sequence.len(), k)
+        .all(|i| is_solid(spectrum, &sequence[i..i+k], threshold))
+}
+
+/// Find the positions of the bases only covered by weak k-mers.
+fn weak_positions(sequence: &[u8], spectrum: &KmerSpectrum, threshold: u32) -> Vec<usize> {
+    let k = spectrum.k();
+    if sequence.len() < k {
+        return vec![];
+    }
+    let solid: Vec<bool> = sequence.windows(k)
+        .map(|kmer| is_solid(spectrum, kmer, threshold))
+        .collect();
+    (0..sequence.len())
+        .filter(|&p| !covering_kmers(p, sequence.len(), k).any(|i| solid[i]))
+        .collect()
+}
+
+/// Flag or correct the weak bases of a read, in place.
+///
+/// Weak bases are corrected from the lowest quality score, so each
+/// correction may make the k-mers of neighboring bases solid. Reads
+/// shorter than the k-mer length are left unchanged.
+pub fn correct_record(record: &mut Record, spectrum: &KmerSpectrum, options: &CorrectionOptions)
+    -> Correction
+{
+    let weak = weak_positions(&record.sequence, spectrum, options.threshold);
+    let mut correction = Correction { weak: weak, corrected: vec![] };
+    if options.mode == CorrectionMode::Flag {
+        return correction;
+    }
+
+    // Only correct low-quality bases, starting from the least reliable.
+    let phred = |p: usize| record.quality.get(p)
+        .map(|q| q.saturating_sub(options.quality_offset))
+        .unwrap_or(0);
+    let mut candidates: Vec<usize> = correction.weak.iter()
+        .cloned()
+        .filter(|&p| phred(p) <= options.max_quality)
+        .collect();
+    candidates.sort_by_key(|&p| (phred(p), p));
+
+    for position in candidates {
+        let original = record.sequence[position];
+        let mut substitutions = NUCLEOTIDES.iter().filter(|&&n| n != original.to_ascii_uppercase()).filter(|&&n| {
+            record.sequence[position] = n;
+            let covered = is_covered(&record.sequence, position, spectrum, options.threshold);
+            record.sequence[position] = original;
+            covered
+        });
+        match (substitutions.next(), substitutions.next()) {
+            (Some(&n), None) => {
+                record.sequence[position] = n;
+                correction.corrected.push(position);
+            },
+            _                => log_trace!("Ambiguous or missing correction at {}", position),
+        }
+    }
+    correction.corrected.sort();
+
+    correction
+}
+
+/// Iterator to flag or correct the errors of a stream of reads.
+///
+/// Wraps a record iterator, and yields each read with the summary of
+/// its correction. The spectrum must be counted beforehand, for example,
+/// from a previous pass over the same reads.
+pub struct CorrectIter<'a, Iter: Iterator<Item = Result<Record>>> {
+    iter: Iter,
+    spectrum: &'a KmerSpectrum,
+    options: CorrectionOptions,
+}
+
+impl<'a, Iter: Iterator<Item = Result<Record>>> CorrectIter<'a, Iter> {
+    /// Create new CorrectIter from a record iterator and k-mer spectrum.
+    #[inline]
+    pub fn new(iter: Iter, spectrum: &'a KmerSpectrum, options: CorrectionOptions) -> Self {
+        CorrectIter { iter: iter, spectrum: spectrum, options: options }
+    }
+}
+
+impl<'a, Iter: Iterator<Item = Result<Record>>> Iterator for CorrectIter<'a, Iter> {
+    type Item = Result<(Record, Correction)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.map(|mut record| {
+            let correction = correct_record(&mut record, self.spectrum, &self.options);
+            (record, correction)
+        }))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(sequence: &[u8], quality: u8) -> Record {
+        Record {
+            seq_id: String::from("read"),
+            description: String::new(),
+            length: sequence.len() as u32,
+            sequence: sequence.to_vec(),
+            quality: vec![quality; sequence.len()],
+        }
+    }
+
+    #[test]
+    fn spectrum_test() {
+        let reads = vec![read(b"ACGTTGCA", b'I'), read(b"TGCAACGN", b'I')];
+        let spectrum = KmerSpectrum::from_records(4, reads.iter());
+        assert_eq!(spectrum.k(), 4);
+        // "ACGT" is its own reverse complement, and "TGCA" is too.
+        assert_eq!(spectrum.count(b"ACGT"), 1);
+        assert_eq!(spectrum.count(b"TGCA"), 2);
+        // "AACG" is the reverse complement of "CGTT".
+        assert_eq!(spectrum.count(b"CGTT"), 2);
+        assert_eq!(spectrum.count(b"AACG"), 2);
+        assert_eq!(spectrum.count(b"ACGN"), 0);
+        assert_eq!(spectrum.count(b"GGGG"), 0);
+        assert_eq!(spectrum.histogram().iter().map(|&(_, n)| n).sum::<usize>(), spectrum.len());
+    }
+
+    #[test]
+    fn correct_record_test() {
+        let genome = b"GATTACAGGCATCCGATAGTCTTAGGCAACTG";
+        let reads: Vec<Record> = (0..5).map(|_| read(genome, b'I')).collect();
+        let spectrum = KmerSpectrum::from_records(7, reads.iter());
+        let mut options = CorrectionOptions::default();
+
+        // Clean reads are unchanged.
+        let mut record = read(genome, b'I');
+        assert!(correct_record(&mut record, &spectrum, &options).is_clean());
+
+        // A low-quality error is substituted.
+        let mut erroneous = genome.to_vec();
+        erroneous[13] = b'T';
+        let mut record = read(&erroneous, b'I');
+        record.quality[13] = b'#';
+        let correction = correct_record(&mut record, &spectrum, &options);
+        assert_eq!(correction.weak, vec![13]);
+        assert_eq!(correction.corrected, vec![13]);
+        assert_eq!(record.sequence, genome.to_vec());
+
+        // High-quality errors are only flagged.
+        let mut record = read(&erroneous, b'I');
+        let correction = correct_record(&mut record, &spectrum, &options);
+        assert_eq!(correction.weak, vec![13]);
+        assert_eq!(correction.uncorrected(), 1);
+        assert_eq!(record.sequence, erroneous);
+
+        // Flag mode never modifies the read.
+        options.mode = CorrectionMode::Flag;
+        let mut record = read(&erroneous, b'#');
+        assert_eq!(correct_record(&mut record, &spectrum, &options).weak, vec![13]);
+        assert_eq!(record.sequence, erroneous);
+    }
+
+    #[test]
+    fn correct_iter_test() {
+        let genome = b"GATTACAGGCATCCGATAGTCTTAGGCAACTG";
+        let reads: Vec<Record> = (0..5).map(|_| read(genome, b'I')).collect();
+        let spectrum = KmerSpectrum::from_iter(7, reads.clone().into_iter().map(Ok)).unwrap();
+        let mut erroneous = read(genome, b'#');
+        erroneous.sequence[3] = b'C';
+
+        let iter = vec![Ok(reads[0].clone()), Ok(erroneous)].into_iter();
+        let v: Vec<_> = CorrectIter::new(iter, &spectrum, CorrectionOptions::default())
+            .map(|r| r.unwrap())
+            .collect();
+        assert!(v[0].1.is_clean());
+        assert_eq!(v[1].1.corrected, vec![3]);
+        assert_eq!(v[1].0.sequence, genome.to_vec());
+    }
+}
@@ -0,0 +1,237 @@
+//! Duplicate read detection for sequencing QC.
+//!
+//! Reads are keyed by a hash of the full sequence, or of the first
+//! bases of the sequence, which also detects duplicates with different
+//! lengths after quality or adapter trimming. Only the hashes are kept,
+//! so memory grows with the number of distinct reads, not their length.
+
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use util::Result;
+use super::record::Record;
+
+/// Marker appended to the description of duplicate reads.
+pub const DUPLICATE_MARKER: &str = "duplicate";
+
+/// Part of the read identifying duplicates.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DuplicateKey {
+    /// Reads with identical sequences are duplicates.
+    Sequence,
+    /// Reads with identical first `N` bases are duplicates.
+    Prefix(usize),
+}
+
+/// Action taken on duplicate reads.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DuplicateAction {
+    /// Append the duplicate marker to the description of duplicates.
+    Mark,
+    /// Remove duplicates from the stream.
+    Drop,
+    /// Only count duplicates, without modifying the stream.
+    Count,
+}
+
+/// Options for duplicate read detection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DuplicateOptions {
+    /// Part of the read identifying duplicates.
+    pub key: DuplicateKey,
+    /// Action taken on duplicate reads.
+    pub action: DuplicateAction,
+}
+
+impl Default for DuplicateOptions {
+    #[inline]
+    fn default() -> Self {
+        DuplicateOptions {
+            key: DuplicateKey::Sequence,
+            action: DuplicateAction::Count,
+        }
+    }
+}
+
+// FILTER
+
+/// Detects duplicate reads, and counts the reads and duplicates seen.
+#[derive(Clone, Debug)]
+pub struct DuplicateFilter {
+    options: DuplicateOptions,
+    seen: HashSet<u64>,
+    total: usize,
+    duplicates: usize,
+}
+
+impl DuplicateFilter {
+    /// Create new duplicate filter from options.
+    #[inline]
+    pub fn new(options: DuplicateOptions) -> Self {
+        DuplicateFilter {
+            options: options,
+            seen: HashSet::new(),
+            total: 0,
+            duplicates: 0,
+        }
+    }
+
+    /// Get the number of reads seen.
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Get the number of duplicate reads seen.
+    #[inline]
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Get the fraction of duplicate reads, or 0 if no reads were seen.
+    #[inline]
+    pub fn duplicate_rate(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            n => self.duplicates as f64 / n as f64,
+        }
+    }
+
+    /// Hash the key of a read, ignoring case.
+    fn hash(&self, record: &Record) -> u64 {
+        let sequence = match self.options.key {
+            DuplicateKey::Sequence  => &record.sequence[..],
+            DuplicateKey::Prefix(n) => &record.sequence[..n.min(record.sequence.len())],
+        };
+        let mut hasher = DefaultHasher::new();
+        for c in sequence {
+            c.to_ascii_uppercase().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Check if a read duplicates a previously seen read.
+    pub fn is_duplicate(&mut self, record: &Record) -> bool {
+        let hash = self.hash(record);
+        let duplicate = !self.seen.insert(hash);
+        self.total += 1;
+        if duplicate {
+            self.duplicates += 1;
+        }
+        duplicate
+    }
+
+    /// Process a read according to the duplicate action.
+    ///
+    /// Returns `None` if the read is dropped.
+    pub fn process(&mut self, mut record: Record) -> Option<Record> {
+        if !self.is_duplicate(&record) {
+            return Some(record);
+        }
+        match self.options.action {
+            DuplicateAction::Mark  => {
+                if !record.description.is_empty() {
+                    record.description.push(' ');
+                }
+                record.description.push_str(DUPLICATE_MARKER);
+                Some(record)
+            },
+            DuplicateAction::Drop  => None,
+            DuplicateAction::Count => Some(record),
+        }
+    }
+}
+
+/// Iterator to detect duplicates in a stream of reads.
+///
+/// Wraps a record iterator, and owns the duplicate filter, so the
+/// duplicate counts are available once the stream is consumed.
+pub struct DuplicateIter<Iter: Iterator<Item = Result<Record>>> {
+    iter: Iter,
+    filter: DuplicateFilter,
+}
+
+impl<Iter: Iterator<Item = Result<Record>>> DuplicateIter<Iter> {
+    /// Create new DuplicateIter from a record iterator and options.
+    #[inline]
+    pub fn new(iter: Iter, options: DuplicateOptions) -> Self {
+        DuplicateIter { iter: iter, filter: DuplicateFilter::new(options) }
+    }
+
+    /// Get the duplicate filter, with the duplicate counts so far.
+    #[inline]
+    pub fn filter(&self) -> &DuplicateFilter {
+        &self.filter
+    }
+
+    /// Consume the iterator and get the duplicate filter.
+    #[inline]
+    pub fn into_filter(self) -> DuplicateFilter {
+        self.filter
+    }
+}
+
+impl<Iter: Iterator<Item = Result<Record>>> Iterator for DuplicateIter<Iter> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next()? {
+                Err(e)     => return Some(Err(e)),
+                Ok(record) => if let Some(record) = self.filter.process(record) {
+                    return Some(Ok(record));
+                },
+            }
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::{srr390728_2, srr390728_3};
+
+    fn reads() -> Vec<Result<Record>> {
+        let mut lowercase = srr390728_2();
+        lowercase.sequence = lowercase.sequence.to_ascii_lowercase();
+        let mut truncated = srr390728_3();
+        truncated.sequence.truncate(40);
+        truncated.quality.truncate(40);
+        truncated.length = 40;
+        vec![Ok(srr390728_2()), Ok(srr390728_3()), Ok(lowercase), Ok(truncated)]
+    }
+
+    #[test]
+    fn count_test() {
+        let mut iter = DuplicateIter::new(reads().into_iter(), DuplicateOptions::default());
+        assert_eq!(iter.by_ref().count(), 4);
+        let filter = iter.into_filter();
+        assert_eq!(filter.total(), 4);
+        assert_eq!(filter.duplicates(), 1);
+        assert_eq!(filter.duplicate_rate(), 0.25);
+    }
+
+    #[test]
+    fn drop_test() {
+        let options = DuplicateOptions { key: DuplicateKey::Prefix(20), action: DuplicateAction::Drop };
+        let v: Vec<Record> = DuplicateIter::new(reads().into_iter(), options)
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(v, vec![srr390728_2(), srr390728_3()]);
+    }
+
+    #[test]
+    fn mark_test() {
+        let options = DuplicateOptions { key: DuplicateKey::Sequence, action: DuplicateAction::Mark };
+        let v: Vec<Record> = DuplicateIter::new(reads().into_iter(), options)
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(v[0].description, "2");
+        assert_eq!(v[2].description, "2 duplicate");
+        assert_eq!(v[3].description, "3");
+    }
+}
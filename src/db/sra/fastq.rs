@@ -2,6 +2,8 @@
 
 use std::io::prelude::*;
 
+use bio::Alphabet;
+use bio::alphabet::check_nucleotide_sequence;
 use traits::*;
 use util::*;
 use super::re::*;
@@ -24,10 +26,16 @@ impl<T: BufRead> FastqIter<T> {
     /// Create new FastqIter from a buffered reader.
     #[inline]
     pub fn new(reader: T) -> Self {
+        FastqIter::with_capacity(reader, DEFAULT_BUFFER_CAPACITY, DEFAULT_LINE_CAPACITY)
+    }
+
+    /// Create new FastqIter from a buffered reader and buffer capacities.
+    #[inline]
+    pub fn with_capacity(reader: T, buffer_capacity: usize, line_capacity: usize) -> Self {
         FastqIter {
             reader: reader,
-            buf: Vec::with_capacity(8000),
-            line: Bytes::with_capacity(8000)
+            buf: Vec::with_capacity(buffer_capacity),
+            line: Bytes::with_capacity(line_capacity)
         }
     }
 }
@@ -36,7 +44,7 @@ impl<T: BufRead> Iterator for FastqIter<T> {
     type Item = Result<Bytes>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        bytes_next_skip_whitespace(b"@", &mut self.reader, &mut self.buf, &mut self.line)
+        bytes_next_skip_whitespace(b"@", None, &mut self.reader, &mut self.buf, &mut self.line)
     }
 }
 
@@ -98,7 +106,7 @@ pub fn record_to_fastq<T: Write>(writer: &mut T, record: &Record)
 
 #[inline(always)]
 fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
-    -> Result<TextWriterState<T>>
+    -> Result<TextWriterState<'_, T>>
 {
     Ok(TextWriterState::new(writer, delimiter))
 }
@@ -195,7 +203,8 @@ pub fn record_from_fastq<T: BufRead>(reader: &mut T)
     let header = none_to_error!(lines.next(), InvalidInput)?;
 
     // process the header and match it to the FASTA record
-    let captures = none_to_error!(FastqHeaderRegex::extract().captures(&header), InvalidInput);
+    let header = strip_bom(header.trim_end());
+    let captures = none_to_error!(FastqHeaderRegex::extract().captures(header), InvalidInput);
 
     // create the record from the header metadata
     let mut record = Record {
@@ -208,7 +217,7 @@ pub fn record_from_fastq<T: BufRead>(reader: &mut T)
 
     // get the FASTQ sequence.
     let sequence = none_to_error!(lines.next(), InvalidInput)?;
-    record.sequence = sequence.into_bytes();
+    record.sequence = sequence.trim_end().as_bytes().to_vec();
     record.length = record.sequence.len() as u32;
 
     // get the header quality line
@@ -217,7 +226,7 @@ pub fn record_from_fastq<T: BufRead>(reader: &mut T)
 
     // get the FASTQ quality scores
     let quality = none_to_error!(lines.next(), InvalidInput)?;
-    record.quality = quality.into_bytes();
+    record.quality = quality.trim_end().as_bytes().to_vec();
     bool_to_error!(record.quality.len() as u32 == record.length, InvalidRecord);
 
     Ok(record)
@@ -229,15 +238,25 @@ pub fn record_from_fastq<T: BufRead>(reader: &mut T)
 ///
 /// Wraps `FastqIter` and converts the text to records.
 pub struct FastqRecordIter<T: BufRead> {
-    iter: FastqIter<T>
+    iter: FastqIter<T>,
+    encoding: Encoding,
+    alphabet: Option<Alphabet>,
 }
 
 impl<T: BufRead> FastqRecordIter<T> {
     /// Create new FastqRecordIter from a buffered reader.
     #[inline]
     pub fn new(reader: T) -> Self {
+        FastqRecordIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new FastqRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
         FastqRecordIter {
-            iter: FastqIter::new(reader)
+            iter: FastqIter::with_capacity(reader, options.buffer_capacity, options.line_capacity),
+            encoding: options.encoding,
+            alphabet: options.alphabet,
         }
     }
 }
@@ -252,7 +271,13 @@ impl<T: BufRead> Iterator for FastqRecordIter<T> {
 
         };
 
-        Some(Record::from_fastq_bytes(&bytes))
+        let bytes = self.encoding.decode_bytes(bytes);
+        let result = Record::from_fastq_bytes(&bytes).and_then(|mut r| {
+            check_nucleotide_sequence(self.alphabet, &mut r.sequence)?;
+            Ok(r)
+        });
+        log_parsed!("FASTQ", &result);
+        Some(result)
     }
 }
 
@@ -262,6 +287,12 @@ pub fn iterator_from_fastq<T: BufRead>(reader: T) -> FastqRecordIter<T> {
     FastqRecordIter::new(reader)
 }
 
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fastq_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastqRecordIter<T> {
+    FastqRecordIter::with_options(reader, options)
+}
+
 // READER -- STRICT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -275,6 +306,12 @@ pub fn iterator_from_fastq_strict<T: BufRead>(reader: T) -> FastqRecordStrictIte
     FastqRecordStrictIter::new(iterator_from_fastq(reader))
 }
 
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fastq_strict_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastqRecordStrictIter<T> {
+    FastqRecordStrictIter::new(iterator_from_fastq_with_options(reader, options))
+}
+
 // READER -- LENIENT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -288,6 +325,12 @@ pub fn iterator_from_fastq_lenient<T: BufRead>(reader: T) -> FastqRecordLenientI
     FastqRecordLenientIter::new(iterator_from_fastq(reader))
 }
 
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fastq_lenient_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastqRecordLenientIter<T> {
+    FastqRecordLenientIter::new(iterator_from_fastq_with_options(reader, options))
+}
+
 // TRAITS
 
 impl Fastq for Record {
@@ -304,6 +347,20 @@ impl Fastq for Record {
     fn from_fastq<T: BufRead>(reader: &mut T) -> Result<Self> {
         record_from_fastq(reader)
     }
+
+    #[inline(always)]
+    fn from_fastq_iter<'a, T: 'a + BufRead>(reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_fastq(reader))
+    }
+
+    #[inline(always)]
+    fn to_fastq_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+    {
+        reference_iterator_to_fastq(writer, iter)
+    }
 }
 
 impl Fastq for RecordList {
@@ -369,6 +426,62 @@ mod tests {
         assert_eq!(r.unwrap(), Vec::<Bytes>::new());
     }
 
+    #[test]
+    fn windows_fastq_test() {
+        use test::windows_text;
+
+        let s = b"@tag desc\nCATTAG\n+tag desc\n;;;;;;\n@tag1 desc1\nTAGCAT\n+tag1 desc1\n;;;;;;\n";
+        let expected: Result<RecordList> = iterator_from_fastq(Cursor::new(s.to_vec())).collect();
+        let actual: Result<RecordList> = iterator_from_fastq(Cursor::new(windows_text(s))).collect();
+        let expected = expected.unwrap();
+        assert_eq!(expected.len(), 2);
+        assert_eq!(actual.unwrap(), expected);
+
+        let record = record_from_fastq(&mut Cursor::new(windows_text(b"@tag desc\nCATTAG\n+tag desc\n;;;;;;\n"))).unwrap();
+        assert_eq!(record, expected[0]);
+    }
+
+    #[test]
+    fn fastq_encoding_test() {
+        let s = b"@tag d\xe9sc\nCATTAG\n+tag desc\n;;;;;;\n".to_vec();
+        let r: Result<RecordList> = iterator_from_fastq(Cursor::new(s.clone())).collect();
+        assert!(r.is_err());
+
+        let options = ReaderOptions::with_encoding(Encoding::Latin1);
+        let r: Result<RecordList> = iterator_from_fastq_with_options(Cursor::new(s), &options).collect();
+        let r = r.unwrap();
+        assert_eq!(r[0].description, "d\u{e9}sc");
+        assert_eq!(r[0].sequence, b"CATTAG".to_vec());
+    }
+
+    #[test]
+    fn fastq_alphabet_test() {
+        let s = b"@tag desc\ncatNag\n+tag desc\n;;;;;;\n".to_vec();
+        let options = ReaderOptions::with_alphabet(Alphabet::Extended);
+        let r: Result<RecordList> = iterator_from_fastq_with_options(Cursor::new(s.clone()), &options).collect();
+        assert!(r.is_err());
+
+        let options = ReaderOptions::with_alphabet(Alphabet::CaseInsensitive);
+        let r: Result<RecordList> = iterator_from_fastq_with_options(Cursor::new(s), &options).collect();
+        assert_eq!(r.unwrap()[0].sequence, b"CATNAG".to_vec());
+    }
+
+    #[test]
+    fn fastq_trait_iter_test() {
+        let s = b"@tag desc\nCATTAG\n+tag desc\n;;;;;;\n@tag1 desc1\nTAGCAT\n+tag1 desc1\n;;;;;;".to_vec();
+        let v: RecordList = Record::from_fastq_iter(Cursor::new(s))
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[1].sequence, b"TAGCAT".to_vec());
+
+        let mut w = Cursor::new(vec![]);
+        Record::to_fastq_iter(&mut w, v.iter()).unwrap();
+        let w = w.into_inner();
+        assert!(w.starts_with(b"@tag desc\nCATTAG\n+tag desc"));
+        assert_eq!(w.iter().filter(|&&c| c == b'@').count(), 2);
+    }
+
     // TODO(ahuszagh)
     //  Implement the unittests.
 }
@@ -10,6 +10,10 @@
 //! typically < 16 KB required for internal buffers, and < 1 KB for each
 //! individual item.
 
+// Re-export the strict and lenient iterator wrappers, to name the
+// strict and lenient reader types.
+pub use util::iterator::{LenientIter, StrictIter};
+
 #[cfg(feature = "fastq")]
 pub use super::fastq::*;
 
@@ -4,14 +4,17 @@
 pub mod low_level;
 
 // Expose the client API in a public submodule.
-// Requires the CSV feature to function.
-#[cfg(all(feature = "csv", feature = "http"))]
+// Requires the FASTQ and gzip features to function.
+#[cfg(all(feature = "fastq", feature = "gzip", feature = "http"))]
 pub mod client;
 
 pub(crate) mod complete;
+pub(crate) mod correct;
+pub(crate) mod duplicates;
 pub(crate) mod re;
 pub(crate) mod record;
 pub(crate) mod record_list;
+pub(crate) mod trim;
 pub(crate) mod valid;
 
 #[cfg(test)]
@@ -23,3 +26,8 @@ pub(crate) mod fastq;
 // Re-export the models into the parent module.
 pub use self::record::Record;
 pub use self::record_list::RecordList;
+
+// Re-export the read processing into the parent module.
+pub use self::correct::{correct_record, CorrectIter, Correction, CorrectionMode, CorrectionOptions, KmerSpectrum};
+pub use self::duplicates::{DuplicateAction, DuplicateFilter, DuplicateIter, DuplicateKey, DuplicateOptions, DUPLICATE_MARKER};
+pub use self::trim::{iupac_matches, Adapter, AdapterEnd, AdapterTrimmer, TrimIter};
@@ -1,5 +1,7 @@
 //! Model for SRA (Sequence Read Archive) read definitions.
 
+use traits::Renamable;
+
 /// Model for a single record from a sequence read.
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
 pub struct Record {
@@ -29,11 +31,25 @@ impl Record {
     }
 }
 
+impl Renamable for Record {
+    /// Get the sequence identifier for the read.
+    #[inline]
+    fn identifier(&self) -> &str {
+        &self.seq_id
+    }
+
+    #[inline]
+    fn set_identifier(&mut self, identifier: String) {
+        self.seq_id = identifier;
+    }
+}
+
 // TESTS
 // -----
 
 #[cfg(test)]
 mod tests {
+    use bio::Alphabet;
     use traits::*;
     //use super::*;
     use super::super::test::*;
@@ -87,6 +103,21 @@ mod tests {
         g2.sequence = g1.sequence.clone();
     }
 
+    #[test]
+    fn alphabet_record_test() {
+        let mut g = srr390728_2();
+        assert!(g.is_valid_with_alphabet(Alphabet::Strict));
+
+        g.sequence[0] = b'N';
+        assert!(!g.is_valid());
+        assert!(!g.is_valid_with_alphabet(Alphabet::Strict));
+        assert!(g.is_valid_with_alphabet(Alphabet::Extended));
+
+        g.sequence[0] = b'r';
+        assert!(!g.is_valid_with_alphabet(Alphabet::Ambiguous));
+        assert!(g.is_valid_with_alphabet(Alphabet::CaseInsensitive));
+    }
+
     // TODO(ahuszagh)
     //      implement...
 }
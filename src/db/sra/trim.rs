@@ -0,0 +1,340 @@
+//! Adapter and primer trimming with IUPAC-aware matching.
+//!
+//! Adapters are aligned to the reads without gaps, allowing a number
+//! of mismatches. Adapters may contain IUPAC ambiguity codes, which
+//! match any of the nucleotides they represent, for example, `N` in
+//! degenerate primers. Ambiguous bases in the reads only match
+//! adapter bases including every nucleotide they represent.
+//!
+//! 3' adapters are trimmed with every base after the match, and may
+//! partially overlap the end of the read. 5' adapters are trimmed with
+//! every base before the match, and may partially overlap the start of
+//! the read.
+
+use util::Result;
+use super::record::Record;
+
+// MATCHING
+
+/// Get the bitmask of the nucleotides represented by an IUPAC code.
+///
+/// Returns 0 for unknown codes.
+fn iupac_mask(code: u8) -> u8 {
+    const A: u8 = 1;
+    const C: u8 = 2;
+    const G: u8 = 4;
+    const T: u8 = 8;
+    match code.to_ascii_uppercase() {
+        b'A'        => A,
+        b'C'        => C,
+        b'G'        => G,
+        b'T' | b'U' => T,
+        b'R'        => A | G,
+        b'Y'        => C | T,
+        b'S'        => C | G,
+        b'W'        => A | T,
+        b'K'        => G | T,
+        b'M'        => A | C,
+        b'B'        => C | G | T,
+        b'D'        => A | G | T,
+        b'H'        => A | C | T,
+        b'V'        => A | C | G,
+        b'N'        => A | C | G | T,
+        _           => 0,
+    }
+}
+
+/// Check if a read base matches an adapter base.
+#[inline]
+pub fn iupac_matches(adapter: u8, base: u8) -> bool {
+    let base = iupac_mask(base);
+    base != 0 && base & !iupac_mask(adapter) == 0
+}
+
+/// Count the mismatches between an adapter and read segment of the same length.
+#[inline]
+fn mismatches(adapter: &[u8], segment: &[u8]) -> usize {
+    adapter.iter().zip(segment).filter(|&(&a, &b)| !iupac_matches(a, b)).count()
+}
+
+// ADAPTER
+
+/// Position of an adapter in the reads.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdapterEnd {
+    /// Adapter ligated to the 5' end, trimmed with the preceding bases.
+    FivePrime,
+    /// Adapter ligated to the 3' end, trimmed with the following bases.
+    ThreePrime,
+}
+
+/// Adapter or primer sequence to trim from the reads.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Adapter {
+    /// Name of the adapter, to report the trim counts.
+    pub name: String,
+    /// Adapter sequence, which may contain IUPAC ambiguity codes.
+    pub sequence: Vec<u8>,
+    /// End of the reads containing the adapter.
+    pub end: AdapterEnd,
+    /// Maximum number of mismatches for a full-length match.
+    ///
+    /// Partial matches at the ends of the read allow proportionally
+    /// fewer mismatches, rounded down.
+    pub max_mismatches: usize,
+    /// Minimum overlap of a partial match at the ends of the read.
+    pub min_overlap: usize,
+}
+
+impl Adapter {
+    /// Create new 3' adapter without allowed mismatches.
+    #[inline]
+    pub fn new(name: &str, sequence: &[u8]) -> Self {
+        Adapter {
+            name: String::from(name),
+            sequence: sequence.to_vec(),
+            end: AdapterEnd::ThreePrime,
+            max_mismatches: 0,
+            min_overlap: 3,
+        }
+    }
+
+    /// Get the allowed mismatches for a match of `length` bases.
+    #[inline]
+    fn allowed_mismatches(&self, length: usize) -> usize {
+        self.max_mismatches * length / self.sequence.len()
+    }
+
+    /// Find the start of the leftmost 3' adapter match in a sequence.
+    fn find_three_prime(&self, sequence: &[u8]) -> Option<usize> {
+        let length = self.sequence.len();
+        let min_overlap = self.min_overlap.max(1).min(length);
+        let last = sequence.len().checked_sub(min_overlap)?;
+        (0..last+1).find(|&start| {
+            let overlap = length.min(sequence.len() - start);
+            let count = mismatches(&self.sequence[..overlap], &sequence[start..start+overlap]);
+            count <= self.allowed_mismatches(overlap)
+        })
+    }
+
+    /// Find the end of the rightmost 5' adapter match in a sequence.
+    fn find_five_prime(&self, sequence: &[u8]) -> Option<usize> {
+        let length = self.sequence.len();
+        let min_overlap = self.min_overlap.max(1).min(length);
+        if sequence.len() < min_overlap {
+            return None;
+        }
+        (min_overlap..sequence.len()+1).rev().find(|&end| {
+            let overlap = length.min(end);
+            let count = mismatches(&self.sequence[length-overlap..], &sequence[end-overlap..end]);
+            count <= self.allowed_mismatches(overlap)
+        })
+    }
+
+    /// Find the range of the read to keep after trimming the adapter.
+    fn find(&self, sequence: &[u8]) -> Option<(usize, usize)> {
+        if self.sequence.is_empty() {
+            return None;
+        }
+        match self.end {
+            AdapterEnd::ThreePrime => self.find_three_prime(sequence).map(|s| (0, s)),
+            AdapterEnd::FivePrime  => self.find_five_prime(sequence).map(|e| (e, sequence.len())),
+        }
+    }
+}
+
+// TRIMMER
+
+/// Trims adapters from reads, and counts the reads trimmed by each adapter.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AdapterTrimmer {
+    adapters: Vec<Adapter>,
+    counts: Vec<usize>,
+}
+
+impl AdapterTrimmer {
+    /// Create new trimmer from a list of adapters.
+    #[inline]
+    pub fn new(adapters: Vec<Adapter>) -> Self {
+        let counts = vec![0; adapters.len()];
+        AdapterTrimmer { adapters: adapters, counts: counts }
+    }
+
+    /// Get the adapters.
+    #[inline]
+    pub fn adapters(&self) -> &[Adapter] {
+        &self.adapters
+    }
+
+    /// Get the number of reads trimmed by each adapter, by adapter name.
+    pub fn counts(&self) -> Vec<(&str, usize)> {
+        self.adapters.iter()
+            .zip(self.counts.iter())
+            .map(|(a, &c)| (a.name.as_str(), c))
+            .collect()
+    }
+
+    /// Trim the adapters from a read, in place.
+    ///
+    /// Each adapter is trimmed at most once, in order. Returns the
+    /// indexes of the adapters trimmed from the read.
+    pub fn trim(&mut self, record: &mut Record) -> Vec<usize> {
+        let mut trimmed = vec![];
+        for (index, adapter) in self.adapters.iter().enumerate() {
+            if let Some((start, end)) = adapter.find(&record.sequence) {
+                trim_record(record, start, end);
+                self.counts[index] += 1;
+                trimmed.push(index);
+            }
+        }
+        trimmed
+    }
+}
+
+/// Keep the bases of a read in `[start, end)`.
+fn trim_record(record: &mut Record, start: usize, end: usize) {
+    record.sequence.truncate(end);
+    record.sequence.drain(..start);
+    if record.quality.len() >= end {
+        record.quality.truncate(end);
+        record.quality.drain(..start);
+    }
+    record.length = record.sequence.len() as u32;
+}
+
+/// Iterator to trim adapters from a stream of reads.
+///
+/// Wraps a record iterator, and owns the trimmer, so the trim counts
+/// are available once the stream is consumed.
+pub struct TrimIter<Iter: Iterator<Item = Result<Record>>> {
+    iter: Iter,
+    trimmer: AdapterTrimmer,
+}
+
+impl<Iter: Iterator<Item = Result<Record>>> TrimIter<Iter> {
+    /// Create new TrimIter from a record iterator and adapter trimmer.
+    #[inline]
+    pub fn new(iter: Iter, trimmer: AdapterTrimmer) -> Self {
+        TrimIter { iter: iter, trimmer: trimmer }
+    }
+
+    /// Get the adapter trimmer, with the trim counts so far.
+    #[inline]
+    pub fn trimmer(&self) -> &AdapterTrimmer {
+        &self.trimmer
+    }
+
+    /// Consume the iterator and get the adapter trimmer.
+    #[inline]
+    pub fn into_trimmer(self) -> AdapterTrimmer {
+        self.trimmer
+    }
+}
+
+impl<Iter: Iterator<Item = Result<Record>>> Iterator for TrimIter<Iter> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let trimmer = &mut self.trimmer;
+        Some(self.iter.next()?.map(|mut record| {
+            trimmer.trim(&mut record);
+            record
+        }))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(sequence: &[u8]) -> Record {
+        Record {
+            seq_id: String::from("read"),
+            description: String::new(),
+            length: sequence.len() as u32,
+            sequence: sequence.to_vec(),
+            quality: (0..sequence.len()).map(|i| b'!' + i as u8).collect(),
+        }
+    }
+
+    #[test]
+    fn iupac_matches_test() {
+        assert!(iupac_matches(b'A', b'A'));
+        assert!(iupac_matches(b'N', b'g'));
+        assert!(iupac_matches(b'R', b'G'));
+        assert!(!iupac_matches(b'R', b'C'));
+        assert!(iupac_matches(b'T', b'U'));
+        assert!(iupac_matches(b'N', b'N'));
+        assert!(!iupac_matches(b'A', b'N'));
+        assert!(!iupac_matches(b'N', b'-'));
+    }
+
+    #[test]
+    fn three_prime_test() {
+        let mut trimmer = AdapterTrimmer::new(vec![Adapter::new("illumina", b"AGATCGGAAGAGC")]);
+
+        // Full-length match.
+        let mut record = read(b"CATTAGCATAGATCGGAAGAGCTTTT");
+        assert_eq!(trimmer.trim(&mut record), vec![0]);
+        assert_eq!(record.sequence, b"CATTAGCAT".to_vec());
+        assert_eq!(record.quality, read(b"CATTAGCAT").quality);
+        assert_eq!(record.length, 9);
+
+        // Partial match at the 3' end, and overlap below the minimum.
+        let mut record = read(b"CATTAGCATAGAT");
+        trimmer.trim(&mut record);
+        assert_eq!(record.sequence, b"CATTAGCAT".to_vec());
+        let mut record = read(b"CATTAGCATAG");
+        assert!(trimmer.trim(&mut record).is_empty());
+        assert_eq!(trimmer.counts(), vec![("illumina", 2)]);
+    }
+
+    #[test]
+    fn mismatches_test() {
+        let mut adapter = Adapter::new("primer", b"GTNTACRGG");
+        let mut trimmer = AdapterTrimmer::new(vec![adapter.clone()]);
+        let mut record = read(b"AAAAGTCTACAGGAAAA");
+        trimmer.trim(&mut record);
+        assert_eq!(record.sequence, b"AAAA".to_vec());
+
+        // A single mismatch requires an allowed mismatch.
+        let mut record = read(b"AAAAGTCTACTGGAAAA");
+        assert!(trimmer.trim(&mut record).is_empty());
+        adapter.max_mismatches = 1;
+        let mut trimmer = AdapterTrimmer::new(vec![adapter]);
+        trimmer.trim(&mut record);
+        assert_eq!(record.sequence, b"AAAA".to_vec());
+    }
+
+    #[test]
+    fn five_prime_test() {
+        let mut adapter = Adapter::new("primer", b"ACGTACGT");
+        adapter.end = AdapterEnd::FivePrime;
+        let mut trimmer = AdapterTrimmer::new(vec![adapter, Adapter::new("polya", b"AAAAAAAA")]);
+
+        let mut record = read(b"TTACGTACGTCATTAGAAAAAAAA");
+        assert_eq!(trimmer.trim(&mut record), vec![0, 1]);
+        assert_eq!(record.sequence, b"CATTAG".to_vec());
+        assert_eq!(record.quality, b"+,-./0".to_vec());
+
+        // Partial match at the 5' end.
+        let mut record = read(b"ACGTCATTAG");
+        trimmer.trim(&mut record);
+        assert_eq!(record.sequence, b"CATTAG".to_vec());
+        assert_eq!(trimmer.counts(), vec![("primer", 2), ("polya", 1)]);
+    }
+
+    #[test]
+    fn trim_iter_test() {
+        let trimmer = AdapterTrimmer::new(vec![Adapter::new("illumina", b"AGATCGGAAGAGC")]);
+        let iter = vec![Ok(read(b"CATAGATCGGAAGAGC")), Ok(read(b"CATTAG"))].into_iter();
+        let mut iter = TrimIter::new(iter, trimmer);
+        let v: Vec<Record> = iter.by_ref().map(|r| r.unwrap()).collect();
+        assert_eq!(v[0].sequence, b"CAT".to_vec());
+        assert_eq!(v[1].sequence, b"CATTAG".to_vec());
+        assert_eq!(iter.into_trimmer().counts(), vec![("illumina", 1)]);
+    }
+}
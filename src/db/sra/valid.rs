@@ -1,19 +1,32 @@
 //! Valid trait implementation for SRA models.
 
+use bio::Alphabet;
 use traits::Valid;
 use super::re::*;
 use super::record::Record;
 use super::record_list::RecordList;
 
+/// Determine if the fields other than the sequence residues are valid.
+fn has_valid_fields(record: &Record) -> bool {
+    !record.seq_id.is_empty() &&
+        !record.sequence.is_empty() &&
+        record.length as usize == record.sequence.len() &&
+        record.length as usize == record.quality.len() &&
+        SequenceQualityRegex::validate().is_match(&record.quality)
+}
+
 impl Valid for Record {
+    #[inline]
     fn is_valid(&self) -> bool {
-        (
-            !self.seq_id.is_empty() &&
-            self.length as usize == self.sequence.len() &&
-            self.length as usize == self.quality.len() &&
-            NucleotideRegex::validate().is_match(&self.sequence) &&
-            SequenceQualityRegex::validate().is_match(&self.quality)
-        )
+        has_valid_fields(self) && NucleotideRegex::validate().is_match(&self.sequence)
+    }
+}
+
+impl Record {
+    /// Determine if the record is valid, with residues from the alphabet.
+    #[inline]
+    pub fn is_valid_with_alphabet(&self, alphabet: Alphabet) -> bool {
+        has_valid_fields(self) && alphabet.is_nucleotide_sequence(&self.sequence)
     }
 }
 
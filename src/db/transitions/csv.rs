@@ -0,0 +1,537 @@
+//! Helper utilities for transition list CSV loading and saving.
+//!
+//! Uses the Skyline transition list column names. The first line
+//! contains the column headers, and columns may be in any order.
+//! Unrecognized columns are ignored on import.
+
+use csv;
+use std::io::prelude::*;
+
+use traits::*;
+use util::*;
+use super::record::Record;
+use super::record_list::RecordList;
+
+// COLUMNS
+
+const PROTEIN: &'static str = "Protein Name";
+const PEPTIDE: &'static str = "Peptide Modified Sequence";
+const PRECURSOR_MZ: &'static str = "Precursor Mz";
+const PRECURSOR_Z: &'static str = "Precursor Charge";
+const PRODUCT_MZ: &'static str = "Product Mz";
+const PRODUCT_Z: &'static str = "Product Charge";
+const FRAGMENT_ION: &'static str = "Fragment Ion";
+const RT: &'static str = "Explicit Retention Time";
+const RT_WINDOW: &'static str = "Explicit Retention Time Window";
+const COLLISION_ENERGY: &'static str = "Collision Energy";
+
+/// Column headers, in export order.
+const CSV_HEADER: [&'static str; 10] = [
+    PROTEIN,
+    PEPTIDE,
+    PRECURSOR_MZ,
+    PRECURSOR_Z,
+    PRODUCT_MZ,
+    PRODUCT_Z,
+    FRAGMENT_ION,
+    RT,
+    RT_WINDOW,
+    COLLISION_ENERGY,
+];
+
+/// Column within a transition list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Column {
+    Protein,
+    Peptide,
+    PrecursorMz,
+    PrecursorZ,
+    ProductMz,
+    ProductZ,
+    FragmentIon,
+    Rt,
+    RtWindow,
+    CollisionEnergy,
+    /// Unrecognized column, which is ignored.
+    Other,
+}
+
+/// Parse the column from the column header, ignoring case.
+fn column_from_header(header: &str) -> Column {
+    let header = strip_bom(header).trim();
+    let columns = [
+        (PROTEIN, Column::Protein),
+        (PEPTIDE, Column::Peptide),
+        (PRECURSOR_MZ, Column::PrecursorMz),
+        (PRECURSOR_Z, Column::PrecursorZ),
+        (PRODUCT_MZ, Column::ProductMz),
+        (PRODUCT_Z, Column::ProductZ),
+        (FRAGMENT_ION, Column::FragmentIon),
+        (RT, Column::Rt),
+        (RT_WINDOW, Column::RtWindow),
+        (COLLISION_ENERGY, Column::CollisionEnergy),
+    ];
+    columns.iter()
+        .find(|c| c.0.eq_ignore_ascii_case(header))
+        .map_or(Column::Other, |c| c.1)
+}
+
+/// Parse the columns from the header row.
+fn columns_from_header(row: &csv::StringRecord) -> Result<Vec<Column>> {
+    let columns: Vec<Column> = row.iter().map(column_from_header).collect();
+    for column in [Column::Peptide, Column::PrecursorMz, Column::ProductMz].iter() {
+        bool_to_error!(columns.contains(column), InvalidInput);
+    }
+    Ok(columns)
+}
+
+// SIZE
+
+/// Size of the CSV header, including the line ending.
+const CSV_HEADER_SIZE: usize = 180;
+
+/// Estimate the size of a CSV row from a record.
+#[inline]
+fn estimate_record_size(record: &Record) -> usize {
+    // Numeric columns, delimiters, and line ending.
+    const CSV_VOCABULARY_SIZE: usize = 75;
+    CSV_VOCABULARY_SIZE +
+        record.protein.len() +
+        record.peptide.len() +
+        record.fragment_ion.len()
+}
+
+/// Estimate the size of a CSV export from list.
+#[inline]
+fn estimate_list_size(list: &RecordList) -> usize {
+    list.iter().fold(0, |sum, x| sum + estimate_record_size(x))
+}
+
+// WRITER
+
+/// Create CSV writer.
+#[inline(always)]
+fn new_writer<T: Write>(writer: T, delimiter: u8)
+    -> csv::Writer<T>
+{
+    csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(csv::QuoteStyle::Necessary)
+        .flexible(false)
+        .from_writer(writer)
+}
+
+/// Serialize an optional number, or an empty cell if `None`.
+#[inline]
+fn optional_to_bytes(value: Option<f64>) -> Result<Bytes> {
+    match value {
+        Some(v) => to_bytes(&v),
+        None    => Ok(vec![]),
+    }
+}
+
+/// Export a record as a CSV row.
+fn to_csv<T: Write>(writer: &mut csv::Writer<T>, record: &Record)
+    -> Result<()>
+{
+    let precursor_mz = to_bytes(&record.precursor_mz)?;
+    let precursor_z = nonzero_to_bytes(&record.precursor_z)?;
+    let product_mz = to_bytes(&record.product_mz)?;
+    let product_z = nonzero_to_bytes(&record.product_z)?;
+    let rt = optional_to_bytes(record.rt)?;
+    let rt_window = optional_to_bytes(record.rt_window)?;
+    let collision_energy = optional_to_bytes(record.collision_energy)?;
+
+    writer.write_record(&[
+        record.protein.as_bytes(),
+        record.peptide.as_bytes(),
+        precursor_mz.as_slice(),
+        precursor_z.as_slice(),
+        product_mz.as_slice(),
+        product_z.as_slice(),
+        record.fragment_ion.as_bytes(),
+        rt.as_slice(),
+        rt_window.as_slice(),
+        collision_energy.as_slice(),
+    ])?;
+
+    Ok(())
+}
+
+/// Export record to CSV.
+pub fn record_to_csv<T: Write>(writer: &mut T, record: &Record, delimiter: u8)
+    -> Result<()>
+{
+    let mut writer = new_writer(writer, delimiter);
+    writer.write_record(&CSV_HEADER)?;
+    to_csv(&mut writer, record)?;
+    Ok(())
+}
+
+// WRITER -- DEFAULT
+
+#[inline(always)]
+fn init_cb<T: Write>(writer: T, delimiter: u8)
+    -> Result<csv::Writer<T>>
+{
+    let mut writer = new_writer(writer, delimiter);
+    writer.write_record(&CSV_HEADER)?;
+    Ok(writer)
+}
+
+#[inline(always)]
+fn export_cb<'a, T: Write>(writer: &mut csv::Writer<T>, record: &'a Record)
+    -> Result<()>
+{
+    to_csv(writer, record)
+}
+
+#[inline(always)]
+fn dest_cb<T: Write>(_: &mut csv::Writer<T>)
+    -> Result<()>
+{
+    Ok(())
+}
+
+/// Default export from a non-owning iterator to CSV.
+#[inline(always)]
+pub fn reference_iterator_to_csv<'a, Iter, T>(writer: &mut T, iter: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to CSV.
+#[inline(always)]
+pub fn value_iterator_to_csv<Iter, T>(writer: &mut T, iter: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- STRICT
+
+/// Strict export from a non-owning iterator to CSV.
+#[inline(always)]
+pub fn reference_iterator_to_csv_strict<'a, Iter, T>(writer: &mut T, iter: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_strict(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to CSV.
+#[inline(always)]
+pub fn value_iterator_to_csv_strict<Iter, T>(writer: &mut T, iter: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_strict(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
+}
+
+// WRITER -- LENIENT
+
+/// Lenient export from a non-owning iterator to CSV.
+#[inline(always)]
+pub fn reference_iterator_to_csv_lenient<'a, Iter, T>(writer: &mut T, iter: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_lenient(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to CSV.
+#[inline(always)]
+pub fn value_iterator_to_csv_lenient<Iter, T>(writer: &mut T, iter: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_lenient(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
+}
+
+// READER
+
+/// Create CSV reader.
+#[inline(always)]
+fn new_reader<T: Read>(reader: T, delimiter: u8)
+    -> csv::Reader<T>
+{
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .comment(Some(CSV_COMMENT[0]))
+        .flexible(false)
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+}
+
+/// Parse an optional number, or `None` from an empty cell.
+#[inline]
+fn optional_from_string(value: &str) -> Result<Option<f64>> {
+    match value.is_empty() {
+        true  => Ok(None),
+        false => Ok(Some(from_string(value)?)),
+    }
+}
+
+/// Import record from a CSV row, with the columns from the header.
+fn record_from_row(columns: &[Column], row: &csv::StringRecord)
+    -> Result<Record>
+{
+    let mut record = Record::new();
+    for (column, value) in columns.iter().zip(row.iter()) {
+        match *column {
+            Column::Protein         => record.protein = String::from(value),
+            Column::Peptide         => record.peptide = String::from(value),
+            Column::PrecursorMz     => record.precursor_mz = from_string(value)?,
+            Column::PrecursorZ      => record.precursor_z = nonzero_from_string(value)?,
+            Column::ProductMz       => record.product_mz = from_string(value)?,
+            Column::ProductZ        => record.product_z = nonzero_from_string(value)?,
+            Column::FragmentIon     => record.fragment_ion = String::from(value),
+            Column::Rt              => record.rt = optional_from_string(value)?,
+            Column::RtWindow        => record.rt_window = optional_from_string(value)?,
+            Column::CollisionEnergy => record.collision_energy = optional_from_string(value)?,
+            Column::Other           => (),
+        }
+    }
+
+    Ok(record)
+}
+
+/// Import record from CSV.
+#[inline]
+pub fn record_from_csv<T: Read>(reader: &mut T, delimiter: u8)
+    -> Result<Record>
+{
+    Ok(none_to_error!(iterator_from_csv(reader, delimiter).next(), InvalidInput)?)
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a document.
+pub struct CsvRecordIter<T: Read> {
+    iter: csv::StringRecordsIntoIter<T>,
+    columns: Option<Vec<Column>>,
+}
+
+impl<T: Read> CsvRecordIter<T> {
+    /// Create new CsvRecordIter from a reader.
+    #[inline]
+    pub fn new(reader: T, delimiter: u8) -> Self {
+        CsvRecordIter {
+            iter: new_reader(reader, delimiter).into_records(),
+            columns: None,
+        }
+    }
+}
+
+impl<T: Read> Iterator for CsvRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = match self.iter.next()? {
+                Err(e)  => return Some(Err(From::from(e))),
+                Ok(row) => row,
+            };
+
+            let result = match self.columns {
+                Some(ref columns) => record_from_row(columns, &row),
+                None              => {
+                    match columns_from_header(&row) {
+                        Err(e) => Err(e),
+                        Ok(v)  => {
+                            self.columns = Some(v);
+                            continue;
+                        },
+                    }
+                },
+            };
+            log_parsed!("Transition CSV", &result);
+            return Some(result);
+        }
+    }
+}
+
+/// Create default record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_csv<T: Read>(reader: T, delimiter: u8) -> CsvRecordIter<T> {
+    CsvRecordIter::new(reader, delimiter)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document.
+pub type CsvRecordStrictIter<T> = StrictIter<Record, CsvRecordIter<T>>;
+
+/// Create strict record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_csv_strict<T: Read>(reader: T, delimiter: u8) -> CsvRecordStrictIter<T> {
+    CsvRecordStrictIter::new(iterator_from_csv(reader, delimiter))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document.
+pub type CsvRecordLenientIter<T> = LenientIter<Record, CsvRecordIter<T>>;
+
+/// Create lenient record iterator from reader.
+#[inline(always)]
+pub fn iterator_from_csv_lenient<T: Read>(reader: T, delimiter: u8) -> CsvRecordLenientIter<T> {
+    CsvRecordLenientIter::new(iterator_from_csv(reader, delimiter))
+}
+
+// TRAITS
+
+impl Csv for Record {
+    #[inline(always)]
+    fn estimate_csv_size(&self) -> usize {
+        CSV_HEADER_SIZE + estimate_record_size(self)
+    }
+
+    #[inline(always)]
+    fn to_csv<T: Write>(&self, writer: &mut T, delimiter: u8) -> Result<()> {
+        record_to_csv(writer, self, delimiter)
+    }
+
+    #[inline(always)]
+    fn from_csv<T: Read>(reader: &mut T, delimiter: u8) -> Result<Self> {
+        record_from_csv(reader, delimiter)
+    }
+
+    #[inline(always)]
+    fn from_csv_iter<'a, T: 'a + Read>(reader: T, delimiter: u8)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_csv(reader, delimiter))
+    }
+
+    #[inline(always)]
+    fn to_csv_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter, delimiter: u8)
+        -> Result<()>
+    {
+        reference_iterator_to_csv(writer, iter, delimiter)
+    }
+}
+
+impl Csv for RecordList {
+    #[inline(always)]
+    fn estimate_csv_size(&self) -> usize {
+        CSV_HEADER_SIZE + estimate_list_size(self)
+    }
+
+    #[inline(always)]
+    fn to_csv<T: Write>(&self, writer: &mut T, delimiter: u8) -> Result<()> {
+        reference_iterator_to_csv(writer, self.iter(), delimiter)
+    }
+
+    #[inline(always)]
+    fn from_csv<T: Read>(reader: &mut T, delimiter: u8) -> Result<RecordList> {
+        iterator_from_csv(reader, delimiter).collect()
+    }
+}
+
+impl CsvCollection for RecordList {
+    #[inline(always)]
+    fn to_csv_strict<T: Write>(&self, writer: &mut T, delimiter: u8) -> Result<()> {
+        reference_iterator_to_csv_strict(writer, self.iter(), delimiter)
+    }
+
+    #[inline(always)]
+    fn to_csv_lenient<T: Write>(&self, writer: &mut T, delimiter: u8) -> Result<()> {
+        reference_iterator_to_csv_lenient(writer, self.iter(), delimiter)
+    }
+
+    #[inline(always)]
+    fn from_csv_strict<T: Read>(reader: &mut T, delimiter: u8) -> Result<RecordList> {
+        iterator_from_csv_strict(reader, delimiter).collect()
+    }
+
+    #[inline(always)]
+    fn from_csv_lenient<T: Read>(reader: &mut T, delimiter: u8) -> Result<RecordList> {
+        Ok(iterator_from_csv_lenient(reader, delimiter).filter_map(Result::ok).collect())
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test::windows_text;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn column_test() {
+        assert_eq!(column_from_header("\u{feff}Protein Name"), Column::Protein);
+        assert_eq!(column_from_header(" precursor mz "), Column::PrecursorMz);
+        assert_eq!(column_from_header("Library Intensity"), Column::Other);
+        assert_eq!(CSV_HEADER.join(",").len() + 1, CSV_HEADER_SIZE);
+    }
+
+    #[test]
+    fn iterator_to_csv_test() {
+        let v = vec![lgevnk_y5(), lgevnk_y4()];
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_csv(&mut w, v.iter(), b',').unwrap();
+        assert_eq!(w.into_inner(), LGEVNK_CSV.to_vec());
+
+        let u = vec![lgevnk_y5(), Record::new(), lgevnk_y4()];
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_csv_strict(&mut w, u.iter(), b',').is_err());
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_csv_lenient(&mut w, u.iter(), b',').unwrap();
+        assert_eq!(w.into_inner(), LGEVNK_CSV.to_vec());
+    }
+
+    #[test]
+    fn iterator_from_csv_test() {
+        let v: Result<RecordList> = iterator_from_csv(Cursor::new(LGEVNK_CSV), b',').collect();
+        assert_eq!(v.unwrap(), vec![lgevnk_y5(), lgevnk_y4()]);
+
+        let text = windows_text(LGEVNK_CSV);
+        let v: Result<RecordList> = iterator_from_csv_strict(Cursor::new(text), b',').collect();
+        assert_eq!(v.unwrap(), vec![lgevnk_y5(), lgevnk_y4()]);
+
+        // Minimal transition list, with reordered columns.
+        let text = b"Product Mz,Peptide Modified Sequence,Precursor Mz,Library Intensity\n546.2882,LGEVNK,330.1898,100\n";
+        let v: RecordList = iterator_from_csv(Cursor::new(&text[..]), b',').collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v[0].peptide, "LGEVNK");
+        assert_eq!(v[0].product_mz, 546.2882);
+        assert_eq!(v[0].rt, None);
+
+        // Missing required columns.
+        let text = b"Peptide Modified Sequence,Precursor Mz\nLGEVNK,330.1898\n";
+        assert!(iterator_from_csv(Cursor::new(&text[..]), b',').next().unwrap().is_err());
+
+        // Invalid numbers.
+        let text = [LGEVNK_CSV, b"P1,LGEVNK,a,2,546.2882,1,y5,,,\n"].concat();
+        let v: Result<RecordList> = iterator_from_csv(Cursor::new(&text), b',').collect();
+        assert!(v.is_err());
+
+        // Invalid records.
+        let text = [LGEVNK_CSV, b"P1,LGEVNK,330.1898,2,0,1,y5,,,\n"].concat();
+        let v: Result<RecordList> = iterator_from_csv_strict(Cursor::new(&text), b',').collect();
+        assert!(v.is_err());
+        let v: RecordList = iterator_from_csv_lenient(Cursor::new(&text), b',').collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v.len(), 2);
+    }
+
+    #[test]
+    fn csv_trait_test() {
+        let list = RecordList::from_csv_bytes(LGEVNK_CSV, b',').unwrap();
+        assert_eq!(list.to_csv_bytes(b',').unwrap(), LGEVNK_CSV.to_vec());
+        assert_eq!(Record::from_csv_bytes(LGEVNK_CSV, b',').unwrap(), lgevnk_y5());
+    }
+}
@@ -0,0 +1,153 @@
+//! Generate transition lists from protein sequences.
+
+use bio::digest::{digest, DigestOptions};
+use bio::fragment::{fragment_ions, Fragment, IonType};
+use bio::mass::{neutral_mass_to_mz, Adduct};
+use bio::proteins::ProteinMassTable;
+use super::record::Record;
+use super::record_list::RecordList;
+
+/// Options to generate transitions from protein sequences.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionOptions {
+    /// Options to digest the proteins into peptides.
+    pub digest: DigestOptions,
+    /// Charges of the precursor ions for each peptide.
+    pub precursor_charges: Vec<i8>,
+    /// Types of fragment ions to use as product ions.
+    pub ions: Vec<IonType>,
+    /// Maximum charge of the product ions.
+    pub max_product_z: i8,
+    /// Minimum number of residues in the product ions.
+    pub min_ordinal: usize,
+    /// Minimum mass to charge ratio of the product ions.
+    pub min_product_mz: f64,
+    /// Maximum mass to charge ratio of the product ions.
+    pub max_product_mz: f64,
+    /// Maximum number of transitions for each precursor ion.
+    pub max_transitions: usize,
+    /// Linear collision energy equation, as `(slope, intercept)`.
+    ///
+    /// The collision energy is `slope * precursor_mz + intercept`.
+    /// If `None`, the collision energy is not set.
+    pub collision_energy: Option<(f64, f64)>,
+}
+
+impl Default for TransitionOptions {
+    /// Doubly- and triply-charged precursors, with 5 singly-charged y-ions.
+    #[inline]
+    fn default() -> Self {
+        TransitionOptions {
+            digest: DigestOptions::default(),
+            precursor_charges: vec![2, 3],
+            ions: vec![IonType::Y],
+            max_product_z: 1,
+            min_ordinal: 3,
+            min_product_mz: 50.0,
+            max_product_mz: 1500.0,
+            max_transitions: 5,
+            collision_energy: Some((0.034, 3.314)),
+        }
+    }
+}
+
+/// Select the product ions for a precursor.
+///
+/// Prefers the longest fragments, which are the most specific to the
+/// peptide, and typically the most intense for y-ions.
+fn select_fragments(mut fragments: Vec<Fragment>, options: &TransitionOptions) -> Vec<Fragment> {
+    fragments.retain(|f| {
+        f.ordinal >= options.min_ordinal &&
+        f.mz >= options.min_product_mz &&
+        f.mz <= options.max_product_mz
+    });
+    fragments.sort_by(|x, y| {
+        y.ordinal.cmp(&x.ordinal)
+            .then(x.ion.cmp(&y.ion))
+            .then(x.charge.cmp(&y.charge))
+    });
+    fragments.truncate(options.max_transitions);
+    fragments
+}
+
+/// Generate the transitions for the peptides of a protein.
+///
+/// Transitions are ordered by peptide, then by precursor charge, then
+/// by the selected product ions. The masses are calculated with the
+/// mass table, for example, from `ProteinMassTable::monoisotopic()`.
+pub fn generate_transitions(protein: &str, sequence: &[u8], options: &TransitionOptions, table: &ProteinMassTable)
+    -> RecordList
+{
+    let mut list = RecordList::new();
+    for peptide in digest(sequence, &options.digest, table) {
+        let name = String::from_utf8_lossy(&peptide.sequence).into_owned();
+        let fragments = fragment_ions(&peptide.sequence, &[], &options.ions, options.max_product_z, table);
+        for &precursor_z in options.precursor_charges.iter() {
+            let precursor_mz = match neutral_mass_to_mz(peptide.mass, precursor_z, Adduct::Proton) {
+                Some(mz) => mz,
+                None     => continue,
+            };
+            let collision_energy = options.collision_energy.map(|(slope, intercept)| {
+                slope * precursor_mz + intercept
+            });
+            for fragment in select_fragments(fragments.clone(), options) {
+                list.push(Record {
+                    protein: String::from(protein),
+                    peptide: name.clone(),
+                    precursor_mz: precursor_mz,
+                    precursor_z: precursor_z,
+                    product_mz: fragment.mz,
+                    product_z: fragment.charge,
+                    fragment_ion: format!("{}{}", fragment.ion.letter(), fragment.ordinal),
+                    rt: None,
+                    rt_window: None,
+                    collision_energy: collision_energy,
+                });
+            }
+        }
+    }
+
+    list
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_transitions_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let mut options = TransitionOptions::default();
+        options.digest.min_length = 5;
+        options.digest.missed_cleavages = 0;
+        options.digest.min_mass = 0.0;
+        options.precursor_charges = vec![2];
+        options.max_transitions = 3;
+
+        let list = generate_transitions("P1", b"SAMPLERLGEVNKA", &options, &table);
+        let peptides: Vec<&str> = list.iter().map(|r| r.peptide.as_str()).collect();
+        assert_eq!(peptides, vec!["SAMPLER", "SAMPLER", "SAMPLER", "LGEVNK", "LGEVNK", "LGEVNK"]);
+
+        let r = &list[3];
+        assert_eq!(r.protein, "P1");
+        assert_eq!(r.precursor_z, 2);
+        assert_approx_eq!(r.precursor_mz, 330.1898, 1e-3);
+        assert_eq!(r.fragment_ion, "y5");
+        assert_eq!(r.product_z, 1);
+        assert_approx_eq!(r.product_mz, 546.2882, 1e-3);
+        assert_approx_eq!(r.collision_energy.unwrap(), 0.034 * r.precursor_mz + 3.314);
+        let ions: Vec<&str> = list[3..].iter().map(|r| r.fragment_ion.as_str()).collect();
+        assert_eq!(ions, vec!["y5", "y4", "y3"]);
+
+        // fragments shorter than the minimum ordinal are excluded
+        options.min_ordinal = 5;
+        let list = generate_transitions("P1", b"LGEVNK", &options, &table);
+        assert_eq!(list.len(), 1);
+        options.collision_energy = None;
+        let list = generate_transitions("P1", b"LGEVNK", &options, &table);
+        assert_eq!(list[0].collision_energy, None);
+    }
+}
@@ -0,0 +1,25 @@
+//! Targeted proteomics transition lists.
+//!
+//! Reads and writes transition lists for selected and parallel reaction
+//! monitoring (SRM/PRM) methods, in the Skyline-style CSV format, with
+//! one precursor-product ion pair per line. Transition lists may be
+//! generated from protein sequences, by digestion and fragmentation.
+
+// Expose the low-level API in a public submodule.
+pub mod low_level;
+
+pub(crate) mod generate;
+pub(crate) mod record;
+pub(crate) mod record_list;
+pub(crate) mod valid;
+
+#[cfg(feature = "csv")]
+pub(crate) mod csv;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+// Re-export the models into the parent module.
+pub use self::generate::{generate_transitions, TransitionOptions};
+pub use self::record::Record;
+pub use self::record_list::{RecordList, RecordListExt};
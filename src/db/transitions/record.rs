@@ -0,0 +1,93 @@
+//! Model for targeted proteomics transition definitions.
+
+/// Model for a single transition (one precursor-product ion pair).
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Record {
+    /// Name of the protein containing the peptide.
+    pub protein: String,
+    /// Peptide sequence, including any modification annotations.
+    pub peptide: String,
+    /// Mass to charge ratio of the precursor ion.
+    pub precursor_mz: f64,
+    /// Charge of the precursor ion.
+    pub precursor_z: i8,
+    /// Mass to charge ratio of the product ion.
+    pub product_mz: f64,
+    /// Charge of the product ion.
+    pub product_z: i8,
+    /// Fragment ion annotation, for example, "y7".
+    pub fragment_ion: String,
+    /// Expected retention time of the peptide, in minutes, if scheduled.
+    pub rt: Option<f64>,
+    /// Width of the retention time window, in minutes, if scheduled.
+    pub rt_window: Option<f64>,
+    /// Collision energy for the transition, if known.
+    pub collision_energy: Option<f64>,
+}
+
+impl Record {
+    /// Create new, empty transition.
+    #[inline]
+    pub fn new() -> Self {
+        Record {
+            protein: String::new(),
+            peptide: String::new(),
+            precursor_mz: 0.0,
+            precursor_z: 0,
+            product_mz: 0.0,
+            product_z: 0,
+            fragment_ion: String::new(),
+            rt: None,
+            rt_window: None,
+            collision_energy: None,
+        }
+    }
+
+    /// Get the scheduled retention time range, in minutes.
+    ///
+    /// Returns `None` for unscheduled transitions.
+    #[inline]
+    pub fn rt_range(&self) -> Option<(f64, f64)> {
+        match (self.rt, self.rt_window) {
+            (Some(rt), Some(window)) => Some((rt - window / 2.0, rt + window / 2.0)),
+            _                        => None,
+        }
+    }
+
+    /// Get the unmodified peptide sequence.
+    ///
+    /// Removes bracketed modification annotations, such as "[+57.0]",
+    /// from the peptide.
+    pub fn unmodified_peptide(&self) -> String {
+        let mut depth = 0usize;
+        self.peptide.chars().filter(|&c| {
+            match c {
+                '[' | '(' => depth += 1,
+                ']' | ')' => depth = depth.saturating_sub(1),
+                _         => return depth == 0,
+            }
+            false
+        }).collect()
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn properties_record_test() {
+        let r = lgevnk_y5();
+        assert_eq!(r.rt_range(), Some((18.5, 23.5)));
+        assert_eq!(Record::new().rt_range(), None);
+        assert_eq!(r.unmodified_peptide(), "LGEVNK");
+
+        let mut r = Record::new();
+        r.peptide = String::from("C[+57.0]PEPTIDEM(Oxidation)K");
+        assert_eq!(r.unmodified_peptide(), "CPEPTIDEMK");
+    }
+}
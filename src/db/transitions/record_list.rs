@@ -0,0 +1,96 @@
+//! Model for targeted proteomics transition collections.
+
+use bio::rt::RetentionPredictor;
+use super::record::Record;
+
+/// Transition collection type.
+pub type RecordList = Vec<Record>;
+
+/// Scheduling and lookups for transition collections.
+pub trait RecordListExt {
+    /// Schedule the transitions from the predicted peptide retention times.
+    ///
+    /// The predictor must predict retention times in minutes, for
+    /// example, a `CalibratedPredictor` fit to observed peptides.
+    fn schedule<P: RetentionPredictor>(&mut self, predictor: &P, rt_window: f64);
+
+    /// Get the transitions scheduled at a retention time, in minutes.
+    ///
+    /// Unscheduled transitions are always included.
+    fn scheduled_at(&self, rt: f64) -> Vec<&Record>;
+
+    /// Get the maximum number of concurrently scheduled transitions.
+    ///
+    /// Limits the dwell time for each transition in an SRM method.
+    fn max_concurrent(&self) -> usize;
+}
+
+impl RecordListExt for RecordList {
+    fn schedule<P: RetentionPredictor>(&mut self, predictor: &P, rt_window: f64) {
+        for record in self.iter_mut() {
+            let peptide = record.unmodified_peptide();
+            record.rt = Some(predictor.predict(peptide.as_bytes()));
+            record.rt_window = Some(rt_window);
+        }
+    }
+
+    fn scheduled_at(&self, rt: f64) -> Vec<&Record> {
+        self.iter()
+            .filter(|r| r.rt_range().map_or(true, |(start, end)| rt >= start && rt <= end))
+            .collect()
+    }
+
+    fn max_concurrent(&self) -> usize {
+        let unscheduled = self.iter().filter(|r| r.rt_range().is_none()).count();
+
+        // Sweep the window boundaries, starting windows before ending
+        // windows at the same time, since the ranges are inclusive.
+        let mut events: Vec<(f64, i32)> = vec![];
+        for (start, end) in self.iter().filter_map(|r| r.rt_range()) {
+            events.push((start, 1));
+            events.push((end, -1));
+        }
+        events.sort_by(|x, y| x.0.total_cmp(&y.0).then(y.1.cmp(&x.1)));
+
+        let mut current = 0;
+        let mut maximum = 0;
+        for (_, delta) in events {
+            current += delta;
+            maximum = maximum.max(current);
+        }
+        unscheduled + maximum as usize
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use bio::rt::{CalibratedPredictor, HydrophobicityModel};
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn schedule_list_test() {
+        let mut v = vec![lgevnk_y5(), lgevnk_y4(), Record::new()];
+        assert_eq!(v.scheduled_at(10.0).len(), 1);
+        assert_eq!(v.scheduled_at(20.0).len(), 3);
+        assert_eq!(v.max_concurrent(), 3);
+
+        let predictor = CalibratedPredictor::new(HydrophobicityModel::new(), 0.0, 30.0);
+        v.schedule(&predictor, 2.0);
+        assert_eq!(v[0].rt_range(), Some((29.0, 31.0)));
+        assert_eq!(v.scheduled_at(20.0).len(), 0);
+        assert_eq!(v.scheduled_at(31.0).len(), 3);
+
+        v[2].rt = Some(32.0);
+        assert_eq!(v.max_concurrent(), 3);
+        v[2].rt = Some(34.0);
+        assert_eq!(v.max_concurrent(), 2);
+
+        // NaN retention times sort last, rather than panicking.
+        v[2].rt = Some(f64::NAN);
+        assert_eq!(v.max_concurrent(), 2);
+    }
+}
@@ -0,0 +1,42 @@
+//! Shared helper utilities for transition list unit testing.
+
+use super::record::Record;
+
+// RECORDS
+
+/// Create a transition for the y5 ion of the doubly-charged LGEVNK.
+pub fn lgevnk_y5() -> Record {
+    Record {
+        protein: String::from("P1"),
+        peptide: String::from("LGEVNK"),
+        precursor_mz: 330.1898,
+        precursor_z: 2,
+        product_mz: 546.2882,
+        product_z: 1,
+        fragment_ion: String::from("y5"),
+        rt: Some(21.0),
+        rt_window: Some(5.0),
+        collision_energy: Some(14.5),
+    }
+}
+
+/// Create a transition for the y4 ion of the doubly-charged LGEVNK.
+pub fn lgevnk_y4() -> Record {
+    Record {
+        protein: String::from("P1"),
+        peptide: String::from("LGEVNK"),
+        precursor_mz: 330.1898,
+        precursor_z: 2,
+        product_mz: 489.2667,
+        product_z: 1,
+        fragment_ion: String::from("y4"),
+        rt: Some(21.0),
+        rt_window: Some(5.0),
+        collision_energy: Some(14.5),
+    }
+}
+
+// CSV
+
+#[cfg(feature = "csv")]
+pub const LGEVNK_CSV: &'static [u8] = b"Protein Name,Peptide Modified Sequence,Precursor Mz,Precursor Charge,Product Mz,Product Charge,Fragment Ion,Explicit Retention Time,Explicit Retention Time Window,Collision Energy\nP1,LGEVNK,330.1898,2,546.2882,1,y5,21.0,5.0,14.5\nP1,LGEVNK,330.1898,2,489.2667,1,y4,21.0,5.0,14.5\n";
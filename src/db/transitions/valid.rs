@@ -0,0 +1,23 @@
+//! Valid trait implementation for targeted proteomics transition models.
+
+use traits::Valid;
+use super::record::Record;
+use super::record_list::RecordList;
+
+impl Valid for Record {
+    fn is_valid(&self) -> bool {
+        !self.peptide.is_empty() &&
+            self.precursor_mz > 0.0 &&
+            self.product_mz > 0.0 &&
+            self.rt_window.map_or(true, |w| w >= 0.0) &&
+            self.collision_energy.map_or(true, |ce| ce >= 0.0)
+    }
+}
+
+impl Valid for RecordList {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.iter().all(|ref x| x.is_valid())
+    }
+}
+
@@ -0,0 +1,78 @@
+//! Models for UniProt keyword and sequence feature annotations.
+
+/// Controlled-vocabulary keyword annotating a UniProt record.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Keyword {
+    /// Keyword identifier, for example, `"KW-0007"`.
+    pub id: String,
+    /// Keyword name, for example, `"Acetylation"`.
+    pub name: String,
+}
+
+impl Keyword {
+    /// Create new keyword.
+    #[inline]
+    pub fn new(id: &str, name: &str) -> Self {
+        Keyword {
+            id: String::from(id),
+            name: String::from(name),
+        }
+    }
+}
+
+/// Annotated region or site of the protein sequence.
+///
+/// Positions are 1-based and inclusive, with `0` for unknown positions.
+/// Features at a single position have identical begin and end positions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Feature {
+    /// Feature type, for example, `"chain"` or `"modified residue"`.
+    pub kind: String,
+    /// Description of the feature.
+    pub description: String,
+    /// Feature identifier, for example, `"PRO_0000145486"`, if any.
+    pub id: String,
+    /// First position of the feature.
+    pub begin: u32,
+    /// Last position of the feature.
+    pub end: u32,
+}
+
+impl Feature {
+    /// Create new feature over a range of positions.
+    #[inline]
+    pub fn new(kind: &str, description: &str, begin: u32, end: u32) -> Self {
+        Feature {
+            kind: String::from(kind),
+            description: String::from(description),
+            id: String::new(),
+            begin: begin,
+            end: end,
+        }
+    }
+
+    /// Get the feature length, or `None` if either position is unknown.
+    #[inline]
+    pub fn length(&self) -> Option<u32> {
+        match self.begin == 0 || self.end == 0 || self.end < self.begin {
+            true    => None,
+            false   => Some(self.end - self.begin + 1),
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_length_test() {
+        assert_eq!(Feature::new("chain", "GAPDH", 2, 333).length(), Some(332));
+        assert_eq!(Feature::new("binding site", "NAD", 34, 34).length(), Some(1));
+        assert_eq!(Feature::new("chain", "GAPDH", 0, 333).length(), None);
+        assert_eq!(Feature::new("chain", "GAPDH", 10, 5).length(), None);
+    }
+}
@@ -1,26 +1,156 @@
 //! Client to request resources from the UniProt KB service.
+//!
+//! Records are transferred as TSV, FASTA or XML, and parsed with the
+//! corresponding reader, so the client only requires one of the `csv`,
+//! `fasta` or `xml` features.
 
-use reqwest::{self, Response};
+use std::collections::{HashMap, HashSet};
+#[cfg(any(feature = "fasta", feature = "xml"))]
+use std::io::BufReader;
 use url;
 
+use concurrency::CancellationToken;
+use io::http::{self, ResponseBody};
 use util::Result;
+use super::record::Record;
+use super::record_list::RecordList;
+
+#[cfg(feature = "csv")]
 use super::csv::CsvRecordIter;
 
+#[cfg(feature = "fasta")]
+use super::fasta::FastaRecordIter;
+
+#[cfg(feature = "xml")]
+use super::xml::XmlRecordIter;
+
 /// Host URL for the UniProt KB domain and path.
 const HOST: &str = "https://www.uniprot.org:443/uniprot/";
 
 /// Delimiter for accession number and mnemonic identifiers.
 const DELIMITER: &str = " OR ";
 
-/// Return type to iteratively produce records.
-type RecordIterator = CsvRecordIter<Response>;
+/// Maximum length of the joined identifiers in a single batch request.
+///
+/// Keeps the request URL well below the common 8 KB server limit,
+/// after form-encoding the query.
+const MAX_QUERY_LENGTH: usize = 2000;
+
+/// Columns requested for the TSV transfer format.
+#[cfg(feature = "csv")]
+const TSV_COLUMNS: &str = "version(sequence),existence,mass,length,genes(PREFERRED),id,entry name,protein names,organism,proteome,sequence,organism-id,reviewed";
+
+// FORMAT
+
+/// Transfer format for the UniProt KB service responses.
+///
+/// The formats do not carry the same fields: FASTA does not contain
+/// the proteome or mass, while XML contains the most annotations.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Tab-delimited text, parsed with the CSV reader.
+    #[cfg(feature = "csv")]
+    Tsv,
+    /// FASTA text.
+    #[cfg(feature = "fasta")]
+    Fasta,
+    /// UniProt XML.
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl Format {
+    /// Get the value of the service's `format` parameter.
+    #[inline]
+    fn parameter(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "csv")]
+            Format::Tsv     => "tab",
+            #[cfg(feature = "fasta")]
+            Format::Fasta   => "fasta",
+            #[cfg(feature = "xml")]
+            Format::Xml     => "xml",
+        }
+    }
+}
+
+impl Default for Format {
+    /// Use the first enabled format of TSV, FASTA and XML.
+    #[inline]
+    fn default() -> Self {
+        cfg_if! {
+            if #[cfg(feature = "csv")] {
+                Format::Tsv
+            } else if #[cfg(feature = "fasta")] {
+                Format::Fasta
+            } else {
+                Format::Xml
+            }
+        }
+    }
+}
+
+// ITERATOR
+
+/// Iterator to lazily parse records from a service response.
+pub enum RecordIterator {
+    /// Records transferred as TSV.
+    #[cfg(feature = "csv")]
+    Tsv(CsvRecordIter<ResponseBody>),
+    /// Records transferred as FASTA.
+    #[cfg(feature = "fasta")]
+    Fasta(FastaRecordIter<BufReader<ResponseBody>>),
+    /// Records transferred as XML.
+    #[cfg(feature = "xml")]
+    Xml(XmlRecordIter<BufReader<ResponseBody>>),
+}
+
+impl RecordIterator {
+    /// Create new iterator from the response and transfer format.
+    fn new(response: ResponseBody, format: Format) -> Self {
+        match format {
+            #[cfg(feature = "csv")]
+            Format::Tsv     => RecordIterator::Tsv(CsvRecordIter::new(response, b'\t')),
+            #[cfg(feature = "fasta")]
+            Format::Fasta   => RecordIterator::Fasta(FastaRecordIter::new(BufReader::new(response))),
+            #[cfg(feature = "xml")]
+            Format::Xml     => RecordIterator::Xml(XmlRecordIter::new(BufReader::new(response))),
+        }
+    }
+}
+
+impl Iterator for RecordIterator {
+    type Item = Result<Record>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            #[cfg(feature = "csv")]
+            RecordIterator::Tsv(ref mut iter)   => iter.next(),
+            #[cfg(feature = "fasta")]
+            RecordIterator::Fasta(ref mut iter) => iter.next(),
+            #[cfg(feature = "xml")]
+            RecordIterator::Xml(ref mut iter)   => iter.next(),
+        }
+    }
+}
+
+// REQUESTS
 
 /// Request UniProt records by accession number.
 ///
 /// * `ids` - Single accession number (eg. P46406).
 #[inline(always)]
 pub fn by_id(id: &str) -> Result<RecordIterator> {
-    by_id_impl(id)
+    by_id_with_format(id, Format::default())
+}
+
+/// Request UniProt records by accession number, with a transfer format.
+///
+/// * `ids` - Single accession number (eg. P46406).
+#[inline(always)]
+pub fn by_id_with_format(id: &str, format: Format) -> Result<RecordIterator> {
+    by_id_impl(id, format)
 }
 
 /// Request UniProt records by accession numbers.
@@ -28,7 +158,65 @@ pub fn by_id(id: &str) -> Result<RecordIterator> {
 /// * `ids` - Slice of accession numbers (eg. [P46406]).
 #[inline(always)]
 pub fn by_id_list(ids: &[&str]) -> Result<RecordIterator> {
-    by_id_impl(&ids.join(DELIMITER))
+    by_id_list_with_format(ids, Format::default())
+}
+
+/// Request UniProt records by accession numbers, with a transfer format.
+///
+/// * `ids` - Slice of accession numbers (eg. [P46406]).
+#[inline(always)]
+pub fn by_id_list_with_format(ids: &[&str], format: Format) -> Result<RecordIterator> {
+    by_id_impl(&ids.join(DELIMITER), format)
+}
+
+/// Records fetched from a batch request by accession numbers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchResult {
+    /// Records found, in the order of the requested accession numbers.
+    ///
+    /// Records not matching a requested accession number, for example,
+    /// entries found by secondary accessions, follow in response order.
+    pub records: RecordList,
+    /// Requested accession numbers without a matching record.
+    pub missing: Vec<String>,
+}
+
+/// Request UniProt records by accession numbers, in batches.
+///
+/// Duplicate accession numbers are removed, and large lists are
+/// split into multiple requests with URL-safe lengths.
+///
+/// * `ids` - Slice of accession numbers (eg. [P46406]).
+#[inline(always)]
+pub fn fetch_by_ids(ids: &[&str]) -> Result<BatchResult> {
+    fetch_by_ids_with_format(ids, Format::default())
+}
+
+/// Request UniProt records by accession numbers in batches, with a transfer format.
+///
+/// * `ids` - Slice of accession numbers (eg. [P46406]).
+pub fn fetch_by_ids_with_format(ids: &[&str], format: Format) -> Result<BatchResult> {
+    // A new token is never cancelled.
+    fetch_by_ids_with_cancel(ids, format, &CancellationToken::new())
+}
+
+/// Request UniProt records by accession numbers in batches, stopping early if cancelled.
+///
+/// The token is checked before each request, and while reading
+/// each response.
+///
+/// * `ids` - Slice of accession numbers (eg. [P46406]).
+pub fn fetch_by_ids_with_cancel(ids: &[&str], format: Format, cancel: &CancellationToken) -> Result<BatchResult> {
+    let ids = dedup_ids(ids);
+    let mut records = vec![];
+    for batch in batch_ids(&ids, MAX_QUERY_LENGTH) {
+        let query = format!("id:{}", batch.join(DELIMITER));
+        for record in call_with_cancel(&query, format, cancel)? {
+            records.push(record?);
+        }
+    }
+
+    Ok(order_by_ids(&ids, records))
 }
 
 /// Request UniProt records by mnemonic.
@@ -36,7 +224,15 @@ pub fn by_id_list(ids: &[&str]) -> Result<RecordIterator> {
 /// * `mnemonic` - Single mnemonic (eg. G3P_RABBIT).
 #[inline(always)]
 pub fn by_mnemonic(mnemonic: &str) -> Result<RecordIterator> {
-    by_mnemonic_impl(mnemonic)
+    by_mnemonic_with_format(mnemonic, Format::default())
+}
+
+/// Request UniProt records by mnemonic, with a transfer format.
+///
+/// * `mnemonic` - Single mnemonic (eg. G3P_RABBIT).
+#[inline(always)]
+pub fn by_mnemonic_with_format(mnemonic: &str, format: Format) -> Result<RecordIterator> {
+    by_mnemonic_impl(mnemonic, format)
 }
 
 /// Request UniProt records by mnemonics.
@@ -44,7 +240,15 @@ pub fn by_mnemonic(mnemonic: &str) -> Result<RecordIterator> {
 /// * `mnemonics` - Slice of mnemonics (eg. [G3P_RABBIT]).
 #[inline(always)]
 pub fn by_mnemonic_list(mnemonics: &[&str]) -> Result<RecordIterator> {
-    by_mnemonic_impl(&mnemonics.join(DELIMITER))
+    by_mnemonic_list_with_format(mnemonics, Format::default())
+}
+
+/// Request UniProt records by mnemonics, with a transfer format.
+///
+/// * `mnemonics` - Slice of mnemonics (eg. [G3P_RABBIT]).
+#[inline(always)]
+pub fn by_mnemonic_list_with_format(mnemonics: &[&str], format: Format) -> Result<RecordIterator> {
+    by_mnemonic_impl(&mnemonics.join(DELIMITER), format)
 }
 
 // PRIVATE
@@ -52,32 +256,109 @@ pub fn by_mnemonic_list(mnemonics: &[&str]) -> Result<RecordIterator> {
 
 /// Helper function for requesting by accession number.
 #[inline(always)]
-fn by_id_impl(param: &str) -> Result<RecordIterator> {
-    call(&format!("id:{}", param))
+fn by_id_impl(param: &str, format: Format) -> Result<RecordIterator> {
+    call(&format!("id:{}", param), format)
 }
 
 /// Helper function for requesting by mnemonic.
 #[inline(always)]
-fn by_mnemonic_impl(param: &str) -> Result<RecordIterator> {
-    call(&format!("mnemonic:{}", param))
+fn by_mnemonic_impl(param: &str, format: Format) -> Result<RecordIterator> {
+    call(&format!("mnemonic:{}", param), format)
 }
 
-// Helper function for calling the UniProt KB service.
-fn call(query: &str) -> Result<RecordIterator> {
-    // create our url with form-encoded parameters
-    let params = url::form_urlencoded::Serializer::new(String::new())
+/// Remove duplicate identifiers, preserving the order of the first occurrences.
+fn dedup_ids<'a>(ids: &[&'a str]) -> Vec<&'a str> {
+    let mut seen = HashSet::with_capacity(ids.len());
+    ids.iter().cloned().filter(|id| seen.insert(*id)).collect()
+}
+
+/// Split identifiers into batches with joined lengths below `max_length`.
+///
+/// Identifiers longer than `max_length` form their own batch.
+fn batch_ids<'a>(ids: &[&'a str], max_length: usize) -> Vec<Vec<&'a str>> {
+    let mut batches = vec![];
+    let mut batch: Vec<&str> = vec![];
+    let mut length = 0;
+    for &id in ids {
+        if !batch.is_empty() && length + DELIMITER.len() + id.len() > max_length {
+            batches.push(batch);
+            batch = vec![];
+        }
+        length = match batch.is_empty() {
+            true  => id.len(),
+            false => length + DELIMITER.len() + id.len(),
+        };
+        batch.push(id);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    batches
+}
+
+/// Order records by the requested identifiers, and find the missing identifiers.
+fn order_by_ids(ids: &[&str], records: Vec<Record>) -> BatchResult {
+    let requested: HashSet<&str> = ids.iter().cloned().collect();
+    let mut found: HashMap<String, Record> = HashMap::with_capacity(records.len());
+    let mut unmatched = vec![];
+    for record in records {
+        if requested.contains(record.id.as_str()) {
+            found.entry(record.id.clone()).or_insert(record);
+        } else {
+            unmatched.push(record);
+        }
+    }
+
+    let mut result = BatchResult::default();
+    for &id in ids {
+        match found.remove(id) {
+            Some(record) => result.records.push(record),
+            None         => result.missing.push(String::from(id)),
+        }
+    }
+    result.records.extend(unmatched);
+
+    result
+}
+
+/// Create the form-encoded query parameters for a request.
+fn parameters(query: &str, format: Format) -> String {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    serializer
         .append_pair("sort", "score")
         .append_pair("desc", "")
         .append_pair("fil", "")
         .append_pair("force", "no")
-        .append_pair("format", "tab")
-        .append_pair("query", query)
-        .append_pair("columns", "version(sequence),existence,mass,length,genes(PREFERRED),id,entry name,protein names,organism,proteome,sequence,organism-id,reviewed")
-        .finish();
-    let url = format!("{}?{}", HOST, params);
-    let response = reqwest::get(&url)?;
+        .append_pair("format", format.parameter())
+        .append_pair("query", query);
 
-    Ok(CsvRecordIter::new(response, b'\t'))
+    #[cfg(feature = "csv")]
+    {
+        if format == Format::Tsv {
+            serializer.append_pair("columns", TSV_COLUMNS);
+        }
+    }
+
+    serializer.finish()
+}
+
+// Helper function for calling the UniProt KB service.
+fn call(query: &str, format: Format) -> Result<RecordIterator> {
+    let url = format!("{}?{}", HOST, parameters(query, format));
+    log_info!("UniProt request: GET {}", url);
+    let response = http::get(&url)?;
+
+    Ok(RecordIterator::new(response, format))
+}
+
+// Helper function for calling the UniProt KB service, with a cancellation token.
+fn call_with_cancel(query: &str, format: Format, cancel: &CancellationToken) -> Result<RecordIterator> {
+    let url = format!("{}?{}", HOST, parameters(query, format));
+    log_info!("UniProt request: GET {}", url);
+    let response = http::get_with_cancel(&url, cancel)?;
+
+    Ok(RecordIterator::new(response, format))
 }
 
 // TESTS
@@ -143,6 +424,109 @@ mod tests {
         check_bsa(&list[1]);
     }
 
+    #[test]
+    fn batch_ids_test() {
+        let ids = dedup_ids(&["P46406", "P02769", "P46406", "P00761"]);
+        assert_eq!(ids, vec!["P46406", "P02769", "P00761"]);
+
+        // "P46406 OR P02769" has 16 characters.
+        let batches = batch_ids(&ids, 16);
+        assert_eq!(batches, vec![vec!["P46406", "P02769"], vec!["P00761"]]);
+        let batches = batch_ids(&ids, 5);
+        assert_eq!(batches.len(), 3);
+        assert!(batch_ids(&[], 16).is_empty());
+    }
+
+    #[test]
+    fn order_by_ids_test() {
+        let mut gapdh = Record::new();
+        gapdh.id = String::from("P46406");
+        let mut bsa = Record::new();
+        bsa.id = String::from("P02769");
+        let mut other = Record::new();
+        other.id = String::from("P00761");
+
+        let records = vec![other.clone(), bsa.clone(), gapdh.clone(), bsa.clone()];
+        let result = order_by_ids(&["P46406", "Q00000", "P02769"], records);
+        assert_eq!(result.records, vec![gapdh, bsa, other]);
+        assert_eq!(result.missing, vec!["Q00000"]);
+    }
+
+    #[test]
+    fn parameters_test() {
+        let format = Format::default();
+        assert!(parameters("id:P46406", format).contains("query=id%3AP46406"));
+
+        #[cfg(feature = "csv")]
+        assert!(parameters("id:P46406", Format::Tsv).contains("format=tab&query=id%3AP46406&columns="));
+
+        #[cfg(feature = "fasta")]
+        assert!(parameters("id:P46406", Format::Fasta).ends_with("format=fasta&query=id%3AP46406"));
+
+        #[cfg(feature = "xml")]
+        assert!(parameters("id:P46406", Format::Xml).ends_with("format=xml&query=id%3AP46406"));
+    }
+
+    #[cfg(feature = "fasta")]
+    #[test]
+    fn replay_test() {
+        use std::env;
+        use std::fs;
+        use io::http::{fixture_path, set_client_mode, ClientMode, CLIENT_MODE_LOCK};
+        use super::super::test::GAPDH_FASTA;
+
+        let dir = env::temp_dir().join("bdb_client_replay_test");
+        let url = format!("{}?{}", HOST, parameters("id:P46406", Format::Fasta));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(fixture_path(&dir, &url), GAPDH_FASTA).unwrap();
+
+        let _lock = CLIENT_MODE_LOCK.lock().unwrap();
+        set_client_mode(ClientMode::Replay(dir.clone()));
+        let record = by_id_with_format("P46406", Format::Fasta).unwrap().next().unwrap();
+        let missing = by_id_with_format("P02769", Format::Fasta).err();
+        let cancel = CancellationToken::new();
+        let fetched = fetch_by_ids_with_cancel(&["P46406"], Format::Fasta, &cancel).unwrap();
+        cancel.cancel();
+        let cancelled = fetch_by_ids_with_cancel(&["P46406"], Format::Fasta, &cancel).err();
+        set_client_mode(ClientMode::Online);
+
+        assert_eq!(record.unwrap().mnemonic, "G3P_RABIT");
+        assert_eq!(fetched.records.len(), 1);
+        assert!(cancelled.is_some());
+        assert!(missing.unwrap().path().is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "fasta")]
+    #[test]
+    #[ignore]
+    fn by_id_fasta_test() {
+        let record: Record = by_id_with_format("P46406", Format::Fasta).unwrap().next().unwrap().unwrap();
+        assert_eq!(record.id, "P46406");
+        assert_eq!(record.mnemonic, "G3P_RABIT");
+        assert_eq!(record.length, 333);
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    #[ignore]
+    fn by_id_xml_test() {
+        let record: Record = by_id_with_format("P46406", Format::Xml).unwrap().next().unwrap().unwrap();
+        assert_eq!(record.id, "P46406");
+        assert_eq!(record.mnemonic, "G3P_RABIT");
+        assert_eq!(record.length, 333);
+    }
+
+    #[test]
+    #[ignore]
+    fn fetch_by_ids_test() {
+        let result = fetch_by_ids(&["P46406", "P02769", "P46406"]).unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert!(result.missing.is_empty());
+        check_gapdh(&result.records[0]);
+        check_bsa(&result.records[1]);
+    }
+
     #[test]
     #[ignore]
     fn by_mnemonic_test() {
@@ -0,0 +1,148 @@
+//! Helper utilities for UniProt columnar (Arrow and Parquet) export.
+//!
+//! Exports the scalar fields of each record, one column per field.
+//! The cross-references, keywords and features are not exported.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanBuilder, StringBuilder, UInt32Builder, UInt64Builder, UInt8Builder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use traits::Columnar;
+use util::Result;
+use super::record::Record;
+
+impl Columnar for Record {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("sequence_version", DataType::UInt8, false),
+            Field::new("protein_evidence", DataType::Utf8, false),
+            Field::new("mass", DataType::UInt64, false),
+            Field::new("length", DataType::UInt32, false),
+            Field::new("gene", DataType::Utf8, false),
+            Field::new("id", DataType::Utf8, false),
+            Field::new("mnemonic", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("organism", DataType::Utf8, false),
+            Field::new("proteome", DataType::Utf8, false),
+            Field::new("sequence", DataType::Utf8, false),
+            Field::new("taxonomy", DataType::Utf8, false),
+            Field::new("reviewed", DataType::Boolean, false),
+        ]))
+    }
+
+    fn to_record_batch(models: &[&Self]) -> Result<RecordBatch> {
+        let mut sequence_version = UInt8Builder::with_capacity(models.len());
+        let mut protein_evidence = StringBuilder::new();
+        let mut mass = UInt64Builder::with_capacity(models.len());
+        let mut length = UInt32Builder::with_capacity(models.len());
+        let mut gene = StringBuilder::new();
+        let mut id = StringBuilder::new();
+        let mut mnemonic = StringBuilder::new();
+        let mut name = StringBuilder::new();
+        let mut organism = StringBuilder::new();
+        let mut proteome = StringBuilder::new();
+        let mut sequence = StringBuilder::new();
+        let mut taxonomy = StringBuilder::new();
+        let mut reviewed = BooleanBuilder::with_capacity(models.len());
+
+        for record in models.iter() {
+            sequence_version.append_value(record.sequence_version);
+            protein_evidence.append_value(record.protein_evidence.verbose());
+            mass.append_value(record.mass);
+            length.append_value(record.length);
+            gene.append_value(&record.gene);
+            id.append_value(&record.id);
+            mnemonic.append_value(&record.mnemonic);
+            name.append_value(&record.name);
+            organism.append_value(&record.organism);
+            proteome.append_value(&record.proteome);
+            sequence.append_value(String::from_utf8_lossy(&record.sequence));
+            taxonomy.append_value(&record.taxonomy);
+            reviewed.append_value(record.reviewed);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(sequence_version.finish()),
+            Arc::new(protein_evidence.finish()),
+            Arc::new(mass.finish()),
+            Arc::new(length.finish()),
+            Arc::new(gene.finish()),
+            Arc::new(id.finish()),
+            Arc::new(mnemonic.finish()),
+            Arc::new(name.finish()),
+            Arc::new(organism.finish()),
+            Arc::new(proteome.finish()),
+            Arc::new(sequence.finish()),
+            Arc::new(taxonomy.finish()),
+            Arc::new(reviewed.finish()),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::fs::File;
+    use std::io::Cursor;
+    use arrow::array::{Array, StringArray, UInt64Array};
+    use arrow::ipc::reader::FileReader;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn record_batch_test() {
+        let (g, b) = (gapdh(), bsa());
+        let batch = Record::to_record_batch(&[&g, &b]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 13);
+
+        let id = batch.column(5).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(id.value(0), "P46406");
+        assert_eq!(id.value(1), "P02769");
+        let mass = batch.column(2).as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(mass.value(0), 35780);
+    }
+
+    #[test]
+    fn arrow_iter_test() {
+        let v = vec![gapdh(), bsa()];
+        let mut w = Cursor::new(vec![]);
+        Record::to_arrow_iter(&mut w, v.iter()).unwrap();
+
+        let reader = FileReader::try_new(Cursor::new(w.into_inner()), None).unwrap();
+        assert_eq!(reader.schema(), Record::arrow_schema());
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 2);
+
+        let path = env::temp_dir().join("bdb_uniprot_arrow_test.arrow");
+        Record::to_arrow_file_iter(&path, v.iter()).unwrap();
+        let reader = FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn parquet_iter_test() {
+        let v = vec![gapdh(), bsa()];
+        let mut w = vec![];
+        Record::to_parquet_iter(&mut w, v.iter()).unwrap();
+        assert!(w.starts_with(b"PAR1"));
+
+        let path = env::temp_dir().join("bdb_uniprot_parquet_test.parquet");
+        Record::to_parquet_file_iter(&path, v.iter()).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+        let gene = batches[0].column(4).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(gene.value(1), "ALB");
+    }
+}
@@ -3,9 +3,9 @@
 use csv;
 use std::collections::BTreeMap;
 use std::io::prelude::*;
-use std::str as stdstr;
 
-use bio::SequenceMass;
+use bio::{Alphabet, SequenceMass};
+use bio::alphabet::check_protein_sequence;
 use bio::proteins::AverageMass;
 use traits::*;
 use util::*;
@@ -120,14 +120,24 @@ fn new_writer<T: Write>(writer: T, delimiter: u8)
 }
 
 /// Create CSV reader.
+///
+/// The CSV reader uses a fixed-size read buffer, so a capacity
+/// of 0 uses the default capacity, rather than reading nothing.
 #[inline(always)]
-fn new_reader<T: Read>(reader: T, delimiter: u8)
+fn new_reader<T: Read>(reader: T, delimiter: u8, capacity: usize)
     -> csv::Reader<T>
 {
+    let capacity = match capacity {
+        0 => DEFAULT_BUFFER_CAPACITY,
+        _ => capacity,
+    };
     csv::ReaderBuilder::new()
+        .buffer_capacity(capacity)
         .delimiter(delimiter)
         .has_headers(false)
+        .comment(Some(CSV_COMMENT[0]))
         .flexible(false)
+        .trim(csv::Trim::All)
         .from_reader(reader)
 }
 
@@ -146,7 +156,10 @@ fn parse_header(opt: CsvIterResult, map: &mut RecordFieldIndex)
     let row = none_to_error!(opt, InvalidInput)?;
 
     for tup in row.iter().enumerate() {
-        let (index, item) = tup;
+        let (index, mut item) = tup;
+        if index == 0 && item.starts_with(UTF8_BOM) {
+            item = &item[UTF8_BOM.len()..];
+        }
         let key: RecordField = match item {
             SEQUENCE_VERSION    => RecordField::SequenceVersion,
             PROTEIN_EVIDENCE    => RecordField::ProteinEvidence,
@@ -177,12 +190,12 @@ macro_rules! load_from_commas {
     })
 }
 
-/// Specialized macro to handle errors while loading text from UTF-8.
+/// Specialized macro to handle errors while decoding text to UTF-8.
 macro_rules! load_as_utf8 {
     // We cannot guarantee the validity of the records! Be safe!
-    ($bytes:expr) => (match stdstr::from_utf8($bytes) {
-        Err(e)  => return Some(Err(From::from(e))),
-        Ok(v)   => String::from(v),
+    ($bytes:expr, $encoding:expr) => (match $encoding.decode_str($bytes) {
+        Err(e)  => return Some(Err(e)),
+        Ok(v)   => v.into_owned(),
     })
 }
 
@@ -204,7 +217,7 @@ macro_rules! load_reviewed {
 }
 
 /// Helper function to return the next `Record` from the CSV iterator.
-fn next(opt: CsvIterResult, map: &RecordFieldIndex)
+fn next(opt: CsvIterResult, map: &RecordFieldIndex, encoding: Encoding)
     -> Option<Result<Record>>
 {
     // Get the next record, and short-circuit if None or an Error.
@@ -225,14 +238,14 @@ fn next(opt: CsvIterResult, map: &RecordFieldIndex)
             RecordField::ProteinEvidence => record.protein_evidence = load_evidence!(value),
             RecordField::Mass            => record.mass = load_from_commas!(value, u64),
             RecordField::Length          => record.length = load_from_commas!(value, u32),
-            RecordField::Gene            => record.gene = load_as_utf8!(value),
-            RecordField::Id              => record.id = load_as_utf8!(value),
-            RecordField::Mnemonic        => record.mnemonic = load_as_utf8!(value),
-            RecordField::Name            => record.name = load_as_utf8!(value),
-            RecordField::Organism        => record.organism = load_as_utf8!(value),
-            RecordField::Proteome        => record.proteome = load_as_utf8!(value),
+            RecordField::Gene            => record.gene = load_as_utf8!(value, encoding),
+            RecordField::Id              => record.id = load_as_utf8!(value, encoding),
+            RecordField::Mnemonic        => record.mnemonic = load_as_utf8!(value, encoding),
+            RecordField::Name            => record.name = load_as_utf8!(value, encoding),
+            RecordField::Organism        => record.organism = load_as_utf8!(value, encoding),
+            RecordField::Proteome        => record.proteome = load_as_utf8!(value, encoding),
             RecordField::Sequence        => record.sequence = value.to_vec(),
-            RecordField::Taxonomy        => record.taxonomy = load_as_utf8!(value),
+            RecordField::Taxonomy        => record.taxonomy = load_as_utf8!(value, encoding),
             RecordField::Reviewed        => record.reviewed = load_reviewed!(value),
         }
     }
@@ -379,6 +392,173 @@ pub fn value_iterator_to_csv_lenient<Iter, T>(writer: &mut T, iter: Iter, delimi
     value_iterator_export_lenient(writer, iter, delimiter, &init_cb, &export_cb, &dest_cb)
 }
 
+// WRITER -- ANNOTATIONS
+
+/// Long-format table of record annotations, with one row per annotation.
+///
+/// Each row starts with the accession number of the record, so the
+/// table can be joined to the record table. Records without
+/// annotations do not produce rows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnnotationTable {
+    /// One row per record-keyword.
+    Keywords,
+    /// One row per record-feature.
+    Features,
+    /// One row per record-cross-reference.
+    Xrefs,
+}
+
+impl AnnotationTable {
+    /// Get the header columns of the table.
+    pub fn header(&self) -> &'static [&'static [u8]] {
+        const KEYWORDS: [&'static [u8]; 3] = [ID, b"Keyword ID", b"Keyword"];
+        const FEATURES: [&'static [u8]; 6] = [ID, b"Feature type", b"Description", b"Feature ID", b"Begin", b"End"];
+        const XREFS: [&'static [u8]; 4] = [ID, b"Database", b"Cross-reference", b"Properties"];
+
+        match *self {
+            AnnotationTable::Keywords   => &KEYWORDS,
+            AnnotationTable::Features   => &FEATURES,
+            AnnotationTable::Xrefs      => &XREFS,
+        }
+    }
+}
+
+/// Write the annotation rows of a record for CSV serialization.
+fn to_annotation_csv<T: Write>(writer: &mut csv::Writer<T>, record: &Record, table: AnnotationTable)
+    -> Result<()>
+{
+    let id = record.id.as_bytes();
+    match table {
+        AnnotationTable::Keywords   => {
+            for keyword in record.keywords.iter() {
+                writer.write_record(&[id, keyword.id.as_bytes(), keyword.name.as_bytes()])?;
+            }
+        },
+        AnnotationTable::Features   => {
+            for feature in record.features.iter() {
+                let begin = nonzero_to_bytes(&feature.begin)?;
+                let end = nonzero_to_bytes(&feature.end)?;
+                writer.write_record(&[
+                    id,
+                    feature.kind.as_bytes(),
+                    feature.description.as_bytes(),
+                    feature.id.as_bytes(),
+                    begin.as_slice(),
+                    end.as_slice(),
+                ])?;
+            }
+        },
+        AnnotationTable::Xrefs      => {
+            for xref in record.xrefs.iter() {
+                // Join the properties as "type=value" pairs.
+                let properties = xref.properties.iter()
+                    .map(|p| format!("{}={}", p.0, p.1))
+                    .collect::<Vec<String>>()
+                    .join("; ");
+                writer.write_record(&[id, xref.db.as_bytes(), xref.id.as_bytes(), properties.as_bytes()])?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Write the header of an annotation table.
+///
+/// The header is written separately from the rows, since the
+/// exporter initializer cannot depend on the table.
+fn write_annotation_header<T: Write>(writer: &mut T, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+{
+    let mut writer = new_writer(writer, delimiter);
+    writer.write_record(table.header())?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[inline(always)]
+fn annotation_init_cb<T: Write>(writer: T, delimiter: u8)
+    -> Result<csv::Writer<T>>
+{
+    Ok(new_writer(writer, delimiter))
+}
+
+/// Export the annotations of a record to a long-format CSV table.
+pub fn record_to_annotation_csv<T: Write>(writer: &mut T, record: &Record, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let mut writer = new_writer(writer, delimiter);
+    to_annotation_csv(&mut writer, record, table)?;
+    Ok(())
+}
+
+/// Default export from a non-owning iterator to a long-format annotation CSV table.
+pub fn reference_iterator_to_annotation_csv<'a, Iter, T>(writer: &mut T, iter: Iter, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let export_cb = |w: &mut csv::Writer<&mut T>, r: &'a Record| to_annotation_csv(w, r, table);
+    reference_iterator_export(writer, iter, delimiter, &annotation_init_cb, &export_cb, &dest_cb)
+}
+
+/// Default export from an owning iterator to a long-format annotation CSV table.
+pub fn value_iterator_to_annotation_csv<Iter, T>(writer: &mut T, iter: Iter, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let export_cb = |w: &mut csv::Writer<&mut T>, r: &Record| to_annotation_csv(w, r, table);
+    value_iterator_export(writer, iter, delimiter, &annotation_init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict export from a non-owning iterator to a long-format annotation CSV table.
+pub fn reference_iterator_to_annotation_csv_strict<'a, Iter, T>(writer: &mut T, iter: Iter, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let export_cb = |w: &mut csv::Writer<&mut T>, r: &'a Record| to_annotation_csv(w, r, table);
+    reference_iterator_export_strict(writer, iter, delimiter, &annotation_init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict export from an owning iterator to a long-format annotation CSV table.
+pub fn value_iterator_to_annotation_csv_strict<Iter, T>(writer: &mut T, iter: Iter, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let export_cb = |w: &mut csv::Writer<&mut T>, r: &Record| to_annotation_csv(w, r, table);
+    value_iterator_export_strict(writer, iter, delimiter, &annotation_init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient export from a non-owning iterator to a long-format annotation CSV table.
+pub fn reference_iterator_to_annotation_csv_lenient<'a, Iter, T>(writer: &mut T, iter: Iter, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let export_cb = |w: &mut csv::Writer<&mut T>, r: &'a Record| to_annotation_csv(w, r, table);
+    reference_iterator_export_lenient(writer, iter, delimiter, &annotation_init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient export from an owning iterator to a long-format annotation CSV table.
+pub fn value_iterator_to_annotation_csv_lenient<Iter, T>(writer: &mut T, iter: Iter, table: AnnotationTable, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    write_annotation_header(writer, table, delimiter)?;
+    let export_cb = |w: &mut csv::Writer<&mut T>, r: &Record| to_annotation_csv(w, r, table);
+    value_iterator_export_lenient(writer, iter, delimiter, &annotation_init_cb, &export_cb, &dest_cb)
+}
+
 // READER
 
 /// Import record from CSV.
@@ -396,16 +576,26 @@ pub struct CsvRecordIter<T: Read> {
     map: RecordFieldIndex,
     iter: csv::ByteRecordsIntoIter<T>,
     has_map: bool,
+    encoding: Encoding,
+    alphabet: Option<Alphabet>,
 }
 
 impl<T: Read> CsvRecordIter<T> {
      /// Create new CsvRecordIter from a reader.
     #[inline]
     pub fn new(reader: T, delimiter: u8) -> Self {
+        CsvRecordIter::with_options(reader, delimiter, &ReaderOptions::default())
+    }
+
+    /// Create new CsvRecordIter from a reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, delimiter: u8, options: &ReaderOptions) -> Self {
         CsvRecordIter {
             map: RecordFieldIndex::new(),
-            iter: new_reader(reader, delimiter).into_byte_records(),
+            iter: new_reader(reader, delimiter, options.buffer_capacity).into_byte_records(),
             has_map: false,
+            encoding: options.encoding,
+            alphabet: options.alphabet,
         }
     }
 
@@ -430,7 +620,12 @@ impl<T: Read> Iterator for CsvRecordIter<T> {
                 _      => (),
             }
         }
-        next(self.iter.next(), &self.map)
+        let result = next(self.iter.next(), &self.map, self.encoding)?.and_then(|mut r| {
+            check_protein_sequence(self.alphabet, &mut r.sequence)?;
+            Ok(r)
+        });
+        log_parsed!("CSV", &result);
+        Some(result)
     }
 }
 
@@ -440,6 +635,12 @@ pub fn iterator_from_csv<T: Read>(reader: T, delimiter: u8) -> CsvRecordIter<T>
     CsvRecordIter::new(reader, delimiter)
 }
 
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_csv_with_options<T: Read>(reader: T, delimiter: u8, options: &ReaderOptions) -> CsvRecordIter<T> {
+    CsvRecordIter::with_options(reader, delimiter, options)
+}
+
 // READER -- STRICT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -451,6 +652,12 @@ pub fn iterator_from_csv_strict<T: Read>(reader: T, delimiter: u8) -> CsvRecordS
     CsvRecordStrictIter::new(iterator_from_csv(reader, delimiter))
 }
 
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_csv_strict_with_options<T: Read>(reader: T, delimiter: u8, options: &ReaderOptions) -> CsvRecordStrictIter<T> {
+    CsvRecordStrictIter::new(iterator_from_csv_with_options(reader, delimiter, options))
+}
+
 // READER -- LENIENT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -462,6 +669,12 @@ pub fn iterator_from_csv_lenient<T: Read>(reader: T, delimiter: u8) -> CsvRecord
     CsvRecordLenientIter::new(iterator_from_csv(reader, delimiter))
 }
 
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_csv_lenient_with_options<T: Read>(reader: T, delimiter: u8, options: &ReaderOptions) -> CsvRecordLenientIter<T> {
+    CsvRecordLenientIter::new(iterator_from_csv_with_options(reader, delimiter, options))
+}
+
 // TRAITS
 
 impl Csv for Record {
@@ -479,6 +692,20 @@ impl Csv for Record {
     fn from_csv<T: Read>(reader: &mut T, delimiter: u8) -> Result<Self> {
         record_from_csv(reader, delimiter)
     }
+
+    #[inline(always)]
+    fn from_csv_iter<'a, T: 'a + Read>(reader: T, delimiter: u8)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_csv(reader, delimiter))
+    }
+
+    #[inline(always)]
+    fn to_csv_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter, delimiter: u8)
+        -> Result<()>
+    {
+        reference_iterator_to_csv(writer, iter, delimiter)
+    }
 }
 
 impl Csv for RecordList {
@@ -527,7 +754,9 @@ impl CsvCollection for RecordList {
 mod tests {
     use std::io::Cursor;
     use super::*;
+    use super::super::annotation::{Feature, Keyword};
     use super::super::test::*;
+    use super::super::xref::DbXref;
 
     #[test]
     fn estimate_size_test() {
@@ -539,6 +768,51 @@ mod tests {
         assert_eq!(estimate_list_size(&v), 1193);
     }
 
+    #[test]
+    fn iterator_to_annotation_csv_test() {
+        let mut x = gapdh();
+        x.keywords.push(Keyword::new("KW-0007", "Acetylation"));
+        x.keywords.push(Keyword::new("KW-0324", "Glycolysis"));
+        let mut chain = Feature::new("chain", "Glyceraldehyde-3-phosphate dehydrogenase", 1, 333);
+        chain.id = String::from("PRO_0000145493");
+        x.features.push(chain);
+        x.features.push(Feature::new("binding site", "NAD", 34, 0));
+        let mut pdb = DbXref::new("PDB", "1J0X");
+        pdb.properties.push((String::from("method"), String::from("X-ray")));
+        pdb.properties.push((String::from("resolution"), String::from("2.40")));
+        x.xrefs.push(pdb);
+        x.xrefs.push(DbXref::new("GeneID", "100009074"));
+        let mut y = bsa();
+        y.keywords.push(Keyword::new("KW-0479", "Metal-binding"));
+        let v = vec![x, y, Record::new()];
+
+        // keywords
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_annotation_csv(&mut w, v.iter(), AnnotationTable::Keywords, b'\t').unwrap();
+        assert_eq!(w.into_inner(), b"Entry\tKeyword ID\tKeyword\nP46406\tKW-0007\tAcetylation\nP46406\tKW-0324\tGlycolysis\nP02769\tKW-0479\tMetal-binding\n".to_vec());
+
+        // features
+        let mut w = Cursor::new(vec![]);
+        value_iterator_to_annotation_csv(&mut w, iterator_by_value!(v.iter()), AnnotationTable::Features, b',').unwrap();
+        assert_eq!(w.into_inner(), b"Entry,Feature type,Description,Feature ID,Begin,End\nP46406,chain,Glyceraldehyde-3-phosphate dehydrogenase,PRO_0000145493,1,333\nP46406,binding site,NAD,,34,\n".to_vec());
+
+        // cross-references
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_annotation_csv_lenient(&mut w, v.iter(), AnnotationTable::Xrefs, b'\t').unwrap();
+        assert_eq!(w.into_inner(), b"Entry\tDatabase\tCross-reference\tProperties\nP46406\tPDB\t1J0X\tmethod=X-ray; resolution=2.40\nP46406\tGeneID\t100009074\t\n".to_vec());
+
+        // strict
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_annotation_csv_strict(&mut w, v.iter(), AnnotationTable::Keywords, b'\t').is_err());
+        let mut w = Cursor::new(vec![]);
+        assert!(value_iterator_to_annotation_csv_strict(&mut w, iterator_by_value!(v[..2].iter()), AnnotationTable::Keywords, b'\t').is_ok());
+
+        // single record
+        let mut w = Cursor::new(vec![]);
+        record_to_annotation_csv(&mut w, &v[1], AnnotationTable::Keywords, b',').unwrap();
+        assert_eq!(w.into_inner(), b"Entry,Keyword ID,Keyword\nP02769,KW-0479,Metal-binding\n".to_vec());
+    }
+
     #[test]
     fn iterator_to_csv_test() {
         let v = vec![gapdh(), bsa()];
@@ -591,6 +865,72 @@ mod tests {
         assert_eq!(w.into_inner(), GAPDH_BSA_CSV_TAB);
     }
 
+    #[test]
+    fn windows_csv_test() {
+        use test::windows_text;
+
+        let text = windows_text(GAPDH_BSA_CSV_TAB);
+        let iter = iterator_from_csv(Cursor::new(text), b'\t');
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(v.unwrap(), vec![gapdh(), bsa()]);
+    }
+
+    #[test]
+    fn csv_encoding_test() {
+        let text = String::from_utf8(GAPDH_CSV_TAB.to_vec()).unwrap().replace("Oryctolagus", "Oryctolagus\u{e9}");
+        let text: Vec<u8> = text.chars().map(|c| c as u8).collect();
+
+        let v: Result<RecordList> = iterator_from_csv(Cursor::new(&text), b'\t').collect();
+        assert!(v.is_err());
+
+        let options = ReaderOptions::with_encoding(Encoding::Latin1);
+        let v: Result<RecordList> = iterator_from_csv_strict_with_options(Cursor::new(&text), b'\t', &options).collect();
+        let v = v.unwrap();
+        assert_eq!(v[0].organism, "Oryctolagus\u{e9} cuniculus");
+        assert_eq!(v[0].sequence, gapdh().sequence);
+
+        let options = ReaderOptions::with_encoding(Encoding::Utf8Lossy);
+        let v: Result<RecordList> = iterator_from_csv_lenient_with_options(Cursor::new(&text), b'\t', &options).collect();
+        assert_eq!(v.unwrap()[0].organism, "Oryctolagus\u{FFFD} cuniculus");
+    }
+
+    #[test]
+    fn csv_capacity_test() {
+        let expected = vec![gapdh(), bsa()];
+        for &(buffer, line) in [(0, 0), (1, 1), (1 << 20, 1 << 16)].iter() {
+            let options = ReaderOptions::with_capacity(buffer, line);
+            let v: Result<RecordList> = iterator_from_csv_with_options(Cursor::new(GAPDH_BSA_CSV_TAB), b'\t', &options).collect();
+            assert_eq!(v.unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn csv_trait_iter_test() {
+        let v: RecordList = Record::from_csv_iter(Cursor::new(GAPDH_BSA_CSV_TAB), b'\t')
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(v, vec![gapdh(), bsa()]);
+
+        let mut w = Cursor::new(vec![]);
+        Record::to_csv_iter(&mut w, v.iter(), b'\t').unwrap();
+        assert_eq!(w.into_inner(), GAPDH_BSA_CSV_TAB);
+    }
+
+    #[test]
+    fn csv_metadata_test() {
+        let v = vec![gapdh(), bsa()];
+        let metadata = ExportMetadata::new("uniprot.xml").parameter("delimiter", "tab");
+        let mut w = Cursor::new(vec![]);
+        v.to_csv_with_metadata(&mut w, b'\t', &metadata).unwrap();
+        let text = w.into_inner();
+        assert!(text.starts_with(b"#bdb-metadata: 1\n#tool: bdb "));
+        assert!(text.ends_with(GAPDH_BSA_CSV_TAB));
+
+        // readers skip the metadata block
+        let list = RecordList::from_csv_bytes(&text, b'\t').unwrap();
+        assert_eq!(list, v);
+    }
+
     #[test]
     fn iterator_from_csv_test() {
         // VALID
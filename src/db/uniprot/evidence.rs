@@ -1,10 +1,12 @@
 //! Model for UniProt protein evidence.
 
+use std::convert::TryFrom;
 use std::mem;
+use std::slice;
 use std::str as stdstr;
 
 use traits::{Deserializable, Serializable, Zero};
-use util::{Bytes, ErrorKind, Result};
+use util::{Bytes, Error, ErrorKind, Result};
 
 /// Identifier for the evidence type for protein existence.
 ///
@@ -47,9 +49,23 @@ impl ProteinEvidence {
     const UNKNOWN_LEVEL_VERBOSE: &'static [u8] = b"";
 
     /// Minimum and maximum bounds on the enumeration.
-    const MIN: u8 = 0;
+    const MIN: u8 = 1;
     const MAX: u8 = 5;
 
+    /// All annotated evidence levels, from strongest to weakest.
+    pub const ALL: [ProteinEvidence; 4] = [
+        ProteinEvidence::ProteinLevel,
+        ProteinEvidence::TranscriptLevel,
+        ProteinEvidence::Inferred,
+        ProteinEvidence::Predicted,
+    ];
+
+    /// Iterate over all annotated evidence levels, from strongest to weakest.
+    #[inline]
+    pub fn iter() -> slice::Iter<'static, ProteinEvidence> {
+        Self::ALL.iter()
+    }
+
     /// Convert enumerated value for ProteinEvidence to verbose bytes.
     #[inline]
     pub fn verbose_bytes(&self) -> &'static [u8] {
@@ -102,6 +118,50 @@ impl ProteinEvidence {
             Err(From::from(ErrorKind::InvalidEnumeration))
         }
     }
+
+    /// Create enumerated value from any textual representation used by UniProt.
+    ///
+    /// Accepts the numeric level ("1"), the FASTA header field ("PE=1"),
+    /// the verbose text ("Evidence at protein level"), the XML verbose
+    /// text ("evidence at protein level"), and the numbered verbose
+    /// text of the UniProt REST API ("1: Evidence at protein level").
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let text = if text.starts_with("PE=") { &text[3..] } else { text };
+        if let Ok(int) = text.parse::<u8>() {
+            bool_to_error!(int < ProteinEvidence::Unknown.to_int(), InvalidEnumeration);
+            return Self::from_int(int);
+        }
+
+        let verbose = match text.find(':') {
+            Some(index) => text[index+1..].trim_start(),
+            None        => text,
+        };
+        for evidence in Self::iter() {
+            if verbose.as_bytes().eq_ignore_ascii_case(evidence.verbose_bytes()) {
+                return Ok(*evidence);
+            }
+        }
+        Err(From::from(ErrorKind::InvalidEnumeration))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ProteinEvidence {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(text: &'a str) -> Result<Self> {
+        ProteinEvidence::parse(text)
+    }
+}
+
+impl TryFrom<u8> for ProteinEvidence {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(int: u8) -> Result<Self> {
+        ProteinEvidence::from_int(int)
+    }
 }
 
 #[cfg(feature = "xml")]
@@ -258,4 +318,25 @@ mod tests {
         serialize_protein_evidence(ProteinEvidence::Inferred, "3");
         serialize_protein_evidence(ProteinEvidence::Predicted, "4");
     }
+
+    #[test]
+    fn parse_protein_evidence_test() {
+        assert_eq!(ProteinEvidence::iter().count(), 4);
+        for evidence in ProteinEvidence::iter() {
+            let int = evidence.to_int();
+            assert_eq!(ProteinEvidence::parse(&int.to_string()).unwrap(), *evidence);
+            assert_eq!(ProteinEvidence::parse(&format!("PE={}", int)).unwrap(), *evidence);
+            assert_eq!(ProteinEvidence::parse(evidence.verbose()).unwrap(), *evidence);
+            assert_eq!(ProteinEvidence::parse(&evidence.verbose().to_lowercase()).unwrap(), *evidence);
+            assert_eq!(ProteinEvidence::parse(&format!("{}: {}", int, evidence.verbose())).unwrap(), *evidence);
+            assert_eq!(ProteinEvidence::try_from(evidence.verbose()).unwrap(), *evidence);
+            assert_eq!(ProteinEvidence::try_from(int).unwrap(), *evidence);
+        }
+
+        assert!(ProteinEvidence::parse("5").is_err());
+        assert!(ProteinEvidence::parse("").is_err());
+        assert!(ProteinEvidence::parse("Uncertain").is_err());
+        assert!(ProteinEvidence::try_from(0u8).is_err());
+        assert!(ProteinEvidence::try_from(6u8).is_err());
+    }
 }
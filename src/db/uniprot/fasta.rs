@@ -1,8 +1,10 @@
 //! Helper utilities for FASTA loading and saving.
 
 use std::io::prelude::*;
+use std::io::Lines;
 
-use bio::SequenceMass;
+use bio::{Alphabet, SequenceMass};
+use bio::alphabet::check_protein_sequence;
 use bio::proteins::AverageMass;
 use traits::*;
 use util::*;
@@ -26,10 +28,16 @@ impl<T: BufRead> FastaIter<T> {
     /// Create new FastaIter from a buffered reader.
     #[inline]
     pub fn new(reader: T) -> Self {
+        FastaIter::with_capacity(reader, DEFAULT_BUFFER_CAPACITY, DEFAULT_LINE_CAPACITY)
+    }
+
+    /// Create new FastaIter from a buffered reader and buffer capacities.
+    #[inline]
+    pub fn with_capacity(reader: T, buffer_capacity: usize, line_capacity: usize) -> Self {
         FastaIter {
             reader: reader,
-            buf: Vec::with_capacity(8000),
-            line: Vec::with_capacity(8000)
+            buf: Vec::with_capacity(buffer_capacity),
+            line: Vec::with_capacity(line_capacity)
         }
     }
 }
@@ -38,10 +46,25 @@ impl<T: BufRead> Iterator for FastaIter<T> {
     type Item = Result<Bytes>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        bytes_next_skip_whitespace(b">", &mut self.reader, &mut self.buf, &mut self.line)
+        bytes_next_skip_whitespace(b">", Some(FASTA_COMMENT), &mut self.reader, &mut self.buf, &mut self.line)
     }
 }
 
+// COUNT
+
+/// Count the FASTA records in a document, without parsing the records.
+///
+/// Much cheaper than loading the records, to pre-allocate collections
+/// or to report progress totals for large documents.
+pub fn count_records_fasta<T: BufRead>(reader: T) -> Result<usize> {
+    let mut count = 0;
+    for result in FastaIter::new(reader) {
+        result?;
+        count += 1;
+    }
+    Ok(count)
+}
+
 // SIZE
 
 /// Estimate the size of a FASTA record.
@@ -133,14 +156,52 @@ pub fn write_trembl_header<T: Write>(record: &Record, writer: &mut T)
     Ok(())
 }
 
+/// Options to format FASTA records on export.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FastaWriterOptions {
+    /// Number of residues per sequence line, or `None` to write
+    /// the sequence on a single line.
+    pub line_length: Option<usize>,
+}
+
+impl FastaWriterOptions {
+    /// Create options to write the sequence on a single line.
+    #[inline]
+    pub fn unwrapped() -> Self {
+        FastaWriterOptions { line_length: None }
+    }
+
+    /// Create options to wrap the sequence at `line_length` residues.
+    #[inline]
+    pub fn wrapped(line_length: usize) -> Self {
+        FastaWriterOptions { line_length: Some(line_length) }
+    }
+}
+
+impl Default for FastaWriterOptions {
+    /// Wrap sequences at 60 residues, like UniProt.
+    #[inline]
+    fn default() -> Self {
+        FastaWriterOptions::wrapped(60)
+    }
+}
+
 #[inline(always)]
 fn to_fasta<'a, T: Write>(writer: &mut T, record: &'a Record) -> Result<()> {
     record_to_fasta(writer, record)
 }
 
 /// Export record to FASTA.
+#[inline]
 pub fn record_to_fasta<T: Write>(writer: &mut T, record: &Record)
     -> Result<()>
+{
+    record_to_fasta_with_options(writer, record, &FastaWriterOptions::default())
+}
+
+/// Export record to FASTA, with custom formatting options.
+pub fn record_to_fasta_with_options<T: Write>(writer: &mut T, record: &Record, options: &FastaWriterOptions)
+    -> Result<()>
 {
     // Write header
     if record.reviewed {
@@ -149,15 +210,23 @@ pub fn record_to_fasta<T: Write>(writer: &mut T, record: &Record)
         write_trembl_header(record, writer)?;
     }
 
-    // Write SwissProt sequence, formatted at 60 characters.
-    // Write the initial, 60 character lines
-    const SEQUENCE_LINE_LENGTH: usize = 60;
+    write_sequence(writer, record, options)
+}
+
+/// Export the sequence lines of a record to FASTA, following the header.
+pub(crate) fn write_sequence<T: Write>(writer: &mut T, record: &Record, options: &FastaWriterOptions)
+    -> Result<()>
+{
+    // Write the initial, full-length lines. A line length of 0
+    // cannot wrap the sequence, so treat it as unwrapped.
     let mut bytes = record.sequence.as_slice();
-    while bytes.len() > SEQUENCE_LINE_LENGTH {
-        let prefix = &bytes[0..SEQUENCE_LINE_LENGTH];
-        bytes = &bytes[SEQUENCE_LINE_LENGTH..];
-        writer.write_all(b"\n")?;
-        writer.write_all(prefix)?;
+    if let Some(line_length) = options.line_length {
+        while line_length > 0 && bytes.len() > line_length {
+            let prefix = &bytes[0..line_length];
+            bytes = &bytes[line_length..];
+            writer.write_all(b"\n")?;
+            writer.write_all(prefix)?;
+        }
     }
 
     // Write the remaining sequence line, if any remainder exists.
@@ -173,7 +242,7 @@ pub fn record_to_fasta<T: Write>(writer: &mut T, record: &Record)
 
 #[inline(always)]
 fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
-    -> Result<TextWriterState<T>>
+    -> Result<TextWriterState<'_, T>>
 {
     Ok(TextWriterState::new(writer, delimiter))
 }
@@ -257,6 +326,80 @@ pub fn value_iterator_to_fasta_lenient<Iter, T>(writer: &mut T, iter: Iter)
     value_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
 }
 
+// WRITER -- OPTIONS
+
+/// Default exporter from a non-owning iterator to FASTA, with custom formatting options.
+pub fn reference_iterator_to_fasta_with_options<'a, Iter, T>(writer: &mut T, iter: Iter, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &'a Record| {
+        w.export(r, &|w: &mut T, r: &'a Record| record_to_fasta_with_options(w, r, options))
+    };
+    reference_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to FASTA, with custom formatting options.
+pub fn value_iterator_to_fasta_with_options<Iter, T>(writer: &mut T, iter: Iter, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &Record| {
+        w.export(r, &|w: &mut T, r: &Record| record_to_fasta_with_options(w, r, options))
+    };
+    value_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from a non-owning iterator to FASTA, with custom formatting options.
+pub fn reference_iterator_to_fasta_strict_with_options<'a, Iter, T>(writer: &mut T, iter: Iter, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &'a Record| {
+        w.export(r, &|w: &mut T, r: &'a Record| record_to_fasta_with_options(w, r, options))
+    };
+    reference_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to FASTA, with custom formatting options.
+pub fn value_iterator_to_fasta_strict_with_options<Iter, T>(writer: &mut T, iter: Iter, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &Record| {
+        w.export(r, &|w: &mut T, r: &Record| record_to_fasta_with_options(w, r, options))
+    };
+    value_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from a non-owning iterator to FASTA, with custom formatting options.
+pub fn reference_iterator_to_fasta_lenient_with_options<'a, Iter, T>(writer: &mut T, iter: Iter, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &'a Record| {
+        w.export(r, &|w: &mut T, r: &'a Record| record_to_fasta_with_options(w, r, options))
+    };
+    reference_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to FASTA, with custom formatting options.
+pub fn value_iterator_to_fasta_lenient_with_options<Iter, T>(writer: &mut T, iter: Iter, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &Record| {
+        w.export(r, &|w: &mut T, r: &Record| record_to_fasta_with_options(w, r, options))
+    };
+    value_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
 // READER
 
 /// Import record from SwissProt FASTA.
@@ -287,6 +430,9 @@ fn record_header_from_swissprot(header: &str) -> Result<Record> {
         // unused fields in header
         proteome: String::new(),
         sequence: vec![],
+        xrefs: vec![],
+        keywords: vec![],
+        features: vec![],
     })
 }
 
@@ -318,9 +464,24 @@ fn record_header_from_trembl(header: &str) -> Result<Record> {
         // unused fields in header
         proteome: String::new(),
         sequence: vec![],
+        xrefs: vec![],
+        keywords: vec![],
+        features: vec![],
     })
 }
 
+/// Import record from a SwissProt or TrEMBL FASTA header.
+fn record_header_from_fasta(header: &str) -> Result<Record> {
+    // Ensure we don't raise an out-of-bounds error on the subsequent slice.
+    bool_to_error!(header.len() >= 3, InvalidInput);
+
+    match &header[..3] {
+        ">sp"   => record_header_from_swissprot(header),
+        ">tr"   => record_header_from_trembl(header),
+        _       => Err(From::from(ErrorKind::InvalidFastaFormat)),
+    }
+}
+
 /// Import record from FASTA.
 pub fn record_from_fasta<T: BufRead>(reader: &mut T)
     -> Result<Record>
@@ -330,19 +491,21 @@ pub fn record_from_fasta<T: BufRead>(reader: &mut T)
     // Short-circuit if the header is `None`.
     let mut lines = reader.lines();
     let header = none_to_error!(lines.next(), InvalidInput)?;
+    let mut record = record_header_from_fasta(strip_bom(header.trim_end()))?;
+    read_sequence(lines, &mut record)?;
 
-    // Ensure we don't raise an out-of-bounds error on the subsequent slice.
-    bool_to_error!(header.len() >= 3, InvalidInput);
-
-    let mut record = match &header[..3] {
-        ">sp"   => record_header_from_swissprot(&header)?,
-        ">tr"   => record_header_from_trembl(&header)?,
-        _       => return Err(From::from(ErrorKind::InvalidFastaFormat)),
-    };
+    Ok(record)
+}
 
+/// Import the sequence lines of a record from FASTA, following the header.
+///
+/// Calculates the protein length and mass from the sequence.
+pub(crate) fn read_sequence<T: BufRead>(lines: Lines<T>, record: &mut Record)
+    -> Result<()>
+{
     // add sequence data to the FASTA sequence
     for line in lines {
-        record.sequence.append(&mut line?.into_bytes());
+        record.sequence.extend_from_slice(line?.trim_end().as_bytes());
     }
 
     // calculate the protein length and mass
@@ -352,7 +515,7 @@ pub fn record_from_fasta<T: BufRead>(reader: &mut T)
         record.mass = mass.round() as u64;
     }
 
-    Ok(record)
+    Ok(())
 }
 
 // READER -- DEFAULT
@@ -361,15 +524,25 @@ pub fn record_from_fasta<T: BufRead>(reader: &mut T)
 ///
 /// Wraps `FastaIter` and converts the text to records.
 pub struct FastaRecordIter<T: BufRead> {
-    iter: FastaIter<T>
+    iter: FastaIter<T>,
+    encoding: Encoding,
+    alphabet: Option<Alphabet>,
 }
 
 impl<T: BufRead> FastaRecordIter<T> {
     /// Create new FastaRecordIter from a buffered reader.
     #[inline]
     pub fn new(reader: T) -> Self {
+        FastaRecordIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new FastaRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
         FastaRecordIter {
-            iter: FastaIter::new(reader)
+            iter: FastaIter::with_capacity(reader, options.buffer_capacity, options.line_capacity),
+            encoding: options.encoding,
+            alphabet: options.alphabet,
         }
     }
 }
@@ -383,7 +556,13 @@ impl<T: BufRead> Iterator for FastaRecordIter<T> {
             Ok(bytes) => bytes,
         };
 
-        Some(Record::from_fasta_bytes(bytes.as_slice()))
+        let bytes = self.encoding.decode_bytes(bytes);
+        let result = Record::from_fasta_bytes(bytes.as_slice()).and_then(|mut r| {
+            check_protein_sequence(self.alphabet, &mut r.sequence)?;
+            Ok(r)
+        });
+        log_parsed!("FASTA", &result);
+        Some(result)
     }
 }
 
@@ -393,6 +572,129 @@ pub fn iterator_from_fasta<T: BufRead>(reader: T) -> FastaRecordIter<T> {
     FastaRecordIter::new(reader)
 }
 
+/// Create default record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fasta_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaRecordIter<T> {
+    FastaRecordIter::with_options(reader, options)
+}
+
+// READER -- HEADERS
+
+/// Iterator to lazily load `Record`s from the FASTA headers of a document.
+///
+/// Only parses the headers, skipping the sequence lines while tracking
+/// the sequence length. The records have an empty sequence and no mass,
+/// greatly reducing allocations for inventory tasks on large documents.
+pub struct FastaHeaderIter<T: BufRead> {
+    reader: T,
+    line: Bytes,
+    header: Option<String>,
+    encoding: Encoding,
+}
+
+impl<T: BufRead> FastaHeaderIter<T> {
+    /// Create new FastaHeaderIter from a buffered reader.
+    #[inline]
+    pub fn new(reader: T) -> Self {
+        FastaHeaderIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new FastaHeaderIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
+        FastaHeaderIter {
+            reader: reader,
+            line: Vec::with_capacity(options.line_capacity),
+            header: None,
+            encoding: options.encoding,
+        }
+    }
+
+    /// Decode the current line as a header.
+    #[inline]
+    fn decode_header(&self) -> Result<String> {
+        Ok(self.encoding.decode_str(&self.line)?.into_owned())
+    }
+
+    /// Read the next line into the buffer, without the line ending.
+    ///
+    /// Returns false at the end of the document.
+    fn read_line(&mut self) -> Result<bool> {
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        normalize_line(&mut self.line);
+        if self.line.last() == Some(&b'\n') {
+            self.line.pop();
+        }
+        Ok(true)
+    }
+
+    /// Find the next header line, skipping any leading whitespace lines.
+    fn next_header(&mut self) -> Result<Option<String>> {
+        if self.header.is_some() {
+            return Ok(self.header.take());
+        }
+        while self.read_line()? {
+            if self.line.starts_with(b">") {
+                return Ok(Some(self.decode_header()?));
+            } else if !self.line.is_empty() {
+                return Err(From::from(ErrorKind::InvalidFastaFormat));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Skip the sequence lines for the current record, returning the length.
+    fn skip_sequence(&mut self) -> Result<usize> {
+        let mut length = 0;
+        while self.read_line()? {
+            if self.line.starts_with(b">") {
+                self.header = Some(self.decode_header()?);
+                break;
+            }
+            length += self.line.len();
+        }
+        Ok(length)
+    }
+}
+
+impl<T: BufRead> Iterator for FastaHeaderIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = match self.next_header() {
+            Err(e)          => return Some(Err(e)),
+            Ok(None)        => return None,
+            Ok(Some(line))  => line,
+        };
+        let length = match self.skip_sequence() {
+            Err(e)  => return Some(Err(e)),
+            Ok(v)   => v,
+        };
+
+        let result = record_header_from_fasta(&header).map(|mut record| {
+            record.length = length as u32;
+            record
+        });
+        log_parsed!("FASTA header", &result);
+        Some(result)
+    }
+}
+
+/// Create header-only record iterator from reader.
+#[inline(always)]
+pub fn header_iterator_from_fasta<T: BufRead>(reader: T) -> FastaHeaderIter<T> {
+    FastaHeaderIter::new(reader)
+}
+
+/// Create header-only record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn header_iterator_from_fasta_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaHeaderIter<T> {
+    FastaHeaderIter::with_options(reader, options)
+}
+
 // READER -- STRICT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -406,6 +708,12 @@ pub fn iterator_from_fasta_strict<T: BufRead>(reader: T) -> FastaRecordStrictIte
     FastaRecordStrictIter::new(iterator_from_fasta(reader))
 }
 
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fasta_strict_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaRecordStrictIter<T> {
+    FastaRecordStrictIter::new(iterator_from_fasta_with_options(reader, options))
+}
+
 // READER -- LENIENT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -419,6 +727,100 @@ pub fn iterator_from_fasta_lenient<T: BufRead>(reader: T) -> FastaRecordLenientI
     FastaRecordLenientIter::new(iterator_from_fasta(reader))
 }
 
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_fasta_lenient_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> FastaRecordLenientIter<T> {
+    FastaRecordLenientIter::new(iterator_from_fasta_with_options(reader, options))
+}
+
+// READER -- MMAP
+
+/// Iterator to lazily load `Record`s from a memory-mapped document.
+///
+/// Parses the records from slices of the mapped memory, only copying
+/// records which require line normalization or text decoding.
+#[cfg(feature = "mmap")]
+pub struct FastaMmapRecordIter<'a> {
+    iter: MmapBlockIter<'a>,
+    encoding: Encoding,
+    alphabet: Option<Alphabet>,
+}
+
+#[cfg(feature = "mmap")]
+impl<'a> FastaMmapRecordIter<'a> {
+    /// Create new FastaMmapRecordIter from mapped memory.
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        FastaMmapRecordIter::with_options(data, &ReaderOptions::default())
+    }
+
+    /// Create new FastaMmapRecordIter from mapped memory and reader options.
+    #[inline]
+    pub fn with_options(data: &'a [u8], options: &ReaderOptions) -> Self {
+        FastaMmapRecordIter {
+            iter: MmapBlockIter::new(skip_metadata(data, FASTA_COMMENT), b">"),
+            encoding: options.encoding,
+            alphabet: options.alphabet,
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<'a> Iterator for FastaMmapRecordIter<'a> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut block = normalize_block(self.iter.next()?, None);
+        while !block.starts_with(b">") {
+            // Skip the text before the first record.
+            block = normalize_block(self.iter.next()?, None);
+        }
+        let bytes = self.encoding.decode_cow(block);
+        let result = Record::from_fasta_bytes(&bytes).and_then(|mut r| {
+            check_protein_sequence(self.alphabet, &mut r.sequence)?;
+            Ok(r)
+        });
+        log_parsed!("FASTA", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from mapped memory.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_fasta_mmap<'a>(data: &'a [u8]) -> FastaMmapRecordIter<'a> {
+    FastaMmapRecordIter::new(data)
+}
+
+/// Create default record iterator from mapped memory, with custom reader options.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_fasta_mmap_with_options<'a>(data: &'a [u8], options: &ReaderOptions) -> FastaMmapRecordIter<'a> {
+    FastaMmapRecordIter::with_options(data, options)
+}
+
+/// Iterator to lazily load `Record`s from a memory-mapped document strictly.
+#[cfg(feature = "mmap")]
+pub type FastaMmapRecordStrictIter<'a> = StrictIter<Record, FastaMmapRecordIter<'a>>;
+
+/// Create strict record iterator from mapped memory.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_fasta_mmap_strict<'a>(data: &'a [u8]) -> FastaMmapRecordStrictIter<'a> {
+    FastaMmapRecordStrictIter::new(iterator_from_fasta_mmap(data))
+}
+
+/// Iterator to lazily load `Record`s from a memory-mapped document leniently.
+#[cfg(feature = "mmap")]
+pub type FastaMmapRecordLenientIter<'a> = LenientIter<Record, FastaMmapRecordIter<'a>>;
+
+/// Create lenient record iterator from mapped memory.
+#[cfg(feature = "mmap")]
+#[inline(always)]
+pub fn iterator_from_fasta_mmap_lenient<'a>(data: &'a [u8]) -> FastaMmapRecordLenientIter<'a> {
+    FastaMmapRecordLenientIter::new(iterator_from_fasta_mmap(data))
+}
+
 // TRAITS
 
 impl Fasta for Record {
@@ -435,6 +837,20 @@ impl Fasta for Record {
     fn from_fasta<T: BufRead>(reader: &mut T) -> Result<Self> {
         record_from_fasta(reader)
     }
+
+    #[inline(always)]
+    fn from_fasta_iter<'a, T: 'a + BufRead>(reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_fasta(reader))
+    }
+
+    #[inline(always)]
+    fn to_fasta_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+    {
+        reference_iterator_to_fasta(writer, iter)
+    }
 }
 
 impl Fasta for RecordList {
@@ -485,7 +901,7 @@ mod tests {
     use std::fs::File;
     use std::io::{BufReader, Cursor};
     use std::path::PathBuf;
-    use test::testdata_dir;
+    use test::{testdata_dir, windows_text};
     use super::*;
     use super::super::test::*;
 
@@ -504,6 +920,44 @@ mod tests {
         assert_eq!(r.unwrap(), Vec::<Bytes>::new());
     }
 
+    #[test]
+    fn header_iterator_from_fasta_test() {
+        let v: Result<RecordList> = header_iterator_from_fasta(Cursor::new(GAPDH_BSA_FASTA)).collect();
+        let v = v.unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].id, "P46406");
+        assert_eq!(v[0].gene, "GAPDH");
+        assert_eq!(v[0].length, 333);
+        assert_eq!(v[1].id, "P02769");
+        assert_eq!(v[1].length, 607);
+        assert!(v.iter().all(|r| r.sequence.is_empty() && r.mass == 0));
+
+        // headers match the fully-parsed records
+        let mut g = gapdh();
+        g.sequence.clear();
+        g.mass = 0;
+        g.proteome = String::new();
+        assert_eq!(v[0], g);
+
+        // leading whitespace and CRLF line endings
+        let text = b"\n>sp|P46406|G3P_RABIT Glyceraldehyde-3-phosphate dehydrogenase OS=Oryctolagus cuniculus OX=9986 GN=GAPDH PE=1 SV=3\r\nMVKV\r\nGVNG\r\n";
+        let v: Result<RecordList> = header_iterator_from_fasta(Cursor::new(&text[..])).collect();
+        assert_eq!(v.unwrap()[0].length, 8);
+
+        // sequence without a header
+        let mut iter = header_iterator_from_fasta(Cursor::new(&b"MVKV\n"[..]));
+        assert!(iter.next().unwrap().is_err());
+        assert!(header_iterator_from_fasta(Cursor::new(b"")).next().is_none());
+    }
+
+    #[test]
+    fn count_records_fasta_test() {
+        assert_eq!(count_records_fasta(Cursor::new(GAPDH_FASTA)).unwrap(), 1);
+        assert_eq!(count_records_fasta(Cursor::new(GAPDH_BSA_FASTA)).unwrap(), 2);
+        assert_eq!(count_records_fasta(Cursor::new(GAPDH_EMPTY_FASTA)).unwrap(), 2);
+        assert_eq!(count_records_fasta(Cursor::new(b"")).unwrap(), 0);
+    }
+
     #[test]
     fn estimate_size_test() {
         let g = gapdh();
@@ -566,6 +1020,162 @@ mod tests {
         assert_eq!(w.into_inner(), GAPDH_BSA_FASTA);
     }
 
+    #[test]
+    fn windows_fasta_test() {
+        let text = windows_text(GAPDH_BSA_FASTA);
+        let expected: RecordList = iterator_from_fasta(Cursor::new(GAPDH_BSA_FASTA)).collect::<Result<_>>().unwrap();
+        let list: RecordList = iterator_from_fasta(Cursor::new(&text)).collect::<Result<_>>().unwrap();
+        assert_eq!(list, expected);
+
+        let list: RecordList = header_iterator_from_fasta(Cursor::new(&text)).collect::<Result<_>>().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].id, "P46406");
+        assert_eq!(list[0].length, 333);
+        assert_eq!(list[1].length, 607);
+
+        let record = record_from_fasta(&mut Cursor::new(windows_text(GAPDH_FASTA))).unwrap();
+        assert_eq!(record, expected[0]);
+    }
+
+    #[test]
+    fn fasta_encoding_test() {
+        let text = String::from_utf8(GAPDH_FASTA.to_vec()).unwrap().replace("OS=Oryctolagus", "OS=Oryctolagus\u{e9}");
+        let text: Vec<u8> = text.chars().map(|c| c as u8).collect();
+
+        let iter = iterator_from_fasta(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = header_iterator_from_fasta(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+
+        let options = ReaderOptions::with_encoding(Encoding::Latin1);
+        let list = iterator_from_fasta_strict_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].organism, "Oryctolagus\u{e9} cuniculus");
+        assert_eq!(list[0].sequence, gapdh().sequence);
+        let list = header_iterator_from_fasta_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].organism, "Oryctolagus\u{e9} cuniculus");
+
+        let options = ReaderOptions::with_encoding(Encoding::Utf8Lossy);
+        let list = iterator_from_fasta_lenient_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].organism, "Oryctolagus\u{FFFD} cuniculus");
+    }
+
+    #[test]
+    fn fasta_alphabet_test() {
+        let text = String::from_utf8(GAPDH_FASTA.to_vec()).unwrap().replace("MVKVGVNGFG", "mvkvgvngfg");
+
+        // Sequences are read unchanged by default.
+        let list = iterator_from_fasta(Cursor::new(&text)).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(&list[0].sequence[..10], b"mvkvgvngfg");
+
+        let options = ReaderOptions::with_alphabet(Alphabet::Strict);
+        let iter = iterator_from_fasta_with_options(Cursor::new(&text), &options);
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+
+        let options = ReaderOptions::with_alphabet(Alphabet::CaseInsensitive);
+        let list = iterator_from_fasta_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].sequence, gapdh().sequence);
+    }
+
+    #[test]
+    fn fasta_capacity_test() {
+        let expected = iterator_from_fasta(Cursor::new(GAPDH_BSA_FASTA)).collect::<Result<RecordList>>().unwrap();
+        for &(buffer, line) in [(0, 0), (1, 1), (1 << 20, 1 << 16)].iter() {
+            let options = ReaderOptions::with_capacity(buffer, line);
+            let list = iterator_from_fasta_with_options(Cursor::new(GAPDH_BSA_FASTA), &options).collect::<Result<RecordList>>().unwrap();
+            assert_eq!(list, expected);
+
+            let count = header_iterator_from_fasta_with_options(Cursor::new(GAPDH_BSA_FASTA), &options).count();
+            assert_eq!(count, 2);
+        }
+    }
+
+    #[test]
+    fn fasta_path_test() {
+        let path = ::std::env::temp_dir().join("bdb_fasta_path_test.fasta");
+        let expected = RecordList::from_fasta_bytes(GAPDH_BSA_FASTA).unwrap();
+        expected.to_fasta_path(&path).unwrap();
+        assert_eq!(RecordList::from_fasta_path(&path).unwrap(), expected);
+        ::std::fs::remove_file(&path).unwrap();
+
+        let err = RecordList::from_fasta_path(&path).err().unwrap();
+        assert_eq!(err.path(), Some(path.as_path()));
+        assert!(err.to_string().contains("bdb_fasta_path_test.fasta"));
+    }
+
+    #[test]
+    fn fasta_writer_options_test() {
+        let v = vec![gapdh(), bsa()];
+
+        // default options match the UniProt 60 character lines
+        let mut w = Cursor::new(vec![]);
+        let options = FastaWriterOptions::default();
+        reference_iterator_to_fasta_with_options(&mut w, v.iter(), &options).unwrap();
+        assert_eq!(w.into_inner(), GAPDH_BSA_FASTA);
+
+        // no wrapping
+        let mut w = Cursor::new(vec![]);
+        record_to_fasta_with_options(&mut w, &gapdh(), &FastaWriterOptions::unwrapped()).unwrap();
+        let text = String::from_utf8(w.into_inner()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].as_bytes(), gapdh().sequence.as_slice());
+
+        // custom wrapping
+        let mut w = Cursor::new(vec![]);
+        let options = FastaWriterOptions::wrapped(100);
+        value_iterator_to_fasta_strict_with_options(&mut w, iterator_by_value!(v.iter()), &options).unwrap();
+        let text = String::from_utf8(w.into_inner()).unwrap();
+        let lengths: Vec<usize> = text.lines().filter(|l| !l.starts_with('>')).map(|l| l.len()).collect();
+        assert_eq!(lengths, vec![100, 100, 100, 33, 100, 100, 100, 100, 100, 100, 7]);
+
+        // wrapped files round-trip
+        let list = iterator_from_fasta(Cursor::new(text)).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].sequence, v[0].sequence);
+        assert_eq!(list[1].sequence, v[1].sequence);
+    }
+
+    #[test]
+    fn fasta_metadata_test() {
+        let v = vec![gapdh(), bsa()];
+        let metadata = ExportMetadata::new("uniprot.xml").date("2018-01-01");
+        let mut w = Cursor::new(vec![]);
+        v.to_fasta_with_metadata(&mut w, &metadata).unwrap();
+        let text = w.into_inner();
+        assert!(text.starts_with(b";bdb-metadata: 1\n;tool: bdb "));
+        assert!(text.ends_with(GAPDH_BSA_FASTA));
+
+        // readers skip the metadata block
+        let list = RecordList::from_fasta_bytes(&text).unwrap();
+        assert_eq!(list, RecordList::from_fasta_bytes(GAPDH_BSA_FASTA).unwrap());
+        let list: RecordList = iterator_from_fasta_strict(Cursor::new(&text)).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list.len(), 2);
+        #[cfg(feature = "mmap")] {
+            let list: RecordList = iterator_from_fasta_mmap_strict(&text).collect::<Result<RecordList>>().unwrap();
+            assert_eq!(list.len(), 2);
+        }
+    }
+
+    #[test]
+    fn fasta_trait_iter_test() {
+        let v: RecordList = Record::from_fasta_iter(Cursor::new(GAPDH_BSA_FASTA))
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].sequence, gapdh().sequence);
+        assert_eq!(v[1].sequence, bsa().sequence);
+
+        let mut w = Cursor::new(vec![]);
+        Record::to_fasta_iter(&mut w, v.iter()).unwrap();
+        assert_eq!(w.into_inner(), GAPDH_BSA_FASTA);
+
+        // Collections are lazily read as a single item.
+        let l: Vec<RecordList> = RecordList::from_fasta_iter(Cursor::new(GAPDH_BSA_FASTA))
+            .collect::<Result<Vec<RecordList>>>()
+            .unwrap();
+        assert_eq!(l.len(), 1);
+        assert_eq!(l[0].len(), 2);
+    }
+
     #[test]
     fn iterator_from_fasta_test() {
         // VALID
@@ -620,6 +1230,34 @@ mod tests {
         incomplete_list_eq(&expected2, &v.unwrap());
     }
 
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn iterator_from_fasta_mmap_test() {
+        // VALID
+        let text = GAPDH_BSA_FASTA;
+        let expected = vec![gapdh(), bsa()];
+        let v: Result<RecordList> = iterator_from_fasta_mmap(text).collect();
+        incomplete_list_eq(&expected, &v.unwrap());
+
+        let text = windows_text(GAPDH_BSA_FASTA);
+        let v: Result<RecordList> = iterator_from_fasta_mmap(&text).collect();
+        incomplete_list_eq(&expected, &v.unwrap());
+
+        // Text before the first record is skipped.
+        let text = [&b"Exported proteins\n\n"[..], GAPDH_BSA_FASTA].concat();
+        let v: Result<RecordList> = iterator_from_fasta_mmap(&text).collect();
+        incomplete_list_eq(&expected, &v.unwrap());
+
+        // INVALID
+        let text = GAPDH_EMPTY_FASTA;
+        let v: Result<RecordList> = iterator_from_fasta_mmap(text).collect();
+        assert_eq!(v.unwrap().len(), 2);
+        let v: Result<RecordList> = iterator_from_fasta_mmap_strict(text).collect();
+        assert!(v.is_err());
+        let v: Result<RecordList> = iterator_from_fasta_mmap_lenient(text).collect();
+        incomplete_list_eq(&vec![gapdh()], &v.unwrap());
+    }
+
     fn fasta_dir() -> PathBuf {
         let mut dir = testdata_dir();
         dir.push("uniprot/fasta");
@@ -639,4 +1277,19 @@ mod tests {
             bencher::black_box(item).unwrap();
         }
     }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    #[ignore]
+    fn human_fasta_mmap_test() {
+        let mut path = fasta_dir();
+        path.push("human.fasta");
+        let file = unsafe { MmapFile::open(path).unwrap() };
+        let iter = FastaMmapRecordIter::new(file.as_bytes());
+
+        // do nothing, just check it parses.
+        for item in iter {
+            bencher::black_box(item).unwrap();
+        }
+    }
 }
@@ -0,0 +1,535 @@
+//! Custom FASTA headers, to round-trip non-UniProt databases.
+//!
+//! A `HeaderTemplate` is a format string with field placeholders, such
+//! as `"{gene}|{id} {name}"`, used to write FASTA headers. A
+//! `HeaderPattern` is a regular expression with named capture groups,
+//! such as `"(?P<gene>\S+)\|(?P<id>\S+) (?P<name>.*)"`, used to read
+//! FASTA headers. The placeholder and group names are the field names
+//! of `Record`, excluding the sequence.
+
+use regex::{Captures, Regex};
+use std::io::prelude::*;
+
+use util::*;
+use super::evidence::ProteinEvidence;
+use super::fasta::{read_sequence, write_sequence, FastaIter, FastaWriterOptions};
+use super::record::{Record, RecordField};
+use super::section::Section;
+
+// FIELDS
+
+/// Get the record field from a placeholder or capture group name.
+fn field_from_name(name: &str) -> Result<RecordField> {
+    Ok(match name {
+        "sequence_version"  => RecordField::SequenceVersion,
+        "protein_evidence"  => RecordField::ProteinEvidence,
+        "mass"              => RecordField::Mass,
+        "length"            => RecordField::Length,
+        "gene"              => RecordField::Gene,
+        "id"                => RecordField::Id,
+        "mnemonic"          => RecordField::Mnemonic,
+        "name"              => RecordField::Name,
+        "organism"          => RecordField::Organism,
+        "proteome"          => RecordField::Proteome,
+        "taxonomy"          => RecordField::Taxonomy,
+        "reviewed"          => RecordField::Reviewed,
+        _                   => return Err(From::from(ErrorKind::InvalidInput)),
+    })
+}
+
+/// Get the placeholder or capture group name from the record field.
+fn name_from_field(field: RecordField) -> &'static str {
+    match field {
+        RecordField::SequenceVersion    => "sequence_version",
+        RecordField::ProteinEvidence    => "protein_evidence",
+        RecordField::Mass               => "mass",
+        RecordField::Length             => "length",
+        RecordField::Gene               => "gene",
+        RecordField::Id                 => "id",
+        RecordField::Mnemonic           => "mnemonic",
+        RecordField::Name               => "name",
+        RecordField::Organism           => "organism",
+        RecordField::Proteome           => "proteome",
+        RecordField::Sequence           => "sequence",
+        RecordField::Taxonomy           => "taxonomy",
+        RecordField::Reviewed           => "reviewed",
+    }
+}
+
+/// Export a record field to a header.
+///
+/// The protein evidence is exported as an integer, and the reviewed
+/// status as the database code ("sp" or "tr").
+fn write_field<T: Write>(writer: &mut T, record: &Record, field: RecordField)
+    -> Result<()>
+{
+    match field {
+        RecordField::SequenceVersion    => writer.write_all(&to_bytes(&record.sequence_version)?)?,
+        RecordField::ProteinEvidence    => writer.write_all(&to_bytes(&record.protein_evidence)?)?,
+        RecordField::Mass               => writer.write_all(&to_bytes(&record.mass)?)?,
+        RecordField::Length             => writer.write_all(&to_bytes(&record.length)?)?,
+        RecordField::Gene               => writer.write_all(record.gene.as_bytes())?,
+        RecordField::Id                 => writer.write_all(record.id.as_bytes())?,
+        RecordField::Mnemonic           => writer.write_all(record.mnemonic.as_bytes())?,
+        RecordField::Name               => writer.write_all(record.name.as_bytes())?,
+        RecordField::Organism           => writer.write_all(record.organism.as_bytes())?,
+        RecordField::Proteome           => writer.write_all(record.proteome.as_bytes())?,
+        RecordField::Sequence           => writer.write_all(&record.sequence)?,
+        RecordField::Taxonomy           => writer.write_all(record.taxonomy.as_bytes())?,
+        RecordField::Reviewed           => writer.write_all(Section::from_reviewed(record.reviewed).code().as_bytes())?,
+    }
+    Ok(())
+}
+
+/// Import a record field from a header.
+///
+/// Empty values leave the field unchanged.
+fn read_field(record: &mut Record, field: RecordField, value: &str)
+    -> Result<()>
+{
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    match field {
+        RecordField::SequenceVersion    => record.sequence_version = from_string(value)?,
+        RecordField::ProteinEvidence    => record.protein_evidence = ProteinEvidence::parse(value)?,
+        RecordField::Mass               => record.mass = from_string(value)?,
+        RecordField::Length             => record.length = from_string(value)?,
+        RecordField::Gene               => record.gene = String::from(value),
+        RecordField::Id                 => record.id = String::from(value),
+        RecordField::Mnemonic           => record.mnemonic = String::from(value),
+        RecordField::Name               => record.name = String::from(value),
+        RecordField::Organism           => record.organism = String::from(value),
+        RecordField::Proteome           => record.proteome = String::from(value),
+        RecordField::Sequence           => record.sequence = value.as_bytes().to_vec(),
+        RecordField::Taxonomy           => record.taxonomy = String::from(value),
+        RecordField::Reviewed           => record.reviewed = Section::parse(value)?.is_reviewed(),
+    }
+    Ok(())
+}
+
+// TEMPLATE
+
+/// Component of a parsed header template.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// Literal text.
+    Literal(String),
+    /// Placeholder for a record field.
+    Field(RecordField),
+}
+
+/// Format string to write custom FASTA headers.
+///
+/// Placeholders are record field names enclosed in braces, for example,
+/// `"{id}"`, and literal braces are escaped as `"{{"` and `"}}"`. The
+/// template excludes the leading `'>'` of the header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderTemplate {
+    tokens: Vec<Token>,
+}
+
+impl HeaderTemplate {
+    /// Parse a header template from a format string.
+    ///
+    /// Returns an error for unknown fields, the sequence field, or
+    /// unbalanced braces.
+    pub fn new(template: &str) -> Result<Self> {
+        let mut tokens = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('{') if name.is_empty() => {
+                                literal.push('{');
+                                break;
+                            },
+                            Some('}') => {
+                                if !literal.is_empty() {
+                                    tokens.push(Token::Literal(literal.clone()));
+                                    literal.clear();
+                                }
+                                tokens.push(Token::Field(field_from_name(name.trim())?));
+                                break;
+                            },
+                            Some(c)   => name.push(c),
+                            None      => return Err(From::from(ErrorKind::InvalidInput)),
+                        }
+                    }
+                },
+                '}' => {
+                    bool_to_error!(chars.next() == Some('}'), InvalidInput);
+                    literal.push('}');
+                },
+                _   => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal));
+        }
+
+        Ok(HeaderTemplate { tokens: tokens })
+    }
+
+    /// Get the fields in the template, in order.
+    pub fn fields(&self) -> Vec<RecordField> {
+        self.tokens.iter()
+            .filter_map(|t| match *t {
+                Token::Field(field) => Some(field),
+                _                   => None,
+            })
+            .collect()
+    }
+
+    /// Export the header of a record, including the leading `'>'`.
+    pub fn write<T: Write>(&self, writer: &mut T, record: &Record)
+        -> Result<()>
+    {
+        writer.write_all(b">")?;
+        for token in self.tokens.iter() {
+            match *token {
+                Token::Literal(ref s) => writer.write_all(s.as_bytes())?,
+                Token::Field(field)   => write_field(writer, record, field)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Export the header of a record to string, including the leading `'>'`.
+    pub fn format(&self, record: &Record) -> Result<String> {
+        let mut bytes = vec![];
+        self.write(&mut bytes, record)?;
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    /// Create the pattern to read headers written by the template.
+    ///
+    /// Each field matches any text up to the following literal, so
+    /// adjacent fields without separating text, or literals found
+    /// within field values, may not round-trip.
+    pub fn pattern(&self) -> HeaderPattern {
+        let mut re = String::from(r"\A");
+        for token in self.tokens.iter() {
+            match *token {
+                Token::Literal(ref s) => re.push_str(&::regex::escape(s)),
+                Token::Field(field)   => {
+                    // A field may appear more than once, only capture the first.
+                    let name = name_from_field(field);
+                    if re.contains(&format!("(?P<{}>", name)) {
+                        re.push_str(".*?");
+                    } else {
+                        re.push_str(&format!("(?P<{}>.*?)", name));
+                    }
+                },
+            }
+        }
+        re.push_str(r"\z");
+
+        // Cannot fail, all literals are escaped and all names are valid.
+        HeaderPattern::new(&re).unwrap()
+    }
+}
+
+// PATTERN
+
+/// Regular expression to read custom FASTA headers.
+///
+/// Named capture groups set the record field of the same name, and
+/// unnamed groups are ignored. The pattern is matched against the
+/// header excluding the leading `'>'`.
+#[derive(Clone, Debug)]
+pub struct HeaderPattern {
+    regex: Regex,
+    fields: Vec<(String, RecordField)>,
+}
+
+impl HeaderPattern {
+    /// Compile a header pattern from a regular expression.
+    ///
+    /// Returns an error for invalid regular expressions, or named
+    /// groups that are not record fields.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let regex = match Regex::new(pattern) {
+            Err(_) => return Err(From::from(ErrorKind::InvalidInput)),
+            Ok(v)  => v,
+        };
+        let mut fields = vec![];
+        for name in regex.capture_names() {
+            if let Some(name) = name {
+                let field = field_from_name(name)?;
+                fields.push((String::from(name), field));
+            }
+        }
+
+        Ok(HeaderPattern { regex: regex, fields: fields })
+    }
+
+    /// Import the header fields of a record, including the leading `'>'`.
+    pub fn read(&self, header: &str) -> Result<Record> {
+        bool_to_error!(header.starts_with('>'), InvalidFastaFormat);
+        let captures = none_to_error!(self.regex.captures(&header[1..]), InvalidInput);
+
+        let mut record = Record::new();
+        for &(ref name, field) in self.fields.iter() {
+            read_field(&mut record, field, capture_value(&captures, name))?;
+        }
+        Ok(record)
+    }
+}
+
+/// Get the value of a named capture group, or an empty string.
+#[inline(always)]
+fn capture_value<'t>(captures: &'t Captures, name: &str) -> &'t str {
+    captures.name(name).map_or("", |m| m.as_str())
+}
+
+// WRITER
+
+/// Export record to FASTA, with a custom header template.
+pub fn record_to_fasta_with_template<T: Write>(writer: &mut T, record: &Record, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+{
+    template.write(writer, record)?;
+    write_sequence(writer, record, options)
+}
+
+#[inline(always)]
+fn init_cb<T: Write>(writer: &mut T, delimiter: u8)
+    -> Result<TextWriterState<'_, T>>
+{
+    Ok(TextWriterState::new(writer, delimiter))
+}
+
+#[inline(always)]
+fn dest_cb<T: Write>(_: &mut TextWriterState<T>)
+    -> Result<()>
+{
+    Ok(())
+}
+
+/// Default exporter from a non-owning iterator to FASTA, with a custom header template.
+pub fn reference_iterator_to_fasta_with_template<'a, Iter, T>(writer: &mut T, iter: Iter, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &'a Record| {
+        w.export(r, &|w: &mut T, r: &'a Record| record_to_fasta_with_template(w, r, template, options))
+    };
+    reference_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to FASTA, with a custom header template.
+pub fn value_iterator_to_fasta_with_template<Iter, T>(writer: &mut T, iter: Iter, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &Record| {
+        w.export(r, &|w: &mut T, r: &Record| record_to_fasta_with_template(w, r, template, options))
+    };
+    value_iterator_export(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from a non-owning iterator to FASTA, with a custom header template.
+pub fn reference_iterator_to_fasta_strict_with_template<'a, Iter, T>(writer: &mut T, iter: Iter, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &'a Record| {
+        w.export(r, &|w: &mut T, r: &'a Record| record_to_fasta_with_template(w, r, template, options))
+    };
+    reference_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to FASTA, with a custom header template.
+pub fn value_iterator_to_fasta_strict_with_template<Iter, T>(writer: &mut T, iter: Iter, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &Record| {
+        w.export(r, &|w: &mut T, r: &Record| record_to_fasta_with_template(w, r, template, options))
+    };
+    value_iterator_export_strict(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from a non-owning iterator to FASTA, with a custom header template.
+pub fn reference_iterator_to_fasta_lenient_with_template<'a, Iter, T>(writer: &mut T, iter: Iter, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &'a Record| {
+        w.export(r, &|w: &mut T, r: &'a Record| record_to_fasta_with_template(w, r, template, options))
+    };
+    reference_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to FASTA, with a custom header template.
+pub fn value_iterator_to_fasta_lenient_with_template<Iter, T>(writer: &mut T, iter: Iter, template: &HeaderTemplate, options: &FastaWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    let export_cb = |w: &mut TextWriterState<T>, r: &Record| {
+        w.export(r, &|w: &mut T, r: &Record| record_to_fasta_with_template(w, r, template, options))
+    };
+    value_iterator_export_lenient(writer, iter, b'\n', &init_cb, &export_cb, &dest_cb)
+}
+
+// READER
+
+/// Import record from FASTA, with a custom header pattern.
+pub fn record_from_fasta_with_pattern<T: BufRead>(reader: &mut T, pattern: &HeaderPattern)
+    -> Result<Record>
+{
+    let mut lines = reader.lines();
+    let header = none_to_error!(lines.next(), InvalidInput)?;
+    let mut record = pattern.read(strip_bom(header.trim_end()))?;
+    read_sequence(lines, &mut record)?;
+
+    Ok(record)
+}
+
+// READER -- DEFAULT
+
+/// Iterator to lazily load `Record`s from a document with custom headers.
+///
+/// Wraps `FastaIter` and converts the text to records.
+pub struct FastaPatternRecordIter<T: BufRead> {
+    iter: FastaIter<T>,
+    pattern: HeaderPattern,
+}
+
+impl<T: BufRead> FastaPatternRecordIter<T> {
+    /// Create new FastaPatternRecordIter from a buffered reader and header pattern.
+    #[inline]
+    pub fn new(reader: T, pattern: HeaderPattern) -> Self {
+        FastaPatternRecordIter {
+            iter: FastaIter::new(reader),
+            pattern: pattern,
+        }
+    }
+}
+
+impl<T: BufRead> Iterator for FastaPatternRecordIter<T> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = match self.iter.next()? {
+            Err(e)   => return Some(Err(e)),
+            Ok(bytes) => bytes,
+        };
+
+        let result = record_from_fasta_with_pattern(&mut bytes.as_slice(), &self.pattern);
+        log_parsed!("FASTA", &result);
+        Some(result)
+    }
+}
+
+/// Create default record iterator from reader, with a custom header pattern.
+#[inline(always)]
+pub fn iterator_from_fasta_with_pattern<T: BufRead>(reader: T, pattern: HeaderPattern) -> FastaPatternRecordIter<T> {
+    FastaPatternRecordIter::new(reader, pattern)
+}
+
+// READER -- STRICT
+
+/// Iterator to lazily load `Record`s from a document with custom headers.
+///
+/// Wraps `FastaPatternRecordIter` and converts the text to records strictly.
+pub type FastaPatternRecordStrictIter<T> = StrictIter<Record, FastaPatternRecordIter<T>>;
+
+/// Create strict record iterator from reader, with a custom header pattern.
+#[inline(always)]
+pub fn iterator_from_fasta_strict_with_pattern<T: BufRead>(reader: T, pattern: HeaderPattern) -> FastaPatternRecordStrictIter<T> {
+    FastaPatternRecordStrictIter::new(iterator_from_fasta_with_pattern(reader, pattern))
+}
+
+// READER -- LENIENT
+
+/// Iterator to lazily load `Record`s from a document with custom headers.
+///
+/// Wraps `FastaPatternRecordIter` and converts the text to records leniently.
+pub type FastaPatternRecordLenientIter<T> = LenientIter<Record, FastaPatternRecordIter<T>>;
+
+/// Create lenient record iterator from reader, with a custom header pattern.
+#[inline(always)]
+pub fn iterator_from_fasta_lenient_with_pattern<T: BufRead>(reader: T, pattern: HeaderPattern) -> FastaPatternRecordLenientIter<T> {
+    FastaPatternRecordLenientIter::new(iterator_from_fasta_with_pattern(reader, pattern))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use super::super::record_list::RecordList;
+    use super::super::test::*;
+
+    #[test]
+    fn header_template_test() {
+        let x = HeaderTemplate::new("{gene}|{id} {name} {{PE={protein_evidence}}} {reviewed}").unwrap();
+        assert_eq!(x.fields(), vec![RecordField::Gene, RecordField::Id, RecordField::Name, RecordField::ProteinEvidence, RecordField::Reviewed]);
+        assert_eq!(x.format(&gapdh()).unwrap(), ">GAPDH|P46406 Glyceraldehyde-3-phosphate dehydrogenase {PE=1} sp");
+
+        // Invalid templates.
+        assert!(HeaderTemplate::new("{gene").is_err());
+        assert!(HeaderTemplate::new("gene}").is_err());
+        assert!(HeaderTemplate::new("{genes}").is_err());
+    }
+
+    #[test]
+    fn header_pattern_test() {
+        let x = HeaderPattern::new(r"(?P<id>\S+) (?P<name>.*) \[(?P<organism>[^\]]*)\]").unwrap();
+        let y = x.read(">P46406 Glyceraldehyde-3-phosphate dehydrogenase [Oryctolagus cuniculus]").unwrap();
+        assert_eq!(y.id, "P46406");
+        assert_eq!(y.name, "Glyceraldehyde-3-phosphate dehydrogenase");
+        assert_eq!(y.organism, "Oryctolagus cuniculus");
+        assert!(x.read("P46406 Glyceraldehyde-3-phosphate dehydrogenase [Oryctolagus cuniculus]").is_err());
+        assert!(x.read(">P46406").is_err());
+
+        // Invalid patterns.
+        assert!(HeaderPattern::new(r"(?P<genes>\S+)").is_err());
+        assert!(HeaderPattern::new(r"(?P<sequence>\S+)").is_err());
+        assert!(HeaderPattern::new(r"(\S+").is_err());
+    }
+
+    #[test]
+    fn fasta_template_test() {
+        let template = HeaderTemplate::new("{id}|{mnemonic}|{gene} {name} OS={organism} OX={taxonomy} PE={protein_evidence} SV={sequence_version} {reviewed}").unwrap();
+        let options = FastaWriterOptions::default();
+        let list: RecordList = vec![gapdh(), bsa()];
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_fasta_with_template(&mut w, list.iter(), &template, &options).unwrap();
+        let text = w.into_inner();
+        assert!(text.starts_with(b">P46406|G3P_RABIT|GAPDH Glyceraldehyde-3-phosphate dehydrogenase OS=Oryctolagus cuniculus OX=9986 PE=1 SV=3 sp\nMVKVGVNGFGRIGRLVTRAAFNSGKVDVVAINDPFIDLHYMVYMFQYDSTHGKFHGTVKA\n"));
+
+        // Round-trip with the template pattern.
+        let iter = iterator_from_fasta_strict_with_pattern(Cursor::new(&text), template.pattern());
+        let y: RecordList = iter.collect::<Result<RecordList>>().unwrap();
+        incomplete_list_eq(&list, &y);
+
+        // Reader with a mismatched pattern.
+        let pattern = HeaderPattern::new(r"(?P<id>\w+)").unwrap();
+        let iter = iterator_from_fasta_strict_with_pattern(Cursor::new(&text), pattern.clone());
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+        let iter = iterator_from_fasta_lenient_with_pattern(Cursor::new(&text), pattern);
+        assert_eq!(iter.collect::<Result<RecordList>>().unwrap().len(), 0);
+
+        // Writer errors for invalid records.
+        let mut w = Cursor::new(vec![]);
+        let list: RecordList = vec![gapdh(), Record::new()];
+        assert!(reference_iterator_to_fasta_strict_with_template(&mut w, list.iter(), &template, &options).is_err());
+    }
+}
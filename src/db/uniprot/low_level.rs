@@ -10,12 +10,19 @@
 //! typically < 16 KB required for internal buffers, and < 1 KB for each
 //! individual item.
 
+// Re-export the strict and lenient iterator wrappers, to name the
+// strict and lenient reader types.
+pub use util::iterator::{LenientIter, StrictIter};
+
 #[cfg(feature = "csv")]
 pub use super::csv::*;
 
 #[cfg(feature = "fasta")]
 pub use super::fasta::*;
 
+#[cfg(feature = "fasta")]
+pub use super::header::*;
+
 #[cfg(feature = "xml")]
 pub use super::xml::*;
 
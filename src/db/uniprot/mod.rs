@@ -4,17 +4,26 @@
 pub mod low_level;
 
 // Expose the client API in a public submodule.
-// Requires the CSV feature to function.
-#[cfg(all(feature = "csv", feature = "http"))]
+// Requires a CSV, FASTA or XML feature to function.
+#[cfg(all(feature = "http", any(feature = "csv", feature = "fasta", feature = "xml")))]
 pub mod client;
 
+pub(crate) mod annotation;
 pub(crate) mod complete;
 pub(crate) mod evidence;
 pub(crate) mod re;
 pub(crate) mod record;
 pub(crate) mod record_list;
 pub(crate) mod section;
+pub(crate) mod sites;
 pub(crate) mod valid;
+pub(crate) mod xref;
+
+#[cfg(feature = "columnar")]
+pub(crate) mod columnar;
+
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
 
 #[cfg(feature = "csv")]
 pub(crate) mod csv;
@@ -22,6 +31,9 @@ pub(crate) mod csv;
 #[cfg(feature = "fasta")]
 pub(crate) mod fasta;
 
+#[cfg(feature = "fasta")]
+pub(crate) mod header;
+
 #[cfg(feature = "xml")]
 pub(crate) mod xml;
 
@@ -29,7 +41,22 @@ pub(crate) mod xml;
 pub(crate) mod test;
 
 // Re-export the models into the parent module.
+pub use self::annotation::{Feature, Keyword};
 pub use self::evidence::ProteinEvidence;
 pub use self::record::{Record, RecordField};
-pub use self::record_list::RecordList;
+pub use self::record_list::{RecordClusters, RecordList, RecordListExt};
 pub use self::section::Section;
+pub use self::sites::{enumerate_sites, enumerate_sites_iter, Site, SiteKind, SiteOptions};
+pub use self::xref::DbXref;
+
+#[cfg(feature = "csv")]
+pub use self::csv::AnnotationTable;
+
+#[cfg(feature = "csv")]
+pub use self::sites::sites_to_csv;
+
+#[cfg(feature = "fasta")]
+pub use self::fasta::FastaWriterOptions;
+
+#[cfg(feature = "fasta")]
+pub use self::header::{HeaderPattern, HeaderTemplate};
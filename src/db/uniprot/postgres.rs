@@ -0,0 +1,80 @@
+//! Helper utilities for UniProt PostgreSQL COPY export.
+//!
+//! Exports the scalar fields of each record, one column per field.
+//! The cross-references, keywords and features are not exported.
+
+use std::borrow::Cow;
+
+use traits::{PgColumn, PgCopy, PgType, PgValue};
+use super::record::Record;
+
+impl PgCopy for Record {
+    fn pg_columns() -> Vec<PgColumn> {
+        vec![
+            PgColumn::new("sequence_version", PgType::Smallint),
+            PgColumn::new("protein_evidence", PgType::Text),
+            PgColumn::new("mass", PgType::Bigint),
+            PgColumn::new("length", PgType::Bigint),
+            PgColumn::new("gene", PgType::Text),
+            PgColumn::new("id", PgType::Text),
+            PgColumn::new("mnemonic", PgType::Text),
+            PgColumn::new("name", PgType::Text),
+            PgColumn::new("organism", PgType::Text),
+            PgColumn::new("proteome", PgType::Text),
+            PgColumn::new("sequence", PgType::Text),
+            PgColumn::new("taxonomy", PgType::Text),
+            PgColumn::new("reviewed", PgType::Boolean),
+        ]
+    }
+
+    fn pg_values<'a>(&'a self) -> Vec<PgValue<'a>> {
+        vec![
+            PgValue::Smallint(self.sequence_version as i16),
+            PgValue::Text(Cow::from(self.protein_evidence.verbose())),
+            PgValue::Bigint(self.mass as i64),
+            PgValue::Bigint(self.length as i64),
+            PgValue::Text(Cow::from(self.gene.as_str())),
+            PgValue::Text(Cow::from(self.id.as_str())),
+            PgValue::Text(Cow::from(self.mnemonic.as_str())),
+            PgValue::Text(Cow::from(self.name.as_str())),
+            PgValue::Text(Cow::from(self.organism.as_str())),
+            PgValue::Text(Cow::from(self.proteome.as_str())),
+            PgValue::Text(String::from_utf8_lossy(&self.sequence)),
+            PgValue::Text(Cow::from(self.taxonomy.as_str())),
+            PgValue::Boolean(self.reviewed),
+        ]
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test::*;
+
+    #[test]
+    fn pg_copy_test() {
+        let ddl = Record::pg_create_table("uniprot");
+        assert!(ddl.starts_with("CREATE TABLE uniprot (\n    sequence_version smallint NOT NULL,\n"));
+        assert!(ddl.ends_with("    reviewed boolean NOT NULL\n);\n"));
+
+        let v = vec![gapdh(), bsa()];
+        let mut w = vec![];
+        Record::to_pg_text_iter(&mut w, v.iter()).unwrap();
+        let text = String::from_utf8(w).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let fields: Vec<&str> = lines[0].split('\t').collect();
+        assert_eq!(fields.len(), 13);
+        assert_eq!(&fields[2..6], &["35780", "333", "GAPDH", "P46406"]);
+        assert_eq!(fields[12], "t");
+
+        let mut w = vec![];
+        Record::to_pg_binary_iter(&mut w, v.iter()).unwrap();
+        assert!(w.starts_with(b"PGCOPY\n\xff\r\n\0"));
+        assert_eq!(&w[19..21], &[0, 13]);
+        assert!(w.ends_with(b"\xff\xff"));
+    }
+}
@@ -47,6 +47,25 @@ impl ExtractionRegex<Regex> for AccessionRegex {
     }
 }
 
+/// Extract the accession number from a sequence identifier.
+///
+/// Accepts UniProt FASTA identifiers ("sp|P46406|G3P_RABIT") and bare
+/// accession numbers.
+pub(crate) fn accession_from_identifier(identifier: &str) -> Option<&str> {
+    let mut split = identifier.split('|');
+    let accession = match (split.next(), split.next()) {
+        (Some("sp"), Some(accession)) => accession,
+        (Some("tr"), Some(accession)) => accession,
+        _                             => identifier,
+    };
+
+    if AccessionRegex::validate().is_match(accession) {
+        Some(accession)
+    } else {
+        None
+    }
+}
+
 // MNEMONIC
 
 /// Regular expression to validate mnemonic identifiers.
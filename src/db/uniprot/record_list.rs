@@ -1,10 +1,115 @@
 //! Model for UniProt protein collections.
 
+use std::collections::HashMap;
+
+use bio::cluster::{cluster_sequences, ClusterOptions};
+use bio::distance::{DistanceMatrix, DistanceMethod};
 use super::record::Record;
 
 /// UniProt record collection type.
 pub type RecordList = Vec<Record>;
 
+/// Clusters of UniProt records with similar sequences.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordClusters<'a> {
+    /// Representative record of each cluster, longest first.
+    pub representatives: Vec<&'a Record>,
+    /// Map from the accession number of each record to the accession
+    /// number of its representative.
+    pub membership: HashMap<&'a str, &'a str>,
+}
+
+/// Lookup maps and pairwise distances for UniProt record collections.
+///
+/// Build a map once to replace repeated linear scans over a collection.
+/// Records with empty keys are not indexed.
+pub trait RecordListExt {
+    /// Create a map from accession number to record.
+    ///
+    /// If multiple records share an accession, the first is kept.
+    fn index_by_id(&self) -> HashMap<&str, &Record>;
+
+    /// Create a map from gene name to all records for the gene.
+    fn index_by_gene(&self) -> HashMap<&str, Vec<&Record>>;
+
+    /// Create a map from mnemonic (entry name) to record.
+    ///
+    /// If multiple records share a mnemonic, the first is kept.
+    fn index_by_mnemonic(&self) -> HashMap<&str, &Record>;
+
+    /// Create a map from cross-reference identifier in a database to
+    /// all records linked to the identifier.
+    fn index_by_xref(&self, db: &str) -> HashMap<&str, Vec<&Record>>;
+
+    /// Calculate the pairwise distances between the record sequences.
+    ///
+    /// The matrix is labeled by accession number, for example, to
+    /// build a tree with `bio::phylo::neighbor_joining`.
+    fn distance_matrix(&self, method: DistanceMethod) -> DistanceMatrix;
+
+    /// Cluster the records by sequence identity, to remove redundant
+    /// sequences, for example, before building a search database.
+    fn cluster_by_identity(&self, options: &ClusterOptions) -> RecordClusters<'_>;
+}
+
+impl RecordListExt for [Record] {
+    fn index_by_id(&self) -> HashMap<&str, &Record> {
+        let mut map = HashMap::with_capacity(self.len());
+        for record in self.iter().filter(|r| !r.id.is_empty()) {
+            map.entry(record.id.as_str()).or_insert(record);
+        }
+        map
+    }
+
+    fn index_by_gene(&self) -> HashMap<&str, Vec<&Record>> {
+        let mut map: HashMap<&str, Vec<&Record>> = HashMap::with_capacity(self.len());
+        for record in self.iter().filter(|r| !r.gene.is_empty()) {
+            map.entry(record.gene.as_str()).or_insert_with(Vec::new).push(record);
+        }
+        map
+    }
+
+    fn index_by_mnemonic(&self) -> HashMap<&str, &Record> {
+        let mut map = HashMap::with_capacity(self.len());
+        for record in self.iter().filter(|r| !r.mnemonic.is_empty()) {
+            map.entry(record.mnemonic.as_str()).or_insert(record);
+        }
+        map
+    }
+
+    fn index_by_xref(&self, db: &str) -> HashMap<&str, Vec<&Record>> {
+        let mut map: HashMap<&str, Vec<&Record>> = HashMap::new();
+        for record in self.iter() {
+            for xref in record.xrefs.iter().filter(|x| x.db == db && !x.id.is_empty()) {
+                map.entry(xref.id.as_str()).or_insert_with(Vec::new).push(record);
+            }
+        }
+        map
+    }
+
+    fn distance_matrix(&self, method: DistanceMethod) -> DistanceMatrix {
+        let sequences = self.iter().map(|r| (r.id.as_str(), r.sequence.as_slice()));
+        DistanceMatrix::from_sequences(sequences, method)
+    }
+
+    fn cluster_by_identity(&self, options: &ClusterOptions) -> RecordClusters<'_> {
+        let sequences: Vec<&[u8]> = self.iter().map(|r| r.sequence.as_slice()).collect();
+        let clusters = cluster_sequences(&sequences, options);
+        let mut membership = HashMap::with_capacity(self.len());
+        for cluster in clusters.iter() {
+            let representative = self[cluster.representative].id.as_str();
+            for &member in cluster.members.iter() {
+                membership.insert(self[member].id.as_str(), representative);
+            }
+        }
+
+        RecordClusters {
+            representatives: clusters.iter().map(|c| &self[c.representative]).collect(),
+            membership: membership,
+        }
+    }
+}
+
 // TESTS
 // -----
 
@@ -13,18 +118,86 @@ mod tests {
     use std::fs::File;
     use std::io::{BufReader, Cursor};
     use std::path::PathBuf;
+    use bio::phylo::neighbor_joining;
     use test::testdata_dir;
     use traits::*;
     use util::Bytes;
     use super::*;
     use super::super::test::*;
+    use super::super::xref::DbXref;
 
     // LIST
 
     #[test]
     fn debug_list_test() {
         let l = format!("{:?}", vec![gapdh(), bsa()]);
-        assert_eq!(l, "[Record { sequence_version: 3, protein_evidence: ProteinLevel, mass: 35780, length: 333, gene: \"GAPDH\", id: \"P46406\", mnemonic: \"G3P_RABIT\", name: \"Glyceraldehyde-3-phosphate dehydrogenase\", organism: \"Oryctolagus cuniculus\", proteome: \"UP000001811\", sequence: [77, 86, 75, 86, 71, 86, 78, 71, 70, 71, 82, 73, 71, 82, 76, 86, 84, 82, 65, 65, 70, 78, 83, 71, 75, 86, 68, 86, 86, 65, 73, 78, 68, 80, 70, 73, 68, 76, 72, 89, 77, 86, 89, 77, 70, 81, 89, 68, 83, 84, 72, 71, 75, 70, 72, 71, 84, 86, 75, 65, 69, 78, 71, 75, 76, 86, 73, 78, 71, 75, 65, 73, 84, 73, 70, 81, 69, 82, 68, 80, 65, 78, 73, 75, 87, 71, 68, 65, 71, 65, 69, 89, 86, 86, 69, 83, 84, 71, 86, 70, 84, 84, 77, 69, 75, 65, 71, 65, 72, 76, 75, 71, 71, 65, 75, 82, 86, 73, 73, 83, 65, 80, 83, 65, 68, 65, 80, 77, 70, 86, 77, 71, 86, 78, 72, 69, 75, 89, 68, 78, 83, 76, 75, 73, 86, 83, 78, 65, 83, 67, 84, 84, 78, 67, 76, 65, 80, 76, 65, 75, 86, 73, 72, 68, 72, 70, 71, 73, 86, 69, 71, 76, 77, 84, 84, 86, 72, 65, 73, 84, 65, 84, 81, 75, 84, 86, 68, 71, 80, 83, 71, 75, 76, 87, 82, 68, 71, 82, 71, 65, 65, 81, 78, 73, 73, 80, 65, 83, 84, 71, 65, 65, 75, 65, 86, 71, 75, 86, 73, 80, 69, 76, 78, 71, 75, 76, 84, 71, 77, 65, 70, 82, 86, 80, 84, 80, 78, 86, 83, 86, 86, 68, 76, 84, 67, 82, 76, 69, 75, 65, 65, 75, 89, 68, 68, 73, 75, 75, 86, 86, 75, 81, 65, 83, 69, 71, 80, 76, 75, 71, 73, 76, 71, 89, 84, 69, 68, 81, 86, 86, 83, 67, 68, 70, 78, 83, 65, 84, 72, 83, 83, 84, 70, 68, 65, 71, 65, 71, 73, 65, 76, 78, 68, 72, 70, 86, 75, 76, 73, 83, 87, 89, 68, 78, 69, 70, 71, 89, 83, 78, 82, 86, 86, 68, 76, 77, 86, 72, 77, 65, 83, 75, 69], taxonomy: \"9986\", reviewed: true }, Record { sequence_version: 4, protein_evidence: ProteinLevel, mass: 69293, length: 607, gene: \"ALB\", id: \"P02769\", mnemonic: \"ALBU_BOVIN\", name: \"Serum albumin\", organism: \"Bos taurus\", proteome: \"UP000009136\", sequence: [77, 75, 87, 86, 84, 70, 73, 83, 76, 76, 76, 76, 70, 83, 83, 65, 89, 83, 82, 71, 86, 70, 82, 82, 68, 84, 72, 75, 83, 69, 73, 65, 72, 82, 70, 75, 68, 76, 71, 69, 69, 72, 70, 75, 71, 76, 86, 76, 73, 65, 70, 83, 81, 89, 76, 81, 81, 67, 80, 70, 68, 69, 72, 86, 75, 76, 86, 78, 69, 76, 84, 69, 70, 65, 75, 84, 67, 86, 65, 68, 69, 83, 72, 65, 71, 67, 69, 75, 83, 76, 72, 84, 76, 70, 71, 68, 69, 76, 67, 75, 86, 65, 83, 76, 82, 69, 84, 89, 71, 68, 77, 65, 68, 67, 67, 69, 75, 81, 69, 80, 69, 82, 78, 69, 67, 70, 76, 83, 72, 75, 68, 68, 83, 80, 68, 76, 80, 75, 76, 75, 80, 68, 80, 78, 84, 76, 67, 68, 69, 70, 75, 65, 68, 69, 75, 75, 70, 87, 71, 75, 89, 76, 89, 69, 73, 65, 82, 82, 72, 80, 89, 70, 89, 65, 80, 69, 76, 76, 89, 89, 65, 78, 75, 89, 78, 71, 86, 70, 81, 69, 67, 67, 81, 65, 69, 68, 75, 71, 65, 67, 76, 76, 80, 75, 73, 69, 84, 77, 82, 69, 75, 86, 76, 65, 83, 83, 65, 82, 81, 82, 76, 82, 67, 65, 83, 73, 81, 75, 70, 71, 69, 82, 65, 76, 75, 65, 87, 83, 86, 65, 82, 76, 83, 81, 75, 70, 80, 75, 65, 69, 70, 86, 69, 86, 84, 75, 76, 86, 84, 68, 76, 84, 75, 86, 72, 75, 69, 67, 67, 72, 71, 68, 76, 76, 69, 67, 65, 68, 68, 82, 65, 68, 76, 65, 75, 89, 73, 67, 68, 78, 81, 68, 84, 73, 83, 83, 75, 76, 75, 69, 67, 67, 68, 75, 80, 76, 76, 69, 75, 83, 72, 67, 73, 65, 69, 86, 69, 75, 68, 65, 73, 80, 69, 78, 76, 80, 80, 76, 84, 65, 68, 70, 65, 69, 68, 75, 68, 86, 67, 75, 78, 89, 81, 69, 65, 75, 68, 65, 70, 76, 71, 83, 70, 76, 89, 69, 89, 83, 82, 82, 72, 80, 69, 89, 65, 86, 83, 86, 76, 76, 82, 76, 65, 75, 69, 89, 69, 65, 84, 76, 69, 69, 67, 67, 65, 75, 68, 68, 80, 72, 65, 67, 89, 83, 84, 86, 70, 68, 75, 76, 75, 72, 76, 86, 68, 69, 80, 81, 78, 76, 73, 75, 81, 78, 67, 68, 81, 70, 69, 75, 76, 71, 69, 89, 71, 70, 81, 78, 65, 76, 73, 86, 82, 89, 84, 82, 75, 86, 80, 81, 86, 83, 84, 80, 84, 76, 86, 69, 86, 83, 82, 83, 76, 71, 75, 86, 71, 84, 82, 67, 67, 84, 75, 80, 69, 83, 69, 82, 77, 80, 67, 84, 69, 68, 89, 76, 83, 76, 73, 76, 78, 82, 76, 67, 86, 76, 72, 69, 75, 84, 80, 86, 83, 69, 75, 86, 84, 75, 67, 67, 84, 69, 83, 76, 86, 78, 82, 82, 80, 67, 70, 83, 65, 76, 84, 80, 68, 69, 84, 89, 86, 80, 75, 65, 70, 68, 69, 75, 76, 70, 84, 70, 72, 65, 68, 73, 67, 84, 76, 80, 68, 84, 69, 75, 81, 73, 75, 75, 81, 84, 65, 76, 86, 69, 76, 76, 75, 72, 75, 80, 75, 65, 84, 69, 69, 81, 76, 75, 84, 86, 77, 69, 78, 70, 86, 65, 70, 86, 68, 75, 67, 67, 65, 65, 68, 68, 75, 69, 65, 67, 70, 65, 86, 69, 71, 80, 75, 76, 86, 86, 83, 84, 81, 84, 65, 76, 65], taxonomy: \"9913\", reviewed: true }]");
+        assert_eq!(l, "[Record { sequence_version: 3, protein_evidence: ProteinLevel, mass: 35780, length: 333, gene: \"GAPDH\", id: \"P46406\", mnemonic: \"G3P_RABIT\", name: \"Glyceraldehyde-3-phosphate dehydrogenase\", organism: \"Oryctolagus cuniculus\", proteome: \"UP000001811\", sequence: [77, 86, 75, 86, 71, 86, 78, 71, 70, 71, 82, 73, 71, 82, 76, 86, 84, 82, 65, 65, 70, 78, 83, 71, 75, 86, 68, 86, 86, 65, 73, 78, 68, 80, 70, 73, 68, 76, 72, 89, 77, 86, 89, 77, 70, 81, 89, 68, 83, 84, 72, 71, 75, 70, 72, 71, 84, 86, 75, 65, 69, 78, 71, 75, 76, 86, 73, 78, 71, 75, 65, 73, 84, 73, 70, 81, 69, 82, 68, 80, 65, 78, 73, 75, 87, 71, 68, 65, 71, 65, 69, 89, 86, 86, 69, 83, 84, 71, 86, 70, 84, 84, 77, 69, 75, 65, 71, 65, 72, 76, 75, 71, 71, 65, 75, 82, 86, 73, 73, 83, 65, 80, 83, 65, 68, 65, 80, 77, 70, 86, 77, 71, 86, 78, 72, 69, 75, 89, 68, 78, 83, 76, 75, 73, 86, 83, 78, 65, 83, 67, 84, 84, 78, 67, 76, 65, 80, 76, 65, 75, 86, 73, 72, 68, 72, 70, 71, 73, 86, 69, 71, 76, 77, 84, 84, 86, 72, 65, 73, 84, 65, 84, 81, 75, 84, 86, 68, 71, 80, 83, 71, 75, 76, 87, 82, 68, 71, 82, 71, 65, 65, 81, 78, 73, 73, 80, 65, 83, 84, 71, 65, 65, 75, 65, 86, 71, 75, 86, 73, 80, 69, 76, 78, 71, 75, 76, 84, 71, 77, 65, 70, 82, 86, 80, 84, 80, 78, 86, 83, 86, 86, 68, 76, 84, 67, 82, 76, 69, 75, 65, 65, 75, 89, 68, 68, 73, 75, 75, 86, 86, 75, 81, 65, 83, 69, 71, 80, 76, 75, 71, 73, 76, 71, 89, 84, 69, 68, 81, 86, 86, 83, 67, 68, 70, 78, 83, 65, 84, 72, 83, 83, 84, 70, 68, 65, 71, 65, 71, 73, 65, 76, 78, 68, 72, 70, 86, 75, 76, 73, 83, 87, 89, 68, 78, 69, 70, 71, 89, 83, 78, 82, 86, 86, 68, 76, 77, 86, 72, 77, 65, 83, 75, 69], taxonomy: \"9986\", reviewed: true, xrefs: [], keywords: [], features: [] }, Record { sequence_version: 4, protein_evidence: ProteinLevel, mass: 69293, length: 607, gene: \"ALB\", id: \"P02769\", mnemonic: \"ALBU_BOVIN\", name: \"Serum albumin\", organism: \"Bos taurus\", proteome: \"UP000009136\", sequence: [77, 75, 87, 86, 84, 70, 73, 83, 76, 76, 76, 76, 70, 83, 83, 65, 89, 83, 82, 71, 86, 70, 82, 82, 68, 84, 72, 75, 83, 69, 73, 65, 72, 82, 70, 75, 68, 76, 71, 69, 69, 72, 70, 75, 71, 76, 86, 76, 73, 65, 70, 83, 81, 89, 76, 81, 81, 67, 80, 70, 68, 69, 72, 86, 75, 76, 86, 78, 69, 76, 84, 69, 70, 65, 75, 84, 67, 86, 65, 68, 69, 83, 72, 65, 71, 67, 69, 75, 83, 76, 72, 84, 76, 70, 71, 68, 69, 76, 67, 75, 86, 65, 83, 76, 82, 69, 84, 89, 71, 68, 77, 65, 68, 67, 67, 69, 75, 81, 69, 80, 69, 82, 78, 69, 67, 70, 76, 83, 72, 75, 68, 68, 83, 80, 68, 76, 80, 75, 76, 75, 80, 68, 80, 78, 84, 76, 67, 68, 69, 70, 75, 65, 68, 69, 75, 75, 70, 87, 71, 75, 89, 76, 89, 69, 73, 65, 82, 82, 72, 80, 89, 70, 89, 65, 80, 69, 76, 76, 89, 89, 65, 78, 75, 89, 78, 71, 86, 70, 81, 69, 67, 67, 81, 65, 69, 68, 75, 71, 65, 67, 76, 76, 80, 75, 73, 69, 84, 77, 82, 69, 75, 86, 76, 65, 83, 83, 65, 82, 81, 82, 76, 82, 67, 65, 83, 73, 81, 75, 70, 71, 69, 82, 65, 76, 75, 65, 87, 83, 86, 65, 82, 76, 83, 81, 75, 70, 80, 75, 65, 69, 70, 86, 69, 86, 84, 75, 76, 86, 84, 68, 76, 84, 75, 86, 72, 75, 69, 67, 67, 72, 71, 68, 76, 76, 69, 67, 65, 68, 68, 82, 65, 68, 76, 65, 75, 89, 73, 67, 68, 78, 81, 68, 84, 73, 83, 83, 75, 76, 75, 69, 67, 67, 68, 75, 80, 76, 76, 69, 75, 83, 72, 67, 73, 65, 69, 86, 69, 75, 68, 65, 73, 80, 69, 78, 76, 80, 80, 76, 84, 65, 68, 70, 65, 69, 68, 75, 68, 86, 67, 75, 78, 89, 81, 69, 65, 75, 68, 65, 70, 76, 71, 83, 70, 76, 89, 69, 89, 83, 82, 82, 72, 80, 69, 89, 65, 86, 83, 86, 76, 76, 82, 76, 65, 75, 69, 89, 69, 65, 84, 76, 69, 69, 67, 67, 65, 75, 68, 68, 80, 72, 65, 67, 89, 83, 84, 86, 70, 68, 75, 76, 75, 72, 76, 86, 68, 69, 80, 81, 78, 76, 73, 75, 81, 78, 67, 68, 81, 70, 69, 75, 76, 71, 69, 89, 71, 70, 81, 78, 65, 76, 73, 86, 82, 89, 84, 82, 75, 86, 80, 81, 86, 83, 84, 80, 84, 76, 86, 69, 86, 83, 82, 83, 76, 71, 75, 86, 71, 84, 82, 67, 67, 84, 75, 80, 69, 83, 69, 82, 77, 80, 67, 84, 69, 68, 89, 76, 83, 76, 73, 76, 78, 82, 76, 67, 86, 76, 72, 69, 75, 84, 80, 86, 83, 69, 75, 86, 84, 75, 67, 67, 84, 69, 83, 76, 86, 78, 82, 82, 80, 67, 70, 83, 65, 76, 84, 80, 68, 69, 84, 89, 86, 80, 75, 65, 70, 68, 69, 75, 76, 70, 84, 70, 72, 65, 68, 73, 67, 84, 76, 80, 68, 84, 69, 75, 81, 73, 75, 75, 81, 84, 65, 76, 86, 69, 76, 76, 75, 72, 75, 80, 75, 65, 84, 69, 69, 81, 76, 75, 84, 86, 77, 69, 78, 70, 86, 65, 70, 86, 68, 75, 67, 67, 65, 65, 68, 68, 75, 69, 65, 67, 70, 65, 86, 69, 71, 80, 75, 76, 86, 86, 83, 84, 81, 84, 65, 76, 65], taxonomy: \"9913\", reviewed: true, xrefs: [], keywords: [], features: [] }]");
+    }
+
+    #[test]
+    fn index_list_test() {
+        let mut v = vec![gapdh(), bsa(), Record::new()];
+        let mut g = gapdh();
+        g.id = String::from("P04406");
+        g.mnemonic = String::from("G3P_HUMAN");
+        g.xrefs.push(DbXref::new("PDB", "1U8F"));
+        g.xrefs.push(DbXref::new("GO", "GO:0005737"));
+        v[0].xrefs.push(DbXref::new("GO", "GO:0005737"));
+        v.push(g);
+
+        let ids = v.index_by_id();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids["P46406"], &v[0]);
+        assert_eq!(ids["P04406"], &v[3]);
+        assert!(!ids.contains_key(""));
+
+        let genes = v.index_by_gene();
+        assert_eq!(genes.len(), 2);
+        assert_eq!(genes["GAPDH"], vec![&v[0], &v[3]]);
+        assert_eq!(genes["ALB"], vec![&v[1]]);
+
+        let mnemonics = v.index_by_mnemonic();
+        assert_eq!(mnemonics.len(), 3);
+        assert_eq!(mnemonics["ALBU_BOVIN"], &v[1]);
+        assert!(RecordList::new().index_by_id().is_empty());
+
+        let go = v.index_by_xref("GO");
+        assert_eq!(go.len(), 1);
+        assert_eq!(go["GO:0005737"], vec![&v[0], &v[3]]);
+        assert_eq!(v.index_by_xref("PDB")["1U8F"], vec![&v[3]]);
+        assert!(v.index_by_xref("RefSeq").is_empty());
+    }
+
+    #[test]
+    fn distance_matrix_list_test() {
+        let mut g = gapdh();
+        g.id = String::from("P04406");
+        g.sequence[5] = b'W';
+        let v = vec![gapdh(), bsa(), g];
+        let matrix = v.distance_matrix(DistanceMethod::Kmer(3));
+        assert_eq!(matrix.labels(), &[String::from("P46406"), String::from("P02769"), String::from("P04406")]);
+        assert!(matrix.get(0, 2) < 0.05);
+        assert!(matrix.get(0, 1) > 0.9);
+
+        let tree = neighbor_joining(&matrix);
+        assert_eq!(tree.leaves().len(), 3);
+        let gapdh = tree.find("P46406").unwrap();
+        let bsa = tree.find("P02769").unwrap();
+        assert!(tree.node(gapdh).length.unwrap() < tree.node(bsa).length.unwrap());
+    }
+
+    #[test]
+    fn cluster_by_identity_list_test() {
+        let mut g = gapdh();
+        g.id = String::from("P04406");
+        g.sequence[5] = b'W';
+        let v = vec![bsa(), g, gapdh()];
+        let clusters = v.cluster_by_identity(&ClusterOptions::default());
+        assert_eq!(clusters.representatives, vec![&v[0], &v[1]]);
+        assert_eq!(clusters.membership.len(), 3);
+        assert_eq!(clusters.membership["P02769"], "P02769");
+        assert_eq!(clusters.membership["P04406"], "P04406");
+        assert_eq!(clusters.membership["P46406"], "P04406");
     }
 
     #[test]
@@ -209,7 +382,11 @@ mod tests {
         assert_eq!(x, GAPDH_EMPTY_CSV_TAB);
 
         let mut buf = Bytes::new();
-        assert!(v.to_csv_strict(&mut Cursor::new(&mut buf), b'\t').is_err());
+        let err = v.to_csv_strict(&mut Cursor::new(&mut buf), b'\t').err().unwrap();
+        assert_eq!(err.record_index(), Some(1));
+        assert_eq!(err.violations().unwrap()[0], "sequence version is not set");
+        assert_eq!(buf, GAPDH_CSV_TAB);
+        let mut buf = Bytes::new();
         v.to_csv_lenient(&mut Cursor::new(&mut buf), b'\t').unwrap();
         assert_eq!(buf, GAPDH_CSV_TAB);
 
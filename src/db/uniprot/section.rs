@@ -1,9 +1,11 @@
 //! Model for Uniprot protein section type.
 
+use std::convert::TryFrom;
 use std::mem;
+use std::slice;
 
 use traits::{Deserializable, Serializable, Zero};
-use util::{Bytes, ErrorKind, Result};
+use util::{Bytes, Error, ErrorKind, Result};
 
 /// Identifier for the section type of a UniProt record.
 ///
@@ -33,6 +35,57 @@ impl Section {
     const MIN: u8 = 0;
     const MAX: u8 = 2;
 
+    /// All annotated sections.
+    pub const ALL: [Section; 2] = [Section::TrEMBL, Section::SwissProt];
+
+    /// Iterate over all annotated sections.
+    #[inline]
+    pub fn iter() -> slice::Iter<'static, Section> {
+        Self::ALL.iter()
+    }
+
+    /// Create section from the reviewed status of a record.
+    #[inline]
+    pub fn from_reviewed(reviewed: bool) -> Self {
+        if reviewed { Section::SwissProt } else { Section::TrEMBL }
+    }
+
+    /// Check if the section contains reviewed records.
+    #[inline]
+    pub fn is_reviewed(&self) -> bool {
+        *self == Section::SwissProt
+    }
+
+    /// Get the verbose name of the section, as used by the XML dataset.
+    #[inline]
+    pub fn verbose(&self) -> &'static str {
+        match self {
+            Section::TrEMBL     => "TrEMBL",
+            Section::SwissProt  => "Swiss-Prot",
+            Section::Unknown    => "",
+        }
+    }
+
+    /// Get the database code of the section, as used by FASTA headers.
+    #[inline]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Section::TrEMBL     => "tr",
+            Section::SwissProt  => "sp",
+            Section::Unknown    => "",
+        }
+    }
+
+    /// Get the reviewed status of the section, as used by CSV exports.
+    #[inline]
+    pub fn reviewed_status(&self) -> &'static str {
+        match self {
+            Section::TrEMBL     => "unreviewed",
+            Section::SwissProt  => "reviewed",
+            Section::Unknown    => "",
+        }
+    }
+
     /// Create raw integer from enumerated value.
     #[inline]
     pub fn to_int(&self) -> u8 {
@@ -48,6 +101,48 @@ impl Section {
             Err(From::from(ErrorKind::InvalidEnumeration))
         }
     }
+
+    /// Create enumerated value from any textual representation used by UniProt.
+    ///
+    /// Accepts the numeric value ("1"), the verbose name ("Swiss-Prot"
+    /// or "SwissProt"), the FASTA database code ("sp"), and the reviewed
+    /// status ("reviewed"), ignoring case.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Ok(int) = text.parse::<u8>() {
+            bool_to_error!(int < Section::Unknown.to_int(), InvalidEnumeration);
+            return Self::from_int(int);
+        }
+
+        for section in Self::iter() {
+            let is_match = text.eq_ignore_ascii_case(section.verbose()) ||
+                text.eq_ignore_ascii_case(&section.verbose().replace('-', "")) ||
+                text.eq_ignore_ascii_case(section.code()) ||
+                text.eq_ignore_ascii_case(section.reviewed_status());
+            if is_match {
+                return Ok(*section);
+            }
+        }
+        Err(From::from(ErrorKind::InvalidEnumeration))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Section {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(text: &'a str) -> Result<Self> {
+        Section::parse(text)
+    }
+}
+
+impl TryFrom<u8> for Section {
+    type Error = Error;
+
+    #[inline(always)]
+    fn try_from(int: u8) -> Result<Self> {
+        Section::from_int(int)
+    }
 }
 
 impl Zero for Section {
@@ -108,4 +203,25 @@ mod tests {
         serialize_section(Section::TrEMBL, "0");
         serialize_section(Section::SwissProt, "1");
     }
+
+    #[test]
+    fn parse_section_test() {
+        assert_eq!(Section::iter().count(), 2);
+        for section in Section::iter() {
+            assert_eq!(Section::parse(&section.to_int().to_string()).unwrap(), *section);
+            assert_eq!(Section::parse(section.verbose()).unwrap(), *section);
+            assert_eq!(Section::parse(section.code()).unwrap(), *section);
+            assert_eq!(Section::parse(section.reviewed_status()).unwrap(), *section);
+            assert_eq!(Section::from_reviewed(section.is_reviewed()), *section);
+            assert_eq!(Section::try_from(section.verbose()).unwrap(), *section);
+            assert_eq!(Section::try_from(section.to_int()).unwrap(), *section);
+        }
+        assert_eq!(Section::parse("SwissProt").unwrap(), Section::SwissProt);
+        assert_eq!(Section::parse("TREMBL").unwrap(), Section::TrEMBL);
+
+        assert!(Section::parse("2").is_err());
+        assert!(Section::parse("").is_err());
+        assert!(Section::parse("PIR").is_err());
+        assert!(Section::try_from(3u8).is_err());
+    }
 }
@@ -0,0 +1,216 @@
+//! Candidate modification site enumeration.
+//!
+//! N-glycosylation sites are found from the sequon motif, and
+//! phosphorylation sites from the acceptor residues within the
+//! peptides of a digest, so only sites detectable by a search with
+//! the same digestion options are reported.
+
+#[cfg(feature = "csv")]
+use csv;
+#[cfg(feature = "csv")]
+use std::io::Write;
+
+use bio::digest::{digest, DigestOptions, Peptide};
+use bio::motif::Motif;
+use bio::proteins::ProteinMassTable;
+use util::Result;
+use super::record::Record;
+
+/// Kind of candidate modification site.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SiteKind {
+    /// N-linked glycosylation, at the asparagine of a sequon.
+    NGlycosylation,
+    /// Phosphorylation, at an acceptor residue.
+    Phosphorylation,
+}
+
+impl SiteKind {
+    /// Get the verbose name of the site kind.
+    #[inline]
+    pub fn verbose(&self) -> &'static str {
+        match *self {
+            SiteKind::NGlycosylation  => "N-glycosylation",
+            SiteKind::Phosphorylation => "Phosphorylation",
+        }
+    }
+}
+
+/// Candidate modification site in a protein.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Site {
+    /// Accession number of the protein.
+    pub protein: String,
+    /// 1-based position of the site in the protein.
+    pub position: u32,
+    /// Residue at the site.
+    pub residue: u8,
+    /// Kind of modification.
+    pub kind: SiteKind,
+    /// Shortest peptide from the digest containing the site, if any.
+    pub peptide: Option<Vec<u8>>,
+}
+
+/// Options for modification site enumeration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SiteOptions {
+    /// Digestion options, to find the searchable peptides.
+    pub digest: DigestOptions,
+    /// Motif for N-glycosylation sites, modified at the first residue.
+    ///
+    /// Disables N-glycosylation sites if `None`.
+    pub glycosylation_motif: Option<Motif>,
+    /// Phosphorylation acceptor residues.
+    ///
+    /// Disables phosphorylation sites if empty.
+    pub phosphorylation_residues: Vec<u8>,
+}
+
+impl Default for SiteOptions {
+    /// The N-X-S/T sequon, and S/T/Y residues in tryptic peptides.
+    fn default() -> Self {
+        SiteOptions {
+            digest: DigestOptions::default(),
+            glycosylation_motif: Some(Motif::new("N{P}[ST]").unwrap()),
+            phosphorylation_residues: b"STY".to_vec(),
+        }
+    }
+}
+
+/// Find the shortest peptide containing a 0-based position.
+fn containing_peptide(peptides: &[Peptide], position: usize) -> Option<&Peptide> {
+    peptides.iter()
+        .filter(|p| p.contains(position))
+        .min_by_key(|p| p.sequence.len())
+}
+
+/// Enumerate the candidate modification sites of a record.
+///
+/// N-glycosylation sites are reported for every sequon, with the
+/// containing peptide if it is searchable. Phosphorylation sites are
+/// only reported within searchable peptides. Sites are ordered by
+/// position, then by kind.
+pub fn enumerate_sites(record: &Record, options: &SiteOptions, table: &ProteinMassTable)
+    -> Vec<Site>
+{
+    let peptides = digest(&record.sequence, &options.digest, table);
+    let site = |position: usize, kind: SiteKind, peptide: Option<&Peptide>| Site {
+        protein: record.id.clone(),
+        position: position as u32 + 1,
+        residue: record.sequence[position],
+        kind: kind,
+        peptide: peptide.map(|p| p.sequence.clone()),
+    };
+
+    let mut sites = vec![];
+    if let Some(ref motif) = options.glycosylation_motif {
+        for position in motif.find(&record.sequence) {
+            sites.push(site(position, SiteKind::NGlycosylation, containing_peptide(&peptides, position)));
+        }
+    }
+    for (position, residue) in record.sequence.iter().enumerate() {
+        if !options.phosphorylation_residues.contains(&residue.to_ascii_uppercase()) {
+            continue;
+        }
+        if let Some(peptide) = containing_peptide(&peptides, position) {
+            sites.push(site(position, SiteKind::Phosphorylation, Some(peptide)));
+        }
+    }
+    sites.sort_by_key(|s| (s.position, s.kind as u8));
+
+    sites
+}
+
+/// Enumerate the candidate modification sites of multiple records.
+pub fn enumerate_sites_iter<'a, Iter>(records: Iter, options: &SiteOptions, table: &ProteinMassTable)
+    -> Vec<Site>
+    where Iter: IntoIterator<Item = &'a Record>
+{
+    records.into_iter()
+        .flat_map(|r| enumerate_sites(r, options, table))
+        .collect()
+}
+
+/// Header columns for the site table.
+#[cfg(feature = "csv")]
+const SITE_HEADER: [&'static str; 5] = ["Entry", "Position", "Residue", "Modification", "Peptide"];
+
+/// Export the sites to a CSV table, with one row per site.
+#[cfg(feature = "csv")]
+pub fn sites_to_csv<'a, Iter, T>(writer: &mut T, sites: Iter, delimiter: u8)
+    -> Result<()>
+    where T: Write,
+          Iter: IntoIterator<Item = &'a Site>
+{
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(csv::QuoteStyle::Necessary)
+        .flexible(false)
+        .from_writer(writer);
+    writer.write_record(&SITE_HEADER)?;
+    for site in sites {
+        let position = site.position.to_string();
+        let peptide = site.peptide.as_ref().map_or(&[][..], |p| p.as_slice());
+        writer.write_record(&[
+            site.protein.as_bytes(),
+            position.as_bytes(),
+            &[site.residue],
+            site.kind.verbose().as_bytes(),
+            peptide,
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> Record {
+        let mut record = Record::new();
+        record.id = String::from("P02769");
+        record.sequence = b"MNGSAMPLERKPEPTIDENPTK".to_vec();
+        record
+    }
+
+    #[test]
+    fn enumerate_sites_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let sites = enumerate_sites(&record(), &SiteOptions::default(), &table);
+        let summary: Vec<(u32, u8, SiteKind)> = sites.iter()
+            .map(|s| (s.position, s.residue, s.kind))
+            .collect();
+        assert_eq!(summary, vec![
+            (2, b'N', SiteKind::NGlycosylation),
+            (4, b'S', SiteKind::Phosphorylation),
+            (15, b'T', SiteKind::Phosphorylation),
+            (21, b'T', SiteKind::Phosphorylation),
+        ]);
+        assert_eq!(sites[0].peptide, Some(b"MNGSAMPLER".to_vec()));
+        assert_eq!(sites[3].peptide, Some(b"KPEPTIDENPTK".to_vec()));
+
+        // Sites outside searchable peptides.
+        let options = SiteOptions {
+            digest: DigestOptions { max_mass: 1000.0, ..DigestOptions::default() },
+            ..SiteOptions::default()
+        };
+        let sites = enumerate_sites(&record(), &options, &table);
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].kind, SiteKind::NGlycosylation);
+        assert_eq!(sites[0].peptide, None);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn sites_to_csv_test() {
+        let table = ProteinMassTable::monoisotopic();
+        let sites = enumerate_sites_iter(&[record()], &SiteOptions::default(), &table);
+        let mut w = vec![];
+        sites_to_csv(&mut w, sites.iter().take(2), b',').unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "Entry,Position,Residue,Modification,Peptide\nP02769,2,N,N-glycosylation,MNGSAMPLER\nP02769,4,S,Phosphorylation,MNGSAMPLER\n");
+    }
+}
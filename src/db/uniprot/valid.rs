@@ -1,37 +1,68 @@
 //! Valid trait implementation for UniProt models.
 
+use bio::Alphabet;
 use traits::Valid;
 use super::re::*;
 use super::evidence::ProteinEvidence;
 use super::record::Record;
 use super::record_list::RecordList;
 
-impl Valid for Record {
-    fn is_valid(&self) -> bool {
+/// Determine if the fields other than the sequence residues are valid.
+fn has_valid_fields(record: &Record) -> bool {
+    record.sequence_version > 0 &&
+        record.protein_evidence < ProteinEvidence::Unknown &&
+        record.mass > 0 &&
+        record.length as usize == record.sequence.len() &&
+        !record.sequence.is_empty() &&
+        !record.name.is_empty() &&
+        !record.organism.is_empty() &&
+        GeneRegex::validate().is_match(&record.gene) &&
+        AccessionRegex::validate().is_match(&record.id) &&
+        MnemonicRegex::validate().is_match(&record.mnemonic) &&
         (
-            // Do not try to validate the Organism
-            // With virus names being non-standard, it is impossible
-            // with an NFA, and extremely time complex otherwise.
-            self.sequence_version > 0 &&
-            self.protein_evidence < ProteinEvidence::Unknown &&
-            self.mass > 0 &&
-            self.length as usize == self.sequence.len() &&
-            !self.sequence.is_empty() &&
-            !self.name.is_empty() &&
-            !self.organism.is_empty() &&
-            GeneRegex::validate().is_match(&self.gene) &&
-            AccessionRegex::validate().is_match(&self.id) &&
-            MnemonicRegex::validate().is_match(&self.mnemonic) &&
-            AminoacidRegex::validate().is_match(&self.sequence) &&
-            (
-                self.proteome.is_empty() ||
-                ProteomeRegex::validate().is_match(&self.proteome)
-            ) &&
-            (
-                self.taxonomy.is_empty() ||
-                TaxonomyRegex::validate().is_match(&self.taxonomy)
-            )
+            record.proteome.is_empty() ||
+            ProteomeRegex::validate().is_match(&record.proteome)
+        ) &&
+        (
+            record.taxonomy.is_empty() ||
+            TaxonomyRegex::validate().is_match(&record.taxonomy)
         )
+}
+
+impl Valid for Record {
+    #[inline]
+    fn is_valid(&self) -> bool {
+        has_valid_fields(self) && AminoacidRegex::validate().is_match(&self.sequence)
+    }
+
+    fn violations(&self) -> Vec<String> {
+        let checks = [
+            (self.sequence_version > 0, "sequence version is not set"),
+            (self.protein_evidence < ProteinEvidence::Unknown, "protein evidence is unknown"),
+            (self.mass > 0, "mass is not set"),
+            (self.length as usize == self.sequence.len(), "length does not match the sequence"),
+            (!self.sequence.is_empty(), "sequence is empty"),
+            (AminoacidRegex::validate().is_match(&self.sequence), "sequence has invalid residues"),
+            (!self.name.is_empty(), "name is empty"),
+            (!self.organism.is_empty(), "organism is empty"),
+            (GeneRegex::validate().is_match(&self.gene), "gene name is invalid"),
+            (AccessionRegex::validate().is_match(&self.id), "accession number is invalid"),
+            (MnemonicRegex::validate().is_match(&self.mnemonic), "mnemonic is invalid"),
+            (self.proteome.is_empty() || ProteomeRegex::validate().is_match(&self.proteome), "proteome is invalid"),
+            (self.taxonomy.is_empty() || TaxonomyRegex::validate().is_match(&self.taxonomy), "taxonomy is invalid"),
+        ];
+        checks.iter()
+            .filter(|x| !x.0)
+            .map(|x| String::from(x.1))
+            .collect()
+    }
+}
+
+impl Record {
+    /// Determine if the record is valid, with residues from the alphabet.
+    #[inline]
+    pub fn is_valid_with_alphabet(&self, alphabet: Alphabet) -> bool {
+        has_valid_fields(self) && alphabet.is_protein_sequence(&self.sequence)
     }
 }
 
@@ -5,14 +5,19 @@
 //! SAX-like API present for the pull XML parser. The module is copiously
 //! commented to try to facilitate maintainability.
 
-use quick_xml::events::BytesStart;
+use quick_xml::events::{BytesStart, Event};
 use std::io::prelude::*;
 
+use bio::Alphabet;
+use bio::alphabet::check_protein_sequence;
+use bio::proteins::sequence_checksum;
 use traits::*;
 use util::*;
+use super::annotation::{Feature, Keyword};
 use super::evidence::ProteinEvidence;
 use super::record::Record;
 use super::record_list::RecordList;
+use super::xref::DbXref;
 
 // SIZE
 
@@ -96,17 +101,62 @@ macro_rules! parse_integer {
     });
 }
 
+/// Callback state to read cross-references.
+#[derive(Default)]
+struct XrefState {
+    /// Cross-reference from a dbReference element.
+    xref: Option<DbXref>,
+    /// Protein evidence from the proteinExistence element.
+    evidence: Option<ProteinEvidence>,
+    /// Found the proteinExistence element, which ends the cross-references.
+    done: bool,
+}
+
+/// Callback state to read keywords and features.
+struct AnnotationState<'r> {
+    /// Record to store the sequence attributes.
+    record: &'r mut Record,
+    /// Keyword from a keyword element.
+    keyword: Option<Keyword>,
+    /// Feature from a feature element.
+    feature: Option<Feature>,
+    /// Found the sequence element, which ends the annotations.
+    done: bool,
+}
+
+impl<'r> AnnotationState<'r> {
+    /// Create new, empty state for a record.
+    #[inline]
+    fn new(record: &'r mut Record) -> Self {
+        AnnotationState {
+            record: record,
+            keyword: None,
+            feature: None,
+            done: false,
+        }
+    }
+}
 
 pub struct XmlRecordIter<T: BufRead> {
-    reader: XmlReader<T>,
+    reader: XmlReader<DecodeReader<T>>,
+    alphabet: Option<Alphabet>,
 }
 
 impl<T: BufRead> XmlRecordIter<T> {
     /// Create new XmlRecordIter from a buffered reader.
     #[inline]
     pub fn new(reader: T) -> Self {
+        XmlRecordIter::with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Create new XmlRecordIter from a buffered reader and reader options.
+    #[inline]
+    pub fn with_options(reader: T, options: &ReaderOptions) -> Self {
+        let mut reader = XmlReader::with_capacity(DecodeReader::new(reader, options.encoding), options.buffer_capacity);
+        reader.set_name_matching(NameMatching::Namespace(UNIPROT_NAMESPACE.to_vec()));
         XmlRecordIter {
-            reader: XmlReader::new(reader),
+            reader: reader,
+            alphabet: options.alphabet,
         }
     }
 
@@ -363,105 +413,294 @@ impl<T: BufRead> XmlRecordIter<T> {
         }
     }
 
-    /// Read the proteome ID.
+    /// Read the properties of a cross-reference.
     #[inline]
-    fn read_proteome(&mut self, record: &mut Record) -> Option<Result<()>> {
-        //  Proteomes XML format.
-        //        <dbReference type="Proteomes" id="UP000001811">
+    fn read_xref_properties(&mut self, xref: &mut DbXref) -> Option<Result<()>> {
+        //  Property XML format.
+        //        <property type="method" value="X-ray"/>
 
-        // Callback to determine if we're reading the proteome reference.
-        fn parse_proteome<'a>(event: BytesStart<'a>, record: &mut Record)
-            -> Option<Result<bool>>
+        // Parse the type and value of a property.
+        fn parse_property<'a>(event: &BytesStart<'a>, xref: &mut DbXref)
+            -> Result<()>
         {
+            let mut key = String::new();
+            let mut value = String::new();
             for result in event.attributes() {
-                let attribute = parse_attribute!(result);
-                if attribute.key == b"type" && &*attribute.value != b"Proteomes" {
-                    return Some(Ok(false));
-                } else if attribute.key == b"id" {
-                     // Parse the taxonomic identifier.
-                    record.proteome = from_utf8!(attribute.value.to_vec());
-                    return Some(Ok(true));
+                let attribute = match result {
+                    Err(e) => return Err(From::from(ErrorKind::Xml(e))),
+                    Ok(v)  => v,
+                };
+                if attribute.key == b"type" {
+                    key = String::from_utf8(attribute.value.to_vec())?;
+                } else if attribute.key == b"value" {
+                    value = String::from_utf8(attribute.value.to_vec())?;
                 }
             }
-            Some(Ok(false))
+            xref.properties.push((key, value));
+            Ok(())
         }
 
-        // Here we invoke the actual callback iteratively until we find the element.
+        // Process events until the end of the cross-reference.
         loop {
-            match self.reader.seek_start_callback(b"dbReference", 2, record, parse_proteome)? {
+            let mut done = false;
+            let result = match self.reader.read_event() {
+                Err(e)                                                          => Err(e),
+                Ok(Event::Start(ref e)) if local_name(e.name()) == b"property"  => parse_property(e, xref),
+                Ok(Event::End(ref e)) if local_name(e.name()) == b"dbReference" => {
+                    done = true;
+                    Ok(())
+                },
+                Ok(Event::Eof)                                                  => return None,
+                Ok(_)                                                           => Ok(()),
+            };
+            self.reader.reset_buffer();
+
+            match result {
                 Err(e)  => return Some(Err(e)),
-                Ok(v)   => {
-                    if v {
-                        return Some(Ok(()));
+                Ok(_)   => if done {
+                    return Some(Ok(()));
+                },
+            }
+        }
+    }
+
+    /// Read the cross-references and the protein evidence.
+    ///
+    /// The proteome cross-reference is stored as the proteome ID,
+    /// and all other cross-references are stored as `DbXref`s.
+    #[inline]
+    fn read_xrefs_and_evidence(&mut self, record: &mut Record) -> Option<Result<()>> {
+        //  Cross-reference XML format.
+        //        <dbReference type="Proteomes" id="UP000001811">
+        //        <property type="component" value="Genome"/>
+        //        </dbReference>
+        //        <dbReference type="PDB" id="1J0X">
+        //        <property type="method" value="X-ray"/>
+        //        </dbReference>
+        //        ...
+        //        <proteinExistence type="evidence at protein level"/>
+
+        // Callback to parse the start of a cross-reference, or the
+        // protein evidence, which follows the cross-references.
+        fn parse_xref_or_evidence<'a>(event: BytesStart<'a>, state: &mut XrefState)
+            -> Option<Result<bool>>
+        {
+            match local_name(event.name()) {
+                b"dbReference"      => {
+                    let mut xref = DbXref::default();
+                    for result in event.attributes() {
+                        let attribute = parse_attribute!(result);
+                        if attribute.key == b"type" {
+                            xref.db = from_utf8!(attribute.value.to_vec());
+                        } else if attribute.key == b"id" {
+                            xref.id = from_utf8!(attribute.value.to_vec());
+                        }
+                    }
+                    state.xref = Some(xref);
+                    Some(Ok(true))
+                },
+                b"proteinExistence" => {
+                    for result in event.attributes() {
+                        let attribute = parse_attribute!(result);
+                        if attribute.key == b"type" {
+                            let pe: &[u8] = &*attribute.value;
+                            state.evidence = match ProteinEvidence::from_xml_verbose_bytes(pe) {
+                                Err(e) => return Some(Err(e)),
+                                Ok(v)  => Some(v),
+                            };
+                        }
                     }
+                    state.done = true;
+                    Some(Ok(true))
+                },
+                _                   => Some(Ok(false)),
+            }
+        }
+
+        // Here we invoke the actual callback iteratively until we find the evidence.
+        loop {
+            let mut state = XrefState::default();
+            match self.reader.seek_start_callback(b"", 2, &mut state, parse_xref_or_evidence)? {
+                Err(e)  => return Some(Err(e)),
+                Ok(_)   => (),
+            }
+
+            if state.done {
+                if let Some(evidence) = state.evidence {
+                    record.protein_evidence = evidence;
+                }
+                return Some(Ok(()));
+            } else if let Some(mut xref) = state.xref {
+                try_opterr!(self.read_xref_properties(&mut xref));
+                if xref.db != "Proteomes" {
+                    record.xrefs.push(xref);
+                } else if record.reviewed {
+                    record.proteome = xref.id;
                 }
             }
         }
     }
 
-    /// Read the protein evidence.
+    /// Read the location of a feature.
     #[inline]
-    fn read_evidence(&mut self, record: &mut Record) -> Option<Result<()>> {
-        // Callback to parse the protein evidence information.
-        fn parse_evidence<'a>(event: BytesStart<'a>, record: &mut Record)
-            -> Option<Result<bool>>
+    fn read_feature_location(&mut self, feature: &mut Feature) -> Option<Result<()>> {
+        //  Location XML format.
+        //        <location>
+        //        <begin position="2"/>
+        //        <end position="333"/>
+        //        </location>
+        //  Or,
+        //        <location>
+        //        <position position="34"/>
+        //        </location>
+
+        // Parse the position attribute, which is missing for unknown positions.
+        fn parse_position<'a>(event: &BytesStart<'a>)
+            -> Result<u32>
         {
             for result in event.attributes() {
-                let attribute = parse_attribute!(result);
-                if attribute.key == b"type" {
-                    // Parse the taxonomic identifier.
-                    let pe: &[u8] = &*attribute.value;
-                    record.protein_evidence = match ProteinEvidence::from_xml_verbose_bytes(pe) {
-                        Err(e) => return Some(Err(e)),
-                        Ok(v)  => v,
-                    };
-                    return Some(Ok(true));
+                let attribute = match result {
+                    Err(e) => return Err(From::from(ErrorKind::Xml(e))),
+                    Ok(v)  => v,
+                };
+                if attribute.key == b"position" {
+                    return from_bytes(&*attribute.value);
                 }
             }
-            Some(Ok(false))
+            Ok(0)
         }
 
-        // Invoke our callback
-        Some(match self.reader.seek_start_callback(b"proteinExistence", 2, record, parse_evidence)? {
-            Err(e)  => Err(e),
-            Ok(_)   => Ok(()),
-        })
+        // Process events until the end of the feature.
+        loop {
+            let mut done = false;
+            let result = match self.reader.read_event() {
+                Err(e)                                                      => Err(e),
+                Ok(Event::Start(ref e)) if local_name(e.name()) == b"begin" => parse_position(e).map(|v| feature.begin = v),
+                Ok(Event::Start(ref e)) if local_name(e.name()) == b"end"   => parse_position(e).map(|v| feature.end = v),
+                Ok(Event::Start(ref e)) if local_name(e.name()) == b"position" => parse_position(e).map(|v| {
+                    feature.begin = v;
+                    feature.end = v;
+                }),
+                Ok(Event::End(ref e)) if local_name(e.name()) == b"feature" => {
+                    done = true;
+                    Ok(())
+                },
+                Ok(Event::Eof)                                              => return None,
+                Ok(_)                                                       => Ok(()),
+            };
+            self.reader.reset_buffer();
+
+            match result {
+                Err(e)  => return Some(Err(e)),
+                Ok(_)   => if done {
+                    return Some(Ok(()));
+                },
+            }
+        }
     }
 
-    // Read the sequence.
+    /// Read the keywords, features and sequence.
     #[inline]
-    fn read_sequence(&mut self, record: &mut Record) -> Option<Result<()>> {
-        // Callback to parse the protein evidence information.
-        fn parse_sequence<'a>(event: BytesStart<'a>, record: &mut Record)
+    fn read_annotations_and_sequence(&mut self, record: &mut Record) -> Option<Result<()>> {
+        //  Annotation XML format.
+        //        <keyword id="KW-0007">Acetylation</keyword>
+        //        ...
+        //        <feature type="chain" description="Glyceraldehyde-3-phosphate dehydrogenase" id="PRO_0000145486">
+        //        <location>...</location>
+        //        </feature>
+        //        ...
+        //        <sequence length="333" mass="35780" ...>MVKVGVNGFGRIGRL...</sequence>
+
+        // Callback to parse the start of a keyword, feature, or the
+        // sequence, which follows the annotations.
+        fn parse_annotation_or_sequence<'a, 'r>(event: BytesStart<'a>, state: &mut AnnotationState<'r>)
             -> Option<Result<bool>>
         {
-            for result in event.attributes() {
-                let attribute = parse_attribute!(result);
+            match local_name(event.name()) {
+                b"keyword"  => {
+                    let mut keyword = Keyword::default();
+                    for result in event.attributes() {
+                        let attribute = parse_attribute!(result);
+                        if attribute.key == b"id" {
+                            keyword.id = from_utf8!(attribute.value.to_vec());
+                        }
+                    }
+                    state.keyword = Some(keyword);
+                    Some(Ok(true))
+                },
+                b"feature"  => {
+                    let mut feature = Feature::default();
+                    for result in event.attributes() {
+                        let attribute = parse_attribute!(result);
+                        if attribute.key == b"type" {
+                            feature.kind = from_utf8!(attribute.value.to_vec());
+                        } else if attribute.key == b"description" {
+                            let value = match attribute.unescaped_value() {
+                                Err(e) => return Some(Err(From::from(ErrorKind::Xml(e)))),
+                                Ok(v)  => v.to_vec(),
+                            };
+                            feature.description = from_utf8!(value);
+                        } else if attribute.key == b"id" {
+                            feature.id = from_utf8!(attribute.value.to_vec());
+                        }
+                    }
+                    state.feature = Some(feature);
+                    Some(Ok(true))
+                },
+                b"sequence" => {
+                    for result in event.attributes() {
+                        let attribute = parse_attribute!(result);
+                        if attribute.key == b"length" {
+                            state.record.length = parse_integer!(&*attribute.value);
+                        } else if attribute.key == b"mass" {
+                            state.record.mass = parse_integer!(&*attribute.value);
+                        } else if attribute.key == b"version" {
+                            state.record.sequence_version = parse_integer!(&*attribute.value);
+                        }
+                    }
+                    state.done = true;
+                    Some(Ok(true))
+                },
+                _           => Some(Ok(false)),
+            }
+        }
 
-                if attribute.key == b"length" {
-                    record.length = parse_integer!(&*attribute.value);
-                } else if attribute.key == b"mass" {
-                    record.mass = parse_integer!(&*attribute.value);
-                } else if attribute.key == b"version" {
-                    record.sequence_version = parse_integer!(&*attribute.value);
+        // Here we invoke the actual callback iteratively until we find the sequence.
+        loop {
+            let (keyword, feature, done) = {
+                let mut state = AnnotationState::new(record);
+                match self.reader.seek_start_callback(b"", 2, &mut state, parse_annotation_or_sequence)? {
+                    Err(e)  => return Some(Err(e)),
+                    Ok(_)   => (),
+                }
+                (state.keyword, state.feature, state.done)
+            };
+
+            if done {
+                return self.read_sequence(record);
+            } else if let Some(mut keyword) = keyword {
+                match self.reader.read_text(b"keyword") {
+                    Err(e)  => return Some(Err(e)),
+                    Ok(v)   => keyword.name = from_utf8!(v),
                 }
+                record.keywords.push(keyword);
+            } else if let Some(mut feature) = feature {
+                try_opterr!(self.read_feature_location(&mut feature));
+                record.features.push(feature);
             }
-            Some(Ok(true))
         }
+    }
 
-        // Invoke our callback
-        Some(match self.reader.seek_start_callback(b"sequence", 2, record, parse_sequence)? {
+    /// Read the sequence text.
+    #[inline]
+    fn read_sequence(&mut self, record: &mut Record) -> Option<Result<()>> {
+        Some(match self.reader.read_text(b"sequence") {
             Err(e)  => Err(e),
-            Ok(_)   => {
-                match self.reader.read_text(b"sequence") {
-                    Err(e)  => Err(e),
-                    Ok(v)   => {
-                        let mut sequence = Vec::with_capacity(v.len());
-                        v.split(|c| *c == b'\n').for_each(|s| sequence.extend(s));
-                        record.sequence = sequence;
-                        Ok(())
-                    },
-                }
+            Ok(v)   => {
+                let mut sequence = Vec::with_capacity(v.len());
+                v.split(|c| *c == b'\n').for_each(|s| sequence.extend(s));
+                record.sequence = sequence;
+                Ok(())
             },
         })
     }
@@ -472,11 +711,8 @@ impl<T: BufRead> XmlRecordIter<T> {
         try_opterr!(self.read_mnemonic(record));
         try_opterr!(self.read_protein(record));
         try_opterr!(self.read_gene_or_organism(record));
-        if record.reviewed {
-            try_opterr!(self.read_proteome(record));
-        }
-        try_opterr!(self.read_evidence(record));
-        try_opterr!(self.read_sequence(record));
+        try_opterr!(self.read_xrefs_and_evidence(record));
+        try_opterr!(self.read_annotations_and_sequence(record));
 
         Some(Ok(()))
     }
@@ -504,7 +740,8 @@ impl<T: BufRead> Iterator for XmlRecordIter<T> {
             },
         }
 
-        Some(Ok(record))
+        log_trace!("parsed XML record");
+        Some(check_protein_sequence(self.alphabet, &mut record.sequence).map(|_| record))
     }
 }
 
@@ -518,6 +755,14 @@ fn iterator_from_xml<T: BufRead>(reader: T)
     XmlRecordIter::new(reader)
 }
 
+/// Import record data from XML, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_xml_with_options<T: BufRead>(reader: T, options: &ReaderOptions)
+    -> XmlRecordIter<T>
+{
+    XmlRecordIter::with_options(reader, options)
+}
+
 // READER -- STRICT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -529,6 +774,12 @@ pub fn iterator_from_xml_strict<T: BufRead>(reader: T) -> XmlRecordStrictIter<T>
     XmlRecordStrictIter::new(iterator_from_xml(reader))
 }
 
+/// Create strict record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_xml_strict_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> XmlRecordStrictIter<T> {
+    XmlRecordStrictIter::new(iterator_from_xml_with_options(reader, options))
+}
+
 // READER -- LENIENT
 
 /// Iterator to lazily load `Record`s from a document.
@@ -540,19 +791,116 @@ pub fn iterator_from_xml_lenient<T: BufRead>(reader: T) -> XmlRecordLenientIter<
     XmlRecordLenientIter::new(iterator_from_xml(reader))
 }
 
+/// Create lenient record iterator from reader, with custom reader options.
+#[inline(always)]
+pub fn iterator_from_xml_lenient_with_options<T: BufRead>(reader: T, options: &ReaderOptions) -> XmlRecordLenientIter<T> {
+    XmlRecordLenientIter::new(iterator_from_xml_with_options(reader, options))
+}
+
 // XML UNIPROT WRITER
 
+/// Namespace of the UniProt XML schema.
+const UNIPROT_NAMESPACE: &'static [u8] = b"http://uniprot.org/uniprot";
+
+/// Namespace for XML schema instances.
+const XSI_NAMESPACE: &'static [u8] = b"http://www.w3.org/2001/XMLSchema-instance";
+
+/// Location of the UniProt XML schema.
+const UNIPROT_SCHEMA_LOCATION: &'static [u8] = b"http://uniprot.org/uniprot http://www.uniprot.org/support/docs/uniprot.xsd";
+
+/// Feature types allowed by the UniProt XML schema.
+const FEATURE_TYPES: &'static [&'static str] = &[
+    "active site", "binding site", "calcium-binding region", "chain",
+    "coiled-coil region", "compositionally biased region", "cross-link",
+    "disulfide bond", "DNA-binding region", "domain", "glycosylation site",
+    "helix", "initiator methionine", "intramembrane region",
+    "lipid moiety-binding region", "metal ion-binding site",
+    "modified residue", "mutagenesis site", "non-consecutive residues",
+    "non-standard amino acid", "non-terminal residue",
+    "nucleotide phosphate-binding region", "peptide", "propeptide",
+    "region of interest", "repeat", "sequence conflict", "sequence variant",
+    "short sequence motif", "signal peptide", "site", "splice variant",
+    "strand", "topological domain", "transit peptide",
+    "transmembrane region", "turn", "unsure residue", "zinc finger region",
+];
+
+/// Options to format UniProt XML on export.
+#[derive(Clone, Debug, PartialEq)]
+pub struct XmlWriterOptions {
+    /// Write output conforming to the UniProt XML schema (uniprot.xsd).
+    ///
+    /// Declares the namespaces correctly, omits empty optional elements,
+    /// and writes the elements and attributes the schema requires. Since
+    /// records do not store the entry history or citations, the entry
+    /// and sequence dates are set to `date`, the entry version to 1,
+    /// and a single placeholder submission reference is written.
+    pub schema: bool,
+    /// Validate each entry against the schema constraints before it is
+    /// written, failing with an invalid record error if not conforming.
+    pub validate: bool,
+    /// Date, formatted as `YYYY-MM-DD`, for the dates required by the schema.
+    pub date: String,
+    /// Number of spaces to indent each nested element, or 0 for compact output.
+    pub indent: usize,
+}
+
+impl XmlWriterOptions {
+    /// Create options to write and validate schema-conforming XML.
+    #[inline]
+    pub fn schema(date: &str) -> Self {
+        XmlWriterOptions {
+            schema: true,
+            validate: true,
+            date: String::from(date),
+            indent: 0,
+        }
+    }
+}
+
+impl Default for XmlWriterOptions {
+    #[inline]
+    fn default() -> Self {
+        XmlWriterOptions {
+            schema: false,
+            validate: false,
+            date: String::from("1970-01-01"),
+            indent: 0,
+        }
+    }
+}
+
+/// Check if a date is formatted as `YYYY-MM-DD`.
+fn is_valid_date(date: &str) -> bool {
+    let bytes = date.as_bytes();
+    bytes.len() == 10 && bytes.iter().enumerate().all(|(i, &c)| match i {
+        4 | 7 => c == b'-',
+        _     => c.is_ascii_digit(),
+    })
+}
+
 /// Internal XML writer for UniProt records.
 struct XmlUniProtWriter<T: Write> {
-    writer: XmlWriter<T>
+    writer: XmlWriter<T>,
+    options: XmlWriterOptions,
 }
 
 impl<T: Write> XmlUniProtWriter<T> {
     /// Create new XmlUniProtWriter.
     #[inline]
     pub fn new(writer: T) -> Self {
+        XmlUniProtWriter::with_options(writer, XmlWriterOptions::default())
+    }
+
+    /// Create new XmlUniProtWriter with custom formatting options.
+    #[inline]
+    pub fn with_options(writer: T, options: XmlWriterOptions) -> Self {
+        let writer = match options.indent {
+            0 => XmlWriter::new(writer),
+            n => XmlWriter::with_indent(writer, b' ', n),
+        };
         XmlUniProtWriter {
-            writer: XmlWriter::new(writer)
+            writer: writer,
+            options: options,
         }
     }
 
@@ -565,6 +913,14 @@ impl<T: Write> XmlUniProtWriter<T> {
     /// Write the UniProt start element.
     #[inline]
     fn write_uniprot_start(&mut self) -> Result<()> {
+        if self.options.schema {
+            self.writer.declare_namespace(b"", UNIPROT_NAMESPACE);
+            self.writer.declare_namespace(b"xsi", XSI_NAMESPACE);
+            let location = self.writer.qualified_name(XSI_NAMESPACE, b"schemaLocation")?;
+            return self.writer.write_start_element(b"uniprot", &[
+                (&location, UNIPROT_SCHEMA_LOCATION)
+            ]);
+        }
         self.writer.write_start_element(b"uniprot", &[
             (b"xlmns", b"http://uniprot.org/uniprot"),
             (b"xmlns:xsi", b"http://www.w3.org/2001/XMLSchema-instance"),
@@ -581,6 +937,19 @@ impl<T: Write> XmlUniProtWriter<T> {
     /// Write the entry start element.
     #[inline]
     fn write_entry_start(&mut self, record: &Record) -> Result<()> {
+        let dataset: &[u8] = match record.reviewed {
+            true  => b"Swiss-Prot",
+            false => b"TrEMBL",
+        };
+        if self.options.schema {
+            let date = self.options.date.as_bytes();
+            return self.writer.write_start_element(b"entry", &[
+                (b"dataset", dataset),
+                (b"created", date),
+                (b"modified", date),
+                (b"version", b"1"),
+            ]);
+        }
         match record.reviewed {
             true    => self.writer.write_start_element(b"entry", &[
                     (b"dataset", b"Swiss-Prot"),
@@ -625,7 +994,9 @@ impl<T: Write> XmlUniProtWriter<T> {
     fn write_recommended_name(&mut self, record: &Record) -> Result<()> {
         self.writer.write_start_element(b"recommendedName", &[])?;
         self.write_full_name(record)?;
-        self.write_gene_name(record)?;
+        if !(self.options.schema && record.gene.is_empty()) {
+            self.write_gene_name(record)?;
+        }
         self.writer.write_end_element(b"recommendedName")
     }
 
@@ -705,6 +1076,27 @@ impl<T: Write> XmlUniProtWriter<T> {
         self.writer.write_end_element(b"dbReference")
     }
 
+    #[inline]
+    fn write_xref(&mut self, xref: &DbXref) -> Result<()> {
+        let attributes: [(&[u8], &[u8]); 2] = [
+            (b"type", xref.db.as_bytes()),
+            (b"id", xref.id.as_bytes())
+        ];
+        if xref.properties.is_empty() {
+            return self.writer.write_empty_element(b"dbReference", &attributes);
+        }
+
+        self.writer.write_start_element(b"dbReference", &attributes)?;
+        for &(ref key, ref value) in xref.properties.iter() {
+            self.writer.write_empty_element(b"property", &[
+                (b"type", key.as_bytes()),
+                (b"value", value.as_bytes())
+            ])?;
+        }
+
+        self.writer.write_end_element(b"dbReference")
+    }
+
     #[inline]
     fn write_protein_existence(&mut self, record: &Record) -> Result<()> {
         self.writer.write_empty_element(b"proteinExistence", &[
@@ -712,6 +1104,51 @@ impl<T: Write> XmlUniProtWriter<T> {
         ])
     }
 
+    #[inline]
+    fn write_keyword(&mut self, keyword: &Keyword) -> Result<()> {
+        self.writer.write_text_element(b"keyword", keyword.name.as_bytes(), &[
+            (b"id", keyword.id.as_bytes())
+        ])
+    }
+
+    #[inline]
+    fn write_feature(&mut self, feature: &Feature) -> Result<()> {
+        let mut attributes: Vec<(&[u8], &[u8])> = vec![
+            (b"type", feature.kind.as_bytes()),
+            (b"description", feature.description.as_bytes())
+        ];
+        if !feature.id.is_empty() {
+            attributes.push((b"id", feature.id.as_bytes()));
+        }
+        self.writer.write_start_element(b"feature", &attributes)?;
+        self.writer.write_start_element(b"location", &[])?;
+        if feature.begin == feature.end {
+            let position = to_bytes(&feature.begin)?;
+            self.writer.write_empty_element(b"position", &[(b"position", position.as_slice())])?;
+        } else {
+            let begin = to_bytes(&feature.begin)?;
+            let end = to_bytes(&feature.end)?;
+            self.writer.write_empty_element(b"begin", &[(b"position", begin.as_slice())])?;
+            self.writer.write_empty_element(b"end", &[(b"position", end.as_slice())])?;
+        }
+        self.writer.write_end_element(b"location")?;
+
+        self.writer.write_end_element(b"feature")
+    }
+
+    /// Write a placeholder reference, since the schema requires one.
+    #[inline]
+    fn write_reference(&mut self) -> Result<()> {
+        self.writer.write_start_element(b"reference", &[(b"key", b"1")])?;
+        self.writer.write_empty_element(b"citation", &[
+            (b"type", b"submission"),
+            (b"date", self.options.date.as_bytes()),
+            (b"db", b"UniProtKB")
+        ])?;
+        self.writer.write_text_element(b"scope", b"SUBMISSION", &[])?;
+        self.writer.write_end_element(b"reference")
+    }
+
     #[inline]
     fn write_sequence(&mut self, record: &Record) -> Result<()>
     {
@@ -719,6 +1156,16 @@ impl<T: Write> XmlUniProtWriter<T> {
         let mass = to_bytes(&record.mass)?;
         let version = to_bytes(&record.sequence_version)?;
 
+        if self.options.schema {
+            let checksum = format!("{:016X}", sequence_checksum(&record.sequence));
+            return self.writer.write_text_element(b"sequence", record.sequence.as_slice(), &[
+                (b"length", length.as_slice()),
+                (b"mass", mass.as_slice()),
+                (b"checksum", checksum.as_bytes()),
+                (b"modified", self.options.date.as_bytes()),
+                (b"version", version.as_slice())
+            ]);
+        }
         self.writer.write_text_element(b"sequence", record.sequence.as_slice(), &[
             (b"length", length.as_slice()),
             (b"mass", mass.as_slice()),
@@ -726,19 +1173,65 @@ impl<T: Write> XmlUniProtWriter<T> {
         ])
     }
 
+    /// Validate the entry against the schema constraints.
+    fn validate_entry(&self, record: &Record) -> Result<()> {
+        let taxonomy = &record.taxonomy;
+        bool_to_error!(!record.id.is_empty(), InvalidRecord);
+        bool_to_error!(!record.mnemonic.is_empty(), InvalidRecord);
+        bool_to_error!(!record.name.is_empty(), InvalidRecord);
+        bool_to_error!(!record.organism.is_empty(), InvalidRecord);
+        bool_to_error!(!taxonomy.is_empty() && taxonomy.bytes().all(|c| c.is_ascii_digit()), InvalidRecord);
+        bool_to_error!(record.protein_evidence != ProteinEvidence::Unknown, InvalidRecord);
+        bool_to_error!(!record.sequence.is_empty(), InvalidRecord);
+        for xref in record.xrefs.iter() {
+            bool_to_error!(!xref.db.is_empty() && !xref.id.is_empty(), InvalidRecord);
+            bool_to_error!(xref.properties.iter().all(|p| !p.0.is_empty()), InvalidRecord);
+        }
+        for keyword in record.keywords.iter() {
+            bool_to_error!(!keyword.id.is_empty(), InvalidRecord);
+        }
+        for feature in record.features.iter() {
+            bool_to_error!(FEATURE_TYPES.contains(&feature.kind.as_str()), InvalidRecord);
+            bool_to_error!(feature.begin <= feature.end, InvalidRecord);
+        }
+        if self.options.schema {
+            bool_to_error!(is_valid_date(&self.options.date), InvalidRecord);
+        }
+        Ok(())
+    }
+
     /// Write the entry element.
     #[inline]
     fn write_entry(&mut self, record: &Record) -> Result<()> {
+        if self.options.validate {
+            self.validate_entry(record)?;
+        }
+        let schema = self.options.schema;
+
         self.write_entry_start(record)?;
         self.write_id(record)?;
         self.write_mnemonic(record)?;
         self.write_protein(record)?;
-        self.write_gene(record)?;
+        if !(schema && record.gene.is_empty()) {
+            self.write_gene(record)?;
+        }
         self.write_organism(record)?;
-        if record.reviewed {
+        if schema {
+            self.write_reference()?;
+        }
+        if record.reviewed && !(schema && record.proteome.is_empty()) {
             self.write_proteome(record)?;
         }
+        for xref in record.xrefs.iter() {
+            self.write_xref(xref)?;
+        }
         self.write_protein_existence(record)?;
+        for keyword in record.keywords.iter() {
+            self.write_keyword(keyword)?;
+        }
+        for feature in record.features.iter() {
+            self.write_feature(feature)?;
+        }
         self.write_sequence(record)?;
 
         self.write_entry_end()
@@ -818,6 +1311,84 @@ pub fn value_iterator_to_xml<Iter, T>(writer: &mut T, iter: Iter)
     value_iterator_export(writer, iter, b'\0', &init_cb, &export_cb, &dest_cb)
 }
 
+// WRITER -- OPTIONS
+
+/// Export record to XML, with custom formatting options.
+pub fn record_to_xml_with_options<T: Write>(writer: &mut T, record: &Record, options: &XmlWriterOptions)
+    -> Result<()>
+{
+    let mut writer = XmlUniProtWriter::with_options(writer, options.clone());
+    writer.write_declaration()?;
+    item_to_xml(&mut writer, record)
+}
+
+/// Create the initializer for the exporters with custom formatting options.
+#[inline(always)]
+fn init_with_options<'o, T: Write>(options: &'o XmlWriterOptions)
+    -> impl Fn(T, u8) -> Result<XmlUniProtWriter<T>> + 'o
+{
+    move |writer: T, _: u8| {
+        let mut writer = XmlUniProtWriter::with_options(writer, options.clone());
+        writer.write_declaration()?;
+        writer.write_uniprot_start()?;
+        Ok(writer)
+    }
+}
+
+/// Default exporter from a non-owning iterator to XML, with custom formatting options.
+pub fn reference_iterator_to_xml_with_options<'a, Iter, T>(writer: &mut T, iter: Iter, options: &XmlWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export(writer, iter, b'\0', &init_with_options(options), &export_cb, &dest_cb)
+}
+
+/// Default exporter from an owning iterator to XML, with custom formatting options.
+pub fn value_iterator_to_xml_with_options<Iter, T>(writer: &mut T, iter: Iter, options: &XmlWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export(writer, iter, b'\0', &init_with_options(options), &export_cb, &dest_cb)
+}
+
+/// Strict exporter from a non-owning iterator to XML, with custom formatting options.
+pub fn reference_iterator_to_xml_strict_with_options<'a, Iter, T>(writer: &mut T, iter: Iter, options: &XmlWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_strict(writer, iter, b'\0', &init_with_options(options), &export_cb, &dest_cb)
+}
+
+/// Strict exporter from an owning iterator to XML, with custom formatting options.
+pub fn value_iterator_to_xml_strict_with_options<Iter, T>(writer: &mut T, iter: Iter, options: &XmlWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_strict(writer, iter, b'\0', &init_with_options(options), &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from a non-owning iterator to XML, with custom formatting options.
+pub fn reference_iterator_to_xml_lenient_with_options<'a, Iter, T>(writer: &mut T, iter: Iter, options: &XmlWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = &'a Record>
+{
+    reference_iterator_export_lenient(writer, iter, b'\0', &init_with_options(options), &export_cb, &dest_cb)
+}
+
+/// Lenient exporter from an owning iterator to XML, with custom formatting options.
+pub fn value_iterator_to_xml_lenient_with_options<Iter, T>(writer: &mut T, iter: Iter, options: &XmlWriterOptions)
+    -> Result<()>
+    where T: Write,
+          Iter: Iterator<Item = Result<Record>>
+{
+    value_iterator_export_lenient(writer, iter, b'\0', &init_with_options(options), &export_cb, &dest_cb)
+}
+
 // WRITER -- STRICT
 
 /// Strict exporter from a non-owning iterator to XML.
@@ -879,6 +1450,20 @@ impl Xml for Record {
     fn from_xml<T: BufRead>(reader: &mut T) -> Result<Self> {
         record_from_xml(reader)
     }
+
+    #[inline(always)]
+    fn from_xml_iter<'a, T: 'a + BufRead>(reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+    {
+        Box::new(iterator_from_xml(reader))
+    }
+
+    #[inline(always)]
+    fn to_xml_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+    {
+        reference_iterator_to_xml(writer, iter)
+    }
 }
 
 impl Xml for RecordList {
@@ -995,6 +1580,134 @@ mod tests {
         assert_eq!(w.into_inner(), GAPDH_BSA_XML);
     }
 
+    #[test]
+    fn xml_trait_iter_test() {
+        let v: RecordList = Record::from_xml_iter(Cursor::new(GAPDH_BSA_XML))
+            .collect::<Result<RecordList>>()
+            .unwrap();
+        assert_eq!(v, vec![gapdh(), bsa()]);
+
+        let mut w = Cursor::new(vec![]);
+        Record::to_xml_iter(&mut w, v.iter()).unwrap();
+        assert_eq!(w.into_inner(), GAPDH_BSA_XML);
+    }
+
+    #[test]
+    fn annotation_xml_test() {
+        let mut x = gapdh();
+        let mut pdb = DbXref::new("PDB", "1J0X");
+        pdb.properties.push((String::from("method"), String::from("X-ray")));
+        pdb.properties.push((String::from("chains"), String::from("O/P/Q/R=2-333")));
+        x.xrefs.push(pdb);
+        x.xrefs.push(DbXref::new("PDBsum", "1J0X"));
+        x.keywords.push(Keyword::new("KW-0007", "Acetylation"));
+        x.keywords.push(Keyword::new("KW-0324", "Glycolysis"));
+        let mut chain = Feature::new("chain", "Glyceraldehyde-3-phosphate dehydrogenase", 2, 333);
+        chain.id = String::from("PRO_0000145486");
+        x.features.push(chain);
+        x.features.push(Feature::new("binding site", "NAD", 34, 34));
+        let mut y = Record::new();
+        y.xrefs.push(DbXref::new("Pfam", "PF00044"));
+
+        let v = vec![x, y];
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_xml(&mut w, v.iter()).unwrap();
+        let text = w.into_inner();
+        let u: RecordList = iterator_from_xml(Cursor::new(&text)).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(u, v);
+        assert_eq!(u[0].xrefs_by_db("PDB")[0].property("chains"), Some("O/P/Q/R=2-333"));
+        assert_eq!(u[0].features[1].description, "NAD");
+    }
+
+    #[test]
+    fn namespace_xml_test() {
+        // prefixed elements
+        let text = String::from_utf8(GAPDH_BSA_XML.to_vec()).unwrap()
+            .replace("</", "</up:")
+            .replace("<", "<up:")
+            .replace("<up:/", "</")
+            .replace("<up:?xml", "<?xml")
+            .replace("xlmns=", "xmlns:up=");
+        let v: RecordList = iterator_from_xml(Cursor::new(text.as_bytes())).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v, vec![gapdh(), bsa()]);
+
+        // elements from other namespaces are skipped
+        let text = String::from_utf8(GAPDH_BSA_XML.to_vec()).unwrap()
+            .replacen("<entry dataset", "<x:entry xmlns:x=\"urn:x\" dataset", 1)
+            .replacen("</entry>", "</x:entry>", 1)
+            .replace("xlmns=", "xmlns=");
+        let v: RecordList = iterator_from_xml(Cursor::new(text.as_bytes())).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v, vec![bsa()]);
+    }
+
+    #[test]
+    fn entity_xml_test() {
+        let text = String::from_utf8(GAPDH_BSA_XML.to_vec()).unwrap()
+            .replacen("<uniprot ", "<!DOCTYPE uniprot [<!ENTITY bos \"Bos\">]><uniprot ", 1)
+            .replace("<fullName>Serum albumin</fullName>", "<fullName><![CDATA[Serum]]> &#97;lbumin</fullName>")
+            .replace("<name type=\"scientific\">Bos taurus</name>", "<name type=\"scientific\">&bos; <!-- cattle -->taurus</name>");
+        let v: RecordList = iterator_from_xml(Cursor::new(text.as_bytes())).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(v, vec![gapdh(), bsa()]);
+    }
+
+    #[test]
+    fn schema_xml_test() {
+        let v = vec![gapdh(), bsa()];
+        let options = XmlWriterOptions::schema("2018-01-31");
+
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_xml_with_options(&mut w, v.iter(), &options).unwrap();
+        let text = String::from_utf8(w.into_inner()).unwrap();
+        assert!(text.contains("<uniprot xmlns=\"http://uniprot.org/uniprot\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\""));
+        assert!(!text.contains("xlmns"));
+        assert!(text.contains("<entry dataset=\"Swiss-Prot\" created=\"2018-01-31\" modified=\"2018-01-31\" version=\"1\">"));
+        assert!(text.contains("<scope>SUBMISSION</scope>"));
+        let checksum = format!("checksum=\"{:016X}\"", sequence_checksum(&v[0].sequence));
+        assert!(text.contains(&checksum));
+
+        // round-trip
+        let u: RecordList = iterator_from_xml(Cursor::new(text.as_bytes())).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(u, v);
+
+        // single record
+        let mut w = Cursor::new(vec![]);
+        record_to_xml_with_options(&mut w, &v[0], &options).unwrap();
+        let u: RecordList = iterator_from_xml(Cursor::new(w.into_inner())).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(u, vec![gapdh()]);
+
+        // validation
+        let mut x = gapdh();
+        x.features.push(Feature::new("unknown type", "", 1, 1));
+        let mut w = Cursor::new(vec![]);
+        assert!(record_to_xml_with_options(&mut w, &x, &options).is_err());
+        let mut w = Cursor::new(vec![]);
+        assert!(record_to_xml_with_options(&mut w, &Record::new(), &options).is_err());
+        let invalid_date = XmlWriterOptions::schema("31/01/2018");
+        let mut w = Cursor::new(vec![]);
+        assert!(record_to_xml_with_options(&mut w, &gapdh(), &invalid_date).is_err());
+
+        // schema violations are errors, even for lenient exporters
+        let u = vec![gapdh(), x, bsa()];
+        let mut w = Cursor::new(vec![]);
+        assert!(reference_iterator_to_xml_lenient_with_options(&mut w, u.iter(), &options).is_err());
+
+        // pretty-printed
+        let pretty = XmlWriterOptions { indent: 2, ..options.clone() };
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_xml_with_options(&mut w, v.iter(), &pretty).unwrap();
+        let text = String::from_utf8(w.into_inner()).unwrap();
+        assert!(text.contains("\n<uniprot "));
+        assert!(text.contains("\n  <entry "));
+        assert!(text.contains("\n    <accession>P46406</accession>"));
+        let u: RecordList = iterator_from_xml(Cursor::new(text.as_bytes())).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(u, v);
+
+        // default options are unchanged
+        let mut w = Cursor::new(vec![]);
+        reference_iterator_to_xml_with_options(&mut w, v.iter(), &XmlWriterOptions::default()).unwrap();
+        assert_eq!(w.into_inner(), GAPDH_BSA_XML);
+    }
+
     #[test]
     fn iterator_from_xml_test() {
         // VALID
@@ -1025,6 +1738,12 @@ mod tests {
         // Compile check only
         iterator_from_xml_lenient(&mut Cursor::new(text));
 
+        // record iterator -- custom capacity
+        let options = ReaderOptions::with_capacity(1, 1);
+        let iter = iterator_from_xml_strict_with_options(Cursor::new(text), &options);
+        let v: Result<RecordList> = iter.collect();
+        assert_eq!(&expected, &v.unwrap());
+
         // INVALID
         let text = GAPDH_EMPTY_XML;
         let expected1 = vec![gapdh(), Record::new()];
@@ -1049,6 +1768,44 @@ mod tests {
         assert_eq!(&expected2, &v.unwrap());
     }
 
+    #[test]
+    fn xml_encoding_test() {
+        let text = String::from_utf8(GAPDH_BSA_XML.to_vec()).unwrap().replacen("Oryctolagus cuniculus", "Oryctolagus\u{e9} cuniculus", 1);
+        let text: Vec<u8> = text.chars().map(|c| c as u8).collect();
+
+        let iter = iterator_from_xml(Cursor::new(&text));
+        assert!(iter.collect::<Result<RecordList>>().is_err());
+
+        let options = ReaderOptions::with_encoding(Encoding::Latin1);
+        let list = iterator_from_xml_strict_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].organism, "Oryctolagus\u{e9} cuniculus");
+        assert_eq!(list[0].sequence, gapdh().sequence);
+        assert_eq!(list[1], bsa());
+
+        let options = ReaderOptions::with_encoding(Encoding::Utf8Lossy);
+        let list = iterator_from_xml_lenient_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list[0].organism, "Oryctolagus\u{FFFD} cuniculus");
+    }
+
+    #[test]
+    fn xml_alphabet_test() {
+        let text = String::from_utf8(GAPDH_BSA_XML.to_vec()).unwrap().replacen("MVKVGVNGFG", "mvkvgvngfg", 1);
+
+        // Sequences are read unchanged by default.
+        let list = iterator_from_xml(Cursor::new(&text)).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(&list[0].sequence[..10], b"mvkvgvngfg");
+
+        let options = ReaderOptions::with_alphabet(Alphabet::Strict);
+        let mut iter = iterator_from_xml_with_options(Cursor::new(&text), &options);
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.next().unwrap().unwrap(), bsa());
+        assert!(iter.next().is_none());
+
+        let options = ReaderOptions::with_alphabet(Alphabet::CaseInsensitive);
+        let list = iterator_from_xml_with_options(Cursor::new(&text), &options).collect::<Result<RecordList>>().unwrap();
+        assert_eq!(list, vec![gapdh(), bsa()]);
+    }
+
     fn xml_dir() -> PathBuf {
         let mut dir = testdata_dir();
         dir.push("uniprot/xml");
@@ -1063,7 +1820,19 @@ mod tests {
         let mut reader = BufReader::new(File::open(path).unwrap());
 
         let p = gapdh();
-        let record = record_from_xml(&mut reader).unwrap();
+        let mut record = record_from_xml(&mut reader).unwrap();
+        let pdb = record.xrefs_by_db("PDB");
+        assert_eq!(pdb.len(), 1);
+        assert_eq!(pdb[0].id, "1J0X");
+        assert_eq!(pdb[0].property("method"), Some("X-ray"));
+        assert!(record.xrefs.iter().all(|x| x.db != "Proteomes"));
+        assert_eq!(record.keywords[1], Keyword::new("KW-0007", "Acetylation"));
+        let chain = record.features.iter().find(|f| f.kind == "chain").unwrap();
+        assert_eq!((chain.begin, chain.end), (1, 333));
+
+        record.xrefs.clear();
+        record.keywords.clear();
+        record.features.clear();
         assert_eq!(p, record);
     }
 
@@ -1075,7 +1844,14 @@ mod tests {
         let mut reader = BufReader::new(File::open(path).unwrap());
 
         let p = bsa();
-        let record = record_from_xml(&mut reader).unwrap();
+        let mut record = record_from_xml(&mut reader).unwrap();
+        assert!(!record.xrefs_by_db("GO").is_empty());
+        assert!(!record.keywords.is_empty());
+        assert!(!record.features.is_empty());
+
+        record.xrefs.clear();
+        record.keywords.clear();
+        record.features.clear();
         assert_eq!(p, record);
     }
 
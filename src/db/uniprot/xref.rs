@@ -0,0 +1,56 @@
+//! Model for UniProt cross-references to external databases.
+
+/// Cross-reference from a UniProt record to an external database.
+///
+/// Cross-references link records to other databases, for example,
+/// structures (`"PDB"`), sequences (`"RefSeq"`, `"Ensembl"`), or
+/// annotations (`"GO"`). The taxonomy and proteome cross-references
+/// are stored in the record, and are not duplicated as `DbXref`s.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct DbXref {
+    /// Database name, for example, `"PDB"`.
+    pub db: String,
+    /// Identifier in the database.
+    pub id: String,
+    /// Database-specific `(type, value)` properties, in document order.
+    pub properties: Vec<(String, String)>,
+}
+
+impl DbXref {
+    /// Create new cross-reference without properties.
+    #[inline]
+    pub fn new(db: &str, id: &str) -> Self {
+        DbXref {
+            db: String::from(db),
+            id: String::from(id),
+            properties: vec![],
+        }
+    }
+
+    /// Get the value of the first property with the type.
+    #[inline]
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.iter()
+            .find(|p| p.0 == key)
+            .map(|p| p.1.as_str())
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_test() {
+        let mut x = DbXref::new("PDB", "1J0X");
+        assert_eq!(x.property("method"), None);
+
+        x.properties.push((String::from("method"), String::from("X-ray")));
+        x.properties.push((String::from("resolution"), String::from("2.40 A")));
+        assert_eq!(x.property("method"), Some("X-ray"));
+        assert_eq!(x.property("resolution"), Some("2.40 A"));
+    }
+}
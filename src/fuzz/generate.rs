@@ -0,0 +1,200 @@
+//! Generators for well-formed and malformed fuzzing inputs.
+
+#[cfg(feature = "uniprot")]
+use bio::proteins::ProteinMassTable;
+#[cfg(feature = "uniprot")]
+use db::uniprot;
+#[cfg(feature = "mass_spectrometry")]
+use db::mass_spectra::{self, Peak};
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+use traits::MgfKind;
+use super::FuzzRng;
+
+/// Standard aminoacids, to generate protein sequences.
+#[cfg(feature = "uniprot")]
+const AMINOACIDS: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Characters for generated identifiers.
+#[cfg(feature = "uniprot")]
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Bytes with special meaning in the supported formats.
+const SPECIAL_BYTES: &[u8] = b"\n\r\t >|=,\"'<>&;#()[]{}0-.\x00\xff";
+
+/// Generate a random word of uppercase letters and digits.
+#[cfg(feature = "uniprot")]
+fn word(rng: &mut FuzzRng, min: usize, max: usize) -> String {
+    let length = rng.range(min, max);
+    String::from_utf8(rng.sequence(ALPHANUMERIC, length)).unwrap()
+}
+
+// UNIPROT
+
+/// Generate a random, valid UniProt record.
+#[cfg(feature = "uniprot")]
+pub fn uniprot_record(rng: &mut FuzzRng) -> uniprot::Record {
+    let length = rng.range(1, 400);
+    let sequence = rng.sequence(AMINOACIDS, length);
+    let mut record = uniprot::Record::new();
+    record.sequence_version = rng.range(1, 10) as u8;
+    record.protein_evidence = *rng.choose(&[
+        uniprot::ProteinEvidence::ProteinLevel,
+        uniprot::ProteinEvidence::TranscriptLevel,
+        uniprot::ProteinEvidence::Inferred,
+        uniprot::ProteinEvidence::Predicted,
+    ]);
+    record.mass = ProteinMassTable::average().total_sequence_mass(&sequence).round() as u64;
+    record.length = length as u32;
+    record.gene = word(rng, 1, 10);
+    record.id = format!("P{}{}{}", rng.range(0, 10), word(rng, 3, 4), rng.range(0, 10));
+    record.mnemonic = format!("{}_HUMAN", word(rng, 1, 6));
+    let words = rng.range(1, 6);
+    record.name = (0..words).map(|_| word(rng, 1, 12)).collect::<Vec<_>>().join(" ");
+    record.organism = String::from("Homo sapiens");
+    record.proteome = String::from("UP000005640");
+    record.sequence = sequence;
+    record.taxonomy = String::from("9606");
+    record.reviewed = rng.chance(0.5);
+    record
+}
+
+/// Generate a FASTA document with up to `count` random UniProt records.
+#[cfg(all(feature = "uniprot", feature = "fasta"))]
+pub fn uniprot_fasta(rng: &mut FuzzRng, count: usize) -> Vec<u8> {
+    let records: Vec<_> = (0..rng.range(0, count + 1)).map(|_| uniprot_record(rng)).collect();
+    let mut writer = vec![];
+    uniprot::low_level::reference_iterator_to_fasta(&mut writer, records.iter())
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+/// Generate a tab-delimited CSV document with up to `count` random UniProt records.
+#[cfg(all(feature = "uniprot", feature = "csv"))]
+pub fn uniprot_csv(rng: &mut FuzzRng, count: usize) -> Vec<u8> {
+    let records: Vec<_> = (0..rng.range(0, count + 1)).map(|_| uniprot_record(rng)).collect();
+    let mut writer = vec![];
+    uniprot::low_level::reference_iterator_to_csv(&mut writer, records.iter(), b'\t')
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+// MASS SPECTRA
+
+/// Generate a random, valid MS2 spectrum.
+#[cfg(feature = "mass_spectrometry")]
+pub fn spectrum(rng: &mut FuzzRng) -> mass_spectra::Record {
+    let mut mz = 50.0;
+    let peaks = (0..rng.range(1, 100)).map(|_| {
+        mz += 0.01 + rng.float() * 20.0;
+        Peak {
+            mz: (mz * 1e4).round() / 1e4,
+            intensity: (rng.float() * 1e6).round(),
+            z: 0,
+            mobility: None,
+        }
+    }).collect();
+
+    let mut record = mass_spectra::Record::new();
+    record.num = rng.range(1, 100000) as u32;
+    record.ms_level = 2;
+    // Whole seconds, since some MGF formats round the retention time.
+    record.rt = rng.range(1, 10000) as f64;
+    record.parent_mz = (200.0 + rng.float() * 1800.0).round();
+    record.parent_intensity = (rng.float() * 1e7).round() + 1.0;
+    record.parent_z = rng.range(1, 6) as i8;
+    record.file = String::from("fuzz");
+    record.peaks = peaks;
+    record
+}
+
+/// Generate an MGF document with up to `count` random spectra.
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+pub fn mgf(rng: &mut FuzzRng, count: usize, kind: MgfKind) -> Vec<u8> {
+    let records: Vec<_> = (0..rng.range(0, count + 1)).map(|_| spectrum(rng)).collect();
+    let mut writer = vec![];
+    mass_spectra::low_level::reference_iterator_to_mgf(&mut writer, records.iter(), kind)
+        .expect("writing to a vector cannot fail");
+    writer
+}
+
+// MUTATIONS
+
+/// Mutate a document to create a (likely) malformed input.
+///
+/// Applies 1-4 random byte substitutions, insertions of special bytes,
+/// deletions, duplications of a region, or truncations.
+pub fn mutate(rng: &mut FuzzRng, input: &[u8]) -> Vec<u8> {
+    let mut output = input.to_vec();
+    for _ in 0..rng.range(1, 5) {
+        let length = output.len();
+        if length == 0 {
+            output.push(*rng.choose(SPECIAL_BYTES));
+            continue;
+        }
+        let index = rng.range(0, length);
+        match rng.range(0, 6) {
+            0 => output[index] = rng.next_u64() as u8,
+            1 => output[index] = *rng.choose(SPECIAL_BYTES),
+            2 => output.insert(index, *rng.choose(SPECIAL_BYTES)),
+            3 => {
+                let end = rng.range(index, length) + 1;
+                output.drain(index..end);
+            },
+            4 => {
+                let end = rng.range(index, length) + 1;
+                let region = output[index..end].to_vec();
+                let at = rng.range(0, length + 1);
+                for (i, &c) in region.iter().enumerate() {
+                    output.insert(at + i, c);
+                }
+            },
+            _ => output.truncate(index),
+        }
+    }
+    output
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutate_test() {
+        let mut rng = FuzzRng::new(1);
+        let input = b">sp|P46406|G3P_RABIT\nMVKVGVNGFGRIGRLVTRAAF\n";
+        let mut changed = 0;
+        for _ in 0..100 {
+            if mutate(&mut rng, input) != input.to_vec() {
+                changed += 1;
+            }
+        }
+        assert!(changed > 90);
+        assert!(!mutate(&mut rng, b"").is_empty());
+        assert_eq!(mutate(&mut FuzzRng::new(2), input), mutate(&mut FuzzRng::new(2), input));
+    }
+
+    #[cfg(feature = "uniprot")]
+    #[test]
+    fn uniprot_record_test() {
+        use traits::Valid;
+
+        let mut rng = FuzzRng::new(3);
+        for _ in 0..50 {
+            assert!(uniprot_record(&mut rng).is_valid());
+        }
+    }
+
+    #[cfg(feature = "mass_spectrometry")]
+    #[test]
+    fn spectrum_test() {
+        use traits::Valid;
+
+        let mut rng = FuzzRng::new(4);
+        for _ in 0..50 {
+            assert!(spectrum(&mut rng).is_valid());
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Property-based fuzzing of the readers and writers.
+//!
+//! Generators create well-formed documents from random records, and
+//! malformed documents by mutating them. Properties check invariants
+//! for any input: the readers never panic, and every record read
+//! from the input is read back unchanged after it is written.
+//! Runs are reproducible from the seed, and downstream crates may
+//! combine the generators and properties for their own formats.
+
+use util::Rng;
+
+pub(crate) mod generate;
+pub(crate) mod property;
+
+pub use self::generate::*;
+pub use self::property::*;
+
+/// Random number generator for the generators, the shared `Rng`.
+pub type FuzzRng = Rng;
@@ -0,0 +1,243 @@
+//! Property checks over generated inputs.
+
+use std::panic::{self, AssertUnwindSafe};
+
+#[cfg(feature = "uniprot")]
+use db::uniprot;
+#[cfg(feature = "mass_spectrometry")]
+use db::mass_spectra;
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+use traits::MgfKind;
+#[cfg(any(feature = "uniprot", feature = "mass_spectrometry"))]
+use traits::Valid;
+use super::generate::mutate;
+use super::FuzzRng;
+
+/// Result of a property check, with a message describing the violation.
+pub type PropertyResult = ::std::result::Result<(), String>;
+
+/// Generated input for a property check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzCase {
+    /// Input document.
+    pub data: Vec<u8>,
+    /// Input was mutated from a well-formed document.
+    pub mutated: bool,
+}
+
+/// Options for a fuzzing run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FuzzOptions {
+    /// Seed for the random number generator.
+    pub seed: u64,
+    /// Number of generated inputs.
+    pub iterations: usize,
+    /// Probability of mutating each generated input.
+    pub mutation_rate: f64,
+}
+
+impl Default for FuzzOptions {
+    #[inline]
+    fn default() -> Self {
+        FuzzOptions {
+            seed: 0,
+            iterations: 100,
+            mutation_rate: 0.5,
+        }
+    }
+}
+
+/// Input that violated a property.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzFailure {
+    /// Iteration that generated the input.
+    pub iteration: usize,
+    /// Input that violated the property.
+    pub case: FuzzCase,
+    /// Description of the violation, or the panic message.
+    pub message: String,
+}
+
+/// Run a property check, converting panics into violations.
+pub fn catch_panic<F>(property: F) -> PropertyResult
+    where F: FnOnce() -> PropertyResult
+{
+    match panic::catch_unwind(AssertUnwindSafe(property)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(s) => String::from(*s),
+                None    => match payload.downcast_ref::<String>() {
+                    Some(s) => s.clone(),
+                    None    => String::from("unknown panic"),
+                },
+            };
+            Err(format!("panicked: {}", message))
+        },
+    }
+}
+
+/// Check a property over generated inputs.
+///
+/// Each input is generated from the seeded random number generator,
+/// and mutated with probability `mutation_rate`. Returns the inputs
+/// violating the property, including inputs causing a panic.
+pub fn fuzz<G, P>(options: &FuzzOptions, mut generate: G, property: P) -> Vec<FuzzFailure>
+    where G: FnMut(&mut FuzzRng) -> Vec<u8>,
+          P: Fn(&FuzzCase) -> PropertyResult
+{
+    let mut rng = FuzzRng::new(options.seed);
+    let mut failures = vec![];
+    for iteration in 0..options.iterations {
+        let mut data = generate(&mut rng);
+        let mutated = rng.chance(options.mutation_rate);
+        if mutated {
+            data = mutate(&mut rng, &data);
+        }
+        let case = FuzzCase { data: data, mutated: mutated };
+        if let Err(message) = catch_panic(|| property(&case)) {
+            failures.push(FuzzFailure {
+                iteration: iteration,
+                case: case,
+                message: message,
+            });
+        }
+    }
+    failures
+}
+
+/// Check parsed records, which must be valid for unmutated inputs,
+/// and round-trip unchanged when valid.
+///
+/// The number of records read is capped, so readers yielding an
+/// error indefinitely do not loop forever.
+fn check_records<T, Iter, F>(case: &FuzzCase, iter: Iter, roundtrip: F) -> PropertyResult
+    where T: Valid + PartialEq + ::std::fmt::Debug,
+          Iter: Iterator<Item = ::util::Result<T>>,
+          F: Fn(&T) -> ::util::Result<Vec<T>>
+{
+    for (index, result) in iter.take(case.data.len() + 1).enumerate() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e)     => match case.mutated {
+                true  => continue,
+                false => return Err(format!("record {}: well-formed input failed to parse: {}", index, e)),
+            },
+        };
+        if !record.is_valid() {
+            match case.mutated {
+                true  => continue,
+                false => return Err(format!("record {}: well-formed input parsed as invalid record", index)),
+            }
+        }
+        match roundtrip(&record) {
+            Ok(ref v) if v.len() == 1 && v[0] == record => (),
+            Ok(v)  => return Err(format!("record {}: round-trip changed {:?} to {:?}", index, record, v)),
+            Err(e) => return Err(format!("record {}: round-trip failed: {}", index, e)),
+        }
+    }
+    Ok(())
+}
+
+// UNIPROT
+
+/// Check UniProt FASTA parsing: no panics, and valid records round-trip.
+#[cfg(all(feature = "uniprot", feature = "fasta"))]
+pub fn uniprot_fasta_property(case: &FuzzCase) -> PropertyResult {
+    use db::uniprot::low_level::{iterator_from_fasta, reference_iterator_to_fasta};
+
+    check_records(case, iterator_from_fasta(&case.data[..]), |record: &uniprot::Record| {
+        let mut writer = vec![];
+        reference_iterator_to_fasta(&mut writer, Some(record).into_iter())?;
+        iterator_from_fasta(&writer[..]).collect()
+    })
+}
+
+/// Check UniProt CSV parsing: no panics, and valid records round-trip.
+#[cfg(all(feature = "uniprot", feature = "csv"))]
+pub fn uniprot_csv_property(case: &FuzzCase) -> PropertyResult {
+    use db::uniprot::low_level::{iterator_from_csv, reference_iterator_to_csv};
+
+    check_records(case, iterator_from_csv(&case.data[..], b'\t'), |record: &uniprot::Record| {
+        let mut writer = vec![];
+        reference_iterator_to_csv(&mut writer, Some(record).into_iter(), b'\t')?;
+        iterator_from_csv(&writer[..], b'\t').collect()
+    })
+}
+
+// MASS SPECTRA
+
+/// Check MGF parsing: no panics, and valid spectra round-trip.
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+pub fn mgf_property(case: &FuzzCase, kind: MgfKind) -> PropertyResult {
+    use db::mass_spectra::low_level::{iterator_from_mgf, reference_iterator_to_mgf};
+
+    check_records(case, iterator_from_mgf(&case.data[..], kind), |record: &mass_spectra::Record| {
+        let mut writer = vec![];
+        reference_iterator_to_mgf(&mut writer, Some(record).into_iter(), kind)?;
+        iterator_from_mgf(&writer[..], kind).collect()
+    })
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catch_panic_test() {
+        assert_eq!(catch_panic(|| Ok(())), Ok(()));
+        assert_eq!(catch_panic(|| Err(String::from("x"))), Err(String::from("x")));
+        assert_eq!(catch_panic(|| panic!("boom")), Err(String::from("panicked: boom")));
+    }
+
+    #[test]
+    fn fuzz_test() {
+        let options = FuzzOptions { iterations: 20, ..FuzzOptions::default() };
+        let failures = fuzz(&options, |rng| vec![rng.next_u64() as u8], |case| {
+            match case.mutated {
+                true  => Err(String::from("mutated")),
+                false => Ok(()),
+            }
+        });
+        assert!(!failures.is_empty() && failures.len() < 20);
+        assert!(failures.iter().all(|f| f.case.mutated && f.message == "mutated"));
+        assert_eq!(failures, fuzz(&options, |rng| vec![rng.next_u64() as u8], |case| {
+            match case.mutated {
+                true  => Err(String::from("mutated")),
+                false => Ok(()),
+            }
+        }));
+    }
+
+    #[cfg(all(feature = "uniprot", feature = "fasta"))]
+    #[test]
+    fn uniprot_fasta_property_test() {
+        use fuzz::generate::uniprot_fasta;
+
+        let failures = fuzz(&FuzzOptions::default(), |rng| uniprot_fasta(rng, 5), uniprot_fasta_property);
+        assert_eq!(failures, vec![]);
+    }
+
+    #[cfg(all(feature = "uniprot", feature = "csv"))]
+    #[test]
+    fn uniprot_csv_property_test() {
+        use fuzz::generate::uniprot_csv;
+
+        let failures = fuzz(&FuzzOptions::default(), |rng| uniprot_csv(rng, 5), uniprot_csv_property);
+        assert_eq!(failures, vec![]);
+    }
+
+    #[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+    #[test]
+    fn mgf_property_test() {
+        use fuzz::generate::mgf;
+
+        for &kind in [MgfKind::MsConvert, MgfKind::Pava, MgfKind::Pwiz, MgfKind::FullMs].iter() {
+            let failures = fuzz(&FuzzOptions::default(), |rng| mgf(rng, 3, kind), |case| mgf_property(case, kind));
+            assert_eq!(failures, vec![]);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Abort-safe file writers.
+//!
+//! `AtomicFile` writes to a temporary file in the destination directory,
+//! and only replaces the destination once the export succeeds, so a
+//! failed export never leaves a truncated or corrupt output file.
+//! `AppendFile` appends to an existing file, and truncates the file to
+//! its original length unless the appended chunk is committed.
+
+use std::convert::AsRef;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use util::Result;
+
+/// Counter to generate unique temporary file names within a process.
+static TEMPORARY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the identifier of the current process.
+#[cfg(not(target_arch = "wasm32"))]
+#[inline]
+fn process_id() -> u32 {
+    process::id()
+}
+
+/// Get the identifier of the current process.
+///
+/// WASM has no processes, and `process::id` panics, so use a constant.
+#[cfg(target_arch = "wasm32")]
+#[inline]
+fn process_id() -> u32 {
+    0
+}
+
+/// Get a unique, hidden temporary path in the same directory as `path`.
+///
+/// The temporary file must be on the same filesystem as the destination
+/// for the rename to be atomic.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let count = TEMPORARY_COUNTER.fetch_add(1, Ordering::SeqCst);
+    let temporary = format!(".{}.{}.{}.tmp", name, process_id(), count);
+    path.with_file_name(temporary)
+}
+
+// ATOMIC
+
+/// Buffered file writer which replaces the destination on commit.
+///
+/// Data is written to a temporary file, which is renamed over the
+/// destination by `commit`. If the writer is dropped without being
+/// committed, the temporary file is removed and the destination is
+/// left unchanged.
+pub struct AtomicFile {
+    writer: Option<BufWriter<File>>,
+    temporary: PathBuf,
+    path: PathBuf,
+}
+
+impl AtomicFile {
+    /// Create new atomic writer to a destination path.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let temporary = temporary_path(&path);
+        let file = File::create(&temporary)?;
+        Ok(AtomicFile {
+            writer: Some(BufWriter::new(file)),
+            temporary: temporary,
+            path: path,
+        })
+    }
+
+    /// Get the destination path.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Flush and sync the written data, and replace the destination.
+    pub fn commit(mut self) -> Result<()> {
+        let writer = self.writer.take().expect("writer is only taken on commit");
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&self.temporary, &self.path)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn writer(&mut self) -> &mut BufWriter<File> {
+        self.writer.as_mut().expect("writer is only taken on commit")
+    }
+}
+
+impl Write for AtomicFile {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer().write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // A failed commit may also leave the temporary file behind.
+        drop(self.writer.take());
+        let _ = fs::remove_file(&self.temporary);
+    }
+}
+
+/// Atomically write a file from an export callback.
+///
+/// The destination is only replaced if the callback succeeds.
+pub fn write_atomic<P, F>(path: P, callback: F) -> Result<()>
+    where P: AsRef<Path>,
+          F: FnOnce(&mut AtomicFile) -> Result<()>
+{
+    let mut file = AtomicFile::create(path)?;
+    callback(&mut file)?;
+    file.commit()
+}
+
+// APPEND
+
+/// Buffered, append-only file writer which rolls back on failure.
+///
+/// The length of the file is stored on creation, and each chunk of
+/// data is appended and committed with `commit`. If the writer is
+/// dropped with uncommitted data, the file is truncated back to the
+/// length after the last commit, so the file only contains complete
+/// chunks.
+pub struct AppendFile {
+    writer: BufWriter<File>,
+    length: u64,
+}
+
+impl AppendFile {
+    /// Open a file for appending, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        let length = file.metadata()?.len();
+        Ok(AppendFile {
+            writer: BufWriter::new(file),
+            length: length,
+        })
+    }
+
+    /// Get the file length after the last commit.
+    #[inline]
+    pub fn committed_length(&self) -> u64 {
+        self.length
+    }
+
+    /// Flush and sync the appended data, marking it as complete.
+    pub fn commit(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()?;
+        self.length = self.writer.get_ref().metadata()?.len();
+        Ok(())
+    }
+
+    /// Discard data appended since the last commit.
+    pub fn rollback(&mut self) -> Result<()> {
+        // Flush before truncating, so buffered data is not written
+        // past the truncated length later.
+        let flushed = self.writer.flush();
+        self.writer.get_ref().set_len(self.length)?;
+        Ok(flushed?)
+    }
+}
+
+impl Write for AppendFile {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for AppendFile {
+    fn drop(&mut self) {
+        let _ = self.rollback();
+    }
+}
+
+/// Append a chunk to a file from an export callback.
+///
+/// The chunk is only kept if the callback succeeds, otherwise, the
+/// file is truncated to its original length.
+pub fn append_atomic<P, F>(path: P, callback: F) -> Result<()>
+    where P: AsRef<Path>,
+          F: FnOnce(&mut AppendFile) -> Result<()>
+{
+    let mut file = AppendFile::open(path)?;
+    callback(&mut file)?;
+    file.commit()
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use super::*;
+    use util::ErrorKind;
+
+    #[test]
+    fn write_atomic_test() {
+        let path = env::temp_dir().join("bdb_write_atomic_test.txt");
+        fs::write(&path, b"original").unwrap();
+
+        // Failed exports leave the destination unchanged.
+        let result = write_atomic(&path, |w| {
+            w.write_all(b"partial")?;
+            Err(From::from(ErrorKind::InvalidRecord))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"original");
+
+        write_atomic(&path, |w| Ok(w.write_all(b"complete")?)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"complete");
+
+        // No temporary files are left behind.
+        let file = AtomicFile::create(&path).unwrap();
+        let temporary = file.temporary.clone();
+        assert!(temporary.exists());
+        drop(file);
+        assert!(!temporary.exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn append_atomic_test() {
+        let path = env::temp_dir().join("bdb_append_atomic_test.txt");
+        let _ = fs::remove_file(&path);
+
+        append_atomic(&path, |w| Ok(w.write_all(b"chunk1\n")?)).unwrap();
+        let result = append_atomic(&path, |w| {
+            w.write_all(b"partial")?;
+            Err(From::from(ErrorKind::InvalidRecord))
+        });
+        assert!(result.is_err());
+        append_atomic(&path, |w| Ok(w.write_all(b"chunk2\n")?)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"chunk1\nchunk2\n");
+
+        let mut file = AppendFile::open(&path).unwrap();
+        file.write_all(b"chunk3\n").unwrap();
+        file.commit().unwrap();
+        file.write_all(b"partial").unwrap();
+        file.rollback().unwrap();
+        assert_eq!(file.committed_length(), 21);
+        drop(file);
+        assert_eq!(fs::read(&path).unwrap(), b"chunk1\nchunk2\nchunk3\n");
+        fs::remove_file(&path).unwrap();
+    }
+}
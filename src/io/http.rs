@@ -0,0 +1,405 @@
+//! Record and replay layer for the HTTP clients.
+//!
+//! In record mode, each successful response body is saved to a fixture
+//! directory, keyed by the request URL. In replay mode, the responses
+//! are read from the fixture directory without network access, so code
+//! using the clients may be tested offline, and against known responses.
+//! Client and server error responses fail, and are never saved.
+//!
+//! The mode is shared by all clients, and defaults to online requests.
+//!
+//! With a cache directory, online requests store each response with
+//! its `ETag` and `Last-Modified` validators, and repeated requests
+//! send conditional headers, reusing the cached response if the
+//! server responds with `304 Not Modified`.
+//!
+//! Requests with a cancellation token fail with `ErrorKind::Cancelled`
+//! once cancelled, including while reading the response body.
+
+use reqwest::{self, Client, Response, StatusCode};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+#[cfg(test)]
+use std::sync::Mutex;
+
+use concurrency::CancellationToken;
+use util::{Bytes, Error, ErrorKind, Result};
+use super::atomic::write_atomic;
+
+/// Readable body of an HTTP response.
+pub type ResponseBody = Box<Read + Send>;
+
+/// Network mode for the HTTP clients.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClientMode {
+    /// Send requests over the network.
+    Online,
+    /// Send requests over the network, saving the responses to a directory.
+    Record(PathBuf),
+    /// Read the saved responses from a directory, without network access.
+    ///
+    /// Requests without a saved response fail with a not found error.
+    Replay(PathBuf),
+}
+
+impl Default for ClientMode {
+    #[inline]
+    fn default() -> Self {
+        ClientMode::Online
+    }
+}
+
+lazy_static! {
+    static ref CLIENT_MODE: RwLock<ClientMode> = RwLock::new(ClientMode::Online);
+    static ref CACHE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+#[cfg(test)]
+lazy_static! {
+    /// Serialize tests changing the client mode, since tests run in parallel.
+    pub(crate) static ref CLIENT_MODE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Get the network mode for the HTTP clients.
+pub fn client_mode() -> ClientMode {
+    CLIENT_MODE.read().unwrap().clone()
+}
+
+/// Set the network mode for the HTTP clients.
+pub fn set_client_mode(mode: ClientMode) {
+    *CLIENT_MODE.write().unwrap() = mode;
+}
+
+/// Get the directory caching responses for conditional requests.
+pub fn cache_dir() -> Option<PathBuf> {
+    CACHE_DIR.read().unwrap().clone()
+}
+
+/// Set the directory caching responses for conditional requests.
+///
+/// If `None`, responses are not cached.
+pub fn set_cache_dir(dir: Option<PathBuf>) {
+    *CACHE_DIR.write().unwrap() = dir;
+}
+
+// FIXTURES
+
+/// Hash the request URL with 64-bit FNV-1a, a stable hash for fixture names.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Get the path to the saved response for a request URL.
+///
+/// The URL is saved alongside the response, with the `url` extension.
+pub fn fixture_path<P: AsRef<Path>>(dir: P, url: &str) -> PathBuf {
+    dir.as_ref().join(format!("{:016x}.response", fnv1a(url.as_bytes())))
+}
+
+/// Save a response body to the fixture directory.
+fn record(dir: &Path, url: &str, body: &[u8]) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|e| Error::from(e).with_path(dir))?;
+    let path = fixture_path(dir, url);
+    fs::write(&path, body).map_err(|e| Error::from(e).with_path(&path))?;
+    let path = path.with_extension("url");
+    fs::write(&path, url).map_err(|e| Error::from(e).with_path(&path))
+}
+
+/// Read a response body from the fixture directory.
+fn replay(dir: &Path, url: &str) -> Result<Bytes> {
+    let path = fixture_path(dir, url);
+    fs::read(&path).map_err(|e| Error::from(e).with_path(&path))
+}
+
+// CACHE
+
+/// Validators to check if a cached response is up-to-date.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Get the validators from the response headers.
+    fn from_response(response: &Response) -> Self {
+        let header = |name| response.headers().get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        Validators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Check if the response has no validators, and cannot be revalidated.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Parse the validators from the cache metadata.
+    fn parse(text: &str) -> Self {
+        let mut validators = Validators::default();
+        for line in text.lines() {
+            if let Some(value) = line.strip_prefix("ETag: ") {
+                validators.etag = Some(String::from(value));
+            } else if let Some(value) = line.strip_prefix("Last-Modified: ") {
+                validators.last_modified = Some(String::from(value));
+            }
+        }
+        validators
+    }
+
+    /// Serialize the validators to the cache metadata.
+    fn serialize(&self) -> String {
+        let mut text = String::new();
+        if let Some(ref etag) = self.etag {
+            text += &format!("ETag: {}\n", etag);
+        }
+        if let Some(ref last_modified) = self.last_modified {
+            text += &format!("Last-Modified: {}\n", last_modified);
+        }
+        text
+    }
+}
+
+/// Get the paths to the cached response body and validators for a URL.
+fn cache_paths(dir: &Path, url: &str) -> (PathBuf, PathBuf) {
+    let body = dir.join(format!("{:016x}.body", fnv1a(url.as_bytes())));
+    let meta = body.with_extension("meta");
+    (body, meta)
+}
+
+/// Send a conditional GET request, reusing the cached response if unmodified.
+fn get_cached(dir: &Path, url: &str) -> Result<ResponseBody> {
+    let (body_path, meta_path) = cache_paths(dir, url);
+    let cached = match (body_path.exists(), fs::read_to_string(&meta_path)) {
+        (true, Ok(text)) => Validators::parse(&text),
+        _                => Validators::default(),
+    };
+
+    let mut request = Client::new().get(url);
+    if let Some(ref etag) = cached.etag {
+        request = request.header(IF_NONE_MATCH, etag.as_str());
+    }
+    if let Some(ref last_modified) = cached.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+    }
+    let response = request.send()?;
+    log_info!("HTTP response: {}", response.status());
+
+    if response.status() == StatusCode::NOT_MODIFIED && !cached.is_empty() {
+        log_info!("HTTP cache: {}", body_path.display());
+    } else {
+        let mut response = response.error_for_status()?;
+        let validators = Validators::from_response(&response);
+        if !response.status().is_success() || validators.is_empty() {
+            return Ok(Box::new(response));
+        }
+        fs::create_dir_all(dir).map_err(|e| Error::from(e).with_path(dir))?;
+        write_atomic(&body_path, |w| { io::copy(&mut response, w)?; Ok(()) })
+            .map_err(|e| e.with_path(&body_path))?;
+        write_atomic(&meta_path, |w| { w.write_all(validators.serialize().as_bytes())?; Ok(()) })
+            .map_err(|e| e.with_path(&meta_path))?;
+    }
+
+    let file = File::open(&body_path).map_err(|e| Error::from(e).with_path(&body_path))?;
+    Ok(Box::new(file))
+}
+
+// REQUEST
+
+/// Send a GET request over the network, using the cache if set.
+///
+/// Client and server error responses fail with an HTTP error.
+fn get_online(url: &str) -> Result<ResponseBody> {
+    match cache_dir() {
+        Some(dir) => get_cached(&dir, url),
+        None      => {
+            let response = reqwest::get(url)?;
+            log_info!("HTTP response: {}", response.status());
+            Ok(Box::new(response.error_for_status()?))
+        },
+    }
+}
+
+/// Send a GET request, according to the client mode.
+pub(crate) fn get(url: &str) -> Result<ResponseBody> {
+    match client_mode() {
+        ClientMode::Online      => get_online(url),
+        ClientMode::Record(dir) => {
+            let mut response = get_online(url)?;
+            let mut body = vec![];
+            response.read_to_end(&mut body)?;
+            record(&dir, url, &body)?;
+            Ok(Box::new(Cursor::new(body)))
+        },
+        ClientMode::Replay(dir) => {
+            log_info!("HTTP replay: {}", fixture_path(&dir, url).display());
+            Ok(Box::new(Cursor::new(replay(&dir, url)?)))
+        },
+    }
+}
+
+/// Response body checking a cancellation token before each read.
+struct CancellableBody {
+    inner: ResponseBody,
+    cancel: CancellationToken,
+}
+
+impl Read for CancellableBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.cancel.is_cancelled() {
+            true  => Err(io::Error::other(Error::from(ErrorKind::Cancelled))),
+            false => self.inner.read(buf),
+        }
+    }
+}
+
+/// Send a GET request according to the client mode, stopping early if cancelled.
+pub(crate) fn get_with_cancel(url: &str, cancel: &CancellationToken) -> Result<ResponseBody> {
+    cancel.check()?;
+    let response = get(url)?;
+    Ok(Box::new(CancellableBody { inner: response, cancel: cancel.clone() }))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use super::*;
+
+    #[test]
+    fn fixture_test() {
+        let dir = env::temp_dir().join("bdb_http_fixture_test");
+        let url = "https://www.uniprot.org:443/uniprot/?query=id%3AP46406";
+        let path = fixture_path(&dir, url);
+        assert_eq!(path, fixture_path(&dir, url));
+        assert_ne!(path, fixture_path(&dir, "https://www.uniprot.org"));
+
+        record(&dir, url, b"Entry\tLength\n").unwrap();
+        assert_eq!(replay(&dir, url).unwrap(), b"Entry\tLength\n");
+        assert_eq!(fs::read_to_string(path.with_extension("url")).unwrap(), url);
+
+        let err = replay(&dir, "https://www.uniprot.org").err().unwrap();
+        assert_eq!(err.path(), Some(fixture_path(&dir, "https://www.uniprot.org").as_path()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn validators_test() {
+        let validators = Validators {
+            etag: Some(String::from("\"abc\"")),
+            last_modified: Some(String::from("Wed, 21 Oct 2015 07:28:00 GMT")),
+        };
+        let text = validators.serialize();
+        assert_eq!(text, "ETag: \"abc\"\nLast-Modified: Wed, 21 Oct 2015 07:28:00 GMT\n");
+        assert_eq!(Validators::parse(&text), validators);
+        assert!(Validators::parse("").is_empty());
+    }
+
+    #[test]
+    fn get_cached_test() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::thread;
+
+        // Serve a response with an ETag, then a 304 for a matching request.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/proteome", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let mut conditional = vec![];
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut is_conditional = false;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    is_conditional |= line.to_lowercase().starts_with("if-none-match: \"abc\"");
+                    line.clear();
+                }
+                let response: &[u8] = match is_conditional {
+                    true  => b"HTTP/1.1 304 Not Modified\r\nETag: \"abc\"\r\nConnection: close\r\n\r\n",
+                    false => b"HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+                };
+                (&stream).write_all(response).unwrap();
+                conditional.push(is_conditional);
+            }
+            conditional
+        });
+
+        let dir = env::temp_dir().join("bdb_http_cache_test");
+        let _ = fs::remove_dir_all(&dir);
+        for _ in 0..2 {
+            let mut body = String::new();
+            get_cached(&dir, &url).unwrap().read_to_string(&mut body).unwrap();
+            assert_eq!(body, "hello");
+        }
+        assert_eq!(server.join().unwrap(), vec![false, true]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn error_status_test() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::thread;
+
+        // Serve error responses, which must not be recorded or cached.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/proteome", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let response = b"HTTP/1.1 404 Not Found\r\nETag: \"abc\"\r\nContent-Length: 9\r\nConnection: close\r\n\r\nnot found";
+                (&stream).write_all(response).unwrap();
+            }
+        });
+
+        let dir = env::temp_dir().join("bdb_http_error_status_test");
+        let _ = fs::remove_dir_all(&dir);
+        {
+            let _lock = CLIENT_MODE_LOCK.lock().unwrap();
+            set_client_mode(ClientMode::Record(dir.clone()));
+            let result = get(&url);
+            set_client_mode(ClientMode::Online);
+            assert!(result.is_err());
+        }
+        assert!(!fixture_path(&dir, &url).exists());
+
+        assert!(get_cached(&dir, &url).is_err());
+        assert!(!cache_paths(&dir, &url).0.exists());
+        server.join().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cancellable_body_test() {
+        let cancel = CancellationToken::new();
+        let inner: ResponseBody = Box::new(Cursor::new(b"hello".to_vec()));
+        let mut body = CancellableBody { inner: inner, cancel: cancel.clone() };
+        let mut buf = [0u8; 2];
+        assert_eq!(body.read(&mut buf).unwrap(), 2);
+
+        cancel.cancel();
+        let err = Error::from(body.read(&mut buf).unwrap_err());
+        match err.kind() {
+            &ErrorKind::Cancelled => (),
+            kind                  => panic!("unexpected error: {:?}", kind),
+        }
+        assert!(get_with_cancel("https://www.uniprot.org", &cancel).is_err());
+    }
+}
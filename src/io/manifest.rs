@@ -0,0 +1,377 @@
+//! Integrity manifests for exported files.
+//!
+//! A manifest stores the number of exported records, the total number
+//! of bytes, and the SHA-256 digest of each fixed-size chunk of the
+//! exported data. The manifest is written to a sidecar file next to
+//! the export, and is used to verify the file is complete and has not
+//! been modified since export, and to locate the first modified chunk.
+//!
+//! The manifest is a tab-delimited text file:
+//!
+//! ```text
+//! bdb-manifest    1
+//! records         2
+//! bytes           1298
+//! chunk_size      1048576
+//! sha256          0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8
+//! ```
+
+use sha2::{Digest, Sha256};
+use std::convert::AsRef;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use util::{Error, ErrorKind, Result};
+use super::atomic::{write_atomic, AtomicFile};
+
+/// Identifier and format version on the first line of a manifest.
+const MANIFEST_HEADER: &'static str = "bdb-manifest\t1";
+
+/// Default size of the chunks to digest, in bytes.
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// Get the path of the sidecar manifest for a file.
+pub fn manifest_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let name = path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.manifest", name))
+}
+
+// MANIFEST
+
+/// Integrity manifest for an exported file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Manifest {
+    /// Number of exported records.
+    pub records: usize,
+    /// Total number of exported bytes.
+    pub bytes: u64,
+    /// Size of each chunk, in bytes. The last chunk may be shorter.
+    pub chunk_size: usize,
+    /// Lowercase, hex-encoded SHA-256 digest of each chunk.
+    pub chunks: Vec<String>,
+}
+
+impl Manifest {
+    /// Compute the manifest for data from a reader.
+    pub fn from_data<T: Read>(reader: &mut T, records: usize, chunk_size: usize)
+        -> Result<Self>
+    {
+        let mut writer = ManifestWriter::new(io::sink(), chunk_size);
+        io::copy(reader, &mut writer)?;
+        Ok(writer.finish(records).1)
+    }
+
+    /// Save manifest to stream.
+    pub fn to_stream<T: Write>(&self, writer: &mut T) -> Result<()> {
+        writeln!(writer, "{}", MANIFEST_HEADER)?;
+        writeln!(writer, "records\t{}", self.records)?;
+        writeln!(writer, "bytes\t{}", self.bytes)?;
+        writeln!(writer, "chunk_size\t{}", self.chunk_size)?;
+        for chunk in self.chunks.iter() {
+            writeln!(writer, "sha256\t{}", chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Save manifest to file, atomically.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        write_atomic(path, |file| self.to_stream(file)).map_err(|e| e.with_path(path))
+    }
+
+    /// Load manifest from stream.
+    pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<Self> {
+        let mut lines = reader.lines();
+        let header = none_to_error!(lines.next(), UnexpectedEof);
+        bool_to_error!(header? == MANIFEST_HEADER, InvalidInput);
+
+        let mut manifest = Manifest::default();
+        for line in lines {
+            let line = line?;
+            let mut fields = line.splitn(2, '\t');
+            let key = fields.next().unwrap_or("");
+            let value = none_to_error!(fields.next(), InvalidInput);
+            match key {
+                "records"       => manifest.records = value.parse()?,
+                "bytes"         => manifest.bytes = value.parse()?,
+                "chunk_size"    => manifest.chunk_size = value.parse()?,
+                "sha256"        => {
+                    let is_digest = value.len() == 64 && value.bytes().all(|c| {
+                        c.is_ascii_digit() || (b'a'..=b'f').contains(&c)
+                    });
+                    bool_to_error!(is_digest, InvalidInput);
+                    manifest.chunks.push(String::from(value));
+                },
+                _               => return Err(From::from(ErrorKind::InvalidInput)),
+            }
+        }
+        bool_to_error!(manifest.chunk_size > 0, InvalidInput);
+        Ok(manifest)
+    }
+
+    /// Load manifest from file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let load = || -> Result<Self> {
+            let mut reader = BufReader::new(File::open(path)?);
+            Manifest::from_stream(&mut reader)
+        };
+        load().map_err(|e| e.with_path(path))
+    }
+
+    /// Verify the data from a reader matches the manifest.
+    ///
+    /// Returns `ChecksumMismatch` with the index of the first chunk
+    /// which differs, including any chunk missing or added at the end
+    /// of the data.
+    pub fn verify<T: Read>(&self, reader: &mut T) -> Result<()> {
+        let actual = Manifest::from_data(reader, self.records, self.chunk_size)?;
+        let mismatch = self.chunks.iter()
+            .zip(actual.chunks.iter())
+            .position(|(x, y)| x != y);
+        let index = match mismatch {
+            Some(index) => index,
+            None if self.chunks.len() != actual.chunks.len() => {
+                self.chunks.len().min(actual.chunks.len())
+            },
+            None if self.bytes != actual.bytes => self.chunks.len().saturating_sub(1),
+            None => return Ok(()),
+        };
+        Err(From::from(ErrorKind::ChecksumMismatch(index)))
+    }
+}
+
+// WRITER
+
+/// Writer which computes the manifest of the data as it is written.
+pub struct ManifestWriter<T: Write> {
+    writer: T,
+    hasher: Sha256,
+    chunk_size: usize,
+    /// Number of bytes in the current chunk.
+    chunk_length: usize,
+    bytes: u64,
+    chunks: Vec<String>,
+}
+
+impl<T: Write> ManifestWriter<T> {
+    /// Create new manifest writer, digesting chunks of `chunk_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk size is 0.
+    pub fn new(writer: T, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        ManifestWriter {
+            writer: writer,
+            hasher: Sha256::new(),
+            chunk_size: chunk_size,
+            chunk_length: 0,
+            bytes: 0,
+            chunks: vec![],
+        }
+    }
+
+    /// Get the number of bytes written.
+    #[inline]
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Store the digest of the current chunk, and start a new chunk.
+    fn finish_chunk(&mut self) {
+        let hasher = mem::replace(&mut self.hasher, Sha256::new());
+        self.chunks.push(format!("{:x}", hasher.result()));
+        self.chunk_length = 0;
+    }
+
+    /// Consume the writer, and return the inner writer and the manifest.
+    pub fn finish(mut self, records: usize) -> (T, Manifest) {
+        if self.chunk_length > 0 {
+            self.finish_chunk();
+        }
+        let manifest = Manifest {
+            records: records,
+            bytes: self.bytes,
+            chunk_size: self.chunk_size,
+            chunks: self.chunks,
+        };
+        (self.writer, manifest)
+    }
+}
+
+impl<T: Write> Write for ManifestWriter<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Only digest the bytes accepted by the inner writer.
+        let count = self.writer.write(buf)?;
+        let mut data = &buf[..count];
+        while !data.is_empty() {
+            let length = data.len().min(self.chunk_size - self.chunk_length);
+            self.hasher.input(&data[..length]);
+            self.chunk_length += length;
+            if self.chunk_length == self.chunk_size {
+                self.finish_chunk();
+            }
+            data = &data[length..];
+        }
+        self.bytes += count as u64;
+        Ok(count)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// EXPORT
+
+/// Atomically write a file and its sidecar manifest from an export callback.
+///
+/// The callback returns the number of exported records. The manifest
+/// is written once the file is committed, so a file without a manifest
+/// may be incomplete.
+pub fn write_with_manifest<P, F>(path: P, chunk_size: usize, callback: F)
+    -> Result<Manifest>
+    where P: AsRef<Path>,
+          F: FnOnce(&mut ManifestWriter<&mut AtomicFile>) -> Result<usize>
+{
+    let path = path.as_ref();
+    let mut manifest = None;
+    write_atomic(path, |file| {
+        let mut writer = ManifestWriter::new(file, chunk_size);
+        let records = callback(&mut writer)?;
+        manifest = Some(writer.finish(records).1);
+        Ok(())
+    }).map_err(|e| e.with_path(path))?;
+
+    let manifest = manifest.expect("manifest is set if the export succeeds");
+    manifest.to_file(manifest_path(path))?;
+    Ok(manifest)
+}
+
+/// Verify a file against its sidecar manifest.
+///
+/// Returns the manifest if the file is complete and unmodified.
+pub fn verify_file<P: AsRef<Path>>(path: P) -> Result<Manifest> {
+    let path = path.as_ref();
+    let manifest = Manifest::from_file(manifest_path(path))?;
+    let verify = || -> Result<()> {
+        let mut reader = BufReader::new(File::open(path)?);
+        manifest.verify(&mut reader)
+    };
+    verify().map_err(|e: Error| e.with_path(path))?;
+    Ok(manifest)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use super::*;
+
+    /// SHA-256 digest of "abc".
+    const ABC_SHA256: &'static str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    /// Get the mismatched chunk from a verification error.
+    fn mismatch(result: Result<()>) -> Option<usize> {
+        let err = result.err()?;
+        let err = match err.kind() {
+            ErrorKind::Path(_, ref err) => err,
+            _                           => &err,
+        };
+        match err.kind() {
+            ErrorKind::ChecksumMismatch(index) => Some(*index),
+            _                                  => None,
+        }
+    }
+
+    #[test]
+    fn manifest_writer_test() {
+        let mut w = ManifestWriter::new(vec![], 3);
+        w.write_all(b"ab").unwrap();
+        w.write_all(b"cabcab").unwrap();
+        assert_eq!(w.bytes(), 8);
+        let (data, manifest) = w.finish(2);
+        assert_eq!(data, b"abcabcab".to_vec());
+        assert_eq!(manifest.records, 2);
+        assert_eq!(manifest.bytes, 8);
+        assert_eq!(manifest.chunk_size, 3);
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.chunks[0], ABC_SHA256);
+        assert_eq!(manifest.chunks[1], ABC_SHA256);
+        assert_ne!(manifest.chunks[2], ABC_SHA256);
+
+        let (_, manifest) = ManifestWriter::new(vec![], 3).finish(0);
+        assert_eq!(manifest.bytes, 0);
+        assert!(manifest.chunks.is_empty());
+    }
+
+    #[test]
+    fn manifest_stream_test() {
+        let manifest = Manifest::from_data(&mut Cursor::new(b"abcabc"), 2, 3).unwrap();
+        let mut w = vec![];
+        manifest.to_stream(&mut w).unwrap();
+        let text = format!("bdb-manifest\t1\nrecords\t2\nbytes\t6\nchunk_size\t3\nsha256\t{0}\nsha256\t{0}\n", ABC_SHA256);
+        assert_eq!(String::from_utf8(w).unwrap(), text);
+        assert_eq!(Manifest::from_stream(&mut Cursor::new(text.as_bytes())).unwrap(), manifest);
+
+        assert!(Manifest::from_stream(&mut Cursor::new(b"")).is_err());
+        assert!(Manifest::from_stream(&mut Cursor::new(b"bdb-manifest\t2\n")).is_err());
+        assert!(Manifest::from_stream(&mut Cursor::new(b"bdb-manifest\t1\nchunk_size\t0\n")).is_err());
+        assert!(Manifest::from_stream(&mut Cursor::new(b"bdb-manifest\t1\nchunk_size\t3\nsha256\tabc\n")).is_err());
+        assert!(Manifest::from_stream(&mut Cursor::new(b"bdb-manifest\t1\nchunk_size\t3\nunknown\t1\n")).is_err());
+    }
+
+    #[test]
+    fn manifest_verify_test() {
+        let manifest = Manifest::from_data(&mut Cursor::new(b"abcdefgh"), 1, 3).unwrap();
+        assert!(manifest.verify(&mut Cursor::new(b"abcdefgh")).is_ok());
+        // modified
+        assert_eq!(mismatch(manifest.verify(&mut Cursor::new(b"abcdXfgh"))), Some(1));
+        // truncated
+        assert_eq!(mismatch(manifest.verify(&mut Cursor::new(b"abcdefg"))), Some(2));
+        assert_eq!(mismatch(manifest.verify(&mut Cursor::new(b"abc"))), Some(1));
+        // extended
+        assert_eq!(mismatch(manifest.verify(&mut Cursor::new(b"abcdefghi"))), Some(2));
+        let manifest = Manifest::from_data(&mut Cursor::new(b"abcdef"), 1, 3).unwrap();
+        assert_eq!(mismatch(manifest.verify(&mut Cursor::new(b"abcdefg"))), Some(2));
+    }
+
+    #[test]
+    fn write_with_manifest_test() {
+        let path = env::temp_dir().join("bdb_write_with_manifest_test.txt");
+        let sidecar = manifest_path(&path);
+        assert_eq!(sidecar.file_name().unwrap(), "bdb_write_with_manifest_test.txt.manifest");
+
+        let manifest = write_with_manifest(&path, 4, |w| {
+            w.write_all(b"record1\nrecord2\n")?;
+            Ok(2)
+        }).unwrap();
+        assert_eq!(manifest.records, 2);
+        assert_eq!(manifest.bytes, 16);
+        assert_eq!(manifest.chunks.len(), 4);
+        assert_eq!(verify_file(&path).unwrap(), manifest);
+
+        fs::write(&path, b"record1\nrecord3\n").unwrap();
+        let err = verify_file(&path).unwrap_err();
+        assert_eq!(err.path(), Some(path.as_path()));
+        assert_eq!(mismatch(verify_file(&path).map(|_| ())), Some(3));
+
+        fs::write(&path, b"record1\n").unwrap();
+        assert_eq!(mismatch(verify_file(&path).map(|_| ())), Some(2));
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&sidecar).unwrap();
+        assert!(verify_file(&path).is_err());
+    }
+}
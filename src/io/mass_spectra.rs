@@ -23,9 +23,11 @@ use std::convert::AsRef;
 use std::io::{BufRead, Write};
 use std::path::Path;
 
+#[cfg(feature = "manifest")]
+use io::manifest::{write_with_manifest, Manifest, DEFAULT_CHUNK_SIZE};
 use db::mass_spectra::RecordList;
 use traits::*;
-use util::{Bytes, Result};
+use util::{Bytes, ExportMetadata, Result};
 
 /// Reader/writer for mass spectral FullMs MGF records.
 #[cfg(feature = "mgf")]
@@ -57,6 +59,21 @@ impl FullMsMgf {
         list.to_mgf_file(path, MgfKind::FullMs)
     }
 
+    /// Save mass spectral records to file, preceded by a provenance metadata block.
+    #[inline(always)]
+    pub fn to_file_with_metadata<P: AsRef<Path>>(list: &RecordList, path: P, metadata: &ExportMetadata) -> Result<()> {
+        list.to_mgf_file_with_metadata(path, MgfKind::FullMs, metadata)
+    }
+
+    /// Save mass spectral records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_mgf(writer, MgfKind::FullMs)?;
+            Ok(list.len())
+        })
+    }
+
     /// Load mass spectral records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -112,6 +129,21 @@ impl MsConvertMgf {
         list.to_mgf_file(path, MgfKind::MsConvert)
     }
 
+    /// Save mass spectral records to file, preceded by a provenance metadata block.
+    #[inline(always)]
+    pub fn to_file_with_metadata<P: AsRef<Path>>(list: &RecordList, path: P, metadata: &ExportMetadata) -> Result<()> {
+        list.to_mgf_file_with_metadata(path, MgfKind::MsConvert, metadata)
+    }
+
+    /// Save mass spectral records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_mgf(writer, MgfKind::MsConvert)?;
+            Ok(list.len())
+        })
+    }
+
     /// Load mass spectral records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -167,6 +199,21 @@ impl PavaMgf {
         list.to_mgf_file(path, MgfKind::Pava)
     }
 
+    /// Save mass spectral records to file, preceded by a provenance metadata block.
+    #[inline(always)]
+    pub fn to_file_with_metadata<P: AsRef<Path>>(list: &RecordList, path: P, metadata: &ExportMetadata) -> Result<()> {
+        list.to_mgf_file_with_metadata(path, MgfKind::Pava, metadata)
+    }
+
+    /// Save mass spectral records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_mgf(writer, MgfKind::Pava)?;
+            Ok(list.len())
+        })
+    }
+
     /// Load mass spectral records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -222,6 +269,21 @@ impl PwizMgf {
         list.to_mgf_file(path, MgfKind::Pwiz)
     }
 
+    /// Save mass spectral records to file, preceded by a provenance metadata block.
+    #[inline(always)]
+    pub fn to_file_with_metadata<P: AsRef<Path>>(list: &RecordList, path: P, metadata: &ExportMetadata) -> Result<()> {
+        list.to_mgf_file_with_metadata(path, MgfKind::Pwiz, metadata)
+    }
+
+    /// Save mass spectral records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_mgf(writer, MgfKind::Pwiz)?;
+            Ok(list.len())
+        })
+    }
+
     /// Load mass spectral records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -8,6 +8,14 @@
 //! of code complexity, look at the low-level APIs re-exported in each
 //! model under `db`.
 
+pub mod atomic;
+
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "manifest")]
+pub mod manifest;
+
 #[cfg(feature = "mass_spectrometry")]
 pub mod mass_spectra;
 
@@ -17,8 +25,14 @@ pub mod peptide_search_matches;
 #[cfg(feature = "pdb")]
 pub mod pdb;
 
+pub mod pipe;
+
+pub mod rename;
+
 #[cfg(feature = "sra")]
 pub mod sra;
 
+pub mod store;
+
 #[cfg(feature = "uniprot")]
 pub mod uniprot;
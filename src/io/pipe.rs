@@ -0,0 +1,458 @@
+//! Framed record streams, to pipe records between processes.
+//!
+//! A stream starts with the `PIPE_MAGIC` signature, followed by one
+//! frame per record. Each frame contains a format tag, a record type
+//! tag, and the serialized record, prefixed by its length:
+//!
+//! ```text
+//! BDBPIPE1
+//! [format: u8][type: u8][length: u32 LE][payload: length bytes]
+//! ...
+//! ```
+//!
+//! The stream ends at the end of the input, on a frame boundary. For
+//! example, a process may write UniProt records as FASTA frames to
+//! stdout with `PipeWriter::stdout()`, which are read by the next
+//! process with `PipeReader::stdin()`.
+
+use std::io::{self, BufReader, BufWriter, Read, Stdin, Stdout, Write};
+use std::marker::PhantomData;
+
+use util::{read_bytes, read_u8, write_bytes, Bytes, ErrorKind, Result};
+
+/// Signature at the start of every framed record stream.
+pub const PIPE_MAGIC: &'static [u8] = b"BDBPIPE1";
+
+/// Serialization format of a frame payload.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PipeFormat {
+    /// Tab-delimited text, with a header line.
+    Csv = 1,
+    /// FASTA.
+    Fasta = 2,
+    /// FASTQ.
+    Fastq = 3,
+    /// MGF, with the MSConvert flavor.
+    Mgf = 4,
+    /// XML.
+    Xml = 5,
+}
+
+impl PipeFormat {
+    /// Get the format from the frame tag.
+    pub fn from_tag(tag: u8) -> Result<PipeFormat> {
+        match tag {
+            1 => Ok(PipeFormat::Csv),
+            2 => Ok(PipeFormat::Fasta),
+            3 => Ok(PipeFormat::Fastq),
+            4 => Ok(PipeFormat::Mgf),
+            5 => Ok(PipeFormat::Xml),
+            _ => Err(From::from(ErrorKind::InvalidEnumeration)),
+        }
+    }
+}
+
+/// Record model of a frame payload.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PipeType {
+    /// UniProt protein record.
+    UniProt = 1,
+    /// Mass spectrum (scan) record.
+    MassSpectrum = 2,
+    /// Nucleotide sequence record.
+    Nucleotide = 3,
+    /// SRA sequencing read record.
+    Sra = 4,
+    /// SRM/PRM transition record.
+    Transition = 5,
+}
+
+impl PipeType {
+    /// Get the record type from the frame tag.
+    pub fn from_tag(tag: u8) -> Result<PipeType> {
+        match tag {
+            1 => Ok(PipeType::UniProt),
+            2 => Ok(PipeType::MassSpectrum),
+            3 => Ok(PipeType::Nucleotide),
+            4 => Ok(PipeType::Sra),
+            5 => Ok(PipeType::Transition),
+            _ => Err(From::from(ErrorKind::InvalidEnumeration)),
+        }
+    }
+}
+
+/// Single frame of a record stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Serialization format of the payload.
+    pub format: PipeFormat,
+    /// Record model of the payload.
+    pub record_type: PipeType,
+    /// Serialized record.
+    pub payload: Bytes,
+}
+
+/// Serialize records to and from frame payloads.
+pub trait Pipe: Sized {
+    /// Get the record type tag of the model.
+    fn pipe_type() -> PipeType;
+
+    /// Export model to a frame payload.
+    ///
+    /// Returns an `InvalidInput` error if the model does not support the format.
+    fn to_pipe_bytes(&self, format: PipeFormat) -> Result<Bytes>;
+
+    /// Import model from a frame payload.
+    ///
+    /// Returns an `InvalidInput` error if the model does not support the format.
+    fn from_pipe_bytes(bytes: &[u8], format: PipeFormat) -> Result<Self>;
+}
+
+// WRITER
+
+/// Writer of framed record streams.
+pub struct PipeWriter<T: Write> {
+    writer: T,
+}
+
+impl<T: Write> PipeWriter<T> {
+    /// Create new pipe writer, and write the stream signature.
+    pub fn new(mut writer: T) -> Result<Self> {
+        writer.write_all(PIPE_MAGIC)?;
+        Ok(PipeWriter {
+            writer: writer,
+        })
+    }
+
+    /// Write a single frame.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        self.writer.write_all(&[frame.format as u8, frame.record_type as u8])?;
+        write_bytes(&mut self.writer, &frame.payload)
+    }
+
+    /// Serialize and write a record to a frame.
+    pub fn write_record<R: Pipe>(&mut self, record: &R, format: PipeFormat) -> Result<()> {
+        self.write_frame(&Frame {
+            format: format,
+            record_type: R::pipe_type(),
+            payload: record.to_pipe_bytes(format)?,
+        })
+    }
+
+    /// Serialize and write records from an iterator, one frame per record.
+    pub fn write_records<'a, R: 'a + Pipe, Iter: Iterator<Item = &'a R>>(&mut self, iter: Iter, format: PipeFormat)
+        -> Result<()>
+    {
+        for record in iter {
+            self.write_record(record, format)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Unwrap the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.writer
+    }
+}
+
+impl PipeWriter<BufWriter<Stdout>> {
+    /// Create new pipe writer to stdout.
+    ///
+    /// The output is buffered, so the writer must be flushed once done.
+    #[inline]
+    pub fn stdout() -> Result<Self> {
+        PipeWriter::new(BufWriter::new(io::stdout()))
+    }
+}
+
+// READER
+
+/// Reader of framed record streams.
+pub struct PipeReader<T: Read> {
+    reader: T,
+}
+
+impl<T: Read> PipeReader<T> {
+    /// Create new pipe reader, and check the stream signature.
+    pub fn new(mut reader: T) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        bool_to_error!(magic == PIPE_MAGIC, InvalidInput);
+        Ok(PipeReader {
+            reader: reader,
+        })
+    }
+
+    /// Read a single frame, or `None` at the end of the stream.
+    pub fn read_frame(&mut self) -> Result<Option<Frame>> {
+        let mut tags = [0u8; 1];
+        if self.reader.read(&mut tags)? == 0 {
+            return Ok(None);
+        }
+        let format = PipeFormat::from_tag(tags[0])?;
+        let record_type = PipeType::from_tag(read_u8(&mut self.reader)?)?;
+        Ok(Some(Frame {
+            format: format,
+            record_type: record_type,
+            payload: read_bytes(&mut self.reader)?,
+        }))
+    }
+
+    /// Read and deserialize a record, or `None` at the end of the stream.
+    ///
+    /// Returns an `InvalidRecord` error if the frame contains another record type.
+    pub fn read_record<R: Pipe>(&mut self) -> Result<Option<R>> {
+        match self.read_frame()? {
+            None        => Ok(None),
+            Some(frame) => {
+                bool_to_error!(frame.record_type == R::pipe_type(), InvalidRecord);
+                Ok(Some(R::from_pipe_bytes(&frame.payload, frame.format)?))
+            },
+        }
+    }
+
+    /// Convert the reader to an iterator over the deserialized records.
+    #[inline]
+    pub fn records<R: Pipe>(self) -> PipeRecordIter<T, R> {
+        PipeRecordIter {
+            reader: self,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl PipeReader<BufReader<Stdin>> {
+    /// Create new pipe reader from stdin.
+    #[inline]
+    pub fn stdin() -> Result<Self> {
+        PipeReader::new(BufReader::new(io::stdin()))
+    }
+}
+
+impl<T: Read> Iterator for PipeReader<T> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_frame() {
+            Ok(Some(frame)) => Some(Ok(frame)),
+            Ok(None)        => None,
+            Err(e)          => Some(Err(e)),
+        }
+    }
+}
+
+/// Iterator over the deserialized records of a framed record stream.
+pub struct PipeRecordIter<T: Read, R: Pipe> {
+    reader: PipeReader<T>,
+    phantom: PhantomData<R>,
+}
+
+impl<T: Read, R: Pipe> Iterator for PipeRecordIter<T, R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_record() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None)         => None,
+            Err(e)           => Some(Err(e)),
+        }
+    }
+}
+
+// MODELS
+
+#[cfg(all(feature = "mass_spectrometry", feature = "mgf"))]
+impl Pipe for ::db::mass_spectra::Record {
+    #[inline]
+    fn pipe_type() -> PipeType {
+        PipeType::MassSpectrum
+    }
+
+    fn to_pipe_bytes(&self, format: PipeFormat) -> Result<Bytes> {
+        match format {
+            PipeFormat::Mgf => ::traits::Mgf::to_mgf_bytes(self, ::traits::MgfKind::MsConvert),
+            _               => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn from_pipe_bytes(bytes: &[u8], format: PipeFormat) -> Result<Self> {
+        match format {
+            PipeFormat::Mgf => ::traits::Mgf::from_mgf_bytes(bytes, ::traits::MgfKind::MsConvert),
+            _               => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+}
+
+#[cfg(all(feature = "nucleotide", feature = "fasta"))]
+impl Pipe for ::db::nucleotide::Record {
+    #[inline]
+    fn pipe_type() -> PipeType {
+        PipeType::Nucleotide
+    }
+
+    fn to_pipe_bytes(&self, format: PipeFormat) -> Result<Bytes> {
+        match format {
+            PipeFormat::Fasta => ::traits::Fasta::to_fasta_bytes(self),
+            _                 => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn from_pipe_bytes(bytes: &[u8], format: PipeFormat) -> Result<Self> {
+        match format {
+            PipeFormat::Fasta => ::traits::Fasta::from_fasta_bytes(bytes),
+            _                 => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+}
+
+#[cfg(all(feature = "sra", feature = "fastq"))]
+impl Pipe for ::db::sra::Record {
+    #[inline]
+    fn pipe_type() -> PipeType {
+        PipeType::Sra
+    }
+
+    fn to_pipe_bytes(&self, format: PipeFormat) -> Result<Bytes> {
+        match format {
+            PipeFormat::Fastq => ::traits::Fastq::to_fastq_bytes(self),
+            _                 => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn from_pipe_bytes(bytes: &[u8], format: PipeFormat) -> Result<Self> {
+        match format {
+            PipeFormat::Fastq => ::traits::Fastq::from_fastq_bytes(bytes),
+            _                 => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+}
+
+#[cfg(all(feature = "transitions", feature = "csv"))]
+impl Pipe for ::db::transitions::Record {
+    #[inline]
+    fn pipe_type() -> PipeType {
+        PipeType::Transition
+    }
+
+    fn to_pipe_bytes(&self, format: PipeFormat) -> Result<Bytes> {
+        match format {
+            #[cfg(feature = "csv")]
+            PipeFormat::Csv => ::traits::Csv::to_csv_bytes(self, b'\t'),
+            _               => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn from_pipe_bytes(bytes: &[u8], format: PipeFormat) -> Result<Self> {
+        match format {
+            #[cfg(feature = "csv")]
+            PipeFormat::Csv => ::traits::Csv::from_csv_bytes(bytes, b'\t'),
+            _               => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+}
+
+#[cfg(all(feature = "uniprot", any(feature = "csv", feature = "fasta", feature = "xml")))]
+impl Pipe for ::db::uniprot::Record {
+    #[inline]
+    fn pipe_type() -> PipeType {
+        PipeType::UniProt
+    }
+
+    fn to_pipe_bytes(&self, format: PipeFormat) -> Result<Bytes> {
+        match format {
+            #[cfg(feature = "csv")]
+            PipeFormat::Csv   => ::traits::Csv::to_csv_bytes(self, b'\t'),
+            #[cfg(feature = "fasta")]
+            PipeFormat::Fasta => ::traits::Fasta::to_fasta_bytes(self),
+            #[cfg(feature = "xml")]
+            PipeFormat::Xml   => ::traits::Xml::to_xml_bytes(self),
+            _                 => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn from_pipe_bytes(bytes: &[u8], format: PipeFormat) -> Result<Self> {
+        match format {
+            #[cfg(feature = "csv")]
+            PipeFormat::Csv   => ::traits::Csv::from_csv_bytes(bytes, b'\t'),
+            #[cfg(feature = "fasta")]
+            PipeFormat::Fasta => ::traits::Fasta::from_fasta_bytes(bytes),
+            #[cfg(feature = "xml")]
+            PipeFormat::Xml   => ::traits::Xml::from_xml_bytes(bytes),
+            _                 => Err(From::from(ErrorKind::InvalidInput)),
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+
+    #[test]
+    fn frame_test() {
+        let frame = Frame {
+            format: PipeFormat::Fasta,
+            record_type: PipeType::Nucleotide,
+            payload: b">seq\nACGT\n".to_vec(),
+        };
+        let mut writer = PipeWriter::new(vec![]).unwrap();
+        writer.write_frame(&frame).unwrap();
+        writer.write_frame(&frame).unwrap();
+        let w = writer.into_inner();
+        assert_eq!(&w[..14], b"BDBPIPE1\x02\x03\x0a\0\0\0");
+
+        let frames: Vec<Frame> = PipeReader::new(Cursor::new(w.clone())).unwrap()
+            .map(|f| f.unwrap())
+            .collect();
+        assert_eq!(frames, vec![frame.clone(), frame]);
+
+        // invalid signature, truncated frame, and unknown tag
+        assert!(PipeReader::new(Cursor::new(b"BDBPIPE0".to_vec())).is_err());
+        let mut reader = PipeReader::new(Cursor::new(w[..w.len() - 1].to_vec())).unwrap();
+        assert!(reader.read_frame().unwrap().is_some());
+        assert!(reader.read_frame().is_err());
+        let mut reader = PipeReader::new(Cursor::new(b"BDBPIPE1\x09".to_vec())).unwrap();
+        assert!(reader.read_frame().is_err());
+    }
+
+    #[cfg(all(feature = "uniprot", feature = "fasta", feature = "xml"))]
+    #[test]
+    fn uniprot_pipe_test() {
+        use db::uniprot::Record;
+        use db::uniprot::test::*;
+
+        let mut writer = PipeWriter::new(vec![]).unwrap();
+        writer.write_record(&gapdh(), PipeFormat::Xml).unwrap();
+        writer.write_records(vec![bsa()].iter(), PipeFormat::Fasta).unwrap();
+        let w = writer.into_inner();
+
+        let records: Vec<Record> = PipeReader::new(Cursor::new(w.clone())).unwrap()
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], gapdh());
+        assert_eq!(records[1].id, "P02769");
+
+        // unsupported format and mismatched record type
+        assert!(gapdh().to_pipe_bytes(PipeFormat::Fastq).is_err());
+        #[cfg(feature = "mass_spectrometry")]
+        {
+            use db::mass_spectra;
+            let mut reader = PipeReader::new(Cursor::new(w)).unwrap();
+            assert!(reader.read_record::<mass_spectra::Record>().is_err());
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Renaming maps to anonymize record identifiers.
+//!
+//! A `RenameMap` replaces identifiers (UniProt accessions, read and
+//! sequence identifiers, or the file names in spectra titles) from a
+//! user-provided map, or with generated names from a stable hash, so
+//! the same identifier is renamed identically across files and runs.
+//! The map is written to disk as a tab-delimited file, with the
+//! original and renamed identifiers, to restore the identifiers later.
+//!
+//! To rename a stream of records, map the records with
+//! `RecordIterExt::map_records`, and call `RenameMap::apply`.
+
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::path::Path;
+
+use io::atomic::write_atomic;
+use traits::Renamable;
+use util::{open_buffered, stable_hash, ErrorKind, Result};
+
+/// Map from original to renamed identifiers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RenameMap {
+    /// Renamed identifiers by original identifier.
+    map: HashMap<String, String>,
+    /// Original identifiers, in insertion order.
+    order: Vec<String>,
+    /// Prefix and seed to generate names for unmapped identifiers.
+    generator: Option<(String, u64)>,
+}
+
+impl RenameMap {
+    /// Create new, empty map, which leaves unmapped identifiers unchanged.
+    #[inline]
+    pub fn new() -> Self {
+        RenameMap::default()
+    }
+
+    /// Create new, empty map, which generates names for unmapped identifiers.
+    ///
+    /// Generated names are the prefix followed by 16 hexadecimal digits
+    /// of the hash of the identifier. A secret seed prevents recovering
+    /// the identifiers by hashing known accessions.
+    #[inline]
+    pub fn hashed(prefix: &str, seed: u64) -> Self {
+        RenameMap {
+            generator: Some((String::from(prefix), seed)),
+            ..RenameMap::default()
+        }
+    }
+
+    /// Get the number of renamed identifiers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Check if the map contains no identifiers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Add or replace the name for an identifier.
+    pub fn insert(&mut self, original: &str, renamed: &str) {
+        if self.map.insert(String::from(original), String::from(renamed)).is_none() {
+            self.order.push(String::from(original));
+        }
+    }
+
+    /// Get the name for an identifier, if mapped.
+    #[inline]
+    pub fn get(&self, original: &str) -> Option<&str> {
+        self.map.get(original).map(String::as_str)
+    }
+
+    /// Get the name for an identifier, generating it if required.
+    ///
+    /// Unmapped identifiers are returned unchanged without a generator.
+    pub fn rename(&mut self, original: &str) -> String {
+        if let Some(renamed) = self.map.get(original) {
+            return renamed.clone();
+        }
+        match self.generator {
+            Some((ref prefix, seed)) => {
+                let renamed = format!("{}{:016x}", prefix, stable_hash(original, seed));
+                self.map.insert(String::from(original), renamed.clone());
+                self.order.push(String::from(original));
+                renamed
+            },
+            None => String::from(original),
+        }
+    }
+
+    /// Rename the identifier of a record, in place.
+    #[inline]
+    pub fn apply<T: Renamable>(&mut self, record: &mut T) {
+        let renamed = self.rename(record.identifier());
+        record.set_identifier(renamed);
+    }
+
+    /// Iterate over the original and renamed identifiers, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.order.iter().map(move |k| (k.as_str(), self.map[k].as_str()))
+    }
+
+    /// Export the map as tab-delimited original and renamed identifiers.
+    pub fn to_tsv<T: Write>(&self, writer: &mut T) -> Result<()> {
+        for (original, renamed) in self.iter() {
+            writeln!(writer, "{}\t{}", original, renamed)?;
+        }
+        Ok(())
+    }
+
+    /// Import the map from tab-delimited original and renamed identifiers.
+    ///
+    /// The imported map leaves unmapped identifiers unchanged.
+    pub fn from_tsv<T: BufRead>(reader: &mut T) -> Result<Self> {
+        let mut map = RenameMap::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut fields = line.trim_end_matches('\r').split('\t');
+            let original = none_to_error!(fields.next(), InvalidInput);
+            let renamed = none_to_error!(fields.next(), InvalidInput);
+            bool_to_error!(fields.next().is_none(), InvalidInput);
+            map.insert(original, renamed);
+        }
+        Ok(map)
+    }
+
+    /// Export the map to a file, replacing the file atomically.
+    pub fn to_tsv_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        write_atomic(path, |w| self.to_tsv(w)).map_err(|e| e.with_path(path))
+    }
+
+    /// Import the map from a file.
+    pub fn from_tsv_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        open_buffered(path)
+            .and_then(|mut reader| RenameMap::from_tsv(&mut reader))
+            .map_err(|e| e.with_path(path))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use super::*;
+
+    struct Item(String);
+
+    impl Renamable for Item {
+        fn identifier(&self) -> &str {
+            &self.0
+        }
+
+        fn set_identifier(&mut self, identifier: String) {
+            self.0 = identifier;
+        }
+    }
+
+    #[test]
+    fn user_map_test() {
+        let mut map = RenameMap::new();
+        map.insert("P46406", "protein1");
+        let mut item = Item(String::from("P46406"));
+        map.apply(&mut item);
+        assert_eq!(item.0, "protein1");
+
+        // Unmapped identifiers are unchanged.
+        let mut item = Item(String::from("P02769"));
+        map.apply(&mut item);
+        assert_eq!(item.0, "P02769");
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn hashed_map_test() {
+        let mut map = RenameMap::hashed("anon_", 42);
+        let x = map.rename("SRR390728.2");
+        assert!(x.starts_with("anon_"));
+        assert_eq!(x.len(), 21);
+        assert_eq!(map.rename("SRR390728.2"), x);
+        assert_ne!(map.rename("SRR390728.3"), x);
+        assert_eq!(RenameMap::hashed("anon_", 42).rename("SRR390728.2"), x);
+        assert_ne!(RenameMap::hashed("anon_", 43).rename("SRR390728.2"), x);
+        assert_eq!(map.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec!["SRR390728.2", "SRR390728.3"]);
+    }
+
+    #[test]
+    fn tsv_test() {
+        let mut map = RenameMap::hashed("anon_", 0);
+        map.insert("QPvivo.raw", "run1.raw");
+        map.rename("P46406");
+
+        let mut w = vec![];
+        map.to_tsv(&mut w).unwrap();
+        let text = String::from_utf8(w.clone()).unwrap();
+        assert!(text.starts_with("QPvivo.raw\trun1.raw\nP46406\tanon_"));
+        let imported = RenameMap::from_tsv(&mut Cursor::new(w)).unwrap();
+        assert_eq!(imported.iter().collect::<Vec<_>>(), map.iter().collect::<Vec<_>>());
+        assert!(RenameMap::from_tsv(&mut Cursor::new(b"P46406\n".to_vec())).is_err());
+
+        let path = env::temp_dir().join("bdb_rename_map_test.tsv");
+        map.to_tsv_path(&path).unwrap();
+        assert_eq!(RenameMap::from_tsv_path(&path).unwrap().get("P46406"), map.get("P46406"));
+        fs::remove_file(&path).unwrap();
+    }
+}
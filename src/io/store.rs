@@ -0,0 +1,151 @@
+//! Thread-safe, shared record stores.
+//!
+//! A `RecordStore` is immutable after creation, and clones share the
+//! same records and lookup map, so a single store may be queried
+//! concurrently from many threads without copying the records.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::slice;
+use std::sync::Arc;
+
+use traits::Keyed;
+
+/// Immutable record collection indexed by record key.
+///
+/// The lookup map from key (UniProt accession, scan number) to record
+/// is built once, on creation. Cloning the store is cheap.
+pub struct RecordStore<T: Keyed> {
+    records: Arc<Vec<T>>,
+    index: Arc<HashMap<T::Key, usize>>,
+}
+
+impl<T: Keyed> RecordStore<T> {
+    /// Create a store from a record collection, indexing the records by key.
+    ///
+    /// If multiple records share the same key, lookups return the first.
+    pub fn new(records: Vec<T>) -> Self {
+        let mut index = HashMap::with_capacity(records.len());
+        for (i, record) in records.iter().enumerate() {
+            index.entry(record.key().clone()).or_insert(i);
+        }
+
+        RecordStore {
+            records: Arc::new(records),
+            index: Arc::new(index),
+        }
+    }
+
+    /// Get the number of records in the store.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check if the store contains no records.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Get the records in the store, in insertion order.
+    #[inline]
+    pub fn records(&self) -> &[T] {
+        self.records.as_slice()
+    }
+
+    /// Iterate over the records in the store, in insertion order.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.records.iter()
+    }
+
+    /// Get the position of the record with the key.
+    #[inline]
+    pub fn position<Q: ?Sized>(&self, key: &Q) -> Option<usize>
+        where T::Key: Borrow<Q>,
+              Q: Eq + Hash
+    {
+        self.index.get(key).cloned()
+    }
+
+    /// Get the record with the key.
+    #[inline]
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&T>
+        where T::Key: Borrow<Q>,
+              Q: Eq + Hash
+    {
+        self.position(key).map(|i| &self.records[i])
+    }
+
+    /// Check if the store contains a record with the key.
+    #[inline]
+    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+        where T::Key: Borrow<Q>,
+              Q: Eq + Hash
+    {
+        self.index.contains_key(key)
+    }
+}
+
+impl<T: Keyed> Clone for RecordStore<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        RecordStore {
+            records: self.records.clone(),
+            index: self.index.clone(),
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "uniprot")]
+    #[test]
+    fn uniprot_store_test() {
+        use db::uniprot::test::*;
+
+        let store = RecordStore::new(vec![gapdh(), bsa(), gapdh()]);
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.position("P46406"), Some(0));
+        assert_eq!(store.position("P02769"), Some(1));
+        assert_eq!(store.get("P02769"), Some(&bsa()));
+        assert!(store.contains_key("P46406"));
+        assert!(!store.contains_key("P00000"));
+        assert_eq!(store.get("P00000"), None);
+
+        let empty = RecordStore::<::db::uniprot::Record>::new(vec![]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.get("P46406"), None);
+    }
+
+    #[cfg(feature = "mass_spectrometry")]
+    #[test]
+    fn concurrent_store_test() {
+        use std::thread;
+        use db::mass_spectra::test::*;
+
+        let mut x = mgf_33450();
+        x.num = 1;
+        let store = RecordStore::new(vec![x, mgf_33450()]);
+        let handles: Vec<_> = (0..4).map(|_| {
+            let store = store.clone();
+            thread::spawn(move || {
+                store.get(&33450).map(|r| r.peaks.len())
+            })
+        }).collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(69));
+        }
+        assert_eq!(store.position(&1), Some(0));
+        assert_eq!(store.iter().count(), 2);
+        assert_eq!(store.records()[1].num, 33450);
+    }
+}
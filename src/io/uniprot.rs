@@ -21,9 +21,11 @@ use std::convert::AsRef;
 use std::io::{BufRead, Write};
 use std::path::Path;
 
+#[cfg(feature = "manifest")]
+use io::manifest::{write_with_manifest, Manifest, DEFAULT_CHUNK_SIZE};
 use db::uniprot::RecordList;
 use traits::*;
-use util::{Bytes, Result};
+use util::{Bytes, ExportMetadata, Result};
 
 /// Reader/writer for UniProt FASTA records.
 #[cfg(feature = "fasta")]
@@ -55,6 +57,21 @@ impl UniProtFasta {
         list.to_fasta_file(path)
     }
 
+    /// Save UniProt records to file, preceded by a provenance metadata block.
+    #[inline(always)]
+    pub fn to_file_with_metadata<P: AsRef<Path>>(list: &RecordList, path: P, metadata: &ExportMetadata) -> Result<()> {
+        list.to_fasta_file_with_metadata(path, metadata)
+    }
+
+    /// Save UniProt records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_fasta(writer)?;
+            Ok(list.len())
+        })
+    }
+
     /// Load UniProt records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -110,6 +127,21 @@ impl UniProtCsv {
         list.to_csv_file(path, b'\t')
     }
 
+    /// Save UniProt records to file, preceded by a provenance metadata block.
+    #[inline(always)]
+    pub fn to_file_with_metadata<P: AsRef<Path>>(list: &RecordList, path: P, metadata: &ExportMetadata) -> Result<()> {
+        list.to_csv_file_with_metadata(path, b'\t', metadata)
+    }
+
+    /// Save UniProt records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_csv(writer, b'\t')?;
+            Ok(list.len())
+        })
+    }
+
     /// Load UniProt records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -165,6 +197,15 @@ impl UniProtXml {
         list.to_xml_file(path)
     }
 
+    /// Save UniProt records to file, with a sidecar integrity manifest.
+    #[cfg(feature = "manifest")]
+    pub fn to_file_with_manifest<P: AsRef<Path>>(list: &RecordList, path: P) -> Result<Manifest> {
+        write_with_manifest(path, DEFAULT_CHUNK_SIZE, |writer| {
+            list.to_xml(writer)?;
+            Ok(list.len())
+        })
+    }
+
     /// Load UniProt records from stream.
     #[inline(always)]
     pub fn from_stream<T: BufRead>(reader: &mut T) -> Result<RecordList> {
@@ -221,6 +262,26 @@ mod tests {
         assert_eq!(expected.lines().nth(2), actual.lines().nth(2));
     }
 
+    #[cfg(all(feature = "fasta", feature = "manifest"))]
+    #[test]
+    fn fasta_manifest_test() {
+        use std::env;
+        use std::fs;
+        use db::uniprot::test::{bsa, gapdh};
+        use io::manifest::{manifest_path, verify_file};
+
+        let path = env::temp_dir().join("bdb_uniprot_fasta_manifest_test.fasta");
+        let list = vec![gapdh(), bsa()];
+        let manifest = Fasta::to_file_with_manifest(&list, &path).unwrap();
+        assert_eq!(manifest.records, 2);
+        assert_eq!(manifest.bytes, fs::metadata(&path).unwrap().len());
+        assert_eq!(verify_file(&path).unwrap(), manifest);
+        assert_eq!(Fasta::from_file(&path).unwrap().len(), 2);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(manifest_path(&path)).unwrap();
+    }
+
     #[cfg(feature = "csv")]
     fn csv_dir() -> PathBuf {
         let mut dir = testdata_dir();
@@ -1,32 +1,100 @@
+// Without the `std` feature, only the mass routines and the mass
+// spectrum and peptide search match models are built, with `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
 #[cfg(test)]
 #[macro_use]
 extern crate assert_approx_eq;
 
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
 #[macro_use]
 extern crate cfg_if;
 
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(feature = "std")]
 extern crate lexical;
+
+#[cfg(feature = "std")]
 extern crate ref_slice;
+
+#[cfg(feature = "std")]
 extern crate regex;
 
+#[cfg(feature = "columnar")]
+extern crate arrow;
+
+#[cfg(feature = "columnar")]
+extern crate parquet;
+
+#[cfg(feature = "config")]
+extern crate toml;
+
 #[cfg(feature = "csv")]
 extern crate csv;
 
 #[cfg(feature = "xml")]
 extern crate quick_xml;
 
+#[cfg(feature = "gzip")]
+extern crate flate2;
+
+#[cfg(feature = "logging")]
+#[macro_use]
+extern crate log;
+
+#[cfg(feature = "mmap")]
+extern crate memmap;
+
 #[cfg(feature = "http")]
 extern crate reqwest;
 
 #[cfg(feature = "http")]
 extern crate url;
 
+#[cfg(feature = "manifest")]
+extern crate sha2;
+
+#[cfg(feature = "service")]
+extern crate tiny_http;
+
+// The `pyo3` macros use `::core` paths, which are not in scope by
+// default for `std` builds with the 2015 edition.
+#[cfg(feature = "python")]
+extern crate core;
+
+#[cfg(feature = "python")]
+extern crate pyo3;
+
 #[cfg(test)]
 extern crate bencher;
 
+// The network and memory-mapped backends require an operating system.
+#[cfg(all(target_arch = "wasm32", any(feature = "http", feature = "mmap", feature = "service")))]
+compile_error!("the `http`, `mmap` and `service` features are not supported on wasm32, use the `wasm` feature without the default features.");
+
+// Facade for `core` and `alloc` under the `std` paths, so the
+// `no_std` modules share their imports with the `std` build.
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+mod std {
+    pub use core::{cmp, convert, error, f64, fmt, hash, iter, marker, mem, num, ops, option, result, slice, str};
+    pub use alloc::{borrow, boxed, string, vec};
+
+    /// Types from the `std` prelude missing from the `core` prelude.
+    pub mod prelude {
+        pub use alloc::borrow::ToOwned;
+        pub use alloc::boxed::Box;
+        pub use alloc::string::{String, ToString};
+        pub use alloc::vec::Vec;
+    }
+}
+
 // Macros and utilities (required by other modules).
 #[macro_use]
 pub(crate) mod util;
@@ -37,10 +105,42 @@ pub(crate) mod util;
 pub(crate) mod test;
 
 // Public modules
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
 pub mod bio;
 pub mod db;
-pub mod io;
 pub mod traits;
 
+#[cfg(feature = "std")]
+pub mod concurrency;
+
+#[cfg(feature = "std")]
+pub mod io;
+
+#[cfg(feature = "std")]
+pub mod testdata;
+
+#[cfg(feature = "config")]
+pub mod config;
+
+#[cfg(feature = "search")]
+pub mod search;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+#[cfg(feature = "python")]
+pub mod python;
+
 // Re-export utility traits that should be shared.
-pub use util::{Error, ErrorKind, Result};
+pub use util::{Error, ErrorKind, Result, Rng};
+
+#[cfg(feature = "std")]
+pub use util::{hash_unit, sample_n, Encoding, ReaderOptions, RecordIterExt, Split};
+
+#[cfg(feature = "mmap")]
+pub use util::MmapFile;
@@ -0,0 +1,199 @@
+//! Python classes for mass spectra records.
+
+use pyo3::prelude::*;
+
+use db::mass_spectra::{Peak, Record, RecordList};
+use io::mass_spectra as mass_spectra_io;
+use traits::{Complete, Valid};
+
+// RECORD
+
+/// Python class for a spectral record.
+#[pyclass(name = "SpectrumRecord", module = "bdb")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PySpectrumRecord {
+    /// Wrapped record.
+    pub inner: Record,
+}
+
+impl From<Record> for PySpectrumRecord {
+    #[inline]
+    fn from(inner: Record) -> Self {
+        PySpectrumRecord { inner: inner }
+    }
+}
+
+impl PySpectrumRecord {
+    /// Copy the wrapped records into a record list.
+    pub(crate) fn to_list(records: &[PyRef<PySpectrumRecord>]) -> RecordList {
+        records.iter().map(|r| r.inner.clone()).collect()
+    }
+}
+
+#[pymethods]
+impl PySpectrumRecord {
+    /// Create new, empty spectral record.
+    #[new]
+    pub fn new() -> Self {
+        PySpectrumRecord::from(Record::new())
+    }
+
+    #[getter]
+    fn get_num(&self) -> u32 {
+        self.inner.num
+    }
+
+    #[setter]
+    fn set_num(&mut self, value: u32) {
+        self.inner.num = value;
+    }
+
+    #[getter]
+    fn get_ms_level(&self) -> u8 {
+        self.inner.ms_level
+    }
+
+    #[setter]
+    fn set_ms_level(&mut self, value: u8) {
+        self.inner.ms_level = value;
+    }
+
+    #[getter]
+    fn get_rt(&self) -> f64 {
+        self.inner.rt
+    }
+
+    #[setter]
+    fn set_rt(&mut self, value: f64) {
+        self.inner.rt = value;
+    }
+
+    #[getter]
+    fn get_parent_mz(&self) -> f64 {
+        self.inner.parent_mz
+    }
+
+    #[setter]
+    fn set_parent_mz(&mut self, value: f64) {
+        self.inner.parent_mz = value;
+    }
+
+    #[getter]
+    fn get_parent_intensity(&self) -> f64 {
+        self.inner.parent_intensity
+    }
+
+    #[setter]
+    fn set_parent_intensity(&mut self, value: f64) {
+        self.inner.parent_intensity = value;
+    }
+
+    #[getter]
+    fn get_parent_z(&self) -> i8 {
+        self.inner.parent_z
+    }
+
+    #[setter]
+    fn set_parent_z(&mut self, value: i8) {
+        self.inner.parent_z = value;
+    }
+
+    #[getter]
+    fn get_file(&self) -> &str {
+        &self.inner.file
+    }
+
+    #[setter]
+    fn set_file(&mut self, value: String) {
+        self.inner.file = value;
+    }
+
+    #[getter]
+    fn get_filter(&self) -> &str {
+        &self.inner.filter
+    }
+
+    #[setter]
+    fn set_filter(&mut self, value: String) {
+        self.inner.filter = value;
+    }
+
+    /// Spectral peaks, as `(mz, intensity, z)` tuples.
+    #[getter]
+    fn get_peaks(&self) -> Vec<(f64, f64, i8)> {
+        self.inner.peaks.iter().map(|p| (p.mz, p.intensity, p.z)).collect()
+    }
+
+    #[setter]
+    fn set_peaks(&mut self, value: Vec<(f64, f64, i8)>) {
+        self.inner.peaks = value.into_iter()
+            .map(|(mz, intensity, z)| Peak { mz: mz, intensity: intensity, z: z, mobility: None })
+            .collect();
+    }
+
+    /// Determine if the record contains only valid information.
+    fn is_valid(&self) -> bool {
+        self.inner.is_valid()
+    }
+
+    /// Determine if the record contains all the required information.
+    fn is_complete(&self) -> bool {
+        self.inner.is_complete()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SpectrumRecord(num={}, ms_level={})", self.inner.num, self.inner.ms_level)
+    }
+}
+
+// IO
+
+py_io_class!("Python class for the FullMs MGF reader/writer.", PyFullMsMgf, "FullMsMgf", mass_spectra_io::FullMsMgf, PySpectrumRecord);
+py_io_class!("Python class for the MSConvert MGF reader/writer.", PyMsConvertMgf, "MsConvertMgf", mass_spectra_io::MsConvertMgf, PySpectrumRecord);
+py_io_class!("Python class for the Pava MGF reader/writer.", PyPavaMgf, "PavaMgf", mass_spectra_io::PavaMgf, PySpectrumRecord);
+py_io_class!("Python class for the ProteoWizard MGF reader/writer.", PyPwizMgf, "PwizMgf", mass_spectra_io::PwizMgf, PySpectrumRecord);
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::{IntoPyDict, PyBytes};
+    use db::mass_spectra::test::*;
+    use super::*;
+
+    #[test]
+    fn record_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let record = Py::new(py, PySpectrumRecord::from(mgf_33450())).unwrap();
+            let locals = [("record", record.clone_ref(py))].into_py_dict_bound(py);
+            py.run_bound(concat!(
+                "assert record.num == 33450\n",
+                "assert record.parent_z == 4\n",
+                "assert len(record.peaks) == 69\n",
+                "record.peaks = [(100.0, 10.0, 1)]\n",
+            ), None, Some(&locals)).unwrap();
+            assert_eq!(record.borrow(py).inner.peaks.len(), 1);
+        });
+    }
+
+    #[test]
+    fn io_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = pyo3::wrap_pymodule!(python::bdb)(py);
+            let locals = [("bdb", module)].into_py_dict_bound(py);
+            locals.set_item("mgf", PyBytes::new_bound(py, MSCONVERT_33450_MGF)).unwrap();
+            py.run_bound(concat!(
+                "records = bdb.MsConvertMgf.from_bytes(mgf)\n",
+                "assert [r.num for r in records] == [33450]\n",
+                "assert bdb.MsConvertMgf.to_bytes(records) == mgf\n",
+            ), None, Some(&locals)).unwrap();
+        });
+    }
+}
@@ -0,0 +1,133 @@
+//! Python bindings for record models and their readers/writers.
+//!
+//! Exposes the UniProt and mass spectra records, and the serializers
+//! from `io`, as the `bdb` Python extension module:
+//!
+//! ```python
+//! import bdb
+//!
+//! records = bdb.UniProtFasta.from_file("proteome.fasta")
+//! reviewed = [r for r in records if r.reviewed]
+//! bdb.UniProtCsv.to_file(reviewed, "reviewed.tsv")
+//!
+//! spectra = bdb.MsConvertMgf.from_file("run.mgf")
+//! ms2 = [s for s in spectra if s.ms_level == 2]
+//! ```
+//!
+//! Records are copied between Rust and Python, and errors are raised
+//! as `IOError` for file errors, or `ValueError` otherwise.
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+use util::{Error, ErrorKind};
+
+// IO
+
+/// Macro to generate a Python class wrapping a reader/writer from `io`.
+///
+/// The reader/writer is exposed with static methods, as in Rust, and
+/// records are converted to and from the Python record class.
+macro_rules! py_io_class {
+    ($doc:literal, $name:ident, $pyname:literal, $io:ty, $record:ident) => (
+        #[doc = $doc]
+        #[pyclass(name = $pyname, module = "bdb")]
+        pub struct $name;
+
+        #[pymethods]
+        impl $name {
+            /// Import records from bytes.
+            #[staticmethod]
+            pub fn from_bytes(bytes: &[u8]) -> PyResult<Vec<$record>> {
+                let list = <$io>::from_bytes(bytes)?;
+                Ok(list.into_iter().map($record::from).collect())
+            }
+
+            /// Import records from a string.
+            #[staticmethod]
+            pub fn from_string(string: &str) -> PyResult<Vec<$record>> {
+                let list = <$io>::from_string(string)?;
+                Ok(list.into_iter().map($record::from).collect())
+            }
+
+            /// Import records from a file.
+            #[staticmethod]
+            pub fn from_file(path: ::std::path::PathBuf) -> PyResult<Vec<$record>> {
+                let list = <$io>::from_file(path)?;
+                Ok(list.into_iter().map($record::from).collect())
+            }
+
+            /// Export records to bytes.
+            #[staticmethod]
+            pub fn to_bytes<'py>(py: Python<'py>, records: Vec<PyRef<$record>>)
+                -> PyResult<Bound<'py, ::pyo3::types::PyBytes>>
+            {
+                let list = $record::to_list(&records);
+                let bytes = <$io>::to_bytes(&list)?;
+                Ok(::pyo3::types::PyBytes::new_bound(py, &bytes))
+            }
+
+            /// Export records to a string.
+            #[staticmethod]
+            pub fn to_string(records: Vec<PyRef<$record>>) -> PyResult<String> {
+                let list = $record::to_list(&records);
+                Ok(<$io>::to_string(&list)?)
+            }
+
+            /// Export records to a file.
+            #[staticmethod]
+            pub fn to_file(records: Vec<PyRef<$record>>, path: ::std::path::PathBuf) -> PyResult<()> {
+                let list = $record::to_list(&records);
+                Ok(<$io>::to_file(&list, path)?)
+            }
+        }
+    );
+}
+
+pub(crate) mod mass_spectra;
+pub(crate) mod uniprot;
+
+// Re-export the classes into the parent module.
+pub use self::mass_spectra::{PyFullMsMgf, PyMsConvertMgf, PyPavaMgf, PyPwizMgf, PySpectrumRecord};
+pub use self::uniprot::{PyUniProtCsv, PyUniProtFasta, PyUniProtRecord, PyUniProtXml};
+
+// ERRORS
+
+/// Determine if the error was caused by file I/O.
+fn is_io_error(err: &Error) -> bool {
+    match err.kind() {
+        ErrorKind::Io(_)            => true,
+        ErrorKind::Path(_, ref err) => is_io_error(err),
+        _                           => false,
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        match is_io_error(&err) {
+            true  => PyIOError::new_err(err.to_string()),
+            false => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+// MODULE
+
+/// Python extension module.
+#[pymodule]
+pub fn bdb(module: &Bound<PyModule>) -> PyResult<()> {
+    // UniProt
+    module.add_class::<PyUniProtRecord>()?;
+    module.add_class::<PyUniProtCsv>()?;
+    module.add_class::<PyUniProtFasta>()?;
+    module.add_class::<PyUniProtXml>()?;
+
+    // Mass spectra
+    module.add_class::<PySpectrumRecord>()?;
+    module.add_class::<PyFullMsMgf>()?;
+    module.add_class::<PyMsConvertMgf>()?;
+    module.add_class::<PyPavaMgf>()?;
+    module.add_class::<PyPwizMgf>()?;
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+//! Python classes for UniProt records.
+
+use pyo3::prelude::*;
+
+use db::uniprot::{ProteinEvidence, Record, RecordList};
+use io::uniprot as uniprot_io;
+use traits::{Complete, Valid};
+
+// RECORD
+
+/// Python class for a UniProt record.
+#[pyclass(name = "UniProtRecord", module = "bdb")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PyUniProtRecord {
+    /// Wrapped record.
+    pub inner: Record,
+}
+
+impl From<Record> for PyUniProtRecord {
+    #[inline]
+    fn from(inner: Record) -> Self {
+        PyUniProtRecord { inner: inner }
+    }
+}
+
+impl PyUniProtRecord {
+    /// Copy the wrapped records into a record list.
+    pub(crate) fn to_list(records: &[PyRef<PyUniProtRecord>]) -> RecordList {
+        records.iter().map(|r| r.inner.clone()).collect()
+    }
+}
+
+#[pymethods]
+impl PyUniProtRecord {
+    /// Create new, empty UniProt record.
+    #[new]
+    pub fn new() -> Self {
+        PyUniProtRecord::from(Record::new())
+    }
+
+    #[getter]
+    fn get_sequence_version(&self) -> u8 {
+        self.inner.sequence_version
+    }
+
+    #[setter]
+    fn set_sequence_version(&mut self, value: u8) {
+        self.inner.sequence_version = value;
+    }
+
+    /// Protein evidence level, from 1 (protein level) to 5 (unknown).
+    #[getter]
+    fn get_protein_evidence(&self) -> u8 {
+        self.inner.protein_evidence.to_int()
+    }
+
+    #[setter]
+    fn set_protein_evidence(&mut self, value: u8) -> PyResult<()> {
+        self.inner.protein_evidence = ProteinEvidence::from_int(value)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_mass(&self) -> u64 {
+        self.inner.mass
+    }
+
+    #[setter]
+    fn set_mass(&mut self, value: u64) {
+        self.inner.mass = value;
+    }
+
+    #[getter]
+    fn get_length(&self) -> u32 {
+        self.inner.length
+    }
+
+    #[setter]
+    fn set_length(&mut self, value: u32) {
+        self.inner.length = value;
+    }
+
+    #[getter]
+    fn get_gene(&self) -> &str {
+        &self.inner.gene
+    }
+
+    #[setter]
+    fn set_gene(&mut self, value: String) {
+        self.inner.gene = value;
+    }
+
+    #[getter]
+    fn get_id(&self) -> &str {
+        &self.inner.id
+    }
+
+    #[setter]
+    fn set_id(&mut self, value: String) {
+        self.inner.id = value;
+    }
+
+    #[getter]
+    fn get_mnemonic(&self) -> &str {
+        &self.inner.mnemonic
+    }
+
+    #[setter]
+    fn set_mnemonic(&mut self, value: String) {
+        self.inner.mnemonic = value;
+    }
+
+    #[getter]
+    fn get_name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[setter]
+    fn set_name(&mut self, value: String) {
+        self.inner.name = value;
+    }
+
+    #[getter]
+    fn get_organism(&self) -> &str {
+        &self.inner.organism
+    }
+
+    #[setter]
+    fn set_organism(&mut self, value: String) {
+        self.inner.organism = value;
+    }
+
+    #[getter]
+    fn get_proteome(&self) -> &str {
+        &self.inner.proteome
+    }
+
+    #[setter]
+    fn set_proteome(&mut self, value: String) {
+        self.inner.proteome = value;
+    }
+
+    /// Aminoacid sequence, as a string.
+    #[getter]
+    fn get_sequence(&self) -> String {
+        String::from_utf8_lossy(&self.inner.sequence).into_owned()
+    }
+
+    #[setter]
+    fn set_sequence(&mut self, value: String) {
+        self.inner.sequence = value.into_bytes();
+    }
+
+    #[getter]
+    fn get_taxonomy(&self) -> &str {
+        &self.inner.taxonomy
+    }
+
+    #[setter]
+    fn set_taxonomy(&mut self, value: String) {
+        self.inner.taxonomy = value;
+    }
+
+    #[getter]
+    fn get_reviewed(&self) -> bool {
+        self.inner.reviewed
+    }
+
+    #[setter]
+    fn set_reviewed(&mut self, value: bool) {
+        self.inner.reviewed = value;
+    }
+
+    /// Determine if the record contains only valid information.
+    fn is_valid(&self) -> bool {
+        self.inner.is_valid()
+    }
+
+    /// Determine if the record contains all the required information.
+    fn is_complete(&self) -> bool {
+        self.inner.is_complete()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __repr__(&self) -> String {
+        format!("UniProtRecord(id={:?}, mnemonic={:?})", self.inner.id, self.inner.mnemonic)
+    }
+}
+
+// IO
+
+py_io_class!("Python class for the UniProt CSV (tab-delimited) reader/writer.", PyUniProtCsv, "UniProtCsv", uniprot_io::Csv, PyUniProtRecord);
+py_io_class!("Python class for the UniProt FASTA reader/writer.", PyUniProtFasta, "UniProtFasta", uniprot_io::Fasta, PyUniProtRecord);
+py_io_class!("Python class for the UniProt XML reader/writer.", PyUniProtXml, "UniProtXml", uniprot_io::Xml, PyUniProtRecord);
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::{IntoPyDict, PyBytes};
+    use db::uniprot::test::*;
+    use super::*;
+
+    #[test]
+    fn record_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let record = Py::new(py, PyUniProtRecord::from(gapdh())).unwrap();
+            let locals = [("record", record.clone_ref(py))].into_py_dict_bound(py);
+            py.run_bound(concat!(
+                "assert record.id == 'P46406'\n",
+                "assert record.protein_evidence == 1\n",
+                "assert record.sequence.startswith('MVKVGVNGFG')\n",
+                "assert record.is_valid()\n",
+                "record.gene = 'GAPDH2'\n",
+                "try:\n",
+                "    record.protein_evidence = 9\n",
+                "    raise AssertionError\n",
+                "except ValueError:\n",
+                "    pass\n",
+            ), None, Some(&locals)).unwrap();
+            assert_eq!(record.borrow(py).inner.gene, "GAPDH2");
+        });
+    }
+
+    #[test]
+    fn io_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = pyo3::wrap_pymodule!(python::bdb)(py);
+            let locals = [("bdb", module)].into_py_dict_bound(py);
+            locals.set_item("fasta", PyBytes::new_bound(py, GAPDH_BSA_FASTA)).unwrap();
+            py.run_bound(concat!(
+                "records = bdb.UniProtFasta.from_bytes(fasta)\n",
+                "assert [r.mnemonic for r in records] == ['G3P_RABIT', 'ALBU_BOVIN']\n",
+                "assert bdb.UniProtFasta.to_bytes(records) == fasta\n",
+                "xml = bdb.UniProtXml.to_string(records)\n",
+                "assert bdb.UniProtXml.from_string(xml) == records\n",
+                "try:\n",
+                "    bdb.UniProtFasta.from_file('/nonexistent/bdb.fasta')\n",
+                "    raise AssertionError\n",
+                "except IOError:\n",
+                "    pass\n",
+            ), None, Some(&locals)).unwrap();
+        });
+    }
+}
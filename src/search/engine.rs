@@ -0,0 +1,233 @@
+//! Database search of MS2 spectra against a peptide index.
+
+use std::io::prelude::*;
+
+use bio::fragment::{fragment_ions, IonType};
+use bio::mass::{mz_to_neutral_mass, Adduct, Tolerance};
+use bio::proteins::ProteinMassTable;
+use concurrency::CancellationToken;
+use db::mass_spectra;
+use db::peptide_search_matches::{self, PeptideIndex};
+use util::Result;
+use super::score::hyperscore;
+
+/// Options for the database search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchOptions {
+    /// Tolerance to match the precursor to the peptide masses.
+    pub precursor_tolerance: Tolerance,
+    /// Tolerance to match the fragment ions to the peaks.
+    pub fragment_tolerance: Tolerance,
+    /// Fragment ion types.
+    pub ions: Vec<IonType>,
+    /// Maximum fragment charge, capped by the precursor charge.
+    pub max_fragment_charge: i8,
+    /// Precursor charges to try for spectra with an unknown charge.
+    pub default_charges: Vec<i8>,
+    /// Minimum number of matched fragments to report a match.
+    pub min_matched: usize,
+}
+
+impl Default for SearchOptions {
+    /// Search high-resolution precursors with low-resolution fragments.
+    fn default() -> Self {
+        SearchOptions {
+            precursor_tolerance: Tolerance::Ppm(10.0),
+            fragment_tolerance: Tolerance::Da(0.02),
+            ions: vec![IonType::B, IonType::Y],
+            max_fragment_charge: 1,
+            default_charges: vec![2, 3],
+            min_matched: 4,
+        }
+    }
+}
+
+/// Database search engine, matching MS2 spectra to indexed peptides.
+///
+/// Peptides are scored by hyperscore, and only the best match for
+/// each spectrum is reported. The mass table must match the table
+/// used to build the index, including fixed modifications.
+pub struct SearchEngine<T: Read + Seek> {
+    index: PeptideIndex<T>,
+    table: ProteinMassTable,
+    options: SearchOptions,
+}
+
+impl<T: Read + Seek> SearchEngine<T> {
+    /// Create new search engine from a peptide index, mass table and options.
+    #[inline]
+    pub fn new(index: PeptideIndex<T>, table: ProteinMassTable, options: SearchOptions) -> Self {
+        SearchEngine {
+            index: index,
+            table: table,
+            options: options,
+        }
+    }
+
+    /// Get the search options.
+    #[inline]
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// Find the best peptide match for a spectrum.
+    ///
+    /// Returns `None` for spectra without a precursor m/z, or without
+    /// a peptide matching the minimum number of fragments.
+    pub fn search(&mut self, spectrum: &mass_spectra::Record) -> Result<Option<peptide_search_matches::Record>> {
+        let charges = match spectrum.parent_z {
+            0 => self.options.default_charges.clone(),
+            z => vec![z],
+        };
+
+        let mut best: Option<peptide_search_matches::Record> = None;
+        for charge in charges {
+            let mass = match mz_to_neutral_mass(spectrum.parent_mz, charge, Adduct::Proton) {
+                Some(mass) if spectrum.parent_mz > 0.0 => mass,
+                _                                      => continue,
+            };
+            let max_fragment_charge = self.options.max_fragment_charge.min(charge.abs());
+            for peptide in self.index.query(mass, self.options.precursor_tolerance)? {
+                let fragments = fragment_ions(&peptide.sequence, &[], &self.options.ions, max_fragment_charge, &self.table);
+                let score = hyperscore(&spectrum.peaks, &fragments, self.options.fragment_tolerance);
+                if score.matched() < self.options.min_matched {
+                    continue;
+                }
+                let better = match best {
+                    Some(ref b) => score.score > b.score,
+                    None        => true,
+                };
+                if better {
+                    best = Some(peptide_search_matches::Record {
+                        num: spectrum.num,
+                        file: spectrum.file.clone(),
+                        peptide: peptide.sequence,
+                        modifications: vec![],
+                        proteins: peptide.proteins,
+                        charge: charge,
+                        score: score.score,
+                    });
+                }
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Find the best peptide matches for MS2 spectra, skipping other MS levels.
+    ///
+    /// Stops at the first error.
+    pub fn search_all<Iter>(&mut self, spectra: Iter) -> Result<peptide_search_matches::RecordList>
+        where Iter: IntoIterator<Item = Result<mass_spectra::Record>>
+    {
+        // A new token is never cancelled.
+        self.search_all_with_cancel(spectra, &CancellationToken::new())
+    }
+
+    /// Find the best peptide matches for MS2 spectra, stopping early if cancelled.
+    ///
+    /// The token is checked before searching each spectrum.
+    pub fn search_all_with_cancel<Iter>(&mut self, spectra: Iter, cancel: &CancellationToken)
+        -> Result<peptide_search_matches::RecordList>
+        where Iter: IntoIterator<Item = Result<mass_spectra::Record>>
+    {
+        let mut matches = vec![];
+        for spectrum in spectra {
+            cancel.check()?;
+            let spectrum = spectrum?;
+            if spectrum.ms_level != 2 {
+                continue;
+            }
+            if let Some(record) = self.search(&spectrum)? {
+                matches.push(record);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use bio::digest::DigestOptions;
+    use bio::mass::neutral_mass_to_mz;
+    use db::mass_spectra::Peak;
+    use db::peptide_search_matches::{index_peptides, write_peptide_index};
+    use db::uniprot;
+    use super::*;
+
+    fn engine() -> SearchEngine<Cursor<Vec<u8>>> {
+        let mut p1 = uniprot::Record::new();
+        p1.id = String::from("P1");
+        p1.sequence = b"SAMPLERKPEPTIDEKLVTRAAFNSGKVVEQEDAVAK".to_vec();
+        let mut p2 = uniprot::Record::new();
+        p2.id = String::from("P2");
+        p2.sequence = b"MRAMPLESRKPEPTIDEK".to_vec();
+
+        let table = ProteinMassTable::monoisotopic();
+        let digest = DigestOptions { min_length: 4, min_mass: 0.0, ..DigestOptions::default() };
+        let peptides = index_peptides(&vec![p1, p2], &digest, &table);
+        let mut w = vec![];
+        write_peptide_index(&mut w, &peptides).unwrap();
+        let index = PeptideIndex::new(Cursor::new(w)).unwrap();
+
+        SearchEngine::new(index, table, SearchOptions::default())
+    }
+
+    /// Create a theoretical MS2 spectrum for a peptide.
+    fn spectrum(num: u32, peptide: &[u8], z: i8) -> mass_spectra::Record {
+        let table = ProteinMassTable::monoisotopic();
+        let mut record = mass_spectra::Record::new();
+        record.num = num;
+        record.ms_level = 2;
+        record.file = String::from("run.raw");
+        record.parent_mz = neutral_mass_to_mz(table.total_sequence_mass(peptide), 2, Adduct::Proton).unwrap();
+        record.parent_z = z;
+        record.peaks = fragment_ions(peptide, &[], &[IonType::B, IonType::Y], 1, &table).into_iter()
+            .map(|f| Peak { mz: f.mz, intensity: 100.0, z: 1, mobility: None })
+            .collect();
+        record
+    }
+
+    #[test]
+    fn search_test() {
+        let mut engine = engine();
+
+        let psm = engine.search(&spectrum(7, b"KPEPTIDEK", 2)).unwrap().unwrap();
+        assert_eq!(psm.num, 7);
+        assert_eq!(psm.file, "run.raw");
+        assert_eq!(psm.peptide, b"KPEPTIDEK".to_vec());
+        assert_eq!(psm.proteins, vec![String::from("P1"), String::from("P2")]);
+        assert_eq!(psm.charge, 2);
+        assert!(psm.score > 0.0);
+
+        // Isobaric peptides are distinguished by the fragments.
+        let psm = engine.search(&spectrum(8, b"AMPLESR", 0)).unwrap().unwrap();
+        assert_eq!(psm.peptide, b"AMPLESR".to_vec());
+        assert_eq!(psm.charge, 2);
+
+        // Unmatched precursor.
+        let mut unmatched = spectrum(9, b"KPEPTIDEK", 2);
+        unmatched.parent_mz += 1.0;
+        assert!(engine.search(&unmatched).unwrap().is_none());
+    }
+
+    #[test]
+    fn search_all_test() {
+        let mut engine = engine();
+        let mut ms1 = spectrum(1, b"LVTR", 2);
+        ms1.ms_level = 1;
+        let spectra = vec![Ok(ms1), Ok(spectrum(2, b"LVTR", 2)), Ok(spectrum(3, b"VVEQEDAVAK", 2))];
+        let matches = engine.search_all(spectra).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].peptide, b"LVTR".to_vec());
+        assert_eq!(matches[1].peptide, b"VVEQEDAVAK".to_vec());
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(engine.search_all_with_cancel(vec![Ok(spectrum(2, b"LVTR", 2))], &cancel).is_err());
+    }
+}
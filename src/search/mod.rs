@@ -0,0 +1,14 @@
+//! Experimental database search of MS2 spectra.
+//!
+//! Matches MS2 spectra against a mass-indexed peptide database,
+//! scoring the theoretical fragments of each candidate peptide by
+//! hyperscore, and reports the best match for each spectrum as a
+//! peptide search match. The search does not estimate false discovery
+//! rates, nor consider variable modifications.
+
+pub(crate) mod engine;
+pub(crate) mod score;
+
+// Re-export the search engine and scores into the parent module.
+pub use self::engine::{SearchEngine, SearchOptions};
+pub use self::score::{hyperscore, HyperScore};
@@ -0,0 +1,92 @@
+//! Peptide-spectrum match scoring.
+
+use bio::fragment::{Fragment, IonType};
+use bio::mass::Tolerance;
+use db::mass_spectra::Peak;
+use util::search::partition_point;
+
+/// Score of a peptide-spectrum match, with the matched fragments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HyperScore {
+    /// Hyperscore, `ln(Nb! * Ny! * sum(I))`, or 0 without matches.
+    pub score: f64,
+    /// Number of matched N-terminal (a- and b-) fragments.
+    pub matched_n: usize,
+    /// Number of matched C-terminal (y-) fragments.
+    pub matched_c: usize,
+}
+
+impl HyperScore {
+    /// Get the total number of matched fragments.
+    #[inline]
+    pub fn matched(&self) -> usize {
+        self.matched_n + self.matched_c
+    }
+}
+
+/// Calculate `ln(n!)`.
+#[inline]
+fn ln_factorial(n: usize) -> f64 {
+    (2..n+1).map(|k| (k as f64).ln()).sum()
+}
+
+/// Calculate the X!Tandem hyperscore of theoretical fragments against peaks.
+///
+/// Each fragment matches the most intense peak within the tolerance,
+/// and the matched intensities are summed. Peaks must be sorted by
+/// increasing m/z.
+pub fn hyperscore(peaks: &[Peak], fragments: &[Fragment], tolerance: Tolerance) -> HyperScore {
+    let mut intensity = 0.0;
+    let mut matched_n = 0;
+    let mut matched_c = 0;
+    for fragment in fragments {
+        let (lo, hi) = tolerance.range(fragment.mz);
+        let first = partition_point(peaks, |p| p.mz < lo);
+        let last = partition_point(peaks, |p| p.mz <= hi);
+        let best = peaks[first..last.max(first)].iter().map(|p| p.intensity).fold(0.0, f64::max);
+        if best > 0.0 {
+            intensity += best;
+            match fragment.ion {
+                IonType::A | IonType::B => matched_n += 1,
+                IonType::Y              => matched_c += 1,
+            }
+        }
+    }
+
+    let score = match intensity > 0.0 {
+        true  => ln_factorial(matched_n) + ln_factorial(matched_c) + intensity.ln(),
+        false => 0.0,
+    };
+    HyperScore { score: score, matched_n: matched_n, matched_c: matched_c }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(mz: f64, intensity: f64) -> Peak {
+        Peak { mz: mz, intensity: intensity, z: 1, mobility: None }
+    }
+
+    fn fragment(ion: IonType, mz: f64) -> Fragment {
+        Fragment { ion: ion, ordinal: 1, charge: 1, mz: mz }
+    }
+
+    #[test]
+    fn hyperscore_test() {
+        let peaks = vec![peak(100.0, 10.0), peak(200.0, 5.0), peak(200.01, 20.0), peak(300.0, 15.0)];
+        let fragments = vec![fragment(IonType::B, 100.005), fragment(IonType::B, 200.0), fragment(IonType::Y, 300.0), fragment(IonType::Y, 400.0)];
+        let score = hyperscore(&peaks, &fragments, Tolerance::Da(0.02));
+        assert_eq!(score.matched_n, 2);
+        assert_eq!(score.matched_c, 1);
+        assert_eq!(score.matched(), 3);
+        assert_approx_eq!(score.score, 2f64.ln() + 45f64.ln(), 1e-12);
+
+        let score = hyperscore(&peaks, &fragments[3..], Tolerance::Da(0.02));
+        assert_eq!(score, HyperScore { score: 0.0, matched_n: 0, matched_c: 0 });
+        assert_eq!(hyperscore(&[], &fragments, Tolerance::Da(0.02)).matched(), 0);
+    }
+}
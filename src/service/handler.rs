@@ -0,0 +1,268 @@
+//! Transport-independent request handling for the HTTP service.
+
+use url::form_urlencoded;
+
+use db::uniprot::RecordList;
+use db::uniprot::client;
+use io::uniprot as uniprot_io;
+use traits::{Complete, Valid};
+use util::{Bytes, Result};
+
+/// Default maximum size of a request body, in bytes.
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+// FORMAT
+
+/// Record format of request and response bodies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ServiceFormat {
+    /// Tab-delimited text.
+    Csv,
+    /// FASTA text.
+    Fasta,
+    /// UniProt XML.
+    Xml,
+}
+
+impl ServiceFormat {
+    /// Parse the format from a query parameter value.
+    pub fn from_name(name: &str) -> Option<ServiceFormat> {
+        match name {
+            "csv" | "tsv" => Some(ServiceFormat::Csv),
+            "fasta"       => Some(ServiceFormat::Fasta),
+            "xml"         => Some(ServiceFormat::Xml),
+            _             => None,
+        }
+    }
+
+    /// Get the MIME type of the format.
+    pub fn content_type(&self) -> &'static str {
+        match *self {
+            ServiceFormat::Csv   => "text/tab-separated-values",
+            ServiceFormat::Fasta => "text/plain",
+            ServiceFormat::Xml   => "application/xml",
+        }
+    }
+
+    /// Import UniProt records from bytes in the format.
+    fn import(&self, bytes: &[u8]) -> Result<RecordList> {
+        match *self {
+            ServiceFormat::Csv   => uniprot_io::Csv::from_bytes(bytes),
+            ServiceFormat::Fasta => uniprot_io::Fasta::from_bytes(bytes),
+            ServiceFormat::Xml   => uniprot_io::Xml::from_bytes(bytes),
+        }
+    }
+
+    /// Export UniProt records to bytes in the format.
+    fn export(&self, list: &RecordList) -> Result<Bytes> {
+        match *self {
+            ServiceFormat::Csv   => uniprot_io::Csv::to_bytes(list),
+            ServiceFormat::Fasta => uniprot_io::Fasta::to_bytes(list),
+            ServiceFormat::Xml   => uniprot_io::Xml::to_bytes(list),
+        }
+    }
+}
+
+// RESPONSE
+
+/// Response to a service request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServiceResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// MIME type of the body.
+    pub content_type: &'static str,
+    /// Response body.
+    pub body: Bytes,
+}
+
+impl ServiceResponse {
+    /// Create new successful response.
+    #[inline]
+    pub fn ok(content_type: &'static str, body: Bytes) -> Self {
+        ServiceResponse {
+            status: 200,
+            content_type: content_type,
+            body: body,
+        }
+    }
+
+    /// Create new error response, with a plain-text message.
+    #[inline]
+    pub fn error(status: u16, message: &str) -> Self {
+        ServiceResponse {
+            status: status,
+            content_type: "text/plain",
+            body: format!("{}\n", message).into_bytes(),
+        }
+    }
+}
+
+// SERVICE
+
+/// HTTP service for UniProt record operations.
+#[derive(Clone, Debug)]
+pub struct Service {
+    /// Maximum size of a request body, in bytes.
+    pub max_body_size: usize,
+}
+
+impl Service {
+    /// Create new service.
+    #[inline]
+    pub fn new() -> Self {
+        Service {
+            max_body_size: MAX_BODY_SIZE,
+        }
+    }
+
+    /// Handle a request from the method, URL (path and query) and body.
+    pub fn handle(&self, method: &str, url: &str, body: &[u8]) -> ServiceResponse {
+        let (path, query) = match url.find('?') {
+            Some(i) => (&url[..i], &url[i+1..]),
+            None    => (url, ""),
+        };
+        let params: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+
+        match (method, path) {
+            ("GET", "/health")            => ServiceResponse::ok("text/plain", b"ok\n".to_vec()),
+            ("GET", "/uniprot/fetch")     => self.fetch(&params),
+            ("POST", "/uniprot/convert")  => self.convert(&params, body),
+            ("POST", "/uniprot/validate") => self.validate(&params, body),
+            (_, "/health")            |
+            (_, "/uniprot/fetch")     |
+            (_, "/uniprot/convert")   |
+            (_, "/uniprot/validate")      => ServiceResponse::error(405, "method not allowed"),
+            _                             => ServiceResponse::error(404, "not found"),
+        }
+    }
+
+    /// Fetch UniProt records by accession numbers.
+    fn fetch(&self, params: &[(String, String)]) -> ServiceResponse {
+        let format = match format_param(params, "format", ServiceFormat::Fasta) {
+            Ok(format) => format,
+            Err(response) => return response,
+        };
+        let ids: Vec<&str> = match param(params, "ids") {
+            Some(ids) => ids.split(',').map(|id| id.trim()).filter(|id| !id.is_empty()).collect(),
+            None      => vec![],
+        };
+        if ids.is_empty() {
+            return ServiceResponse::error(400, "missing parameter: ids");
+        }
+
+        let result = match client::fetch_by_ids(&ids) {
+            Ok(result) => result,
+            Err(e)     => return ServiceResponse::error(502, &format!("cannot fetch records: {}", e)),
+        };
+        match format.export(&result.records) {
+            Ok(body) => ServiceResponse::ok(format.content_type(), body),
+            Err(e)   => ServiceResponse::error(500, &format!("cannot export records: {}", e)),
+        }
+    }
+
+    /// Convert UniProt records between formats.
+    fn convert(&self, params: &[(String, String)], body: &[u8]) -> ServiceResponse {
+        let from = match format_param(params, "from", ServiceFormat::Fasta) {
+            Ok(format) => format,
+            Err(response) => return response,
+        };
+        let to = match format_param(params, "to", ServiceFormat::Fasta) {
+            Ok(format) => format,
+            Err(response) => return response,
+        };
+
+        let list = match from.import(body) {
+            Ok(list) => list,
+            Err(e)   => return ServiceResponse::error(400, &format!("cannot import records: {}", e)),
+        };
+        match to.export(&list) {
+            Ok(body) => ServiceResponse::ok(to.content_type(), body),
+            Err(e)   => ServiceResponse::error(400, &format!("cannot export records: {}", e)),
+        }
+    }
+
+    /// Validate UniProt records, reporting one tab-delimited line per record.
+    fn validate(&self, params: &[(String, String)], body: &[u8]) -> ServiceResponse {
+        let format = match format_param(params, "format", ServiceFormat::Fasta) {
+            Ok(format) => format,
+            Err(response) => return response,
+        };
+        let list = match format.import(body) {
+            Ok(list) => list,
+            Err(e)   => return ServiceResponse::error(400, &format!("cannot import records: {}", e)),
+        };
+
+        let mut report = String::from("id\tvalid\tcomplete\n");
+        for record in list.iter() {
+            report.push_str(&format!("{}\t{}\t{}\n", record.id, record.is_valid(), record.is_complete()));
+        }
+        ServiceResponse::ok("text/tab-separated-values", report.into_bytes())
+    }
+}
+
+// PARAMETERS
+
+/// Get the first value of a query parameter.
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params.iter().find(|p| p.0 == name).map(|p| p.1.as_str())
+}
+
+/// Get a format query parameter, or an error response for unknown formats.
+fn format_param(params: &[(String, String)], name: &str, default: ServiceFormat)
+    -> ::std::result::Result<ServiceFormat, ServiceResponse>
+{
+    match param(params, name) {
+        None        => Ok(default),
+        Some(value) => ServiceFormat::from_name(value)
+            .ok_or_else(|| ServiceResponse::error(400, &format!("unknown format: {}", value))),
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::str;
+    use db::uniprot::test::*;
+    use super::*;
+
+    #[test]
+    fn routing_test() {
+        let service = Service::new();
+        assert_eq!(service.handle("GET", "/health", b"").body, b"ok\n".to_vec());
+        assert_eq!(service.handle("GET", "/missing", b"").status, 404);
+        assert_eq!(service.handle("GET", "/uniprot/convert", b"").status, 405);
+        assert_eq!(service.handle("POST", "/uniprot/fetch", b"").status, 405);
+        assert_eq!(service.handle("GET", "/uniprot/fetch", b"").status, 400);
+        assert_eq!(service.handle("GET", "/uniprot/fetch?ids=,", b"").status, 400);
+        assert_eq!(service.handle("GET", "/uniprot/fetch?ids=P46406&format=pdb", b"").status, 400);
+    }
+
+    #[test]
+    fn convert_test() {
+        let service = Service::new();
+        let response = service.handle("POST", "/uniprot/convert?from=fasta&to=csv", GAPDH_FASTA);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "text/tab-separated-values");
+        let list = uniprot_io::Csv::from_bytes(&response.body).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].mnemonic, "G3P_RABIT");
+
+        let response = service.handle("POST", "/uniprot/convert?from=fasta", b"not fasta\n");
+        assert_eq!(response.status, 400);
+        assert!(str::from_utf8(&response.body).unwrap().starts_with("cannot import records"));
+    }
+
+    #[test]
+    fn validate_test() {
+        let service = Service::new();
+        let body = uniprot_io::Fasta::to_bytes(&vec![gapdh(), bsa()]).unwrap();
+        let response = service.handle("POST", "/uniprot/validate", &body);
+        assert_eq!(response.status, 200);
+        assert_eq!(str::from_utf8(&response.body).unwrap(), "id\tvalid\tcomplete\nP46406\ttrue\tfalse\nP02769\ttrue\tfalse\n");
+    }
+}
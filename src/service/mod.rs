@@ -0,0 +1,22 @@
+//! HTTP service for record operations.
+//!
+//! Exposes the UniProt client and converters as a small HTTP API, so
+//! bdb may run as a microservice within pipeline infrastructures:
+//!
+//! * `GET /health` - Check the service is running.
+//! * `GET /uniprot/fetch?ids=P46406,P02769&format=fasta` - Fetch
+//!   records from UniProt KB by accession numbers.
+//! * `POST /uniprot/convert?from=fasta&to=xml` - Convert the records
+//!   in the request body between formats.
+//! * `POST /uniprot/validate?format=fasta` - Check if the records in
+//!   the request body are valid and complete.
+//!
+//! The record formats are `csv` (tab-delimited text), `fasta` and
+//! `xml`. Requests are handled independently of the transport by
+//! `Service::handle`, and served over HTTP by `Service::serve`.
+
+pub(crate) mod handler;
+pub(crate) mod server;
+
+// Re-export the service and responses into the parent module.
+pub use self::handler::{Service, ServiceFormat, ServiceResponse};
@@ -0,0 +1,90 @@
+//! HTTP transport for the service, built on `tiny_http`.
+
+use std::io::{self, Read};
+use tiny_http::{Header, Request, Response, Server};
+
+use util::Result;
+use super::handler::{Service, ServiceResponse};
+
+impl Service {
+    /// Serve requests on an address, for example, `"0.0.0.0:8080"`.
+    ///
+    /// Requests are handled sequentially, and the function only
+    /// returns if the server cannot be started.
+    pub fn serve(&self, address: &str) -> Result<()> {
+        let server = Server::http(address).map_err(io::Error::other)?;
+        self.serve_with(&server);
+        Ok(())
+    }
+
+    /// Serve requests from a running server, until the server is unblocked.
+    pub fn serve_with(&self, server: &Server) {
+        for request in server.incoming_requests() {
+            // Failures to respond are from disconnected clients,
+            // and must not stop the service.
+            let _ = self.respond(request);
+        }
+    }
+
+    /// Handle and respond to a single request.
+    fn respond(&self, mut request: Request) -> io::Result<()> {
+        let mut body = vec![];
+        let limit = self.max_body_size as u64;
+        request.as_reader().take(limit + 1).read_to_end(&mut body)?;
+        let response = match body.len() as u64 > limit {
+            true  => ServiceResponse::error(413, "request body too large"),
+            false => self.handle(request.method().as_str(), request.url(), &body),
+        };
+
+        let content_type = Header::from_bytes(&b"Content-Type"[..], response.content_type.as_bytes())
+            .expect("content types are valid header values");
+        let reply = Response::from_data(response.body)
+            .with_status_code(response.status)
+            .with_header(content_type);
+        request.respond(reply)
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::thread;
+    use super::*;
+
+    /// Send a raw HTTP request, and read the response.
+    fn send(address: &str, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serve_test() {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let address = server.server_addr().to_ip().unwrap().to_string();
+        let mut service = Service::new();
+        service.max_body_size = 8;
+        let handle = {
+            let server = server.clone();
+            thread::spawn(move || service.serve_with(&server))
+        };
+
+        let response = send(&address, b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("Content-Type: text/plain"));
+        assert!(response.ends_with("\r\n\r\nok\n"));
+
+        let response = send(&address, b"POST /uniprot/validate HTTP/1.1\r\nHost: localhost\r\nContent-Length: 10\r\nConnection: close\r\n\r\n0123456789");
+        assert!(response.starts_with("HTTP/1.1 413"));
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+}
@@ -1,49 +1,25 @@
 //! Shared helper utilities for unit testing.
 
-use std::env;
-use std::path::PathBuf;
-
 // PATH
 
-/// Return the `target/debug` directory path.
-pub fn debug_dir() -> PathBuf {
-    env::current_exe()
-        .expect("unittest executable path")
-        .parent()
-        .expect("unittest executable directory")
-        .parent()
-        .expect("debug directory")
-        .to_path_buf()
-}
-
-/// Return the `target` directory path.
-pub fn target_dir() -> PathBuf {
-    debug_dir()
-        .parent()
-        .expect("target directory")
-        .to_path_buf()
-}
-
-/// Return the project directory path.
-pub fn project_dir() -> PathBuf {
-    target_dir()
-        .parent()
-        .expect("project directory")
-        .to_path_buf()
-}
-
-/// Return the `test` directory path.
-pub fn test_dir() -> PathBuf {
-    let mut dir = project_dir();
-    dir.push("test");
-    dir
-}
-
-/// Return the `test/data` directory path.
-pub fn testdata_dir() -> PathBuf {
-    let mut dir = test_dir();
-    dir.push("data");
-    dir
+pub use testdata::testdata_dir;
+
+// TEXT
+
+/// Convert a document to the quirks of files produced on Windows.
+///
+/// Adds a UTF-8 byte order mark, converts line endings to `\r\n`,
+/// and adds trailing whitespace to every line.
+pub fn windows_text(text: &[u8]) -> Vec<u8> {
+    let mut result = b"\xEF\xBB\xBF".to_vec();
+    for &c in text {
+        if c == b'\n' {
+            result.extend_from_slice(b" \r\n");
+        } else {
+            result.push(c);
+        }
+    }
+    result
 }
 
 // REGEX
@@ -0,0 +1,284 @@
+//! Reference corpora for conformance testing.
+//!
+//! Small corpora are shipped in the `test/data` directory of the
+//! repository, while larger corpora are downloaded on first use and
+//! cached locally. Each corpus is identified by a stable name, so
+//! the crate and dependents may test readers against identical data.
+//! Since `test/data` is excluded from the published crate, dependents
+//! download the shipped corpora from the repository.
+//!
+//! The downloads use the HTTP client mode, so recorded responses
+//! may be replayed to test without network access.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::PathBuf;
+
+#[cfg(feature = "mgf")]
+use traits::MgfKind;
+use util::{Bytes, Error, Result};
+
+/// Environment variable overriding the `test/data` directory.
+pub const TESTDATA_DIR_VAR: &'static str = "BDB_TESTDATA_DIR";
+
+/// Base URL for the shipped corpora, from the repository.
+pub const REPOSITORY_DATA_URL: &'static str = "https://raw.githubusercontent.com/Alexhuszagh/bdb/master/test/data";
+
+/// Environment variable overriding the download cache directory.
+pub const TESTDATA_CACHE_VAR: &'static str = "BDB_TESTDATA_CACHE";
+
+/// Return the directory of the corpora shipped with the crate.
+///
+/// Defaults to the `test/data` directory of the crate, and may be
+/// overridden with the `BDB_TESTDATA_DIR` environment variable.
+pub fn testdata_dir() -> PathBuf {
+    match env::var_os(TESTDATA_DIR_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None      => PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/test/data")),
+    }
+}
+
+/// Return the directory caching the downloaded corpora.
+///
+/// Defaults to `bdb_testdata` in the temporary directory, and may be
+/// overridden with the `BDB_TESTDATA_CACHE` environment variable.
+pub fn cache_dir() -> PathBuf {
+    match env::var_os(TESTDATA_CACHE_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None      => env::temp_dir().join("bdb_testdata"),
+    }
+}
+
+/// File format of a reference corpus.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CorpusFormat {
+    /// UniProt FASTA.
+    UniProtFasta,
+    /// UniProt tab-delimited CSV.
+    UniProtCsv,
+    /// UniProt XML.
+    UniProtXml,
+    /// Mass spectra MGF, with the vendor flavor.
+    #[cfg(feature = "mgf")]
+    Mgf(MgfKind),
+    /// Protein Data Bank entry.
+    Pdb,
+}
+
+/// Reference corpus, shipped with the repository or downloaded on demand.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Corpus {
+    /// Unique name of the corpus.
+    pub name: &'static str,
+    /// File format of the corpus.
+    pub format: CorpusFormat,
+    /// Relative path, from the `test/data` directory for shipped
+    /// corpora, or from the cache directory for downloaded corpora.
+    pub path: &'static str,
+    /// URL to download the corpus, or `None` if shipped with the repository.
+    pub url: Option<&'static str>,
+}
+
+lazy_static! {
+    /// Registry of reference corpora, for the enabled formats.
+    static ref CORPORA: Vec<Corpus> = {
+        #[allow(unused_mut)]
+        let mut corpora = vec![
+            // UNIPROT
+            Corpus { name: "uniprot_fasta_p46406", format: CorpusFormat::UniProtFasta, path: "uniprot/fasta/P46406.fasta", url: None },
+            Corpus { name: "uniprot_fasta_list", format: CorpusFormat::UniProtFasta, path: "uniprot/fasta/list.fasta", url: None },
+            Corpus { name: "uniprot_csv_list", format: CorpusFormat::UniProtCsv, path: "uniprot/csv/list.csv", url: None },
+            Corpus { name: "uniprot_xml_p46406", format: CorpusFormat::UniProtXml, path: "uniprot/xml/P46406.xml", url: None },
+            Corpus { name: "uniprot_xml_list", format: CorpusFormat::UniProtXml, path: "uniprot/xml/list.xml", url: None },
+            Corpus { name: "uniprot_xml_p69905", format: CorpusFormat::UniProtXml, path: "uniprot/xml/P69905.xml", url: Some("https://rest.uniprot.org/uniprotkb/P69905.xml") },
+            // PDB
+            Corpus { name: "pdb_1crn", format: CorpusFormat::Pdb, path: "pdb/1CRN.pdb", url: Some("https://files.rcsb.org/download/1CRN.pdb") },
+            Corpus { name: "pdb_4hhb", format: CorpusFormat::Pdb, path: "pdb/4HHB.pdb", url: Some("https://files.rcsb.org/download/4HHB.pdb") },
+        ];
+        // MASS SPECTRA
+        #[cfg(feature = "mgf")]
+        corpora.extend_from_slice(&[
+            Corpus { name: "mgf_fullms", format: CorpusFormat::Mgf(MgfKind::FullMs), path: "mass_spectra/mgf/mgf_fullms.txt", url: None },
+            Corpus { name: "mgf_msconvert_ms2", format: CorpusFormat::Mgf(MgfKind::MsConvert), path: "mass_spectra/mgf/mgf_msconvert_ms2.txt", url: None },
+            Corpus { name: "mgf_pava_ms2", format: CorpusFormat::Mgf(MgfKind::Pava), path: "mass_spectra/mgf/mgf_pava_ms2.txt", url: None },
+            Corpus { name: "mgf_pava_ms3", format: CorpusFormat::Mgf(MgfKind::Pava), path: "mass_spectra/mgf/mgf_pava_ms3.txt", url: None },
+            Corpus { name: "mgf_pwiz_ms2", format: CorpusFormat::Mgf(MgfKind::Pwiz), path: "mass_spectra/mgf/mgf_pwiz_ms2.txt", url: None },
+            Corpus { name: "mgf_pwiz_ms3", format: CorpusFormat::Mgf(MgfKind::Pwiz), path: "mass_spectra/mgf/mgf_pwiz_ms3.txt", url: None },
+        ]);
+        corpora
+    };
+}
+
+/// Get the registry of reference corpora.
+pub fn corpora() -> &'static [Corpus] {
+    &CORPORA
+}
+
+/// Find a reference corpus by name.
+pub fn find_corpus(name: &str) -> Option<&'static Corpus> {
+    corpora().iter().find(|c| c.name == name)
+}
+
+/// Get the reference corpora of a file format.
+pub fn corpora_with_format(format: CorpusFormat) -> Vec<&'static Corpus> {
+    corpora().iter().filter(|c| c.format == format).collect()
+}
+
+impl Corpus {
+    /// Check if the corpus is downloaded, rather than shipped with the repository.
+    #[inline]
+    pub fn is_remote(&self) -> bool {
+        self.url.is_some()
+    }
+
+    /// Get the URL to download the corpus.
+    pub fn download_url(&self) -> String {
+        match self.url {
+            Some(url) => String::from(url),
+            None      => format!("{}/{}", REPOSITORY_DATA_URL, self.path),
+        }
+    }
+
+    /// Get the local path to the corpus, which may not exist yet.
+    ///
+    /// Shipped corpora are read from the `test/data` directory if
+    /// present, otherwise all corpora are read from the cache.
+    pub fn local_path(&self) -> PathBuf {
+        if !self.is_remote() {
+            let path = testdata_dir().join(self.path);
+            if path.is_file() {
+                return path;
+            }
+        }
+        cache_dir().join(self.path)
+    }
+
+    /// Check if the corpus is available without a download.
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        self.local_path().is_file()
+    }
+
+    /// Get the path to the corpus, downloading it if not available.
+    pub fn fetch(&self) -> Result<PathBuf> {
+        let path = self.local_path();
+        match path.is_file() {
+            true  => Ok(path),
+            false => download(&self.download_url(), path),
+        }
+    }
+
+    /// Open the corpus, downloading it if not available.
+    pub fn open(&self) -> Result<BufReader<File>> {
+        let path = self.fetch()?;
+        let file = File::open(&path).map_err(|e| Error::from(e).with_path(&path))?;
+        Ok(BufReader::new(file))
+    }
+
+    /// Read the corpus to bytes, downloading it if not available.
+    pub fn read(&self) -> Result<Bytes> {
+        let path = self.fetch()?;
+        ::std::fs::read(&path).map_err(|e| Error::from(e).with_path(&path))
+    }
+}
+
+/// Download a corpus to the cache, atomically.
+#[cfg(feature = "http")]
+fn download(url: &str, path: PathBuf) -> Result<PathBuf> {
+    use std::fs;
+    use io::atomic::write_atomic;
+    use io::http;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::from(e).with_path(dir))?;
+    }
+    let mut response = http::get(url)?;
+    write_atomic(&path, |w| { io::copy(&mut response, w)?; Ok(()) })
+        .map_err(|e| e.with_path(&path))?;
+    Ok(path)
+}
+
+/// Download a corpus to the cache, which requires the `http` feature.
+#[cfg(not(feature = "http"))]
+fn download(_: &str, path: PathBuf) -> Result<PathBuf> {
+    let error = io::Error::new(io::ErrorKind::NotFound, "reference corpus not found");
+    Err(Error::from(error).with_path(path))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    #[test]
+    fn registry_test() {
+        let names: HashSet<_> = corpora().iter().map(|c| c.name).collect();
+        assert_eq!(names.len(), corpora().len());
+        assert_eq!(find_corpus("uniprot_csv_list").unwrap().format, CorpusFormat::UniProtCsv);
+        assert!(find_corpus("missing").is_none());
+        assert_eq!(corpora_with_format(CorpusFormat::Pdb).len(), 2);
+
+        // Shipped corpora must exist, and never be downloaded.
+        for corpus in corpora().iter().filter(|c| !c.is_remote()) {
+            assert!(corpus.is_available(), "missing corpus {}", corpus.name);
+            assert_eq!(corpus.fetch().unwrap(), testdata_dir().join(corpus.path));
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn fetch_test() {
+        use std::fs;
+        use io::http::{fixture_path, set_client_mode, ClientMode, CLIENT_MODE_LOCK};
+
+        // Replay a recorded response, to download without network access.
+        let _lock = CLIENT_MODE_LOCK.lock().unwrap();
+        let fixtures = env::temp_dir().join("bdb_testdata_fixtures");
+        let corpus = find_corpus("pdb_1crn").unwrap();
+        let text = b"HEADER    PLANT PROTEIN                           30-APR-81   1CRN              \n";
+        fs::create_dir_all(&fixtures).unwrap();
+        fs::write(fixture_path(&fixtures, corpus.url.unwrap()), &text[..]).unwrap();
+        let _ = fs::remove_file(corpus.local_path());
+
+        set_client_mode(ClientMode::Replay(fixtures.clone()));
+        let result = corpus.read();
+        set_client_mode(ClientMode::Online);
+        assert_eq!(result.unwrap(), text.to_vec());
+        assert!(corpus.is_available());
+
+        // Shipped corpora missing locally are downloaded from the repository.
+        let missing = Corpus { name: "missing", format: CorpusFormat::Pdb, path: "missing.pdb", url: None };
+        assert_eq!(missing.local_path(), cache_dir().join("missing.pdb"));
+        assert_eq!(missing.download_url(), format!("{}/missing.pdb", REPOSITORY_DATA_URL));
+        set_client_mode(ClientMode::Replay(fixtures.clone()));
+        let result = missing.fetch();
+        set_client_mode(ClientMode::Online);
+        assert!(result.is_err());
+
+        fs::remove_file(corpus.local_path()).unwrap();
+        fs::remove_dir_all(&fixtures).unwrap();
+    }
+
+    #[cfg(all(feature = "uniprot", feature = "mass_spectrometry", feature = "fasta", feature = "csv", feature = "xml", feature = "mgf"))]
+    #[test]
+    fn conformance_test() {
+        use db::mass_spectra::low_level::iterator_from_mgf;
+        use db::uniprot::low_level::{iterator_from_csv, iterator_from_fasta, iterator_from_xml_lenient};
+
+        for corpus in corpora().iter().filter(|c| !c.is_remote()) {
+            let reader = corpus.open().unwrap();
+            let count = match corpus.format {
+                CorpusFormat::UniProtFasta => iterator_from_fasta(reader).map(|r| r.unwrap()).count(),
+                CorpusFormat::UniProtCsv   => iterator_from_csv(reader, b'\t').map(|r| r.unwrap()).count(),
+                CorpusFormat::UniProtXml   => iterator_from_xml_lenient(reader).map(|r| r.unwrap()).count(),
+                CorpusFormat::Mgf(kind)    => iterator_from_mgf(reader, kind).map(|r| r.unwrap()).count(),
+                CorpusFormat::Pdb          => continue,
+            };
+            assert!(count > 0, "empty corpus {}", corpus.name);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::convert::AsRef;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::path::Path;
+
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, Result};
+
+/// Serialize to and from Clustal.
+///
+/// Clustal is the multiple sequence alignment format written by the
+/// Clustal family of aligners, with the alignment split into blocks
+/// and an optional conservation line below each block. Each document
+/// contains a single alignment.
+///
+/// # Serialized Format
+///
+/// ```text
+/// CLUSTAL W multiple sequence alignment
+///
+///
+/// O31698/18-71      AVKKHEHVQDVLLRGSNVLA-CAMENSKLYGS
+/// O83071/192-246    MTCRAQLIAVPRASSLDE--AIACAQKMAHL-
+///                   :  :  :   .      *    :* :. *
+/// ```
+pub trait Clustal: Sized {
+    /// Estimate the size of the resulting Clustal output to avoid reallocations.
+    #[inline(always)]
+    fn estimate_clustal_size(&self) -> usize {
+        0
+    }
+
+    /// Export model to Clustal.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_clustal<T: Write>(&self, writer: &mut T) -> Result<()>;
+
+    /// Export model to Clustal bytes.
+    fn to_clustal_bytes(&self) -> Result<Bytes> {
+        let capacity = self.estimate_clustal_size();
+        let mut writer = Cursor::new(Vec::with_capacity(capacity));
+
+        self.to_clustal(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    /// Export model to Clustal string.
+    #[inline]
+    fn to_clustal_string(&self) -> Result<String> {
+        Ok(String::from_utf8(self.to_clustal_bytes()?)?)
+    }
+
+    /// Export model to Clustal output file.
+    #[inline]
+    fn to_clustal_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.to_clustal(&mut writer)
+    }
+
+    /// Export model to Clustal output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_clustal_path`, adding the path to errors.
+    #[inline]
+    fn to_clustal_file_atomic<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_clustal_path(path)
+    }
+
+    /// Import model from Clustal.
+    fn from_clustal<T: BufRead>(reader: &mut T) -> Result<Self>;
+
+    /// Import model from Clustal bytes.
+    #[inline]
+    fn from_clustal_bytes(bytes: &[u8]) -> Result<Self> {
+        // Rust uses the contents of the immutable &str as the buffer
+        // Cursor is then immutable.
+        let mut reader = Cursor::new(bytes);
+        Self::from_clustal(&mut reader)
+    }
+
+    /// Import model from Clustal string.
+    #[inline]
+    fn from_clustal_string(string: &str) -> Result<Self> {
+        Self::from_clustal_bytes(string.as_bytes())
+    }
+
+    /// Import model from Clustal file.
+    #[inline]
+    fn from_clustal_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let mut reader = BufReader::new(file);
+        Self::from_clustal(&mut reader)
+    }
+
+    path_methods!(to_clustal_path, from_clustal_path, to_clustal, from_clustal);
+}
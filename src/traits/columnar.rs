@@ -0,0 +1,90 @@
+use std::convert::AsRef;
+use std::io::Write;
+use std::path::Path;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+
+use io::atomic::write_atomic;
+use util::Result;
+
+/// Default number of models in each columnar record batch.
+pub const COLUMNAR_BATCH_SIZE: usize = 8192;
+
+/// Serialize to columnar formats, Apache Arrow IPC and Parquet.
+///
+/// Models are converted to Arrow record batches of up to
+/// `COLUMNAR_BATCH_SIZE` models, so iterators are exported lazily,
+/// with a bounded memory footprint. Only the batch conversion must be
+/// implemented, the file formats are handled by the Arrow writers.
+pub trait Columnar: Sized {
+    /// Get the Arrow schema of the record batches.
+    fn arrow_schema() -> SchemaRef;
+
+    /// Convert models to an Arrow record batch.
+    fn to_record_batch(models: &[&Self]) -> Result<RecordBatch>;
+
+    /// Export models from an iterator to an Arrow IPC file.
+    fn to_arrow_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        let mut writer = FileWriter::try_new(writer, &Self::arrow_schema())?;
+        for_each_batch(iter, |batch| Ok(writer.write(&batch)?))?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Export models from an iterator to an Arrow IPC output file, atomically.
+    #[inline]
+    fn to_arrow_file_iter<'a, P: AsRef<Path>, Iter: Iterator<Item = &'a Self>>(path: P, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        write_atomic(path, |writer| Self::to_arrow_iter(writer, iter))
+    }
+
+    /// Export models from an iterator to a Parquet file.
+    fn to_parquet_iter<'a, T: Write + Send, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        let mut writer = ArrowWriter::try_new(writer, Self::arrow_schema(), None)?;
+        for_each_batch(iter, |batch| Ok(writer.write(&batch)?))?;
+        writer.close()?;
+        Ok(())
+    }
+
+    /// Export models from an iterator to a Parquet output file, atomically.
+    #[inline]
+    fn to_parquet_file_iter<'a, P: AsRef<Path>, Iter: Iterator<Item = &'a Self>>(path: P, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        write_atomic(path, |writer| Self::to_parquet_iter(writer, iter))
+    }
+}
+
+/// Convert models from an iterator to record batches, and process each batch.
+fn for_each_batch<'a, M, Iter, F>(iter: Iter, mut callback: F)
+    -> Result<()>
+    where M: 'a + Columnar,
+          Iter: Iterator<Item = &'a M>,
+          F: FnMut(RecordBatch) -> Result<()>
+{
+    let mut models: Vec<&'a M> = Vec::with_capacity(COLUMNAR_BATCH_SIZE);
+    for model in iter {
+        models.push(model);
+        if models.len() == COLUMNAR_BATCH_SIZE {
+            callback(M::to_record_batch(&models)?)?;
+            models.clear();
+        }
+    }
+    if !models.is_empty() {
+        callback(M::to_record_batch(&models)?)?;
+    }
+
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use std::convert::AsRef;
 use std::fs::File;
 use std::io::{Cursor, Read, Write};
+use std::iter;
 use std::path::Path;
 
-use util::{Bytes, Result};
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, ExportMetadata, Result, CSV_COMMENT};
 
 /// Serialize to and from CSV.
 ///
@@ -42,6 +44,31 @@ pub trait Csv: Sized {
         self.to_csv(&mut file, delimiter)
     }
 
+    /// Export model to CSV output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_csv_path`, adding the path to errors.
+    #[inline]
+    fn to_csv_file_atomic<P: AsRef<Path>>(&self, path: P, delimiter: u8) -> Result<()> {
+        self.to_csv_path(path, delimiter)
+    }
+
+    /// Export model to CSV, preceded by a provenance metadata block.
+    ///
+    /// The metadata is written as leading comment lines, which are
+    /// skipped by the CSV readers.
+    fn to_csv_with_metadata<T: Write>(&self, writer: &mut T, delimiter: u8, metadata: &ExportMetadata) -> Result<()> {
+        metadata.to_comment(writer, CSV_COMMENT)?;
+        self.to_csv(writer, delimiter)
+    }
+
+    /// Export model to CSV output file, preceded by a provenance metadata block.
+    #[inline]
+    fn to_csv_file_with_metadata<P: AsRef<Path>>(&self, path: P, delimiter: u8, metadata: &ExportMetadata) -> Result<()> {
+        let mut file = File::create(path)?;
+        self.to_csv_with_metadata(&mut file, delimiter, metadata)
+    }
+
     /// Import model from CSV (with headers).
     ///
     /// Works identically to a collection importer, only fetches at max
@@ -69,6 +96,36 @@ pub trait Csv: Sized {
         let mut reader = File::open(path)?;
         Self::from_csv(&mut reader, delimiter)
     }
+
+    path_methods!(to_csv_path, from_csv_path, to_csv, from_csv, delimiter: u8);
+
+    /// Lazily import models from CSV.
+    ///
+    /// By default, the entire document is imported as a single model.
+    /// Models stored as individual items within a document (such as
+    /// records) yield each item in turn.
+    #[inline]
+    fn from_csv_iter<'a, T: 'a + Read>(mut reader: T, delimiter: u8)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+        where Self: 'a
+    {
+        Box::new(iter::once(()).map(move |_| Self::from_csv(&mut reader, delimiter)))
+    }
+
+    /// Export models from an iterator to CSV.
+    ///
+    /// By default, each model is exported as a separate CSV document,
+    /// so models that share a document (such as records) should
+    /// override this to write a single document.
+    fn to_csv_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter, delimiter: u8)
+        -> Result<()>
+        where Self: 'a
+    {
+        for item in iter {
+            item.to_csv(writer, delimiter)?;
+        }
+        Ok(())
+    }
 }
 
 /// Specialization of the `Csv` trait for collections.
@@ -1,9 +1,11 @@
 use std::convert::AsRef;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::iter;
 use std::path::Path;
 
-use util::{Bytes, Result};
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, ExportMetadata, Result, FASTA_COMMENT};
 
 /// Serialize to and from FASTA.
 ///
@@ -54,6 +56,32 @@ pub trait Fasta: Sized {
         self.to_fasta(&mut writer)
     }
 
+    /// Export model to FASTA output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_fasta_path`, adding the path to errors.
+    #[inline]
+    fn to_fasta_file_atomic<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_fasta_path(path)
+    }
+
+    /// Export model to FASTA, preceded by a provenance metadata block.
+    ///
+    /// The metadata is written as leading comment lines, which are
+    /// skipped by the FASTA readers.
+    fn to_fasta_with_metadata<T: Write>(&self, writer: &mut T, metadata: &ExportMetadata) -> Result<()> {
+        metadata.to_comment(writer, FASTA_COMMENT)?;
+        self.to_fasta(writer)
+    }
+
+    /// Export model to FASTA output file, preceded by a provenance metadata block.
+    #[inline]
+    fn to_fasta_file_with_metadata<P: AsRef<Path>>(&self, path: P, metadata: &ExportMetadata) -> Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.to_fasta_with_metadata(&mut writer, metadata)
+    }
+
     /// Import model from FASTA.
     fn from_fasta<T: BufRead>(reader: &mut T) -> Result<Self>;
 
@@ -79,6 +107,41 @@ pub trait Fasta: Sized {
         let mut reader = BufReader::new(file);
         Self::from_fasta(&mut reader)
     }
+
+    path_methods!(to_fasta_path, from_fasta_path, to_fasta, from_fasta);
+
+    /// Lazily import models from FASTA.
+    ///
+    /// By default, the entire document is imported as a single model.
+    /// Models stored as individual items within a document (such as
+    /// records) yield each item in turn.
+    #[inline]
+    fn from_fasta_iter<'a, T: 'a + BufRead>(mut reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+        where Self: 'a
+    {
+        Box::new(iter::once(()).map(move |_| Self::from_fasta(&mut reader)))
+    }
+
+    /// Export models from an iterator to FASTA.
+    ///
+    /// By default, each model is exported in turn, separated by a
+    /// newline.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_fasta_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        for (index, item) in iter.enumerate() {
+            if index > 0 {
+                writer.write_all(b"\n")?;
+            }
+            item.to_fasta(writer)?;
+        }
+        Ok(())
+    }
 }
 
 /// Specialization of the `Fasta` trait for collections.
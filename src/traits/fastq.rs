@@ -1,9 +1,11 @@
 use std::convert::AsRef;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::iter;
 use std::path::Path;
 
-use util::{Bytes, Result};
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, Result};
 
 /// Serialize to and from FASTQ.
 ///
@@ -51,6 +53,15 @@ pub trait Fastq: Sized {
         self.to_fastq(&mut writer)
     }
 
+    /// Export model to FASTQ output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_fastq_path`, adding the path to errors.
+    #[inline]
+    fn to_fastq_file_atomic<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_fastq_path(path)
+    }
+
     /// Import model from FASTQ.
     fn from_fastq<T: BufRead>(reader: &mut T) -> Result<Self>;
 
@@ -76,6 +87,41 @@ pub trait Fastq: Sized {
         let mut reader = BufReader::new(file);
         Self::from_fastq(&mut reader)
     }
+
+    path_methods!(to_fastq_path, from_fastq_path, to_fastq, from_fastq);
+
+    /// Lazily import models from FASTQ.
+    ///
+    /// By default, the entire document is imported as a single model.
+    /// Models stored as individual items within a document (such as
+    /// records) yield each item in turn.
+    #[inline]
+    fn from_fastq_iter<'a, T: 'a + BufRead>(mut reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+        where Self: 'a
+    {
+        Box::new(iter::once(()).map(move |_| Self::from_fastq(&mut reader)))
+    }
+
+    /// Export models from an iterator to FASTQ.
+    ///
+    /// By default, each model is exported in turn, separated by a
+    /// newline.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_fastq_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        for (index, item) in iter.enumerate() {
+            if index > 0 {
+                writer.write_all(b"\n")?;
+            }
+            item.to_fastq(writer)?;
+        }
+        Ok(())
+    }
 }
 
 /// Specialization of the `Fastq` trait for collections.
@@ -0,0 +1,12 @@
+//! Unique keys for models, used by the indexed record stores.
+
+use std::hash::Hash;
+
+/// Model with a unique key, for indexed lookups.
+pub trait Keyed {
+    /// Type of the key, for example, an accession or a scan number.
+    type Key: Clone + Eq + Hash;
+
+    /// Get the key identifying the model.
+    fn key(&self) -> &Self::Key;
+}
@@ -1,9 +1,11 @@
 use std::convert::AsRef;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::iter;
 use std::path::Path;
 
-use util::{Bytes, Result};
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, ExportMetadata, Result, MGF_COMMENT};
 
 /// Identifier for the MGF file format type.
 ///
@@ -83,6 +85,32 @@ pub trait Mgf: Sized {
         self.to_mgf(&mut writer, kind)
     }
 
+    /// Export model to MGF output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_mgf_path`, adding the path to errors.
+    #[inline]
+    fn to_mgf_file_atomic<P: AsRef<Path>>(&self, path: P, kind: MgfKind) -> Result<()> {
+        self.to_mgf_path(path, kind)
+    }
+
+    /// Export model to MGF, preceded by a provenance metadata block.
+    ///
+    /// The metadata is written as leading comment lines, which are
+    /// skipped by the MGF readers.
+    fn to_mgf_with_metadata<T: Write>(&self, writer: &mut T, kind: MgfKind, metadata: &ExportMetadata) -> Result<()> {
+        metadata.to_comment(writer, MGF_COMMENT)?;
+        self.to_mgf(writer, kind)
+    }
+
+    /// Export model to MGF output file, preceded by a provenance metadata block.
+    #[inline]
+    fn to_mgf_file_with_metadata<P: AsRef<Path>>(&self, path: P, kind: MgfKind, metadata: &ExportMetadata) -> Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.to_mgf_with_metadata(&mut writer, kind, metadata)
+    }
+
     /// Import model from MGF.
     fn from_mgf<T: BufRead>(reader: &mut T, kind: MgfKind) -> Result<Self>;
 
@@ -108,6 +136,41 @@ pub trait Mgf: Sized {
         let mut reader = BufReader::new(file);
         Self::from_mgf(&mut reader, kind)
     }
+
+    path_methods!(to_mgf_path, from_mgf_path, to_mgf, from_mgf, kind: MgfKind);
+
+    /// Lazily import models from MGF.
+    ///
+    /// By default, the entire document is imported as a single model.
+    /// Models stored as individual items within a document (such as
+    /// records) yield each item in turn.
+    #[inline]
+    fn from_mgf_iter<'a, T: 'a + BufRead>(mut reader: T, kind: MgfKind)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+        where Self: 'a
+    {
+        Box::new(iter::once(()).map(move |_| Self::from_mgf(&mut reader, kind)))
+    }
+
+    /// Export models from an iterator to MGF.
+    ///
+    /// By default, each model is exported in turn, separated by a
+    /// newline.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_mgf_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter, kind: MgfKind)
+        -> Result<()>
+        where Self: 'a
+    {
+        for (index, item) in iter.enumerate() {
+            if index > 0 {
+                writer.write_all(b"\n")?;
+            }
+            item.to_mgf(writer, kind)?;
+        }
+        Ok(())
+    }
 }
 
 /// Specialization of the `Mgf` trait for collections.
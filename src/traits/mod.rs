@@ -1,11 +1,24 @@
 //! Shared traits.
 
 pub(crate) mod complete;
-pub(crate) mod fmt;
-pub(crate) mod num;
-pub(crate) mod parse;
+pub(crate) mod keyed;
+pub(crate) mod rename;
 pub(crate) mod valid;
 
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        pub(crate) mod fmt;
+        pub(crate) mod num;
+        pub(crate) mod parse;
+    }
+}
+
+#[cfg(feature = "clustal")]
+pub(crate) mod clustal;
+
+#[cfg(feature = "columnar")]
+pub(crate) mod columnar;
+
 #[cfg(feature = "csv")]
 pub(crate) mod csv;
 
@@ -18,6 +31,12 @@ pub(crate) mod fastq;
 #[cfg(feature = "mgf")]
 pub(crate) mod mgf;
 
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
+
+#[cfg(feature = "stockholm")]
+pub(crate) mod stockholm;
+
 #[cfg(feature = "xml")]
 pub(crate) mod xml;
 
@@ -25,7 +44,17 @@ pub(crate) mod xml;
 pub use self::complete::{Complete};
 pub use self::valid::{Valid};
 
+// Record lookup traits
+pub use self::keyed::{Keyed};
+pub use self::rename::{Renamable};
+
 // Serialization Traits
+#[cfg(feature = "clustal")]
+pub use self::clustal::Clustal;
+
+#[cfg(feature = "columnar")]
+pub use self::columnar::{Columnar, COLUMNAR_BATCH_SIZE};
+
 #[cfg(feature = "csv")]
 pub use self::csv::{Csv, CsvCollection};
 
@@ -38,10 +67,20 @@ pub use self::fastq::{Fastq, FastqCollection};
 #[cfg(feature = "mgf")]
 pub use self::mgf::{Mgf, MgfCollection, MgfKind};
 
+#[cfg(feature = "postgres")]
+pub use self::postgres::{PgColumn, PgCopy, PgType, PgValue};
+
+#[cfg(feature = "stockholm")]
+pub use self::stockholm::{Stockholm, StockholmCollection};
+
 #[cfg(feature = "xml")]
 pub use self::xml::{Xml, XmlCollection};
 
 // Export for internal use only.
-pub(crate) use self::fmt::Serializable;
-pub(crate) use self::num::*;
-pub(crate) use self::parse::Deserializable;
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        pub(crate) use self::fmt::Serializable;
+        pub(crate) use self::num::*;
+        pub(crate) use self::parse::Deserializable;
+    }
+}
@@ -9,7 +9,6 @@ use super::num::Zero;
 /// Efficient conversion of bytes or string to numbers.
 pub(crate) trait Deserializable: Zero {
     /// Efficient bytes to number conversion.
-    #[inline]
     fn import_bytes(bytes: &[u8]) -> Result<Self>;
 }
 
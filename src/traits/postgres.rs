@@ -0,0 +1,429 @@
+use std::borrow::Cow;
+use std::convert::AsRef;
+use std::io::Write;
+use std::path::Path;
+
+use io::atomic::write_atomic;
+use util::Result;
+
+/// PostgreSQL column type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PgType {
+    /// 1-byte boolean (`boolean`).
+    Boolean,
+    /// 2-byte integer (`smallint`).
+    Smallint,
+    /// 4-byte integer (`integer`).
+    Integer,
+    /// 8-byte integer (`bigint`).
+    Bigint,
+    /// 8-byte floating point number (`double precision`).
+    Double,
+    /// Variable-length string (`text`).
+    Text,
+    /// Array of 4-byte integers (`integer[]`).
+    IntegerArray,
+    /// Array of 8-byte integers (`bigint[]`).
+    BigintArray,
+    /// Array of 8-byte floating point numbers (`double precision[]`).
+    DoubleArray,
+    /// Array of variable-length strings (`text[]`).
+    TextArray,
+}
+
+impl PgType {
+    /// Get the SQL name of the type.
+    pub fn sql_name(&self) -> &'static str {
+        match *self {
+            PgType::Boolean      => "boolean",
+            PgType::Smallint     => "smallint",
+            PgType::Integer      => "integer",
+            PgType::Bigint       => "bigint",
+            PgType::Double       => "double precision",
+            PgType::Text         => "text",
+            PgType::IntegerArray => "integer[]",
+            PgType::BigintArray  => "bigint[]",
+            PgType::DoubleArray  => "double precision[]",
+            PgType::TextArray    => "text[]",
+        }
+    }
+}
+
+/// PostgreSQL column definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PgColumn {
+    /// Column name.
+    pub name: &'static str,
+    /// Column type.
+    pub kind: PgType,
+    /// Column accepts null values.
+    pub nullable: bool,
+}
+
+impl PgColumn {
+    /// Create new, non-nullable column.
+    #[inline]
+    pub fn new(name: &'static str, kind: PgType) -> Self {
+        PgColumn {
+            name: name,
+            kind: kind,
+            nullable: false,
+        }
+    }
+
+    /// Create new, nullable column.
+    #[inline]
+    pub fn nullable(name: &'static str, kind: PgType) -> Self {
+        PgColumn {
+            nullable: true,
+            ..PgColumn::new(name, kind)
+        }
+    }
+}
+
+/// Value of a single field within a PostgreSQL row.
+///
+/// The value must match the type of the column.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PgValue<'a> {
+    /// Null value, for nullable columns.
+    Null,
+    /// `boolean` value.
+    Boolean(bool),
+    /// `smallint` value.
+    Smallint(i16),
+    /// `integer` value.
+    Integer(i32),
+    /// `bigint` value.
+    Bigint(i64),
+    /// `double precision` value.
+    Double(f64),
+    /// `text` value.
+    Text(Cow<'a, str>),
+    /// `integer[]` value.
+    IntegerArray(Vec<i32>),
+    /// `bigint[]` value.
+    BigintArray(Vec<i64>),
+    /// `double precision[]` value.
+    DoubleArray(Vec<f64>),
+    /// `text[]` value.
+    TextArray(Vec<Cow<'a, str>>),
+}
+
+impl<'a> PgValue<'a> {
+    /// Create a value from an optional `double precision`.
+    #[inline]
+    pub fn from_double(value: Option<f64>) -> Self {
+        match value {
+            Some(v) => PgValue::Double(v),
+            None    => PgValue::Null,
+        }
+    }
+}
+
+/// Signature of PostgreSQL binary COPY documents.
+const BINARY_SIGNATURE: &'static [u8] = b"PGCOPY\n\xff\r\n\0";
+
+/// Object identifier of the `int4` type, for binary arrays.
+const INT4_OID: i32 = 23;
+
+/// Object identifier of the `int8` type, for binary arrays.
+const INT8_OID: i32 = 20;
+
+/// Object identifier of the `float8` type, for binary arrays.
+const FLOAT8_OID: i32 = 701;
+
+/// Object identifier of the `text` type, for binary arrays.
+const TEXT_OID: i32 = 25;
+
+/// Serialize to the PostgreSQL COPY formats.
+///
+/// Models are exported as rows of a table, in the text (the default
+/// COPY format) or binary formats, to bulk-load records with
+/// `COPY table FROM STDIN` or `COPY table FROM STDIN (FORMAT binary)`.
+/// The table may be created with the generated schema.
+pub trait PgCopy: Sized {
+    /// Get the table columns for the model.
+    fn pg_columns() -> Vec<PgColumn>;
+
+    /// Get the field values of the model, in column order.
+    fn pg_values<'a>(&'a self) -> Vec<PgValue<'a>>;
+
+    /// Generate the `CREATE TABLE` statement for a table of models.
+    fn pg_create_table(table: &str) -> String {
+        let columns: Vec<String> = Self::pg_columns().iter()
+            .map(|c| match c.nullable {
+                true  => format!("    {} {}", c.name, c.kind.sql_name()),
+                false => format!("    {} {} NOT NULL", c.name, c.kind.sql_name()),
+            })
+            .collect();
+        format!("CREATE TABLE {} (\n{}\n);\n", table, columns.join(",\n"))
+    }
+
+    /// Generate the `COPY` statement to load a table of models.
+    fn pg_copy_statement(table: &str, binary: bool) -> String {
+        let columns: Vec<&str> = Self::pg_columns().iter().map(|c| c.name).collect();
+        let format = match binary {
+            true  => " (FORMAT binary)",
+            false => "",
+        };
+        format!("COPY {} ({}) FROM STDIN{};\n", table, columns.join(", "), format)
+    }
+
+    /// Export models from an iterator to the COPY text format.
+    fn to_pg_text_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        for model in iter {
+            for (i, value) in model.pg_values().iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b"\t")?;
+                }
+                export_text_value(writer, value)?;
+            }
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Export models from an iterator to the COPY binary format.
+    fn to_pg_binary_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        // Signature, flags, and header extension length.
+        write_alls!(writer, BINARY_SIGNATURE, &0i32.to_be_bytes(), &0i32.to_be_bytes())?;
+        for model in iter {
+            let values = model.pg_values();
+            writer.write_all(&(values.len() as i16).to_be_bytes())?;
+            for value in values.iter() {
+                export_binary_value(writer, value)?;
+            }
+        }
+        writer.write_all(&(-1i16).to_be_bytes())?;
+        Ok(())
+    }
+
+    /// Export models from an iterator to a COPY text output file, atomically.
+    #[inline]
+    fn to_pg_text_file_iter<'a, P: AsRef<Path>, Iter: Iterator<Item = &'a Self>>(path: P, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        write_atomic(path, |writer| Self::to_pg_text_iter(writer, iter))
+    }
+
+    /// Export models from an iterator to a COPY binary output file, atomically.
+    #[inline]
+    fn to_pg_binary_file_iter<'a, P: AsRef<Path>, Iter: Iterator<Item = &'a Self>>(path: P, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        write_atomic(path, |writer| Self::to_pg_binary_iter(writer, iter))
+    }
+}
+
+// TEXT
+
+/// Export text with the COPY text escapes.
+fn export_text_escaped<T: Write>(writer: &mut T, text: &str)
+    -> Result<()>
+{
+    for &c in text.as_bytes() {
+        match c {
+            b'\\' => writer.write_all(b"\\\\")?,
+            b'\n' => writer.write_all(b"\\n")?,
+            b'\r' => writer.write_all(b"\\r")?,
+            b'\t' => writer.write_all(b"\\t")?,
+            _     => writer.write_all(&[c])?,
+        }
+    }
+    Ok(())
+}
+
+/// Export a single value in the COPY text format.
+fn export_text_value<T: Write>(writer: &mut T, value: &PgValue)
+    -> Result<()>
+{
+    match *value {
+        PgValue::Null                => writer.write_all(b"\\N")?,
+        PgValue::Boolean(v)          => writer.write_all(if v { b"t" } else { b"f" })?,
+        PgValue::Smallint(v)         => write!(writer, "{}", v)?,
+        PgValue::Integer(v)          => write!(writer, "{}", v)?,
+        PgValue::Bigint(v)           => write!(writer, "{}", v)?,
+        PgValue::Double(v)           => write!(writer, "{:?}", v)?,
+        PgValue::Text(ref v)         => export_text_escaped(writer, v)?,
+        PgValue::IntegerArray(ref v) => {
+            let items: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+            write!(writer, "{{{}}}", items.join(","))?;
+        },
+        PgValue::BigintArray(ref v)  => {
+            let items: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+            write!(writer, "{{{}}}", items.join(","))?;
+        },
+        PgValue::DoubleArray(ref v)  => {
+            let items: Vec<String> = v.iter().map(|x| format!("{:?}", x)).collect();
+            write!(writer, "{{{}}}", items.join(","))?;
+        },
+        PgValue::TextArray(ref v)    => {
+            // Quote every element, escaping quotes and backslashes
+            // for the array literal, then escape the literal for COPY.
+            let items: Vec<String> = v.iter()
+                .map(|x| format!("\"{}\"", x.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect();
+            export_text_escaped(writer, &format!("{{{}}}", items.join(",")))?;
+        },
+    }
+    Ok(())
+}
+
+// BINARY
+
+/// Export a single value in the COPY binary format, with the length prefix.
+fn export_binary_value<T: Write>(writer: &mut T, value: &PgValue)
+    -> Result<()>
+{
+    match *value {
+        PgValue::Null                => writer.write_all(&(-1i32).to_be_bytes())?,
+        PgValue::Boolean(v)          => write_alls!(writer, &1i32.to_be_bytes(), &[v as u8])?,
+        PgValue::Smallint(v)         => write_alls!(writer, &2i32.to_be_bytes(), &v.to_be_bytes())?,
+        PgValue::Integer(v)          => write_alls!(writer, &4i32.to_be_bytes(), &v.to_be_bytes())?,
+        PgValue::Bigint(v)           => write_alls!(writer, &8i32.to_be_bytes(), &v.to_be_bytes())?,
+        PgValue::Double(v)           => write_alls!(writer, &8i32.to_be_bytes(), &v.to_bits().to_be_bytes())?,
+        PgValue::Text(ref v)         => {
+            write_alls!(writer, &(v.len() as i32).to_be_bytes(), v.as_bytes())?
+        },
+        PgValue::IntegerArray(ref v) => {
+            let items: Vec<Vec<u8>> = v.iter().map(|x| x.to_be_bytes().to_vec()).collect();
+            export_binary_array(writer, INT4_OID, &items)?
+        },
+        PgValue::BigintArray(ref v)  => {
+            let items: Vec<Vec<u8>> = v.iter().map(|x| x.to_be_bytes().to_vec()).collect();
+            export_binary_array(writer, INT8_OID, &items)?
+        },
+        PgValue::DoubleArray(ref v)  => {
+            let items: Vec<Vec<u8>> = v.iter().map(|x| x.to_bits().to_be_bytes().to_vec()).collect();
+            export_binary_array(writer, FLOAT8_OID, &items)?
+        },
+        PgValue::TextArray(ref v)    => {
+            let items: Vec<Vec<u8>> = v.iter().map(|x| x.as_bytes().to_vec()).collect();
+            export_binary_array(writer, TEXT_OID, &items)?
+        },
+    }
+    Ok(())
+}
+
+/// Export a 1-dimensional array of non-null elements in the binary format.
+fn export_binary_array<T: Write>(writer: &mut T, oid: i32, items: &[Vec<u8>])
+    -> Result<()>
+{
+    // Dimensions, null flag, element type, and the size and lower
+    // bound of the single dimension. Empty arrays have 0 dimensions.
+    let header: Vec<i32> = match items.len() {
+        0 => vec![0, 0, oid],
+        n => vec![1, 0, oid, n as i32, 1],
+    };
+    let length = 4 * header.len() + items.iter().fold(0, |sum, x| sum + 4 + x.len());
+    writer.write_all(&(length as i32).to_be_bytes())?;
+    for field in header {
+        writer.write_all(&field.to_be_bytes())?;
+    }
+    for item in items {
+        write_alls!(writer, &(item.len() as i32).to_be_bytes(), item.as_slice())?;
+    }
+    Ok(())
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use super::*;
+
+    struct Point {
+        name: String,
+        x: f64,
+        tags: Vec<String>,
+        weight: Option<f64>,
+    }
+
+    impl PgCopy for Point {
+        fn pg_columns() -> Vec<PgColumn> {
+            vec![
+                PgColumn::new("name", PgType::Text),
+                PgColumn::new("x", PgType::Double),
+                PgColumn::new("tags", PgType::TextArray),
+                PgColumn::nullable("weight", PgType::Double),
+            ]
+        }
+
+        fn pg_values<'a>(&'a self) -> Vec<PgValue<'a>> {
+            vec![
+                PgValue::Text(Cow::from(self.name.as_str())),
+                PgValue::Double(self.x),
+                PgValue::TextArray(self.tags.iter().map(|t| Cow::from(t.as_str())).collect()),
+                PgValue::from_double(self.weight),
+            ]
+        }
+    }
+
+    fn points() -> Vec<Point> {
+        vec![
+            Point { name: String::from("a\tb"), x: 1.0, tags: vec![String::from("x\"y"), String::from("z")], weight: None },
+            Point { name: String::from("c\\d"), x: -0.5, tags: vec![], weight: Some(2.0) },
+        ]
+    }
+
+    #[test]
+    fn create_table_test() {
+        assert_eq!(Point::pg_create_table("points"), "CREATE TABLE points (\n    name text NOT NULL,\n    x double precision NOT NULL,\n    tags text[] NOT NULL,\n    weight double precision\n);\n");
+        assert_eq!(Point::pg_copy_statement("points", true), "COPY points (name, x, tags, weight) FROM STDIN (FORMAT binary);\n");
+        assert_eq!(Point::pg_copy_statement("points", false), "COPY points (name, x, tags, weight) FROM STDIN;\n");
+    }
+
+    #[test]
+    fn text_iter_test() {
+        let mut w = vec![];
+        Point::to_pg_text_iter(&mut w, points().iter()).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), "a\\tb\t1.0\t{\"x\\\\\"y\",\"z\"}\t\\N\nc\\\\d\t-0.5\t{}\t2.0\n");
+    }
+
+    #[test]
+    fn binary_iter_test() {
+        let mut w = vec![];
+        Point::to_pg_binary_iter(&mut w, points()[1..].iter()).unwrap();
+        let mut expected: Vec<u8> = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0".to_vec();
+        expected.extend_from_slice(b"\0\x04");
+        expected.extend_from_slice(b"\0\0\0\x03c\\d");
+        expected.extend_from_slice(b"\0\0\0\x08");
+        expected.extend_from_slice(&(-0.5f64).to_bits().to_be_bytes());
+        expected.extend_from_slice(b"\0\0\0\x0c\0\0\0\0\0\0\0\0\0\0\0\x19");
+        expected.extend_from_slice(b"\0\0\0\x08");
+        expected.extend_from_slice(&2.0f64.to_bits().to_be_bytes());
+        expected.extend_from_slice(b"\xff\xff");
+        assert_eq!(w, expected);
+
+        let mut w = vec![];
+        export_binary_value(&mut w, &PgValue::DoubleArray(vec![1.0])).unwrap();
+        assert_eq!(&w[..4], b"\0\0\0\x20");
+        assert_eq!(w.len(), 4 + 32);
+    }
+
+    #[test]
+    fn file_iter_test() {
+        let path = env::temp_dir().join("bdb_pg_file_iter_test.copy");
+        Point::to_pg_text_file_iter(&path, points().iter()).unwrap();
+        let mut w = vec![];
+        Point::to_pg_text_iter(&mut w, points().iter()).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), w);
+
+        Point::to_pg_binary_file_iter(&path, points().iter()).unwrap();
+        let mut w = vec![];
+        Point::to_pg_binary_iter(&mut w, points().iter()).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), w);
+        fs::remove_file(&path).unwrap();
+    }
+}
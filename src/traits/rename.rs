@@ -0,0 +1,11 @@
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Model with an identifier which may be replaced, to anonymize records.
+pub trait Renamable {
+    /// Get the identifier, for example, an accession or a file name.
+    fn identifier(&self) -> &str;
+
+    /// Replace the identifier.
+    fn set_identifier(&mut self, identifier: String);
+}
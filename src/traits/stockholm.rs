@@ -0,0 +1,164 @@
+use std::convert::AsRef;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::iter;
+use std::path::Path;
+
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, Result};
+
+/// Serialize to and from Stockholm.
+///
+/// Stockholm is the multiple sequence alignment format used by Pfam
+/// and Rfam, with markup lines for file, sequence, residue and column
+/// annotations. Each alignment ends with a "//" line.
+///
+/// # Serialized Format
+///
+/// ```text
+/// # STOCKHOLM 1.0
+/// #=GF ID  CBS
+/// O31698/18-71   AVKKHEHVQDVLLRGSNVLA.CAMENSKLYGS
+/// O83071/192-246 MTCRAQLIAVPRASSLDE..AIACAQKMAHL
+/// #=GC SS_cons   CCCCCHHHHHHHHHHHHHHH.EEEEEEEEEEE
+/// //
+/// ```
+pub trait Stockholm: Sized {
+    /// Estimate the size of the resulting Stockholm output to avoid reallocations.
+    #[inline(always)]
+    fn estimate_stockholm_size(&self) -> usize {
+        0
+    }
+
+    /// Export model to Stockholm.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_stockholm<T: Write>(&self, writer: &mut T) -> Result<()>;
+
+    /// Export model to Stockholm bytes.
+    fn to_stockholm_bytes(&self) -> Result<Bytes> {
+        let capacity = self.estimate_stockholm_size();
+        let mut writer = Cursor::new(Vec::with_capacity(capacity));
+
+        self.to_stockholm(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    /// Export model to Stockholm string.
+    #[inline]
+    fn to_stockholm_string(&self) -> Result<String> {
+        Ok(String::from_utf8(self.to_stockholm_bytes()?)?)
+    }
+
+    /// Export model to Stockholm output file.
+    #[inline]
+    fn to_stockholm_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.to_stockholm(&mut writer)
+    }
+
+    /// Export model to Stockholm output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_stockholm_path`, adding the path to errors.
+    #[inline]
+    fn to_stockholm_file_atomic<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_stockholm_path(path)
+    }
+
+    /// Import model from Stockholm.
+    fn from_stockholm<T: BufRead>(reader: &mut T) -> Result<Self>;
+
+    /// Import model from Stockholm bytes.
+    #[inline]
+    fn from_stockholm_bytes(bytes: &[u8]) -> Result<Self> {
+        // Rust uses the contents of the immutable &str as the buffer
+        // Cursor is then immutable.
+        let mut reader = Cursor::new(bytes);
+        Self::from_stockholm(&mut reader)
+    }
+
+    /// Import model from Stockholm string.
+    #[inline]
+    fn from_stockholm_string(string: &str) -> Result<Self> {
+        Self::from_stockholm_bytes(string.as_bytes())
+    }
+
+    /// Import model from Stockholm file.
+    #[inline]
+    fn from_stockholm_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let mut reader = BufReader::new(file);
+        Self::from_stockholm(&mut reader)
+    }
+
+    path_methods!(to_stockholm_path, from_stockholm_path, to_stockholm, from_stockholm);
+
+    /// Lazily import models from Stockholm.
+    ///
+    /// By default, the entire document is imported as a single model.
+    /// Models stored as individual items within a document (such as
+    /// records) yield each item in turn.
+    #[inline]
+    fn from_stockholm_iter<'a, T: 'a + BufRead>(mut reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+        where Self: 'a
+    {
+        Box::new(iter::once(()).map(move |_| Self::from_stockholm(&mut reader)))
+    }
+
+    /// Export models from an iterator to Stockholm.
+    ///
+    /// By default, each model is exported in turn, separated by a
+    /// newline.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_stockholm_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        for (index, item) in iter.enumerate() {
+            if index > 0 {
+                writer.write_all(b"\n")?;
+            }
+            item.to_stockholm(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Specialization of the `Stockholm` trait for collections.
+pub trait StockholmCollection: Stockholm {
+    /// Export collection to Stockholm.
+    ///
+    /// Returns an error if any of the items within the collection
+    /// are invalid.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_stockholm_strict<T: Write>(&self, writer: &mut T) -> Result<()>;
+
+    /// Export collection to Stockholm.
+    ///
+    /// Returns only errors due to serialization issues, otherwise,
+    /// exports as many items as possible.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_stockholm_lenient<T: Write>(&self, writer: &mut T) -> Result<()>;
+
+    /// Import collection from Stockholm.
+    ///
+    /// Returns an error if any of the items within the Stockholm document
+    /// are invalid.
+    fn from_stockholm_strict<T: BufRead>(reader: &mut T) -> Result<Self>;
+
+    /// Import collection from Stockholm.
+    ///
+    /// Returns only errors due to deserialization errors, otherwise,
+    /// imports as many items as possible.
+    fn from_stockholm_lenient<T: BufRead>(reader: &mut T) -> Result<Self>;
+}
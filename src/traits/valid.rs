@@ -1,6 +1,20 @@
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
 /// Determine if model is valid.
 pub trait Valid: Sized {
     /// Determine if model contains only valid information.
     fn is_valid(&self) -> bool;
+
+    /// Describe the validation rules the model violates.
+    ///
+    /// Empty if the model is valid. Models without detailed validation
+    /// report a single, generic violation.
+    fn violations(&self) -> Vec<String> {
+        match self.is_valid() {
+            true  => vec![],
+            false => vec![String::from("record is invalid")],
+        }
+    }
 }
 
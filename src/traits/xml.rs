@@ -1,9 +1,11 @@
 use std::convert::AsRef;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::iter;
 use std::path::Path;
 
-use util::{Bytes, Result};
+use io::atomic::write_atomic;
+use util::{open_buffered, Bytes, Result};
 
 /// Serialize to and from XML.
 pub trait Xml: Sized {
@@ -42,6 +44,15 @@ pub trait Xml: Sized {
         self.to_xml(&mut writer)
     }
 
+    /// Export model to XML output file, atomically.
+    ///
+    /// The output file is only replaced if the export succeeds.
+    /// Forwards to `to_xml_path`, adding the path to errors.
+    #[inline]
+    fn to_xml_file_atomic<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_xml_path(path)
+    }
+
     /// Import model from XML.
     fn from_xml<T: BufRead>(reader: &mut T) -> Result<Self>;
 
@@ -67,6 +78,39 @@ pub trait Xml: Sized {
         let mut reader = BufReader::new(file);
         Self::from_xml(&mut reader)
     }
+
+    path_methods!(to_xml_path, from_xml_path, to_xml, from_xml);
+
+    /// Lazily import models from XML.
+    ///
+    /// By default, the entire document is imported as a single model.
+    /// Models stored as individual items within a document (such as
+    /// records) yield each item in turn.
+    #[inline]
+    fn from_xml_iter<'a, T: 'a + BufRead>(mut reader: T)
+        -> Box<dyn Iterator<Item = Result<Self>> + 'a>
+        where Self: 'a
+    {
+        Box::new(iter::once(()).map(move |_| Self::from_xml(&mut reader)))
+    }
+
+    /// Export models from an iterator to XML.
+    ///
+    /// By default, each model is exported as a separate XML document,
+    /// so models that share a document (such as records) should
+    /// override this to write a single document.
+    ///
+    /// Note that many small writers are made to the writer, so the writer
+    /// should be buffered.
+    fn to_xml_iter<'a, T: Write, Iter: Iterator<Item = &'a Self>>(writer: &mut T, iter: Iter)
+        -> Result<()>
+        where Self: 'a
+    {
+        for item in iter {
+            item.to_xml(writer)?;
+        }
+        Ok(())
+    }
 }
 
 /// Specialization of the `Xml` trait for collections.
@@ -4,6 +4,7 @@ use super::error::Error;
 use std::result::Result as StdResult;
 
 /// General buffer type.
+#[cfg(feature = "std")]
 pub type Bytes = Vec<u8>;
 
 /// General result type.
@@ -0,0 +1,107 @@
+//! Little-endian binary serialization helpers.
+//!
+//! Variable-length fields are prefixed by their length, as a 32-bit
+//! integer.
+
+use std::io::prelude::*;
+
+use super::alias::Result;
+use super::error::ErrorKind;
+
+/// Write a length as a little-endian 32-bit integer.
+#[inline]
+pub(crate) fn write_len<T: Write>(writer: &mut T, length: usize) -> Result<()> {
+    bool_to_error!(length <= u32::MAX as usize, InvalidRecord);
+    writer.write_all(&(length as u32).to_le_bytes())?;
+    Ok(())
+}
+
+/// Write length-prefixed bytes.
+#[inline]
+pub(crate) fn write_bytes<T: Write>(writer: &mut T, bytes: &[u8]) -> Result<()> {
+    write_len(writer, bytes.len())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+/// Read an 8-bit integer.
+#[inline]
+pub(crate) fn read_u8<T: Read>(reader: &mut T) -> Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+/// Read a little-endian 32-bit integer.
+#[inline]
+pub(crate) fn read_u32<T: Read>(reader: &mut T) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Read a little-endian 64-bit integer.
+#[inline]
+pub(crate) fn read_u64<T: Read>(reader: &mut T) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Read a little-endian 64-bit float.
+#[inline]
+pub(crate) fn read_f64<T: Read>(reader: &mut T) -> Result<f64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(f64::from_le_bytes(buf))
+}
+
+/// Read a length as a little-endian 32-bit integer.
+#[inline]
+pub(crate) fn read_len<T: Read>(reader: &mut T) -> Result<usize> {
+    Ok(read_u32(reader)? as usize)
+}
+
+/// Read length-prefixed bytes.
+#[inline]
+pub(crate) fn read_bytes<T: Read>(reader: &mut T) -> Result<Vec<u8>> {
+    let length = read_len(reader)?;
+    let mut bytes = vec![];
+    reader.take(length as u64).read_to_end(&mut bytes)?;
+    bool_to_error!(bytes.len() == length, UnexpectedEof);
+    Ok(bytes)
+}
+
+/// Read a length-prefixed UTF-8 string.
+#[inline]
+pub(crate) fn read_string<T: Read>(reader: &mut T) -> Result<String> {
+    Ok(String::from_utf8(read_bytes(reader)?)?)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+
+    #[test]
+    fn binary_test() {
+        let mut w = vec![];
+        write_bytes(&mut w, b"PEPTIDE").unwrap();
+        w.extend_from_slice(&1.5f64.to_le_bytes());
+        w.extend_from_slice(&7u64.to_le_bytes());
+        w.push(3);
+
+        let mut reader = Cursor::new(w.clone());
+        assert_eq!(read_string(&mut reader).unwrap(), "PEPTIDE");
+        assert_eq!(read_f64(&mut reader).unwrap(), 1.5);
+        assert_eq!(read_u64(&mut reader).unwrap(), 7);
+        assert_eq!(read_u8(&mut reader).unwrap(), 3);
+        assert!(read_u8(&mut reader).is_err());
+
+        // Truncated length-prefixed bytes.
+        assert!(read_bytes(&mut Cursor::new(&w[..6])).is_err());
+    }
+}
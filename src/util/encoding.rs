@@ -0,0 +1,193 @@
+//! Text transcoding for documents that are not valid UTF-8.
+//!
+//! The text readers decode each record through an `Encoding` before
+//! parsing, so records from legacy documents may be read without
+//! format-specific workarounds.
+
+use std::borrow::Cow;
+use std::io::{self, BufRead, Read};
+use std::mem;
+use std::str as stdstr;
+
+use super::alias::{Bytes, Result};
+
+/// Text encoding of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, raising an error for invalid text.
+    Utf8,
+    /// UTF-8, replacing invalid sequences with the replacement character.
+    Utf8Lossy,
+    /// Latin-1 (ISO-8859-1), used for any text that is not valid UTF-8.
+    Latin1,
+}
+
+impl Default for Encoding {
+    #[inline]
+    fn default() -> Self {
+        Encoding::Utf8
+    }
+}
+
+/// Transcode Latin-1 text to UTF-8.
+#[inline]
+fn latin1_to_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|&c| c as char).collect()
+}
+
+impl Encoding {
+    /// Decode a buffer to UTF-8 text.
+    ///
+    /// Valid UTF-8 is returned unchanged. For `Utf8`, invalid text is
+    /// also returned unchanged, so the error is raised during parsing.
+    pub fn decode_bytes(&self, bytes: Bytes) -> Bytes {
+        if *self == Encoding::Utf8 || stdstr::from_utf8(&bytes).is_ok() {
+            return bytes;
+        }
+
+        match *self {
+            Encoding::Utf8      => bytes,
+            Encoding::Utf8Lossy => String::from_utf8_lossy(&bytes).into_owned().into_bytes(),
+            Encoding::Latin1    => latin1_to_string(&bytes).into_bytes(),
+        }
+    }
+
+    /// Decode a borrowed or owned buffer, borrowing the buffer if possible.
+    pub fn decode_cow<'a>(&self, bytes: Cow<'a, [u8]>) -> Cow<'a, [u8]> {
+        if *self == Encoding::Utf8 || stdstr::from_utf8(&bytes).is_ok() {
+            return bytes;
+        }
+        Cow::Owned(self.decode_bytes(bytes.into_owned()))
+    }
+
+    /// Decode a buffer to a string, borrowing the buffer if it is valid UTF-8.
+    pub fn decode_str<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>> {
+        match *self {
+            Encoding::Utf8      => Ok(Cow::Borrowed(stdstr::from_utf8(bytes)?)),
+            Encoding::Utf8Lossy => Ok(String::from_utf8_lossy(bytes)),
+            Encoding::Latin1    => Ok(match stdstr::from_utf8(bytes) {
+                Ok(v)   => Cow::Borrowed(v),
+                Err(_)  => Cow::Owned(latin1_to_string(bytes)),
+            }),
+        }
+    }
+}
+
+// READER
+
+/// Buffered reader decoding each line of a document to UTF-8.
+///
+/// Used by readers that parse a byte stream, rather than individual
+/// records, such as the XML readers. Each line is decoded separately,
+/// while `Utf8` documents are read unchanged.
+pub(crate) struct DecodeReader<T: BufRead> {
+    reader: T,
+    encoding: Encoding,
+    line: Bytes,
+    position: usize,
+}
+
+impl<T: BufRead> DecodeReader<T> {
+    /// Create new DecodeReader from a buffered reader and encoding.
+    #[inline]
+    pub fn new(reader: T, encoding: Encoding) -> Self {
+        DecodeReader {
+            reader: reader,
+            encoding: encoding,
+            line: vec![],
+            position: 0,
+        }
+    }
+}
+
+impl<T: BufRead> Read for DecodeReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = {
+            let data = self.fill_buf()?;
+            let count = data.len().min(buf.len());
+            buf[..count].copy_from_slice(&data[..count]);
+            count
+        };
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl<T: BufRead> BufRead for DecodeReader<T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.encoding == Encoding::Utf8 {
+            return self.reader.fill_buf();
+        }
+        if self.position == self.line.len() {
+            let mut line = mem::take(&mut self.line);
+            line.clear();
+            self.reader.read_until(b'\n', &mut line)?;
+            self.line = self.encoding.decode_bytes(line);
+            self.position = 0;
+        }
+        Ok(&self.line[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match self.encoding {
+            Encoding::Utf8 => self.reader.consume(amount),
+            _              => self.position = (self.position + amount).min(self.line.len()),
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_bytes_test() {
+        let valid = "caf\u{e9}".as_bytes().to_vec();
+        let invalid = b"caf\xe9".to_vec();
+        assert_eq!(Encoding::Utf8.decode_bytes(valid.clone()), valid);
+        assert_eq!(Encoding::Utf8.decode_bytes(invalid.clone()), invalid);
+        assert_eq!(Encoding::Utf8Lossy.decode_bytes(valid.clone()), valid);
+        assert_eq!(Encoding::Utf8Lossy.decode_bytes(invalid.clone()), "caf\u{FFFD}".as_bytes().to_vec());
+        assert_eq!(Encoding::Latin1.decode_bytes(valid.clone()), valid);
+        assert_eq!(Encoding::Latin1.decode_bytes(invalid), valid);
+    }
+
+    #[test]
+    fn decode_cow_test() {
+        let valid = "caf\u{e9}".as_bytes();
+        let invalid = &b"caf\xe9"[..];
+        match Encoding::Latin1.decode_cow(Cow::Borrowed(valid)) {
+            Cow::Borrowed(v) => assert_eq!(v, valid),
+            Cow::Owned(_)    => panic!("expected borrowed bytes"),
+        }
+        assert_eq!(Encoding::Utf8.decode_cow(Cow::Borrowed(invalid)).as_ref(), invalid);
+        assert_eq!(Encoding::Latin1.decode_cow(Cow::Borrowed(invalid)).as_ref(), valid);
+    }
+
+    #[test]
+    fn decode_str_test() {
+        let valid = "caf\u{e9}".as_bytes();
+        let invalid = b"caf\xe9";
+        assert_eq!(Encoding::Utf8.decode_str(valid).unwrap(), "caf\u{e9}");
+        assert!(Encoding::Utf8.decode_str(invalid).is_err());
+        assert_eq!(Encoding::Utf8Lossy.decode_str(invalid).unwrap(), "caf\u{FFFD}");
+        assert_eq!(Encoding::Latin1.decode_str(valid).unwrap(), "caf\u{e9}");
+        assert_eq!(Encoding::Latin1.decode_str(invalid).unwrap(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn decode_reader_test() {
+        let text = b"<name>caf\xe9</name>\n<name>na\xefve</name>";
+        let read = |encoding| {
+            let mut buf = vec![];
+            DecodeReader::new(&text[..], encoding).read_to_end(&mut buf).unwrap();
+            buf
+        };
+        assert_eq!(read(Encoding::Utf8), text.to_vec());
+        assert_eq!(read(Encoding::Utf8Lossy), "<name>caf\u{FFFD}</name>\n<name>na\u{FFFD}ve</name>".as_bytes().to_vec());
+        assert_eq!(read(Encoding::Latin1), "<name>caf\u{e9}</name>\n<name>na\u{ef}ve</name>".as_bytes().to_vec());
+    }
+}
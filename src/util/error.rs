@@ -1,13 +1,27 @@
 //! Error definitions for UniProt models and services.
 
 use std::error::Error as StdError;
-use std::io;
 use std::fmt;
 use std::num::ParseFloatError;
 use std::num::ParseIntError;
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+#[cfg(feature = "columnar")]
+use arrow::error::ArrowError;
+
+#[cfg(feature = "columnar")]
+use parquet::errors::ParquetError;
+
 #[cfg(feature = "csv")]
 use csv::Error as CsvError;
 
@@ -17,6 +31,9 @@ use reqwest::Error as HttpError;
 #[cfg(feature = "xml")]
 use quick_xml::Error as XmlError;
 
+#[cfg(feature = "config")]
+use toml::de::Error as TomlError;
+
 // TYPE
 
 /// Enumerated error type during BDB error handling.
@@ -31,6 +48,9 @@ pub enum ErrorKind {
 
     /// Serializer fails due to invalid record data.
     InvalidRecord,
+    /// Strict serializer fails due to invalid record data, with the
+    /// index of the record and the violated validation rules.
+    InvalidRecordAt(usize, Vec<String>),
 
     // DESERIALIZER
 
@@ -41,8 +61,32 @@ pub enum ErrorKind {
     /// Deserializer fails because of an unexpected EOF.
     UnexpectedEof,
 
+    // INTEGRITY
+
+    /// Verification fails because the data does not match the manifest,
+    /// with the index of the first mismatched chunk.
+    ChecksumMismatch(usize),
+
+    // CONFIGURATION
+
+    /// Configuration fails due to an unknown key or invalid value,
+    /// with the dotted path of the key.
+    InvalidConfig(String),
+
+    // CANCELLATION
+
+    /// Operation stops early because its cancellation token was cancelled.
+    Cancelled,
+
+    // CONTEXT
+
+    /// Error while reading or writing a file, with the file path.
+    #[cfg(feature = "std")]
+    Path(PathBuf, Box<Error>),
+
     // INHERITED
     /// Inherited `io::Error`.
+    #[cfg(feature = "std")]
     Io(io::Error),
     /// Inherited `Utf8Error`.
     Utf8(Utf8Error),
@@ -53,6 +97,14 @@ pub enum ErrorKind {
     /// Inherited `ParseFloatError`.
     ParseFloat(ParseFloatError),
 
+    /// Inherited `arrow::error::ArrowError`.
+    #[cfg(feature = "columnar")]
+    Arrow(ArrowError),
+
+    /// Inherited `parquet::errors::ParquetError`.
+    #[cfg(feature = "columnar")]
+    Parquet(ParquetError),
+
     /// Inherited `csv::Error`.
     #[cfg(feature = "csv")]
     Csv(CsvError),
@@ -64,12 +116,21 @@ pub enum ErrorKind {
     /// Inherited `quick_xml::Error`.
     #[cfg(feature = "xml")]
     Xml(XmlError),
+
+    /// Inherited `toml::de::Error`.
+    #[cfg(feature = "config")]
+    Toml(TomlError),
 }
 
 // CONVERSIONS
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Self {
+        // Unwrap errors raised within readers, such as cancellation.
+        if err.get_ref().is_some_and(|e| e.is::<Error>()) {
+            return *err.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
         Error(ErrorKind::Io(err))
     }
 }
@@ -98,6 +159,20 @@ impl From<ParseIntError> for Error {
     }
 }
 
+#[cfg(feature = "columnar")]
+impl From<ArrowError> for Error {
+    fn from(err: ArrowError) -> Self {
+        Error(ErrorKind::Arrow(err))
+    }
+}
+
+#[cfg(feature = "columnar")]
+impl From<ParquetError> for Error {
+    fn from(err: ParquetError) -> Self {
+        Error(ErrorKind::Parquet(err))
+    }
+}
+
 #[cfg(feature = "csv")]
 impl From<CsvError> for Error {
     fn from(err: CsvError) -> Self {
@@ -119,6 +194,13 @@ impl From<XmlError> for Error {
     }
 }
 
+#[cfg(feature = "config")]
+impl From<TomlError> for Error {
+    fn from(err: TomlError) -> Self {
+        Error(ErrorKind::Toml(err))
+    }
+}
+
 impl From<ErrorKind> for Error {
     fn from(kind: ErrorKind) -> Self {
         Error(kind)
@@ -139,15 +221,71 @@ impl Error {
     pub fn kind(&self) -> &ErrorKind {
         &self.0
     }
+
+    /// Add the path of the file being read or written to the error.
+    #[cfg(feature = "std")]
+    pub fn with_path<P: AsRef<Path>>(self, path: P) -> Self {
+        Error(ErrorKind::Path(path.as_ref().to_path_buf(), Box::new(self)))
+    }
+
+    /// Get the path of the file which caused the error, if known.
+    #[cfg(feature = "std")]
+    pub fn path(&self) -> Option<&Path> {
+        match self.kind() {
+            ErrorKind::Path(ref path, _) => Some(path),
+            _                            => None,
+        }
+    }
+
+    /// Get the index of the invalid record which caused the error, if known.
+    pub fn record_index(&self) -> Option<usize> {
+        match self.kind() {
+            ErrorKind::InvalidRecordAt(index, _) => Some(*index),
+            #[cfg(feature = "std")]
+            ErrorKind::Path(_, ref err)          => err.record_index(),
+            _                                    => None,
+        }
+    }
+
+    /// Get the validation rules violated by the invalid record, if known.
+    pub fn violations(&self) -> Option<&[String]> {
+        match self.kind() {
+            ErrorKind::InvalidRecordAt(_, ref violations) => Some(violations),
+            #[cfg(feature = "std")]
+            ErrorKind::Path(_, ref err)                   => err.violations(),
+            _                                             => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
+    #[allow(deprecated)]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "UniProt error: {}", self.description())
+        match self.kind() {
+            #[cfg(feature = "std")]
+            ErrorKind::Path(ref path, _) => {
+                write!(f, "UniProt error: {}: {}", path.display(), self.description())
+            },
+            ErrorKind::InvalidRecordAt(index, ref violations) => {
+                write!(f, "UniProt error: {} at index {}: {}", self.description(), index, violations.join("; "))
+            },
+            ErrorKind::ChecksumMismatch(chunk) => {
+                write!(f, "UniProt error: {} at chunk {}", self.description(), chunk)
+            },
+            ErrorKind::InvalidConfig(ref key) => {
+                write!(f, "UniProt error: {}: {}", self.description(), key)
+            },
+            #[cfg(feature = "config")]
+            ErrorKind::Toml(ref err) => {
+                write!(f, "UniProt error: {}: {}", self.description(), err)
+            },
+            _ => write!(f, "UniProt error: {}", self.description()),
+        }
     }
 }
 
 impl StdError for Error {
+    #[allow(deprecated)]
     fn description(&self) -> &str {
         match self.kind() {
             // PROTEIN EVIDENCE
@@ -161,6 +299,9 @@ impl StdError for Error {
             ErrorKind::InvalidRecord => {
                 "invalid record found, cannot write data"
             },
+            ErrorKind::InvalidRecordAt(_, _) => {
+                "invalid record found, cannot write data"
+            },
 
             // DESERIALIZER
 
@@ -174,19 +315,51 @@ impl StdError for Error {
                 "unexpected EOF, cannot read data"
             }
 
+            // INTEGRITY
+
+            ErrorKind::ChecksumMismatch(_) => {
+                "data does not match manifest, file is incomplete or modified"
+            },
+
+            // CONFIGURATION
+
+            ErrorKind::InvalidConfig(_) => {
+                "invalid configuration key or value, cannot load configuration"
+            },
+
+            // CANCELLATION
+
+            ErrorKind::Cancelled => {
+                "operation cancelled before completion"
+            },
+
+            // CONTEXT
+            #[cfg(feature = "std")]
+            ErrorKind::Path(_, ref err) => err.description(),
+
             // INHERITED
+            #[cfg(feature = "std")]
             ErrorKind::Io(ref err) => err.description(),
             ErrorKind::Utf8(ref err) => err.description(),
             ErrorKind::FromUtf8(ref err) => err.description(),
             ErrorKind::ParseFloat(ref err) => err.description(),
             ErrorKind::ParseInt(ref err) => err.description(),
 
+            #[cfg(feature = "columnar")]
+            ErrorKind::Arrow(_) => "arrow: cannot write columnar data",
+
+            #[cfg(feature = "columnar")]
+            ErrorKind::Parquet(_) => "parquet: cannot write columnar data",
+
             #[cfg(feature = "csv")]
             ErrorKind::Csv(ref err) => err.description(),
 
             #[cfg(feature = "http")]
             ErrorKind::Http(ref err) => err.description(),
 
+            #[cfg(feature = "config")]
+            ErrorKind::Toml(_) => "toml: cannot parse configuration",
+
             #[cfg(feature = "xml")]
             ErrorKind::Xml(ref err) => match err {
                 XmlError::Io(ref e) => e.description(),
@@ -206,19 +379,31 @@ impl StdError for Error {
         }
     }
 
-    fn cause(&self) -> Option<&StdError> {
+    fn cause(&self) -> Option<&dyn StdError> {
         match self.kind() {
+            #[cfg(feature = "std")]
+            ErrorKind::Path(_, ref err) => Some(&**err),
+            #[cfg(feature = "std")]
             ErrorKind::Io(ref err) => Some(err),
             ErrorKind::Utf8(ref err) => Some(err),
             ErrorKind::FromUtf8(ref err) => Some(err),
             ErrorKind::ParseFloat(ref err) => Some(err),
             ErrorKind::ParseInt(ref err) => Some(err),
 
+            #[cfg(feature = "columnar")]
+            ErrorKind::Arrow(ref err) => Some(err),
+
+            #[cfg(feature = "columnar")]
+            ErrorKind::Parquet(ref err) => Some(err),
+
+            #[cfg(feature = "config")]
+            ErrorKind::Toml(ref err) => Some(err),
+
             #[cfg(feature = "csv")]
-            ErrorKind::Csv(ref err) => err.cause(),
+            ErrorKind::Csv(ref err) => err.source(),
 
             #[cfg(feature = "http")]
-            ErrorKind::Http(ref err) => err.cause(),
+            ErrorKind::Http(ref err) => err.source(),
 
             #[cfg(feature = "xml")]
             ErrorKind::Xml(ref err) => match err {
@@ -1,10 +1,15 @@
 //! Shared iterator templates and utilities.
 
+use std::collections::HashSet;
+use std::hash::Hash;
 use std::io::prelude::*;
+use std::marker::PhantomData;
 
-use traits::Valid;
+use traits::{Complete, Valid};
 use super::alias::{Bytes, Result};
 use super::error::ErrorKind;
+use super::parse::UTF8_BOM;
+use super::sample::{self, SampleFractionIter, SplitIter};
 
 // READER
 
@@ -64,12 +69,261 @@ impl<T: Valid, U: Iterator<Item = Result<T>>> Iterator for LenientIter<T, U> {
                     if r.is_valid() {
                         return Some(Ok(r));
                     }
+                    log_debug!("skipped invalid record");
                 },
             }
         }
     }
 }
 
+// ADAPTORS
+
+/// Iterator which ignores incomplete items.
+pub struct CompleteIter<T: Complete, U: Iterator<Item = Result<T>>> {
+    /// Wrapped internal iterator.
+    iter: U,
+}
+
+impl<T: Complete, U: Iterator<Item = Result<T>>> CompleteIter<T, U> {
+    /// Create new CompleteIter from an iterator.
+    #[inline]
+    pub fn new(iter: U) -> Self {
+        CompleteIter {
+            iter: iter
+        }
+    }
+}
+
+impl<T: Complete, U: Iterator<Item = Result<T>>> Iterator for CompleteIter<T, U> {
+    type Item = U::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next()? {
+                Err(e)  => return Some(Err(e)),
+                Ok(r)   => {
+                    if r.is_complete() {
+                        return Some(Ok(r));
+                    }
+                    log_debug!("skipped incomplete record");
+                },
+            }
+        }
+    }
+}
+
+/// Iterator which ignores items with a previously seen key.
+pub struct UniqueByIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Eq + Hash,
+          F: FnMut(&T) -> K
+{
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Callback to get the key from an item.
+    key: F,
+    /// Keys of previously yielded items.
+    seen: HashSet<K>,
+}
+
+impl<T, U, K, F> UniqueByIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Eq + Hash,
+          F: FnMut(&T) -> K
+{
+    /// Create new UniqueByIter from an iterator and key callback.
+    #[inline]
+    pub fn new(iter: U, key: F) -> Self {
+        UniqueByIter {
+            iter: iter,
+            key: key,
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl<T, U, K, F> Iterator for UniqueByIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Eq + Hash,
+          F: FnMut(&T) -> K
+{
+    type Item = U::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next()? {
+                Err(e)  => return Some(Err(e)),
+                Ok(r)   => {
+                    if self.seen.insert((self.key)(&r)) {
+                        return Some(Ok(r));
+                    }
+                    log_trace!("skipped duplicate record");
+                },
+            }
+        }
+    }
+}
+
+/// Iterator which stops after the first error.
+pub struct TakeWhileOkIter<T, U: Iterator<Item = Result<T>>> {
+    /// Wrapped internal iterator.
+    iter: U,
+    /// If an error has been produced.
+    done: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<T, U: Iterator<Item = Result<T>>> TakeWhileOkIter<T, U> {
+    /// Create new TakeWhileOkIter from an iterator.
+    #[inline]
+    pub fn new(iter: U) -> Self {
+        TakeWhileOkIter {
+            iter: iter,
+            done: false,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, U: Iterator<Item = Result<T>>> Iterator for TakeWhileOkIter<T, U> {
+    type Item = U::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = self.iter.next()?;
+        self.done = item.is_err();
+        Some(item)
+    }
+}
+
+/// Iterator which transforms each item.
+pub struct MapRecordsIter<T, U, F>
+    where U: Iterator<Item = Result<T>>,
+          F: FnMut(T) -> T
+{
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Callback to transform each item.
+    transform: F,
+}
+
+impl<T, U, F> MapRecordsIter<T, U, F>
+    where U: Iterator<Item = Result<T>>,
+          F: FnMut(T) -> T
+{
+    /// Create new MapRecordsIter from an iterator and transform callback.
+    #[inline]
+    pub fn new(iter: U, transform: F) -> Self {
+        MapRecordsIter {
+            iter: iter,
+            transform: transform,
+        }
+    }
+}
+
+impl<T, U, F> Iterator for MapRecordsIter<T, U, F>
+    where U: Iterator<Item = Result<T>>,
+          F: FnMut(T) -> T
+{
+    type Item = U::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let transform = &mut self.transform;
+        Some(self.iter.next()?.map(|r| transform(r)))
+    }
+}
+
+/// Adaptors for iterators over fallible items, such as the record
+/// iterators, to chain common steps between readers and writers.
+pub trait RecordIterExt<T>: Iterator<Item = Result<T>> + Sized {
+    /// Raise an error for invalid items.
+    #[inline]
+    fn validated(self) -> StrictIter<T, Self>
+        where T: Valid
+    {
+        StrictIter::new(self)
+    }
+
+    /// Ignore incomplete items.
+    #[inline]
+    fn completed(self) -> CompleteIter<T, Self>
+        where T: Complete
+    {
+        CompleteIter::new(self)
+    }
+
+    /// Ignore items with the same key as a previous item.
+    ///
+    /// Every key seen is kept in memory until the iterator is dropped.
+    #[inline]
+    fn unique_by<K, F>(self, key: F) -> UniqueByIter<T, Self, K, F>
+        where K: Eq + Hash,
+              F: FnMut(&T) -> K
+    {
+        UniqueByIter::new(self, key)
+    }
+
+    /// Transform each item, leaving errors unchanged.
+    ///
+    /// Use this to modify records uniformly before serialization, for
+    /// example, to renumber scans while converting between formats.
+    /// Owning iterators can then be passed to the `value_iterator_to_*`
+    /// exporters.
+    #[inline]
+    fn map_records<F>(self, transform: F) -> MapRecordsIter<T, Self, F>
+        where F: FnMut(T) -> T
+    {
+        MapRecordsIter::new(self, transform)
+    }
+
+    /// Stop iterating after the first error.
+    ///
+    /// The error is still produced, so it may be reported, however,
+    /// no items are read past it.
+    #[inline]
+    fn take_while_ok(self) -> TakeWhileOkIter<T, Self> {
+        TakeWhileOkIter::new(self)
+    }
+
+    /// Draw `n` items uniformly, with reservoir sampling.
+    ///
+    /// Reads every item, and returns the sampled items in order.
+    #[inline]
+    fn sample_n(self, n: usize, seed: u64) -> Result<Vec<T>> {
+        sample::sample_n(self, n, seed)
+    }
+
+    /// Keep a deterministic fraction of the items, by the hash of a key.
+    ///
+    /// The same items are kept for the same keys and seed, independent
+    /// of the item order.
+    #[inline]
+    fn sample_fraction<K, F>(self, fraction: f64, seed: u64, key: F) -> SampleFractionIter<T, Self, K, F>
+        where K: Hash,
+              F: FnMut(&T) -> K
+    {
+        SampleFractionIter::new(self, fraction, seed, key)
+    }
+
+    /// Assign each item to a training or test split, by the hash of a key.
+    ///
+    /// `fraction` of the items are assigned to the training split.
+    #[inline]
+    fn split_by_hash<K, F>(self, fraction: f64, seed: u64, key: F) -> SplitIter<T, Self, K, F>
+        where K: Hash,
+              F: FnMut(&T) -> K
+    {
+        SplitIter::new(self, fraction, seed, key)
+    }
+}
+
+impl<T, U: Iterator<Item = Result<T>>> RecordIterExt<T> for U {
+}
+
 // WRITER
 
 // These are extremely low-level helpers to facilitate writing
@@ -82,6 +336,9 @@ impl<T: Valid, U: Iterator<Item = Result<T>>> Iterator for LenientIter<T, U> {
 // 4. A callback which converts the writer to an inner writer.
 // 5. A callback which exports a record using the inner writer.
 // 6. A callback which ends the inner writer.
+//
+// To transform records before export, map the owning iterator
+// with `RecordIterExt::map_records`.
 
 /// Default exporter from a non-owning iterator.
 pub fn reference_iterator_export<
@@ -111,14 +368,18 @@ pub fn reference_iterator_export<
           DestCb: Fn(&mut InnerWriter) -> Result<()>
 {
     let mut inner = init_cb(writer, delimiter)?;
+    let mut count: usize = 0;
 
     // Write all records
     // Error only raised for write error, which should percolate.
     for record in iter {
         export_cb(&mut inner, record)?;
+        count += 1;
     }
 
-    dest_cb(&mut inner)
+    dest_cb(&mut inner)?;
+    log_debug!("exported {} records", count);
+    Ok(())
 }
 
 /// Default exporter from an owning iterator.
@@ -149,17 +410,35 @@ pub fn value_iterator_export<
           DestCb: Fn(&mut InnerWriter) -> Result<()>
 {
     let mut inner = init_cb(writer, delimiter)?;
+    let mut count: usize = 0;
 
     // Write all records
     // Error only raised for read or write errors, which should percolate.
     for record in iter {
         export_cb(&mut inner, &record?)?;
+        count += 1;
     }
 
-    dest_cb(&mut inner)
+    dest_cb(&mut inner)?;
+    log_debug!("exported {} records", count);
+    Ok(())
+}
+
+/// Check a record is valid, reporting the index and violations if not.
+#[inline]
+fn check_valid<Record: Valid>(index: usize, record: &Record) -> Result<()> {
+    match record.is_valid() {
+        true  => Ok(()),
+        false => Err(From::from(ErrorKind::InvalidRecordAt(index, record.violations()))),
+    }
 }
 
 /// Strict exporter from a non-owning iterator.
+///
+/// The first invalid record aborts the export with its index.
+/// Records are validated as they are written, so the records preceding
+/// an invalid record are already written. Write to an `AtomicFile` to
+/// discard the partial output.
 pub fn reference_iterator_export_strict<
     'a, 'b,
     Iter,
@@ -187,16 +466,25 @@ pub fn reference_iterator_export_strict<
           DestCb: Fn(&mut InnerWriter) -> Result<()>
 {
     let mut inner = init_cb(writer, delimiter)?;
+    let mut count: usize = 0;
 
-    for record in iter {
-        bool_to_error!(record.is_valid(), InvalidRecord);
+    for (index, record) in iter.enumerate() {
+        check_valid(index, record)?;
         export_cb(&mut inner, record)?;
+        count += 1;
     }
 
-    dest_cb(&mut inner)
+    dest_cb(&mut inner)?;
+    log_debug!("exported {} records", count);
+    Ok(())
 }
 
 /// Strict exporter from an owning iterator.
+///
+/// The first invalid record aborts the export with its index.
+/// Records are validated as they are written, so the records preceding
+/// an invalid record are already written. Write to an `AtomicFile` to
+/// discard the partial output.
 pub fn value_iterator_export_strict<
     'a,
     Iter,
@@ -224,14 +512,18 @@ pub fn value_iterator_export_strict<
           DestCb: Fn(&mut InnerWriter) -> Result<()>
 {
     let mut inner = init_cb(writer, delimiter)?;
+    let mut count: usize = 0;
 
-    for result in iter {
+    for (index, result) in iter.enumerate() {
         let record = result?;
-        bool_to_error!(record.is_valid(), InvalidRecord);
+        check_valid(index, &record)?;
         export_cb(&mut inner, &record)?;
+        count += 1;
     }
 
-    dest_cb(&mut inner)
+    dest_cb(&mut inner)?;
+    log_debug!("exported {} records", count);
+    Ok(())
 }
 
 /// Lenient exporter from a non-owning iterator.
@@ -262,16 +554,22 @@ pub fn reference_iterator_export_lenient<
           DestCb: Fn(&mut InnerWriter) -> Result<()>
 {
     let mut inner = init_cb(writer, delimiter)?;
+    let mut count: usize = 0;
 
     // Write all records
     // Error only raised for write error, which should percolate.
     for record in iter {
         if record.is_valid() {
             export_cb(&mut inner, record)?;
+            count += 1;
+        } else {
+            log_debug!("skipped invalid record on export");
         }
     }
 
-    dest_cb(&mut inner)
+    dest_cb(&mut inner)?;
+    log_debug!("exported {} records", count);
+    Ok(())
 }
 
 /// Lenient exporter from an owning iterator.
@@ -302,6 +600,7 @@ pub fn value_iterator_export_lenient<
           DestCb: Fn(&mut InnerWriter) -> Result<()>
 {
     let mut inner = init_cb(writer, delimiter)?;
+    let mut count: usize = 0;
 
     // Write all records
     // Error only raised for write error, which should percolate.
@@ -309,10 +608,15 @@ pub fn value_iterator_export_lenient<
         let record = result?;
         if record.is_valid() {
             export_cb(&mut inner, &record)?;
+            count += 1;
+        } else {
+            log_debug!("skipped invalid record on export");
         }
     }
 
-    dest_cb(&mut inner)
+    dest_cb(&mut inner)?;
+    log_debug!("exported {} records", count);
+    Ok(())
 }
 
 // NEXT
@@ -351,9 +655,32 @@ macro_rules! bytes_next {
     })
 }
 
+/// Normalize a line read from a text document, in-place.
+///
+/// Removes a leading UTF-8 byte order mark and any trailing whitespace,
+/// including the `\r` from Windows line endings, and restores the `\n`
+/// line ending if the line had one. Whitespace-only lines become empty.
+pub fn normalize_line(line: &mut Bytes) {
+    if line.starts_with(UTF8_BOM) {
+        line.drain(..UTF8_BOM.len());
+    }
+
+    let has_newline = line.last() == Some(&b'\n');
+    while line.last().map_or(false, |c| c.is_ascii_whitespace()) {
+        line.pop();
+    }
+    if has_newline && !line.is_empty() {
+        line.push(b'\n');
+    }
+}
+
 /// Produce the next element from a bytes-based iterator (skipping whitespace).
+///
+/// If `skip` is provided, lines with the prefix before the first
+/// element, such as an export metadata block, are also skipped.
 pub fn bytes_next_skip_whitespace<T: BufRead>(
     start: &[u8],
+    skip: Option<&[u8]>,
     reader: &mut T,
     buf: &mut Bytes,
     line: &mut Bytes
@@ -361,10 +688,15 @@ pub fn bytes_next_skip_whitespace<T: BufRead>(
     -> Option<Result<Bytes>>
 {
     bytes_next!(reader, buf, line, unsafe {
-        if line == b"\n" || line == b"\r\n" {
+        normalize_line(line);
+        if line.is_empty() {
             // Ignore whitespace.
             line.set_len(0);
             continue;
+        } else if buf.is_empty() && skip.map_or(false, |s| line.starts_with(s)) {
+            // Ignore leading comments.
+            line.set_len(0);
+            continue;
         } else if buf.len() > 0 && line.starts_with(start) {
             // Create result from existing buffer,
             // clear the existing buffer, and add
@@ -378,3 +710,138 @@ pub fn bytes_next_skip_whitespace<T: BufRead>(
         }
     })
 }
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Item(u8);
+
+    impl Valid for Item {
+        fn is_valid(&self) -> bool {
+            self.0 % 2 == 0
+        }
+    }
+
+    impl Complete for Item {
+        fn is_complete(&self) -> bool {
+            self.0 < 4
+        }
+    }
+
+    fn items() -> Vec<Result<Item>> {
+        vec![Ok(Item(0)), Ok(Item(1)), Err(From::from(ErrorKind::InvalidInput)), Ok(Item(4)), Ok(Item(2))]
+    }
+
+    #[test]
+    fn record_iter_ext_test() {
+        let v: Vec<bool> = items().into_iter().validated().map(|r| r.is_ok()).collect();
+        assert_eq!(v, vec![true, false, false, true, true]);
+
+        let v: Vec<Option<u8>> = items().into_iter().completed().map(|r| r.ok().map(|i| i.0)).collect();
+        assert_eq!(v, vec![Some(0), Some(1), None, Some(2)]);
+
+        let v: Vec<Option<u8>> = items().into_iter().unique_by(|i| i.0 % 2).map(|r| r.ok().map(|i| i.0)).collect();
+        assert_eq!(v, vec![Some(0), Some(1), None]);
+
+        let v: Vec<Option<u8>> = items().into_iter().take_while_ok().map(|r| r.ok().map(|i| i.0)).collect();
+        assert_eq!(v, vec![Some(0), Some(1), None]);
+
+        let mut index = 10;
+        let v: Vec<Option<u8>> = items().into_iter()
+            .map_records(|i| { index += 1; Item(i.0 + index) })
+            .map(|r| r.ok().map(|i| i.0))
+            .collect();
+        assert_eq!(v, vec![Some(11), Some(13), None, Some(17), Some(16)]);
+
+        // Adaptors compose.
+        let v: Vec<Item> = items().into_iter()
+            .filter(Result::is_ok)
+            .completed()
+            .unique_by(|i| i.0 % 2)
+            .collect::<Result<Vec<Item>>>()
+            .unwrap();
+        assert_eq!(v, vec![Item(0), Item(1)]);
+
+        let v = items().into_iter().filter(Result::is_ok).sample_n(2, 0).unwrap();
+        assert_eq!(v.len(), 2);
+        let v: Vec<Result<Item>> = items().into_iter().sample_fraction(1.0, 0, |i| i.0).collect();
+        assert_eq!(v.len(), 5);
+        let (train, test) = items().into_iter().filter(Result::is_ok).split_by_hash(0.0, 0, |i| i.0).partition().unwrap();
+        assert_eq!((train.len(), test.len()), (0, 4));
+    }
+
+    fn normalize(line: &[u8]) -> Bytes {
+        let mut line = line.to_vec();
+        normalize_line(&mut line);
+        line
+    }
+
+    fn init_cb(writer: &mut Vec<u8>, _: u8) -> Result<&mut Vec<u8>> {
+        Ok(writer)
+    }
+
+    fn export_cb(writer: &mut &mut Vec<u8>, item: &Item) -> Result<()> {
+        writer.push(item.0);
+        Ok(())
+    }
+
+    fn dest_cb(_: &mut &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    #[test]
+    fn export_strict_test() {
+        let v = vec![Item(0), Item(2), Item(3), Item(4)];
+        let mut w = vec![];
+        let err = reference_iterator_export_strict(&mut w, v.iter(), b'\n', &init_cb, &export_cb, &dest_cb).err().unwrap();
+        assert_eq!(err.record_index(), Some(2));
+        assert_eq!(err.violations(), Some(&[String::from("record is invalid")][..]));
+        assert_eq!(w, vec![0, 2]);
+
+        let mut w = vec![];
+        let iter = v.into_iter().map(Ok);
+        let err = value_iterator_export_strict(&mut w, iter, b'\n', &init_cb, &export_cb, &dest_cb).err().unwrap();
+        assert_eq!(err.record_index(), Some(2));
+        assert_eq!(w, vec![0, 2]);
+
+        let mut w = vec![];
+        assert!(reference_iterator_export_strict(&mut w, [Item(0)].iter(), b'\n', &init_cb, &export_cb, &dest_cb).is_ok());
+        assert_eq!(w, vec![0]);
+    }
+
+    #[test]
+    fn normalize_line_test() {
+        assert_eq!(normalize(b"BEGIN IONS\n"), b"BEGIN IONS\n".to_vec());
+        assert_eq!(normalize(b"BEGIN IONS\r\n"), b"BEGIN IONS\n".to_vec());
+        assert_eq!(normalize(b"BEGIN IONS \t\r\n"), b"BEGIN IONS\n".to_vec());
+        assert_eq!(normalize(b"\xEF\xBB\xBFBEGIN IONS\r\n"), b"BEGIN IONS\n".to_vec());
+        assert_eq!(normalize(b"END IONS  "), b"END IONS".to_vec());
+        assert_eq!(normalize(b" \r\n"), b"".to_vec());
+        assert_eq!(normalize(b"\xEF\xBB\xBF\n"), b"".to_vec());
+    }
+
+    #[test]
+    fn bytes_next_skip_whitespace_test() {
+        use std::io::Cursor;
+
+        let mut reader = Cursor::new(b"\xEF\xBB\xBF>A \r\nAB\r\n \r\n>B\r\nC".to_vec());
+        let mut buf = vec![];
+        let mut line = vec![];
+        let mut next = || bytes_next_skip_whitespace(b">", None, &mut reader, &mut buf, &mut line);
+        assert_eq!(next().unwrap().unwrap(), b">A\nAB\n".to_vec());
+        assert_eq!(next().unwrap().unwrap(), b">B\nC".to_vec());
+        assert!(next().is_none());
+
+        let mut reader = Cursor::new(b";a: 1\n\n;b: 2\n>A\n;AB\n".to_vec());
+        let mut buf = vec![];
+        let mut line = vec![];
+        let mut next = || bytes_next_skip_whitespace(b">", Some(b";"), &mut reader, &mut buf, &mut line);
+        assert_eq!(next().unwrap().unwrap(), b">A\n;AB\n".to_vec());
+        assert!(next().is_none());
+    }
+}
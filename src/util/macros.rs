@@ -1,5 +1,8 @@
 //! Shared macros.
 
+// The path and logging macros are only used by the `std` modules.
+#![cfg_attr(not(feature = "std"), allow(unused_macros))]
+
 // RECURSIVE APPLICATION
 
 /// Macro to call `s.write_all(x)` for all x.
@@ -42,7 +45,7 @@ macro_rules! none_to_error {
         match $e {
             None    => return Err(From::from(ErrorKind::$t)),
             Some(v) => v,
-        };
+        }
     )
 }
 
@@ -53,10 +56,93 @@ macro_rules! bool_to_error {
     ($e:expr, $t:ident) => (
         if !$e {
             return Err(From::from(ErrorKind::$t));
-        };
+        }
     )
 }
 
+// PATH
+
+/// Macro to generate the path-based methods for a serialization trait.
+///
+/// The reader opens the file with a buffer sized from the file, the
+/// writer replaces the file atomically, and both add the file path to
+/// any error. Additional arguments are forwarded to the stream methods.
+macro_rules! path_methods {
+    ($to_path:ident, $from_path:ident, $to:ident, $from:ident $(, $arg:ident: $type:ty)*) => (
+        /// Export model to an output file, adding the path to errors.
+        ///
+        /// The output file is only replaced if the export succeeds.
+        #[inline]
+        fn $to_path<P: AsRef<Path>>(&self, path: P $(, $arg: $type)*) -> Result<()> {
+            let path = path.as_ref();
+            write_atomic(path, |writer| self.$to(writer $(, $arg)*))
+                .map_err(|e| e.with_path(path))
+        }
+
+        /// Import model from a file, adding the path to errors.
+        #[inline]
+        fn $from_path<P: AsRef<Path>>(path: P $(, $arg: $type)*) -> Result<Self> {
+            let path = path.as_ref();
+            open_buffered(path)
+                .and_then(|mut reader| Self::$from(&mut reader $(, $arg)*))
+                .map_err(|e| e.with_path(path))
+        }
+    );
+}
+
+// LOGGING
+
+// Forward to the `log` crate with the `logging` feature. Otherwise,
+// the arguments are type-checked but never evaluated, so values used
+// only for logging do not raise unused warnings.
+
+/// Log a trace-level message.
+#[cfg(feature = "logging")]
+macro_rules! log_trace {
+    ($($arg:tt)*) => (trace!($($arg)*))
+}
+
+/// Log a trace-level message.
+#[cfg(not(feature = "logging"))]
+macro_rules! log_trace {
+    ($($arg:tt)*) => (if false { let _ = format_args!($($arg)*); })
+}
+
+/// Log a debug-level message.
+#[cfg(feature = "logging")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => (debug!($($arg)*))
+}
+
+/// Log a debug-level message.
+#[cfg(not(feature = "logging"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => (if false { let _ = format_args!($($arg)*); })
+}
+
+// Only used by the network clients, so unused in offline builds.
+
+/// Log an info-level message.
+#[cfg(feature = "logging")]
+#[allow(unused_macros)]
+macro_rules! log_info {
+    ($($arg:tt)*) => (info!($($arg)*))
+}
+
+/// Log an info-level message.
+#[cfg(not(feature = "logging"))]
+#[allow(unused_macros)]
+macro_rules! log_info {
+    ($($arg:tt)*) => (if false { let _ = format_args!($($arg)*); })
+}
+
+/// Log the result of parsing a record from a document.
+macro_rules! log_parsed {
+    ($format:expr, $result:expr) => (match $result {
+        &Ok(_)      => log_trace!("parsed {} record", $format),
+        &Err(ref e) => log_debug!("failed to parse {} record: {}", $format, e),
+    })
+}
 
 /// Map an iterator to take items by value.
 #[cfg(test)]
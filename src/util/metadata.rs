@@ -0,0 +1,157 @@
+//! Versioned provenance metadata for text exports.
+//!
+//! The metadata is written as a block of comment lines before the first
+//! record, in a fixed order, so identical metadata always produces
+//! identical bytes. The readers skip any leading comment block, so
+//! documents with and without metadata load identically.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use super::alias::Result;
+#[cfg(feature = "mmap")]
+use super::parse::UTF8_BOM;
+
+/// Version of the metadata block layout.
+pub(crate) const METADATA_VERSION: &'static [u8] = b"1";
+
+/// Comment prefix for metadata in FASTA documents.
+pub(crate) const FASTA_COMMENT: &'static [u8] = b";";
+
+/// Comment prefix for metadata in CSV documents.
+pub(crate) const CSV_COMMENT: &'static [u8] = b"#";
+
+/// Comment prefix for metadata in MGF documents.
+pub(crate) const MGF_COMMENT: &'static [u8] = b"#";
+
+/// Provenance metadata written at the top of an export.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportMetadata {
+    /// Name and version of the tool creating the export.
+    pub tool: String,
+    /// Creation date of the export, or empty to omit the date.
+    ///
+    /// The date is never filled in automatically, so repeated exports
+    /// of the same data remain byte-for-byte identical.
+    pub date: String,
+    /// Source of the exported records, or empty to omit the source.
+    pub source: String,
+    /// Parameters used to create the export, written in sorted order.
+    pub parameters: BTreeMap<String, String>,
+}
+
+impl Default for ExportMetadata {
+    #[inline]
+    fn default() -> Self {
+        ExportMetadata {
+            tool: concat!("bdb ", env!("CARGO_PKG_VERSION")).to_string(),
+            date: String::new(),
+            source: String::new(),
+            parameters: BTreeMap::new(),
+        }
+    }
+}
+
+impl ExportMetadata {
+    /// Create metadata for records exported from a source.
+    #[inline]
+    pub fn new(source: &str) -> Self {
+        ExportMetadata { source: source.to_string(), ..ExportMetadata::default() }
+    }
+
+    /// Set the creation date of the export.
+    #[inline]
+    pub fn date(mut self, date: &str) -> Self {
+        self.date = date.to_string();
+        self
+    }
+
+    /// Add a parameter used to create the export.
+    #[inline]
+    pub fn parameter(mut self, key: &str, value: &str) -> Self {
+        self.parameters.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Write the metadata as comment lines starting with `prefix`.
+    pub(crate) fn to_comment<T: Write>(&self, writer: &mut T, prefix: &[u8])
+        -> Result<()>
+    {
+        write_line(writer, prefix, b"bdb-metadata", METADATA_VERSION)?;
+        write_line(writer, prefix, b"tool", self.tool.as_bytes())?;
+        if !self.date.is_empty() {
+            write_line(writer, prefix, b"date", self.date.as_bytes())?;
+        }
+        if !self.source.is_empty() {
+            write_line(writer, prefix, b"source", self.source.as_bytes())?;
+        }
+        for (key, value) in self.parameters.iter() {
+            let parameter = format!("{}={}", key, value);
+            write_line(writer, prefix, b"parameter", parameter.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a single metadata line, replacing embedded line breaks.
+fn write_line<T: Write>(writer: &mut T, prefix: &[u8], key: &[u8], value: &[u8])
+    -> Result<()>
+{
+    let value: Vec<u8> = value.iter()
+        .map(|&c| if c == b'\n' || c == b'\r' { b' ' } else { c })
+        .collect();
+    write_alls!(writer, prefix, key, b": ", value.as_slice(), b"\n")?;
+    Ok(())
+}
+
+/// Skip the leading comment block and blank lines from a document.
+#[cfg(feature = "mmap")]
+pub(crate) fn skip_metadata<'a>(data: &'a [u8], prefix: &[u8]) -> &'a [u8] {
+    let mut data = match data.starts_with(UTF8_BOM) {
+        true  => &data[UTF8_BOM.len()..],
+        false => data,
+    };
+    loop {
+        let end = data.iter().position(|&c| c == b'\n').map_or(data.len(), |i| i + 1);
+        let line = &data[..end];
+        let is_blank = line.iter().all(|c| c.is_ascii_whitespace());
+        if data.is_empty() || !(is_blank || line.starts_with(prefix)) {
+            return data;
+        }
+        data = &data[end..];
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_comment_test() {
+        let metadata = ExportMetadata::new("uniprot.xml")
+            .date("2018-01-01")
+            .parameter("organism", "Homo\nsapiens")
+            .parameter("delimiter", "tab");
+        let mut w = vec![];
+        metadata.to_comment(&mut w, b"#").unwrap();
+        let expected = format!("#bdb-metadata: 1\n#tool: bdb {}\n#date: 2018-01-01\n#source: uniprot.xml\n#parameter: delimiter=tab\n#parameter: organism=Homo sapiens\n", env!("CARGO_PKG_VERSION"));
+        assert_eq!(String::from_utf8(w).unwrap(), expected);
+
+        let mut w = vec![];
+        ExportMetadata::default().to_comment(&mut w, b";").unwrap();
+        assert_eq!(w.iter().filter(|&&c| c == b'\n').count(), 2);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn skip_metadata_test() {
+        assert_eq!(skip_metadata(b";a: 1\n\n;b: 2\r\n>A\nAB\n", b";"), b">A\nAB\n");
+        assert_eq!(skip_metadata(b"\xEF\xBB\xBF;a: 1\n>A", b";"), b">A");
+        assert_eq!(skip_metadata(b">A\n;a\n", b";"), b">A\n;a\n");
+        assert_eq!(skip_metadata(b";a: 1", b";"), b"");
+        assert_eq!(skip_metadata(b"", b";"), b"");
+    }
+}
@@ -0,0 +1,206 @@
+//! Memory-mapped, read-only document backends.
+//!
+//! Memory-mapped documents are split into record blocks which borrow
+//! the mapped memory, avoiding the copies through the internal buffers
+//! of the `BufRead` iterators. Workflows which repeatedly scan the same
+//! large documents benefit from the shared page cache.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::path::Path;
+use memmap::Mmap;
+
+use super::alias::Result;
+use super::iterator::normalize_line;
+use super::parse::UTF8_BOM;
+
+/// Read-only, memory-mapped file.
+///
+/// Empty files are not mapped, and have an empty buffer.
+pub struct MmapFile {
+    mmap: Option<Mmap>,
+}
+
+impl MmapFile {
+    /// Map a file into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified, by this or another
+    /// process, while it is mapped. Otherwise, the mapped memory may
+    /// change underneath the borrowed records, or accessing it may
+    /// raise `SIGBUS`, which is undefined behavior.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = match file.metadata()?.len() {
+            0 => None,
+            _ => Some(Mmap::map(&file)?),
+        };
+        Ok(MmapFile { mmap: mmap })
+    }
+
+    /// Get the mapped memory as bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self.mmap {
+            None           => &[],
+            Some(ref mmap) => &mmap[..],
+        }
+    }
+}
+
+// BLOCK ITERATOR
+
+/// Find the end of the first block, before the next line starting with `start`.
+fn block_end(data: &[u8], start: &[u8]) -> usize {
+    let mut index = 0;
+    while let Some(position) = data[index..].iter().position(|&c| c == b'\n') {
+        index += position + 1;
+        if data[index..].starts_with(start) {
+            return index;
+        }
+    }
+    data.len()
+}
+
+/// Iterator over the record blocks of a memory-mapped document.
+///
+/// Each block starts with a line starting with `start`, and the
+/// blocks borrow the underlying memory. Whitespace-only blocks are
+/// skipped. Equivalent to the block iterators over `BufRead`, without
+/// normalizing the lines, see `normalize_block`.
+pub struct MmapBlockIter<'a> {
+    data: &'a [u8],
+    start: &'static [u8],
+}
+
+impl<'a> MmapBlockIter<'a> {
+    /// Create new MmapBlockIter from a buffer and the block start.
+    #[inline]
+    pub fn new(data: &'a [u8], start: &'static [u8]) -> Self {
+        MmapBlockIter {
+            data: data,
+            start: start,
+        }
+    }
+}
+
+impl<'a> Iterator for MmapBlockIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.is_empty() {
+            let (block, rest) = self.data.split_at(block_end(self.data, self.start));
+            self.data = rest;
+            if block.iter().any(|c| !c.is_ascii_whitespace() && !UTF8_BOM.contains(c)) {
+                return Some(block);
+            }
+        }
+        None
+    }
+}
+
+// NORMALIZE
+
+/// Determine if the lines of a block are already normalized.
+fn is_normalized(block: &[u8], skip: Option<&[u8]>) -> bool {
+    if block.starts_with(UTF8_BOM) {
+        return false;
+    }
+
+    let mut offset = 0;
+    for line in block.split(|&c| c == b'\n') {
+        offset += line.len() + 1;
+        let is_last = offset > block.len();
+        if line.is_empty() && is_last {
+            continue;
+        }
+        let is_valid = (
+            !line.is_empty() &&
+            !line.last().unwrap().is_ascii_whitespace() &&
+            !skip.map_or(false, |s| line.starts_with(s))
+        );
+        if !is_valid {
+            return false;
+        }
+    }
+    true
+}
+
+/// Normalize the lines of a block, as `normalize_line` does.
+///
+/// Blank lines, and lines starting with `skip`, are removed. The
+/// block is borrowed if it is already normalized, which is the
+/// common case for documents with Unix line endings.
+pub fn normalize_block<'a>(block: &'a [u8], skip: Option<&[u8]>) -> Cow<'a, [u8]> {
+    if is_normalized(block, skip) {
+        return Cow::Borrowed(block);
+    }
+
+    let mut normalized = Vec::with_capacity(block.len());
+    let mut line = vec![];
+    for chunk in block.split(|&c| c == b'\n') {
+        line.clear();
+        line.extend_from_slice(chunk);
+        line.push(b'\n');
+        normalize_line(&mut line);
+        if !line.is_empty() && !skip.map_or(false, |s| line.starts_with(s)) {
+            normalized.append(&mut line);
+        }
+    }
+    // Remove the line ending added to the final line.
+    if !block.ends_with(b"\n") && normalized.last() == Some(&b'\n') {
+        normalized.pop();
+    }
+
+    Cow::Owned(normalized)
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::*;
+
+    #[test]
+    fn mmap_block_iter_test() {
+        let v: Vec<&[u8]> = MmapBlockIter::new(b"\n>A\nAB\n>B\nC", b">").collect();
+        assert_eq!(v, vec![&b">A\nAB\n"[..], &b">B\nC"[..]]);
+
+        let v: Vec<&[u8]> = MmapBlockIter::new(b"#A\n>B\nC\n", b">").collect();
+        assert_eq!(v, vec![&b"#A\n"[..], &b">B\nC\n"[..]]);
+
+        let v: Vec<&[u8]> = MmapBlockIter::new(b"\xEF\xBB\xBF \r\n", b">").collect();
+        assert!(v.is_empty());
+        assert_eq!(MmapBlockIter::new(b"", b">").count(), 0);
+    }
+
+    #[test]
+    fn normalize_block_test() {
+        let normalize = |b: &'static [u8]| normalize_block(b, Some(b"MASS="));
+        match normalize(b">A\nAB\n") {
+            Cow::Borrowed(v) => assert_eq!(v, b">A\nAB\n"),
+            Cow::Owned(_)    => panic!("expected borrowed block"),
+        }
+        assert_eq!(normalize(b">A\nAB").as_ref(), b">A\nAB");
+        assert_eq!(normalize(b"\n>A \r\nAB\r\n\r\n").as_ref(), b">A\nAB\n");
+        assert_eq!(normalize(b"\xEF\xBB\xBF>A\r\nAB").as_ref(), b">A\nAB");
+        assert_eq!(normalize(b"MASS=Monoisotopic\nBEGIN IONS\n").as_ref(), b"BEGIN IONS\n");
+    }
+
+    #[test]
+    fn mmap_file_test() {
+        let path = env::temp_dir().join("bdb_mmap_file_test.fasta");
+        fs::write(&path, b">A\nAB\n").unwrap();
+        let file = unsafe { MmapFile::open(&path).unwrap() };
+        assert_eq!(file.as_bytes(), b">A\nAB\n");
+
+        fs::write(&path, b"").unwrap();
+        let file = unsafe { MmapFile::open(&path).unwrap() };
+        assert_eq!(file.as_bytes(), b"");
+        fs::remove_file(&path).unwrap();
+    }
+}
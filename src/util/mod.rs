@@ -5,32 +5,92 @@
 #[macro_use]
 pub(crate) mod macros;
 
+#[cfg(feature = "std")]
 #[macro_use]
 pub(crate) mod iterator;
 
+#[cfg(feature = "std")]
 #[macro_use]
 pub(crate) mod re;
 
 pub(crate) mod alias;
 pub(crate) mod error;
+pub(crate) mod rng;
+pub(crate) mod search;
+
+#[cfg(feature = "std")]
+pub(crate) mod binary;
+
+#[cfg(feature = "std")]
+pub(crate) mod encoding;
+
+#[cfg(feature = "std")]
 pub(crate) mod fmt;
+
+#[cfg(feature = "std")]
+pub(crate) mod metadata;
+
+#[cfg(feature = "std")]
+pub(crate) mod options;
+
+#[cfg(feature = "std")]
 pub(crate) mod parse;
-pub(crate) mod search;
+
+#[cfg(feature = "std")]
+pub(crate) mod path;
+
+#[cfg(feature = "std")]
+pub(crate) mod sample;
+
+#[cfg(feature = "std")]
 pub(crate) mod writer;
 
+#[cfg(feature = "mmap")]
+pub(crate) mod mmap;
+
 #[cfg(feature = "xml")]
 pub(crate) mod xml;
 
 // Export low-level converters internally.
-pub(crate) use self::fmt::*;
-pub(crate) use self::iterator::*;
-pub(crate) use self::parse::*;
-pub(crate) use self::re::*;
-pub(crate) use self::writer::TextWriterState;
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        pub(crate) use self::binary::*;
+        pub(crate) use self::encoding::DecodeReader;
+        pub(crate) use self::fmt::*;
+        pub(crate) use self::iterator::*;
+        pub(crate) use self::metadata::{CSV_COMMENT, FASTA_COMMENT, MGF_COMMENT};
+        pub(crate) use self::options::{DEFAULT_BUFFER_CAPACITY, DEFAULT_LINE_CAPACITY};
+        pub(crate) use self::parse::*;
+        pub(crate) use self::path::open_buffered;
+        pub(crate) use self::re::*;
+        pub(crate) use self::sample::stable_hash;
+        pub(crate) use self::writer::TextWriterState;
+    }
+}
+
+#[cfg(feature = "mmap")]
+pub(crate) use self::mmap::{normalize_block, MmapBlockIter};
+#[cfg(feature = "mmap")]
+pub(crate) use self::metadata::skip_metadata;
 
 #[cfg(feature = "xml")]
-pub(crate) use self::xml::{XmlReader, XmlWriter};
+pub(crate) use self::xml::{local_name, NameMatching, XmlReader, XmlWriter};
 
 // Publicly expose high-level APIs.
-pub use self::alias::{Bytes, Result};
+pub use self::alias::Result;
 pub use self::error::{Error, ErrorKind};
+pub use self::rng::Rng;
+#[cfg(feature = "std")]
+pub use self::alias::Bytes;
+#[cfg(feature = "std")]
+pub use self::encoding::Encoding;
+#[cfg(feature = "std")]
+pub use self::iterator::RecordIterExt;
+#[cfg(feature = "std")]
+pub use self::metadata::ExportMetadata;
+#[cfg(feature = "mmap")]
+pub use self::mmap::MmapFile;
+#[cfg(feature = "std")]
+pub use self::options::ReaderOptions;
+#[cfg(feature = "std")]
+pub use self::sample::{hash_unit, sample_n, Split};
@@ -0,0 +1,84 @@
+//! Options shared by the document readers.
+
+use bio::Alphabet;
+use bio::mass::Adduct;
+use super::encoding::Encoding;
+
+/// Default initial capacity, in bytes, of the reader buffers.
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 8000;
+
+/// Default initial capacity, in bytes, of the reader line buffers.
+pub(crate) const DEFAULT_LINE_CAPACITY: usize = 8000;
+
+/// Options to customize how documents are read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReaderOptions {
+    /// Text encoding of the document.
+    ///
+    /// The XML readers decode the document before parsing, so the XML
+    /// encoding declaration is ignored.
+    pub encoding: Encoding,
+    /// Alphabet to normalize and check sequences against.
+    ///
+    /// Records with residues outside of the alphabet raise an error.
+    /// If `None`, sequences are read unchanged.
+    pub alphabet: Option<Alphabet>,
+    /// Adduct of the parent ion of spectral records.
+    ///
+    /// The spectral readers store the adduct on each record, from which
+    /// the neutral parent mass is calculated. If `None`, the adduct is
+    /// left unknown and protonation is assumed.
+    pub adduct: Option<Adduct>,
+    /// Initial capacity, in bytes, of the buffer holding each record.
+    ///
+    /// Set to the size of the largest records (for example, titin or
+    /// chromosome-length sequences) to avoid repeated reallocation.
+    /// The CSV readers instead use a fixed-size read buffer of this
+    /// capacity, where 0 uses the default capacity.
+    pub buffer_capacity: usize,
+    /// Initial capacity, in bytes, of the buffer holding each line.
+    pub line_capacity: usize,
+}
+
+impl Default for ReaderOptions {
+    #[inline]
+    fn default() -> Self {
+        ReaderOptions {
+            encoding: Encoding::default(),
+            alphabet: None,
+            adduct: None,
+            buffer_capacity: DEFAULT_BUFFER_CAPACITY,
+            line_capacity: DEFAULT_LINE_CAPACITY,
+        }
+    }
+}
+
+impl ReaderOptions {
+    /// Create reader options with a custom text encoding.
+    #[inline]
+    pub fn with_encoding(encoding: Encoding) -> Self {
+        ReaderOptions { encoding: encoding, ..ReaderOptions::default() }
+    }
+
+    /// Create reader options with a sequence alphabet.
+    #[inline]
+    pub fn with_alphabet(alphabet: Alphabet) -> Self {
+        ReaderOptions { alphabet: Some(alphabet), ..ReaderOptions::default() }
+    }
+
+    /// Create reader options with a parent ion adduct.
+    #[inline]
+    pub fn with_adduct(adduct: Adduct) -> Self {
+        ReaderOptions { adduct: Some(adduct), ..ReaderOptions::default() }
+    }
+
+    /// Create reader options with custom buffer capacities.
+    #[inline]
+    pub fn with_capacity(buffer_capacity: usize, line_capacity: usize) -> Self {
+        ReaderOptions {
+            buffer_capacity: buffer_capacity,
+            line_capacity: line_capacity,
+            ..ReaderOptions::default()
+        }
+    }
+}
@@ -15,6 +15,21 @@ macro_rules! nonzero {
     })
 }
 
+// BYTE ORDER MARK
+
+/// UTF-8 byte order mark, written at the start of some documents.
+pub(crate) const UTF8_BOM: &'static [u8] = b"\xEF\xBB\xBF";
+
+/// Remove a leading UTF-8 byte order mark from a string.
+#[inline]
+pub(crate) fn strip_bom(s: &str) -> &str {
+    if s.starts_with('\u{FEFF}') {
+        &s[UTF8_BOM.len()..]
+    } else {
+        s
+    }
+}
+
 // COMMAS
 
 /// Remove all commas from a buffer.
@@ -116,6 +131,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn strip_bom_test() {
+        assert_eq!(strip_bom("\u{FEFF}>sp|P46406"), ">sp|P46406");
+        assert_eq!(strip_bom(">sp|P46406"), ">sp|P46406");
+        assert_eq!(strip_bom(""), "");
+    }
+
     #[test]
     fn from_bytes_test() {
         repeat!(from_bytes, b"0", 0, 0.0);
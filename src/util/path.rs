@@ -0,0 +1,45 @@
+//! Helpers for the path-based readers and writers.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::alias::Result;
+
+/// Minimum capacity of the buffered file readers.
+const MIN_READER_CAPACITY: usize = 8 * 1024;
+
+/// Maximum capacity of the buffered file readers.
+const MAX_READER_CAPACITY: usize = 1024 * 1024;
+
+/// Choose the reader buffer capacity from the file size.
+///
+/// Small files use the default capacity, while large files use larger
+/// buffers to reduce the number of reads, up to a maximum of 1 MiB.
+#[inline]
+pub(crate) fn reader_capacity(length: u64) -> usize {
+    let length = length.min(MAX_READER_CAPACITY as u64) as usize;
+    length.max(MIN_READER_CAPACITY)
+}
+
+/// Open a file for buffered reading, choosing the buffer capacity.
+pub(crate) fn open_buffered(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path)?;
+    let capacity = reader_capacity(file.metadata()?.len());
+    Ok(BufReader::with_capacity(capacity, file))
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_capacity_test() {
+        assert_eq!(reader_capacity(0), 8192);
+        assert_eq!(reader_capacity(100_000), 100_000);
+        assert_eq!(reader_capacity(1 << 40), 1 << 20);
+    }
+}
@@ -0,0 +1,126 @@
+//! Seeded random number generator for the randomized features.
+//!
+//! Sampling, decoy generation, fuzzing and the benchmark corpora all
+//! draw from this generator, so identical seeds produce identical
+//! outputs across runs and platforms.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+/// Mix the bits of a 64-bit integer, from the SplitMix64 generator.
+#[inline]
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Random number generator, reproducible from the seed (SplitMix64).
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create new generator from a seed.
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Generate a random 64-bit integer.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        splitmix64(self.state)
+    }
+
+    /// Generate a random integer in `[start, end)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty.
+    #[inline]
+    pub fn range(&mut self, start: usize, end: usize) -> usize {
+        assert!(start < end, "Rng::range requires a non-empty range.");
+        start + (self.next_u64() % (end - start) as u64) as usize
+    }
+
+    /// Generate a random float in `[0, 1)`.
+    #[inline]
+    pub fn float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Return true with probability `p`.
+    #[inline]
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.float() < p
+    }
+
+    /// Choose a random item from a non-empty slice.
+    #[inline]
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len())]
+    }
+
+    /// Generate a random sequence of items from an alphabet.
+    #[inline]
+    pub fn sequence<T: Clone>(&mut self, alphabet: &[T], length: usize) -> Vec<T> {
+        (0..length).map(|_| self.choose(alphabet).clone()).collect()
+    }
+
+    /// Shuffle the items in-place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0, i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_test() {
+        let mut x = Rng::new(7);
+        let mut y = Rng::new(7);
+        let v: Vec<u64> = (0..10).map(|_| x.next_u64()).collect();
+        let w: Vec<u64> = (0..10).map(|_| y.next_u64()).collect();
+        assert_eq!(v, w);
+        assert_ne!(v[0], Rng::new(8).next_u64());
+
+        for _ in 0..100 {
+            let i = x.range(3, 7);
+            assert!(i >= 3 && i < 7);
+            let f = x.float();
+            assert!(f >= 0.0 && f < 1.0);
+        }
+        assert!(!x.chance(0.0));
+        assert!(x.chance(1.0));
+        assert_eq!(x.sequence(b"A", 3), b"AAA".to_vec());
+    }
+
+    #[test]
+    fn shuffle_test() {
+        let items: Vec<u32> = (0..100).collect();
+        let mut x = items.clone();
+        let mut y = items.clone();
+        Rng::new(1).shuffle(&mut x);
+        Rng::new(1).shuffle(&mut y);
+        assert_eq!(x, y);
+        assert_ne!(x, items);
+
+        x.sort();
+        assert_eq!(x, items);
+        let mut empty: Vec<u32> = vec![];
+        Rng::new(1).shuffle(&mut empty);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! Subsampling and splitting of record streams.
+//!
+//! Reservoir sampling draws a fixed number of records uniformly from
+//! a stream of unknown length, with a seeded generator. Hash-based
+//! sampling and splitting assign each record from the hash of a key,
+//! such as the identifier, so the assignment is deterministic across
+//! runs, independent of the record order, and paired files sampled
+//! with the same key and seed keep the same records.
+
+use std::hash::{Hash, Hasher};
+
+use super::alias::Result;
+use super::rng::{splitmix64, Rng};
+
+// HASHING
+
+/// Hasher with 64-bit FNV-1a, a stable hash across runs and platforms.
+struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    #[inline]
+    fn new(seed: u64) -> Self {
+        let mut hasher = Fnv1aHasher(0xcbf29ce484222325);
+        hasher.write(&seed.to_le_bytes());
+        hasher
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        // FNV-1a poorly mixes the high bits, finalize with SplitMix64.
+        splitmix64(self.0)
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Hash a key to a 64-bit integer, stable across runs and platforms.
+pub(crate) fn stable_hash<K: Hash + ?Sized>(key: &K, seed: u64) -> u64 {
+    let mut hasher = Fnv1aHasher::new(seed);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash a key to a number uniformly distributed in `[0, 1)`.
+pub fn hash_unit<K: Hash + ?Sized>(key: &K, seed: u64) -> f64 {
+    (stable_hash(key, seed) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// RESERVOIR
+
+/// Draw `n` items uniformly from an iterator, with a seeded generator.
+///
+/// Every item is read, and the items are returned in their original
+/// order. Returns all items if the iterator has fewer than `n` items,
+/// and stops at the first error.
+pub fn sample_n<T, Iter>(iter: Iter, n: usize, seed: u64) -> Result<Vec<T>>
+    where Iter: Iterator<Item = Result<T>>
+{
+    let mut rng = Rng::new(seed);
+    let mut reservoir: Vec<(usize, T)> = Vec::with_capacity(n);
+    for (index, item) in iter.enumerate() {
+        let item = item?;
+        if reservoir.len() < n {
+            reservoir.push((index, item));
+        } else {
+            // Algorithm R: replace a random item with probability n / (index + 1).
+            let j = rng.range(0, index + 1);
+            if j < n {
+                reservoir[j] = (index, item);
+            }
+        }
+    }
+    reservoir.sort_by_key(|&(index, _)| index);
+
+    Ok(reservoir.into_iter().map(|(_, item)| item).collect())
+}
+
+// FRACTION
+
+/// Iterator which keeps a deterministic fraction of items, by key hash.
+pub struct SampleFractionIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Hash,
+          F: FnMut(&T) -> K
+{
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Fraction of items to keep.
+    fraction: f64,
+    /// Seed for the key hash.
+    seed: u64,
+    /// Callback to get the key from an item.
+    key: F,
+}
+
+impl<T, U, K, F> SampleFractionIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Hash,
+          F: FnMut(&T) -> K
+{
+    /// Create new SampleFractionIter from an iterator, fraction, seed and key callback.
+    #[inline]
+    pub fn new(iter: U, fraction: f64, seed: u64, key: F) -> Self {
+        SampleFractionIter {
+            iter: iter,
+            fraction: fraction,
+            seed: seed,
+            key: key,
+        }
+    }
+}
+
+impl<T, U, K, F> Iterator for SampleFractionIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Hash,
+          F: FnMut(&T) -> K
+{
+    type Item = U::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.iter.next()? {
+                Err(e)  => return Some(Err(e)),
+                Ok(r)   => {
+                    if hash_unit(&(self.key)(&r), self.seed) < self.fraction {
+                        return Some(Ok(r));
+                    }
+                },
+            }
+        }
+    }
+}
+
+// SPLIT
+
+/// Subset assigned to an item by a split.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Split {
+    /// Item in the first fraction of the split.
+    Train,
+    /// Item in the remainder of the split.
+    Test,
+}
+
+/// Iterator which assigns each item to a deterministic split, by key hash.
+pub struct SplitIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Hash,
+          F: FnMut(&T) -> K
+{
+    /// Wrapped internal iterator.
+    iter: U,
+    /// Fraction of items in the training split.
+    fraction: f64,
+    /// Seed for the key hash.
+    seed: u64,
+    /// Callback to get the key from an item.
+    key: F,
+}
+
+impl<T, U, K, F> SplitIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Hash,
+          F: FnMut(&T) -> K
+{
+    /// Create new SplitIter from an iterator, fraction, seed and key callback.
+    #[inline]
+    pub fn new(iter: U, fraction: f64, seed: u64, key: F) -> Self {
+        SplitIter {
+            iter: iter,
+            fraction: fraction,
+            seed: seed,
+            key: key,
+        }
+    }
+
+    /// Collect the items into the training and test splits.
+    ///
+    /// Stops at the first error.
+    pub fn partition(self) -> Result<(Vec<T>, Vec<T>)> {
+        let mut train = vec![];
+        let mut test = vec![];
+        for item in self {
+            match item? {
+                (Split::Train, r) => train.push(r),
+                (Split::Test, r)  => test.push(r),
+            }
+        }
+        Ok((train, test))
+    }
+}
+
+impl<T, U, K, F> Iterator for SplitIter<T, U, K, F>
+    where U: Iterator<Item = Result<T>>,
+          K: Hash,
+          F: FnMut(&T) -> K
+{
+    type Item = Result<(Split, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let fraction = self.fraction;
+        let seed = self.seed;
+        let key = &mut self.key;
+        Some(self.iter.next()?.map(|r| {
+            match hash_unit(&key(&r), seed) < fraction {
+                true  => (Split::Train, r),
+                false => (Split::Test, r),
+            }
+        }))
+    }
+}
+
+// TESTS
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::ErrorKind;
+
+    fn items(count: u32) -> Vec<Result<u32>> {
+        (0..count).map(Ok).collect()
+    }
+
+    #[test]
+    fn hash_unit_test() {
+        let x = hash_unit("SRR390728.2", 0);
+        assert!(x >= 0.0 && x < 1.0);
+        assert_eq!(x, hash_unit("SRR390728.2", 0));
+        assert_ne!(x, hash_unit("SRR390728.2", 1));
+        assert_ne!(x, hash_unit("SRR390728.3", 0));
+    }
+
+    #[test]
+    fn sample_n_test() {
+        let v = sample_n(items(1000).into_iter(), 10, 42).unwrap();
+        assert_eq!(v.len(), 10);
+        assert!(v.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(v, sample_n(items(1000).into_iter(), 10, 42).unwrap());
+        assert_ne!(v, sample_n(items(1000).into_iter(), 10, 43).unwrap());
+        assert_eq!(sample_n(items(5).into_iter(), 10, 42).unwrap(), vec![0, 1, 2, 3, 4]);
+
+        let mut v = items(5);
+        v.push(Err(From::from(ErrorKind::InvalidInput)));
+        assert!(sample_n(v.into_iter(), 10, 42).is_err());
+    }
+
+    #[test]
+    fn sample_fraction_test() {
+        let v: Vec<u32> = SampleFractionIter::new(items(10000).into_iter(), 0.25, 0, |&i| i)
+            .map(|r| r.unwrap())
+            .collect();
+        assert!(v.len() > 2300 && v.len() < 2700, "{}", v.len());
+
+        // Sampling is independent of the item order.
+        let reversed: Vec<u32> = SampleFractionIter::new(items(10000).into_iter().rev(), 0.25, 0, |&i| i)
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(v, reversed.into_iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn split_test() {
+        let (train, test) = SplitIter::new(items(1000).into_iter(), 0.8, 7, |&i| i).partition().unwrap();
+        assert_eq!(train.len() + test.len(), 1000);
+        assert!(train.len() > 750 && train.len() < 850, "{}", train.len());
+        let fraction: Vec<u32> = SampleFractionIter::new(items(1000).into_iter(), 0.8, 7, |&i| i)
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(train, fraction);
+    }
+}
@@ -41,6 +41,30 @@ pub(crate) fn linear<T: Ord>(slice: &[T], value: &T)
     None
 }
 
+/// Get the index of the first element that does not satisfy a predicate.
+///
+/// Works on input partitioned by the predicate (all elements satisfying
+/// the predicate precede all elements that do not), in O(log(n)) time.
+#[cfg(any(feature = "mass_spectrometry", feature = "search"))]
+pub(crate) fn partition_point<T, F>(slice: &[T], predicate: F)
+    -> usize
+    where F: Fn(&T) -> bool
+{
+    let mut first = 0;
+    let mut last = slice.len();
+
+    while first < last {
+        let pivot = first + (last-first)/2;
+        if predicate(&slice[pivot]) {
+            first = pivot + 1;
+        } else {
+            last = pivot;
+        }
+    }
+
+    first
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,6 +82,16 @@ mod tests {
         assert_eq!(index, None);
     }
 
+    #[test]
+    fn partition_point_test() {
+        let x = [1, 2, 3, 3, 5];
+        assert_eq!(partition_point(&x, |v| *v < 3), 2);
+        assert_eq!(partition_point(&x, |v| *v <= 3), 4);
+        assert_eq!(partition_point(&x, |v| *v < 0), 0);
+        assert_eq!(partition_point(&x, |v| *v < 6), 5);
+        assert_eq!(partition_point(&[] as &[i32], |v| *v < 6), 0);
+    }
+
     #[test]
     fn linear_test() {
         let x = [5, 3, 1, 4, 2];
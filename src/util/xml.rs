@@ -2,7 +2,7 @@
 
 // RE-EXPORTS
 
-pub use self::reader::{XmlReader};
+pub use self::reader::{local_name, NameMatching, XmlReader};
 pub use self::writer::{XmlWriter};
 
 // READER
@@ -10,10 +10,11 @@ pub use self::writer::{XmlWriter};
 mod reader {
 
 use quick_xml::{Error as XmlError, Reader, Result as XmlResult};
-use quick_xml::events::{BytesStart, Event};
+use quick_xml::events::{BytesStart, BytesText, Event};
 use std::io::BufRead;
 use super::super::alias::{Bytes, Result};
 use super::super::error::ErrorKind;
+use super::super::options::DEFAULT_BUFFER_CAPACITY;
 
 /// Macro to seek another element within the tree.
 ///
@@ -40,6 +41,129 @@ macro_rules! xml_seek {
     })
 }
 
+/// Get the local name of a (possibly prefixed) element or attribute name.
+#[inline]
+pub fn local_name(name: &[u8]) -> &[u8] {
+    match name.iter().rposition(|c| *c == b':') {
+        Some(index) => &name[index+1..],
+        None        => name,
+    }
+}
+
+/// Get the prefix of an element or attribute name, or empty if unprefixed.
+#[inline]
+fn name_prefix(name: &[u8]) -> &[u8] {
+    match name.iter().rposition(|c| *c == b':') {
+        Some(index) => &name[..index],
+        None        => b"",
+    }
+}
+
+/// Strategy to match element names when seeking elements.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(dead_code)]
+pub enum NameMatching {
+    /// Match the raw name, including any prefix.
+    Raw,
+    /// Match the local name, ignoring any prefix.
+    Local,
+    /// Match the local name, and require the prefix to be bound to the
+    /// namespace URI.
+    ///
+    /// Elements without any namespace (no prefix, and no default
+    /// namespace in scope) also match, so documents missing the
+    /// namespace declarations still parse.
+    Namespace(Bytes),
+}
+
+/// Skip events until the end of the current element.
+fn skip_to_end<T: BufRead>(reader: &mut Reader<T>, buf: &mut Bytes)
+    -> XmlResult<()>
+{
+    let mut depth = 0;
+    loop {
+        match reader.read_event(buf) {
+            Ok(Event::Start(_)) => depth += 1,
+            Ok(Event::End(_))   => {
+                if depth == 0 {
+                    return Ok(());
+                }
+                depth -= 1;
+            },
+            Ok(Event::Eof)      => return Err(XmlError::UnexpectedEof("end element".to_string())),
+            Err(e)              => return Err(e),
+            _                   => (),
+        }
+        buf.clear();
+    }
+}
+
+/// Entity declared in the document type definition, as the name and value.
+type Entity = (Bytes, Bytes);
+
+/// Parse the general entities declared in the internal DTD subset.
+///
+/// Parameter and external entities are ignored.
+pub(super) fn parse_entities(doctype: &[u8]) -> Vec<Entity> {
+    let is_space = |c: &u8| c.is_ascii_whitespace();
+    let mut entities = vec![];
+    let mut rest = doctype;
+    while let Some(i) = rest.windows(8).position(|w| w == b"<!ENTITY") {
+        rest = &rest[i+8..];
+        let start = rest.iter().position(|c| !is_space(c)).unwrap_or(rest.len());
+        rest = &rest[start..];
+        if rest.first() == Some(&b'%') {
+            continue;
+        }
+        let end = rest.iter().position(is_space).unwrap_or(rest.len());
+        let name = &rest[..end];
+        rest = &rest[end..];
+        let start = rest.iter().position(|c| !is_space(c)).unwrap_or(rest.len());
+        rest = &rest[start..];
+        let quote = match rest.first() {
+            Some(&c) if c == b'"' || c == b'\'' => c,
+            _ => continue,
+        };
+        if let Some(end) = rest[1..].iter().position(|c| *c == quote) {
+            entities.push((name.to_vec(), rest[1..end+1].to_vec()));
+            rest = &rest[end+2..];
+        }
+    }
+    entities
+}
+
+/// Unescape text, expanding the entities declared in the DTD.
+///
+/// The predefined and character entities are always expanded, however,
+/// declared entities are not expanded recursively.
+pub(super) fn unescape_text(raw: &[u8], entities: &[Entity]) -> XmlResult<Bytes> {
+    if entities.is_empty() {
+        return Ok(BytesText::from_escaped(raw).unescaped()?.into_owned());
+    }
+
+    let mut expanded = Vec::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(i) = rest.iter().position(|c| *c == b'&') {
+        expanded.extend_from_slice(&rest[..i]);
+        rest = &rest[i..];
+        let entity = rest.iter().position(|c| *c == b';').and_then(|j| {
+            entities.iter().find(|e| e.0.as_slice() == &rest[1..j]).map(|e| (j, e))
+        });
+        match entity {
+            Some((j, e)) => {
+                expanded.extend_from_slice(&e.1);
+                rest = &rest[j+1..];
+            },
+            None => {
+                expanded.push(b'&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    expanded.extend_from_slice(rest);
+    Ok(BytesText::from_escaped(expanded.as_slice()).unescaped()?.into_owned())
+}
+
 /// Internal struct to store the current XML reader state.
 struct XmlState<T: BufRead> {
     /// Internal XML reader.
@@ -48,6 +172,12 @@ struct XmlState<T: BufRead> {
     raw_depth: usize,
     /// Node is a start element.
     is_start: bool,
+    /// Strategy to match element names.
+    matching: NameMatching,
+    /// Namespaces declared as the raw depth, prefix and URI.
+    namespaces: Vec<(usize, Bytes, Bytes)>,
+    /// Entities declared in the document type definition.
+    entities: Vec<Entity>,
 }
 
 impl<T: BufRead> XmlState<T> {
@@ -60,7 +190,44 @@ impl<T: BufRead> XmlState<T> {
             reader: reader,
             raw_depth: 0,
             is_start: false,
+            matching: NameMatching::Raw,
+            namespaces: vec![],
+            entities: vec![],
+        }
+    }
+
+    /// Remove namespaces declared by elements that have been closed.
+    ///
+    /// Deferred until the next event, so the namespaces declared by an
+    /// element are in scope for its end element.
+    #[inline(always)]
+    fn close_namespaces(&mut self) {
+        let depth = self.raw_depth;
+        let count = self.namespaces.iter().take_while(|ns| ns.0 <= depth).count();
+        self.namespaces.truncate(count);
+    }
+
+    /// Store the namespaces declared by a start element.
+    fn open_namespaces(&mut self, event: &BytesStart) -> XmlResult<()> {
+        for result in event.attributes() {
+            let attribute = result?;
+            let prefix: &[u8] = match attribute.key {
+                b"xmlns" => b"",
+                key if key.starts_with(b"xmlns:") => &key[6..],
+                _ => continue,
+            };
+            let uri = attribute.unescaped_value()?.to_vec();
+            self.namespaces.push((self.raw_depth, prefix.to_vec(), uri));
         }
+        Ok(())
+    }
+
+    /// Get the namespace URI bound to the prefix of a name, if any.
+    fn resolve_namespace(&self, name: &[u8]) -> Option<&[u8]> {
+        let prefix = name_prefix(name);
+        self.namespaces.iter().rev()
+            .find(|ns| ns.1.as_slice() == prefix)
+            .map(|ns| ns.2.as_slice())
     }
 
     /// Get the current depth (0-indexed) of the reader.
@@ -83,10 +250,16 @@ impl<T: BufRead> XmlState<T> {
     pub fn read_event<'a>(&mut self, buffer: &'a mut Bytes)
         -> Result<Event<'a>>
     {
+        self.close_namespaces();
         match self.reader.read_event(buffer) {
             Ok(Event::Start(e)) => {
                 self.raw_depth += 1;
                 self.is_start = true;
+                if let NameMatching::Namespace(_) = self.matching {
+                    if let Err(e) = self.open_namespaces(&e) {
+                        return Err(From::from(ErrorKind::Xml(e)));
+                    }
+                }
                 Ok(Event::Start(e))
             },
             Ok(Event::End(e)) => {
@@ -94,6 +267,11 @@ impl<T: BufRead> XmlState<T> {
                 self.is_start = false;
                 Ok(Event::End(e))
             }
+            Ok(Event::DocType(e)) => {
+                self.is_start = false;
+                self.entities = parse_entities(e.escaped());
+                Ok(Event::DocType(e))
+            },
             Ok(event) => {
                 self.is_start = false;
                 Ok(event)
@@ -107,10 +285,10 @@ impl<T: BufRead> XmlState<T> {
 
     /// Read until the corresponding end element.
     #[inline]
-    pub fn read_to_end(&mut self, buffer: &mut Bytes, name: &[u8])
+    pub fn read_to_end(&mut self, buffer: &mut Bytes)
         -> Result<Bytes>
     {
-        match self.reader.read_to_end(name, buffer) {
+        match skip_to_end(&mut self.reader, buffer) {
             Err(e) => return Err(From::from(ErrorKind::Xml(e))),
             Ok(_)  => self.is_start = false,
         }
@@ -121,25 +299,38 @@ impl<T: BufRead> XmlState<T> {
     }
 
     /// Read text between the start and end element.
+    ///
+    /// Text and CDATA sections are concatenated, including the text
+    /// of nested elements, and comments are skipped.
     #[inline]
-    pub fn read_text(&mut self, buffer: &mut Bytes, name: &[u8])
+    pub fn read_text(&mut self, buffer: &mut Bytes)
         -> Result<Bytes>
     {
-        fn read_text_impl<T: BufRead>(reader: &mut Reader<T>, buf: &mut Bytes, end: &[u8])
+        fn read_text_impl<T: BufRead>(reader: &mut Reader<T>, buf: &mut Bytes, entities: &[Entity])
             -> XmlResult<Bytes>
         {
-            let s = match reader.read_event(buf) {
-                Ok(Event::Text(e)) => Ok(e.unescaped()?.to_vec()),
-                Ok(Event::End(ref e)) if e.name() == end => return Ok(vec![]),
-                Ok(Event::Eof) => return Err(XmlError::UnexpectedEof("text".to_string())),
-                Err(e) => return Err(e),
-                _       => return Err(XmlError::TextNotFound),
-            };
-            reader.read_to_end(end, buf)?;
-            s
+            let mut text = vec![];
+            let mut depth = 0;
+            loop {
+                match reader.read_event(buf) {
+                    Ok(Event::Text(e))  => text.extend(unescape_text(e.escaped(), entities)?),
+                    Ok(Event::CData(e)) => text.extend_from_slice(e.escaped()),
+                    Ok(Event::Start(_)) => depth += 1,
+                    Ok(Event::End(_))   => {
+                        if depth == 0 {
+                            return Ok(text);
+                        }
+                        depth -= 1;
+                    },
+                    Ok(Event::Eof)      => return Err(XmlError::UnexpectedEof("text".to_string())),
+                    Err(e)              => return Err(e),
+                    _                   => (),
+                }
+                buf.clear();
+            }
         }
 
-        let result = match read_text_impl(&mut self.reader, buffer, name) {
+        let result = match read_text_impl(&mut self.reader, buffer, &self.entities) {
             Err(e) => Err(From::from(ErrorKind::Xml(e))),
             Ok(v)  => {
                 self.is_start = false;
@@ -160,7 +351,19 @@ impl<T: BufRead> XmlState<T> {
     /// Check if we found the correct name.
     #[inline(always)]
     fn found_name(&self, expected: &[u8], actual: &[u8]) -> bool {
-        return expected == b"" || actual == expected;
+        if expected == b"" {
+            return true;
+        }
+        match self.matching {
+            NameMatching::Raw           => actual == expected,
+            NameMatching::Local         => local_name(actual) == expected,
+            NameMatching::Namespace(ref uri) => {
+                local_name(actual) == expected && match self.resolve_namespace(actual) {
+                    Some(v) => v == uri.as_slice(),
+                    None    => name_prefix(actual).is_empty(),
+                }
+            },
+        }
     }
 
     /// Implied function to process a callback on a start element.
@@ -310,10 +513,17 @@ pub struct XmlReader<T: BufRead> {
 impl<T: BufRead> XmlReader<T> {
     /// Create new XmlReader.
     #[inline]
+    #[allow(dead_code)]
     pub fn new(reader: T) -> Self {
+        XmlReader::with_capacity(reader, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Create new XmlReader with the capacity of the event buffer.
+    #[inline]
+    pub fn with_capacity(reader: T, capacity: usize) -> Self {
         XmlReader {
             state: XmlState::new(reader),
-            buffer: Bytes::with_capacity(8000),
+            buffer: Bytes::with_capacity(capacity),
         }
     }
 
@@ -322,7 +532,7 @@ impl<T: BufRead> XmlReader<T> {
     /// You must clear the buffer after this.
     #[inline(always)]
     #[allow(dead_code)]
-    pub fn read_event(&mut self) -> Result<Event> {
+    pub fn read_event(&mut self) -> Result<Event<'_>> {
         self.state.read_event(&mut self.buffer)
     }
 
@@ -333,17 +543,38 @@ impl<T: BufRead> XmlReader<T> {
         self.buffer.clear();
     }
 
+    /// Set the strategy to match element names.
+    ///
+    /// Must be set before reading, so namespace declarations are tracked.
+    #[inline]
+    #[allow(dead_code)]
+    pub fn set_name_matching(&mut self, matching: NameMatching) {
+        self.state.matching = matching;
+    }
+
+    /// Get the namespace URI bound to the prefix of an element name.
+    ///
+    /// Namespaces are only tracked when matching by namespace.
+    #[inline]
+    #[allow(dead_code)]
+    pub fn resolve_namespace(&self, name: &[u8]) -> Option<&[u8]> {
+        self.state.resolve_namespace(name)
+    }
+
     /// Read until the matching XML end element.
+    ///
+    /// The name is not checked, since the end element must close the
+    /// current element.
     #[inline(always)]
     #[allow(dead_code)]
-    pub fn read_to_end(&mut self, name: &[u8]) -> Result<Bytes> {
-        self.state.read_to_end(&mut self.buffer, name)
+    pub fn read_to_end(&mut self, _name: &[u8]) -> Result<Bytes> {
+        self.state.read_to_end(&mut self.buffer)
     }
 
     /// Read text between the start and end element.
     #[inline(always)]
-    pub fn read_text(&mut self, name: &[u8]) -> Result<Bytes> {
-        self.state.read_text(&mut self.buffer, name)
+    pub fn read_text(&mut self, _name: &[u8]) -> Result<Bytes> {
+        self.state.read_text(&mut self.buffer)
     }
 
     /// Get the current depth (0-indexed) of the reader.
@@ -475,23 +706,70 @@ mod writer {
 
 use quick_xml::Writer;
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use std::borrow::Cow;
 use std::io::Write;
 
-use super::super::alias::Result;
+use super::super::alias::{Bytes, Result};
 use super::super::error::ErrorKind;
 
+/// Escape the special characters in an attribute value.
+///
+/// Values are quoted with `"`, so apostrophes are left as-is.
+pub fn escape_attribute(value: &[u8]) -> Cow<'_, [u8]> {
+    if !value.iter().any(|c| b"&<>\"".contains(c)) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped = Vec::with_capacity(value.len() + 16);
+    for &c in value {
+        match c {
+            b'&' => escaped.extend_from_slice(b"&amp;"),
+            b'<' => escaped.extend_from_slice(b"&lt;"),
+            b'>' => escaped.extend_from_slice(b"&gt;"),
+            b'"' => escaped.extend_from_slice(b"&quot;"),
+            _    => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Namespace declaration, as a prefix and URI.
+///
+/// The default namespace has an empty prefix.
+type Namespace = (Bytes, Bytes);
+
 /// Public API for the XML writer.
 pub struct XmlWriter<T: Write> {
     /// Internal XML writer.
     writer: Writer<T>,
+    /// Namespaces to declare on the next element.
+    pending: Vec<Namespace>,
+    /// Namespaces declared by each open element.
+    scopes: Vec<Vec<Namespace>>,
 }
 
 impl<T: Write> XmlWriter<T> {
     /// Create new XmlWriter.
     #[inline]
     pub fn new(writer: T) -> Self {
+        XmlWriter::from_writer(Writer::new(writer))
+    }
+
+    /// Create new XmlWriter, pretty-printing with an indent per nested element.
+    ///
+    /// Each element starts on a new line, except those following text.
+    #[inline]
+    pub fn with_indent(writer: T, indent_char: u8, indent_size: usize) -> Self {
+        XmlWriter::from_writer(Writer::new_with_indent(writer, indent_char, indent_size))
+    }
+
+    /// Create new XmlWriter from the internal writer.
+    #[inline(always)]
+    fn from_writer(writer: Writer<T>) -> Self {
         XmlWriter {
-            writer: Writer::new(writer)
+            writer: writer,
+            pending: vec![],
+            scopes: vec![],
         }
     }
 
@@ -502,21 +780,77 @@ impl<T: Write> XmlWriter<T> {
         self.writer.into_inner()
     }
 
+    /// Declare a namespace on the next start or empty element.
+    ///
+    /// The namespace stays in scope until the element is closed. Use
+    /// an empty prefix to declare the default namespace.
+    #[inline]
+    pub fn declare_namespace(&mut self, prefix: &[u8], uri: &[u8]) {
+        self.pending.push((prefix.to_vec(), uri.to_vec()));
+    }
+
+    /// Get the prefix for a namespace URI, if the namespace is in scope.
+    ///
+    /// Namespaces declared for the next element are considered in scope.
+    pub fn namespace_prefix(&self, uri: &[u8]) -> Option<&[u8]> {
+        self.pending.iter().rev()
+            .chain(self.scopes.iter().rev().flat_map(|scope| scope.iter().rev()))
+            .find(|ns| ns.1.as_slice() == uri)
+            .map(|ns| ns.0.as_slice())
+    }
+
+    /// Get the qualified name for a local name within a namespace.
+    ///
+    /// Returns an error if the namespace is not in scope.
+    pub fn qualified_name(&self, uri: &[u8], local: &[u8]) -> Result<Bytes> {
+        let prefix = none_to_error!(self.namespace_prefix(uri), InvalidInput);
+        let mut name = Vec::with_capacity(prefix.len() + local.len() + 1);
+        if !prefix.is_empty() {
+            name.extend_from_slice(prefix);
+            name.push(b':');
+        }
+        name.extend_from_slice(local);
+        Ok(name)
+    }
+
     /// Create start element
     #[inline(always)]
-    fn new_start_element(bytes: &[u8]) -> BytesStart {
+    fn new_start_element(bytes: &[u8]) -> BytesStart<'_> {
         BytesStart::borrowed(bytes, bytes.len())
     }
 
+    /// Create start element with the pending namespaces and attributes.
+    fn new_start_element_with_attributes<'a>(
+        name: &'a [u8],
+        namespaces: &[Namespace],
+        attributes: &[(&[u8], &[u8])]
+    )
+        -> BytesStart<'a>
+    {
+        let mut elem = Self::new_start_element(name);
+        for namespace in namespaces {
+            let mut key = b"xmlns".to_vec();
+            if !namespace.0.is_empty() {
+                key.push(b':');
+                key.extend_from_slice(&namespace.0);
+            }
+            elem.push_attribute((key.as_slice(), &*escape_attribute(&namespace.1)));
+        }
+        for &(key, value) in attributes {
+            elem.push_attribute((key, &*escape_attribute(value)));
+        }
+        elem
+    }
+
     /// Create text element.
     #[inline(always)]
-    fn new_text_element<'a>(text: &[u8]) -> BytesText {
+    fn new_text_element<'a>(text: &[u8]) -> BytesText<'_> {
         BytesText::from_plain(text)
     }
 
     /// Create end element.
     #[inline(always)]
-    fn new_end_element(bytes: &[u8]) -> BytesEnd {
+    fn new_end_element(bytes: &[u8]) -> BytesEnd<'_> {
         BytesEnd::borrowed(bytes)
     }
 
@@ -541,10 +875,9 @@ impl<T: Write> XmlWriter<T> {
     pub fn write_start_element(&mut self, name: &[u8], attributes: &[(&[u8], &[u8])])
         -> Result<()>
     {
-        let mut elem = Self::new_start_element(name);
-        for attribute in attributes {
-            elem.push_attribute(*attribute);
-        }
+        let namespaces: Vec<Namespace> = self.pending.drain(..).collect();
+        let elem = Self::new_start_element_with_attributes(name, &namespaces, attributes);
+        self.scopes.push(namespaces);
         self.write_event(Event::Start(elem))
     }
 
@@ -557,23 +890,22 @@ impl<T: Write> XmlWriter<T> {
         self.write_end_element(name)
     }
 
-    /// Write start element.
+    /// Write empty element.
     #[inline(always)]
     pub fn write_empty_element(&mut self, name: &[u8], attributes: &[(&[u8], &[u8])])
         -> Result<()>
     {
-        let mut elem = Self::new_start_element(name);
-        for attribute in attributes {
-            elem.push_attribute(*attribute);
-        }
+        let namespaces: Vec<Namespace> = self.pending.drain(..).collect();
+        let elem = Self::new_start_element_with_attributes(name, &namespaces, attributes);
         self.write_event(Event::Empty(elem))
     }
 
-    /// Write start element.
+    /// Write end element.
     #[inline(always)]
     pub fn write_end_element(&mut self, name: &[u8])
         -> Result<()>
     {
+        self.scopes.pop();
         self.write_event(Event::End(Self::new_end_element(name)))
     }
 }
@@ -610,6 +942,188 @@ mod tests {
         assert_eq!(text, b"<?xml version=\"1.0\" encoding=\"UTF-8\"?><t1 k1=\"v1\"><t2 k2=\"v2\">Text</t2><t3 k3=\"v3\"/></t1>".to_vec());
     }
 
+    #[test]
+    fn xml_escape_attribute_test() {
+        assert_eq!(&*writer::escape_attribute(b"plain text"), b"plain text");
+        assert_eq!(&*writer::escape_attribute(b"a & b"), b"a &amp; b");
+        assert_eq!(&*writer::escape_attribute(b"<\"x\">"), b"&lt;&quot;x&quot;&gt;");
+        assert_eq!(&*writer::escape_attribute(b"it's"), b"it's");
+        assert_eq!(&*writer::escape_attribute(b"&amp;"), b"&amp;amp;");
+
+        let mut w = XmlWriter::new(Cursor::new(vec![]));
+        w.write_empty_element(b"t1", &[(b"k1", b"O/P=\"2-333\" & <x>")]).unwrap();
+        let text = w.into_inner().into_inner();
+        assert_eq!(text, b"<t1 k1=\"O/P=&quot;2-333&quot; &amp; &lt;x&gt;\"/>".to_vec());
+
+        // round-trip
+        let mut r = XmlReader::new(Cursor::new(text));
+        loop {
+            match r.read_event().unwrap() {
+                Event::Start(e) | Event::Empty(e) => {
+                    let attribute = e.attributes().next().unwrap().unwrap();
+                    assert_eq!(&*attribute.unescaped_value().unwrap(), b"O/P=\"2-333\" & <x>");
+                    break;
+                },
+                Event::Eof => panic!("expected element"),
+                _ => (),
+            }
+            r.reset_buffer();
+        }
+    }
+
+    #[test]
+    fn xml_indent_test() {
+        let mut w = XmlWriter::with_indent(Cursor::new(vec![]), b' ', 2);
+
+        w.write_declaration().unwrap();
+        w.write_start_element(b"t1", &[(b"k1", b"v1")]).unwrap();
+        w.write_start_element(b"t2", &[]).unwrap();
+        w.write_text_element(b"t3", b"Text", &[]).unwrap();
+        w.write_end_element(b"t2").unwrap();
+        w.write_empty_element(b"t4", &[]).unwrap();
+        w.write_end_element(b"t1").unwrap();
+
+        let text = w.into_inner().into_inner();
+        let expected = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<t1 k1=\"v1\">\n  <t2>\n    <t3>Text</t3>\n  </t2>\n  <t4/>\n</t1>";
+        assert_eq!(String::from_utf8(text).unwrap(), expected);
+
+        let mut w = XmlWriter::with_indent(Cursor::new(vec![]), b'\t', 1);
+        w.write_start_element(b"t1", &[]).unwrap();
+        w.write_empty_element(b"t2", &[]).unwrap();
+        w.write_end_element(b"t1").unwrap();
+        assert_eq!(w.into_inner().into_inner(), b"<t1>\n\t<t2/>\n</t1>".to_vec());
+    }
+
+    #[test]
+    fn xml_namespace_test() {
+        let ns = b"http://uniprot.org/uniprot";
+        let xsi = b"http://www.w3.org/2001/XMLSchema-instance";
+        let mut w = XmlWriter::new(Cursor::new(vec![]));
+        assert!(w.qualified_name(ns, b"entry").is_err());
+
+        w.declare_namespace(b"", ns);
+        w.declare_namespace(b"xsi", xsi);
+        assert_eq!(w.namespace_prefix(xsi), Some(&b"xsi"[..]));
+        let location = w.qualified_name(xsi, b"schemaLocation").unwrap();
+        w.write_start_element(b"uniprot", &[(&location, b"uri")]).unwrap();
+        assert_eq!(w.qualified_name(ns, b"entry").unwrap(), b"entry".to_vec());
+
+        // scoped to the element
+        w.declare_namespace(b"up", b"urn:up");
+        w.write_start_element(b"entry", &[]).unwrap();
+        assert_eq!(w.qualified_name(b"urn:up", b"x").unwrap(), b"up:x".to_vec());
+        w.declare_namespace(b"e", b"urn:e");
+        w.write_empty_element(b"e:name", &[]).unwrap();
+        assert_eq!(w.namespace_prefix(b"urn:e"), None);
+        w.write_end_element(b"entry").unwrap();
+        assert_eq!(w.namespace_prefix(b"urn:up"), None);
+        assert_eq!(w.namespace_prefix(xsi), Some(&b"xsi"[..]));
+        w.write_end_element(b"uniprot").unwrap();
+        assert_eq!(w.namespace_prefix(ns), None);
+
+        let text = w.into_inner().into_inner();
+        let expected = "<uniprot xmlns=\"http://uniprot.org/uniprot\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:schemaLocation=\"uri\"><entry xmlns:up=\"urn:up\"><e:name xmlns:e=\"urn:e\"/></entry></uniprot>";
+        assert_eq!(String::from_utf8(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn xml_local_name_test() {
+        assert_eq!(local_name(b"entry"), b"entry");
+        assert_eq!(local_name(b"up:entry"), b"entry");
+        assert_eq!(local_name(b"xsi:schemaLocation"), b"schemaLocation");
+        assert_eq!(local_name(b""), b"");
+    }
+
+    #[test]
+    fn xml_namespace_read_test() {
+        let text = b"<up:uniprot xmlns:up=\"urn:up\" xmlns:x=\"urn:x\"><x:entry>A</x:entry><up:entry><up:name>B</up:name></up:entry><entry xmlns=\"urn:up\">C</entry><entry xmlns=\"urn:x\">D</entry></up:uniprot>".to_vec();
+
+        // raw
+        let mut r = XmlReader::new(Cursor::new(text.clone()));
+        assert!(r.seek_start(b"entry", 1).unwrap().is_ok());
+        assert_eq!(r.read_text(b"entry").unwrap(), b"C".to_vec());
+
+        // local
+        let mut r = XmlReader::new(Cursor::new(text.clone()));
+        r.set_name_matching(NameMatching::Local);
+        assert!(r.seek_start(b"entry", 1).unwrap().is_ok());
+        assert_eq!(r.read_text(b"entry").unwrap(), b"A".to_vec());
+
+        // namespace
+        let mut r = XmlReader::new(Cursor::new(text.clone()));
+        r.set_name_matching(NameMatching::Namespace(b"urn:up".to_vec()));
+        assert!(r.seek_start(b"uniprot", 0).unwrap().is_ok());
+        assert_eq!(r.resolve_namespace(b"x:entry"), Some(&b"urn:x"[..]));
+        assert_eq!(r.resolve_namespace(b"entry"), None);
+        assert!(r.seek_start(b"entry", 1).unwrap().is_ok());
+        assert!(r.seek_start(b"name", 2).unwrap().is_ok());
+        assert_eq!(r.read_text(b"name").unwrap(), b"B".to_vec());
+        assert!(r.seek_end(b"entry", 1).unwrap().is_ok());
+        assert!(r.seek_start(b"entry", 1).unwrap().is_ok());
+        assert_eq!(r.read_text(b"entry").unwrap(), b"C".to_vec());
+        assert!(r.seek_start(b"entry", 1).is_none());
+
+        // declared on the end element's start
+        let mut r = XmlReader::new(Cursor::new(text.clone()));
+        r.set_name_matching(NameMatching::Namespace(b"urn:up".to_vec()));
+        assert!(r.seek_end(b"uniprot", 0).unwrap().is_ok());
+
+        // unqualified documents match any namespace
+        let mut r = XmlReader::new(Cursor::new(b"<uniprot><entry>E</entry></uniprot>".to_vec()));
+        r.set_name_matching(NameMatching::Namespace(b"urn:up".to_vec()));
+        assert!(r.seek_start(b"entry", 1).unwrap().is_ok());
+        assert_eq!(r.read_text(b"entry").unwrap(), b"E".to_vec());
+    }
+
+    #[test]
+    fn xml_entity_test() {
+        let doctype = b" uniprot [\n<!ENTITY alpha \"&#x3B1;\">\n<!ENTITY % param \"x\">\n<!ENTITY org 'Homo sapiens'>\n<!ENTITY ext SYSTEM \"ext.xml\">\n]";
+        let entities = reader::parse_entities(doctype);
+        assert_eq!(entities, vec![
+            (b"alpha".to_vec(), b"&#x3B1;".to_vec()),
+            (b"org".to_vec(), b"Homo sapiens".to_vec()),
+        ]);
+
+        assert_eq!(reader::unescape_text(b"a &amp; b &lt;c&gt; &#65;&#x42;", &[]).unwrap(), b"a & b <c> AB".to_vec());
+        assert_eq!(reader::unescape_text(b"&org; &alpha;-actin &amp;", &entities).unwrap(), "Homo sapiens \u{3B1}-actin &".as_bytes().to_vec());
+        assert!(reader::unescape_text(b"&unknown;", &entities).is_err());
+        assert!(reader::unescape_text(b"&amp", &[]).is_err());
+    }
+
+    #[test]
+    fn xml_read_text_test() {
+        // UniProt
+        let text = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE uniprot [<!ENTITY alpha \"&#x3B1;\">]>\n<uniprot><entry><fullName>Na(+)/K(+) ATPase &alpha;-1 &amp; &lt;pump&gt;</fullName><name><!-- primary -->ATP1A1</name><sequence>MGKG<![CDATA[VGRD]]>\nKYEP</sequence><empty/></entry></uniprot>";
+        let mut r = XmlReader::new(Cursor::new(text.as_bytes().to_vec()));
+        assert!(r.seek_start(b"fullName", 2).unwrap().is_ok());
+        assert_eq!(r.read_text(b"fullName").unwrap(), "Na(+)/K(+) ATPase \u{3B1}-1 & <pump>".as_bytes().to_vec());
+        assert!(r.seek_start(b"name", 2).unwrap().is_ok());
+        assert_eq!(r.read_text(b"name").unwrap(), b"ATP1A1".to_vec());
+        assert!(r.seek_start(b"sequence", 2).unwrap().is_ok());
+        assert_eq!(r.read_text(b"sequence").unwrap(), b"MGKGVGRD\nKYEP".to_vec());
+        assert!(r.seek_start(b"empty", 2).unwrap().is_ok());
+        assert_eq!(r.read_text(b"empty").unwrap(), b"".to_vec());
+        assert!(r.seek_end(b"uniprot", 0).unwrap().is_ok());
+
+        // PRIDE
+        let text = b"<ExperimentCollection version=\"2.1\"><Experiment><Title><![CDATA[Human <plasma> proteome & \"friends\"]]></Title><Protocol><ProtocolName>In-gel <i>tryptic</i> digest</ProtocolName></Protocol></Experiment></ExperimentCollection>".to_vec();
+        let mut r = XmlReader::new(Cursor::new(text));
+        assert!(r.seek_start(b"Title", 2).unwrap().is_ok());
+        assert_eq!(r.read_text(b"Title").unwrap(), b"Human <plasma> proteome & \"friends\"".to_vec());
+        assert!(r.seek_start(b"ProtocolName", 3).unwrap().is_ok());
+        assert_eq!(r.read_text(b"ProtocolName").unwrap(), b"In-gel tryptic digest".to_vec());
+        assert_eq!(r.depth(), 3);
+        assert!(r.seek_end(b"Experiment", 1).unwrap().is_ok());
+
+        // invalid
+        let mut r = XmlReader::new(Cursor::new(b"<a><b>&unknown;</b></a>".to_vec()));
+        assert!(r.seek_start(b"b", 1).unwrap().is_ok());
+        assert!(r.read_text(b"b").is_err());
+        let mut r = XmlReader::new(Cursor::new(b"<a><b>text".to_vec()));
+        assert!(r.seek_start(b"b", 1).unwrap().is_ok());
+        assert!(r.read_text(b"b").is_err());
+    }
+
     #[test]
     fn xml_read_test() {
         let text = b"<?xml version=\"1.0\" encoding=\"UTF-8\"?><t1 k1=\"v1\"><t2 k2=\"v2\">Text</t2><t3 k3=\"v3\"></t3></t1>".to_vec();